@@ -6,10 +6,13 @@
 
 use std::collections::{BTreeSet, BinaryHeap, HashSet, LinkedList, VecDeque};
 
-use divan::{black_box, Bencher};
+use divan::{black_box, AllocProfiler, Bencher};
 
 mod util;
 
+#[global_allocator]
+static ALLOC: AllocProfiler = AllocProfiler::system();
+
 fn main() {
     divan::main();
 }
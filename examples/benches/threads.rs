@@ -62,13 +62,13 @@ mod mutex {
         #[divan::bench]
         fn block() {
             static M: Mutex<u64> = Mutex::new(0);
-            _ = black_box(M.lock());
+            drop(black_box(M.lock()));
         }
 
         #[divan::bench]
         fn r#try() {
             static M: Mutex<u64> = Mutex::new(0);
-            _ = black_box(M.try_lock());
+            drop(black_box(M.try_lock()));
         }
     }
 
@@ -102,13 +102,13 @@ mod rw_lock {
         #[divan::bench]
         fn block() {
             static L: RwLock<u64> = RwLock::new(0);
-            _ = black_box(L.read());
+            drop(black_box(L.read()));
         }
 
         #[divan::bench]
         fn r#try() {
             static L: RwLock<u64> = RwLock::new(0);
-            _ = black_box(L.try_read());
+            drop(black_box(L.try_read()));
         }
     }
 
@@ -118,13 +118,13 @@ mod rw_lock {
         #[divan::bench]
         fn block() {
             static L: RwLock<u64> = RwLock::new(0);
-            _ = black_box(L.write());
+            drop(black_box(L.write()));
         }
 
         #[divan::bench]
         fn r#try() {
             static L: RwLock<u64> = RwLock::new(0);
-            _ = black_box(L.try_write());
+            drop(black_box(L.try_write()));
         }
     }
 
@@ -184,7 +184,7 @@ mod thread_id {
             #[divan::bench]
             fn ptr() -> *mut u8 {
                 thread_local! {
-                    static LOCAL: UnsafeCell<u8> = UnsafeCell::new(0);
+                    static LOCAL: UnsafeCell<u8> = const { UnsafeCell::new(0) };
                 }
 
                 LOCAL.with(|addr| addr.get())
@@ -3,7 +3,7 @@ use quote::{quote, ToTokens};
 use syn::{
     parse::{Parse, Parser},
     spanned::Spanned,
-    Expr, ExprArray, Ident, Token, Type,
+    Expr, ExprArray, ExprLit, ExprRange, ExprUnary, Ident, Lit, RangeLimits, Token, Type, UnOp,
 };
 
 use crate::Macro;
@@ -33,9 +33,23 @@ pub(crate) struct AttrOptions {
     /// Options for generic functions.
     pub generic: GenericOptions,
 
+    /// Runtime arguments over which to run the benchmark.
+    pub args: Option<Expr>,
+
+    /// Labels for selecting benchmarks with `--tag`.
+    pub tags: Option<ExprArray>,
+
     /// The `BenchOptions.counters` field and its value, followed by a comma.
     pub counters: proc_macro2::TokenStream,
 
+    /// Function run once before a benchmark, whose result may be passed to
+    /// the benchmark.
+    pub setup: Option<Expr>,
+
+    /// Function run once after a benchmark, which may take the value returned
+    /// by `setup`.
+    pub teardown: Option<Expr>,
+
     /// Options used directly as `BenchOptions` fields.
     ///
     /// Option reuse is handled by the compiler ensuring `BenchOptions` fields
@@ -49,6 +63,11 @@ impl AttrOptions {
 
         let mut divan_crate = None::<syn::Path>;
         let mut name_expr = None::<Expr>;
+        let mut setup = None::<Expr>;
+        let mut teardown = None::<Expr>;
+        let mut args = None::<Expr>;
+        let mut args_from_dir = None::<Expr>;
+        let mut tags = None::<ExprArray>;
         let mut bench_options = Vec::new();
 
         let mut counters = Vec::<Expr>::new();
@@ -109,6 +128,47 @@ impl AttrOptions {
                     }
 
                     parse!(generic.consts);
+
+                    // Ranges are expanded into an array of their values.
+                    if let Some(Expr::Range(range)) = &generic.consts {
+                        generic.consts = Some(expand_const_range(range)?);
+                    }
+                }
+                "args" | "args_from_dir" => {
+                    match target_macro {
+                        Macro::Bench { fn_sig } => {
+                            if fn_sig.inputs.is_empty() {
+                                error!("argument parameter required for '{macro_name}' option '{ident_name}'");
+                            }
+                            if fn_sig.inputs.len() > 2 {
+                                error!("'{macro_name}' option '{ident_name}' requires at most a 'Bencher' and an argument parameter");
+                            }
+                        }
+                        _ => return unsupported_error(),
+                    }
+
+                    if ident_name == "args" {
+                        parse!(args);
+                    } else {
+                        parse!(args_from_dir);
+                    }
+                }
+                "tags" => parse!(tags),
+                "setup" | "teardown" if !matches!(target_macro, Macro::Bench { .. }) => {
+                    return unsupported_error();
+                }
+                "setup" => {
+                    if let Macro::Bench { fn_sig } = target_macro {
+                        if fn_sig.inputs.len() > 2 {
+                            error!("'{macro_name}' option 'setup' requires at most a 'Bencher' and a state parameter");
+                        }
+                    }
+
+                    parse!(setup);
+                }
+                "teardown" => parse!(teardown),
+                "time_budget" if !matches!(target_macro, Macro::BenchGroup) => {
+                    return unsupported_error();
                 }
                 "counter" => {
                     if counters_ident.is_some() {
@@ -137,7 +197,15 @@ impl AttrOptions {
                         }),
                     };
 
-                    bench_options.push((ident.clone(), value));
+                    // `assert_max` is an alias of `max_time_mean`, so setting
+                    // both is caught as a repeated field.
+                    let ident = if ident_name == "assert_max" {
+                        Ident::new("max_time_mean", ident.span())
+                    } else {
+                        ident.clone()
+                    };
+
+                    bench_options.push((ident, value));
                 }
             }
 
@@ -152,6 +220,31 @@ impl AttrOptions {
         let divan_crate = divan_crate.unwrap_or_else(|| syn::parse_quote!(::divan));
         let private_mod = quote! { #divan_crate::__private };
 
+        if let Some(dir) = args_from_dir {
+            if args.is_some() {
+                let message =
+                    format!("'{macro_name}' option 'args_from_dir' cannot be used with 'args'");
+                return Err(syn::Error::new(dir.span(), message).into_compile_error().into());
+            }
+
+            // Relative paths are resolved from the benchmarked package's root.
+            args = Some(syn::parse_quote! {
+                #private_mod::FixtureFile::from_dir(
+                    #private_mod::std::path::Path::new(#private_mod::std::env!("CARGO_MANIFEST_DIR"))
+                        .join(#dir)
+                )
+            });
+        }
+
+        if let Some(args) = &args {
+            if setup.is_some() || teardown.is_some() {
+                let message = format!(
+                    "'{macro_name}' option 'args' cannot be used with 'setup' or 'teardown'"
+                );
+                return Err(syn::Error::new(args.span(), message).into_compile_error().into());
+            }
+        }
+
         let counters = counters_ident
             .map(|ident| {
                 quote! {
@@ -166,6 +259,10 @@ impl AttrOptions {
             private_mod,
             name_expr,
             generic,
+            args,
+            tags,
+            setup,
+            teardown,
             counters,
             bench_options,
         })
@@ -173,14 +270,11 @@ impl AttrOptions {
 
     /// Produces a function expression for creating `BenchOptions`.
     ///
-    /// If the `#[ignore]` attribute is specified, this be provided its
-    /// identifier to set `BenchOptions` using its span. Doing this instead of
+    /// If the `#[ignore]` attribute is specified, this be provided it to set
+    /// `BenchOptions` using its identifier's span. Doing this instead of
     /// creating the `ignore` identifier ourselves improves compiler error
-    /// diagnostics.
-    pub fn bench_options_fn(
-        &self,
-        ignore_attr_ident: Option<&syn::Path>,
-    ) -> proc_macro2::TokenStream {
+    /// diagnostics. The reason in `#[ignore = "reason"]` is also used.
+    pub fn bench_options_fn(&self, ignore_attr: Option<&syn::Meta>) -> proc_macro2::TokenStream {
         fn is_lit_array(expr: &Expr) -> bool {
             let Expr::Array(expr) = expr else {
                 return false;
@@ -198,8 +292,7 @@ impl AttrOptions {
         // twice, even if raw identifiers are used. This also has the accidental
         // benefit of Rust Analyzer recognizing fields and emitting suggestions
         // with docs and type info.
-        if self.bench_options.is_empty() && self.counters.is_empty() && ignore_attr_ident.is_none()
-        {
+        if self.bench_options.is_empty() && self.counters.is_empty() && ignore_attr.is_none() {
             quote! { #private_mod::None }
         } else {
             let options_iter = self.bench_options.iter().map(|(option, value)| {
@@ -223,21 +316,42 @@ impl AttrOptions {
                     }
 
                     // If the option is a `Duration`, use `IntoDuration` to be
-                    // polymorphic over `Duration` or `u64`/`f64` seconds.
-                    "min_time" | "max_time" => {
+                    // polymorphic over `Duration`, `u64`/`f64` seconds, or
+                    // strings like "100µs".
+                    "sample_time" | "min_time" | "max_time" | "max_time_mean" | "warmup_time"
+                    | "timeout" => {
                         wrapped_value =
                             quote! { #private_mod::IntoDuration::into_duration(#value) };
                         &wrapped_value
                     }
 
+                    // A string is the reason for ignoring the benchmark.
+                    "ignore" if matches!(value, Expr::Lit(ExprLit { lit: Lit::Str(_), .. })) => {
+                        return quote! {
+                            #option: #private_mod::Some(true),
+                            ignore_reason: #private_mod::Some(#value),
+                        };
+                    }
+
                     _ => value,
                 };
 
                 quote! { #option: #private_mod::Some(#value), }
             });
 
-            let ignore = match ignore_attr_ident {
-                Some(ignore_attr_ident) => quote! { #ignore_attr_ident: #private_mod::Some(true), },
+            let ignore = match ignore_attr {
+                Some(ignore_attr) => {
+                    let ignore_attr_ident = ignore_attr.path();
+
+                    let reason = match ignore_attr {
+                        syn::Meta::NameValue(syn::MetaNameValue { value, .. }) => {
+                            quote! { ignore_reason: #private_mod::Some(#value), }
+                        }
+                        _ => Default::default(),
+                    };
+
+                    quote! { #ignore_attr_ident: #private_mod::Some(true), #reason }
+                }
                 None => Default::default(),
             };
 
@@ -263,6 +377,57 @@ impl AttrOptions {
     }
 }
 
+/// The maximum number of values in a `consts` range, since each value is a
+/// separate instantiation of the benchmark.
+const MAX_CONST_RANGE_LEN: i128 = 256;
+
+/// Expands `start..end` or `start..=end` with integer literal bounds into an
+/// array expression of each value in the range.
+fn expand_const_range(range: &ExprRange) -> syn::Result<Expr> {
+    let parse_bound = |bound: Option<&Expr>| -> syn::Result<i128> {
+        let error =
+            || syn::Error::new(range.span(), "'consts' range bounds must be integer literals");
+
+        let (is_neg, lit) = match bound.ok_or_else(error)? {
+            Expr::Lit(ExprLit { lit: Lit::Int(lit), .. }) => (false, lit),
+            Expr::Unary(ExprUnary { op: UnOp::Neg(_), expr, .. }) => match &**expr {
+                Expr::Lit(ExprLit { lit: Lit::Int(lit), .. }) => (true, lit),
+                _ => return Err(error()),
+            },
+            _ => return Err(error()),
+        };
+
+        let value: i128 = lit.base10_parse()?;
+        Ok(if is_neg { -value } else { value })
+    };
+
+    let start = parse_bound(range.start.as_deref())?;
+    let end = parse_bound(range.end.as_deref())?;
+
+    let values = match range.limits {
+        RangeLimits::HalfOpen(_) => start..end,
+        RangeLimits::Closed(_) => start..end.saturating_add(1),
+    };
+
+    if values.end.saturating_sub(values.start) > MAX_CONST_RANGE_LEN {
+        return Err(syn::Error::new_spanned(
+            range,
+            format!("'consts' range must have at most {MAX_CONST_RANGE_LEN} values"),
+        ));
+    }
+
+    let values = values.map(|value| {
+        let abs = proc_macro2::Literal::u128_unsuffixed(value.unsigned_abs());
+        if value < 0 {
+            quote! { -#abs }
+        } else {
+            abs.into_token_stream()
+        }
+    });
+
+    Ok(syn::parse_quote! { [#(#values),*] })
+}
+
 /// Options for generic functions.
 #[derive(Default)]
 pub struct GenericOptions {
@@ -46,8 +46,8 @@ pub fn bench(options: TokenStream, item: TokenStream) -> TokenStream {
 
     // Find any `#[ignore]` attribute so that we can use its span to help
     // compiler diagnostics.
-    let ignore_attr_ident =
-        fn_item.attrs.iter().map(|attr| attr.meta.path()).find(|path| path.is_ident("ignore"));
+    let ignore_attr =
+        fn_item.attrs.iter().map(|attr| &attr.meta).find(|meta| meta.path().is_ident("ignore"));
 
     // If the function is `extern "ABI"`, it is wrapped in a Rust-ABI function.
     let is_extern_abi = fn_sig.abi.is_some();
@@ -90,16 +90,61 @@ pub fn bench(options: TokenStream, item: TokenStream) -> TokenStream {
         fn_ident.span(),
     );
 
-    let meta = entry_meta_expr(&fn_name, &options, ignore_attr_ident);
+    let meta = entry_meta_expr(&fn_name, &options, ignore_attr);
 
-    // Creates a function expr for the benchmarking function, optionally
+    // Creates a call to the benchmarking function with `value` as its last
+    // parameter. As with other benchmarks, a function with two parameters takes
+    // the `Bencher` first.
+    let make_call =
+        |fn_expr: &proc_macro2::TokenStream, value: proc_macro2::TokenStream| match fn_args.len() {
+            0 => quote! { divan.bench(|| #fn_expr()) },
+            1 => quote! { divan.bench(|| #fn_expr(#value)) },
+            _ => quote! { #fn_expr(divan, #value) },
+        };
+
+    // Creates a runner that runs `setup` and `teardown` around the benchmark.
+    // Without `setup`, the function takes at most a `Bencher` like other
+    // benchmarks.
+    let make_setup_runner = |fn_expr: proc_macro2::TokenStream| {
+        let (setup, call) = match &options.setup {
+            Some(setup) => (quote! { (#setup)() }, make_call(&fn_expr, quote! { __divan_state })),
+            None if fn_args.is_empty() => (quote! { () }, quote! { divan.bench(|| #fn_expr()) }),
+            None => (quote! { () }, quote! { #fn_expr(divan) }),
+        };
+
+        let teardown = match &options.teardown {
+            Some(teardown) => {
+                quote! { #private_mod::Teardown::teardown(#teardown, __divan_state) }
+            }
+            None => quote! { #private_mod::std::mem::drop(__divan_state) },
+        };
+
+        quote! {
+            #private_mod::BenchEntryRunner::Setup(|| {
+                static __DIVAN_SETUP: #private_mod::BenchSetup = #private_mod::BenchSetup::new();
+                __DIVAN_SETUP.runner(
+                    || #setup,
+                    |divan, __divan_state| #call,
+                    |__divan_state| #teardown,
+                )
+            })
+        }
+    };
+
+    // Creates a path expr for the benchmarking function, optionally
     // monomorphized with generic parameters.
-    let make_bench_fn = |generics: &[&dyn ToTokens]| {
-        let fn_expr = if generics.is_empty() {
+    let make_fn_expr = |generics: &[&dyn ToTokens]| {
+        if generics.is_empty() {
             fn_ident.to_token_stream()
         } else {
             quote! { #fn_ident::< #(#generics),* > }
-        };
+        }
+    };
+
+    // Creates a function expr for the benchmarking function, optionally
+    // monomorphized with generic parameters.
+    let make_bench_fn = |generics: &[&dyn ToTokens]| {
+        let fn_expr = make_fn_expr(generics);
 
         match (is_extern_abi, fn_args.is_empty()) {
             (false, false) => fn_expr,
@@ -109,6 +154,31 @@ pub fn bench(options: TokenStream, item: TokenStream) -> TokenStream {
         }
     };
 
+    // Creates a runner that benchmarks the function over runtime arguments.
+    let make_args_runner = |args: &Expr, fn_expr: proc_macro2::TokenStream| {
+        let call = make_call(&fn_expr, quote! { #private_mod::Arg::get(__divan_arg) });
+
+        quote! {
+            #private_mod::BenchEntryRunner::Args(|| {
+                static __DIVAN_ARGS: #private_mod::BenchArgs = #private_mod::BenchArgs::new();
+                __DIVAN_ARGS.runner(|| #args, |divan, __divan_arg| #call)
+            })
+        }
+    };
+
+    // Creates a `BenchEntryRunner` expr, optionally monomorphized with generic
+    // parameters.
+    let make_bench_runner = |generics: &[&dyn ToTokens]| match &options.args {
+        Some(args) => make_args_runner(args, make_fn_expr(generics)),
+        None if options.setup.is_some() || options.teardown.is_some() => {
+            make_setup_runner(make_fn_expr(generics))
+        }
+        None => {
+            let bench_fn = make_bench_fn(generics);
+            quote! { #private_mod::BenchEntryRunner::Plain(#bench_fn) }
+        }
+    };
+
     let option_none = quote! { #private_mod::None };
     let option_some = quote! { #private_mod::Some };
 
@@ -182,7 +252,7 @@ pub fn bench(options: TokenStream, item: TokenStream) -> TokenStream {
                 generics
             };
 
-            let bench_fn = make_bench_fn(&generics);
+            let bench_fn = make_bench_runner(&generics);
 
             let type_value = match ty {
                 Some(ty) => quote! {
@@ -215,7 +285,7 @@ pub fn bench(options: TokenStream, item: TokenStream) -> TokenStream {
         None => match &options.generic.types {
             // No generics; generate a simple benchmark entry.
             None => {
-                let bench_fn = make_bench_fn(&[]);
+                let bench_fn = make_bench_runner(&[]);
 
                 let entry = quote! {
                     #private_mod::BenchEntry {
@@ -379,8 +449,8 @@ pub fn bench_group(options: TokenStream, item: TokenStream) -> TokenStream {
     // compiler diagnostics.
     //
     // TODO: Fix `unused_attributes` warning when using `#[ignore]` on a module.
-    let ignore_attr_ident =
-        mod_item.attrs.iter().map(|attr| attr.meta.path()).find(|path| path.is_ident("ignore"));
+    let ignore_attr =
+        mod_item.attrs.iter().map(|attr| &attr.meta).find(|meta| meta.path().is_ident("ignore"));
 
     // Prefixed with "__" to prevent IDEs from recommending using this symbol.
     //
@@ -391,7 +461,7 @@ pub fn bench_group(options: TokenStream, item: TokenStream) -> TokenStream {
         mod_ident.span(),
     );
 
-    let meta = entry_meta_expr(&mod_name, &options, ignore_attr_ident);
+    let meta = entry_meta_expr(&mod_name, &options, ignore_attr);
 
     let entry_static = quote! {
         static #static_ident: #private_mod::GroupEntry = #private_mod::GroupEntry {
@@ -453,7 +523,7 @@ pub fn bench_group(options: TokenStream, item: TokenStream) -> TokenStream {
 fn entry_meta_expr(
     raw_name: &str,
     options: &AttrOptions,
-    ignore_attr_ident: Option<&syn::Path>,
+    ignore_attr: Option<&syn::Meta>,
 ) -> proc_macro2::TokenStream {
     let AttrOptions { private_mod, std_crate, .. } = &options;
 
@@ -464,7 +534,12 @@ fn entry_meta_expr(
         None => &raw_name_pretty,
     };
 
-    let bench_options_fn = options.bench_options_fn(ignore_attr_ident);
+    let bench_options_fn = options.bench_options_fn(ignore_attr);
+
+    let tags = match &options.tags {
+        Some(tags) => quote! { &#tags },
+        None => quote! { &[] },
+    };
 
     quote! {
         #private_mod::EntryMeta {
@@ -479,6 +554,8 @@ fn entry_meta_expr(
                 col: #std_crate::column!(),
             },
 
+            tags: #tags,
+
             get_bench_options: #bench_options_fn,
             cached_bench_options: #private_mod::OnceLock::new(),
         }
@@ -0,0 +1,255 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    sync::atomic::{AtomicBool, Ordering::Relaxed},
+};
+
+/// Whether [`AllocProfiler`] is the global allocator.
+///
+/// This is set on the first allocation, which happens before `main`.
+static IS_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Allocation info of the current thread.
+    ///
+    /// This is `const`-initialized without a destructor so that accessing it
+    /// never allocates or fails during thread teardown.
+    static THREAD_INFO: ThreadAllocInfo = const { ThreadAllocInfo::new() };
+}
+
+/// Measures [`GlobalAlloc`] memory usage.
+///
+/// When used as the global allocator, benchmarks show the mean number of
+/// allocations, deallocations, and bytes allocated per iteration, as well as
+/// the peak number of bytes allocated during the sample loop. Because outputs
+/// are dropped after the sample loop, the peak includes all outputs of a
+/// sample.
+///
+/// Allocations are tracked per-thread, so only those made by the benchmarked
+/// function are measured. Reallocations are counted as both a deallocation and
+/// an allocation.
+///
+/// # Examples
+///
+/// The default usage is to create a
+/// [`#[global_allocator]`](macro@global_allocator) that wraps the [`System`]
+/// allocator with [`AllocProfiler::system()`]:
+///
+/// ```
+/// use std::collections::*;
+/// use divan::AllocProfiler;
+///
+/// #[global_allocator]
+/// static ALLOC: AllocProfiler = AllocProfiler::system();
+///
+/// fn main() {
+///     divan::main();
+/// }
+///
+/// #[divan::bench(types = [
+///     Vec<i32>,
+///     LinkedList<i32>,
+///     HashSet<i32>,
+/// ])]
+/// fn from_iter<T>() -> T
+/// where
+///     T: FromIterator<i32>,
+/// {
+///     (0..100).collect()
+/// }
+/// ```
+///
+/// Another allocator can be wrapped with [`AllocProfiler::new()`].
+#[derive(Debug, Default)]
+pub struct AllocProfiler<Alloc = System> {
+    alloc: Alloc,
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for AllocProfiler<A> {
+    #[inline]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        Self::track_alloc(layout.size());
+        self.alloc.alloc(layout)
+    }
+
+    #[inline]
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        Self::track_alloc(layout.size());
+        self.alloc.alloc_zeroed(layout)
+    }
+
+    #[inline]
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        Self::track_dealloc(layout.size());
+        Self::track_alloc(new_size);
+        self.alloc.realloc(ptr, layout, new_size)
+    }
+
+    #[inline]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        Self::track_dealloc(layout.size());
+        self.alloc.dealloc(ptr, layout)
+    }
+}
+
+impl AllocProfiler {
+    /// Profiles the [`System`] allocator.
+    #[inline]
+    pub const fn system() -> Self {
+        Self::new(System)
+    }
+}
+
+impl<A> AllocProfiler<A> {
+    /// Profiles a [`GlobalAlloc`].
+    #[inline]
+    pub const fn new(alloc: A) -> Self {
+        Self { alloc }
+    }
+
+    #[inline]
+    fn track_alloc(size: usize) {
+        if !IS_ACTIVE.load(Relaxed) {
+            IS_ACTIVE.store(true, Relaxed);
+        }
+
+        THREAD_INFO.with(|info| {
+            info.alloc_count.set(info.alloc_count.get() + 1);
+            info.alloc_bytes.set(info.alloc_bytes.get() + size as u64);
+
+            let current = info.current_bytes.get() + size as i64;
+            info.current_bytes.set(current);
+            if current > info.peak_bytes.get() {
+                info.peak_bytes.set(current);
+            }
+        });
+    }
+
+    #[inline]
+    fn track_dealloc(size: usize) {
+        THREAD_INFO.with(|info| {
+            info.dealloc_count.set(info.dealloc_count.get() + 1);
+            info.current_bytes.set(info.current_bytes.get() - size as i64);
+        });
+    }
+}
+
+/// Returns `true` if [`AllocProfiler`] is the global allocator.
+#[inline]
+pub(crate) fn is_active() -> bool {
+    IS_ACTIVE.load(Relaxed)
+}
+
+struct ThreadAllocInfo {
+    alloc_count: Cell<u64>,
+    alloc_bytes: Cell<u64>,
+    dealloc_count: Cell<u64>,
+
+    /// Bytes currently allocated by this thread. This may be negative if the
+    /// thread deallocates memory allocated by another thread.
+    current_bytes: Cell<i64>,
+
+    /// The maximum of `current_bytes` since the last [`AllocSnapshot::start`].
+    peak_bytes: Cell<i64>,
+}
+
+impl ThreadAllocInfo {
+    const fn new() -> Self {
+        Self {
+            alloc_count: Cell::new(0),
+            alloc_bytes: Cell::new(0),
+            dealloc_count: Cell::new(0),
+            current_bytes: Cell::new(0),
+            peak_bytes: Cell::new(0),
+        }
+    }
+}
+
+/// Allocation info of the current thread at the start of a sample.
+#[derive(Clone, Copy)]
+pub(crate) struct AllocSnapshot {
+    alloc_count: u64,
+    alloc_bytes: u64,
+    dealloc_count: u64,
+    current_bytes: i64,
+}
+
+impl AllocSnapshot {
+    /// Takes a snapshot and resets the peak to the current allocated bytes.
+    #[inline]
+    pub fn start() -> Self {
+        THREAD_INFO.with(|info| {
+            info.peak_bytes.set(info.current_bytes.get());
+
+            Self {
+                alloc_count: info.alloc_count.get(),
+                alloc_bytes: info.alloc_bytes.get(),
+                dealloc_count: info.dealloc_count.get(),
+                current_bytes: info.current_bytes.get(),
+            }
+        })
+    }
+
+    /// Returns the allocations made since the snapshot.
+    #[inline]
+    pub fn end(self) -> AllocCounts {
+        THREAD_INFO.with(|info| AllocCounts {
+            alloc_count: info.alloc_count.get() - self.alloc_count,
+            alloc_bytes: info.alloc_bytes.get() - self.alloc_bytes,
+            dealloc_count: info.dealloc_count.get() - self.dealloc_count,
+            peak_bytes: (info.peak_bytes.get() - self.current_bytes).max(0) as u64,
+        })
+    }
+}
+
+/// Allocations made during a single sample.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct AllocCounts {
+    pub alloc_count: u64,
+    pub alloc_bytes: u64,
+    pub dealloc_count: u64,
+
+    /// The maximum bytes allocated at once above the amount allocated at the
+    /// start of the sample.
+    pub peak_bytes: u64,
+}
+
+/// Allocation statistics of a benchmark.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct AllocStats {
+    /// Mean allocations per iteration.
+    pub alloc_count: f64,
+
+    /// Mean bytes allocated per iteration.
+    pub alloc_bytes: f64,
+
+    /// Mean deallocations per iteration.
+    pub dealloc_count: f64,
+
+    /// The maximum [`AllocCounts::peak_bytes`] over all samples.
+    pub peak_bytes: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests tracking without installing the profiler globally.
+    #[test]
+    fn track() {
+        type Profiler = AllocProfiler;
+
+        let snapshot = AllocSnapshot::start();
+
+        Profiler::track_alloc(100);
+        Profiler::track_alloc(50);
+        Profiler::track_dealloc(100);
+        Profiler::track_alloc(10);
+
+        let counts = snapshot.end();
+        assert_eq!(
+            counts,
+            AllocCounts { alloc_count: 3, alloc_bytes: 160, dealloc_count: 1, peak_bytes: 150 }
+        );
+    }
+}
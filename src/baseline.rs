@@ -0,0 +1,294 @@
+//! Named baselines for comparing results against a previous run.
+//!
+//! Baselines are stored as JSON in `target/divan/baselines/<name>/`, with one
+//! file per benchmark binary so that `cargo bench` over multiple targets does
+//! not have them overwrite each other.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{
+    history::{HistoryRecord, RunInfo},
+    json::Json,
+    results::BenchResults,
+    time::{FineDuration, TimerKind},
+};
+
+/// Results of a previous run saved under a name.
+#[derive(Debug)]
+pub(crate) struct Baseline {
+    pub name: String,
+
+    /// The name of the timer used to measure samples.
+    pub timer: Option<String>,
+
+    /// Records keyed by benchmark path.
+    pub records: HashMap<String, HistoryRecord>,
+
+    /// Per-iteration sample times keyed by benchmark path, for testing the
+    /// significance of changes. Baselines saved by older versions do not
+    /// have these.
+    pub samples: HashMap<String, Vec<FineDuration>>,
+}
+
+impl Baseline {
+    pub fn from_results(
+        name: &str,
+        results: &BenchResults,
+        timer: TimerKind,
+        run: &RunInfo,
+    ) -> Self {
+        Self {
+            name: name.to_owned(),
+            timer: Some(timer.name().to_owned()),
+            records: HistoryRecord::from_results(results, run)
+                .into_iter()
+                .map(|record| (record.path.clone(), record))
+                .collect(),
+            samples: results
+                .entries()
+                .filter(|entry| !entry.sample_times.is_empty())
+                .map(|entry| (entry.path.clone(), entry.sample_times.clone()))
+                .collect(),
+        }
+    }
+
+    /// Returns the saved record of the benchmark at `path`.
+    #[inline]
+    pub fn get(&self, path: &str) -> Option<&HistoryRecord> {
+        self.records.get(path)
+    }
+
+    /// Returns the file path of the baseline for the current benchmark binary.
+    pub fn file_path(name: &str) -> io::Result<PathBuf> {
+        check_name(name)?;
+
+        let dir = baselines_dir()?;
+        Ok(dir.join(name).join(format!("{}.json", binary_name()?)))
+    }
+
+    /// Loads the baseline saved under `name` for the current benchmark binary.
+    pub fn load(name: &str) -> io::Result<Self> {
+        let path = Self::file_path(name)?;
+        let json = fs::read_to_string(&path)?;
+
+        let invalid = |message: String| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}: {message}", path.display()))
+        };
+
+        let json = Json::parse(&json).map_err(|error| invalid(error.to_string()))?;
+        Self::from_json(name, &json).ok_or_else(|| invalid("invalid baseline".to_owned()))
+    }
+
+    /// Saves the baseline for the current benchmark binary, replacing any
+    /// existing baseline of the same name.
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::file_path(&self.name)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_json().to_string())
+    }
+
+    fn to_json(&self) -> Json {
+        // Sort for deterministic output.
+        let mut records: Vec<&HistoryRecord> = self.records.values().collect();
+        records.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+
+        let mut samples: Vec<(&String, &Vec<FineDuration>)> = self.samples.iter().collect();
+        samples.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+        let samples = samples.into_iter().fold(Json::object(), |json, (path, times)| {
+            json.with(path, times.iter().map(|time| time.picos).collect::<Vec<_>>())
+        });
+
+        Json::object()
+            .with("timer", self.timer.as_deref())
+            .with("benchmarks", records.into_iter().map(HistoryRecord::to_json).collect::<Vec<_>>())
+            .with("samples", samples)
+    }
+
+    fn from_json(name: &str, json: &Json) -> Option<Self> {
+        let Json::Array(benchmarks) = json.get("benchmarks")? else {
+            return None;
+        };
+
+        let records = benchmarks
+            .iter()
+            .map(|json| HistoryRecord::from_json(json).map(|record| (record.path.clone(), record)))
+            .collect::<Option<_>>()?;
+
+        let samples = match json.get("samples") {
+            Some(Json::Object(entries)) => entries
+                .iter()
+                .map(|(path, times)| {
+                    let times = times
+                        .as_array()?
+                        .iter()
+                        .map(|time| Some(FineDuration { picos: time.as_u128()? }))
+                        .collect::<Option<_>>()?;
+                    Some((path.clone(), times))
+                })
+                .collect::<Option<_>>()?,
+            _ => HashMap::new(),
+        };
+
+        Some(Self {
+            name: name.to_owned(),
+            timer: json.get("timer").and_then(Json::as_str).map(ToOwned::to_owned),
+            records,
+            samples,
+        })
+    }
+}
+
+/// Checks that `name` can be used as a directory name within the baselines
+/// directory, so that it cannot refer to files outside of it.
+pub(crate) fn check_name(name: &str) -> io::Result<()> {
+    // A single normal component cannot be a root, prefix, or parent directory.
+    let mut components = Path::new(name).components();
+    let is_single =
+        matches!((components.next(), components.next()), (Some(Component::Normal(_)), None));
+
+    // On Windows, `C:x` is relative to the current directory of drive `C:`.
+    let has_drive = cfg!(windows) && name.contains(':');
+
+    if is_single && !has_drive {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid baseline name '{name}' (must be a single directory name)"),
+        ))
+    }
+}
+
+/// Returns the directory containing all baselines.
+///
+/// This is `DIVAN_BASELINE_DIR` if set, otherwise `divan/baselines` within the
+/// Cargo target directory, which is `CARGO_TARGET_DIR` if set.
+fn baselines_dir() -> io::Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("DIVAN_BASELINE_DIR") {
+        return Ok(dir.into());
+    }
+
+    let target_dir = match std::env::var_os("CARGO_TARGET_DIR") {
+        Some(dir) => PathBuf::from(dir),
+
+        // Cargo marks the target directory with `CACHEDIR.TAG`. Benchmark
+        // binaries are at `target/<profile>/deps/<name>-<hash>`, or within
+        // `target/<triple>` when cross-compiling.
+        None => {
+            let exe = std::env::current_exe()?;
+            exe.ancestors()
+                .skip(1)
+                .find(|dir| dir.join("CACHEDIR.TAG").is_file())
+                .or_else(|| exe.ancestors().nth(3))
+                .map(ToOwned::to_owned)
+                .ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::NotFound, "could not find target directory")
+                })?
+        }
+    };
+
+    Ok(target_dir.join("divan").join("baselines"))
+}
+
+/// Returns the current executable's name without Cargo's hash suffix.
+fn binary_name() -> io::Result<String> {
+    let exe = std::env::current_exe()?;
+    let stem = exe.file_stem().unwrap_or_default().to_string_lossy();
+    Ok(strip_hash(&stem).to_owned())
+}
+
+/// Removes the `-<hash>` suffix from Cargo artifact names.
+fn strip_hash(name: &str) -> &str {
+    match name.rsplit_once('-') {
+        Some((name, hash)) if hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            name
+        }
+        _ => name,
+    }
+}
+
+/// Returns the percentage change from `baseline` to `current`.
+pub(crate) fn percent_change(baseline: u128, current: u128) -> Option<f64> {
+    if baseline == 0 {
+        return None;
+    }
+    Some((current as f64 - baseline as f64) / baseline as f64 * 100.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::StatsSet;
+
+    #[test]
+    fn strip_hash() {
+        assert_eq!(super::strip_hash("math-0123456789abcdef"), "math");
+        assert_eq!(super::strip_hash("my-bench-0123456789abcdef"), "my-bench");
+        assert_eq!(super::strip_hash("my-bench"), "my-bench");
+        assert_eq!(super::strip_hash("math-0123456789abcdeg"), "math-0123456789abcdeg");
+    }
+
+    #[test]
+    fn check_name() {
+        assert!(super::check_name("main").is_ok());
+        assert!(super::check_name("v1.2").is_ok());
+        assert!(super::check_name("v1..2").is_ok());
+
+        let invalid: &[&str] = if cfg!(windows) {
+            &["", ".", "..", "../../x", "a/b", "a\\b", "/tmp/x", "C:x", "C:\\x", "\\\\?\\x"]
+        } else {
+            &["", ".", "..", "../../x", "a/b", "./a", "/tmp/x"]
+        };
+
+        for name in invalid {
+            assert_eq!(super::check_name(name).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn percent_change() {
+        assert_eq!(super::percent_change(100, 150), Some(50.));
+        assert_eq!(super::percent_change(100, 50), Some(-50.));
+        assert_eq!(super::percent_change(0, 50), None);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mean = FineDuration { picos: 1234 };
+        let record = HistoryRecord {
+            path: "a::b".to_owned(),
+            commit: None,
+            timestamp: 1,
+            machine: None,
+            arch: None,
+            env: None,
+            seed: None,
+            sample_count: 10,
+            iter_count: 100,
+            time: StatsSet { fastest: mean, slowest: mean, median: mean, mean },
+        };
+
+        let baseline = Baseline {
+            name: "main".to_owned(),
+            timer: Some("tsc".to_owned()),
+            records: [(record.path.clone(), record)].into_iter().collect(),
+            samples: [("a::b".to_owned(), vec![mean, FineDuration { picos: 1000 }])]
+                .into_iter()
+                .collect(),
+        };
+
+        let json = Json::parse(&baseline.to_json().to_string()).unwrap();
+        let parsed = Baseline::from_json("main", &json).unwrap();
+
+        assert_eq!(parsed.timer, baseline.timer);
+        assert_eq!(parsed.records, baseline.records);
+        assert_eq!(parsed.samples, baseline.samples);
+    }
+}
@@ -0,0 +1,67 @@
+use std::{
+    panic,
+    sync::mpsc::{self, Receiver, Sender},
+    thread::{self, Scope},
+};
+
+use crate::stats::RawSample;
+
+use super::defer::DeferStore;
+
+/// A thread that records samples alongside the main thread of a multi-threaded
+/// benchmark.
+///
+/// Threads are kept for all of a benchmark's samples, so that per-thread state
+/// like hardware counters is only set up once.
+pub(crate) struct AuxThread {
+    /// Sends the size of the next sample to record.
+    start: Sender<u32>,
+
+    /// Receives the recorded sample, or the payload of a panic.
+    sample: Receiver<thread::Result<RawSample>>,
+}
+
+impl AuxThread {
+    /// Spawns a thread that calls `record_sample` each time a sample is
+    /// started, until `self` is dropped.
+    pub fn spawn<'scope, I, O>(
+        scope: &'scope Scope<'scope, '_>,
+        record_sample: &'scope (impl Fn(u32, &mut DeferStore<I, O>) -> RawSample + Sync),
+    ) -> Self {
+        let (start, start_rx) = mpsc::channel::<u32>();
+        let (sample_tx, sample) = mpsc::channel();
+
+        scope.spawn(move || {
+            let mut defer_store = DeferStore::default();
+
+            for sample_size in start_rx {
+                let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+                    record_sample(sample_size, &mut defer_store)
+                }));
+
+                if sample_tx.send(result).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self { start, sample }
+    }
+
+    /// Starts recording a sample of `sample_size` iterations.
+    #[inline]
+    pub fn start(&self, sample_size: u32) {
+        // The thread only stops once `self` is dropped.
+        _ = self.start.send(sample_size);
+    }
+
+    /// Waits for the sample started by [`start`](Self::start), propagating any
+    /// panic to behave the same as joining the thread.
+    pub fn finish(&self) -> RawSample {
+        match self.sample.recv() {
+            Ok(Ok(sample)) => sample,
+            Ok(Err(payload)) => panic::resume_unwind(payload),
+            Err(_) => unreachable!("auxiliary thread stopped while recording"),
+        }
+    }
+}
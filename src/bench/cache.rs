@@ -0,0 +1,26 @@
+use std::{hint::black_box, sync::OnceLock};
+
+/// The size of the buffer read to evict CPU caches, which is larger than the
+/// last-level cache of most CPUs.
+const EVICT_BUFFER_SIZE: usize = 64 * 1024 * 1024;
+
+/// The stride between reads, which is the cache line size of most CPUs.
+const CACHE_LINE_SIZE: usize = 64;
+
+/// Evicts data from CPU caches by reading a buffer larger than them.
+///
+/// The buffer is shared between threads so that multi-threaded benchmarks do
+/// not allocate a buffer per thread.
+pub(crate) fn flush() {
+    static EVICT_BUFFER: OnceLock<Box<[u8]>> = OnceLock::new();
+
+    // Filling with a non-zero value ensures that pages are backed by distinct
+    // memory, rather than all mapping to the same zero page.
+    let buffer = EVICT_BUFFER.get_or_init(|| vec![1; EVICT_BUFFER_SIZE].into_boxed_slice());
+
+    let mut sum = 0u8;
+    for &byte in buffer.iter().step_by(CACHE_LINE_SIZE) {
+        sum = sum.wrapping_add(black_box(byte));
+    }
+    black_box(sum);
+}
@@ -0,0 +1,101 @@
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+
+/// Round-robin turns between benchmarks measured on separate threads.
+///
+/// Only the benchmark holding the turn runs. It passes the turn to the next
+/// unfinished benchmark between samples, so that thermal drift and background
+/// noise are spread evenly across benchmarks.
+pub(crate) struct Turns {
+    state: Mutex<TurnState>,
+    changed: Condvar,
+}
+
+struct TurnState {
+    /// The index of the benchmark allowed to run.
+    current: usize,
+
+    /// Whether each benchmark has finished.
+    is_done: Vec<bool>,
+}
+
+/// A benchmark's handle to its place in [`Turns`].
+#[derive(Clone, Copy)]
+pub(crate) struct Turn<'a> {
+    turns: &'a Turns,
+    index: usize,
+}
+
+/// Passes the turn along for good when dropped, including when unwinding.
+pub(crate) struct TurnGuard<'a> {
+    turn: Turn<'a>,
+}
+
+impl Turns {
+    pub fn new(count: usize) -> Self {
+        Self {
+            state: Mutex::new(TurnState { current: 0, is_done: vec![false; count] }),
+            changed: Condvar::new(),
+        }
+    }
+
+    /// Blocks until benchmark `index` holds the turn, and releases it once the
+    /// returned guard is dropped.
+    pub fn enter(&self, index: usize) -> TurnGuard<'_> {
+        let turn = Turn { turns: self, index };
+        drop(turn.wait(self.lock()));
+        TurnGuard { turn }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TurnState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl TurnState {
+    /// Gives the turn to the next unfinished benchmark after `index`, which may
+    /// be `index` itself if it is the only one left.
+    fn pass(&mut self, index: usize) {
+        let count = self.is_done.len();
+
+        if let Some(next) =
+            (1..=count).map(|offset| (index + offset) % count).find(|&next| !self.is_done[next])
+        {
+            self.current = next;
+        }
+    }
+}
+
+impl<'a> Turn<'a> {
+    /// Lets every other unfinished benchmark run before continuing.
+    pub fn yield_now(self) {
+        let mut state = self.turns.lock();
+        state.pass(self.index);
+        self.turns.changed.notify_all();
+        drop(self.wait(state));
+    }
+
+    fn wait(self, state: MutexGuard<'a, TurnState>) -> MutexGuard<'a, TurnState> {
+        self.turns
+            .changed
+            .wait_while(state, |state| state.current != self.index)
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for TurnGuard<'_> {
+    fn drop(&mut self) {
+        let Turn { turns, index } = self.turn;
+
+        let mut state = turns.lock();
+        state.is_done[index] = true;
+        state.pass(index);
+        turns.changed.notify_all();
+    }
+}
+
+impl TurnGuard<'_> {
+    #[inline]
+    pub fn turn(&self) -> Turn<'_> {
+        self.turn
+    }
+}
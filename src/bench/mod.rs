@@ -1,17 +1,30 @@
 use std::{
-    cell::UnsafeCell,
+    cell::{Cell, UnsafeCell},
     fmt,
+    future::Future,
+    marker::PhantomData,
     mem::{self, MaybeUninit},
     num::NonZeroUsize,
     sync::Barrier,
     thread,
+    time::{Duration, Instant},
 };
 
 use crate::{
+    alloc::{AllocCounts, AllocSnapshot},
     black_box,
-    counter::{AnyCounter, CounterCollection, IntoCounter, KnownCounterKind, MaxCountUInt},
+    callgrind::CallgrindRole,
+    counter::{AnyCounter, Count, CountTotal, CounterCollection, IntoCounter, KnownCounterKind},
     divan::SharedContext,
-    stats::{RawSample, Sample, SampleCollection, Stats, ThreadSample},
+    executor::{BlockOn, Executor},
+    io_counters::{IoCounts, IoSnapshot},
+    profiler::SampleHook,
+    progress::Status,
+    stats::{
+        Bimodality, RawSample, Sample, SampleCollection, SampleExtreme, Stats, StreamingStats,
+        ThreadSample, TimeHistogram, DEFAULT_RESERVOIR_SIZE, STREAMING_SAMPLE_THRESHOLD,
+        TIME_PERCENTILES,
+    },
     time::{FineDuration, Timestamp, UntaggedTimestamp},
     util::{self, SyncWrap, Unit},
 };
@@ -23,14 +36,33 @@ use crate::counter::BytesCount;
 #[cfg(test)]
 mod tests;
 
+mod aux_thread;
+mod cache;
 mod defer;
+mod interleave;
 mod options;
-
+mod overhead;
+#[cfg(feature = "rand")]
+mod rng;
+mod timer_guard;
+mod tune;
+mod unroll;
+
+use aux_thread::AuxThread;
 use defer::{DeferSlot, DeferStore};
+pub(crate) use interleave::{Turn, Turns};
 pub use options::BenchOptions;
+#[cfg(feature = "rand")]
+pub use rng::BenchRng;
+pub use timer_guard::TimerGuard;
+use tune::Tuner;
 
 pub(crate) const DEFAULT_SAMPLE_COUNT: u32 = 100;
 
+/// The minimum number of samples when `until_stable` is set without
+/// `sample_count`.
+const DEFAULT_STABLE_SAMPLE_COUNT: u32 = 10;
+
 /// Enables contextual benchmarking in [`#[divan::bench]`](attr.bench.html).
 ///
 /// # Examples
@@ -59,10 +91,19 @@ pub struct Bencher<'a, 'b, C = BencherConfig> {
 ///
 /// This enables configuring `Bencher` using the builder pattern with zero
 /// runtime cost.
-pub struct BencherConfig<GenI = Unit> {
+pub struct BencherConfig<GenI = Unit, Exec = BlockOn, GenS = Unit> {
     gen_input: GenI,
+    executor: Exec,
+    gen_state: GenS,
 }
 
+/// Public-in-private type for a [shared input](Bencher::with_shared_input)
+/// generator.
+///
+/// Wrapping the generator prevents per-iteration input methods like
+/// [`Bencher::bench_values`] from being used with it.
+pub struct SharedInput<G>(G);
+
 impl<C> fmt::Debug for Bencher<'_, '_, C> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Bencher").finish_non_exhaustive()
@@ -72,7 +113,10 @@ impl<C> fmt::Debug for Bencher<'_, '_, C> {
 impl<'a, 'b> Bencher<'a, 'b> {
     #[inline]
     pub(crate) fn new(context: &'a mut BenchContext<'b>) -> Self {
-        Self { context, config: BencherConfig { gen_input: Unit } }
+        Self {
+            context,
+            config: BencherConfig { gen_input: Unit, executor: BlockOn, gen_state: Unit },
+        }
     }
 }
 
@@ -123,6 +167,210 @@ impl<'a, 'b> Bencher<'a, 'b> {
         self.with_inputs(|| ()).bench_local_values(|_: ()| benched());
     }
 
+    /// Benchmarks a function that measures its own time.
+    ///
+    /// `benched` is called once per sample with the number of iterations to
+    /// run, and returns the time taken by those iterations. This allows timing
+    /// only a region of interest within code that cannot be separated from
+    /// its scaffolding, such as waiting on a server or setting up I/O.
+    ///
+    /// The function can be benchmarked in parallel using the [`threads`
+    /// option](macro@crate::bench#threads). If the function is strictly
+    /// single-threaded, use [`Bencher::bench_local_custom`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Instant;
+    ///
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher.bench_custom(|iters| {
+    ///         // Untimed setup...
+    ///
+    ///         let start = Instant::now();
+    ///         for _ in 0..iters {
+    ///             // Benchmarked code...
+    ///         }
+    ///         start.elapsed()
+    ///     });
+    /// }
+    /// ```
+    #[doc(alias = "iter_custom")]
+    pub fn bench_custom<B>(self, benched: B)
+    where
+        B: Fn(u64) -> Duration + Sync,
+    {
+        self.context.bench_loop_threaded_custom(|iters| benched(iters).into());
+    }
+
+    /// Benchmarks a function that measures its own time, on the current
+    /// thread.
+    ///
+    /// See [`Bencher::bench_custom`] for how `benched` is called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Instant;
+    ///
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher.bench_local_custom(|iters| {
+    ///         // Untimed setup...
+    ///
+    ///         let start = Instant::now();
+    ///         for _ in 0..iters {
+    ///             // Benchmarked code...
+    ///         }
+    ///         start.elapsed()
+    ///     });
+    /// }
+    /// ```
+    pub fn bench_local_custom<B>(self, mut benched: B)
+    where
+        B: FnMut(u64) -> Duration,
+    {
+        self.context.bench_loop_local_custom(|iters| benched(iters).into());
+    }
+
+    /// Benchmarks a function that can exclude parts of each iteration from
+    /// measurement.
+    ///
+    /// `benched` is given a [`TimerGuard`] for
+    /// [pausing](TimerGuard::pause) and [resuming](TimerGuard::resume) the
+    /// timer. Unlike with [`skip_ext_time`](macro@crate::bench#skip_ext_time),
+    /// untimed work can be interleaved with timed work. Outputs are dropped
+    /// within the timed region, unless the timer is paused.
+    ///
+    /// The function can be benchmarked in parallel using the [`threads`
+    /// option](macro@crate::bench#threads). If the function is strictly
+    /// single-threaded, use [`Bencher::bench_local_with_timer`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher.bench_with_timer(|timer| {
+    ///         timer.pause();
+    ///         // Untimed code...
+    ///         timer.resume();
+    ///
+    ///         // Timed code...
+    ///     });
+    /// }
+    /// ```
+    pub fn bench_with_timer<O, B>(self, benched: B)
+    where
+        B: Fn(&TimerGuard) -> O + Sync,
+    {
+        let timer = self.context.shared_context.timer;
+        self.context.bench_loop_threaded_custom(|iters| {
+            let guard = TimerGuard::new(timer);
+
+            let start = Timestamp::start(timer.kind());
+            for _ in 0..iters {
+                black_box(benched(&guard));
+            }
+            let end = Timestamp::end(timer.kind());
+
+            guard.measured(start, end)
+        });
+    }
+
+    /// Benchmarks a function that can exclude parts of each iteration from
+    /// measurement, on the current thread.
+    ///
+    /// See [`Bencher::bench_with_timer`] for how the timer is paused.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     let mut values = Vec::new();
+    ///     bencher.bench_local_with_timer(|timer| {
+    ///         timer.paused(|| values.clear());
+    ///         values.push(42);
+    ///     });
+    /// }
+    /// ```
+    pub fn bench_local_with_timer<O, B>(self, mut benched: B)
+    where
+        B: FnMut(&TimerGuard) -> O,
+    {
+        let timer = self.context.shared_context.timer;
+        self.context.bench_loop_local_custom(|iters| {
+            let guard = TimerGuard::new(timer);
+
+            let start = Timestamp::start(timer.kind());
+            for _ in 0..iters {
+                black_box(benched(&guard));
+            }
+            let end = Timestamp::end(timer.kind());
+
+            guard.measured(start, end)
+        });
+    }
+}
+
+impl<'a, 'b, Exec> Bencher<'a, 'b, BencherConfig<Unit, Exec>> {
+    /// Benchmarks an `async` function.
+    ///
+    /// Each iteration creates a future and runs it to completion with the
+    /// [executor](Self::with_executor), which is [`BlockOn`] by default. Only
+    /// creating and running the future is measured, so the executor should be
+    /// set up beforehand rather than within `benched`.
+    ///
+    /// The function can be benchmarked in parallel using the [`threads`
+    /// option](macro@crate::bench#threads). If the function is strictly
+    /// single-threaded, use [`Bencher::bench_local_async`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher.bench_async(|| async {
+    ///         // Benchmarked code...
+    ///     });
+    /// }
+    /// ```
+    pub fn bench_async<O, B, F>(self, benched: B)
+    where
+        B: Fn() -> F + Sync,
+        F: Future<Output = O>,
+        Exec: Executor + Sync,
+    {
+        self.with_inputs(|| ()).bench_values_async(|_: ()| benched());
+    }
+
+    /// Benchmarks an `async` function on the current thread.
+    ///
+    /// See [`Bencher::bench_async`] for how futures are run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher.bench_local_async(|| async {
+    ///         // Benchmarked code...
+    ///     });
+    /// }
+    /// ```
+    pub fn bench_local_async<O, B, F>(self, mut benched: B)
+    where
+        B: FnMut() -> F,
+        F: Future<Output = O>,
+        Exec: Executor,
+    {
+        self.with_inputs(|| ()).bench_local_values_async(|_: ()| benched());
+    }
+}
+
+impl<'a, 'b, Exec, GenS> Bencher<'a, 'b, BencherConfig<Unit, Exec, GenS>> {
     /// Generate inputs for the [benchmarked function](#input-bench).
     ///
     /// Time spent generating inputs does not affect benchmark timing.
@@ -147,12 +395,204 @@ impl<'a, 'b> Bencher<'a, 'b> {
     ///         });
     /// }
     /// ```
-    pub fn with_inputs<G>(self, gen_input: G) -> Bencher<'a, 'b, BencherConfig<G>> {
-        Bencher { context: self.context, config: BencherConfig { gen_input } }
+    pub fn with_inputs<G>(self, gen_input: G) -> Bencher<'a, 'b, BencherConfig<G, Exec, GenS>> {
+        Bencher {
+            context: self.context,
+            config: BencherConfig {
+                gen_input,
+                executor: self.config.executor,
+                gen_state: self.config.gen_state,
+            },
+        }
+    }
+
+    /// Generate random inputs for the [benchmarked function](#input-bench)
+    /// from a [seeded generator](BenchRng).
+    ///
+    /// Inputs are reproducible across runs and machines by setting the seed
+    /// with `--seed` or [`Divan::seed`](crate::Divan::seed). Otherwise, a
+    /// random seed is chosen and printed.
+    ///
+    /// Time spent generating inputs does not affect benchmark timing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand_core::RngCore;
+    ///
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher
+    ///         .with_rng_inputs(|rng| {
+    ///             (0..1000).map(|_| rng.next_u64()).collect::<Vec<u64>>()
+    ///         })
+    ///         .bench_refs(|v| v.sort());
+    /// }
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn with_rng_inputs<I, G>(
+        self,
+        gen_input: G,
+    ) -> Bencher<'a, 'b, BencherConfig<impl Fn() -> I + Sync, Exec, GenS>>
+    where
+        G: Fn(&mut BenchRng) -> I + Sync,
+    {
+        use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
+
+        let seed = self.context.shared_context.seed;
+        let index = AtomicU64::new(0);
+
+        self.with_inputs(move || gen_input(&mut BenchRng::new(seed, index.fetch_add(1, Relaxed))))
+    }
+}
+
+impl<'a, 'b, Exec> Bencher<'a, 'b, BencherConfig<Unit, Exec>> {
+    /// Generate a single input that is shared by-reference across all
+    /// iterations of a sample of the [benchmarked function](#shared-bench).
+    ///
+    /// Unlike [`Bencher::with_inputs`], only one input is created per sample,
+    /// so large read-only fixtures are not cloned for every iteration. The
+    /// input is dropped after the sample. If the input can be shared by every
+    /// sample, create it once before benchmarking and reference it from
+    /// [`Bencher::bench`] instead.
+    ///
+    /// Time spent generating and dropping the input does not affect benchmark
+    /// timing. When [benchmarking in parallel](macro@crate::bench#threads),
+    /// each thread gets its own input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher
+    ///         .with_shared_input(|| {
+    ///             // Generate input:
+    ///             (0..1_000_000).collect::<Vec<u64>>()
+    ///         })
+    ///         .bench_shared(|v| {
+    ///             // Use input by-reference:
+    ///             v.iter().sum::<u64>()
+    ///         });
+    /// }
+    /// ```
+    pub fn with_shared_input<G>(
+        self,
+        gen_input: G,
+    ) -> Bencher<'a, 'b, BencherConfig<SharedInput<G>, Exec>> {
+        Bencher {
+            context: self.context,
+            config: BencherConfig {
+                gen_input: SharedInput(gen_input),
+                executor: self.config.executor,
+                gen_state: self.config.gen_state,
+            },
+        }
     }
 }
 
-impl<'a, 'b, GenI> Bencher<'a, 'b, BencherConfig<GenI>> {
+/// <span id="shared-bench"></span> Benchmark over a [shared
+/// input](Self::with_shared_input).
+impl<'a, 'b, I, G, Exec> Bencher<'a, 'b, BencherConfig<SharedInput<G>, Exec>>
+where
+    G: FnMut() -> I,
+{
+    /// Benchmarks a function over a [shared input](Self::with_shared_input),
+    /// provided by-reference.
+    ///
+    /// The function can be benchmarked in parallel using the [`threads`
+    /// option](macro@crate::bench#threads). If the function is strictly
+    /// single-threaded, use [`Bencher::bench_local_shared`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher
+    ///         .with_shared_input(|| vec![1u64; 1_000_000])
+    ///         .bench_shared(|v| v.iter().sum::<u64>());
+    /// }
+    /// ```
+    pub fn bench_shared<O, B>(self, benched: B)
+    where
+        B: Fn(&I) -> O + Sync,
+        G: Fn() -> I + Sync,
+    {
+        // The shared input is per-sample state that is only borrowed
+        // immutably, so no per-iteration inputs are stored.
+        self.context.bench_loop_threaded(
+            self.config.gen_input.0,
+            || (),
+            |input, _| benched(input),
+            |_input| {},
+        );
+    }
+
+    /// Benchmarks a function over a [shared input](Self::with_shared_input),
+    /// provided by-reference, on the current thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher
+    ///         .with_shared_input(|| vec![1u64; 1_000_000])
+    ///         .bench_local_shared(|v| v.iter().sum::<u64>());
+    /// }
+    /// ```
+    pub fn bench_local_shared<O, B>(self, mut benched: B)
+    where
+        B: FnMut(&I) -> O,
+    {
+        self.context.bench_loop_local(
+            self.config.gen_input.0,
+            || (),
+            |input, _| benched(input),
+            |_input| {},
+        );
+    }
+}
+
+impl<'a, 'b, GenI, Exec, GenS> Bencher<'a, 'b, BencherConfig<GenI, Exec, GenS>> {
+    /// Sets the [`Executor`] for running futures in
+    /// [`Bencher::bench_async`] and similar methods.
+    ///
+    /// The executor is reused across all iterations, so expensive setup such
+    /// as building a runtime is not measured. A runtime can be shared with
+    /// other benchmarks by passing it by-reference.
+    ///
+    /// See [`Executor`] for how to use runtimes such as Tokio.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use divan::executor::BlockOn;
+    ///
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher
+    ///         .with_executor(BlockOn)
+    ///         .bench_async(|| async {
+    ///             // Benchmarked code...
+    ///         });
+    /// }
+    /// ```
+    pub fn with_executor<E>(self, executor: E) -> Bencher<'a, 'b, BencherConfig<GenI, E, GenS>>
+    where
+        E: Executor,
+    {
+        Bencher {
+            context: self.context,
+            config: BencherConfig {
+                gen_input: self.config.gen_input,
+                executor,
+                gen_state: self.config.gen_state,
+            },
+        }
+    }
+
     /// Assign a [`Counter`](crate::counter::Counter) for all iterations of the
     /// benchmarked function.
     ///
@@ -184,6 +624,7 @@ impl<'a, 'b, GenI> Bencher<'a, 'b, BencherConfig<GenI>> {
     /// }
     /// ```
     #[doc(alias = "throughput")]
+    #[track_caller]
     pub fn counter<C>(self, counter: C) -> Self
     where
         C: IntoCounter,
@@ -192,10 +633,77 @@ impl<'a, 'b, GenI> Bencher<'a, 'b, BencherConfig<GenI>> {
         self.context.counters.set_counter(counter);
         self
     }
+
+    /// Returns the seed for randomly generated inputs.
+    ///
+    /// This is set by `--seed` or [`Divan::seed`](crate::Divan::seed), and is
+    /// otherwise chosen randomly for each run. Inputs generated by a random
+    /// number generator seeded with this value are reproducible across runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     let seed = bencher.seed();
+    ///
+    ///     bencher.bench(|| {
+    ///         // Benchmarked code using `seed`...
+    ///         # divan::black_box(seed);
+    ///     });
+    /// }
+    /// ```
+    #[inline]
+    pub fn seed(&self) -> u64 {
+        self.context.shared_context.seed
+    }
 }
 
-/// <span id="input-bench"></span> Benchmark over [generated inputs](Self::with_inputs).
-impl<'a, 'b, I, GenI> Bencher<'a, 'b, BencherConfig<GenI>>
+impl<'a, 'b, GenI, Exec> Bencher<'a, 'b, BencherConfig<GenI, Exec>> {
+    /// Generate state for each sample of the [benchmarked
+    /// function](#state-bench).
+    ///
+    /// A fresh state is created before each sample and dropped after it, so
+    /// that every iteration within a sample shares the same state. This is
+    /// useful for things like arena allocators that need to be reset
+    /// periodically, but would be too costly to reset on every iteration.
+    ///
+    /// Time spent generating and dropping state does not affect benchmark
+    /// timing. When [benchmarking in parallel](macro@crate::bench#threads),
+    /// each thread gets its own state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher
+    ///         .with_sample_state(|| {
+    ///             // Generate state:
+    ///             Vec::<u64>::with_capacity(1_000_000)
+    ///         })
+    ///         .bench_local(|vec| {
+    ///             // Use state by-reference:
+    ///             vec.push(42);
+    ///         });
+    /// }
+    /// ```
+    pub fn with_sample_state<G>(
+        self,
+        gen_state: G,
+    ) -> Bencher<'a, 'b, BencherConfig<GenI, Exec, G>> {
+        Bencher {
+            context: self.context,
+            config: BencherConfig {
+                gen_input: self.config.gen_input,
+                executor: self.config.executor,
+                gen_state,
+            },
+        }
+    }
+}
+
+impl<'a, 'b, I, GenI, Exec, GenS> Bencher<'a, 'b, BencherConfig<GenI, Exec, GenS>>
 where
     GenI: FnMut() -> I,
 {
@@ -243,7 +751,13 @@ where
         self.context.counters.set_input_counter(make_counter);
         self
     }
+}
 
+/// <span id="input-bench"></span> Benchmark over [generated inputs](Self::with_inputs).
+impl<'a, 'b, I, GenI, Exec> Bencher<'a, 'b, BencherConfig<GenI, Exec>>
+where
+    GenI: FnMut() -> I,
+{
     /// Benchmarks a function over per-iteration [generated inputs](Self::with_inputs),
     /// provided by-value.
     ///
@@ -276,8 +790,9 @@ where
         GenI: Fn() -> I + Sync,
     {
         self.context.bench_loop_threaded(
+            || (),
             self.config.gen_input,
-            |input| {
+            |_, input| {
                 // SAFETY: Input is guaranteed to be initialized and not
                 // currently referenced by anything else.
                 let input = unsafe { input.get().read().assume_init() };
@@ -317,8 +832,9 @@ where
         B: FnMut(I) -> O,
     {
         self.context.bench_loop_local(
+            || (),
             self.config.gen_input,
-            |input| {
+            |_, input| {
                 // SAFETY: Input is guaranteed to be initialized and not
                 // currently referenced by anything else.
                 let input = unsafe { input.get().read().assume_init() };
@@ -330,6 +846,100 @@ where
         );
     }
 
+    /// Benchmarks an `async` function over per-iteration [generated
+    /// inputs](Self::with_inputs), provided by-value.
+    ///
+    /// Each input is passed to `benched` and the resulting future is run to
+    /// completion with the [executor](Self::with_executor).
+    ///
+    /// The function can be benchmarked in parallel using the [`threads`
+    /// option](macro@crate::bench#threads). If the function is strictly
+    /// single-threaded, use [`Bencher::bench_local_values_async`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher
+    ///         .with_inputs(|| {
+    ///             // Generate input:
+    ///             String::from("...")
+    ///         })
+    ///         .bench_values_async(|s| async move {
+    ///             // Use input by-value:
+    ///             s + "123"
+    ///         });
+    /// }
+    /// ```
+    pub fn bench_values_async<O, B, F>(self, benched: B)
+    where
+        B: Fn(I) -> F + Sync,
+        F: Future<Output = O>,
+        GenI: Fn() -> I + Sync,
+        Exec: Executor + Sync,
+    {
+        let executor = &self.config.executor;
+        self.context.bench_loop_threaded(
+            || (),
+            self.config.gen_input,
+            |_, input| {
+                // SAFETY: Input is guaranteed to be initialized and not
+                // currently referenced by anything else.
+                let input = unsafe { input.get().read().assume_init() };
+
+                executor.block_on(benched(input))
+            },
+            // Input ownership is transferred to `benched`.
+            |_input| {},
+        );
+    }
+
+    /// Benchmarks an `async` function over per-iteration [generated
+    /// inputs](Self::with_inputs), provided by-value.
+    ///
+    /// See [`Bencher::bench_values_async`] for how futures are run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     let mut values = Vec::new();
+    ///     bencher
+    ///         .with_inputs(|| {
+    ///             // Generate input:
+    ///             String::from("...")
+    ///         })
+    ///         .bench_local_values_async(|s| {
+    ///             // Use input by-value:
+    ///             values.push(s);
+    ///             async {}
+    ///         });
+    /// }
+    /// ```
+    pub fn bench_local_values_async<O, B, F>(self, mut benched: B)
+    where
+        B: FnMut(I) -> F,
+        F: Future<Output = O>,
+        Exec: Executor,
+    {
+        let executor = &self.config.executor;
+        self.context.bench_loop_local(
+            || (),
+            self.config.gen_input,
+            |_, input| {
+                // SAFETY: Input is guaranteed to be initialized and not
+                // currently referenced by anything else.
+                let input = unsafe { input.get().read().assume_init() };
+
+                executor.block_on(benched(input))
+            },
+            // Input ownership is transferred to `benched`.
+            |_input| {},
+        );
+    }
+
     /// Benchmarks a function over per-iteration [generated inputs](Self::with_inputs),
     /// provided by-reference.
     ///
@@ -359,8 +969,9 @@ where
     {
         // TODO: Allow `O` to reference `&mut I` as long as `I` outlives `O`.
         self.context.bench_loop_threaded(
+            || (),
             self.config.gen_input,
-            |input| {
+            |_, input| {
                 // SAFETY: Input is guaranteed to be initialized and not
                 // currently referenced by anything else.
                 let input = unsafe { (*input.get()).assume_init_mut() };
@@ -404,8 +1015,9 @@ where
     {
         // TODO: Allow `O` to reference `&mut I` as long as `I` outlives `O`.
         self.context.bench_loop_local(
+            || (),
             self.config.gen_input,
-            |input| {
+            |_, input| {
                 // SAFETY: Input is guaranteed to be initialized and not
                 // currently referenced by anything else.
                 let input = unsafe { (*input.get()).assume_init_mut() };
@@ -422,6 +1034,220 @@ where
     }
 }
 
+/// <span id="state-bench"></span> Benchmark with [per-sample state](Self::with_sample_state).
+impl<'a, 'b, S, Exec, GenS> Bencher<'a, 'b, BencherConfig<Unit, Exec, GenS>>
+where
+    GenS: FnMut() -> S,
+{
+    /// Benchmarks a function over [per-sample state](Self::with_sample_state).
+    ///
+    /// The function can be benchmarked in parallel using the [`threads`
+    /// option](macro@crate::bench#threads). If the function is strictly
+    /// single-threaded, use [`Bencher::bench_local`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher
+    ///         .with_sample_state(|| Vec::<u64>::with_capacity(1_000_000))
+    ///         .bench(|vec| vec.push(42));
+    /// }
+    /// ```
+    pub fn bench<O, B>(self, benched: B)
+    where
+        B: Fn(&mut S) -> O + Sync,
+        GenS: Fn() -> S + Sync,
+    {
+        self.with_inputs(|| ()).bench_values(|state, _: ()| benched(state));
+    }
+
+    /// Benchmarks a function over [per-sample state](Self::with_sample_state)
+    /// on the current thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher
+    ///         .with_sample_state(|| Vec::<u64>::with_capacity(1_000_000))
+    ///         .bench_local(|vec| vec.push(42));
+    /// }
+    /// ```
+    pub fn bench_local<O, B>(self, mut benched: B)
+    where
+        B: FnMut(&mut S) -> O,
+    {
+        self.with_inputs(|| ()).bench_local_values(|state, _: ()| benched(state));
+    }
+}
+
+/// Benchmark with [per-sample state](Self::with_sample_state) over [generated
+/// inputs](Self::with_inputs).
+impl<'a, 'b, S, I, GenI, Exec, GenS> Bencher<'a, 'b, BencherConfig<GenI, Exec, GenS>>
+where
+    GenI: FnMut() -> I,
+    GenS: FnMut() -> S,
+{
+    /// Benchmarks a function over [per-sample state](Self::with_sample_state)
+    /// and per-iteration [generated inputs](Self::with_inputs), provided
+    /// by-value.
+    ///
+    /// The function can be benchmarked in parallel using the [`threads`
+    /// option](macro@crate::bench#threads). If the function is strictly
+    /// single-threaded, use [`Bencher::bench_local_values`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher
+    ///         .with_sample_state(|| Vec::<String>::with_capacity(1_000_000))
+    ///         .with_inputs(|| String::from("..."))
+    ///         .bench_values(|vec, s| vec.push(s));
+    /// }
+    /// ```
+    pub fn bench_values<O, B>(self, benched: B)
+    where
+        B: Fn(&mut S, I) -> O + Sync,
+        GenI: Fn() -> I + Sync,
+        GenS: Fn() -> S + Sync,
+    {
+        self.context.bench_loop_threaded(
+            self.config.gen_state,
+            self.config.gen_input,
+            |state, input| {
+                // SAFETY: Input is guaranteed to be initialized and not
+                // currently referenced by anything else.
+                let input = unsafe { input.get().read().assume_init() };
+
+                benched(state, input)
+            },
+            // Input ownership is transferred to `benched`.
+            |_input| {},
+        );
+    }
+
+    /// Benchmarks a function over [per-sample state](Self::with_sample_state)
+    /// and per-iteration [generated inputs](Self::with_inputs), provided
+    /// by-value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher
+    ///         .with_sample_state(|| Vec::<String>::with_capacity(1_000_000))
+    ///         .with_inputs(|| String::from("..."))
+    ///         .bench_local_values(|vec, s| vec.push(s));
+    /// }
+    /// ```
+    pub fn bench_local_values<O, B>(self, mut benched: B)
+    where
+        B: FnMut(&mut S, I) -> O,
+    {
+        self.context.bench_loop_local(
+            self.config.gen_state,
+            self.config.gen_input,
+            |state, input| {
+                // SAFETY: Input is guaranteed to be initialized and not
+                // currently referenced by anything else.
+                let input = unsafe { input.get().read().assume_init() };
+
+                benched(state, input)
+            },
+            // Input ownership is transferred to `benched`.
+            |_input| {},
+        );
+    }
+
+    /// Benchmarks a function over [per-sample state](Self::with_sample_state)
+    /// and per-iteration [generated inputs](Self::with_inputs), provided
+    /// by-reference.
+    ///
+    /// The function can be benchmarked in parallel using the [`threads`
+    /// option](macro@crate::bench#threads). If the function is strictly
+    /// single-threaded, use [`Bencher::bench_local_refs`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher
+    ///         .with_sample_state(|| String::with_capacity(1_000_000))
+    ///         .with_inputs(|| String::from("..."))
+    ///         .bench_refs(|buf, s| buf.push_str(s));
+    /// }
+    /// ```
+    pub fn bench_refs<O, B>(self, benched: B)
+    where
+        B: Fn(&mut S, &mut I) -> O + Sync,
+        GenI: Fn() -> I + Sync,
+        GenS: Fn() -> S + Sync,
+    {
+        self.context.bench_loop_threaded(
+            self.config.gen_state,
+            self.config.gen_input,
+            |state, input| {
+                // SAFETY: Input is guaranteed to be initialized and not
+                // currently referenced by anything else.
+                let input = unsafe { (*input.get()).assume_init_mut() };
+
+                benched(state, input)
+            },
+            // Input ownership was not transferred to `benched`.
+            |input| {
+                // SAFETY: This function is called after `benched` outputs are
+                // dropped, so we have exclusive access.
+                unsafe { (*input.get()).assume_init_drop() }
+            },
+        );
+    }
+
+    /// Benchmarks a function over [per-sample state](Self::with_sample_state)
+    /// and per-iteration [generated inputs](Self::with_inputs), provided
+    /// by-reference.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher
+    ///         .with_sample_state(|| String::with_capacity(1_000_000))
+    ///         .with_inputs(|| String::from("..."))
+    ///         .bench_local_refs(|buf, s| buf.push_str(s));
+    /// }
+    /// ```
+    pub fn bench_local_refs<O, B>(self, mut benched: B)
+    where
+        B: FnMut(&mut S, &mut I) -> O,
+    {
+        self.context.bench_loop_local(
+            self.config.gen_state,
+            self.config.gen_input,
+            |state, input| {
+                // SAFETY: Input is guaranteed to be initialized and not
+                // currently referenced by anything else.
+                let input = unsafe { (*input.get()).assume_init_mut() };
+
+                benched(state, input)
+            },
+            // Input ownership was not transferred to `benched`.
+            |input| {
+                // SAFETY: This function is called after `benched` outputs are
+                // dropped, so we have exclusive access.
+                unsafe { (*input.get()).assume_init_drop() }
+            },
+        );
+    }
+}
+
 /// State machine for how the benchmark is being run.
 #[derive(Clone, Copy)]
 pub(crate) enum BenchMode {
@@ -453,11 +1279,83 @@ impl BenchMode {
         matches!(self, Self::Collect { .. })
     }
 
-    #[inline]
-    pub fn sample_size(self) -> u32 {
-        match self {
-            Self::Test => 1,
-            Self::Tune { sample_size, .. } | Self::Collect { sample_size, .. } => sample_size,
+    #[inline]
+    pub fn sample_size(self) -> u32 {
+        match self {
+            Self::Test => 1,
+            Self::Tune { sample_size, .. } | Self::Collect { sample_size, .. } => sample_size,
+        }
+    }
+}
+
+/// A single thread's sample, as returned by a sample recorder.
+struct RecordedSample {
+    /// The timestamps before and after the sample loop.
+    timestamps: [Timestamp; 2],
+
+    /// The duration reported by the benchmark itself, if any.
+    custom_duration: Option<FineDuration>,
+
+    /// The time spent generating inputs, if measured.
+    gen_duration: Option<FineDuration>,
+
+    /// The time spent dropping outputs and inputs, if measured.
+    drop_duration: Option<FineDuration>,
+
+    /// Allocations made during the sample loop, if profiled.
+    alloc_counts: Option<AllocCounts>,
+
+    /// I/O done during the sample loop, if counted.
+    io_counts: Option<IoCounts>,
+}
+
+/// Totals of counts from a sample's inputs of type `I`, passed to a sample
+/// recorder.
+///
+/// This is a concrete type rather than `dyn FnMut(&I)` so that counting is a
+/// direct call that is inlined into input generation, and skipped entirely if
+/// no counter counts inputs.
+struct InputCounter<'a, I> {
+    counters: &'a CounterCollection,
+
+    /// Whether any counter counts inputs.
+    is_counting: bool,
+
+    totals: [CountTotal; KnownCounterKind::COUNT],
+
+    /// Ties the counter to the input type it was created for.
+    marker: PhantomData<fn(&I)>,
+}
+
+impl<'a, I> InputCounter<'a, I> {
+    /// Creates a counter with zeroed totals.
+    ///
+    /// # Safety
+    ///
+    /// `I` must be the type used by `set_input_counter`, if called.
+    unsafe fn new(counters: &'a CounterCollection) -> Self {
+        Self {
+            counters,
+            is_counting: KnownCounterKind::ALL
+                .into_iter()
+                .any(|counter_kind| counters.uses_input_counts(counter_kind)),
+            totals: [CountTotal::ZERO; KnownCounterKind::COUNT],
+            marker: PhantomData,
+        }
+    }
+
+    /// Adds the counts of `input` to the totals.
+    #[inline(always)]
+    fn count(&mut self, input: &I) {
+        if !self.is_counting {
+            return;
+        }
+
+        for counter_kind in KnownCounterKind::ALL {
+            // SAFETY: `I` is the input counter's type, as ensured by `new`.
+            if let Some(count) = unsafe { self.counters.get_input_count(counter_kind, input) } {
+                self.totals[counter_kind as usize] += count.into();
+            }
         }
     }
 }
@@ -475,6 +1373,9 @@ pub(crate) struct BenchContext<'a> {
     /// Whether the benchmark loop was started.
     pub did_run: bool,
 
+    /// Whether the benchmark loop was stopped by the `timeout` option.
+    pub timed_out: bool,
+
     /// The number of threads to run the benchmark. The default is 1.
     ///
     /// When set to 1, the benchmark loop is guaranteed to stay on the current
@@ -482,10 +1383,16 @@ pub(crate) struct BenchContext<'a> {
     pub thread_count: NonZeroUsize,
 
     /// Recorded samples.
-    samples: SampleCollection,
+    pub samples: SampleCollection,
 
     /// Per-iteration counters grouped by sample.
     counters: CounterCollection,
+
+    /// This benchmark's turn when interleaved with others.
+    pub turn: Option<Turn<'a>>,
+
+    /// The benchmark's full path, which names its profiler spans.
+    pub entry_path: &'a str,
 }
 
 impl<'a> BenchContext<'a> {
@@ -500,8 +1407,11 @@ impl<'a> BenchContext<'a> {
             options,
             thread_count,
             did_run: false,
+            timed_out: false,
             samples: SampleCollection::default(),
             counters: options.counters.to_collection(),
+            turn: None,
+            entry_path: "",
         }
     }
 
@@ -510,23 +1420,26 @@ impl<'a> BenchContext<'a> {
     /// # Safety
     ///
     /// See `bench_loop_threaded`.
-    pub fn bench_loop_local<I, O>(
+    pub fn bench_loop_local<S, I, O>(
         &mut self,
+        gen_state: impl FnMut() -> S,
         gen_input: impl FnMut() -> I,
-        benched: impl FnMut(&UnsafeCell<MaybeUninit<I>>) -> O,
+        benched: impl FnMut(&mut S, &UnsafeCell<MaybeUninit<I>>) -> O,
         drop_input: impl Fn(&UnsafeCell<MaybeUninit<I>>),
     ) {
         // SAFETY: Closures are guaranteed to run on the current thread, so they
         // can safely be mutable and non-`Sync`.
         unsafe {
+            let gen_state = SyncWrap::new(UnsafeCell::new(gen_state));
             let gen_input = SyncWrap::new(UnsafeCell::new(gen_input));
             let benched = SyncWrap::new(UnsafeCell::new(benched));
             let drop_input = SyncWrap::new(drop_input);
 
             self.thread_count = NonZeroUsize::MIN;
-            self.bench_loop_threaded::<I, O>(
+            self.bench_loop_threaded::<S, I, O>(
+                || (*gen_state.get())(),
                 || (*gen_input.get())(),
-                |input| (*benched.get())(input),
+                |state, input| (*benched.get())(state, input),
                 |input| drop_input(input),
             )
         }
@@ -540,6 +1453,9 @@ impl<'a> BenchContext<'a> {
     /// current thread. This guarantee ensures `bench_loop_local` can soundly
     /// reuse this method with mutable non-`Sync` closures.
     ///
+    /// `gen_state` is called once per sample on each thread, and the state is
+    /// dropped after the sample's inputs and outputs.
+    ///
     /// When `benched` is called:
     /// - `I` is guaranteed to be initialized.
     /// - No external `&I` or `&mut I` exists.
@@ -548,18 +1464,70 @@ impl<'a> BenchContext<'a> {
     /// - All instances of `O` returned from `benched` have been dropped.
     /// - The same guarantees for `I` apply as in `benched`, unless `benched`
     ///   escaped references to `I`.
-    fn bench_loop_threaded<I, O>(
+    fn bench_loop_threaded<S, I, O>(
         &mut self,
+        gen_state: impl Fn() -> S + Sync,
         gen_input: impl Fn() -> I + Sync,
-        benched: impl Fn(&UnsafeCell<MaybeUninit<I>>) -> O + Sync,
+        benched: impl Fn(&mut S, &UnsafeCell<MaybeUninit<I>>) -> O + Sync,
         drop_input: impl Fn(&UnsafeCell<MaybeUninit<I>>) + Sync,
+    ) {
+        let (shared_context, options) = (self.shared_context, self.options);
+        let loop_overhead = || overhead::measure::<I, O>(shared_context, options);
+
+        let record_sample = self.sample_recorder(gen_state, gen_input, benched, drop_input);
+        self.sample_loop(loop_overhead, record_sample);
+    }
+
+    /// Runs the single-threaded loop for benchmarking `benched`, which times
+    /// its iterations itself.
+    pub fn bench_loop_local_custom(&mut self, benched: impl FnMut(u64) -> FineDuration) {
+        // SAFETY: The closure is guaranteed to run on the current thread, so
+        // it can safely be mutable and non-`Sync`.
+        unsafe {
+            let benched = SyncWrap::new(UnsafeCell::new(benched));
+
+            self.thread_count = NonZeroUsize::MIN;
+            self.bench_loop_threaded_custom(|iters| (*benched.get())(iters))
+        }
+    }
+
+    /// Runs the multi-threaded loop for benchmarking `benched`, which times
+    /// its iterations itself.
+    ///
+    /// # Safety
+    ///
+    /// See `bench_loop_threaded`.
+    fn bench_loop_threaded_custom(&mut self, benched: impl Fn(u64) -> FineDuration + Sync) {
+        // Iterations are timed by `benched`, so there is no sample loop to
+        // calibrate.
+        let bench_overhead = self.shared_context.bench_overhead;
+
+        let record_sample = self.custom_sample_recorder(benched);
+        self.sample_loop(|| bench_overhead, record_sample);
+    }
+
+    /// Collects samples from `record_sample` until the sample count and time
+    /// limits are reached.
+    ///
+    /// `loop_overhead` returns the per-iteration overhead to subtract from
+    /// samples, and is only called if samples are recorded.
+    #[allow(clippy::type_complexity)]
+    fn sample_loop<I, O>(
+        &mut self,
+        loop_overhead: impl FnOnce() -> FineDuration,
+        record_sample: impl Fn(
+                usize,
+                Option<&Barrier>,
+                &mut DeferStore<I, O>,
+                &mut InputCounter<I>,
+            ) -> RecordedSample
+            + Sync,
     ) {
         self.did_run = true;
 
         let mut current_mode = self.initial_mode();
         let is_test = current_mode.is_test();
 
-        let record_sample = self.sample_recorder(gen_input, benched, drop_input);
         let mut defer_store = DeferStore::default();
 
         let thread_count = self.thread_count.get();
@@ -579,6 +1547,10 @@ impl<'a> BenchContext<'a> {
         // `benched`, such as time spent generating inputs and running drop.
         let mut elapsed_picos: u128 = 0;
 
+        // The time spent waiting for interleaved benchmarks, in picoseconds.
+        // This is excluded from elapsed time.
+        let mut waited_picos: u128 = 0;
+
         // The minimum time for benchmarking, in picoseconds.
         let min_picos = self.options.min_time().picos;
 
@@ -590,233 +1562,572 @@ impl<'a> BenchContext<'a> {
             return;
         }
 
+        let loop_overhead = if is_test { FineDuration::default() } else { loop_overhead() };
+
         let timer = self.shared_context.timer;
         let timer_kind = timer.kind();
 
-        let mut rem_samples = if current_mode.is_collect() {
-            Some(self.options.sample_count.unwrap_or(DEFAULT_SAMPLE_COUNT))
+        let hw_counters = self.shared_context.hw_counters;
+        let has_perf_events = !self.shared_context.perf_events.is_empty();
+
+        // With `until_stable`, the sample count is a minimum rather than a
+        // target.
+        let until_stable = self.options.until_stable;
+        let sample_count = self.options.sample_count.unwrap_or(if until_stable.is_some() {
+            DEFAULT_STABLE_SAMPLE_COUNT
         } else {
-            None
-        };
+            DEFAULT_SAMPLE_COUNT
+        });
+
+        let mut rem_samples = if current_mode.is_collect() { Some(sample_count) } else { None };
+
+        // Running sums of sample durations in picoseconds, for computing the
+        // coefficient of variation when `until_stable` is set.
+        let mut duration_sum = 0f64;
+        let mut duration_sq_sum = 0f64;
 
         // Only measure precision if we need to tune sample size.
         let timer_precision =
             if current_mode.is_tune() { timer.precision() } else { FineDuration::default() };
 
+        // Limit the time of each sample's body to fit `sample_count` samples
+        // within `max_time`.
+        let max_body_time = FineDuration { picos: max_picos / sample_count.max(1) as u128 };
+        let mut tuner = Tuner::new(
+            timer_precision,
+            max_body_time.min(tune::DEFAULT_MAX_BODY_TIME),
+            self.options.sample_time.map(FineDuration::from),
+        );
+
+        // Flushing caches is part of each sample body but should not limit
+        // sample sizes like expensive inputs do, so a flush is timed once to
+        // exclude it when tuning.
+        let flush_picos = if self.options.flush_cache.unwrap_or_default() && !is_test {
+            let flush_start = Timestamp::start(timer_kind);
+            cache::flush();
+            Timestamp::start(timer_kind).duration_since(flush_start, timer).picos
+        } else {
+            0
+        };
+
+        // Samples are aggregated instead of stored if there would be too many.
+        self.samples.streaming_threshold = match self.options.streaming_stats {
+            Some(true) => Some(0),
+            Some(false) => None,
+            None => Some(STREAMING_SAMPLE_THRESHOLD),
+        };
+        self.samples.reservoir_size =
+            self.options.reservoir_size.map_or(DEFAULT_RESERVOIR_SIZE, |size| size as usize);
+        self.samples.thread_count = thread_count;
+        self.samples.histogram =
+            if self.shared_context.hdr_histogram && !is_test { TimeHistogram::new() } else { None };
+
         if !is_test {
-            self.samples.all.reserve(self.options.sample_count.unwrap_or(1) as usize);
+            let reserve_count = self.options.sample_count.unwrap_or(1) as usize;
+            self.samples
+                .all
+                .reserve(reserve_count.min(self.samples.streaming_threshold.unwrap_or(usize::MAX)));
         }
 
+        // Warm-up samples are run before tuning and collecting, and then
+        // discarded. Their time is not counted towards `min_time` or
+        // `max_time`.
+        let mut warmup_rem_samples =
+            if is_test { 0 } else { self.options.warmup_samples.unwrap_or_default() };
+        let warmup_picos = if is_test { 0 } else { self.options.warmup_time().picos };
+        let mut is_warming_up = warmup_rem_samples > 0 || warmup_picos > 0;
+        let warmup_start = Timestamp::start(timer_kind);
+
         let skip_ext_time = self.options.skip_ext_time.unwrap_or_default();
-        let initial_start = if skip_ext_time { None } else { Some(Timestamp::start(timer_kind)) };
-
-        while {
-            // Conditions for when sampling is over:
-            if elapsed_picos >= max_picos {
-                // Depleted the benchmarking time budget. This is a strict
-                // condition regardless of sample count and minimum time.
-                false
-            } else if rem_samples.unwrap_or(1) > 0 {
-                // More samples expected.
-                true
-            } else {
-                // Continue if we haven't reached the time floor.
-                elapsed_picos < min_picos
+        let mut initial_start =
+            if skip_ext_time { None } else { Some(Timestamp::start(timer_kind)) };
+
+        // The timeout is checked between samples against wall-clock time, so
+        // it includes warm-up and time spent waiting for other benchmarks.
+        // Sampling also stops before a sample that would not fit within the
+        // remaining time, based on the time per iteration of the previous
+        // sample.
+        let timeout = self.options.timeout;
+        let loop_start = Instant::now();
+        let mut iter_picos: Option<u128> = None;
+
+        #[cfg(feature = "tracing")]
+        let _bench_span =
+            tracing::info_span!("bench", path = self.entry_path, threads = thread_count).entered();
+
+        #[cfg(feature = "tracy")]
+        let tracy = crate::tracy::TracyBench::start(self.entry_path);
+
+        #[cfg(all(target_os = "macos", not(miri)))]
+        let signposts = crate::signpost::BenchSignposts::begin(self.entry_path);
+
+        let _profile_range = self
+            .shared_context
+            .profile_markers
+            .as_ref()
+            .map(|markers| markers.start(self.entry_path, thread_count));
+
+        let _profiler_hooks =
+            crate::profiler::start(&self.shared_context.profiler_hooks, self.entry_path);
+
+        // Interleaved benchmarks share rows, so they do not show progress.
+        let progress = self.shared_context.progress.as_ref().filter(|_| self.turn.is_none());
+
+        let is_status_shown = self.shared_context.status_dump && self.turn.is_none();
+        let _status = is_status_shown.then(|| crate::status::start(self.entry_path));
+
+        // Calls a per-sample hook, returning the time spent in picoseconds so
+        // that it can be excluded from elapsed time like waiting.
+        let entry_path = self.entry_path;
+        let call_sample_hook = |hook: Option<SampleHook>| -> u128 {
+            let Some(hook) = hook else {
+                return 0;
+            };
+
+            let hook_start = Timestamp::start(timer_kind);
+            hook(entry_path);
+            Timestamp::start(timer_kind).duration_since(hook_start, timer).picos
+        };
+
+        // Reused by every sample.
+        let barrier = if is_single_thread { None } else { Some(Barrier::new(thread_count)) };
+
+        // Per-iteration input counts of kept samples. These are added to
+        // `self.counters` after sampling, since auxiliary threads read its
+        // input counters in the meantime.
+        let mut input_counts: [Vec<Count>; KnownCounterKind::COUNT] = Default::default();
+
+        // Sample loop helper:
+        let record_sample = |sample_size: u32, defer_store: &mut DeferStore<I, O>| -> RawSample {
+            // Updates per-input counter info for this sample.
+            //
+            // SAFETY: The `I` type cannot change since `with_inputs` cannot
+            // be called more than once on the same `Bencher`, and custom
+            // benchmarks cannot have inputs.
+            let mut input_counter = unsafe { InputCounter::<I>::new(&self.counters) };
+
+            // Sample loop:
+            let RecordedSample {
+                timestamps: [start, end],
+                custom_duration,
+                gen_duration,
+                drop_duration,
+                alloc_counts,
+                io_counts,
+            } = record_sample(
+                sample_size as usize,
+                barrier.as_ref(),
+                defer_store,
+                &mut input_counter,
+            );
+
+            let counter_totals = input_counter.totals;
+
+            let hw_counts = if hw_counters { crate::hw::read() } else { None };
+            let perf_counts = if has_perf_events { crate::hw::read_perf() } else { None };
+
+            RawSample {
+                start,
+                end,
+                timer,
+                counter_totals,
+                hw_counts,
+                perf_counts,
+                alloc_counts,
+                io_counts,
+                custom_duration,
+                gen_duration,
+                drop_duration,
             }
-        } {
-            let sample_size = current_mode.sample_size();
-            self.samples.sample_size = sample_size;
+        };
 
-            let barrier = if is_single_thread { None } else { Some(Barrier::new(thread_count)) };
+        thread::scope(|scope| {
+            let aux_threads: Vec<AuxThread> =
+                (0..aux_thread_count).map(|_| AuxThread::spawn(scope, &record_sample)).collect();
+
+            while {
+                // Conditions for when sampling is over:
+                if timeout.is_some_and(|timeout| loop_start.elapsed() >= timeout) {
+                    self.timed_out = true;
+                    false
+                } else if is_warming_up {
+                    // Warm-up is not limited by `max_time`.
+                    true
+                } else if elapsed_picos >= max_picos {
+                    // Depleted the benchmarking time budget. This is a strict
+                    // condition regardless of sample count and minimum time.
+                    false
+                } else if rem_samples.unwrap_or(1) > 0 {
+                    // More samples expected.
+                    true
+                } else if elapsed_picos < min_picos {
+                    // Continue if we haven't reached the time floor.
+                    true
+                } else if let Some(max_cv) = until_stable {
+                    // Continue until samples vary by less than the threshold.
+                    let count = self.samples.len() as f64;
+                    let mean = duration_sum / count;
+                    let variance = (duration_sq_sum / count - mean * mean).max(0.0);
+                    let cv = variance.sqrt() / mean * 100.0;
+                    cv > max_cv
+                } else {
+                    false
+                }
+            } {
+                if let (Some(timeout), Some(iter_picos)) = (timeout, iter_picos) {
+                    let rem_picos = timeout.saturating_sub(loop_start.elapsed()).as_nanos() * 1_000;
+                    let max_size = rem_picos / iter_picos.max(1);
+                    if max_size < current_mode.sample_size() as u128 {
+                        self.timed_out = true;
+                        break;
+                    }
+                }
 
-            // Sample loop helper:
-            let record_sample = |defer_store: &mut DeferStore<I, O>| -> RawSample {
-                let mut counter_totals: [u128; KnownCounterKind::COUNT] =
-                    [0; KnownCounterKind::COUNT];
+                // Let interleaved benchmarks record a sample.
+                if let Some(turn) = self.turn {
+                    let wait_start = Timestamp::start(timer_kind);
+                    turn.yield_now();
+                    let wait_end = Timestamp::start(timer_kind);
 
-                // Updates per-input counter info for this sample.
-                let mut count_input = |input: &I| {
-                    for counter_kind in KnownCounterKind::ALL {
-                        // SAFETY: The `I` type cannot change since `with_inputs`
-                        // cannot be called more than once on the same `Bencher`.
-                        if let Some(count) =
-                            unsafe { self.counters.get_input_count(counter_kind, input) }
-                        {
-                            let total = &mut counter_totals[counter_kind as usize];
-                            *total = (*total).saturating_add(count as u128);
+                    waited_picos += wait_end.duration_since(wait_start, timer).picos;
+                }
+
+                if let Some(progress) = progress {
+                    progress.update(|| {
+                        if is_warming_up {
+                            Status::WarmingUp
+                        } else if current_mode.is_tune() {
+                            Status::Tuning
+                        } else {
+                            let samples = self.samples.len();
+
+                            // Extrapolate from the time taken by samples so far,
+                            // within the bounds of `min_time` and `max_time`.
+                            let eta = (samples > 0).then(|| {
+                                let rem_samples = rem_samples.unwrap_or_default() as u128;
+                                let picos = (elapsed_picos / samples as u128 * rem_samples)
+                                    .max(min_picos.saturating_sub(elapsed_picos))
+                                    .min(max_picos.saturating_sub(elapsed_picos));
+                                FineDuration { picos }
+                            });
+
+                            Status::Collecting { samples, expected: sample_count as usize, eta }
                         }
-                    }
-                };
+                    });
+                }
+
+                waited_picos += call_sample_hook(self.shared_context.on_sample_begin);
+
+                let sample_size = current_mode.sample_size();
+                self.samples.sample_size = sample_size;
+
+                #[cfg(feature = "tracing")]
+                let sample_span = tracing::trace_span!(
+                    "sample",
+                    sample_size,
+                    warmup = is_warming_up,
+                    duration_ns = tracing::field::Empty,
+                )
+                .entered();
+
+                #[cfg(feature = "tracy")]
+                let tracy_zone = tracy.start_sample(sample_size);
+
+                #[cfg(all(target_os = "macos", not(miri)))]
+                let signpost_id = signposts.begin_sample();
 
                 // Sample loop:
-                let [start, end] = record_sample(
-                    sample_size as usize,
-                    barrier.as_ref(),
-                    defer_store,
-                    &mut count_input,
-                );
-
-                RawSample { start, end, timer, counter_totals }
-            };
+                raw_samples.clear();
+                let body_start = Timestamp::start(timer_kind);
+                if is_single_thread {
+                    let sample = record_sample(sample_size, &mut defer_store);
+                    if !is_test {
+                        raw_samples.push(sample);
+                    }
+                } else {
+                    for aux_thread in &aux_threads {
+                        aux_thread.start(sample_size);
+                    }
 
-            // Sample loop:
-            raw_samples.clear();
-            if is_single_thread {
-                let sample = record_sample(&mut defer_store);
-                if !is_test {
-                    raw_samples.push(sample);
-                }
-            } else {
-                // TODO: Reuse auxiliary threads across samples.
-                thread::scope(|scope| {
-                    let thread_handles: Vec<_> = (0..aux_thread_count)
-                        .map(|_| scope.spawn(|| record_sample(&mut DeferStore::default())))
-                        .collect();
+                    let local_sample = record_sample(sample_size, &mut defer_store);
 
-                    let local_sample = record_sample(&mut defer_store);
+                    for aux_thread in &aux_threads {
+                        let sample = aux_thread.finish();
+                        if !is_test {
+                            raw_samples.push(sample);
+                        }
+                    }
 
                     if !is_test {
-                        raw_samples.extend(
-                            thread_handles
-                                .into_iter()
-                                .map(|handle| {
-                                    // Propagate panics to behave the same as
-                                    // automatic joining.
-                                    handle
-                                        .join()
-                                        .unwrap_or_else(|error| std::panic::resume_unwind(error))
-                                })
-                                .chain(Some(local_sample)),
-                        );
+                        raw_samples.push(local_sample);
                     }
-                });
-            }
+                }
+                let body_end = Timestamp::start(timer_kind);
 
-            #[cfg(test)]
-            if is_test {
-                // '--test' should run the expected number of times but not
-                // allocate any samples.
-                assert_eq!(raw_samples.capacity(), 0);
-            } else {
-                assert_eq!(raw_samples.len(), thread_count);
-            }
+                iter_picos =
+                    Some(body_end.duration_since(body_start, timer).picos / sample_size as u128);
 
-            // If testing, exit the benchmarking loop immediately after timing a
-            // single run.
-            if is_test {
-                break;
-            }
+                #[cfg(all(target_os = "macos", not(miri)))]
+                signposts.end_sample(signpost_id);
 
-            let slowest_sample = raw_samples.iter().max_by_key(|s| s.duration()).unwrap();
-            let slowest_time = slowest_sample.duration();
+                // Threads' samples run in parallel, so the slowest is the duration
+                // of the whole sample.
+                #[cfg(any(feature = "tracing", feature = "tracy"))]
+                let sample_duration = raw_samples.iter().map(RawSample::duration).max();
 
-            // TODO: Make tuning be less influenced by early runs. Currently if
-            // early runs are very quick but later runs are slow, benchmarking
-            // will take a very long time.
-            //
-            // TODO: Make `sample_size` consider time generating inputs and
-            // dropping inputs/outputs. Currently benchmarks like
-            // `Bencher::bench_refs(String::clear)` take a very long time.
-            if current_mode.is_tune() {
-                // Clear previous smaller samples.
-                self.samples.clear();
-                self.counters.clear_input_counts();
-
-                // If within 100x timer precision, continue tuning.
-                let precision_multiple = slowest_time.picos / timer_precision.picos;
-                if precision_multiple <= 100 {
-                    current_mode = BenchMode::Tune { sample_size: sample_size * 2 };
+                #[cfg(feature = "tracing")]
+                {
+                    if let Some(duration) = sample_duration {
+                        sample_span.record("duration_ns", (duration.picos / 1_000) as u64);
+                    }
+                    drop(sample_span);
+                }
+
+                #[cfg(feature = "tracy")]
+                {
+                    drop(tracy_zone);
+                    tracy.end_sample(sample_duration);
+                }
+
+                waited_picos += call_sample_hook(self.shared_context.on_sample_end);
+
+                #[cfg(test)]
+                if is_test {
+                    // '--test' should run the expected number of times but not
+                    // allocate any samples.
+                    assert_eq!(raw_samples.capacity(), 0);
                 } else {
-                    current_mode = BenchMode::Collect { sample_size };
-                    rem_samples = Some(self.options.sample_count.unwrap_or(DEFAULT_SAMPLE_COUNT));
+                    assert_eq!(raw_samples.len(), thread_count);
                 }
-            }
 
-            // Account the sample duration for the per-sample benchmarking
-            // overhead.
-            let sub_sample_overhead = {
-                let overhead =
-                    self.shared_context.bench_overhead.picos.saturating_mul(sample_size as u128);
+                // If testing, exit the benchmarking loop immediately after timing a
+                // single run.
+                if is_test {
+                    break;
+                }
+
+                if is_warming_up {
+                    warmup_rem_samples = warmup_rem_samples.saturating_sub(1);
+
+                    let last_end = raw_samples.iter().map(|s| s.end).max().unwrap();
+                    let warmup_elapsed_picos = last_end
+                        .duration_since(warmup_start, timer)
+                        .picos
+                        .saturating_sub(waited_picos);
+
+                    is_warming_up = warmup_rem_samples > 0 || warmup_elapsed_picos < warmup_picos;
 
-                move |d: FineDuration| {
-                    FineDuration {
-                        picos: d.clamp_to(timer_precision).picos.saturating_sub(overhead),
+                    if !is_warming_up {
+                        waited_picos = 0;
+
+                        if initial_start.is_some() {
+                            initial_start = Some(Timestamp::start(timer_kind));
+                        }
                     }
-                    .clamp_to(timer_precision)
+
+                    continue;
                 }
-            };
 
-            if is_multi_thread {
-                // The total wall clock time spent over the current
-                // multi-threaded sample set.
-                let total_wall_time = {
-                    let first_start = raw_samples.iter().map(|s| s.start).min().unwrap();
-                    let last_end = raw_samples.iter().map(|s| s.end).max().unwrap();
-                    sub_sample_overhead(last_end.duration_since(first_start, timer))
+                let slowest_sample = raw_samples.iter().max_by_key(|s| s.duration()).unwrap();
+                let slowest_time = slowest_sample.duration();
+
+                // The whole sample body includes generating and dropping inputs
+                // and outputs.
+                let body_time = FineDuration {
+                    picos: body_end
+                        .duration_since(body_start, timer)
+                        .picos
+                        .saturating_sub(flush_picos),
                 };
 
-                self.samples.threads.push(ThreadSample { total_wall_time });
-            }
+                // Samples are discarded if taken with a different size than the
+                // next sample, since sizes cannot be mixed.
+                let mut keep_sample = true;
+
+                if current_mode.is_tune() {
+                    // Clear previous smaller samples.
+                    self.samples.clear();
+                    input_counts.iter_mut().for_each(Vec::clear);
+                    duration_sum = 0.0;
+                    duration_sq_sum = 0.0;
+
+                    let (new_size, is_tuned) = tuner.tune(sample_size, slowest_time, body_time);
+                    keep_sample = new_size == sample_size;
+
+                    if is_tuned {
+                        current_mode = BenchMode::Collect { sample_size: new_size };
+                        rem_samples = Some(sample_count);
+                    } else {
+                        current_mode = BenchMode::Tune { sample_size: new_size };
+                    }
+                } else if let Some(new_size) =
+                    tuner.check_drift(sample_size, slowest_time, body_time)
+                {
+                    // Samples drifted far from the tuned estimate, so restart
+                    // collecting with a better size.
+                    self.samples.clear();
+                    input_counts.iter_mut().for_each(Vec::clear);
+                    duration_sum = 0.0;
+                    duration_sq_sum = 0.0;
+
+                    keep_sample = false;
+                    current_mode = BenchMode::Collect { sample_size: new_size };
+                    rem_samples = Some(sample_count);
+                }
+
+                // Account the sample duration for the per-sample benchmarking
+                // overhead.
+                let sub_sample_overhead = {
+                    let overhead = loop_overhead.picos.saturating_mul(sample_size as u128);
 
-            for raw_sample in &raw_samples {
-                self.samples
-                    .all
-                    .push(Sample { duration: sub_sample_overhead(raw_sample.duration()) });
+                    move |d: FineDuration| {
+                        FineDuration {
+                            picos: d.clamp_to(timer_precision).picos.saturating_sub(overhead),
+                        }
+                        .clamp_to(timer_precision)
+                    }
+                };
+
+                if keep_sample && is_multi_thread {
+                    // The total wall clock time spent over the current
+                    // multi-threaded sample set.
+                    let total_wall_time = {
+                        let first_start = raw_samples.iter().map(|s| s.start).min().unwrap();
+                        let last_end = raw_samples.iter().map(|s| s.end).max().unwrap();
+                        sub_sample_overhead(last_end.duration_since(first_start, timer))
+                    };
 
-                // Insert per-input counter information.
-                for counter_kind in KnownCounterKind::ALL {
-                    if !self.counters.uses_input_counts(counter_kind) {
-                        continue;
+                    self.samples.push_thread_sample(ThreadSample { total_wall_time });
+                }
+
+                for raw_sample in raw_samples.iter().filter(|_| keep_sample) {
+                    let counter_totals = KnownCounterKind::ALL.map(|counter_kind| {
+                        if self.counters.uses_input_counts(counter_kind) {
+                            Some(raw_sample.counter_totals[counter_kind as usize])
+                        } else {
+                            let count = *self.counters.counts(counter_kind).first()?;
+                            Some(count.times(sample_size as u64))
+                        }
+                    });
+
+                    let duration = match raw_sample.custom_duration {
+                        // Reported durations do not include loop overhead.
+                        Some(duration) => duration.clamp_to(timer_precision),
+                        None => sub_sample_overhead(raw_sample.duration()),
+                    };
+
+                    let picos = duration.picos as f64;
+                    duration_sum += picos;
+                    duration_sq_sum += picos * picos;
+
+                    self.samples.push(Sample {
+                        duration,
+                        counter_totals,
+                        hw_counts: raw_sample.hw_counts,
+                        perf_counts: raw_sample.perf_counts,
+                        alloc_counts: raw_sample.alloc_counts,
+                        io_counts: raw_sample.io_counts,
+                    });
+
+                    if let Some(gen_duration) = raw_sample.gen_duration {
+                        *self.samples.gen_duration.get_or_insert_default() += gen_duration;
+                    }
+                    if let Some(drop_duration) = raw_sample.drop_duration {
+                        *self.samples.drop_duration.get_or_insert_default() += drop_duration;
                     }
 
-                    let total_count = raw_sample.counter_totals[counter_kind as usize];
+                    // Insert per-input counter information, unless aggregated by
+                    // streaming statistics.
+                    for counter_kind in KnownCounterKind::ALL {
+                        if !self.counters.uses_input_counts(counter_kind)
+                            || self.samples.is_streaming()
+                        {
+                            continue;
+                        }
+
+                        let total_count = raw_sample.counter_totals[counter_kind as usize];
 
-                    // Cannot overflow `MaxCountUInt` because `total_count`
-                    // cannot exceed `MaxCountUInt::MAX * sample_size`.
-                    let per_iter_count = (total_count / sample_size as u128) as MaxCountUInt;
+                        // Cannot overflow `MaxCountUInt` because `total_count`
+                        // cannot exceed `MaxCountUInt::MAX * sample_size`.
+                        let per_iter_count = total_count.per(sample_size as u64);
+
+                        input_counts[counter_kind as usize].push(per_iter_count);
+                    }
+
+                    if let Some(rem_samples) = &mut rem_samples {
+                        *rem_samples = rem_samples.saturating_sub(1);
+                    }
+                }
 
-                    self.counters.push_counter(AnyCounter::known(counter_kind, per_iter_count));
+                if let Some(initial_start) = initial_start {
+                    let last_end = raw_samples.iter().map(|s| s.end).max().unwrap();
+                    elapsed_picos = last_end
+                        .duration_since(initial_start, timer)
+                        .picos
+                        .saturating_sub(waited_picos);
+                } else {
+                    // Progress by at least 1ns to prevent extremely fast
+                    // functions from taking forever when `min_time` is set.
+                    let progress_picos = slowest_time.picos.max(1_000);
+                    elapsed_picos = elapsed_picos.saturating_add(progress_picos);
                 }
 
-                if let Some(rem_samples) = &mut rem_samples {
-                    *rem_samples = rem_samples.saturating_sub(1);
+                if is_status_shown {
+                    let count = self.samples.len();
+                    let mean = (count > 0).then(|| FineDuration {
+                        picos: (duration_sum / count as f64 / sample_size as f64) as u128,
+                    });
+                    crate::status::update(count, mean);
                 }
             }
+        });
 
-            if let Some(initial_start) = initial_start {
-                let last_end = raw_samples.iter().map(|s| s.end).max().unwrap();
-                elapsed_picos = last_end.duration_since(initial_start, timer).picos;
-            } else {
-                // Progress by at least 1ns to prevent extremely fast
-                // functions from taking forever when `min_time` is set.
-                let progress_picos = slowest_time.picos.max(1_000);
-                elapsed_picos = elapsed_picos.saturating_add(progress_picos);
+        for (counter_kind, counts) in KnownCounterKind::ALL.into_iter().zip(input_counts) {
+            for count in counts {
+                self.counters.push_count(counter_kind, count);
             }
         }
     }
 
     /// Returns a closure that takes the sample size and input counter, and then
-    /// returns a newly recorded sample.
-    fn sample_recorder<I, O>(
+    /// returns a newly recorded sample's timestamps, allocations, and drop time.
+    ///
+    /// All closures and the input counter are concrete types, so the sample
+    /// loop is monomorphized per benchmark and makes no indirect calls between
+    /// its timestamps. The `sample_loop` internal benchmarks measure the cost
+    /// this avoids.
+    #[allow(clippy::type_complexity)]
+    fn sample_recorder<S, I, O>(
         &self,
+        gen_state: impl Fn() -> S,
         gen_input: impl Fn() -> I,
-        benched: impl Fn(&UnsafeCell<MaybeUninit<I>>) -> O,
+        benched: impl Fn(&mut S, &UnsafeCell<MaybeUninit<I>>) -> O,
         drop_input: impl Fn(&UnsafeCell<MaybeUninit<I>>),
-    ) -> impl Fn(usize, Option<&Barrier>, &mut DeferStore<I, O>, &mut dyn FnMut(&I)) -> [Timestamp; 2]
+    ) -> impl Fn(usize, Option<&Barrier>, &mut DeferStore<I, O>, &mut InputCounter<I>) -> RecordedSample
     {
         // We defer:
-        // - Usage of `gen_input` values.
+        // - Usage of `gen_state` and `gen_input` values.
         // - Drop destructor for `O`, preventing it from affecting sample
         //   measurements. Outputs are stored into a pre-allocated buffer during
         //   the sample loop. The allocation is reused between samples to reduce
         //   time spent between samples.
 
-        let timer_kind = self.shared_context.timer.kind();
+        let timer = self.shared_context.timer;
+        let timer_kind = timer.kind();
+        let hw_counters = self.shared_context.hw_counters;
+        let perf_events = self.shared_context.perf_events.clone();
+        let io_counters = self.shared_context.io_counters;
+        let alloc_profiler = self.shared_context.alloc_profiler;
+        let callgrind_child = self.shared_context.callgrind == Some(CallgrindRole::Child);
+        let flush_cache = self.options.flush_cache.unwrap_or_default();
+        let unroll = self.options.unroll.unwrap_or(1);
+        let measure_gen = self.shared_context.measure_gen;
+        let measure_drop = self.shared_context.measure_drop;
 
         move |sample_size: usize,
               barrier: Option<&Barrier>,
               defer_store: &mut DeferStore<I, O>,
-              count_input: &mut dyn FnMut(&I)| {
+              input_counter: &mut InputCounter<I>| {
             // Ensures:
             // - All threads start the timed section simultaneously.
             // - Work external to the timed section does not affect the timing
@@ -827,6 +2138,14 @@ impl<'a> BenchContext<'a> {
                 }
             };
 
+            // Evicts inputs and any other benchmark data from CPU caches
+            // before the timed section.
+            let flush_cache = || {
+                if flush_cache {
+                    cache::flush();
+                }
+            };
+
             // The following logic chooses how to efficiently sample the
             // benchmark function once and assigns `sample_start`/`sample_end`
             // before/after the sample loop.
@@ -838,36 +2157,105 @@ impl<'a> BenchContext<'a> {
             let sample_start: UntaggedTimestamp;
             let sample_end: UntaggedTimestamp;
 
+            // Generating inputs and dropping outputs and inputs is only timed
+            // when reported, and happens outside of `sample_start` and
+            // `sample_end` so it never affects sample timing.
+            let gen_duration: Option<FineDuration>;
+            let drop_start: Option<Timestamp>;
+            let start_ext = |measure: bool| {
+                if measure {
+                    Some(Timestamp::start(timer_kind))
+                } else {
+                    None
+                }
+            };
+            let end_ext = |start: Option<Timestamp>| {
+                start.map(|start| Timestamp::start(timer_kind).duration_since(start, timer))
+            };
+
+            // Per-sample state is created before inputs and dropped after
+            // them, outside of the timed section.
+            let mut state = gen_state();
+
+            // Hardware counters, I/O counters, and allocation tracking start
+            // before the start timestamp and stop after the end timestamp to
+            // not affect sample timing. Callgrind only collects between the
+            // timestamps to exclude everything but the sample loop.
+            let alloc_snapshot = Cell::new(None::<AllocSnapshot>);
+            let alloc_counts = Cell::new(None::<AllocCounts>);
+            let io_snapshot = Cell::new(None::<IoSnapshot>);
+            let io_counts = Cell::new(None::<IoCounts>);
+
+            let start_sample = || {
+                if io_counters {
+                    io_snapshot.set(IoSnapshot::start().ok());
+                }
+                if alloc_profiler {
+                    alloc_snapshot.set(Some(AllocSnapshot::start()));
+                }
+                if hw_counters {
+                    crate::hw::start();
+                }
+                if !perf_events.is_empty() {
+                    crate::hw::start_perf(&perf_events);
+                }
+                let start = UntaggedTimestamp::start(timer_kind);
+                if callgrind_child {
+                    crate::callgrind::toggle_collect();
+                }
+                start
+            };
+            let end_sample = || {
+                if callgrind_child {
+                    crate::callgrind::toggle_collect();
+                }
+                let end = UntaggedTimestamp::end(timer_kind);
+                if !perf_events.is_empty() {
+                    crate::hw::stop_perf();
+                }
+                if hw_counters {
+                    crate::hw::stop();
+                }
+                alloc_counts.set(alloc_snapshot.take().map(AllocSnapshot::end));
+                io_counts.set(io_snapshot.take().and_then(IoSnapshot::end));
+                end
+            };
+
             if mem::size_of::<I>() == 0 && (mem::size_of::<O>() == 0 || !mem::needs_drop::<O>()) {
                 // Use a range instead of `defer_store` to make the benchmarking
                 // loop cheaper.
 
                 // Run `gen_input` the expected number of times in case it
-                // updates external state used by `benched`.
+                // updates external state used by `benched`. This is not timed
+                // since there are no inputs to generate.
+                gen_duration = None;
                 for _ in 0..sample_size {
                     let input = gen_input();
-                    count_input(&input);
+                    input_counter.count(&input);
 
                     // Inputs are consumed/dropped later.
                     mem::forget(input);
                 }
 
+                flush_cache();
+
                 sync_threads();
-                sample_start = UntaggedTimestamp::start(timer_kind);
+                sample_start = start_sample();
 
                 // Sample loop:
-                for _ in 0..sample_size {
+                unroll::repeat(sample_size, unroll, || {
                     // SAFETY: Input is a ZST, so we can construct one out of
                     // thin air.
                     let input = unsafe { UnsafeCell::new(MaybeUninit::<I>::zeroed()) };
 
-                    mem::forget(black_box(benched(&input)));
-                }
+                    mem::forget(black_box(benched(&mut state, &input)));
+                });
 
-                sample_end = UntaggedTimestamp::end(timer_kind);
+                sample_end = end_sample();
                 sync_threads();
 
                 // Drop outputs and inputs.
+                drop_start = start_ext(measure_drop);
                 for _ in 0..sample_size {
                     // Output only needs drop if ZST.
                     if mem::size_of::<O>() == 0 {
@@ -890,30 +2278,30 @@ impl<'a> BenchContext<'a> {
                     // loop by inserting it into `defer_store`.
                     Ok(defer_slots_slice) => {
                         // Initialize and store inputs.
+                        let gen_start = start_ext(measure_gen);
                         for DeferSlot { input, .. } in defer_slots_slice {
                             // SAFETY: We have exclusive access to `input`.
                             let input = unsafe { &mut *input.get() };
                             let input = input.write(gen_input());
-                            count_input(input);
+                            input_counter.count(input);
 
                             // Make input opaque to benchmarked function.
                             black_box(input);
                         }
+                        gen_duration = end_ext(gen_start);
 
-                        // Create iterator before the sample timing section to
-                        // reduce benchmarking overhead.
-                        let defer_slots_iter = defer_slots_slice.iter();
+                        flush_cache();
 
                         sync_threads();
-                        sample_start = UntaggedTimestamp::start(timer_kind);
+                        sample_start = start_sample();
 
                         // Sample loop:
-                        for defer_slot in defer_slots_iter {
+                        unroll::for_each(defer_slots_slice, unroll, |defer_slot| {
                             // SAFETY: All inputs in `defer_store` were
                             // initialized and we have exclusive access to the
                             // output slot.
                             unsafe {
-                                let output = benched(&defer_slot.input);
+                                let output = benched(&mut state, &defer_slot.input);
                                 *defer_slot.output.get() = MaybeUninit::new(output);
                             }
 
@@ -926,12 +2314,13 @@ impl<'a> BenchContext<'a> {
                             //   type like `String` since then it will write a
                             //   single word instead of three words.
                             _ = black_box(defer_slot);
-                        }
+                        });
 
-                        sample_end = UntaggedTimestamp::end(timer_kind);
+                        sample_end = end_sample();
                         sync_threads();
 
                         // Drop outputs and inputs.
+                        drop_start = start_ext(measure_drop);
                         for DeferSlot { input, output } in defer_slots_slice {
                             // SAFETY: All outputs were initialized in the
                             // sample loop and we have exclusive access.
@@ -948,34 +2337,35 @@ impl<'a> BenchContext<'a> {
                     // Output does not need to be dropped.
                     Err(defer_inputs_slice) => {
                         // Initialize and store inputs.
+                        let gen_start = start_ext(measure_gen);
                         for input in defer_inputs_slice {
                             // SAFETY: We have exclusive access to `input`.
                             let input = unsafe { &mut *input.get() };
                             let input = input.write(gen_input());
-                            count_input(input);
+                            input_counter.count(input);
 
                             // Make input opaque to benchmarked function.
                             black_box(input);
                         }
+                        gen_duration = end_ext(gen_start);
 
-                        // Create iterator before the sample timing section to
-                        // reduce benchmarking overhead.
-                        let defer_inputs_iter = defer_inputs_slice.iter();
+                        flush_cache();
 
                         sync_threads();
-                        sample_start = UntaggedTimestamp::start(timer_kind);
+                        sample_start = start_sample();
 
                         // Sample loop:
-                        for input in defer_inputs_iter {
+                        unroll::for_each(defer_inputs_slice, unroll, |input| {
                             // SAFETY: All inputs in `defer_store` were
                             // initialized.
-                            _ = black_box(unsafe { benched(input) });
-                        }
+                            _ = black_box(unsafe { benched(&mut state, input) });
+                        });
 
-                        sample_end = UntaggedTimestamp::end(timer_kind);
+                        sample_end = end_sample();
                         sync_threads();
 
                         // Drop inputs.
+                        drop_start = start_ext(measure_drop);
                         if mem::needs_drop::<I>() {
                             for input in defer_inputs_slice {
                                 // SAFETY: We have exclusive access to inputs.
@@ -986,10 +2376,97 @@ impl<'a> BenchContext<'a> {
                 }
             }
 
+            let drop_duration = end_ext(drop_start);
+
+            drop(state);
+
+            // SAFETY: These values are guaranteed to be the correct variant
+            // because they were created from the same `timer_kind`.
+            let timestamps = unsafe {
+                [sample_start.into_timestamp(timer_kind), sample_end.into_timestamp(timer_kind)]
+            };
+
+            RecordedSample {
+                timestamps,
+                custom_duration: None,
+                gen_duration,
+                drop_duration,
+                alloc_counts: alloc_counts.get(),
+                io_counts: io_counts.get(),
+            }
+        }
+    }
+
+    /// Returns a closure like `sample_recorder` for benchmarks that report the
+    /// duration of their iterations.
+    #[allow(clippy::type_complexity)]
+    fn custom_sample_recorder(
+        &self,
+        benched: impl Fn(u64) -> FineDuration,
+    ) -> impl Fn(usize, Option<&Barrier>, &mut DeferStore<(), ()>, &mut InputCounter<()>) -> RecordedSample
+    {
+        let timer_kind = self.shared_context.timer.kind();
+        let hw_counters = self.shared_context.hw_counters;
+        let perf_events = self.shared_context.perf_events.clone();
+        let io_counters = self.shared_context.io_counters;
+        let alloc_profiler = self.shared_context.alloc_profiler;
+        let callgrind_child = self.shared_context.callgrind == Some(CallgrindRole::Child);
+
+        move |sample_size: usize,
+              barrier: Option<&Barrier>,
+              _defer_store: &mut DeferStore<(), ()>,
+              _input_counter: &mut InputCounter<()>| {
+            if let Some(barrier) = barrier {
+                barrier.wait();
+            }
+
+            // Measurements are collected around the whole call, since the
+            // timed region is only known to `benched`.
+            let io_snapshot = if io_counters { IoSnapshot::start().ok() } else { None };
+            let alloc_snapshot = if alloc_profiler { Some(AllocSnapshot::start()) } else { None };
+            if hw_counters {
+                crate::hw::start();
+            }
+            if !perf_events.is_empty() {
+                crate::hw::start_perf(&perf_events);
+            }
+            let sample_start = UntaggedTimestamp::start(timer_kind);
+            if callgrind_child {
+                crate::callgrind::toggle_collect();
+            }
+
+            let duration = benched(sample_size as u64);
+
+            if callgrind_child {
+                crate::callgrind::toggle_collect();
+            }
+            let sample_end = UntaggedTimestamp::end(timer_kind);
+            if !perf_events.is_empty() {
+                crate::hw::stop_perf();
+            }
+            if hw_counters {
+                crate::hw::stop();
+            }
+            let alloc_counts = alloc_snapshot.map(AllocSnapshot::end);
+            let io_counts = io_snapshot.and_then(IoSnapshot::end);
+
+            if let Some(barrier) = barrier {
+                barrier.wait();
+            }
+
             // SAFETY: These values are guaranteed to be the correct variant
             // because they were created from the same `timer_kind`.
-            unsafe {
+            let timestamps = unsafe {
                 [sample_start.into_timestamp(timer_kind), sample_end.into_timestamp(timer_kind)]
+            };
+
+            RecordedSample {
+                timestamps,
+                custom_duration: Some(duration),
+                gen_duration: None,
+                drop_duration: None,
+                alloc_counts,
+                io_counts,
             }
         }
     }
@@ -1008,6 +2485,10 @@ impl<'a> BenchContext<'a> {
     pub fn compute_stats(&self) -> Stats {
         use crate::stats::StatsSet;
 
+        if let Some(streaming) = &self.samples.streaming {
+            return self.compute_streaming_stats(streaming);
+        }
+
         let samples = &self.samples.all;
         let sample_count = samples.len();
         let sample_size = self.samples.sample_size;
@@ -1031,7 +2512,7 @@ impl<'a> BenchContext<'a> {
         };
 
         let counter_count_for_sample =
-            |sample: &Sample, counter_kind: KnownCounterKind| -> Option<MaxCountUInt> {
+            |sample: &Sample, counter_kind: KnownCounterKind| -> Option<Count> {
                 let counts = self.counters.counts(counter_kind);
 
                 let index = if self.counters.uses_input_counts(counter_kind) {
@@ -1048,6 +2529,16 @@ impl<'a> BenchContext<'a> {
         let max_duration =
             sorted_samples.last().map(|s| s.duration / sample_size).unwrap_or_default();
 
+        let time_percentiles = crate::stats::TIME_PERCENTILES.map(|percentile| {
+            crate::stats::percentile_index(sorted_samples.len(), percentile)
+                .map(|index| sorted_samples[index].duration / sample_size)
+                .unwrap_or_default()
+        });
+
+        let sorted_durations: Vec<FineDuration> =
+            sorted_samples.iter().map(|s| s.duration).collect();
+        let per_iter = |duration: FineDuration| duration / sample_size;
+
         let median_duration = if median_samples.is_empty() {
             FineDuration::default()
         } else {
@@ -1056,17 +2547,14 @@ impl<'a> BenchContext<'a> {
         };
 
         let counts = KnownCounterKind::ALL.map(|counter_kind| {
-            let median: MaxCountUInt = {
-                let mut sum: u128 = 0;
+            let median: Count = {
+                let mut sum = CountTotal::ZERO;
 
                 for sample in median_samples {
-                    let sample_count = counter_count_for_sample(sample, counter_kind)? as u128;
-
-                    // Saturating add in case `MaxUIntCount > u64`.
-                    sum = sum.saturating_add(sample_count);
+                    sum += counter_count_for_sample(sample, counter_kind)?.into();
                 }
 
-                (sum / median_samples.len() as u128) as MaxCountUInt
+                sum.per(median_samples.len() as u64)
             };
 
             Some(StatsSet {
@@ -1081,6 +2569,9 @@ impl<'a> BenchContext<'a> {
             })
         });
 
+        let (time_std_dev, throughput_std_dev, custom_throughput_std_dev) =
+            self.spread_stats(samples);
+
         Stats {
             sample_count: sample_count as u32,
             iter_count: total_count,
@@ -1090,7 +2581,218 @@ impl<'a> BenchContext<'a> {
                 slowest: max_duration,
                 median: median_duration,
             },
+            time_percentiles,
+            counts,
+            custom_counts: self
+                .counters
+                .custom_counts()
+                .iter()
+                .map(|&(unit, count)| {
+                    (unit, StatsSet { fastest: count, slowest: count, median: count, mean: count })
+                })
+                .collect(),
+            time_std_dev,
+            run_std_dev: None,
+            trimmed_mean: crate::stats::trimmed_mean(&sorted_durations).map(per_iter),
+            winsorized_mean: crate::stats::winsorized_mean(&sorted_durations).map(per_iter),
+            time_mad: crate::stats::median_abs_dev(&sorted_durations).map(per_iter),
+            bimodality: crate::stats::detect_bimodality(&sorted_durations).map(|bimodality| {
+                Bimodality { modes: bimodality.modes.map(per_iter), ..bimodality }
+            }),
+            time_histogram: self.samples.histogram.clone(),
+            throughput_std_dev,
+            custom_throughput_std_dev,
+            hw_counts: self.samples.hw_counts_per_iter(),
+            perf_counts: self.perf_counts_per_iter(),
+            threads: self.samples.thread_stats(),
+            allocs: self.samples.alloc_stats(),
+            io_counts: self.samples.io_counts_per_iter(),
+            gen_time: self.samples.gen_time_per_iter(),
+            drop_time: self.samples.drop_time_per_iter(),
+        }
+    }
+
+    /// Computes the mean count of each of `--perf-events` per iteration, if
+    /// recorded for all samples.
+    fn perf_counts_per_iter(&self) -> Vec<(&'static str, f64)> {
+        let Some(counts) = self.samples.perf_counts_per_iter() else {
+            return Vec::new();
+        };
+
+        self.shared_context
+            .perf_events
+            .iter()
+            .zip(counts)
+            .map(|(event, count)| (event.name, count))
+            .collect()
+    }
+
+    /// Computes the standard deviation of time per iteration and of throughput
+    /// across `samples`.
+    #[allow(clippy::type_complexity)]
+    fn spread_stats(
+        &self,
+        samples: &[Sample],
+    ) -> (Option<FineDuration>, [Option<f64>; KnownCounterKind::COUNT], Vec<(&'static str, f64)>)
+    {
+        let sample_size = self.samples.sample_size;
+
+        let time_std_dev = crate::stats::std_dev(
+            samples.iter().map(|sample| sample.duration.picos as f64 / sample_size as f64),
+        )
+        .map(|picos| FineDuration { picos: picos.round() as u128 });
+
+        // Per-sample throughput of samples that took measurable time.
+        let throughputs = |count: &dyn Fn(&Sample) -> Option<CountTotal>| {
+            crate::stats::std_dev(samples.iter().filter(|s| !s.duration.is_zero()).map_while(
+                |sample| Some(count(sample)?.as_f64() * 1e12 / sample.duration.picos as f64),
+            ))
+        };
+
+        let throughput_std_dev = KnownCounterKind::ALL.map(|counter_kind| {
+            throughputs(&|sample| sample.counter_totals[counter_kind as usize])
+        });
+
+        let custom_throughput_std_dev = (self.counters.custom_counts().iter())
+            .filter_map(|&(unit, count)| {
+                Some((unit, throughputs(&|_| Some(count.times(sample_size as u64)))?))
+            })
+            .collect();
+
+        (time_std_dev, throughput_std_dev, custom_throughput_std_dev)
+    }
+
+    /// Computes statistics from streaming aggregates.
+    ///
+    /// Counts of the median sample are not known, so the mean count is used.
+    fn compute_streaming_stats(&self, streaming: &StreamingStats) -> Stats {
+        use crate::stats::StatsSet;
+
+        let sample_size = self.samples.sample_size;
+        let total_count = self.samples.iter_count();
+
+        let per_iter_count = |total: CountTotal| -> Count { total.per(sample_size as u64) };
+
+        let counts = KnownCounterKind::ALL.map(|counter_kind| {
+            let index = counter_kind as usize;
+            let extreme_count = |extreme: Option<SampleExtreme>| -> Option<Count> {
+                extreme?.counter_totals[index].map(per_iter_count)
+            };
+
+            let total = streaming.counter_totals[index]?;
+            let mean = total.per(total_count);
+
+            Some(StatsSet {
+                fastest: extreme_count(streaming.fastest)?,
+                slowest: extreme_count(streaming.slowest)?,
+                median: mean,
+                mean,
+            })
+        });
+
+        let per_iter = |duration: FineDuration| duration / sample_size;
+
+        // The histogram has every sample, unlike the reservoir.
+        let (median, time_percentiles) = match &self.samples.histogram {
+            Some(histogram) => (
+                histogram.percentile(50),
+                TIME_PERCENTILES.map(|percentile| histogram.percentile(percentile)),
+            ),
+            None => {
+                let (median, time_percentiles) = streaming.quantile_durations();
+                (per_iter(median), time_percentiles.map(per_iter))
+            }
+        };
+
+        // The reservoir preserves the shape of the sample distribution.
+        let (time_std_dev, throughput_std_dev, custom_throughput_std_dev) =
+            self.spread_stats(&streaming.reservoir);
+
+        let mut reservoir_durations: Vec<FineDuration> =
+            streaming.reservoir.iter().map(|s| s.duration).collect();
+        reservoir_durations.sort_unstable();
+
+        Stats {
+            sample_count: streaming.count as u32,
+            iter_count: total_count,
+            time: StatsSet {
+                mean: FineDuration {
+                    picos: streaming
+                        .total_duration
+                        .picos
+                        .checked_div(total_count as u128)
+                        .unwrap_or_default(),
+                },
+                fastest: streaming.fastest.map(|s| per_iter(s.duration)).unwrap_or_default(),
+                slowest: streaming.slowest.map(|s| per_iter(s.duration)).unwrap_or_default(),
+                median,
+            },
+            time_percentiles,
             counts,
+            custom_counts: self
+                .counters
+                .custom_counts()
+                .iter()
+                .map(|&(unit, count)| {
+                    (unit, StatsSet { fastest: count, slowest: count, median: count, mean: count })
+                })
+                .collect(),
+            time_std_dev,
+            run_std_dev: None,
+            trimmed_mean: crate::stats::trimmed_mean(&reservoir_durations).map(per_iter),
+            winsorized_mean: crate::stats::winsorized_mean(&reservoir_durations).map(per_iter),
+            time_mad: crate::stats::median_abs_dev(&reservoir_durations).map(per_iter),
+            bimodality: crate::stats::detect_bimodality(&reservoir_durations).map(|bimodality| {
+                Bimodality { modes: bimodality.modes.map(per_iter), ..bimodality }
+            }),
+            time_histogram: self.samples.histogram.clone(),
+            throughput_std_dev,
+            custom_throughput_std_dev,
+            hw_counts: self.samples.hw_counts_per_iter(),
+            perf_counts: self.perf_counts_per_iter(),
+            threads: streaming.thread_stats(sample_size),
+            allocs: self.samples.alloc_stats(),
+            io_counts: self.samples.io_counts_per_iter(),
+            gen_time: self.samples.gen_time_per_iter(),
+            drop_time: self.samples.drop_time_per_iter(),
         }
     }
 }
+
+/// Benchmarks the sample loop by running an empty benchmark with a fixed number
+/// of samples and iterations.
+#[cfg(feature = "internal_benches")]
+mod sample_loop {
+    use super::*;
+    use crate::{config::Action, time::Timer};
+
+    const SAMPLE_COUNT: u32 = 100;
+    const SAMPLE_SIZE: u32 = 1_000;
+
+    fn run(bench: impl FnOnce(Bencher)) {
+        let shared_context = SharedContext::test_default(Action::Bench, Timer::Os);
+        let options = BenchOptions {
+            sample_count: Some(SAMPLE_COUNT),
+            sample_size: Some(SAMPLE_SIZE),
+            ..BenchOptions::default()
+        };
+
+        let mut context = BenchContext::new(&shared_context, &options, NonZeroUsize::MIN);
+        bench(Bencher::new(&mut context));
+    }
+
+    /// The benchmarked closure is called directly, because the sample loop is
+    /// monomorphized over it.
+    #[crate::bench(crate = crate)]
+    fn direct() {
+        run(|bencher| bencher.bench_local(|| {}));
+    }
+
+    /// The benchmarked closure is called through `dyn Fn`, which is the cost
+    /// of an indirect call within the sample loop.
+    #[crate::bench(crate = crate)]
+    fn dyn_fn() {
+        let benched: &dyn Fn() = crate::black_box(&|| {});
+        run(|bencher| bencher.bench_local(benched));
+    }
+}
@@ -1,9 +1,16 @@
 use std::time::Duration;
 
-use crate::{counter::CounterSet, time::FineDuration};
+use crate::{
+    counter::{BytesFormat, CounterSet},
+    time::FineDuration,
+};
 
 /// Benchmarking options set directly by the user in `#[divan::bench]` and
-/// `#[divan::bench_group]`.
+/// `#[divan::bench_group]`, or passed to [`Divan::add_bench`] and
+/// [`Divan::add_group`].
+///
+/// [`Divan::add_bench`]: crate::Divan::add_bench
+/// [`Divan::add_group`]: crate::Divan::add_group
 ///
 /// Changes to fields must be reflected in the "Options" sections of the docs
 /// for `#[divan::bench]` and `#[divan::bench_group]`.
@@ -15,6 +22,16 @@ pub struct BenchOptions {
     /// The number of iterations inside a single sample.
     pub sample_size: Option<u32>,
 
+    /// The wall-clock time that each sample should take, from which
+    /// `sample_size` is chosen.
+    pub sample_time: Option<Duration>,
+
+    /// The coefficient of variation, as a percentage, below which samples are
+    /// considered stable enough to stop collecting.
+    ///
+    /// When set, `sample_count` is the minimum number of samples.
+    pub until_stable: Option<f64>,
+
     /// The number of threads to benchmark the sample. This is 1 by default.
     ///
     /// If set to 0, this will use [`std::thread::available_parallelism`].
@@ -27,22 +44,76 @@ pub struct BenchOptions {
     /// function.
     pub counters: CounterSet,
 
+    /// The numerical base for bytes in outputs: decimal (MB/s) or binary
+    /// (MiB/s).
+    pub bytes_format: Option<BytesFormat>,
+
     /// The time floor for benchmarking a function.
     pub min_time: Option<Duration>,
 
     /// The time ceiling for benchmarking a function.
     pub max_time: Option<Duration>,
 
+    /// The maximum mean time per iteration before the run is considered
+    /// failed.
+    pub max_time_mean: Option<Duration>,
+
+    /// The wall-clock time after which a benchmark is stopped and reported as
+    /// timed out.
+    pub timeout: Option<Duration>,
+
+    /// The time spent running the benchmarked function before recording
+    /// samples.
+    pub warmup_time: Option<Duration>,
+
+    /// The number of samples run and discarded before recording samples.
+    pub warmup_samples: Option<u32>,
+
     /// When accounting for `min_time` or `max_time`, skip time external to
     /// benchmarked functions, such as time spent generating inputs and running
     /// [`Drop`].
     pub skip_ext_time: Option<bool>,
 
+    /// Whether statistics are computed incrementally instead of storing every
+    /// sample, which bounds memory for long runs.
+    ///
+    /// If unset, this is enabled once there are very many samples.
+    pub streaming_stats: Option<bool>,
+
+    /// The number of randomly chosen samples kept by streaming statistics for
+    /// computing percentiles and reporting individual samples.
+    ///
+    /// If set to 0, percentiles are estimated without keeping samples.
+    pub reservoir_size: Option<u32>,
+
+    /// Whether CPU caches are flushed before each sample, for measuring
+    /// cold-cache behavior.
+    pub flush_cache: Option<bool>,
+
+    /// The number of times the benchmarked function is called per iteration of
+    /// the sample loop, for reducing loop overhead of extremely fast functions.
+    ///
+    /// This is rounded down to a power of two no greater than 16.
+    pub unroll: Option<u32>,
+
+    /// Whether `min_time` and `max_time` set on a group apply to the group as a
+    /// whole, divided evenly among its benchmarks.
+    ///
+    /// This is only set by `#[divan::bench_group]` and does not cascade into
+    /// child groups.
+    pub time_budget: Option<bool>,
+
     /// Whether the benchmark should be ignored.
     ///
     /// This may be set within the attribute or with a separate
     /// [`#[ignore]`](https://doc.rust-lang.org/reference/attributes/testing.html#the-ignore-attribute).
     pub ignore: Option<bool>,
+
+    /// Why the benchmark is ignored, which is shown in place of its results.
+    ///
+    /// This is set by `ignore = "reason"` within the attribute or by a
+    /// separate `#[ignore = "reason"]`.
+    pub ignore_reason: Option<&'static str>,
 }
 
 impl BenchOptions {
@@ -53,11 +124,24 @@ impl BenchOptions {
             // `Copy` values:
             sample_count: self.sample_count.or(other.sample_count),
             sample_size: self.sample_size.or(other.sample_size),
+            sample_time: self.sample_time.or(other.sample_time),
+            until_stable: self.until_stable.or(other.until_stable),
             threads: self.threads.or(other.threads),
+            bytes_format: self.bytes_format.or(other.bytes_format),
             min_time: self.min_time.or(other.min_time),
             max_time: self.max_time.or(other.max_time),
+            max_time_mean: self.max_time_mean.or(other.max_time_mean),
+            timeout: self.timeout.or(other.timeout),
+            warmup_time: self.warmup_time.or(other.warmup_time),
+            warmup_samples: self.warmup_samples.or(other.warmup_samples),
             skip_ext_time: self.skip_ext_time.or(other.skip_ext_time),
+            streaming_stats: self.streaming_stats.or(other.streaming_stats),
+            reservoir_size: self.reservoir_size.or(other.reservoir_size),
+            flush_cache: self.flush_cache.or(other.flush_cache),
+            unroll: self.unroll.or(other.unroll),
+            time_budget: self.time_budget,
             ignore: self.ignore.or(other.ignore),
+            ignore_reason: self.ignore_reason.or(other.ignore_reason),
 
             // `Clone` values:
             counters: self.counters.overwrite(&other.counters),
@@ -79,4 +163,9 @@ impl BenchOptions {
     pub(crate) fn max_time(&self) -> FineDuration {
         self.max_time.map(FineDuration::from).unwrap_or(FineDuration::MAX)
     }
+
+    #[inline]
+    pub(crate) fn warmup_time(&self) -> FineDuration {
+        self.warmup_time.map(FineDuration::from).unwrap_or_default()
+    }
 }
@@ -0,0 +1,112 @@
+//! Calibrating the sample loop overhead of each benchmark.
+//!
+//! The global `bench_overhead` is measured with a loop over integers, but a
+//! benchmark's sample loop also iterates its deferred inputs and stores each
+//! output, which costs more for larger types. Running the same loop with an
+//! empty body gives the overhead to subtract from that benchmark's samples.
+//!
+//! Samples hold at most [`MAX_SAMPLE_BYTES`] of inputs and outputs, so
+//! calibrating benchmarks with large types does not allocate and zero-fill
+//! large buffers.
+
+use std::mem::{self, MaybeUninit};
+
+use crate::{
+    bench::{
+        defer::{DeferSlot, DeferStore},
+        unroll, BenchOptions,
+    },
+    black_box,
+    divan::SharedContext,
+    time::{FineDuration, UntaggedTimestamp},
+};
+
+/// The number of calibration samples, of which the fastest is used.
+const SAMPLE_COUNT: usize = 100;
+
+/// The maximum number of iterations in each calibration sample.
+const MAX_SAMPLE_SIZE: usize = 1_000;
+
+/// The maximum number of bytes of inputs and outputs in each calibration
+/// sample.
+const MAX_SAMPLE_BYTES: usize = 1 << 20;
+
+/// Returns the number of iterations in each calibration sample for inputs `I`
+/// and outputs `O`, which is at least 1.
+pub(crate) fn sample_size<I, O>() -> usize {
+    let slot_size = mem::size_of::<DeferSlot<I, O>>().max(1);
+    (MAX_SAMPLE_BYTES / slot_size).clamp(1, MAX_SAMPLE_SIZE)
+}
+
+/// Measures the per-iteration overhead of the sample loop for inputs `I` and
+/// outputs `O`, or returns the global `bench_overhead` if that is zero, such as
+/// when not benchmarking.
+pub(crate) fn measure<I, O>(
+    shared_context: &SharedContext,
+    options: &BenchOptions,
+) -> FineDuration {
+    let bench_overhead = shared_context.bench_overhead;
+    if bench_overhead.is_zero() {
+        return bench_overhead;
+    }
+
+    let timer = shared_context.timer;
+    let timer_kind = timer.kind();
+    let unroll = options.unroll.unwrap_or(1);
+    let sample_size = sample_size::<I, O>();
+
+    let mut defer_store = DeferStore::<I, O>::default();
+
+    // The minimum non-zero sample.
+    let mut min_sample = FineDuration::default();
+
+    for _ in 0..SAMPLE_COUNT {
+        let start: UntaggedTimestamp;
+        let end: UntaggedTimestamp;
+
+        // Each loop mirrors the corresponding loop in `sample_recorder`, with
+        // zeroed outputs in place of calling the benchmarked function.
+        if mem::size_of::<I>() == 0 && (mem::size_of::<O>() == 0 || !mem::needs_drop::<O>()) {
+            start = UntaggedTimestamp::start(timer_kind);
+            unroll::repeat(sample_size, unroll, || {
+                _ = black_box(MaybeUninit::<O>::zeroed());
+            });
+            end = UntaggedTimestamp::end(timer_kind);
+        } else {
+            defer_store.prepare(sample_size);
+
+            match defer_store.slots() {
+                Ok(defer_slots_slice) => {
+                    start = UntaggedTimestamp::start(timer_kind);
+                    unroll::for_each(defer_slots_slice, unroll, |defer_slot| {
+                        // SAFETY: We have exclusive access to the output slot,
+                        // which is never read or dropped.
+                        unsafe { *defer_slot.output.get() = MaybeUninit::zeroed() }
+                        _ = black_box(defer_slot);
+                    });
+                    end = UntaggedTimestamp::end(timer_kind);
+                }
+
+                Err(defer_inputs_slice) => {
+                    start = UntaggedTimestamp::start(timer_kind);
+                    unroll::for_each(defer_inputs_slice, unroll, |_| {
+                        _ = black_box(MaybeUninit::<O>::zeroed());
+                    });
+                    end = UntaggedTimestamp::end(timer_kind);
+                }
+            }
+        }
+
+        // SAFETY: These values are guaranteed to be the correct variant
+        // because they were created from the same `timer_kind`.
+        let [start, end] =
+            unsafe { [start.into_timestamp(timer_kind), end.into_timestamp(timer_kind)] };
+
+        let mut sample = end.duration_since(start, timer);
+        sample.picos /= sample_size as u128;
+
+        min_sample = min_sample.clamp_to_min(sample);
+    }
+
+    min_sample
+}
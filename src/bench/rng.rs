@@ -0,0 +1,74 @@
+use std::fmt;
+
+use crate::util::Rng;
+
+/// Random number generator for inputs created by
+/// [`Bencher::with_rng_inputs`](crate::Bencher::with_rng_inputs).
+///
+/// Each input gets its own generator derived from the run's seed and the
+/// input's index, so inputs are reproducible across runs and machines when the
+/// seed is set with `--seed`.
+///
+/// This implements [`rand_core::RngCore`], so it can be used with
+/// [`rand::Rng`](https://docs.rs/rand/0.8/rand/trait.Rng.html) methods.
+pub struct BenchRng {
+    rng: Rng,
+}
+
+impl fmt::Debug for BenchRng {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BenchRng").finish_non_exhaustive()
+    }
+}
+
+impl BenchRng {
+    /// Creates a generator for the input at `index`.
+    #[inline]
+    pub(crate) fn new(seed: u64, index: u64) -> Self {
+        // Mixing the index prevents nearby inputs from having correlated
+        // sequences.
+        Self { rng: Rng::new(seed ^ Rng::new(index).next_u64()) }
+    }
+}
+
+impl rand_core::RngCore for BenchRng {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        (self.rng.next_u64() >> 32) as u32
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest)
+    }
+
+    #[inline]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::RngCore;
+
+    use super::*;
+
+    #[test]
+    fn reproducible() {
+        let sequence = |seed: u64, index: u64| {
+            let mut rng = BenchRng::new(seed, index);
+            [rng.next_u64(), rng.next_u64(), rng.next_u64()]
+        };
+
+        assert_eq!(sequence(1, 0), sequence(1, 0));
+        assert_ne!(sequence(1, 0), sequence(1, 1));
+        assert_ne!(sequence(1, 0), sequence(2, 0));
+    }
+}
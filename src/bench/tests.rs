@@ -3,7 +3,10 @@
 
 use std::{
     collections::HashSet,
-    sync::atomic::{AtomicUsize, Ordering::SeqCst},
+    sync::{
+        atomic::{AtomicUsize, Ordering::SeqCst},
+        Mutex,
+    },
 };
 
 use super::*;
@@ -41,8 +44,7 @@ fn test_bencher(test: &mut dyn FnMut(Bencher)) {
 
     for timer in Timer::available() {
         for action in [Action::Bench, Action::Test] {
-            let shared_context =
-                SharedContext { action, timer, bench_overhead: FineDuration::default() };
+            let shared_context = SharedContext::test_default(action, timer);
 
             for &thread_count in THREAD_COUNTS {
                 let mut bench_context = BenchContext::new(
@@ -74,6 +76,13 @@ fn test_bencher(test: &mut dyn FnMut(Bencher)) {
                         "Thread sample count must be a multiple of total sample count"
                     );
                 }
+
+                if action.is_bench() && thread_count > 1 {
+                    let thread_stats = samples.thread_stats().unwrap();
+                    assert_eq!(thread_stats.thread_count(), thread_count);
+                } else {
+                    assert!(samples.thread_stats().is_none());
+                }
             }
         }
     }
@@ -550,3 +559,768 @@ mod zst_input {
         assert_eq!(ZST_COUNT.load(SeqCst), 0);
     }
 }
+
+mod async_bench {
+    use super::*;
+
+    #[test]
+    fn no_input() {
+        test_bencher(&mut |b| b.bench_async(|| async { make_string() }));
+    }
+
+    #[test]
+    fn string_input() {
+        test_bencher(&mut |b| {
+            b.with_inputs(make_string).bench_values_async(|s| async move { s.to_ascii_uppercase() })
+        });
+    }
+
+    #[test]
+    fn zst_input() {
+        struct DroppedZst;
+
+        static ZST_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        impl Drop for DroppedZst {
+            fn drop(&mut self) {
+                ZST_COUNT.fetch_sub(1, SeqCst);
+            }
+        }
+
+        test_bencher(&mut |b| {
+            b.with_inputs(|| {
+                ZST_COUNT.fetch_add(1, SeqCst);
+                DroppedZst
+            })
+            .bench_values_async(|zst| async move { black_box(zst) });
+        });
+
+        assert_eq!(ZST_COUNT.load(SeqCst), 0);
+    }
+}
+
+/// Tests that warm-up samples run the benchmarked function but are not
+/// recorded.
+#[test]
+fn warmup_samples() {
+    const WARMUP_SAMPLES: u32 = 2;
+
+    let bench_options = BenchOptions {
+        sample_count: Some(SAMPLE_COUNT),
+        sample_size: Some(SAMPLE_SIZE),
+        warmup_samples: Some(WARMUP_SAMPLES),
+        ..BenchOptions::default()
+    };
+
+    for timer in Timer::available() {
+        for action in [Action::Bench, Action::Test] {
+            let shared_context = SharedContext::test_default(action, timer);
+
+            let mut bench_context =
+                BenchContext::new(&shared_context, &bench_options, NonZeroUsize::MIN);
+
+            let run_count = AtomicUsize::new(0);
+            Bencher::new(&mut bench_context).bench(|| {
+                run_count.fetch_add(1, SeqCst);
+            });
+
+            let run_count = run_count.into_inner() as u32;
+
+            if action.is_test() {
+                // '--test' should not warm up.
+                assert_eq!(run_count, 1);
+            } else {
+                assert_eq!(run_count, (WARMUP_SAMPLES + SAMPLE_COUNT) * SAMPLE_SIZE);
+                assert_eq!(bench_context.samples.all.len(), SAMPLE_COUNT as usize);
+            }
+        }
+    }
+}
+
+/// Tests that `until_stable` treats the sample count as a minimum and stops once
+/// samples are stable.
+#[test]
+fn until_stable() {
+    let shared_context = SharedContext::test_default(Action::Bench, Timer::Os);
+
+    // The coefficient of variation of `n` positive samples cannot exceed
+    // `sqrt(n - 1) * 100%`, so this threshold is always met.
+    for (sample_count, expected) in
+        [(None, DEFAULT_STABLE_SAMPLE_COUNT), (Some(SAMPLE_COUNT), SAMPLE_COUNT)]
+    {
+        let bench_options = BenchOptions {
+            sample_count,
+            sample_size: Some(SAMPLE_SIZE),
+            until_stable: Some(1000.0),
+            ..BenchOptions::default()
+        };
+
+        let mut bench_context =
+            BenchContext::new(&shared_context, &bench_options, NonZeroUsize::MIN);
+
+        Bencher::new(&mut bench_context).bench(|| black_box(1) + 1);
+
+        assert_eq!(bench_context.samples.all.len(), expected as usize);
+    }
+}
+
+/// Tests that `sample_time` chooses a sample size for samples to take roughly
+/// the requested time.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn sample_time() {
+    let shared_context = SharedContext::test_default(Action::Bench, Timer::Os);
+
+    let bench_options = BenchOptions {
+        sample_count: Some(SAMPLE_COUNT),
+        sample_time: Some(Duration::from_millis(1)),
+        ..BenchOptions::default()
+    };
+
+    let mut bench_context = BenchContext::new(&shared_context, &bench_options, NonZeroUsize::MIN);
+
+    Bencher::new(&mut bench_context).bench(|| black_box(1) + 1);
+
+    let samples = &bench_context.samples;
+    assert_eq!(samples.all.len(), SAMPLE_COUNT as usize);
+    assert!(samples.sample_size > 1);
+
+    // Allow for a very loose bound since timing is unreliable.
+    let total: u128 = samples.all.iter().map(|sample| sample.duration.picos).sum();
+    assert!(total / SAMPLE_COUNT as u128 >= FineDuration::from(Duration::from_micros(100)).picos);
+}
+
+/// Tests that samples record counter totals over all of their iterations.
+#[test]
+fn sample_counter_totals() {
+    use crate::counter::{BytesCount, ItemsCount};
+
+    let bench_options = BenchOptions {
+        sample_count: Some(SAMPLE_COUNT),
+        sample_size: Some(SAMPLE_SIZE),
+        ..BenchOptions::default()
+    };
+
+    let shared_context = SharedContext::test_default(Action::Bench, Timer::Os);
+
+    let mut bench_context = BenchContext::new(&shared_context, &bench_options, NonZeroUsize::MIN);
+
+    let input_len = AtomicUsize::new(0);
+    Bencher::new(&mut bench_context)
+        .counter(ItemsCount::new(3usize))
+        .with_inputs(|| "a".repeat(input_len.fetch_add(1, SeqCst)))
+        .input_counter(BytesCount::of_str)
+        .bench_refs(|s| black_box(s.len()));
+
+    assert_eq!(bench_context.samples.all.len(), SAMPLE_COUNT as usize);
+
+    // Inputs are generated for every iteration, so each sample's inputs have
+    // consecutive lengths.
+    let mut next_len = 0;
+    for sample in &bench_context.samples.all {
+        let input_bytes: usize = (next_len..next_len + SAMPLE_SIZE as usize).sum();
+        next_len += SAMPLE_SIZE as usize;
+
+        let totals = sample.counter_totals;
+        assert_eq!(
+            totals[KnownCounterKind::Bytes as usize],
+            Some(CountTotal::Int(input_bytes as u128))
+        );
+        assert_eq!(totals[KnownCounterKind::Chars as usize], None);
+        assert_eq!(
+            totals[KnownCounterKind::Items as usize],
+            Some(CountTotal::Int(3 * SAMPLE_SIZE as u128))
+        );
+    }
+}
+
+/// Tests that fractional counts are not rounded when computing stats.
+#[test]
+fn fractional_counts() {
+    use crate::counter::{CustomCount, ItemsCount};
+
+    let bench_options = BenchOptions {
+        sample_count: Some(SAMPLE_COUNT),
+        sample_size: Some(SAMPLE_SIZE),
+        ..BenchOptions::default()
+    };
+
+    let shared_context = SharedContext::test_default(Action::Bench, Timer::Os);
+
+    let mut bench_context = BenchContext::new(&shared_context, &bench_options, NonZeroUsize::MIN);
+
+    Bencher::new(&mut bench_context)
+        .counter(CustomCount::from_f64("GFLOP", 0.25))
+        .with_inputs(|| 3usize)
+        .input_counter(|&n| ItemsCount::from_f64(n as f64 / 2.0))
+        .bench_refs(|n| black_box(*n));
+
+    let stats = bench_context.compute_stats();
+
+    let items = stats.items().unwrap();
+    assert_eq!(items.mean, 1.5);
+    assert_eq!(items.median, 1.5);
+
+    let (unit, gflops) = stats.custom_counts().next().unwrap();
+    assert_eq!(unit, "GFLOP");
+    assert_eq!(gflops.mean, 0.25);
+}
+
+/// Tests that `streaming_stats` aggregates samples instead of storing them,
+/// with the same exact statistics as stored samples.
+#[test]
+fn streaming_stats() {
+    use crate::counter::{BytesCount, ItemsCount};
+
+    let shared_context = SharedContext::test_default(Action::Bench, Timer::Os);
+
+    let [stored, streamed] = [false, true].map(|streaming_stats| {
+        let bench_options = BenchOptions {
+            sample_count: Some(SAMPLE_COUNT),
+            sample_size: Some(SAMPLE_SIZE),
+            streaming_stats: Some(streaming_stats),
+            ..BenchOptions::default()
+        };
+
+        let mut bench_context =
+            BenchContext::new(&shared_context, &bench_options, NonZeroUsize::MIN);
+
+        let input_len = AtomicUsize::new(0);
+        Bencher::new(&mut bench_context)
+            .counter(ItemsCount::new(3usize))
+            .with_inputs(|| "a".repeat(input_len.fetch_add(1, SeqCst)))
+            .input_counter(BytesCount::of_str)
+            .bench_refs(|s| black_box(s.len()));
+
+        let samples = &bench_context.samples;
+        assert_eq!(samples.is_streaming(), streaming_stats);
+        assert_eq!(samples.len(), SAMPLE_COUNT as usize);
+        if streaming_stats {
+            assert!(samples.all.is_empty());
+        }
+
+        bench_context.compute_stats()
+    });
+
+    assert_eq!(streamed.sample_count, stored.sample_count);
+    assert_eq!(streamed.iter_count, stored.iter_count);
+
+    let get_mean = |stats: &Stats, kind: KnownCounterKind| stats.get_counts(kind).map(|c| c.mean);
+    for kind in KnownCounterKind::ALL {
+        assert_eq!(get_mean(&streamed, kind), get_mean(&stored, kind));
+    }
+}
+
+/// Tests that `streaming_stats` still computes per-thread statistics.
+#[test]
+fn streaming_thread_stats() {
+    let shared_context = SharedContext::test_default(Action::Bench, Timer::Os);
+
+    let bench_options = BenchOptions {
+        sample_count: Some(SAMPLE_COUNT),
+        sample_size: Some(SAMPLE_SIZE),
+        streaming_stats: Some(true),
+        ..BenchOptions::default()
+    };
+
+    let thread_count = NonZeroUsize::new(2).unwrap();
+    let mut bench_context = BenchContext::new(&shared_context, &bench_options, thread_count);
+
+    Bencher::new(&mut bench_context).with_inputs(make_string).bench_refs(|s| black_box(s.len()));
+
+    assert!(bench_context.samples.is_streaming());
+    assert!(bench_context.samples.threads.is_empty());
+
+    let stats = bench_context.compute_stats();
+    let thread_stats = stats.threads.unwrap();
+    assert_eq!(thread_stats.thread_count(), 2);
+
+    for time in thread_stats.per_thread.iter().chain(Some(&thread_stats.wall_time)) {
+        assert!(time.fastest <= time.mean && time.mean <= time.slowest);
+        assert!(time.fastest <= time.median && time.median <= time.slowest);
+    }
+}
+
+/// Tests that `flush_cache` flushes outside of the timed section without
+/// affecting the number of samples or iterations.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn flush_cache() {
+    let shared_context = SharedContext::test_default(Action::Bench, Timer::Os);
+
+    let bench_options = BenchOptions {
+        sample_count: Some(10),
+        sample_size: Some(SAMPLE_SIZE),
+        flush_cache: Some(true),
+        ..BenchOptions::default()
+    };
+
+    let mut bench_context = BenchContext::new(&shared_context, &bench_options, NonZeroUsize::MIN);
+
+    Bencher::new(&mut bench_context).with_inputs(make_string).bench_refs(|s| black_box(s.len()));
+
+    let stats = bench_context.compute_stats();
+    assert_eq!(stats.sample_count, 10);
+    assert_eq!(stats.iter_count, 10 * SAMPLE_SIZE as u64);
+}
+
+/// Tests that `unroll` calls the benchmarked function once per iteration,
+/// including sample sizes that are not a multiple of the unroll factor.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn unroll() {
+    let shared_context = SharedContext::test_default(Action::Bench, Timer::Os);
+
+    let bench_options = BenchOptions {
+        sample_count: Some(10),
+        sample_size: Some(7),
+        unroll: Some(4),
+        ..BenchOptions::default()
+    };
+
+    let expected_calls = 10 * 7;
+
+    let new_context = || BenchContext::new(&shared_context, &bench_options, NonZeroUsize::MIN);
+
+    // No inputs or outputs.
+    let calls = Cell::new(0);
+    let mut bench_context = new_context();
+    Bencher::new(&mut bench_context).bench_local(|| calls.set(calls.get() + 1));
+    assert_eq!(calls.get(), expected_calls);
+    assert_eq!(bench_context.compute_stats().iter_count, expected_calls as u64);
+
+    // Outputs that need to be dropped.
+    let calls = Cell::new(0);
+    let mut bench_context = new_context();
+    Bencher::new(&mut bench_context).with_inputs(make_string).bench_local_refs(|s| {
+        calls.set(calls.get() + 1);
+        s.clone()
+    });
+    assert_eq!(calls.get(), expected_calls);
+
+    // Outputs that do not need to be dropped.
+    let calls = Cell::new(0);
+    let mut bench_context = new_context();
+    Bencher::new(&mut bench_context).with_inputs(make_string).bench_local_refs(|s| {
+        calls.set(calls.get() + 1);
+        s.len()
+    });
+    assert_eq!(calls.get(), expected_calls);
+}
+
+/// Tests that the sample loop of each input and output shape can be calibrated,
+/// and that calibration is skipped without a global overhead.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn loop_overhead() {
+    let mut shared_context = SharedContext::test_default(Action::Bench, Timer::Os);
+
+    let options = BenchOptions::default();
+
+    assert!(overhead::measure::<String, String>(&shared_context, &options).is_zero());
+
+    shared_context.bench_overhead = FineDuration { picos: 1 };
+
+    // Iterations of an empty loop take far less than a microsecond.
+    let max = FineDuration { picos: 1_000_000 };
+    assert!(overhead::measure::<(), ()>(&shared_context, &options) < max);
+    assert!(overhead::measure::<(), [u64; 4]>(&shared_context, &options) < max);
+    assert!(overhead::measure::<String, String>(&shared_context, &options) < max);
+    assert!(overhead::measure::<String, usize>(&shared_context, &options) < max);
+
+    // Large outputs are calibrated with fewer slots rather than allocating and
+    // zeroing gigabytes.
+    type Large = ([u8; 1 << 20], String);
+    assert_eq!(overhead::sample_size::<(), u64>(), 1_000);
+    assert_eq!(overhead::sample_size::<(), Large>(), 1);
+
+    // Zeroing a large output may use a large stack frame without
+    // optimizations.
+    let large = thread::scope(|scope| {
+        thread::Builder::new()
+            .stack_size(16 << 20)
+            .spawn_scoped(scope, || overhead::measure::<(), Large>(&shared_context, &options))
+            .unwrap()
+            .join()
+            .unwrap()
+    });
+    assert!(!large.is_zero());
+}
+
+/// Tests that time spent generating inputs and dropping outputs is only
+/// reported when measured.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn ext_time() {
+    for (measure_gen, measure_drop) in [(false, false), (true, false), (false, true)] {
+        let shared_context = SharedContext {
+            measure_gen,
+            measure_drop,
+            ..SharedContext::test_default(Action::Bench, Timer::Os)
+        };
+
+        let bench_options = BenchOptions {
+            sample_count: Some(10),
+            sample_size: Some(SAMPLE_SIZE),
+            ..BenchOptions::default()
+        };
+
+        let mut bench_context =
+            BenchContext::new(&shared_context, &bench_options, NonZeroUsize::MIN);
+
+        Bencher::new(&mut bench_context).with_inputs(make_string).bench_values(|s| s);
+
+        let stats = bench_context.compute_stats();
+        assert_eq!(stats.gen_time.is_some(), measure_gen);
+        assert_eq!(stats.drop_time.is_some(), measure_drop);
+    }
+}
+
+/// Tests that sample state is created fresh for each sample and shared by all
+/// of its iterations.
+#[test]
+fn sample_state() {
+    struct State<'a> {
+        iter_count: u32,
+        dropped: &'a Mutex<Vec<u32>>,
+    }
+
+    impl Drop for State<'_> {
+        fn drop(&mut self) {
+            self.dropped.lock().unwrap().push(self.iter_count);
+        }
+    }
+
+    let dropped = Mutex::new(Vec::new());
+
+    test_bencher(&mut |bencher| {
+        let sample_size =
+            if bencher.context.shared_context.action.is_test() { 1 } else { SAMPLE_SIZE };
+
+        bencher
+            .with_sample_state(|| State { iter_count: 0, dropped: &dropped })
+            .with_inputs(make_string)
+            .bench_refs(|state, s| {
+                state.iter_count += 1;
+                s.len()
+            });
+
+        let mut dropped = dropped.lock().unwrap();
+        assert!(!dropped.is_empty());
+        assert!(dropped.iter().all(|&iter_count| iter_count == sample_size));
+        dropped.clear();
+    });
+}
+
+/// Tests that a shared input is generated once per sample and borrowed by all
+/// of its iterations.
+#[test]
+fn shared_input() {
+    let gen_count = AtomicUsize::new(0);
+    let iter_count = AtomicUsize::new(0);
+
+    test_bencher(&mut |bencher| {
+        let sample_size =
+            if bencher.context.shared_context.action.is_test() { 1 } else { SAMPLE_SIZE };
+
+        gen_count.store(0, SeqCst);
+        iter_count.store(0, SeqCst);
+
+        bencher
+            .with_shared_input(|| {
+                gen_count.fetch_add(1, SeqCst);
+                make_string()
+            })
+            .bench_shared(|s| {
+                iter_count.fetch_add(1, SeqCst);
+                s.len()
+            });
+
+        let gen_count = gen_count.load(SeqCst);
+        assert_ne!(gen_count, 0);
+        assert_eq!(iter_count.load(SeqCst), gen_count * sample_size as usize);
+    });
+}
+
+/// Tests that custom benchmarks are called once per sample and record the
+/// duration they report.
+#[test]
+fn custom_duration() {
+    test_bencher(&mut |bencher| bencher.bench_custom(Duration::from_nanos));
+
+    let bench_options = BenchOptions {
+        sample_count: Some(SAMPLE_COUNT),
+        sample_size: Some(SAMPLE_SIZE),
+        ..BenchOptions::default()
+    };
+
+    let shared_context = SharedContext::test_default(Action::Bench, Timer::Os);
+
+    let mut bench_context = BenchContext::new(&shared_context, &bench_options, NonZeroUsize::MIN);
+
+    let mut call_count = 0;
+    Bencher::new(&mut bench_context).bench_local_custom(|iters| {
+        assert_eq!(iters, SAMPLE_SIZE as u64);
+        call_count += 1;
+        Duration::from_micros(iters)
+    });
+
+    assert_eq!(call_count, SAMPLE_COUNT);
+
+    for sample in &bench_context.samples.all {
+        assert_eq!(sample.duration, FineDuration { picos: SAMPLE_SIZE as u128 * 1_000_000 });
+    }
+}
+
+/// Tests that time spent with the timer paused is excluded from samples.
+#[test]
+fn timer_pause() {
+    test_bencher(&mut |bencher| bencher.bench_with_timer(|timer| timer.paused(|| {})));
+
+    let bench_options = BenchOptions {
+        sample_count: Some(SAMPLE_COUNT),
+        sample_size: Some(SAMPLE_SIZE),
+        ..BenchOptions::default()
+    };
+
+    for timer in Timer::available() {
+        let shared_context = SharedContext::test_default(Action::Bench, timer);
+
+        let mut bench_context =
+            BenchContext::new(&shared_context, &bench_options, NonZeroUsize::MIN);
+
+        let sleep_time = Duration::from_millis(1);
+        Bencher::new(&mut bench_context).bench_local_with_timer(|timer| {
+            timer.pause();
+            assert!(timer.is_paused());
+            thread::sleep(sleep_time);
+            timer.resume();
+        });
+
+        assert_eq!(bench_context.samples.all.len(), SAMPLE_COUNT as usize);
+
+        for sample in &bench_context.samples.all {
+            assert!(sample.duration < FineDuration::from(sleep_time));
+        }
+    }
+}
+
+/// Tests that interleaved benchmarks alternate recording samples.
+#[test]
+fn interleave_samples() {
+    let bench_options = BenchOptions {
+        sample_count: Some(SAMPLE_COUNT),
+        sample_size: Some(SAMPLE_SIZE),
+        ..BenchOptions::default()
+    };
+
+    let shared_context = SharedContext::test_default(Action::Bench, Timer::Os);
+
+    let turns = Turns::new(2);
+    let runs = Mutex::new(Vec::<usize>::new());
+
+    thread::scope(|scope| {
+        for index in 0..2 {
+            let (turns, runs) = (&turns, &runs);
+            let (shared_context, bench_options) = (&shared_context, &bench_options);
+
+            scope.spawn(move || {
+                let turn = turns.enter(index);
+
+                let mut bench_context =
+                    BenchContext::new(shared_context, bench_options, NonZeroUsize::MIN);
+                bench_context.turn = Some(turn.turn());
+
+                Bencher::new(&mut bench_context).bench(|| runs.lock().unwrap().push(index));
+
+                assert_eq!(bench_context.samples.all.len(), SAMPLE_COUNT as usize);
+            });
+        }
+    });
+
+    let expected: Vec<usize> = (0..SAMPLE_COUNT as usize)
+        .flat_map(|_| [0, 1])
+        .flat_map(|index| [index; SAMPLE_SIZE as usize])
+        .collect();
+
+    assert_eq!(runs.into_inner().unwrap(), expected);
+}
+
+/// Tests that a span is emitted for the benchmark and each of its samples.
+#[cfg(feature = "tracing")]
+#[test]
+fn tracing_spans() {
+    use std::sync::Arc;
+
+    use tracing::{span, Event, Metadata, Subscriber};
+
+    /// Collects the names of new spans and counts recorded fields.
+    #[derive(Default)]
+    struct SpanCollector {
+        names: Mutex<Vec<&'static str>>,
+        record_count: AtomicUsize,
+    }
+
+    impl Subscriber for SpanCollector {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &span::Attributes) -> span::Id {
+            let mut names = self.names.lock().unwrap();
+            names.push(span.metadata().name());
+            span::Id::from_u64(names.len() as u64)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record) {
+            self.record_count.fetch_add(1, SeqCst);
+        }
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, _: &Event) {}
+
+        fn enter(&self, _: &span::Id) {}
+
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    let bench_options = BenchOptions {
+        sample_count: Some(SAMPLE_COUNT),
+        sample_size: Some(SAMPLE_SIZE),
+        ..BenchOptions::default()
+    };
+
+    let shared_context = SharedContext::test_default(Action::Bench, Timer::Os);
+
+    let collector = Arc::new(SpanCollector::default());
+
+    tracing::subscriber::with_default(collector.clone(), || {
+        let mut bench_context =
+            BenchContext::new(&shared_context, &bench_options, NonZeroUsize::MIN);
+        bench_context.entry_path = "tests::tracing_spans";
+
+        Bencher::new(&mut bench_context).bench(|| black_box(1) + 1);
+    });
+
+    let expected: Vec<&str> =
+        Some("bench").into_iter().chain(["sample"; SAMPLE_COUNT as usize]).collect();
+
+    assert_eq!(*collector.names.lock().unwrap(), expected);
+    assert_eq!(collector.record_count.load(SeqCst), SAMPLE_COUNT as usize);
+}
+
+/// Tests that profiler hooks are started and stopped around sampling, even if
+/// the benchmark panics.
+#[test]
+fn profiler_hooks() {
+    use std::sync::Arc;
+
+    use crate::profiler::{ProfilerHook, SharedHook};
+
+    struct LogHook {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl ProfilerHook for LogHook {
+        fn start(&mut self, path: &str) {
+            self.log.lock().unwrap().push(format!("start {} {path}", self.name));
+        }
+
+        fn stop(&mut self, path: &str) {
+            self.log.lock().unwrap().push(format!("stop {} {path}", self.name));
+        }
+    }
+
+    let log = Arc::new(Mutex::new(Vec::<String>::new()));
+
+    let hooks: Vec<SharedHook> = ["a", "b"]
+        .into_iter()
+        .map(|name| Arc::new(Mutex::new(LogHook { name, log: log.clone() })) as SharedHook)
+        .collect();
+
+    let bench_options = BenchOptions {
+        sample_count: Some(SAMPLE_COUNT),
+        sample_size: Some(SAMPLE_SIZE),
+        ..BenchOptions::default()
+    };
+
+    let shared_context = SharedContext {
+        profiler_hooks: hooks,
+        ..SharedContext::test_default(Action::Bench, Timer::Os)
+    };
+
+    let mut bench_context = BenchContext::new(&shared_context, &bench_options, NonZeroUsize::MIN);
+    bench_context.entry_path = "tests::ok";
+
+    Bencher::new(&mut bench_context).bench(|| {
+        // Hooks are started before the first sample.
+        assert_eq!(log.lock().unwrap().len(), 2);
+    });
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let mut bench_context =
+            BenchContext::new(&shared_context, &bench_options, NonZeroUsize::MIN);
+        bench_context.entry_path = "tests::panic";
+
+        Bencher::new(&mut bench_context).bench(|| panic!());
+    }));
+    assert!(result.is_err());
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            "start a tests::ok",
+            "start b tests::ok",
+            "stop b tests::ok",
+            "stop a tests::ok",
+            "start a tests::panic",
+            "start b tests::panic",
+            "stop b tests::panic",
+            "stop a tests::panic",
+        ]
+    );
+}
+
+/// Tests that sample hooks are called around every sample, including warm-up
+/// samples.
+#[test]
+fn sample_hooks() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    static BEGIN_COUNT: AtomicU32 = AtomicU32::new(0);
+    static END_COUNT: AtomicU32 = AtomicU32::new(0);
+
+    const WARMUP_SAMPLES: u32 = 3;
+
+    let bench_options = BenchOptions {
+        sample_count: Some(SAMPLE_COUNT),
+        sample_size: Some(SAMPLE_SIZE),
+        warmup_samples: Some(WARMUP_SAMPLES),
+        ..BenchOptions::default()
+    };
+
+    let shared_context = SharedContext {
+        on_sample_begin: Some(|path| {
+            assert_eq!(path, "tests::sample_hooks");
+            BEGIN_COUNT.fetch_add(1, Ordering::Relaxed);
+        }),
+        on_sample_end: Some(|path| {
+            assert_eq!(path, "tests::sample_hooks");
+            END_COUNT.fetch_add(1, Ordering::Relaxed);
+        }),
+        ..SharedContext::test_default(Action::Bench, Timer::Os)
+    };
+
+    let mut bench_context = BenchContext::new(&shared_context, &bench_options, NonZeroUsize::MIN);
+    bench_context.entry_path = "tests::sample_hooks";
+
+    Bencher::new(&mut bench_context).bench(|| {
+        // Sample bodies run between the hooks.
+        assert_eq!(BEGIN_COUNT.load(Ordering::Relaxed), END_COUNT.load(Ordering::Relaxed) + 1);
+    });
+
+    assert_eq!(BEGIN_COUNT.load(Ordering::Relaxed), SAMPLE_COUNT + WARMUP_SAMPLES);
+    assert_eq!(END_COUNT.load(Ordering::Relaxed), SAMPLE_COUNT + WARMUP_SAMPLES);
+}
@@ -0,0 +1,98 @@
+use std::{cell::Cell, fmt};
+
+use crate::time::{FineDuration, Timer, Timestamp};
+
+/// Excludes parts of each iteration from measurement in
+/// [`Bencher::bench_with_timer`](crate::Bencher::bench_with_timer).
+///
+/// Time between [`pause`](Self::pause) and [`resume`](Self::resume) is
+/// subtracted from the sample. Reading the timer is not free, so pausing
+/// adds some overhead to the measured time.
+///
+/// Allocations and hardware counters are not paused.
+pub struct TimerGuard {
+    timer: Timer,
+
+    /// When the timer was paused, if it is currently paused.
+    paused_at: Cell<Option<Timestamp>>,
+
+    /// The total time spent paused in the current sample.
+    paused_time: Cell<FineDuration>,
+}
+
+impl fmt::Debug for TimerGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimerGuard").field("is_paused", &self.is_paused()).finish_non_exhaustive()
+    }
+}
+
+impl TimerGuard {
+    #[inline]
+    pub(crate) fn new(timer: Timer) -> Self {
+        Self { timer, paused_at: Cell::new(None), paused_time: Cell::new(FineDuration::default()) }
+    }
+
+    /// Stops measuring time until [`resume`](Self::resume) is called.
+    ///
+    /// This does nothing if the timer is already paused.
+    #[inline]
+    pub fn pause(&self) {
+        if self.paused_at.get().is_none() {
+            self.paused_at.set(Some(Timestamp::end(self.timer.kind())));
+        }
+    }
+
+    /// Starts measuring time again after [`pause`](Self::pause).
+    ///
+    /// This does nothing if the timer is not paused.
+    #[inline]
+    pub fn resume(&self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            let resumed_at = Timestamp::start(self.timer.kind());
+            self.paused_time
+                .set(self.paused_time.get() + resumed_at.duration_since(paused_at, self.timer));
+        }
+    }
+
+    /// Runs `f` with the timer paused.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher.bench_with_timer(|timer| {
+    ///         timer.paused(|| {
+    ///             // Untimed code...
+    ///         });
+    ///
+    ///         // Timed code...
+    ///     });
+    /// }
+    /// ```
+    #[inline]
+    pub fn paused<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.pause();
+        let result = f();
+        self.resume();
+        result
+    }
+
+    /// Returns `true` if the timer is paused.
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.get().is_some()
+    }
+
+    /// Returns the time between `start` and `end` that was not paused.
+    pub(crate) fn measured(&self, start: Timestamp, end: Timestamp) -> FineDuration {
+        let mut paused_time = self.paused_time.get();
+        if let Some(paused_at) = self.paused_at.get() {
+            paused_time += end.duration_since(paused_at, self.timer);
+        }
+
+        FineDuration {
+            picos: end.duration_since(start, self.timer).picos.saturating_sub(paused_time.picos),
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use crate::time::FineDuration;
+
+/// A sample must take longer than this multiple of timer precision to be
+/// measurable.
+const MIN_PRECISION_MULTIPLE: u128 = 100;
+
+/// The multiple of timer precision that tuned samples aim to take, leaving
+/// headroom over `MIN_PRECISION_MULTIPLE` for faster iterations.
+const TARGET_PRECISION_MULTIPLE: u128 = 150;
+
+/// The number of measurable trial samples whose median per-iteration time
+/// decides the tuned sample size.
+const TRIAL_COUNT: usize = 3;
+
+/// The number of recent samples whose median per-iteration time is compared
+/// against the tuned estimate to detect drift.
+const DRIFT_WINDOW: usize = 5;
+
+/// How many times slower or faster recent samples must be than the tuned
+/// estimate to re-tune.
+const DRIFT_FACTOR: u128 = 4;
+
+/// The default limit on how long a sample may take including generating and
+/// dropping inputs, which is 5ms.
+pub(crate) const DEFAULT_MAX_BODY_TIME: FineDuration = FineDuration { picos: 5_000_000_000 };
+
+/// The maximum number of times to re-tune after collecting has started, so
+/// that erratic benchmarks still finish.
+const MAX_RETUNES: u32 = 2;
+
+/// Chooses `sample_size` from a rolling estimate of per-iteration time.
+///
+/// Tuning doubles the sample size until samples are measurable, and then
+/// sizes samples by the median of several trials so that a single
+/// unrepresentative sample does not decide the size. Once collecting, samples
+/// that drift far from the estimate cause the size to be chosen again.
+///
+/// Sample sizes are also limited by the time of the whole sample body, which
+/// includes generating and dropping inputs and outputs. This keeps benchmarks
+/// with expensive inputs and very fast bodies from taking very long.
+///
+/// If `sample_time` is set, samples are instead sized for their bodies to take
+/// that long, regardless of timer precision.
+pub(crate) struct Tuner {
+    /// The minimum measurable sample duration, in picoseconds.
+    min_picos: u128,
+
+    /// The sample duration that tuned sizes aim for, in picoseconds.
+    target_picos: u128,
+
+    /// The maximum sample body duration, in picoseconds.
+    max_body_picos: u128,
+
+    /// The requested sample body duration, in picoseconds.
+    sample_time_picos: Option<u128>,
+
+    /// Per-iteration times of recent samples and their bodies, in
+    /// picoseconds.
+    recent: Vec<PerIter>,
+
+    /// The per-iteration times that the current sample size was tuned for.
+    estimate: Option<PerIter>,
+
+    retune_count: u32,
+}
+
+impl Tuner {
+    pub fn new(
+        timer_precision: FineDuration,
+        max_body_time: FineDuration,
+        sample_time: Option<FineDuration>,
+    ) -> Self {
+        Self {
+            min_picos: timer_precision.picos.saturating_mul(MIN_PRECISION_MULTIPLE),
+            target_picos: timer_precision.picos.saturating_mul(TARGET_PRECISION_MULTIPLE),
+            max_body_picos: max_body_time.picos,
+            sample_time_picos: sample_time.map(|time| time.picos),
+            recent: Vec::new(),
+            estimate: None,
+            retune_count: 0,
+        }
+    }
+
+    /// Records the slowest sample and the sample body time while tuning,
+    /// returning the next sample size and whether it is ready for collecting.
+    pub fn tune(
+        &mut self,
+        sample_size: u32,
+        slowest: FineDuration,
+        body: FineDuration,
+    ) -> (u32, bool) {
+        let is_short = match self.sample_time_picos {
+            Some(sample_time_picos) => body.picos < sample_time_picos,
+            None => slowest.picos <= self.min_picos && body.picos < self.max_body_picos,
+        };
+
+        if is_short {
+            // Too fast to measure, so earlier trials are not representative.
+            self.recent.clear();
+            return (sample_size.saturating_mul(2), false);
+        }
+
+        self.recent.push(PerIter::new(sample_size, slowest, body));
+        if self.recent.len() < TRIAL_COUNT {
+            return (sample_size, false);
+        }
+
+        let estimate = self.take_median();
+        self.estimate = Some(estimate);
+
+        (self.size_for(estimate), true)
+    }
+
+    /// Records the slowest sample and the sample body time while collecting,
+    /// returning a new sample size if recent samples drifted far from the
+    /// tuned estimate.
+    pub fn check_drift(
+        &mut self,
+        sample_size: u32,
+        slowest: FineDuration,
+        body: FineDuration,
+    ) -> Option<u32> {
+        let estimate = self.estimate?;
+        if self.retune_count >= MAX_RETUNES {
+            return None;
+        }
+
+        self.recent.push(PerIter::new(sample_size, slowest, body));
+        if self.recent.len() < DRIFT_WINDOW {
+            return None;
+        }
+
+        let median = self.take_median();
+
+        let is_slower = median.timed > estimate.timed.saturating_mul(DRIFT_FACTOR);
+        let is_faster = median.timed.saturating_mul(DRIFT_FACTOR) < estimate.timed;
+        if !is_slower && !is_faster {
+            return None;
+        }
+
+        self.estimate = Some(median);
+        self.retune_count += 1;
+
+        Some(self.size_for(median)).filter(|&new_size| new_size != sample_size)
+    }
+
+    /// Returns the medians of recent per-iteration times and clears them.
+    fn take_median(&mut self) -> PerIter {
+        let mid = self.recent.len() / 2;
+
+        self.recent.sort_unstable_by_key(|per_iter| per_iter.timed);
+        let timed = self.recent[mid].timed;
+
+        self.recent.sort_unstable_by_key(|per_iter| per_iter.body);
+        let body = self.recent[mid].body;
+
+        self.recent.clear();
+        PerIter { timed, body }
+    }
+
+    /// Returns the sample size that takes `target_picos` for the timed section,
+    /// limited by `max_body_picos` for the whole sample body.
+    ///
+    /// If `sample_time_picos` is set, it is used for the whole sample body
+    /// instead.
+    fn size_for(&self, per_iter: PerIter) -> u32 {
+        let size = match self.sample_time_picos {
+            Some(sample_time_picos) => sample_time_picos / per_iter.body.max(1),
+            None => self
+                .target_picos
+                .div_ceil(per_iter.timed.max(1))
+                .min(self.max_body_picos / per_iter.body.max(1)),
+        };
+
+        size.clamp(1, u32::MAX as u128) as u32
+    }
+}
+
+/// Per-iteration times of a sample, in picoseconds.
+#[derive(Clone, Copy)]
+struct PerIter {
+    /// Time spent in the timed section.
+    timed: u128,
+
+    /// Time spent in the whole sample body, including generating and dropping
+    /// inputs and outputs.
+    body: u128,
+}
+
+impl PerIter {
+    fn new(sample_size: u32, slowest: FineDuration, body: FineDuration) -> Self {
+        let sample_size = sample_size as u128;
+        let body = body.picos.max(slowest.picos);
+        Self { timed: slowest.picos / sample_size, body: body / sample_size }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRECISION: FineDuration = FineDuration { picos: 1_000 };
+
+    fn picos(picos: u128) -> FineDuration {
+        FineDuration { picos }
+    }
+
+    #[test]
+    fn doubles_until_measurable() {
+        let mut tuner = Tuner::new(PRECISION, DEFAULT_MAX_BODY_TIME, None);
+
+        assert_eq!(tuner.tune(1, picos(1_000), picos(1_000)), (2, false));
+        assert_eq!(tuner.tune(2, picos(2_000), picos(2_000)), (4, false));
+    }
+
+    #[test]
+    fn uses_median_of_trials() {
+        let mut tuner = Tuner::new(PRECISION, DEFAULT_MAX_BODY_TIME, None);
+
+        // An early fast sample does not decide the size.
+        assert_eq!(tuner.tune(100, picos(200_000), picos(200_000)), (100, false));
+        assert_eq!(tuner.tune(100, picos(1_000_000), picos(1_000_000)), (100, false));
+
+        // Median per-iteration time is 10ns, so 150ns needs 15 iterations.
+        assert_eq!(tuner.tune(100, picos(1_100_000), picos(1_100_000)), (15, true));
+    }
+
+    /// Expensive inputs stop doubling and limit the sample size.
+    #[test]
+    fn limits_body_time() {
+        let mut tuner = Tuner::new(PRECISION, picos(2_000_000), None);
+
+        // The timed section takes 1ps but the body takes 1ns per iteration.
+        assert_eq!(tuner.tune(1_000, picos(1_000), picos(1_000_000)), (2_000, false));
+        for _ in 0..TRIAL_COUNT - 1 {
+            assert_eq!(tuner.tune(2_000, picos(2_000), picos(2_000_000)), (2_000, false));
+        }
+
+        // Without the limit, the sample size would be 150,000.
+        assert_eq!(tuner.tune(2_000, picos(2_000), picos(2_000_000)), (2_000, true));
+    }
+
+    /// `sample_time` sizes samples regardless of timer precision.
+    #[test]
+    fn uses_sample_time() {
+        let mut tuner = Tuner::new(PRECISION, DEFAULT_MAX_BODY_TIME, Some(picos(1_000_000_000)));
+
+        // Doubling continues past measurable samples until reaching 1ms.
+        assert_eq!(tuner.tune(1_000, picos(200_000_000), picos(250_000_000)), (2_000, false));
+        assert_eq!(tuner.tune(2_000, picos(400_000_000), picos(500_000_000)), (4_000, false));
+        for _ in 0..TRIAL_COUNT - 1 {
+            assert_eq!(tuner.tune(4_000, picos(800_000_000), picos(1_000_000_000)), (4_000, false));
+        }
+
+        // Bodies take 250ns per iteration, so 1ms needs 4,000 iterations.
+        assert_eq!(tuner.tune(4_000, picos(800_000_000), picos(1_000_000_000)), (4_000, true));
+    }
+
+    #[test]
+    fn retunes_on_drift() {
+        let mut tuner = Tuner::new(PRECISION, DEFAULT_MAX_BODY_TIME, None);
+
+        for _ in 0..TRIAL_COUNT {
+            tuner.tune(15, picos(150_000), picos(150_000));
+        }
+
+        // Samples close to the estimate do not re-tune.
+        for _ in 0..DRIFT_WINDOW {
+            assert_eq!(tuner.check_drift(15, picos(300_000), picos(300_000)), None);
+        }
+
+        // Samples 10x slower re-tune to a smaller size.
+        let drift: Vec<_> = (0..DRIFT_WINDOW)
+            .map(|_| tuner.check_drift(15, picos(1_500_000), picos(1_500_000)))
+            .collect();
+        assert_eq!(drift[..DRIFT_WINDOW - 1], [None; DRIFT_WINDOW - 1]);
+        assert_eq!(drift[DRIFT_WINDOW - 1], Some(2));
+    }
+
+    #[test]
+    fn limits_retunes() {
+        let mut tuner = Tuner::new(PRECISION, DEFAULT_MAX_BODY_TIME, None);
+
+        for _ in 0..TRIAL_COUNT {
+            tuner.tune(15, picos(150_000), picos(150_000));
+        }
+
+        let mut sample_size = 15;
+        let mut slowest = 150_000;
+        let mut retune_count = 0;
+
+        for _ in 0..(MAX_RETUNES as usize + 2) * DRIFT_WINDOW {
+            slowest *= 10;
+            if let Some(new_size) = tuner.check_drift(sample_size, picos(slowest), picos(slowest)) {
+                sample_size = new_size;
+                retune_count += 1;
+            }
+        }
+
+        assert!(retune_count <= MAX_RETUNES);
+    }
+}
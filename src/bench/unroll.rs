@@ -0,0 +1,101 @@
+//! Unrolled sample loops for the `unroll` option.
+//!
+//! Each sample loop is compiled for every supported factor and the factor is
+//! chosen at runtime. Within an unrolled chunk, the benchmarked function is
+//! called a constant number of times, so the loop's induction variable update
+//! and branch happen once per chunk instead of once per iteration.
+
+/// The largest supported unroll factor.
+pub(crate) const MAX_UNROLL: u32 = 16;
+
+/// Returns the factor used for `unroll`, which is rounded down to a power of
+/// two no greater than [`MAX_UNROLL`].
+pub(crate) fn factor(unroll: u32) -> u32 {
+    match unroll.checked_ilog2() {
+        Some(log) => (1 << log).min(MAX_UNROLL),
+        None => 1,
+    }
+}
+
+/// Calls `f` `count` times, unrolled by the [`factor`] of `unroll`.
+#[inline(always)]
+pub(crate) fn repeat(count: usize, unroll: u32, f: impl FnMut()) {
+    match factor(unroll) {
+        1 => repeat_n::<1>(count, f),
+        2 => repeat_n::<2>(count, f),
+        4 => repeat_n::<4>(count, f),
+        8 => repeat_n::<8>(count, f),
+        _ => repeat_n::<16>(count, f),
+    }
+}
+
+/// Calls `f` with each of `items`, unrolled by the [`factor`] of `unroll`.
+#[inline(always)]
+pub(crate) fn for_each<T>(items: &[T], unroll: u32, f: impl FnMut(&T)) {
+    match factor(unroll) {
+        1 => for_each_n::<T, 1>(items, f),
+        2 => for_each_n::<T, 2>(items, f),
+        4 => for_each_n::<T, 4>(items, f),
+        8 => for_each_n::<T, 8>(items, f),
+        _ => for_each_n::<T, 16>(items, f),
+    }
+}
+
+#[inline(always)]
+fn repeat_n<const N: usize>(count: usize, mut f: impl FnMut()) {
+    for _ in 0..count / N {
+        for _ in 0..N {
+            f();
+        }
+    }
+
+    for _ in 0..count % N {
+        f();
+    }
+}
+
+#[inline(always)]
+fn for_each_n<T, const N: usize>(items: &[T], mut f: impl FnMut(&T)) {
+    let chunks = items.chunks_exact(N);
+    let remainder = chunks.remainder();
+
+    for chunk in chunks {
+        // Arrays have a constant length, which lets the compiler unroll.
+        if let Ok(chunk) = <&[T; N]>::try_from(chunk) {
+            for item in chunk {
+                f(item);
+            }
+        }
+    }
+
+    for item in remainder {
+        f(item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn factor() {
+        let factors: Vec<u32> = [0, 1, 2, 3, 4, 7, 8, 16, 17, 100].map(super::factor).to_vec();
+        assert_eq!(factors, [1, 1, 2, 2, 4, 4, 8, 16, 16, 16]);
+    }
+
+    #[test]
+    fn every_item_once() {
+        for unroll in 0..=20 {
+            for count in [0, 1, 5, 16, 33] {
+                let mut calls = 0;
+                repeat(count, unroll, || calls += 1);
+                assert_eq!(calls, count, "unroll {unroll}");
+
+                let items: Vec<usize> = (0..count).collect();
+                let mut visited = Vec::new();
+                for_each(&items, unroll, |&item| visited.push(item));
+                assert_eq!(visited, items, "unroll {unroll}");
+            }
+        }
+    }
+}
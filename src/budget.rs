@@ -0,0 +1,123 @@
+//! Dividing a total time budget among benchmarks, set by `--total-time`.
+//!
+//! Each benchmark is allotted an equal share of the budget, within the bounds
+//! of its own `min_time` and `max_time`. Time not taken by benchmarks capped
+//! below their share is divided among the rest.
+
+use std::time::Duration;
+
+/// The time limits of a benchmark that is measured `runs` times, such as once
+/// per thread count.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Limits {
+    pub runs: u32,
+    pub min_time: Duration,
+    pub max_time: Option<Duration>,
+}
+
+impl Limits {
+    /// The maximum time, which has priority over the minimum time.
+    fn max_nanos(&self) -> u128 {
+        self.max_time.map_or(u128::MAX, |time| time.as_nanos())
+    }
+
+    fn min_nanos(&self) -> u128 {
+        self.min_time.as_nanos().min(self.max_nanos())
+    }
+}
+
+/// Divides `total` among benchmarks with `limits`, returning the time allotted
+/// to each run of each benchmark.
+///
+/// If the minimum times alone exceed `total`, they are ignored and their sum is
+/// returned alongside the allotments.
+pub(crate) fn allot(total: Duration, limits: &[Limits]) -> (Vec<Duration>, Option<Duration>) {
+    let total = total.as_nanos();
+
+    let min_total: u128 =
+        limits.iter().map(|limits| limits.runs as u128 * limits.min_nanos()).sum();
+
+    // Minimum times take priority unless they cannot fit.
+    let (use_min, ignored_min) = if min_total > total {
+        (false, Some(Duration::from_nanos(min_total.try_into().unwrap_or(u64::MAX))))
+    } else {
+        (true, None)
+    };
+
+    let allotment = |limits: &Limits, level: u128| -> u128 {
+        let min = if use_min { limits.min_nanos() } else { 0 };
+        level.clamp(min, limits.max_nanos())
+    };
+
+    let used = |level: u128| -> u128 {
+        limits.iter().map(|limits| limits.runs as u128 * allotment(limits, level)).sum()
+    };
+
+    // Find the highest level whose allotments fit, which is at most `total`.
+    let (mut low, mut high) = (0, total);
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        if used(mid) <= total {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    let allotments = limits
+        .iter()
+        .map(|limits| {
+            let nanos = allotment(limits, low);
+            Duration::from_nanos(nanos.try_into().unwrap_or(u64::MAX))
+        })
+        .collect();
+
+    (allotments, ignored_min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(runs: u32, min_time: u64, max_time: Option<u64>) -> Limits {
+        Limits {
+            runs,
+            min_time: Duration::from_secs(min_time),
+            max_time: max_time.map(Duration::from_secs),
+        }
+    }
+
+    fn allot_secs(total: u64, limits: &[Limits]) -> (Vec<u64>, bool) {
+        let (allotments, ignored_min) = allot(Duration::from_secs(total), limits);
+        (allotments.iter().map(Duration::as_secs).collect(), ignored_min.is_some())
+    }
+
+    #[test]
+    fn equal_shares() {
+        assert_eq!(allot_secs(30, &[limits(1, 0, None); 3]), (vec![10, 10, 10], false));
+
+        // Each run of a benchmark with multiple thread counts is allotted time.
+        assert_eq!(
+            allot_secs(30, &[limits(2, 0, None), limits(1, 0, None)]),
+            (vec![10, 10], false)
+        );
+    }
+
+    #[test]
+    fn configured_limits() {
+        // Time not taken by capped benchmarks goes to the rest.
+        assert_eq!(
+            allot_secs(30, &[limits(1, 0, Some(2)), limits(1, 0, None), limits(1, 0, None)]),
+            (vec![2, 14, 14], false)
+        );
+
+        // Minimum times are taken first.
+        assert_eq!(
+            allot_secs(30, &[limits(1, 20, None), limits(1, 0, None), limits(1, 0, None)]),
+            (vec![20, 5, 5], false)
+        );
+
+        // Minimum times that cannot fit are ignored.
+        assert_eq!(allot_secs(30, &[limits(1, 20, None); 2]), (vec![15, 15], true));
+    }
+}
@@ -0,0 +1,343 @@
+//! Deterministic instruction counting with Valgrind's Callgrind.
+//!
+//! With `--mode callgrind`, the benchmark process re-runs itself under
+//! Callgrind once per benchmark. The child process runs a single iteration of
+//! that benchmark and toggles collection around it with a Valgrind client
+//! request, so counts exclude startup and the rest of Divan. The parent then
+//! reads instruction, cache simulation, and branch simulation totals from
+//! Callgrind's output file.
+//!
+//! Unlike time and hardware counters, these counts are the same across runs
+//! of the same binary, which makes them reliable on noisy shared CI runners.
+
+use std::{
+    env, fs, io,
+    process::{Command, Stdio},
+};
+
+use crate::{
+    counter::KnownCounterKind,
+    hw::HwCounterKind,
+    stats::{Stats, StatsSet, TIME_PERCENTILES},
+    time::FineDuration,
+    util::TempDir,
+};
+
+/// Environment variable set to the path of the benchmark run by a child
+/// process.
+const CHILD_ENV: &str = "DIVAN_CALLGRIND_CHILD";
+
+/// Whether Valgrind client requests are implemented for this target.
+pub(crate) const IS_SUPPORTED: bool =
+    cfg!(all(any(target_arch = "x86_64", target_arch = "aarch64"), not(miri)));
+
+/// The role of this process when benchmarking under Callgrind.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CallgrindRole {
+    /// Spawns a child process under Callgrind for each benchmark.
+    Parent,
+
+    /// Runs a single benchmark under Callgrind.
+    Child,
+}
+
+/// Returns the path of the benchmark to run if this is a child process.
+pub(crate) fn child_path() -> Option<String> {
+    env::var(CHILD_ENV).ok()
+}
+
+/// Checks that Valgrind can be run.
+pub(crate) fn check_valgrind() -> io::Result<()> {
+    if !IS_SUPPORTED {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Callgrind is not supported on this platform",
+        ));
+    }
+
+    let output = Command::new("valgrind").arg("--version").stdin(Stdio::null()).output().map_err(
+        |error| {
+            if error.kind() == io::ErrorKind::NotFound {
+                io::Error::new(io::ErrorKind::NotFound, "'valgrind' not found")
+            } else {
+                error
+            }
+        },
+    )?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other("'valgrind --version' failed"))
+    }
+}
+
+/// Runs the benchmark at `path` under Callgrind with the input `seed` and
+/// returns its counts.
+pub(crate) fn run(path: &str, seed: u64) -> io::Result<CallgrindCounts> {
+    let out_dir = TempDir::new("divan-callgrind")?;
+    let out_file = out_dir.path().join("callgrind.out");
+
+    let mut out_file_arg = std::ffi::OsString::from("--callgrind-out-file=");
+    out_file_arg.push(&out_file);
+
+    let output = Command::new("valgrind")
+        .args(["--tool=callgrind", "--collect-atstart=no", "--cache-sim=yes", "--branch-sim=yes"])
+        .arg(out_file_arg)
+        .arg("--")
+        .arg(env::current_exe()?)
+        .args(["--bench", "--include-ignored"])
+        .env(CHILD_ENV, path)
+        .env("DIVAN_SEED", seed.to_string())
+        .stdin(Stdio::null())
+        .output()?;
+
+    let contents = fs::read_to_string(&out_file);
+    drop(out_dir);
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!(
+            "Callgrind exited with {}:\n{}",
+            output.status,
+            stderr.trim_end()
+        )));
+    }
+
+    parse(&contents?).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "Callgrind output is missing event totals")
+    })
+}
+
+/// Toggles whether Callgrind collects events.
+///
+/// This does nothing when not running under Valgrind.
+#[inline]
+pub(crate) fn toggle_collect() {
+    // `CALLGRIND_TOGGLE_COLLECT` from `callgrind.h`.
+    const TOGGLE_COLLECT: usize = ((b'C' as usize) << 24 | (b'T' as usize) << 16) + 2;
+
+    client_request(TOGGLE_COLLECT, [0; 5]);
+}
+
+/// Performs a Valgrind client request, returning 0 when not running under
+/// Valgrind.
+///
+/// This uses the "special instruction" sequences from `valgrind.h`, which are
+/// no-ops on real hardware.
+#[inline(always)]
+#[allow(unused_variables)]
+fn client_request(request: usize, args: [usize; 5]) -> usize {
+    let args = [request, args[0], args[1], args[2], args[3], args[4]];
+    let mut result: usize = 0;
+
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    // SAFETY: The preamble rotates `rdi` by a total of 128 bits, leaving it
+    // unchanged, and `xchg rbx, rbx` is a no-op. Valgrind reads `args` and
+    // writes `rdx`.
+    unsafe {
+        std::arch::asm!(
+            "rol rdi, 3",
+            "rol rdi, 13",
+            "rol rdi, 61",
+            "rol rdi, 51",
+            "xchg rbx, rbx",
+            in("rax") args.as_ptr(),
+            inout("rdx") result,
+            options(nostack),
+        );
+    }
+
+    #[cfg(all(target_arch = "aarch64", not(miri)))]
+    // SAFETY: The preamble rotates `x12` by a total of 128 bits, leaving it
+    // unchanged, and `orr x10, x10, x10` is a no-op. Valgrind reads `args` and
+    // writes `x3`.
+    unsafe {
+        std::arch::asm!(
+            "ror x12, x12, #3",
+            "ror x12, x12, #13",
+            "ror x12, x12, #51",
+            "ror x12, x12, #61",
+            "orr x10, x10, x10",
+            in("x4") args.as_ptr(),
+            inout("x3") result,
+            options(nostack),
+        );
+    }
+
+    result
+}
+
+/// Event totals from a Callgrind run with cache and branch simulation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct CallgrindCounts {
+    /// Instructions executed.
+    pub instructions: u64,
+
+    /// Data reads and writes.
+    pub data_reads: u64,
+    pub data_writes: u64,
+
+    /// First-level cache misses for instructions, reads, and writes.
+    pub l1_misses: u64,
+
+    /// Last-level cache misses for instructions, reads, and writes.
+    pub ll_misses: u64,
+
+    /// Executed conditional and indirect branches.
+    pub branches: u64,
+
+    /// Mispredicted conditional and indirect branches.
+    pub branch_misses: u64,
+}
+
+impl CallgrindCounts {
+    /// Estimates cycles from cache simulation, using the same weights as
+    /// KCachegrind's "Cycle Estimation": 1 cycle per L1 hit, 5 per last-level
+    /// hit, and 35 per last-level miss.
+    pub fn estimated_cycles(self) -> u64 {
+        let accesses = self.instructions + self.data_reads + self.data_writes;
+        let l1_hits = accesses.saturating_sub(self.l1_misses);
+        let ll_hits = self.l1_misses.saturating_sub(self.ll_misses);
+
+        l1_hits + 5 * ll_hits + 35 * self.ll_misses
+    }
+
+    /// Converts counts of a single iteration into benchmark statistics.
+    ///
+    /// Counts are reported as hardware counts, and time is zero.
+    pub fn to_stats(self) -> Stats {
+        let mut hw_counts = [0.0; HwCounterKind::COUNT];
+        hw_counts[HwCounterKind::Cycles as usize] = self.estimated_cycles() as f64;
+        hw_counts[HwCounterKind::Instructions as usize] = self.instructions as f64;
+        hw_counts[HwCounterKind::CacheMisses as usize] = self.ll_misses as f64;
+        hw_counts[HwCounterKind::Branches as usize] = self.branches as f64;
+        hw_counts[HwCounterKind::BranchMisses as usize] = self.branch_misses as f64;
+
+        let time = FineDuration::default();
+
+        Stats {
+            sample_count: 1,
+            iter_count: 1,
+            time: StatsSet { fastest: time, slowest: time, median: time, mean: time },
+            time_percentiles: [time; TIME_PERCENTILES.len()],
+            counts: [None; KnownCounterKind::COUNT],
+            custom_counts: Vec::new(),
+            time_std_dev: None,
+            run_std_dev: None,
+            trimmed_mean: None,
+            winsorized_mean: None,
+            time_mad: None,
+            bimodality: None,
+            time_histogram: None,
+            throughput_std_dev: [None; KnownCounterKind::COUNT],
+            custom_throughput_std_dev: Vec::new(),
+            hw_counts: Some(hw_counts),
+            perf_counts: Vec::new(),
+            threads: None,
+            allocs: None,
+            io_counts: None,
+            gen_time: None,
+            drop_time: None,
+        }
+    }
+}
+
+/// Parses event totals from a Callgrind output file.
+fn parse(contents: &str) -> Option<CallgrindCounts> {
+    let mut events: Option<Vec<&str>> = None;
+    let mut totals: Option<Vec<u64>> = None;
+
+    for line in contents.lines() {
+        if let Some(names) = line.strip_prefix("events:") {
+            events = Some(names.split_whitespace().collect());
+        } else if let Some(values) =
+            line.strip_prefix("totals:").or_else(|| line.strip_prefix("summary:"))
+        {
+            totals = values.split_whitespace().map(|value| value.parse().ok()).collect();
+        }
+    }
+
+    let (events, totals) = (events?, totals?);
+
+    // Trailing zero totals may be omitted.
+    let get = |name: &str| -> u64 {
+        events
+            .iter()
+            .position(|&event| event == name)
+            .and_then(|index| totals.get(index).copied())
+            .unwrap_or_default()
+    };
+
+    if !events.contains(&"Ir") {
+        return None;
+    }
+
+    Some(CallgrindCounts {
+        instructions: get("Ir"),
+        data_reads: get("Dr"),
+        data_writes: get("Dw"),
+        l1_misses: get("I1mr") + get("D1mr") + get("D1mw"),
+        ll_misses: get("ILmr") + get("DLmr") + get("DLmw"),
+        branches: get("Bc") + get("Bi"),
+        branch_misses: get("Bcm") + get("Bim"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_totals() {
+        let contents = "\
+# callgrind format
+version: 1
+creator: callgrind-3.22.0
+pid: 1234
+cmd:  target/release/deps/example-1234
+part: 1
+
+positions: line
+events: Ir Dr Dw I1mr D1mr D1mw ILmr DLmr DLmw Bc Bcm Bi Bim
+summary: 1000 300 200 10 20 5 2 3 1 150 12 4 1
+
+fl=(1) src/main.rs
+fn=(1) main
+1 1000 300 200 10 20 5 2 3 1 150 12 4 1
+
+totals: 1000 300 200 10 20 5 2 3 1 150 12 4 1
+";
+
+        let counts = parse(contents).unwrap();
+
+        assert_eq!(
+            counts,
+            CallgrindCounts {
+                instructions: 1000,
+                data_reads: 300,
+                data_writes: 200,
+                l1_misses: 35,
+                ll_misses: 6,
+                branches: 154,
+                branch_misses: 13,
+            }
+        );
+
+        // 1465 L1 hits + 5 * 29 LL hits + 35 * 6 LL misses.
+        assert_eq!(counts.estimated_cycles(), 1465 + 5 * 29 + 35 * 6);
+    }
+
+    #[test]
+    fn parse_missing_totals() {
+        assert_eq!(parse("events: Ir\n"), None);
+        assert_eq!(parse("totals: 1\n"), None);
+    }
+
+    /// Tests that client requests are no-ops when not running under Valgrind.
+    #[test]
+    fn toggle_collect_outside_valgrind() {
+        toggle_collect();
+        toggle_collect();
+    }
+}
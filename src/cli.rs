@@ -1,8 +1,13 @@
+use std::path::PathBuf;
+
 use clap::{builder::PossibleValue, value_parser, Arg, ArgAction, ColorChoice, Command, ValueEnum};
 
 use crate::{
-    config::{ParsedSeconds, SortingAttr},
+    config::{MeasureMode, ParsedSeconds, ShuffleSeed, SortingAttr},
+    hw::PerfEvent,
+    message::MessageFormat,
     time::TimerKind,
+    tree_painter::{TreeColumn, TreeStyle},
 };
 
 pub(crate) fn command() -> Command {
@@ -20,19 +25,61 @@ pub(crate) fn command() -> Command {
 
     // Custom arguments not supported by libtest:
     // - bytes-format
+    // - counter-format
+    // - time-unit
+    // - style
+    // - tag
+    // - const
     // - sample-count
     // - sample-size
+    // - sample-time
+    // - max-time-mean
+    // - threads
+    // - warmup-time
+    // - warmup-samples
+    // - streaming-stats
+    // - reservoir-size
+    // - flush-cache
+    // - unroll
     // - timer
     // - sort
     // - sortr
+    // - history
+    // - dashboard
+    // - trend
+    // - merge-reports
+    // - save-baseline
+    // - baseline
+    // - compare
+    // - fail-on-regression
+    // - message-format
+    // - message-output
+    // - output-file
+    // - stats
+    // - mode
+    // - output
+    // - push-metrics
+    // - notify-url
+    // - dump-samples
+    // - dump-samples-parquet
+    // - interleave
+    // - shuffle
+    // - high-priority
+    // - status-signal
+    // - isolate
+    // - seed
+    // - verbose
+    // - summary
+    // - github
 
     // TODO: `--format <pretty|terse>`
 
     Command::new("divan")
         .arg(
             Arg::new("filter")
+                .env("DIVAN_FILTER")
                 .value_name("FILTER")
-                .help("Only run benchmarks whose names match this pattern")
+                .help("Only run benchmarks whose full paths match this pattern, such as '^crate::hash$'")
                 .action(ArgAction::Append),
         )
         .arg(
@@ -43,6 +90,7 @@ pub(crate) fn command() -> Command {
         .arg(flag("list").help("Lists benchmarks").conflicts_with("test"))
         .arg(
             option("color")
+                .env("DIVAN_COLOR")
                 .value_name("WHEN")
                 .help("Controls when to use colors")
                 .value_parser(value_parser!(ColorChoice))
@@ -54,22 +102,69 @@ pub(crate) fn command() -> Command {
                 .value_name("decimal|binary")
                 .value_parser(value_parser!(crate::counter::PrivBytesFormat))
         )
+        .arg(
+            option("counter-format")
+                .env("DIVAN_COUNTER_FORMAT")
+                .help("Show counters as throughput per second or as time per count")
+                .value_name("per-sec|per-iter")
+                .value_parser(value_parser!(crate::counter::PrivCounterFormat))
+        )
+        .arg(
+            option("time-unit")
+                .env("DIVAN_TIME_UNIT")
+                .help("Set a fixed unit for times in output, rather than scaling each value")
+                .value_name("UNIT")
+                .value_parser(value_parser!(crate::time::PrivTimeUnit))
+        )
+        .arg(
+            option("style")
+                .env("DIVAN_STYLE")
+                .value_name("STYLE")
+                .help("Set how the benchmark tree is drawn: Unicode, ASCII, or flat full names")
+                .value_parser(value_parser!(TreeStyle)),
+        )
         .arg(
             option("skip")
+                .env("DIVAN_SKIP")
                 .value_name("FILTER")
                 .help("Skip benchmarks whose names match this pattern")
                 .action(ArgAction::Append),
         )
-        .arg(flag("exact").help("Filter benchmarks by exact name rather than by pattern"))
-        .arg(flag("ignored").help("Run only ignored benchmarks").conflicts_with("include-ignored"))
+        .arg(
+            option("const")
+                .env("DIVAN_CONST")
+                .value_name("VALUE")
+                .help("Only run generic benchmarks instantiated with this const value")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            option("tag")
+                .env("DIVAN_TAG")
+                .value_name("TAG")
+                .help("Only run benchmarks with this tag, in addition to name filters")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            flag("exact")
+                .env("DIVAN_EXACT")
+                .help("Filter benchmarks by exact full path rather than by pattern"),
+        )
+        .arg(
+            flag("ignored")
+                .env("DIVAN_IGNORED")
+                .help("Run only ignored benchmarks")
+                .conflicts_with("include-ignored"),
+        )
         .arg(
             flag("include-ignored")
+                .env("DIVAN_INCLUDE_IGNORED")
                 .help("Run ignored and not-ignored benchmarks")
                 .conflicts_with("ignored"),
         )
         .arg(
             option("sort")
                 .env("DIVAN_SORT")
+                .alias("sort-by")
                 .value_name("ATTRIBUTE")
                 .help("Sort benchmarks in ascending order")
                 .value_parser(value_parser!(SortingAttr))
@@ -86,7 +181,7 @@ pub(crate) fn command() -> Command {
             option("timer")
                 .env("DIVAN_TIMER")
                 .value_name("os|tsc")
-                .help("Set the timer used for measuring samples")
+                .help("Set the timer used for measuring samples ('monotonic' is an alias for 'os')")
                 .value_parser(value_parser!(TimerKind)),
         )
         .arg(
@@ -103,6 +198,13 @@ pub(crate) fn command() -> Command {
                 .help("Set the number of iterations inside a single sample")
                 .value_parser(value_parser!(u32)),
         )
+        .arg(
+            option("sample-time")
+                .env("DIVAN_SAMPLE_TIME")
+                .value_name("SECS")
+                .help("Set the seconds that each sample should take, from which '--sample-size' is chosen")
+                .value_parser(value_parser!(ParsedSeconds)),
+        )
         .arg(
             option("min-time")
                 .env("DIVAN_MIN_TIME")
@@ -117,6 +219,63 @@ pub(crate) fn command() -> Command {
                 .help("Set the maximum seconds spent benchmarking a single function, with priority over '--min-time'")
                 .value_parser(value_parser!(ParsedSeconds)),
         )
+        .arg(
+            option("total-time")
+                .env("DIVAN_TOTAL_TIME")
+                .value_name("SECS")
+                .help("Divide a budget of seconds among all benchmarks, setting the time spent on each")
+                .value_parser(value_parser!(ParsedSeconds)),
+        )
+        .arg(
+            option("max-time-mean")
+                .env("DIVAN_MAX_TIME_MEAN")
+                .value_name("SECS")
+                .help("Fail the run if a benchmark's mean time per iteration exceeds this many seconds")
+                .value_parser(value_parser!(ParsedSeconds)),
+        )
+        .arg(
+            option("until-stable")
+                .env("DIVAN_UNTIL_STABLE")
+                .value_name("CV%")
+                .help("Collect samples until their coefficient of variation is at most this percentage, with '--sample-count' as the minimum")
+                .value_parser(value_parser!(f64)),
+        )
+        .arg(
+            option("noise-threshold")
+                .env("DIVAN_NOISE_THRESHOLD")
+                .value_name("CV%")
+                .help("Flag benchmarks whose coefficient of variation exceeds this percentage as noisy, or 0 to disable [default: 10]")
+                .value_parser(value_parser!(f64)),
+        )
+        .arg(
+            option("timeout")
+                .env("DIVAN_TIMEOUT")
+                .value_name("SECS")
+                .help("Stop benchmarking a single function after this many seconds and report it as timed out")
+                .value_parser(value_parser!(ParsedSeconds)),
+        )
+        .arg(
+            option("threads")
+                .env("DIVAN_THREADS")
+                .value_name("N")
+                .help("Set the thread counts to benchmark with, separated by commas, where 0 means available parallelism")
+                .value_parser(value_parser!(usize))
+                .value_delimiter(','),
+        )
+        .arg(
+            option("warmup-time")
+                .env("DIVAN_WARMUP_TIME")
+                .value_name("SECS")
+                .help("Set the seconds spent running a single function before recording samples")
+                .value_parser(value_parser!(ParsedSeconds)),
+        )
+        .arg(
+            option("warmup-samples")
+                .env("DIVAN_WARMUP_SAMPLES")
+                .value_name("N")
+                .help("Set the number of samples run and discarded before recording samples")
+                .value_parser(value_parser!(u32)),
+        )
         .arg(
             option("skip-ext-time")
                 .env("DIVAN_SKIP_EXT_TIME")
@@ -125,6 +284,265 @@ pub(crate) fn command() -> Command {
                 .value_parser(value_parser!(bool))
                 .num_args(0..=1),
         )
+        .arg(
+            option("streaming-stats")
+                .env("DIVAN_STREAMING_STATS")
+                .value_name("true|false")
+                .help("Compute statistics incrementally instead of storing every sample, which bounds memory for long runs")
+                .value_parser(value_parser!(bool))
+                .num_args(0..=1),
+        )
+        .arg(
+            option("reservoir-size")
+                .env("DIVAN_RESERVOIR_SIZE")
+                .value_name("N")
+                .help("Set the number of random samples kept by streaming statistics for percentiles and sample output")
+                .value_parser(value_parser!(u32)),
+        )
+        .arg(
+            option("flush-cache")
+                .env("DIVAN_FLUSH_CACHE")
+                .value_name("true|false")
+                .help("Flush CPU caches before each sample, for measuring cold-cache behavior")
+                .value_parser(value_parser!(bool))
+                .num_args(0..=1),
+        )
+        .arg(
+            option("unroll")
+                .env("DIVAN_UNROLL")
+                .value_name("N")
+                .help("Call benchmarked functions N times per sample loop iteration, rounded down to a power of two up to 16")
+                .value_parser(value_parser!(u32).range(1..)),
+        )
+        .arg(
+            option("history")
+                .env("DIVAN_HISTORY")
+                .value_name("PATH")
+                .help("Append results to a JSON Lines history file")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            option("dashboard")
+                .env("DIVAN_DASHBOARD")
+                .value_name("DIR")
+                .help("Generate a static HTML dashboard of results history in this directory")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            option("trend")
+                .env("DIVAN_TREND")
+                .value_name("RUNS")
+                .help("Warn about benchmarks whose mean changed in the same direction over the last RUNS runs in history")
+                .value_parser(value_parser!(u32).range(3..)),
+        )
+        .arg(
+            option("save-baseline")
+                .env("DIVAN_SAVE_BASELINE")
+                .value_name("NAME")
+                .help("Save results under a name for later comparison with '--baseline'"),
+        )
+        .arg(
+            option("baseline")
+                .env("DIVAN_BASELINE")
+                .value_name("NAME")
+                .help("Compare results against a baseline saved with '--save-baseline'"),
+        )
+        .arg(
+            option("compare")
+                .env("DIVAN_COMPARE")
+                .value_name("NAME")
+                .help("Compare results against a baseline and test whether changes are significant")
+                .conflicts_with("baseline"),
+        )
+        .arg(
+            option("merge-reports")
+                .env("DIVAN_MERGE_REPORTS")
+                .value_name("FILE")
+                .help("Compare history files from multiple machines instead of running benchmarks")
+                .value_parser(value_parser!(PathBuf))
+                .num_args(1..)
+                .action(ArgAction::Append),
+        )
+        .arg(
+            option("fail-on-regression")
+                .env("DIVAN_FAIL_ON_REGRESSION")
+                .value_name("PERCENT")
+                .help("Exit with an error if median time increased by more than a percentage relative to '--baseline'")
+                .value_parser(value_parser!(f64)),
+        )
+        .arg(
+            option("message-format")
+                .env("DIVAN_MESSAGE_FORMAT")
+                .value_name("FORMAT")
+                .help("Set the format of benchmark results and '--list' output written to stdout")
+                .value_parser(value_parser!(MessageFormat)),
+        )
+        .arg(
+            option("message-output")
+                .env("DIVAN_MESSAGE_OUTPUT")
+                .value_name("FILE")
+                .help("Write JSON Lines events to a file as benchmarks run")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            option("output-file")
+                .env("DIVAN_OUTPUT_FILE")
+                .value_name("PATH")
+                .help("Write machine-readable output to a file while the tree output prints to stdout: the single '--output', '--dump-samples', or '--dump-samples-parquet' given without a path, or otherwise JSON Lines events")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            flag("hw-counters")
+                .env("DIVAN_HW_COUNTERS")
+                .help("Record CPU cycles, instructions, cache misses, and branch misses via hardware performance counters (Linux, Apple Silicon macOS, and cycles only on Windows)"),
+        )
+        .arg(
+            option("perf-events")
+                .env("DIVAN_PERF_EVENTS")
+                .value_name("EVENTS")
+                .help("Record performance monitoring events by 'perf list' name or raw code like 'r01c2', separated by commas (Linux, Apple Silicon macOS, and cycles only on Windows)")
+                .value_parser(value_parser!(PerfEvent))
+                .value_delimiter(','),
+        )
+        .arg(
+            flag("io-counters")
+                .env("DIVAN_IO_COUNTERS")
+                .help("Record bytes and syscalls read and written, and storage I/O, via '/proc/thread-self/io' (Linux only)"),
+        )
+        .arg(
+            option("stats")
+                .env("DIVAN_STATS")
+                .value_name("STATS")
+                .help("Set which time statistics are shown, separated by commas")
+                .value_parser(value_parser!(TreeColumn))
+                .value_delimiter(','),
+        )
+        .arg(
+            flag("robust-stats")
+                .env("DIVAN_ROBUST_STATS")
+                .help("Show 5%-trimmed and winsorized mean times and the median absolute deviation, which are less skewed by outliers, alongside other time statistics"),
+        )
+        .arg(
+            flag("hdr-histogram")
+                .env("DIVAN_HDR_HISTOGRAM")
+                .help("Record every sample's time per iteration in an HDR histogram for accurate percentiles and export it with JSON messages (requires the 'hdr' feature)"),
+        )
+        .arg(
+            option("mode")
+                .env("DIVAN_MODE")
+                .value_name("time|callgrind|cold-start")
+                .help("Set whether benchmarks are timed, have instructions counted by Valgrind's Callgrind, or have their first call timed in new processes")
+                .value_parser(value_parser!(MeasureMode)),
+        )
+        .arg(
+            option("output")
+                .value_names(["FORMAT", "PATH"])
+                .help("Write results to a file in the given format (csv, markdown, junit, openmetrics), or to '--output-file' if no path is given")
+                .num_args(1..=2)
+                .action(ArgAction::Append),
+        )
+        .arg(
+            option("push-metrics")
+                .env("DIVAN_PUSH_METRICS")
+                .value_name("URL")
+                .help("Push results as OpenMetrics to a Prometheus Pushgateway http:// URL"),
+        )
+        .arg(
+            option("notify-url")
+                .env("DIVAN_NOTIFY_URL")
+                .value_name("URL")
+                .help("Post results as JSON to a plain http:// URL after benchmarks finish, such as a local relay to an HTTPS webhook (requires the 'notify' feature)"),
+        )
+        .arg(
+            option("dump-samples")
+                .env("DIVAN_DUMP_SAMPLES")
+                .value_name("PATH")
+                .help("Write every recorded sample to a JSON Lines file, or to '--output-file' if no path is given")
+                .value_parser(value_parser!(PathBuf))
+                .num_args(0..=1),
+        )
+        .arg(
+            option("dump-samples-parquet")
+                .env("DIVAN_DUMP_SAMPLES_PARQUET")
+                .value_name("PATH")
+                .help("Write every recorded sample to a Parquet file, or to '--output-file' if no path is given (requires the 'parquet' feature)")
+                .value_parser(value_parser!(PathBuf))
+                .num_args(0..=1),
+        )
+        .arg(
+            option("profile")
+                .env("DIVAN_PROFILE")
+                .value_name("PATH")
+                .help("Write the time range of each benchmark for filtering 'perf record -k mono' profiles with '--time' (Linux only)")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            option("resume")
+                .env("DIVAN_RESUME")
+                .value_name("PATH")
+                .help("Checkpoint results to a file and skip benchmarks completed by an interrupted run")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            flag("interleave")
+                .env("DIVAN_INTERLEAVE")
+                .help("Have benchmarks in the same group take turns recording samples"),
+        )
+        .arg(
+            option("shuffle")
+                .env("DIVAN_SHUFFLE")
+                .value_name("SEED")
+                .help("Run benchmarks in a random order, optionally with a specific seed")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("")
+                .value_parser(value_parser!(ShuffleSeed)),
+        )
+        .arg(
+            option("runs")
+                .env("DIVAN_RUNS")
+                .value_name("N")
+                .help("Run the entire suite N times and aggregate results across runs")
+                .value_parser(value_parser!(u32).range(1..)),
+        )
+        .arg(
+            flag("high-priority")
+                .env("DIVAN_HIGH_PRIORITY")
+                .help("Raise the scheduling priority of the benchmarking process"),
+        )
+        .arg(
+            flag("status-signal")
+                .env("DIVAN_STATUS_SIGNAL")
+                .help("Print the running benchmark's status on SIGUSR1, or SIGINFO on BSD and macOS"),
+        )
+        .arg(
+            flag("isolate")
+                .env("DIVAN_ISOLATE")
+                .help("Run each benchmark in a separate process"),
+        )
+        .arg(
+            flag("verbose")
+                .short('v')
+                .env("DIVAN_VERBOSE")
+                .help("Print timer calibration and environment details before benchmarking"),
+        )
+        .arg(
+            flag("github")
+                .env("DIVAN_GITHUB")
+                .help("Annotate problems for GitHub Actions and write a Markdown job summary"),
+        )
+        .arg(
+            flag("summary")
+                .env("DIVAN_SUMMARY")
+                .help("Print the geometric mean time of each group and of all benchmarks"),
+        )
+        .arg(
+            option("seed")
+                .env("DIVAN_SEED")
+                .value_name("SEED")
+                .help("Set the seed for randomly generated benchmark inputs")
+                .value_parser(value_parser!(u64)),
+        )
         // ignored:
         .args([ignored_flag("bench"), ignored_flag("nocapture"), ignored_flag("show-output")])
 }
@@ -135,17 +553,59 @@ impl ValueEnum for TimerKind {
     }
 
     fn to_possible_value(&self) -> Option<PossibleValue> {
-        let name = match self {
-            Self::Os => "os",
-            Self::Tsc => "tsc",
-        };
-        Some(PossibleValue::new(name))
+        let value = PossibleValue::new(self.name());
+
+        Some(match self {
+            // The OS timer is monotonic.
+            Self::Os => value.alias("monotonic"),
+            Self::Tsc => value,
+        })
+    }
+}
+
+impl ValueEnum for TreeColumn {
+    fn value_variants<'a>() -> &'a [Self] {
+        &Self::TIME_STATS
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(PossibleValue::new(self.name()))
+    }
+}
+
+impl ValueEnum for TreeStyle {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Unicode, Self::Ascii, Self::Flat]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(PossibleValue::new(self.name()))
+    }
+}
+
+impl ValueEnum for MessageFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Human, Self::JsonLines]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(PossibleValue::new(self.name()))
+    }
+}
+
+impl ValueEnum for MeasureMode {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Time, Self::Callgrind, Self::ColdStart]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(PossibleValue::new(self.name()))
     }
 }
 
 impl ValueEnum for SortingAttr {
     fn value_variants<'a>() -> &'a [Self] {
-        &[Self::Kind, Self::Name, Self::Location]
+        &[Self::Kind, Self::Name, Self::Location, Self::Time]
     }
 
     fn to_possible_value(&self) -> Option<PossibleValue> {
@@ -153,6 +613,7 @@ impl ValueEnum for SortingAttr {
             Self::Kind => "kind",
             Self::Name => "name",
             Self::Location => "location",
+            Self::Time => "time",
         };
         Some(PossibleValue::new(name))
     }
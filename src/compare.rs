@@ -0,0 +1,259 @@
+//! Comparison of results recorded on different machines.
+//!
+//! Reports are results history files (see [`crate::history`]) collected from
+//! each machine. They are merged into a benchmark × machine matrix of mean
+//! times, where each machine's time is also shown relative to the first
+//! machine.
+
+use std::{collections::BTreeMap, fmt::Write, io, path::Path};
+
+use crate::history::HistoryRecord;
+
+/// Latest results of a single machine.
+#[derive(Debug)]
+pub(crate) struct MachineResults {
+    /// Machine name and architecture.
+    pub label: String,
+
+    /// The latest record of each benchmark, keyed by path.
+    pub records: BTreeMap<String, HistoryRecord>,
+}
+
+/// Reads report files and merges them into per-machine results.
+///
+/// Records without a machine name are attributed to the file they came from.
+pub(crate) fn read_reports(paths: &[impl AsRef<Path>]) -> io::Result<Vec<MachineResults>> {
+    let mut reports = Vec::with_capacity(paths.len());
+
+    for path in paths {
+        let path = path.as_ref();
+        let records = crate::history::read(path).map_err(|error| {
+            io::Error::new(error.kind(), format!("{}: {error}", path.display()))
+        })?;
+
+        let file_label = path.file_stem().unwrap_or(path.as_os_str()).to_string_lossy();
+        reports.push((file_label.into_owned(), records));
+    }
+
+    Ok(merge(reports))
+}
+
+/// Merges records into per-machine results, ordered by first appearance.
+pub(crate) fn merge(reports: Vec<(String, Vec<HistoryRecord>)>) -> Vec<MachineResults> {
+    let mut machines: Vec<MachineResults> = Vec::new();
+
+    for (file_label, records) in reports {
+        for record in records {
+            let name = record.machine.as_deref().unwrap_or(&file_label);
+            let label = match &record.arch {
+                Some(arch) => format!("{name} ({arch})"),
+                None => name.to_owned(),
+            };
+
+            let machine = match machines.iter().position(|m| m.label == label) {
+                Some(i) => &mut machines[i],
+                None => {
+                    machines.push(MachineResults { label, records: BTreeMap::new() });
+                    machines.last_mut().unwrap()
+                }
+            };
+
+            // Keep only the latest record of each benchmark.
+            match machine.records.get(&record.path) {
+                Some(existing) if existing.timestamp > record.timestamp => {}
+                _ => {
+                    machine.records.insert(record.path.clone(), record);
+                }
+            }
+        }
+    }
+
+    machines
+}
+
+/// Returns the geometric mean of each machine's time relative to the first
+/// machine, over benchmarks present on both.
+///
+/// This is a hint for normalizing results across machines: dividing a
+/// machine's times by its factor makes them comparable to the first machine.
+pub(crate) fn relative_speeds(machines: &[MachineResults]) -> Vec<Option<f64>> {
+    let Some(baseline) = machines.first() else {
+        return Vec::new();
+    };
+
+    machines
+        .iter()
+        .map(|machine| {
+            let mut log_sum = 0.0;
+            let mut count = 0;
+
+            for (path, record) in &machine.records {
+                let Some(base) = baseline.records.get(path) else { continue };
+                let (time, base_time) = (record.time.mean.picos, base.time.mean.picos);
+                if time == 0 || base_time == 0 {
+                    continue;
+                }
+
+                log_sum += (time as f64 / base_time as f64).ln();
+                count += 1;
+            }
+
+            (count != 0).then(|| (log_sum / count as f64).exp())
+        })
+        .collect()
+}
+
+/// Renders a benchmark × machine matrix of mean times.
+pub(crate) fn render(machines: &[MachineResults]) -> String {
+    const SEPARATOR: &str = " │ ";
+
+    let Some(baseline) = machines.first() else {
+        return "No results to compare.\n".to_owned();
+    };
+
+    let mut paths: Vec<&str> =
+        machines.iter().flat_map(|m| m.records.keys()).map(String::as_str).collect();
+    paths.sort_unstable();
+    paths.dedup();
+
+    // Cells are rendered first to compute column widths.
+    let mut header = vec!["benchmark".to_owned()];
+    header.extend(machines.iter().map(|m| m.label.clone()));
+
+    let mut rows: Vec<Vec<String>> = vec![header];
+
+    for path in paths {
+        let mut row = vec![path.to_owned()];
+        let base = baseline.records.get(path).map(|r| r.time.mean);
+
+        for machine in machines {
+            let Some(record) = machine.records.get(path) else {
+                row.push("-".to_owned());
+                continue;
+            };
+
+            let time = record.time.mean;
+            let cell = match base {
+                Some(base) if base.picos != 0 && !std::ptr::eq(machine, baseline) => {
+                    format!("{time} ({:.2}×)", time.picos as f64 / base.picos as f64)
+                }
+                _ => time.to_string(),
+            };
+            row.push(cell);
+        }
+
+        rows.push(row);
+    }
+
+    let mut speeds_row = vec!["relative".to_owned()];
+    speeds_row.extend(relative_speeds(machines).into_iter().map(|speed| match speed {
+        Some(speed) => format!("{speed:.2}×"),
+        None => "-".to_owned(),
+    }));
+    rows.push(speeds_row);
+
+    let column_count = machines.len() + 1;
+    let widths: Vec<usize> = (0..column_count)
+        .map(|i| rows.iter().map(|row| row[i].chars().count()).max().unwrap_or_default())
+        .collect();
+
+    let mut result = String::new();
+    for (row_index, row) in rows.iter().enumerate() {
+        // Separate the summary row.
+        if row_index == rows.len() - 1 {
+            let rule: Vec<String> = widths.iter().map(|&w| "─".repeat(w)).collect();
+            _ = writeln!(result, "{}", rule.join("─┼─"));
+        }
+
+        let mut line = String::new();
+        for (i, cell) in row.iter().enumerate() {
+            if i != 0 {
+                line.push_str(SEPARATOR);
+            }
+            _ = write!(line, "{cell:<width$}", width = widths[i]);
+        }
+        result.push_str(line.trim_end());
+        result.push('\n');
+
+        if row_index == 0 {
+            let rule: Vec<String> = widths.iter().map(|&w| "─".repeat(w)).collect();
+            _ = writeln!(result, "{}", rule.join("─┼─"));
+        }
+    }
+
+    if machines.len() > 1 {
+        _ = writeln!(
+            result,
+            "\nTimes are relative to '{}'. The 'relative' row is the geometric mean over shared \
+             benchmarks; divide a machine's times by it to normalize for overall machine speed.",
+            baseline.label
+        );
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{stats::StatsSet, time::FineDuration};
+
+    fn record(path: &str, machine: Option<&str>, timestamp: u64, mean: u128) -> HistoryRecord {
+        let mean = FineDuration { picos: mean };
+        HistoryRecord {
+            path: path.to_owned(),
+            commit: None,
+            timestamp,
+            machine: machine.map(ToOwned::to_owned),
+            arch: machine.map(|_| "x86_64".to_owned()),
+            env: None,
+            seed: None,
+            sample_count: 1,
+            iter_count: 1,
+            time: StatsSet { fastest: mean, slowest: mean, median: mean, mean },
+        }
+    }
+
+    #[test]
+    fn merge_latest() {
+        let machines = merge(vec![
+            (
+                "a".to_owned(),
+                vec![record("x", Some("alpha"), 2, 100), record("x", Some("alpha"), 1, 999)],
+            ),
+            ("b".to_owned(), vec![record("x", None, 1, 200), record("y", None, 1, 300)]),
+        ]);
+
+        assert_eq!(machines.len(), 2);
+        assert_eq!(machines[0].label, "alpha (x86_64)");
+        assert_eq!(machines[0].records["x"].time.mean.picos, 100);
+        assert_eq!(machines[1].label, "b");
+        assert_eq!(machines[1].records.len(), 2);
+    }
+
+    #[test]
+    fn relative() {
+        let machines = merge(vec![
+            ("a".to_owned(), vec![record("x", None, 1, 100), record("y", None, 1, 100)]),
+            (
+                "b".to_owned(),
+                vec![
+                    record("x", None, 1, 200),
+                    record("y", None, 1, 800),
+                    // Not on baseline machine, so not considered.
+                    record("z", None, 1, 1),
+                ],
+            ),
+            ("c".to_owned(), vec![record("z", None, 1, 1)]),
+        ]);
+
+        let speeds = relative_speeds(&machines);
+        assert_eq!(speeds[0], Some(1.0));
+        assert!((speeds[1].unwrap() - 4.0).abs() < 1e-9);
+        assert_eq!(speeds[2], None);
+
+        let matrix = render(&machines);
+        assert!(matrix.contains("(8.00×)"), "{matrix}");
+        assert!(matrix.contains("relative"), "{matrix}");
+    }
+}
@@ -34,3 +34,22 @@
 //! #[divan::bench(types = [], consts = ['a', 'b', 'c'])]
 //! fn bench<T, const C: i32>() {}
 //! ```
+//!
+//! # Range Limits
+//!
+//! Each value of a `consts` range is a separate instantiation, so large ranges
+//! are rejected rather than slowing compilation to a crawl.
+//!
+//! ```compile_fail
+//! #[divan::bench(consts = 0..1_000_000)]
+//! fn bench<const N: usize>() {}
+//! ```
+//!
+//! # Group-Only Options
+//!
+//! `time_budget` only makes sense for groups.
+//!
+//! ```compile_fail
+//! #[divan::bench(max_time = 1, time_budget)]
+//! fn bench() {}
+//! ```
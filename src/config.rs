@@ -1,6 +1,5 @@
 use std::{error::Error, str::FromStr, time::Duration};
 
-pub use clap::ColorChoice;
 use regex::Regex;
 
 /// `Duration` wrapper for parsing seconds from the CLI.
@@ -15,6 +14,22 @@ impl FromStr for ParsedSeconds {
     }
 }
 
+/// Seed for `--shuffle`, which is random if not provided.
+#[derive(Clone, Copy)]
+pub(crate) struct ShuffleSeed(pub Option<u64>);
+
+impl FromStr for ShuffleSeed {
+    type Err = Box<dyn Error + Send + Sync>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            Ok(Self(None))
+        } else {
+            Ok(Self(Some(u64::from_str(s)?)))
+        }
+    }
+}
+
 /// The primary action to perform.
 #[derive(Clone, Copy, Default)]
 pub(crate) enum Action {
@@ -47,9 +62,40 @@ impl Action {
     }
 }
 
+/// How benchmarks are measured.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum MeasureMode {
+    /// Time samples of many iterations.
+    #[default]
+    Time,
+
+    /// Count instructions of a single iteration under Valgrind's Callgrind.
+    Callgrind,
+
+    /// Time the first iteration in each of many spawned processes.
+    ColdStart,
+}
+
+impl MeasureMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Time => "time",
+            Self::Callgrind => "callgrind",
+            Self::ColdStart => "cold-start",
+        }
+    }
+}
+
 /// Filters which benchmark to run based on name.
+///
+/// Filters are matched against each benchmark's full `::`-separated path, such
+/// as `crate::group::bench`, and not against its parent groups' paths.
 pub(crate) enum Filter {
+    /// Matches paths containing the pattern anywhere, so `hash` also matches
+    /// `crate::hash_map`. Anchors like `^crate::hash$` match the whole path.
     Regex(Regex),
+
+    /// Matches paths equal to the string, set with `--exact`.
     Exact(String),
 }
 
@@ -95,6 +141,42 @@ impl RunIgnored {
     }
 }
 
+/// The format of a results file written with `--output`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    Csv,
+    Markdown,
+    Junit,
+    OpenMetrics,
+}
+
+impl OutputFormat {
+    pub const ALL: [Self; 4] = [Self::Csv, Self::Markdown, Self::Junit, Self::OpenMetrics];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Markdown => "markdown",
+            Self::Junit => "junit",
+            Self::OpenMetrics => "openmetrics",
+        }
+    }
+
+    /// Returns the name of the format for use in messages.
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Csv => "CSV",
+            Self::Markdown => "Markdown",
+            Self::Junit => "JUnit XML",
+            Self::OpenMetrics => "OpenMetrics",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| format.name() == name)
+    }
+}
+
 /// The attribute to sort benchmarks by.
 #[derive(Clone, Copy, Default)]
 pub(crate) enum SortingAttr {
@@ -107,6 +189,10 @@ pub(crate) enum SortingAttr {
 
     /// Sort by location, then by kind and name.
     Location,
+
+    /// Sort benchmarks by mean time after measuring, and otherwise by kind and
+    /// name.
+    Time,
 }
 
 impl SortingAttr {
@@ -119,6 +205,34 @@ impl SortingAttr {
             Kind => [self, Name, Location],
             Name => [self, Location, Kind],
             Location => [self, Kind, Name],
+            Time => [self, Kind, Name],
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_is_match() {
+        let regex = |pattern: &str| Filter::Regex(Regex::new(pattern).unwrap());
+
+        // Patterns match anywhere in the path.
+        assert!(regex("hash").is_match("crate::hash"));
+        assert!(regex("hash").is_match("crate::hash_map"));
+        assert!(regex("hash").is_match("crate::group::rehash"));
+
+        // Anchors match the whole path.
+        assert!(regex("^crate::hash$").is_match("crate::hash"));
+        assert!(!regex("^crate::hash$").is_match("crate::hash_map"));
+        assert!(!regex("^hash$").is_match("crate::hash"));
+
+        // Exact names must equal the whole path.
+        let exact = Filter::Exact("crate::hash".to_owned());
+        assert!(exact.is_match("crate::hash"));
+        assert!(!exact.is_match("crate::hash_map"));
+        assert!(!exact.is_match("crate::hash::sip"));
+        assert!(!exact.is_match("hash"));
+    }
+}
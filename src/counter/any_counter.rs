@@ -1,19 +1,31 @@
 use std::{any::TypeId, fmt};
 
 use crate::{
-    counter::{BytesCount, BytesFormat, CharsCount, IntoCounter, ItemsCount, MaxCountUInt},
+    counter::{
+        BytesCount, BytesFormat, CharsCount, Count, CounterFormat, CustomCount, IntoCounter,
+        ItemsCount,
+    },
     time::FineDuration,
     util,
 };
 
 /// Type-erased `Counter`.
-///
-/// This does not implement `Copy` because in the future it will contain
-/// user-defined counters.
 #[derive(Clone)]
 pub(crate) struct AnyCounter {
-    kind: KnownCounterKind,
-    count: MaxCountUInt,
+    kind: AnyCounterKind,
+    count: Count,
+
+    /// Whether `count` is multiplied by the benchmark's generic `const`.
+    per_const: bool,
+}
+
+/// Kind of [`AnyCounter`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum AnyCounterKind {
+    Known(KnownCounterKind),
+
+    /// [`CustomCount`] with its unit.
+    Custom(&'static str),
 }
 
 impl AnyCounter {
@@ -21,34 +33,43 @@ impl AnyCounter {
     pub(crate) fn new<C: IntoCounter>(counter: C) -> Self {
         let counter = counter.into_counter();
 
-        if let Some(bytes) = util::cast_ref::<BytesCount>(&counter) {
-            Self::bytes(bytes.count)
+        let (counter, per_const) = if let Some(bytes) = util::cast_ref::<BytesCount>(&counter) {
+            (Self::bytes(bytes.count), bytes.per_const)
         } else if let Some(chars) = util::cast_ref::<CharsCount>(&counter) {
-            Self::chars(chars.count)
+            (Self::chars(chars.count), chars.per_const)
         } else if let Some(items) = util::cast_ref::<ItemsCount>(&counter) {
-            Self::items(items.count)
+            (Self::items(items.count), items.per_const)
+        } else if let Some(custom) = util::cast_ref::<CustomCount>(&counter) {
+            (Self::custom(custom.unit, custom.count), custom.per_const)
         } else {
             unreachable!()
-        }
+        };
+
+        Self { per_const, ..counter }
+    }
+
+    #[inline]
+    pub(crate) fn known(kind: KnownCounterKind, count: Count) -> Self {
+        Self { kind: AnyCounterKind::Known(kind), count, per_const: false }
     }
 
     #[inline]
-    pub(crate) fn known(kind: KnownCounterKind, count: MaxCountUInt) -> Self {
-        Self { kind, count }
+    pub(crate) fn custom(unit: &'static str, count: Count) -> Self {
+        Self { kind: AnyCounterKind::Custom(unit), count, per_const: false }
     }
 
     #[inline]
-    pub(crate) fn bytes(count: MaxCountUInt) -> Self {
+    pub(crate) fn bytes(count: Count) -> Self {
         Self::known(KnownCounterKind::Bytes, count)
     }
 
     #[inline]
-    pub(crate) fn chars(count: MaxCountUInt) -> Self {
+    pub(crate) fn chars(count: Count) -> Self {
         Self::known(KnownCounterKind::Chars, count)
     }
 
     #[inline]
-    pub(crate) fn items(count: MaxCountUInt) -> Self {
+    pub(crate) fn items(count: Count) -> Self {
         Self::known(KnownCounterKind::Items, count)
     }
 
@@ -56,23 +77,36 @@ impl AnyCounter {
         &self,
         duration: FineDuration,
         bytes_format: BytesFormat,
-    ) -> DisplayThroughput {
-        DisplayThroughput { counter: self, picos: duration.picos as f64, bytes_format }
+        counter_format: CounterFormat,
+    ) -> DisplayThroughput<'_> {
+        DisplayThroughput {
+            counter: self,
+            picos: duration.picos as f64,
+            bytes_format,
+            counter_format,
+        }
     }
 
     #[inline]
-    pub(crate) fn count(&self) -> MaxCountUInt {
+    pub(crate) fn count(&self) -> Count {
         self.count
     }
 
     #[inline]
-    pub(crate) fn known_kind(&self) -> KnownCounterKind {
+    pub(crate) fn kind(&self) -> AnyCounterKind {
         self.kind
     }
+
+    /// Whether this was created with `of_const`, and thus must be multiplied
+    /// by the benchmark's generic `const` value.
+    #[inline]
+    pub(crate) fn is_per_const(&self) -> bool {
+        self.per_const
+    }
 }
 
 /// Kind of `Counter` defined by this crate.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub(crate) enum KnownCounterKind {
     Bytes,
     Chars,
@@ -87,25 +121,38 @@ impl KnownCounterKind {
     /// The maximum width for columns displaying counters.
     pub const MAX_COMMON_COLUMN_WIDTH: usize = "1.111 Kitem/s".len();
 
+    /// The plural name of the counted unit.
     #[inline]
-    pub fn of<C: IntoCounter>() -> Self {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Bytes => "bytes",
+            Self::Chars => "chars",
+            Self::Items => "items",
+        }
+    }
+
+    /// Returns the kind of `C`, or `None` if it is [`CustomCount`].
+    #[inline]
+    pub fn of<C: IntoCounter>() -> Option<Self> {
         let id = TypeId::of::<C::Counter>();
         if id == TypeId::of::<BytesCount>() {
-            Self::Bytes
+            Some(Self::Bytes)
         } else if id == TypeId::of::<CharsCount>() {
-            Self::Chars
+            Some(Self::Chars)
         } else if id == TypeId::of::<ItemsCount>() {
-            Self::Items
+            Some(Self::Items)
         } else {
-            unreachable!()
+            None
         }
     }
 }
 
+/// Displays throughput, or time per count with [`CounterFormat::PerIter`].
 pub(crate) struct DisplayThroughput<'a> {
     counter: &'a AnyCounter,
     picos: f64,
     bytes_format: BytesFormat,
+    counter_format: CounterFormat,
 }
 
 impl fmt::Debug for DisplayThroughput<'_> {
@@ -116,38 +163,80 @@ impl fmt::Debug for DisplayThroughput<'_> {
 
 impl fmt::Display for DisplayThroughput<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sig_figs = f.precision().unwrap_or(4);
+
+        let mut str = match self.counter_format {
+            CounterFormat::PerSec => self.per_sec_string(sig_figs),
+            CounterFormat::PerIter => self.per_count_string(sig_figs),
+        };
+
+        // Fill up to specified width.
+        if let Some(fill_len) = f.width().and_then(|width| width.checked_sub(str.len())) {
+            match f.align() {
+                None | Some(fmt::Alignment::Left) => {
+                    str.extend(std::iter::repeat_n(f.fill(), fill_len));
+                }
+                _ => return Err(fmt::Error),
+            }
+        }
+
+        f.write_str(&str)
+    }
+}
+
+impl DisplayThroughput<'_> {
+    /// Formats the counts processed per second, such as "1.5 MB/s".
+    fn per_sec_string(&self, sig_figs: usize) -> String {
         let picos = self.picos;
         let count = self.counter.count();
-        let count_per_sec = if count == 0 { 0. } else { count as f64 * (1e12 / picos) };
+        let count_per_sec = if count.is_zero() { 0. } else { count.as_f64() * (1e12 / picos) };
 
         let (scales, suffixes) = match self.counter.kind {
-            KnownCounterKind::Bytes => match self.bytes_format {
+            AnyCounterKind::Known(KnownCounterKind::Bytes) => match self.bytes_format {
                 BytesFormat::Binary => (scale::BINARY_SCALES, scale::BYTES_BINARY_SUFFIXES),
                 BytesFormat::Decimal => (scale::DECIMAL_SCALES, scale::BYTES_DECIMAL_SUFFIXES),
             },
-            KnownCounterKind::Chars => (scale::DECIMAL_SCALES, scale::CHARS_SUFFIXES),
-            KnownCounterKind::Items => (scale::DECIMAL_SCALES, scale::ITEMS_SUFFIXES),
+            AnyCounterKind::Known(KnownCounterKind::Chars) => {
+                (scale::DECIMAL_SCALES, scale::CHARS_SUFFIXES)
+            }
+            AnyCounterKind::Known(KnownCounterKind::Items) => {
+                (scale::DECIMAL_SCALES, scale::ITEMS_SUFFIXES)
+            }
+            AnyCounterKind::Custom(_) => (scale::DECIMAL_SCALES, scale::DECIMAL_PREFIXES),
         };
 
         let (val, suffix) = scale_throughput(count_per_sec, scales, suffixes);
 
-        let sig_figs = f.precision().unwrap_or(4);
-
         let mut str = util::format_f64(val, sig_figs);
         str.push(' ');
         str.push_str(suffix);
 
-        // Fill up to specified width.
-        if let Some(fill_len) = f.width().and_then(|width| width.checked_sub(str.len())) {
-            match f.align() {
-                None | Some(fmt::Alignment::Left) => {
-                    str.extend(std::iter::repeat(f.fill()).take(fill_len));
-                }
-                _ => return Err(fmt::Error),
-            }
+        // Custom suffixes are the unit with a metric prefix.
+        if let AnyCounterKind::Custom(unit) = self.counter.kind {
+            str.push_str(unit);
+            str.push_str("/s");
         }
 
-        f.write_str(&str)
+        str
+    }
+
+    /// Formats the time spent per count, such as "1.5 ns/B".
+    fn per_count_string(&self, sig_figs: usize) -> String {
+        let unit = match self.counter.kind {
+            AnyCounterKind::Known(KnownCounterKind::Bytes) => "B",
+            AnyCounterKind::Known(KnownCounterKind::Chars) => "char",
+            AnyCounterKind::Known(KnownCounterKind::Items) => "item",
+            AnyCounterKind::Custom(unit) => unit,
+        };
+
+        match self.counter.count() {
+            // Nothing was processed, so there is no cost per count.
+            Count::Int(0) => format!("- /{unit}"),
+            count => {
+                let picos = (self.picos / count.as_f64()).round() as u128;
+                format!("{:.sig_figs$}/{unit}", FineDuration { picos })
+            }
+        }
     }
 }
 
@@ -203,11 +292,15 @@ mod scale {
 
     pub const ITEMS_SUFFIXES: &Suffixes =
         &["item/s", "Kitem/s", "Mitem/s", "Gitem/s", "Titem/s", "Pitem/s"];
+
+    /// Prefixes for [`CustomCount`](crate::counter::CustomCount) units.
+    pub const DECIMAL_PREFIXES: &Suffixes = &["", "K", "M", "G", "T", "P"];
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::counter::MaxCountUInt;
 
     mod display_throughput {
         use super::*;
@@ -226,8 +319,12 @@ mod tests {
                     (BytesFormat::Decimal, expected_decimal),
                 ] {
                     assert_eq!(
-                        AnyCounter::bytes(bytes)
-                            .display_throughput(FineDuration { picos }, bytes_format)
+                        AnyCounter::bytes(Count::Int(bytes))
+                            .display_throughput(
+                                FineDuration { picos },
+                                bytes_format,
+                                CounterFormat::default()
+                            )
                             .to_string(),
                         expected
                     );
@@ -252,8 +349,12 @@ mod tests {
             #[track_caller]
             fn test(chars: MaxCountUInt, picos: u128, expected: &str) {
                 assert_eq!(
-                    AnyCounter::chars(chars)
-                        .display_throughput(FineDuration { picos }, BytesFormat::default())
+                    AnyCounter::chars(Count::Int(chars))
+                        .display_throughput(
+                            FineDuration { picos },
+                            BytesFormat::default(),
+                            CounterFormat::default(),
+                        )
                         .to_string(),
                     expected
                 );
@@ -272,8 +373,12 @@ mod tests {
             #[track_caller]
             fn test(items: MaxCountUInt, picos: u128, expected: &str) {
                 assert_eq!(
-                    AnyCounter::items(items)
-                        .display_throughput(FineDuration { picos }, BytesFormat::default())
+                    AnyCounter::items(Count::Int(items))
+                        .display_throughput(
+                            FineDuration { picos },
+                            BytesFormat::default(),
+                            CounterFormat::default(),
+                        )
                         .to_string(),
                     expected
                 );
@@ -286,5 +391,64 @@ mod tests {
             test(0, 1, "0 item/s");
             test(0, u128::MAX, "0 item/s");
         }
+
+        #[test]
+        fn custom() {
+            #[track_caller]
+            fn test(count: MaxCountUInt, picos: u128, expected: &str) {
+                assert_eq!(
+                    AnyCounter::custom("packet", Count::Int(count))
+                        .display_throughput(
+                            FineDuration { picos },
+                            BytesFormat::default(),
+                            CounterFormat::default(),
+                        )
+                        .to_string(),
+                    expected
+                );
+            }
+
+            test(1, 0, "inf packet/s");
+            test(0, 1, "0 packet/s");
+            test(1, 1_000_000_000_000, "1 packet/s");
+            test(2_500, 1_000_000_000_000, "2.5 Kpacket/s");
+            test(1, 1_000, "1 Gpacket/s");
+
+            assert_eq!(
+                AnyCounter::custom("GFLOP", Count::Frac(0.5))
+                    .display_throughput(
+                        FineDuration { picos: 1_000_000_000_000 },
+                        BytesFormat::default(),
+                        CounterFormat::default(),
+                    )
+                    .to_string(),
+                "0.5 GFLOP/s"
+            );
+        }
+
+        #[test]
+        fn per_iter() {
+            #[track_caller]
+            fn test(counter: AnyCounter, picos: u128, expected: &str) {
+                assert_eq!(
+                    counter
+                        .display_throughput(
+                            FineDuration { picos },
+                            BytesFormat::Binary,
+                            CounterFormat::PerIter,
+                        )
+                        .to_string(),
+                    expected
+                );
+            }
+
+            test(AnyCounter::bytes(Count::Int(0)), 1_000, "- /B");
+            test(AnyCounter::bytes(Count::Int(1)), 0, "0 ns/B");
+            test(AnyCounter::bytes(Count::Int(1_000)), 1_500_000, "1.5 ns/B");
+            test(AnyCounter::chars(Count::Int(4)), 2_000, "0.5 ns/char");
+            test(AnyCounter::items(Count::Int(2)), 3_000_000_000, "1.5 ms/item");
+            test(AnyCounter::custom("req", Count::Int(10)), 250_000_000, "25 µs/req");
+            test(AnyCounter::items(Count::Frac(2.5)), 5_000, "2 ns/item");
+        }
     }
 }
@@ -1,19 +1,25 @@
-use crate::counter::{AnyCounter, IntoCounter, KnownCounterKind, MaxCountUInt};
+use crate::counter::{AnyCounter, AnyCounterKind, Count, IntoCounter, KnownCounterKind};
+
+const PER_CONST_MESSAGE: &str =
+    "`of_const` counters can only be used with the `counters` benchmark option";
 
 /// Multi-map from counters to their counts and input-based initializer.
 #[derive(Default)]
 pub(crate) struct CounterCollection {
     info: [KnownCounterInfo; KnownCounterKind::COUNT],
+
+    /// Constant counts of `CustomCount` keyed by unit, in insertion order.
+    custom: Vec<(&'static str, Count)>,
 }
 
 #[derive(Default)]
 struct KnownCounterInfo {
     // TODO: Inlinable vector.
-    counts: Vec<MaxCountUInt>,
+    counts: Vec<Count>,
 
     /// `BencherConfig::with_inputs` can only be called once, so the input type
     /// cannot change.
-    count_input: Option<Box</* unsafe */ dyn Fn(*const ()) -> MaxCountUInt + Sync>>,
+    count_input: Option<Box</* unsafe */ dyn Fn(*const ()) -> Count + Sync>>,
 }
 
 impl CounterCollection {
@@ -28,16 +34,12 @@ impl CounterCollection {
     }
 
     #[inline]
-    pub(crate) fn counts(&self, counter_kind: KnownCounterKind) -> &[MaxCountUInt] {
+    pub(crate) fn counts(&self, counter_kind: KnownCounterKind) -> &[Count] {
         &self.info(counter_kind).counts
     }
 
-    pub(crate) fn mean_count(&self, counter_kind: KnownCounterKind) -> MaxCountUInt {
-        let counts = self.counts(counter_kind);
-
-        let sum: u128 = counts.iter().map(|&c| c as u128).sum();
-
-        (sum / counts.len() as u128) as MaxCountUInt
+    pub(crate) fn mean_count(&self, counter_kind: KnownCounterKind) -> Count {
+        Count::mean(self.counts(counter_kind))
     }
 
     #[inline]
@@ -45,9 +47,27 @@ impl CounterCollection {
         self.info(counter_kind).count_input.is_some()
     }
 
+    /// Returns the constant counts of custom counters.
+    #[inline]
+    pub(crate) fn custom_counts(&self) -> &[(&'static str, Count)] {
+        &self.custom
+    }
+
+    #[track_caller]
     pub(crate) fn set_counter(&mut self, counter: AnyCounter) {
+        assert!(!counter.is_per_const(), "{PER_CONST_MESSAGE}");
+
         let new_count = counter.count();
-        let info = self.info_mut(counter.known_kind());
+
+        let counter_kind = match counter.kind() {
+            AnyCounterKind::Known(counter_kind) => counter_kind,
+            AnyCounterKind::Custom(unit) => {
+                set_custom_count(&mut self.custom, unit, new_count);
+                return;
+            }
+        };
+
+        let info = self.info_mut(counter_kind);
 
         if let Some(old_count) = info.counts.first_mut() {
             *old_count = new_count;
@@ -56,8 +76,8 @@ impl CounterCollection {
         }
     }
 
-    pub(crate) fn push_counter(&mut self, counter: AnyCounter) {
-        self.info_mut(counter.known_kind()).counts.push(counter.count());
+    pub(crate) fn push_count(&mut self, counter_kind: KnownCounterKind, count: Count) {
+        self.info_mut(counter_kind).counts.push(count);
     }
 
     /// Set the input-based count generator function for a counter.
@@ -66,7 +86,11 @@ impl CounterCollection {
         F: Fn(&I) -> C + Sync + 'static,
         C: IntoCounter,
     {
-        let info = self.info_mut(KnownCounterKind::of::<C::Counter>());
+        let Some(counter_kind) = KnownCounterKind::of::<C::Counter>() else {
+            panic!("`CustomCount` cannot be used as an input counter");
+        };
+
+        let info = self.info_mut(counter_kind);
 
         // Ignore previously-set counts.
         info.counts.clear();
@@ -76,7 +100,9 @@ impl CounterCollection {
             // is passed.
             let counter = unsafe { make_counter(&*input.cast::<I>()) };
 
-            AnyCounter::new(counter).count()
+            let counter = AnyCounter::new(counter);
+            assert!(!counter.is_per_const(), "{PER_CONST_MESSAGE}");
+            counter.count()
         }));
     }
 
@@ -90,44 +116,106 @@ impl CounterCollection {
         &self,
         counter_kind: KnownCounterKind,
         input: &I,
-    ) -> Option<MaxCountUInt> {
+    ) -> Option<Count> {
         let from_input = self.info(counter_kind).count_input.as_ref()?;
 
         // SAFETY: The caller ensures that this is called on the same input type
         // used for calling `set_input_counter`.
         Some(unsafe { from_input(input as *const I as *const ()) })
     }
-
-    /// Removes counts that came from input.
-    pub(crate) fn clear_input_counts(&mut self) {
-        for info in &mut self.info {
-            if info.count_input.is_some() {
-                info.counts.clear();
-            }
-        }
-    }
 }
 
-/// A set of known and (future) custom counters.
+/// A set of known and custom counters.
 #[derive(Clone, Debug, Default)]
 pub struct CounterSet {
-    counts: [Option<MaxCountUInt>; KnownCounterKind::COUNT],
+    counts: [Option<Count>; KnownCounterKind::COUNT],
+    custom: Vec<(&'static str, Count)>,
+
+    /// Counters created with `of_const`, whose counts are multiplied by the
+    /// benchmark's generic `const` value.
+    per_const: Vec<AnyCounterKind>,
 }
 
 impl CounterSet {
     pub fn with(mut self, counter: impl IntoCounter) -> Self {
         let counter = AnyCounter::new(counter);
-        self.counts[counter.known_kind() as usize] = Some(counter.count());
+        match counter.kind() {
+            AnyCounterKind::Known(counter_kind) => {
+                self.counts[counter_kind as usize] = Some(counter.count());
+            }
+            AnyCounterKind::Custom(unit) => {
+                set_custom_count(&mut self.custom, unit, counter.count());
+            }
+        }
+
+        self.per_const.retain(|&kind| kind != counter.kind());
+        if counter.is_per_const() {
+            self.per_const.push(counter.kind());
+        }
+
         self
     }
 
-    pub(crate) fn get(&self, counter_kind: KnownCounterKind) -> Option<MaxCountUInt> {
+    /// Returns `true` if any counter was created with `of_const`.
+    pub(crate) fn has_per_const(&self) -> bool {
+        !self.per_const.is_empty()
+    }
+
+    /// Multiplies counts created with `of_const` by `value`, or removes them
+    /// if there is no `const` value.
+    pub(crate) fn resolve_const(&self, value: Option<f64>) -> Self {
+        let mut result = Self { per_const: Vec::new(), ..self.clone() };
+
+        for &kind in &self.per_const {
+            match kind {
+                AnyCounterKind::Known(kind) => {
+                    let count = &mut result.counts[kind as usize];
+                    *count = value.zip(*count).map(|(value, count)| count.scale(value));
+                }
+                AnyCounterKind::Custom(unit) => match value {
+                    Some(value) => {
+                        for (_, count) in result.custom.iter_mut().filter(|(u, _)| *u == unit) {
+                            *count = count.scale(value);
+                        }
+                    }
+                    None => result.custom.retain(|(u, _)| *u != unit),
+                },
+            }
+        }
+
+        result
+    }
+
+    pub(crate) fn get(&self, counter_kind: KnownCounterKind) -> Option<Count> {
         self.counts[counter_kind as usize]
     }
 
+    /// Returns the counts of custom counters.
+    pub(crate) fn custom_counts(&self) -> &[(&'static str, Count)] {
+        &self.custom
+    }
+
     /// Overwrites `other` with values set in `self`.
     pub(crate) fn overwrite(&self, other: &Self) -> Self {
-        Self { counts: KnownCounterKind::ALL.map(|kind| self.get(kind).or(other.get(kind))) }
+        let mut custom = other.custom.clone();
+        for &(unit, count) in &self.custom {
+            set_custom_count(&mut custom, unit, count);
+        }
+
+        // Keep `other`'s `of_const` counters only if not replaced by `self`.
+        let is_set = |kind: AnyCounterKind| match kind {
+            AnyCounterKind::Known(kind) => self.get(kind).is_some(),
+            AnyCounterKind::Custom(unit) => self.custom.iter().any(|(u, _)| *u == unit),
+        };
+        let per_const = (other.per_const.iter().copied().filter(|&kind| !is_set(kind)))
+            .chain(self.per_const.iter().copied())
+            .collect();
+
+        Self {
+            counts: KnownCounterKind::ALL.map(|kind| self.get(kind).or(other.get(kind))),
+            custom,
+            per_const,
+        }
     }
 
     pub(crate) fn to_collection(&self) -> CounterCollection {
@@ -136,6 +224,15 @@ impl CounterSet {
                 counts: self.get(kind).into_iter().collect(),
                 count_input: None,
             }),
+            custom: self.custom.clone(),
         }
     }
 }
+
+/// Sets the count for `unit`, replacing any previous count.
+fn set_custom_count(custom: &mut Vec<(&'static str, Count)>, unit: &'static str, count: Count) {
+    match custom.iter_mut().find(|(u, _)| *u == unit) {
+        Some((_, old_count)) => *old_count = count,
+        None => custom.push((unit, count)),
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    cmp::Ordering,
+    ops::{Add, AddAssign},
+};
+
+use crate::{counter::MaxCountUInt, json::Json};
+
+/// Number of values processed per iteration.
+///
+/// Integer counts are kept exact, and only fractional counts use `f64`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Count {
+    Int(MaxCountUInt),
+
+    /// A finite non-negative count that is not representable as `Int`.
+    Frac(f64),
+}
+
+/// Sum of [`Count`s](Count) over many iterations.
+///
+/// Integer totals are `u128` so that they cannot overflow when summing counts
+/// for averaging.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum CountTotal {
+    Int(u128),
+    Frac(f64),
+}
+
+impl Count {
+    /// Converts a finite non-negative count, which is `Int` if integral.
+    #[inline]
+    pub fn from_f64(count: f64) -> Self {
+        if count.fract() == 0.0 && count < MaxCountUInt::MAX as f64 {
+            Self::Int(count as MaxCountUInt)
+        } else {
+            Self::Frac(count)
+        }
+    }
+
+    #[inline]
+    pub fn from_json(json: &Json) -> Option<Self> {
+        match json.as_u128() {
+            Some(count) => Some(Self::Int(count.try_into().ok()?)),
+            None => Some(Self::from_f64(json.as_f64()?)),
+        }
+    }
+
+    #[inline]
+    pub fn as_f64(self) -> f64 {
+        match self {
+            Self::Int(count) => count as f64,
+            Self::Frac(count) => count,
+        }
+    }
+
+    #[inline]
+    pub fn is_zero(self) -> bool {
+        matches!(self, Self::Int(0))
+    }
+
+    /// Multiplies by a non-negative `factor`, exactly if both are integers.
+    pub fn scale(self, factor: f64) -> Self {
+        match (self, Self::from_f64(factor)) {
+            (Self::Int(count), Self::Int(factor)) => count
+                .checked_mul(factor)
+                .map_or(Self::Frac(count as f64 * factor as f64), Self::Int),
+            _ => Self::from_f64(self.as_f64() * factor),
+        }
+    }
+
+    /// Returns the total over `n` iterations.
+    #[inline]
+    pub fn times(self, n: u64) -> CountTotal {
+        match self {
+            Self::Int(count) => CountTotal::Int(count as u128 * n as u128),
+            Self::Frac(count) => CountTotal::Frac(count * n as f64),
+        }
+    }
+
+    /// Returns the mean of `counts`.
+    pub fn mean(counts: &[Self]) -> Self {
+        let total = counts.iter().fold(CountTotal::ZERO, |total, &count| total + count.into());
+        total.per(counts.len() as u64)
+    }
+}
+
+impl CountTotal {
+    pub const ZERO: Self = Self::Int(0);
+
+    #[inline]
+    pub fn as_f64(self) -> f64 {
+        match self {
+            Self::Int(total) => total as f64,
+            Self::Frac(total) => total,
+        }
+    }
+
+    /// Returns the count per iteration over `n` iterations.
+    #[inline]
+    pub fn per(self, n: u64) -> Count {
+        let n = n.max(1);
+        match self {
+            // Cannot overflow `MaxCountUInt` because the total cannot exceed
+            // `MaxCountUInt::MAX * n`.
+            Self::Int(total) => Count::Int((total / n as u128) as MaxCountUInt),
+            Self::Frac(total) => Count::from_f64(total / n as f64),
+        }
+    }
+}
+
+impl From<Count> for CountTotal {
+    #[inline]
+    fn from(count: Count) -> Self {
+        count.times(1)
+    }
+}
+
+impl Add for CountTotal {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, other: Self) -> Self {
+        match (self, other) {
+            // Saturating add in case `MaxCountUInt > u64`.
+            (Self::Int(a), Self::Int(b)) => Self::Int(a.saturating_add(b)),
+            (a, b) => Self::Frac(a.as_f64() + b.as_f64()),
+        }
+    }
+}
+
+impl AddAssign for CountTotal {
+    #[inline]
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+// Counts are never NaN, so they have a total order.
+impl PartialEq for Count {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Count {}
+
+impl PartialOrd for Count {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Count {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (*self, *other) {
+            (Self::Int(a), Self::Int(b)) => a.cmp(&b),
+            (a, b) => a.as_f64().total_cmp(&b.as_f64()),
+        }
+    }
+}
+
+impl From<Count> for Json {
+    #[inline]
+    fn from(count: Count) -> Self {
+        match count {
+            Count::Int(count) => count.into(),
+            Count::Frac(count) => count.into(),
+        }
+    }
+}
+
+impl From<CountTotal> for Json {
+    #[inline]
+    fn from(total: CountTotal) -> Self {
+        match total {
+            CountTotal::Int(total) => total.into(),
+            CountTotal::Frac(total) => total.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mean() {
+        let max = MaxCountUInt::MAX;
+        assert_eq!(Count::mean(&[Count::Int(max), Count::Int(max - 2)]), Count::Int(max - 1));
+        assert_eq!(Count::mean(&[Count::Int(1), Count::Frac(2.5)]), Count::Frac(1.75));
+        assert_eq!(Count::mean(&[Count::Frac(0.5), Count::Frac(1.5)]), Count::Int(1));
+    }
+
+    #[test]
+    fn scale() {
+        let large = 1 << 60;
+        assert_eq!(Count::Int(large + 1).scale(4.0), Count::Int(4 * large + 4));
+        assert_eq!(Count::Int(3).scale(0.5), Count::Frac(1.5));
+        assert_eq!(Count::Frac(2.5).scale(2.0), Count::Int(5));
+    }
+
+    #[test]
+    fn cmp() {
+        assert!(Count::Frac(0.5) < Count::Int(1));
+        assert!(Count::Frac(1.5) > Count::Int(1));
+        assert!(Count::Int(MaxCountUInt::MAX) > Count::Int(MaxCountUInt::MAX - 1));
+    }
+}
@@ -1,11 +1,11 @@
-use crate::counter::Counter;
+use crate::counter::{Counter, ItemsCount};
 
 /// Conversion into a [`Counter`].
 ///
 /// # Examples
 ///
-/// This trait is implemented for unsigned integers over
-/// [`ItemsCount`](crate::counter::ItemsCount):
+/// This trait is implemented for unsigned integers and `f64` over
+/// [`ItemsCount`]:
 ///
 /// ```
 /// #[divan::bench]
@@ -36,3 +36,13 @@ impl<C: Counter> IntoCounter for C {
         self
     }
 }
+
+impl IntoCounter for f64 {
+    type Counter = ItemsCount;
+
+    #[inline]
+    #[track_caller]
+    fn into_counter(self) -> ItemsCount {
+        ItemsCount::from_f64(self)
+    }
+}
@@ -29,13 +29,15 @@ use std::{any::Any, mem};
 
 mod any_counter;
 mod collection;
+mod count;
 mod into_counter;
 mod sealed;
 mod uint;
 
 pub(crate) use self::{
-    any_counter::{AnyCounter, KnownCounterKind},
+    any_counter::{AnyCounter, AnyCounterKind, KnownCounterKind},
     collection::{CounterCollection, CounterSet},
+    count::{Count, CountTotal},
     sealed::Sealed,
     uint::{CountUInt, MaxCountUInt},
 };
@@ -55,7 +57,10 @@ pub trait Counter: Sized + Any + Sealed {}
 /// Process N bytes.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct BytesCount {
-    count: MaxCountUInt,
+    count: Count,
+
+    /// Whether `count` is multiplied by the benchmark's generic `const`.
+    per_const: bool,
 }
 
 /// Process N [`char`s](char).
@@ -65,35 +70,105 @@ pub struct BytesCount {
 /// reference.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct CharsCount {
-    count: MaxCountUInt,
+    count: Count,
+
+    /// Whether `count` is multiplied by the benchmark's generic `const`.
+    per_const: bool,
 }
 
 /// Process N items.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ItemsCount {
-    count: MaxCountUInt,
+    count: Count,
+
+    /// Whether `count` is multiplied by the benchmark's generic `const`.
+    per_const: bool,
+}
+
+/// Process N user-defined units, such as packets or queries.
+///
+/// Throughput is displayed with the unit and a metric prefix, such as
+/// "1.5 Kpacket/s". A benchmark can have multiple custom counters as long as
+/// their units differ.
+///
+/// # Examples
+///
+/// ```
+/// use divan::counter::CustomCount;
+///
+/// #[divan::bench(counters = [
+///     CustomCount::new("packet", 4u32),
+///     CustomCount::new("query", 2u32),
+/// ])]
+/// fn handle_packets() {
+///     // ...
+/// }
+/// ```
+///
+/// Custom counters cannot be used with
+/// [`Bencher::input_counter`](crate::Bencher::input_counter).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CustomCount {
+    unit: &'static str,
+    count: Count,
+
+    /// Whether `count` is multiplied by the benchmark's generic `const`.
+    per_const: bool,
 }
 
 impl Sealed for BytesCount {}
 impl Sealed for CharsCount {}
 impl Sealed for ItemsCount {}
+impl Sealed for CustomCount {}
 
 impl Counter for BytesCount {}
 impl Counter for CharsCount {}
 impl Counter for ItemsCount {}
+impl Counter for CustomCount {}
+
+/// Returns `count` if it is usable as a counter count.
+#[inline]
+#[track_caller]
+fn checked_f64_count(count: f64) -> Count {
+    assert!(count.is_finite() && count >= 0.0, "counts must be finite and non-negative: {count}");
+    Count::from_f64(count)
+}
 
 impl BytesCount {
     /// Count N bytes.
     #[inline]
     pub fn new<N: CountUInt>(count: N) -> Self {
-        Self { count: count.into_max_uint() }
+        Self { count: Count::Int(count.into_max_uint()), per_const: false }
+    }
+
+    /// Count a possibly fractional number of bytes, such as the average
+    /// number of bytes processed per iteration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is negative, infinite, or NaN.
+    #[inline]
+    #[track_caller]
+    pub fn from_f64(count: f64) -> Self {
+        Self { count: checked_f64_count(count), per_const: false }
+    }
+
+    /// Count bytes equal to the benchmark's generic `const` value, such as
+    /// `N` in [`consts = [64, 1024]`](macro@crate::bench#consts).
+    ///
+    /// This is meant for the [`counters`](macro@crate::bench#counters) option
+    /// of benchmarks with numeric `const` values, and cannot be passed to
+    /// [`Bencher::counter`](crate::Bencher::counter).
+    #[inline]
+    pub const fn of_const() -> Self {
+        Self { count: Count::Int(1), per_const: true }
     }
 
     /// Counts the size of a type with [`std::mem::size_of`].
     #[inline]
     #[doc(alias = "size_of")]
     pub const fn of<T>() -> Self {
-        Self { count: mem::size_of::<T>() as MaxCountUInt }
+        Self { count: Count::Int(mem::size_of::<T>() as MaxCountUInt), per_const: false }
     }
 
     /// Counts the size of multiple instances of a type with
@@ -102,7 +177,7 @@ impl BytesCount {
     #[doc(alias = "size_of")]
     pub const fn of_many<T>(n: usize) -> Self {
         match (mem::size_of::<T>() as MaxCountUInt).checked_mul(n as MaxCountUInt) {
-            Some(count) => Self { count },
+            Some(count) => Self { count: Count::Int(count), per_const: false },
             None => panic!("overflow"),
         }
     }
@@ -112,7 +187,7 @@ impl BytesCount {
     #[doc(alias = "size_of_val")]
     pub fn of_val<T: ?Sized>(val: &T) -> Self {
         // TODO: Make const, https://github.com/rust-lang/rust/issues/46571
-        Self { count: mem::size_of_val(val) as MaxCountUInt }
+        Self { count: Count::Int(mem::size_of_val(val) as MaxCountUInt), per_const: false }
     }
 
     /// Counts the bytes of [`Iterator::Item`s](Iterator::Item).
@@ -181,7 +256,30 @@ impl CharsCount {
     /// Count N [`char`s](char).
     #[inline]
     pub fn new<N: CountUInt>(count: N) -> Self {
-        Self { count: count.into_max_uint() }
+        Self { count: Count::Int(count.into_max_uint()), per_const: false }
+    }
+
+    /// Count a possibly fractional number of [`char`s](char), such as the
+    /// average number of `char`s processed per iteration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is negative, infinite, or NaN.
+    #[inline]
+    #[track_caller]
+    pub fn from_f64(count: f64) -> Self {
+        Self { count: checked_f64_count(count), per_const: false }
+    }
+
+    /// Count [`char`s](char) equal to the benchmark's generic `const` value, such as
+    /// `N` in [`consts = [64, 1024]`](macro@crate::bench#consts).
+    ///
+    /// This is meant for the [`counters`](macro@crate::bench#counters) option
+    /// of benchmarks with numeric `const` values, and cannot be passed to
+    /// [`Bencher::counter`](crate::Bencher::counter).
+    #[inline]
+    pub const fn of_const() -> Self {
+        Self { count: Count::Int(1), per_const: true }
     }
 
     /// Counts the [`char`s](prim@char) of a [`&str`](prim@str).
@@ -195,7 +293,30 @@ impl ItemsCount {
     /// Count N items.
     #[inline]
     pub fn new<N: CountUInt>(count: N) -> Self {
-        Self { count: count.into_max_uint() }
+        Self { count: Count::Int(count.into_max_uint()), per_const: false }
+    }
+
+    /// Count a possibly fractional number of items, such as the average number
+    /// of items processed per iteration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is negative, infinite, or NaN.
+    #[inline]
+    #[track_caller]
+    pub fn from_f64(count: f64) -> Self {
+        Self { count: checked_f64_count(count), per_const: false }
+    }
+
+    /// Count items equal to the benchmark's generic `const` value, such as
+    /// `N` in [`consts = [64, 1024]`](macro@crate::bench#consts).
+    ///
+    /// This is meant for the [`counters`](macro@crate::bench#counters) option
+    /// of benchmarks with numeric `const` values, and cannot be passed to
+    /// [`Bencher::counter`](crate::Bencher::counter).
+    #[inline]
+    pub const fn of_const() -> Self {
+        Self { count: Count::Int(1), per_const: true }
     }
 
     /// Counts [`Iterator::Item`s](Iterator::Item).
@@ -208,6 +329,45 @@ impl ItemsCount {
     }
 }
 
+impl CustomCount {
+    /// Count N of `unit`.
+    ///
+    /// The unit should be singular, such as "packet" rather than "packets".
+    #[inline]
+    pub fn new<N: CountUInt>(unit: &'static str, count: N) -> Self {
+        Self { unit, count: Count::Int(count.into_max_uint()), per_const: false }
+    }
+
+    /// Count a possibly fractional amount of `unit`, such as FLOPs scaled to
+    /// "GFLOP".
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is negative, infinite, or NaN.
+    #[inline]
+    #[track_caller]
+    pub fn from_f64(unit: &'static str, count: f64) -> Self {
+        Self { unit, count: checked_f64_count(count), per_const: false }
+    }
+
+    /// Count `unit` equal to the benchmark's generic `const` value, such as
+    /// `N` in [`consts = [64, 1024]`](macro@crate::bench#consts).
+    ///
+    /// This is meant for the [`counters`](macro@crate::bench#counters) option
+    /// of benchmarks with numeric `const` values, and cannot be passed to
+    /// [`Bencher::counter`](crate::Bencher::counter).
+    #[inline]
+    pub const fn of_const(unit: &'static str) -> Self {
+        Self { unit, count: Count::Int(1), per_const: true }
+    }
+
+    /// Returns the unit being counted.
+    #[inline]
+    pub const fn unit(&self) -> &'static str {
+        self.unit
+    }
+}
+
 /// The numerical base for [`BytesCount`] in benchmark outputs.
 ///
 /// See [`Divan::bytes_format`](crate::Divan::bytes_format) for more info.
@@ -222,6 +382,40 @@ pub enum BytesFormat {
     Binary,
 }
 
+/// How counters are displayed in benchmark outputs.
+///
+/// See [`Divan::counter_format`](crate::Divan::counter_format) for more info.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum CounterFormat {
+    /// Throughput as counts processed per second, such as "1.5 MB/s". This is
+    /// the default.
+    #[default]
+    PerSec,
+
+    /// Cost as time spent per count, such as "666.6 ps/B".
+    PerIter,
+}
+
+/// Private `CounterFormat` that prevents leaking trait implementations we
+/// don't want to publicly commit to.
+#[derive(Clone, Copy)]
+pub(crate) struct PrivCounterFormat(pub CounterFormat);
+
+impl clap::ValueEnum for PrivCounterFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self(CounterFormat::PerSec), Self(CounterFormat::PerIter)]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        let name = match self.0 {
+            CounterFormat::PerSec => "per-sec",
+            CounterFormat::PerIter => "per-iter",
+        };
+        Some(clap::builder::PossibleValue::new(name))
+    }
+}
+
 /// Private `BytesFormat` that prevents leaking trait implementations we don't
 /// want to publicly commit to.
 #[derive(Clone, Copy)]
@@ -252,5 +446,23 @@ mod tests {
         fn of_iter() {
             assert_eq!(BytesCount::of_iter::<i32, _>([1, 2, 3]), BytesCount::of_slice(&[1, 2, 3]));
         }
+
+        #[test]
+        fn from_f64() {
+            assert_eq!(BytesCount::from_f64(12.0), BytesCount::new(12u32));
+            assert!(BytesCount::from_f64(1.5) > BytesCount::new(1u32));
+        }
+
+        #[test]
+        #[should_panic = "counts must be finite and non-negative"]
+        fn from_f64_nan() {
+            _ = BytesCount::from_f64(f64::NAN);
+        }
+
+        /// Integers beyond `f64` precision must stay distinct.
+        #[test]
+        fn new_exact() {
+            assert_ne!(BytesCount::new(MaxCountUInt::MAX), BytesCount::new(MaxCountUInt::MAX - 1));
+        }
     }
 }
@@ -0,0 +1,246 @@
+//! CSV export of benchmark results.
+//!
+//! Each row is a single benchmark leaf. Times are nanoseconds per iteration
+//! with picosecond precision, and throughputs are counts per second. Counter
+//! columns are only present if any benchmark uses that counter. The last
+//! columns describe the commit and machine that produced the results.
+
+use std::{fmt::Write as _, fs, io, path::Path};
+
+use crate::{
+    counter::{Count, KnownCounterKind},
+    environment::Environment,
+    history::RunInfo,
+    results::{BenchResults, EntryResult},
+    stats::TIME_PERCENTILES,
+    time::FineDuration,
+};
+
+/// Writes results to a CSV file at `path`.
+pub(crate) fn write(path: &Path, results: &BenchResults, run: &RunInfo) -> io::Result<()> {
+    fs::write(path, render(results, run))
+}
+
+/// Renders results as CSV with a header row.
+pub(crate) fn render(results: &BenchResults, run: &RunInfo) -> String {
+    let entries: Vec<&EntryResult> = results.entries().collect();
+
+    let known_counters: Vec<KnownCounterKind> = KnownCounterKind::ALL
+        .into_iter()
+        .filter(|&kind| entries.iter().any(|entry| entry.stats.get_counts(kind).is_some()))
+        .collect();
+
+    let mut custom_units: Vec<&str> = Vec::new();
+    for entry in &entries {
+        for &(unit, _) in &entry.stats.custom_counts {
+            if !custom_units.contains(&unit) {
+                custom_units.push(unit);
+            }
+        }
+    }
+
+    let mut csv = String::new();
+
+    // Header:
+    let mut header: Vec<String> = ["path", "name", "type", "const", "samples", "iters"]
+        .into_iter()
+        .chain(["fastest_ns", "slowest_ns", "median_ns", "mean_ns"])
+        .map(str::to_owned)
+        .collect();
+
+    header.extend(TIME_PERCENTILES.iter().map(|percentile| format!("p{percentile}_ns")));
+
+    let counter_names = known_counters
+        .iter()
+        .map(|kind| kind.name().to_owned())
+        .chain(custom_units.iter().map(|unit| format!("{unit}s")));
+
+    for name in counter_names {
+        header.push(format!("{name}_per_sec_median"));
+        header.push(format!("{name}_per_sec_mean"));
+    }
+
+    header.push("commit".to_owned());
+    header.extend(Environment::CSV_HEADER.map(str::to_owned));
+
+    let run_fields: Vec<String> = Some(run.commit.clone().unwrap_or_default())
+        .into_iter()
+        .chain(run.env.as_ref().map(Environment::csv_fields).unwrap_or_default())
+        .collect();
+
+    write_row(&mut csv, header.iter().map(String::as_str));
+
+    // Rows:
+    for entry in entries {
+        let stats = &entry.stats;
+
+        let mut row: Vec<String> = vec![
+            entry.path.clone(),
+            entry.name.clone(),
+            entry.generic_type.clone().unwrap_or_default(),
+            entry.generic_const.clone().unwrap_or_default(),
+            stats.sample_count.to_string(),
+            stats.iter_count.to_string(),
+        ];
+
+        let time = &stats.time;
+        row.extend([time.fastest, time.slowest, time.median, time.mean].map(format_nanos));
+        row.extend(stats.time_percentiles.map(format_nanos));
+
+        let known_counts = known_counters.iter().map(|&kind| stats.get_counts(kind));
+        let custom_counts = custom_units.iter().map(|unit| {
+            stats.custom_counts.iter().find(|(u, _)| u == unit).map(|(_, counts)| counts)
+        });
+
+        for counts in known_counts.chain(custom_counts) {
+            match counts {
+                Some(counts) => {
+                    row.push(format_throughput(counts.median, time.median));
+                    row.push(format_throughput(counts.mean, time.mean));
+                }
+                None => row.extend([String::new(), String::new()]),
+            }
+        }
+
+        row.extend(run_fields.iter().cloned());
+
+        write_row(&mut csv, row.iter().map(String::as_str));
+    }
+
+    csv
+}
+
+/// Writes a line of fields, quoting fields as needed.
+fn write_row<'a>(csv: &mut String, fields: impl Iterator<Item = &'a str>) {
+    for (i, field) in fields.enumerate() {
+        if i > 0 {
+            csv.push(',');
+        }
+
+        if field.contains([',', '"', '\n', '\r']) {
+            csv.push('"');
+            csv.push_str(&field.replace('"', "\"\""));
+            csv.push('"');
+        } else {
+            csv.push_str(field);
+        }
+    }
+    csv.push('\n');
+}
+
+/// Formats a duration as nanoseconds without losing precision.
+fn format_nanos(duration: FineDuration) -> String {
+    let picos = duration.picos;
+    let mut result = (picos / 1_000).to_string();
+
+    let frac = picos % 1_000;
+    if frac != 0 {
+        _ = write!(result, ".{frac:03}");
+        result.truncate(result.trim_end_matches('0').len());
+    }
+
+    result
+}
+
+/// Formats the number of counts processed per second.
+fn format_throughput(count: Count, time: FineDuration) -> String {
+    if time.picos == 0 {
+        return String::new();
+    }
+
+    let per_sec = count.as_f64() * 1e12 / time.picos as f64;
+    per_sec.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        counter::MaxCountUInt,
+        results::ResultTree,
+        stats::{Stats, StatsSet},
+    };
+
+    fn entry(path: &str, generic: Option<&str>, median: u128, bytes: Option<u64>) -> ResultTree {
+        let time = |picos| FineDuration { picos };
+
+        let mut counts = [None; KnownCounterKind::COUNT];
+        counts[KnownCounterKind::Bytes as usize] = bytes.map(|bytes| {
+            let bytes = Count::Int(bytes as MaxCountUInt);
+            StatsSet { fastest: bytes, slowest: bytes, median: bytes, mean: bytes }
+        });
+
+        ResultTree::Leaf(EntryResult {
+            name: path.rsplit("::").next().unwrap().to_owned(),
+            path: path.to_owned(),
+            generic_type: generic.map(ToOwned::to_owned),
+            generic_const: None,
+            stats: Stats {
+                counts,
+                ..Stats::test_default(
+                    100,
+                    1_000,
+                    StatsSet {
+                        fastest: time(median / 2),
+                        slowest: time(median * 2),
+                        median: time(median),
+                        mean: time(median),
+                    },
+                )
+            },
+            sample_times: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn render_rows() {
+        let results = BenchResults {
+            tree: vec![
+                entry("a::b", None, 1_500, None),
+                ResultTree::Parent {
+                    name: "c".to_owned(),
+                    children: vec![entry(
+                        "a::c::HashMap<K, V>",
+                        Some("HashMap<K, V>"),
+                        2_000,
+                        Some(4),
+                    )],
+                },
+            ],
+            failures: Vec::new(),
+            errors: Vec::new(),
+            throttled: Vec::new(),
+            noisy: Vec::new(),
+            bimodal: Vec::new(),
+        };
+
+        let run = RunInfo {
+            commit: Some("abc123".to_owned()),
+            env: Some(Environment {
+                cpu: Some("Test CPU, 3GHz".to_owned()),
+                cores: Some(8),
+                ..Environment::default()
+            }),
+            ..RunInfo::default()
+        };
+
+        assert_eq!(
+            render(&results, &run),
+            "\
+path,name,type,const,samples,iters,fastest_ns,slowest_ns,median_ns,mean_ns,p75_ns,p90_ns,p95_ns,p99_ns,bytes_per_sec_median,bytes_per_sec_mean,commit,cpu,cores,governor,os,rustc,profile
+a::b,b,,,100,1000,0.75,3,1.5,1.5,1.5,1.5,1.5,1.5,,,abc123,\"Test CPU, 3GHz\",8,,,,
+\"a::c::HashMap<K, V>\",\"HashMap<K, V>\",\"HashMap<K, V>\",,100,1000,1,4,2,2,2,2,2,2,2000000000,2000000000,abc123,\"Test CPU, 3GHz\",8,,,,
+"
+        );
+    }
+
+    #[test]
+    fn format_nanos() {
+        let nanos = |picos| super::format_nanos(FineDuration { picos });
+
+        assert_eq!(nanos(0), "0");
+        assert_eq!(nanos(1), "0.001");
+        assert_eq!(nanos(1_230), "1.23");
+        assert_eq!(nanos(12_000), "12");
+    }
+}
@@ -0,0 +1,288 @@
+//! Static HTML dashboard generated from results history.
+//!
+//! The output is a single self-contained `index.html` with inline SVG charts,
+//! so it can be published as-is (e.g. to GitHub Pages) without any scripts or
+//! external services.
+
+use std::{collections::BTreeMap, fmt::Write, fs, io, path::Path};
+
+use crate::{history::HistoryRecord, time::FineDuration};
+
+const CHART_WIDTH: f64 = 640.;
+const CHART_HEIGHT: f64 = 200.;
+
+/// Chart padding: top, right, bottom, left.
+const CHART_PAD: [f64; 4] = [12., 12., 28., 76.];
+
+const STYLE: &str = "\
+body{font-family:system-ui,sans-serif;margin:2em auto;max-width:720px;color:#222}\
+h1{font-size:1.5em}h2{font-size:1em;font-family:ui-monospace,monospace;margin-bottom:.25em}\
+section{margin-bottom:2em}svg{display:block;background:#fafafa;border:1px solid #ddd}\
+.axis{stroke:#999}.mean{fill:none;stroke:#1f6feb;stroke-width:2}.point{fill:#1f6feb}\
+.label{font-size:11px;fill:#555}.latest{font-size:.85em;color:#555}";
+
+/// Writes `index.html` into `dir`, creating the directory if needed.
+pub(crate) fn write(dir: &Path, records: &[HistoryRecord]) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join("index.html"), render(records))
+}
+
+/// Renders the dashboard HTML.
+pub(crate) fn render(records: &[HistoryRecord]) -> String {
+    let runs = Run::collect(records);
+
+    // Sorted by path for stable output.
+    let mut benches = BTreeMap::<&str, Vec<(usize, &HistoryRecord)>>::new();
+    for record in records {
+        let run_index = runs.iter().position(|run| run.matches(record)).unwrap_or_default();
+        benches.entry(&record.path).or_default().push((run_index, record));
+    }
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>Benchmarks</title>\n");
+    _ = writeln!(html, "<style>{STYLE}</style>\n</head>\n<body>\n<h1>Benchmarks</h1>");
+
+    match runs.last() {
+        None => html.push_str("<p>No results recorded yet.</p>\n"),
+        Some(latest) => {
+            _ = write!(html, "<p>{} benchmarks over {} runs", benches.len(), runs.len());
+            if let Some(commit) = latest.commit {
+                _ = write!(html, ", latest commit <code>{}</code>", escape(short_commit(commit)));
+            }
+            html.push_str(".</p>\n");
+
+            let latest_env = records
+                .iter()
+                .filter(|record| latest.matches(record))
+                .find_map(|record| record.env.as_ref());
+
+            if let Some(env) = latest_env {
+                _ = writeln!(
+                    html,
+                    "<p class=\"latest\">Latest run on {}.</p>",
+                    escape(&env.summary())
+                );
+            }
+        }
+    }
+
+    for (path, points) in &benches {
+        _ = writeln!(html, "<section>\n<h2>{}</h2>", escape(path));
+        render_chart(&mut html, &runs, points);
+
+        if let Some((_, latest)) = points.last() {
+            let time = &latest.time;
+            _ = writeln!(
+                html,
+                "<p class=\"latest\">latest: fastest {} · median {} · mean {} · slowest {} · {} samples × {} iters</p>",
+                time.fastest,
+                time.median,
+                time.mean,
+                time.slowest,
+                latest.sample_count,
+                latest.iter_count / latest.sample_count.max(1) as u64,
+            );
+        }
+
+        html.push_str("</section>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Renders an SVG chart of mean time over runs.
+fn render_chart(html: &mut String, runs: &[Run], points: &[(usize, &HistoryRecord)]) {
+    let [pad_top, pad_right, pad_bottom, pad_left] = CHART_PAD;
+    let plot_width = CHART_WIDTH - pad_left - pad_right;
+    let plot_height = CHART_HEIGHT - pad_top - pad_bottom;
+
+    let max_picos = points.iter().map(|(_, r)| r.time.mean.picos).max().unwrap_or_default();
+
+    // Leave headroom above the highest point.
+    let y_max = (max_picos as f64 * 1.1).max(1.);
+
+    let x_of = |run_index: usize| -> f64 {
+        let x_steps = runs.len().saturating_sub(1).max(1) as f64;
+        pad_left + plot_width * (run_index as f64 / x_steps)
+    };
+    let y_of = |picos: u128| -> f64 { pad_top + plot_height * (1. - picos as f64 / y_max) };
+
+    _ = writeln!(
+        html,
+        "<svg width=\"{CHART_WIDTH}\" height=\"{CHART_HEIGHT}\" viewBox=\"0 0 {CHART_WIDTH} {CHART_HEIGHT}\" role=\"img\">"
+    );
+
+    // Axes.
+    let x_axis_y = pad_top + plot_height;
+    _ = writeln!(
+        html,
+        "<path class=\"axis\" d=\"M{pad_left} {pad_top}V{x_axis_y}H{}\"/>",
+        pad_left + plot_width
+    );
+
+    // Y-axis labels.
+    let y_max_label = FineDuration { picos: y_max as u128 };
+    _ = writeln!(
+        html,
+        "<text class=\"label\" x=\"{}\" y=\"{}\" text-anchor=\"end\">{y_max_label:.3}</text>",
+        pad_left - 6.,
+        pad_top + 4.
+    );
+    _ = writeln!(
+        html,
+        "<text class=\"label\" x=\"{}\" y=\"{x_axis_y}\" text-anchor=\"end\">0</text>",
+        pad_left - 6.
+    );
+
+    // X-axis labels for the first and last runs.
+    let mut x_labels = vec![(0, "start")];
+    if runs.len() > 1 {
+        x_labels.push((runs.len() - 1, "end"));
+    }
+    for (run_index, anchor) in x_labels {
+        let label = runs[run_index].commit.map(short_commit).unwrap_or("?");
+        _ = writeln!(
+            html,
+            "<text class=\"label\" x=\"{:.1}\" y=\"{}\" text-anchor=\"{anchor}\">{}</text>",
+            x_of(run_index),
+            x_axis_y + 18.,
+            escape(label)
+        );
+    }
+
+    // Mean line.
+    html.push_str("<polyline class=\"mean\" points=\"");
+    for (i, (run_index, record)) in points.iter().enumerate() {
+        if i != 0 {
+            html.push(' ');
+        }
+        _ = write!(html, "{:.1},{:.1}", x_of(*run_index), y_of(record.time.mean.picos));
+    }
+    html.push_str("\"/>\n");
+
+    // Points with tooltips.
+    for (run_index, record) in points {
+        _ = writeln!(
+            html,
+            "<circle class=\"point\" cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\"><title>{}: {}</title></circle>",
+            x_of(*run_index),
+            y_of(record.time.mean.picos),
+            escape(record.commit.as_deref().map(short_commit).unwrap_or("unknown commit")),
+            record.time.mean,
+        );
+    }
+
+    html.push_str("</svg>\n");
+}
+
+/// A single invocation that recorded results.
+struct Run<'a> {
+    timestamp: u64,
+    commit: Option<&'a str>,
+}
+
+impl<'a> Run<'a> {
+    /// Returns distinct runs ordered by time.
+    fn collect(records: &'a [HistoryRecord]) -> Vec<Self> {
+        let mut runs: Vec<Self> = Vec::new();
+
+        for record in records {
+            if !runs.iter().any(|run| run.matches(record)) {
+                runs.push(Run { timestamp: record.timestamp, commit: record.commit.as_deref() });
+            }
+        }
+
+        // Stable sort keeps file order for runs with the same timestamp.
+        runs.sort_by_key(|run| run.timestamp);
+        runs
+    }
+
+    fn matches(&self, record: &HistoryRecord) -> bool {
+        self.timestamp == record.timestamp && self.commit == record.commit.as_deref()
+    }
+}
+
+fn short_commit(commit: &str) -> &str {
+    commit.get(..7).unwrap_or(commit)
+}
+
+/// Escapes text for HTML content and attribute values.
+fn escape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#39;"),
+            ch => result.push(ch),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{environment::Environment, stats::StatsSet};
+
+    fn record(path: &str, commit: &str, timestamp: u64, mean: u128) -> HistoryRecord {
+        let mean = FineDuration { picos: mean };
+        HistoryRecord {
+            path: path.to_owned(),
+            commit: Some(commit.to_owned()),
+            timestamp,
+            machine: None,
+            arch: None,
+            env: None,
+            seed: None,
+            sample_count: 10,
+            iter_count: 100,
+            time: StatsSet { fastest: mean, slowest: mean, median: mean, mean },
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let html = render(&[]);
+        assert!(html.contains("No results recorded yet."));
+        assert!(!html.contains("<svg"));
+    }
+
+    #[test]
+    fn charts_per_bench() {
+        let records = [
+            record("sort::<u8>", "aaaaaaaaaa", 1, 1_000),
+            record("hash", "aaaaaaaaaa", 1, 2_000),
+            HistoryRecord {
+                env: Some(Environment {
+                    cpu: Some("Test CPU".to_owned()),
+                    cores: Some(8),
+                    ..Environment::default()
+                }),
+                ..record("sort::<u8>", "bbbbbbbbbb", 2, 1_500)
+            },
+        ];
+
+        let html = render(&records);
+
+        assert_eq!(html.matches("<svg").count(), 2);
+        assert_eq!(html.matches("<circle").count(), 3);
+        assert!(html.contains("2 benchmarks over 2 runs, latest commit <code>bbbbbbb</code>"));
+        assert!(html.contains("Latest run on Test CPU, 8 cores."));
+
+        // Generic arguments are escaped.
+        assert!(html.contains("<h2>sort::&lt;u8&gt;</h2>"));
+
+        // Sorted by path.
+        assert!(html.find("<h2>hash</h2>").unwrap() < html.find("<h2>sort").unwrap());
+    }
+
+    #[test]
+    fn escape() {
+        assert_eq!(super::escape("<a href=\"x\">&'"), "&lt;a href=&quot;x&quot;&gt;&amp;&#39;");
+    }
+}
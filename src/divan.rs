@@ -1,17 +1,56 @@
-use std::{fmt, num::NonZeroUsize, time::Duration};
+use std::{
+    borrow::Cow,
+    cell::{OnceCell, RefCell},
+    collections::HashMap,
+    fmt,
+    io::IsTerminal,
+    num::NonZeroUsize,
+    panic,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
 
 use clap::ColorChoice;
 use regex::Regex;
 
 use crate::{
-    bench::{BenchOptions, Bencher},
-    config::{Action, Filter, ParsedSeconds, RunIgnored, SortingAttr},
-    counter::{BytesFormat, PrivBytesFormat},
-    entry::{AnyBenchEntry, EntryTree},
-    time::{FineDuration, Timer, TimerKind},
-    tree_painter::{TreeColumn, TreePainter},
+    baseline::Baseline,
+    bench::{BenchContext, BenchOptions, Bencher, Turn, Turns, DEFAULT_SAMPLE_COUNT},
+    budget::Limits,
+    callgrind::CallgrindRole,
+    config::{
+        Action, Filter, MeasureMode, OutputFormat, ParsedSeconds, RunIgnored, ShuffleSeed,
+        SortingAttr,
+    },
+    counter::{BytesFormat, CounterFormat, PrivBytesFormat, PrivCounterFormat},
+    entry::{AnyBenchEntry, BenchEntry, EntryTree, GroupEntry},
+    history::{HistoryRecord, RunInfo, Trend},
+    hw::{PerfEvent, MAX_PERF_EVENTS},
+    isolate::IsolateRole,
+    message::{MessageFormat, MessageWriter},
+    profile::ProfileMarkers,
+    profiler::{ProfilerHook, SampleHook, SharedHook},
+    progress::Progress,
+    reporter::{Reporter, Sample},
+    results::{
+        BenchError, BenchErrorKind, BenchResults, BimodalBench, EntryResult, NoisyBench,
+        ResultTree, ThresholdFailure, ThresholdKind, ThrottledBench,
+    },
+    resume::{Completed, ResumeState},
+    sample_dump::SampleDump,
+    stats::{SampleCollection, Stats},
+    throttle::Throttling,
+    time::{FineDuration, PrivTimeUnit, TimeUnit, Timer, TimerKind},
+    tree_painter::{TreeColumn, TreePainter, TreeStyle},
+    util::Rng,
 };
 
+/// The coefficient of variation percentage above which benchmarks are flagged
+/// as noisy by default.
+const DEFAULT_NOISE_THRESHOLD: f64 = 10.0;
+
 /// The benchmark runner.
 #[derive(Default)]
 pub struct Divan {
@@ -20,11 +59,137 @@ pub struct Divan {
     reverse_sort: bool,
     sorting_attr: SortingAttr,
     color: ColorChoice,
-    bytes_format: BytesFormat,
+    counter_format: CounterFormat,
+    time_unit: TimeUnit,
+    tree_style: TreeStyle,
     filters: Vec<Filter>,
     skip_filters: Vec<Filter>,
+    tags: Vec<String>,
+    generic_consts: Vec<String>,
     run_ignored: RunIgnored,
     bench_options: BenchOptions,
+    history: Option<PathBuf>,
+    dashboard: Option<PathBuf>,
+
+    /// Number of recent runs in history over which to detect trends.
+    trend: Option<usize>,
+    merge_reports: Vec<PathBuf>,
+    save_baseline: Option<String>,
+    baseline: Option<String>,
+
+    /// Whether to test the significance of changes relative to `baseline`.
+    compare: bool,
+    hw_counters: bool,
+
+    /// Events to count around each sample, in addition to `hw_counters`.
+    perf_events: Vec<PerfEvent>,
+
+    io_counters: bool,
+
+    /// Time statistics columns to show, or the defaults if empty.
+    stats: Vec<TreeColumn>,
+
+    /// Whether trimmed and winsorized means and median absolute deviation are
+    /// shown, set by `--robust-stats`.
+    robust_stats: bool,
+
+    /// The coefficient of variation percentage above which benchmarks are
+    /// flagged as noisy, or [`DEFAULT_NOISE_THRESHOLD`] if `None`.
+    noise_threshold: Option<f64>,
+
+    /// Whether every sample's time per iteration is recorded in a histogram,
+    /// set by `--hdr-histogram`.
+    hdr_histogram: bool,
+
+    /// Maximum percentage increase in median time relative to `baseline`.
+    fail_on_regression: Option<f64>,
+
+    message_format: MessageFormat,
+
+    /// File to write JSON Lines events to instead of stdout.
+    message_output: Option<PathBuf>,
+
+    /// Whether to not print the tree output.
+    hide_tree: bool,
+
+    mode: MeasureMode,
+
+    /// Files to write results to after benchmarking.
+    outputs: Vec<(OutputFormat, PathBuf)>,
+
+    /// Pushgateway URL to push OpenMetrics results to after benchmarking.
+    push_metrics: Option<String>,
+
+    /// URL to post the results document to after benchmarking.
+    notify_url: Option<String>,
+
+    /// File to write every recorded sample to as JSON Lines.
+    dump_samples: Option<PathBuf>,
+
+    /// File to write every recorded sample to as Parquet.
+    dump_samples_parquet: Option<PathBuf>,
+
+    /// File to write the time range of each benchmark to for `perf`.
+    profile: Option<PathBuf>,
+
+    /// File to checkpoint results to and resume from.
+    resume: Option<PathBuf>,
+
+    /// Whether benchmarks in the same group take turns recording samples.
+    interleave: bool,
+
+    /// Whether benchmarks run in a random order.
+    shuffle: bool,
+
+    /// The seed for shuffling, or a random seed if `None`.
+    shuffle_seed: Option<u64>,
+
+    /// The number of times the suite is run, or once if `None`.
+    runs: Option<u32>,
+
+    /// The time divided among all benchmarks, set by `--total-time`.
+    total_time: Option<Duration>,
+
+    /// The seed for randomly generated inputs, or a random seed if `None`.
+    seed: Option<u64>,
+
+    /// Whether to raise the scheduling priority before benchmarking.
+    high_priority: bool,
+
+    /// Whether to print the running benchmark's status on a signal.
+    status_signal: bool,
+
+    /// Whether each benchmark runs in a separate process.
+    isolate: bool,
+
+    /// Whether to print timer calibration and environment details.
+    verbose: bool,
+
+    /// Whether to print summary rows for groups and the whole run.
+    summary: bool,
+
+    /// Whether to emit GitHub Actions annotations and a job summary.
+    github: bool,
+
+    /// Custom output added with [`Divan::with_reporter`].
+    ///
+    /// This uses `RefCell` because benchmarks are run through `&self`.
+    reporters: RefCell<Vec<Box<dyn Reporter>>>,
+
+    /// Profilers added with [`Divan::with_profiler_hook`].
+    profiler_hooks: Vec<SharedHook>,
+
+    /// Set by [`Divan::on_sample_begin`].
+    on_sample_begin: Option<SampleHook>,
+
+    /// Set by [`Divan::on_sample_end`].
+    on_sample_end: Option<SampleHook>,
+
+    /// Benchmarks registered with [`Divan::add_bench`].
+    dynamic_benches: Vec<&'static BenchEntry>,
+
+    /// Groups registered with [`Divan::add_group`].
+    dynamic_groups: Vec<&'static GroupEntry>,
 }
 
 /// Immutable context shared between entry runs.
@@ -39,6 +204,104 @@ pub(crate) struct SharedContext {
     ///
     /// `min_time` and `max_time` do not consider this as benchmarking time.
     pub bench_overhead: FineDuration,
+
+    /// Previous results to compare against.
+    pub baseline: Option<Baseline>,
+
+    /// Whether to test the significance of changes relative to `baseline`.
+    pub compare: bool,
+
+    /// Whether to record hardware performance counters around samples.
+    pub hw_counters: bool,
+
+    /// Events selected with `--perf-events` to count around samples.
+    pub perf_events: Vec<PerfEvent>,
+
+    /// Whether to record I/O counters around samples.
+    pub io_counters: bool,
+
+    /// Whether `AllocProfiler` is the global allocator.
+    pub alloc_profiler: bool,
+
+    /// Whether benchmarks are counted with Callgrind instead of timed.
+    pub callgrind: Option<CallgrindRole>,
+
+    /// Whether each benchmark runs in a separate process.
+    pub isolate: Option<IsolateRole>,
+
+    /// Whether only the first call of each benchmark is timed, in each of many
+    /// spawned processes.
+    pub cold_start: bool,
+
+    /// The seed for randomly generated inputs.
+    pub seed: u64,
+
+    /// Whether time spent generating inputs is measured.
+    pub measure_gen: bool,
+
+    /// Whether time spent dropping outputs and inputs is measured.
+    pub measure_drop: bool,
+
+    /// Output of the time range of each benchmark, set by `--profile`.
+    pub profile_markers: Option<ProfileMarkers>,
+
+    /// Hooks called around the sample collection of each benchmark.
+    pub profiler_hooks: Vec<SharedHook>,
+
+    /// Called before each sample, outside of the timed region.
+    pub on_sample_begin: Option<SampleHook>,
+
+    /// Called after each sample, outside of the timed region.
+    pub on_sample_end: Option<SampleHook>,
+
+    /// Progress of the running benchmark, if drawn to the terminal.
+    pub progress: Option<Progress>,
+
+    /// Whether the status of the running benchmark can be printed on request.
+    pub status_dump: bool,
+
+    /// Checkpointed results, set by `--resume`.
+    pub resume: Option<ResumeState>,
+
+    /// Whether to record every sample's time per iteration in a histogram.
+    pub hdr_histogram: bool,
+
+    /// The time allotted to each benchmark by path, set by `--total-time`.
+    pub time_allotments: HashMap<String, Duration>,
+}
+
+#[cfg(any(test, feature = "internal_benches"))]
+impl SharedContext {
+    /// Returns a context for `action` with `timer` and all other features
+    /// disabled, for tests to override as needed.
+    pub fn test_default(action: Action, timer: Timer) -> Self {
+        Self {
+            action,
+            timer,
+            bench_overhead: FineDuration::default(),
+            baseline: None,
+            compare: false,
+            hw_counters: false,
+            perf_events: Vec::new(),
+            io_counters: false,
+            alloc_profiler: false,
+            callgrind: None,
+            isolate: None,
+            cold_start: false,
+            seed: 0,
+            measure_gen: false,
+            measure_drop: false,
+            profile_markers: None,
+            profiler_hooks: Vec::new(),
+            on_sample_begin: None,
+            on_sample_end: None,
+            progress: None,
+            status_dump: false,
+            resume: None,
+            hdr_histogram: false,
+            time_allotments: HashMap::new(),
+        }
+    }
 }
 
 impl fmt::Debug for Divan {
@@ -52,14 +315,40 @@ impl Divan {
     ///
     /// By default, this will be [`Divan::run_benches`].
     pub fn main(&self) {
+        if !self.merge_reports.is_empty() {
+            self.compare_reports();
+            return;
+        }
+
         self.run_action(self.action);
     }
 
+    /// Print a benchmark × machine comparison of the reports set by
+    /// [`Divan::merge_reports`], as if the `--merge-reports` flag was used.
+    ///
+    /// This does not run any benchmarks.
+    pub fn compare_reports(&self) {
+        match crate::compare::read_reports(&self.merge_reports) {
+            Ok(machines) => print!("{}", crate::compare::render(&machines)),
+            Err(error) => eprintln!("error: Failed to read reports: {error}"),
+        }
+    }
+
     /// Benchmark registered functions.
     pub fn run_benches(&self) {
         self.run_action(Action::Bench);
     }
 
+    /// Benchmark registered functions and return their results.
+    ///
+    /// Unlike [`Divan::run_benches`], this does not exit the process if a
+    /// benchmark exceeds a threshold, which can instead be checked with
+    /// [`BenchResults::passed`]. Output is printed as usual unless disabled
+    /// with [`Divan::tree_output`].
+    pub fn collect(&self) -> BenchResults {
+        self.collect_action(Action::Bench)
+    }
+
     /// Test registered functions as if the `--test` flag was used.
     ///
     /// Unlike [`Divan::run_benches`], this runs each benchmarked function only
@@ -73,62 +362,137 @@ impl Divan {
         self.run_action(Action::Test);
     }
 
-    /// Returns `true` if an entry at the given path should be considered for
-    /// running.
+    /// Returns `true` if an entry at the given path with the given tags should
+    /// be considered for running.
+    ///
+    /// Filters of generic benchmarks also match their path written like Rust
+    /// generics, such as `crate::sort<u64>` for `crate::sort::u64`.
     ///
     /// This does not take into account `entry.ignored` because that is handled
     /// separately.
-    fn filter(&self, entry_path: &str) -> bool {
-        if !self.filters.is_empty()
-            && !self.filters.iter().any(|filter| filter.is_match(entry_path))
+    fn filter(&self, entry_path: &str, entry_tags: &[&str], entry: AnyBenchEntry) -> bool {
+        if !self.tags.is_empty() && !self.tags.iter().any(|tag| entry_tags.contains(&tag.as_str()))
         {
             return false;
         }
 
-        !self.skip_filters.iter().any(|filter| filter.is_match(entry_path))
+        if !self.generic_consts.is_empty() {
+            let Some(const_value) = entry.generic_const_name() else {
+                return false;
+            };
+            if !self.generic_consts.iter().any(|value| value == const_value) {
+                return false;
+            }
+        }
+
+        let generic_path = entry.generic_path(entry_path);
+        let is_match = |filter: &Filter| {
+            filter.is_match(entry_path)
+                || generic_path.as_deref().is_some_and(|path| filter.is_match(path))
+        };
+
+        if !self.filters.is_empty() && !self.filters.iter().any(is_match) {
+            return false;
+        }
+
+        !self.skip_filters.iter().any(is_match)
     }
 
     pub(crate) fn should_ignore(&self, ignored: bool) -> bool {
         !self.run_ignored.should_run(ignored)
     }
 
+    /// Returns `true` if terminal output should be colored.
+    fn use_color(&self) -> bool {
+        match self.color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => std::io::stdout().is_terminal(),
+        }
+    }
+
+    /// Performs `action`, exiting the process with a nonzero status if any
+    /// benchmark panicked or exceeded a threshold.
     pub(crate) fn run_action(&self, action: Action) {
-        let mut tree: Vec<EntryTree> = if cfg!(miri) {
-            // Miri does not work with `linkme`.
-            Vec::new()
-        } else {
-            let group_entries = &crate::entry::GROUP_ENTRIES;
+        if !self.collect_action(action).passed() {
+            std::process::exit(1);
+        }
+    }
 
-            let generic_bench_entries = group_entries
-                .iter()
-                .flat_map(|group| group.generic_benches_iter().map(AnyBenchEntry::GenericBench));
+    fn collect_action(&self, action: Action) -> BenchResults {
+        let (static_bench_entries, static_group_entries): (Vec<&BenchEntry>, Vec<&GroupEntry>) =
+            if cfg!(miri) {
+                // Miri does not work with `linkme`.
+                Default::default()
+            } else {
+                (
+                    crate::entry::BENCH_ENTRIES.iter().collect(),
+                    crate::entry::GROUP_ENTRIES.iter().collect(),
+                )
+            };
+
+        let group_entries =
+            || static_group_entries.iter().copied().chain(self.dynamic_groups.iter().copied());
+
+        let mut tree: Vec<EntryTree> = {
+            let generic_bench_entries = group_entries().flat_map(GroupEntry::generic_any_entries);
 
-            let bench_entries = crate::entry::BENCH_ENTRIES
+            let bench_entries = static_bench_entries
                 .iter()
-                .map(AnyBenchEntry::Bench)
+                .copied()
+                .chain(self.dynamic_benches.iter().copied())
+                .flat_map(BenchEntry::any_entries)
                 .chain(generic_bench_entries);
 
             let mut tree = EntryTree::from_benches(bench_entries);
 
-            for group in group_entries.iter() {
+            for group in group_entries() {
                 EntryTree::insert_group(&mut tree, group);
             }
 
             tree
         };
 
+        // A process spawned under Callgrind runs the one benchmark that its
+        // parent is counting.
+        let callgrind_child_path = crate::callgrind::child_path();
+
+        // Likewise for a process spawned to isolate a benchmark.
+        let isolate_child_path = crate::isolate::child_path();
+
+        let child_path = callgrind_child_path.as_ref().or(isolate_child_path.as_ref());
+
         // Filter after inserting groups so that we can properly use groups'
         // display names.
-        EntryTree::retain(&mut tree, |entry_path| self.filter(entry_path));
+        EntryTree::retain(&mut tree, |entry_path, entry_tags, entry| match child_path {
+            Some(child_path) => entry_path == child_path,
+            None => self.filter(entry_path, entry_tags, entry),
+        });
 
         // Quick exit without doing unnecessary work.
         if tree.is_empty() {
-            return;
+            if action.is_bench() && child_path.is_none() {
+                let results = BenchResults::default();
+                for reporter in self.reporters.borrow_mut().iter_mut() {
+                    reporter.on_finish(&results);
+                }
+                let run_info = OnceCell::new();
+                self.record_results(&results, || {
+                    run_info.get_or_init(|| RunInfo::current(self.seed))
+                });
+            }
+            return BenchResults::default();
         }
 
         // Sorting is after filtering to compare fewer elements.
         EntryTree::sort_by_attr(&mut tree, self.sorting_attr, self.reverse_sort);
 
+        // Events written to stdout replace the tree.
+        if action.is_list() && self.message_format == MessageFormat::JsonLines {
+            self.list_json(&tree, None, "", &[], &mut MessageWriter::stdout());
+            return BenchResults::default();
+        }
+
         let timer = match self.timer {
             TimerKind::Os => Timer::Os,
 
@@ -143,11 +507,200 @@ impl Divan {
             }
         };
 
-        if action.is_bench() {
+        let callgrind = if callgrind_child_path.is_some() {
+            Some(CallgrindRole::Child)
+        } else if self.mode == MeasureMode::Callgrind && action.is_bench() {
+            match crate::callgrind::check_valgrind() {
+                Ok(()) => Some(CallgrindRole::Parent),
+                Err(error) => {
+                    eprintln!("warning: Callgrind is unavailable ({error}), defaulting to time");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let cold_start = self.mode == MeasureMode::ColdStart && action.is_bench();
+
+        let isolate = if isolate_child_path.is_some() {
+            Some(IsolateRole::Child)
+        } else if (self.isolate || cold_start) && action.is_bench() && callgrind.is_none() {
+            let arg = if cold_start { "--mode cold-start" } else { "--isolate" };
+            if self.interleave {
+                eprintln!("warning: '--interleave' has no effect with '{arg}'");
+            }
+            if self.shuffle {
+                eprintln!("warning: '--shuffle' has no effect with '{arg}'");
+            }
+            if self.runs.is_some_and(|runs| runs > 1) {
+                eprintln!("warning: '--runs' has no effect with '{arg}'");
+            }
+            if self.total_time.is_some() {
+                eprintln!("warning: '--total-time' has no effect with '{arg}'");
+            }
+            Some(IsolateRole::Parent)
+        } else {
+            None
+        };
+
+        if action.is_bench() && callgrind.is_none() && isolate != Some(IsolateRole::Child) {
             eprintln!("Timer precision: {}", timer.precision());
         }
 
-        let shared_context = SharedContext {
+        if self.high_priority && action.is_bench() {
+            if let Err(error) = crate::priority::raise() {
+                eprintln!(
+                    "warning: Failed to raise scheduling priority ({error}); to allow this, {}",
+                    crate::priority::permission_hint()
+                );
+            }
+        }
+
+        let shuffle_seed =
+            if self.shuffle && action.is_bench() && callgrind.is_none() && isolate.is_none() {
+                if self.interleave {
+                    eprintln!("warning: '--shuffle' has no effect with '--interleave'");
+                    None
+                } else {
+                    let seed = self.shuffle_seed.unwrap_or_else(Rng::random_seed);
+                    eprintln!("Shuffle seed: {seed}");
+                    Some(seed)
+                }
+            } else {
+                None
+            };
+
+        let run_count = match self.runs {
+            Some(runs)
+                if runs > 1 && action.is_bench() && callgrind.is_none() && isolate.is_none() =>
+            {
+                if self.interleave {
+                    eprintln!("warning: '--runs' has no effect with '--interleave'");
+                    1
+                } else {
+                    runs
+                }
+            }
+            _ => 1,
+        };
+
+        let sort_by_time = if matches!(self.sorting_attr, SortingAttr::Time)
+            && action.is_bench()
+            && callgrind.is_none()
+            && isolate.is_none()
+        {
+            if self.interleave {
+                eprintln!("warning: '--sort time' has no effect with '--interleave'");
+                false
+            } else {
+                true
+            }
+        } else {
+            false
+        };
+
+        let seed = self.seed.unwrap_or_else(Rng::random_seed);
+
+        // Child processes receive the parent's seed, so only the parent
+        // prints it.
+        let is_child =
+            callgrind == Some(CallgrindRole::Child) || isolate == Some(IsolateRole::Child);
+        if cfg!(feature = "rand") && action.is_bench() && !is_child {
+            eprintln!("Seed: {seed}");
+        }
+
+        // Counts and cold starts are not comparable with hot loop times, so
+        // they are not recorded.
+        let is_recorded = action.is_bench()
+            && callgrind.is_none()
+            && !cold_start
+            && isolate != Some(IsolateRole::Child);
+
+        let baseline = match &self.baseline {
+            Some(name) if is_recorded => self.load_baseline(name, timer.kind()),
+            _ => None,
+        };
+
+        if self.fail_on_regression.is_some() && self.baseline.is_none() && is_recorded {
+            eprintln!("warning: '--fail-on-regression' has no effect without '--baseline'");
+        }
+
+        let hw_counters = self.hw_counters && action.is_bench() && callgrind.is_none() && {
+            match crate::hw::HwCounters::open() {
+                Ok(_) => true,
+                Err(error) => {
+                    eprintln!("warning: Hardware counters are unavailable ({error})");
+                    false
+                }
+            }
+        };
+
+        let perf_events = if action.is_bench() && callgrind.is_none() {
+            self.open_perf_events()
+        } else {
+            Vec::new()
+        };
+
+        // Events written to stdout replace the tree. Child processes only
+        // measure for their parent.
+        let is_quiet = self.hide_tree
+            || is_child
+            || (action.is_bench()
+                && self.message_output.is_none()
+                && self.message_format == MessageFormat::JsonLines);
+
+        // Progress is drawn in place on the rows of sequentially run benchmarks.
+        let progress = (action.is_bench()
+            && callgrind.is_none()
+            && isolate.is_none()
+            && !self.interleave
+            && !is_quiet
+            && std::io::stdout().is_terminal())
+        .then(|| Progress::new(self.use_color()));
+
+        let profile_markers = match &self.profile {
+            Some(path) if action.is_bench() && callgrind.is_none() && isolate.is_none() => {
+                match ProfileMarkers::create(path) {
+                    Ok(markers) => Some(markers),
+                    Err(error) => {
+                        eprintln!("warning: Failed to create '{}': {error}", path.display());
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        let (resume, completed) = match &self.resume {
+            Some(path) if action.is_bench() && callgrind.is_none() && !is_child => {
+                if self.interleave {
+                    eprintln!("warning: '--resume' has no effect with '--interleave'");
+                    (None, Completed::new())
+                } else {
+                    match ResumeState::open(path) {
+                        Ok((state, completed)) => (Some(state), completed),
+                        Err(error) => {
+                            eprintln!("warning: Failed to open '{}': {error}", path.display());
+                            (None, Completed::new())
+                        }
+                    }
+                }
+            }
+            _ => (None, Completed::new()),
+        };
+
+        let io_counters = self.io_counters && action.is_bench() && callgrind.is_none() && {
+            match crate::io_counters::IoSnapshot::start() {
+                Ok(_) => true,
+                Err(error) => {
+                    eprintln!("warning: I/O counters are unavailable ({error})");
+                    false
+                }
+            }
+        };
+
+        let mut shared_context = SharedContext {
             action,
             timer,
             bench_overhead: if action.is_bench() {
@@ -155,8 +708,54 @@ impl Divan {
             } else {
                 FineDuration::default()
             },
+            compare: self.compare && baseline.is_some(),
+            baseline,
+            hw_counters,
+            perf_events,
+            io_counters,
+            alloc_profiler: action.is_bench() && callgrind.is_none() && crate::alloc::is_active(),
+            callgrind,
+            isolate,
+            cold_start,
+            seed,
+            measure_gen: action.is_bench()
+                && callgrind.is_none()
+                && (self.stats.contains(&TreeColumn::Gen)
+                    || self.message_output.is_some()
+                    || self.message_format == MessageFormat::JsonLines),
+            measure_drop: action.is_bench()
+                && callgrind.is_none()
+                && self.stats.contains(&TreeColumn::Drop),
+            profile_markers,
+            profiler_hooks: if action.is_bench() {
+                self.profiler_hooks.clone()
+            } else {
+                Vec::new()
+            },
+            on_sample_begin: self.on_sample_begin.filter(|_| action.is_bench()),
+            on_sample_end: self.on_sample_end.filter(|_| action.is_bench()),
+            progress,
+            status_dump: self.status_signal
+                && action.is_bench()
+                && callgrind.is_none()
+                && crate::status::install(),
+            resume,
+            hdr_histogram: self.hdr_histogram && action.is_bench() && callgrind.is_none(),
+            time_allotments: HashMap::new(),
         };
 
+        // Allotments depend on the options of each benchmark to run.
+        if let Some(total_time) = self.total_time {
+            if action.is_bench() && callgrind.is_none() && isolate.is_none() {
+                shared_context.time_allotments =
+                    self.time_allotments(&tree, &shared_context, total_time / run_count);
+            }
+        }
+
+        if self.verbose && action.is_bench() && callgrind.is_none() && !is_child {
+            self.print_verbose_header(&shared_context);
+        }
+
         let column_widths = if action.is_bench() {
             TreeColumn::ALL.map(|column| {
                 if column.is_last() {
@@ -170,435 +769,3107 @@ impl Divan {
             [0; TreeColumn::COUNT]
         };
 
-        let mut tree_painter = TreePainter::new(EntryTree::max_name_span(&tree, 0), column_widths);
+        // Only the parent process reports results.
+        let is_reported = action.is_bench()
+            && callgrind != Some(CallgrindRole::Child)
+            && isolate != Some(IsolateRole::Child);
+
+        // Gathering run info spawns `git` and `hostname`, so it is done at most
+        // once and only if an output needs it.
+        let run_info_cell = OnceCell::new();
+        let run_info = || run_info_cell.get_or_init(|| RunInfo::current(Some(shared_context.seed)));
+
+        let mut message_writer = match &self.message_output {
+            Some(path) if is_reported => match MessageWriter::create(path) {
+                Ok(writer) => Some(writer),
+                Err(error) => {
+                    eprintln!("warning: Failed to create '{}': {error}", path.display());
+                    None
+                }
+            },
+            None if is_reported && self.message_format == MessageFormat::JsonLines => {
+                Some(MessageWriter::stdout())
+            }
+            _ => None,
+        };
 
-        self.run_tree(action, &tree, &shared_context, None, &mut tree_painter);
-    }
+        if let Some(writer) = &mut message_writer {
+            writer.write_run_start(run_info());
+        }
 
-    fn run_tree(
-        &self,
-        action: Action,
-        tree: &[EntryTree],
-        shared_context: &SharedContext,
-        parent_options: Option<&BenchOptions>,
-        tree_painter: &mut TreePainter,
-    ) {
-        for (i, child) in tree.iter().enumerate() {
-            let is_last = i == tree.len() - 1;
+        let mut sample_dump = match &self.dump_samples {
+            Some(path) if is_reported => {
+                match SampleDump::create(path, shared_context.perf_events.clone()) {
+                    Ok(dump) => Some(dump),
+                    Err(error) => {
+                        eprintln!("warning: Failed to create '{}': {error}", path.display());
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
 
-            let name = child.display_name();
+        #[cfg(feature = "parquet")]
+        let mut sample_parquet = match &self.dump_samples_parquet {
+            Some(path) if is_reported => Some(crate::parquet::SampleParquet::new(path)),
+            _ => None,
+        };
 
-            let child_options = child.bench_options();
+        #[cfg(not(feature = "parquet"))]
+        if self.dump_samples_parquet.is_some() && is_reported {
+            eprintln!("warning: Writing samples as Parquet requires the 'parquet' feature");
+        }
 
-            // Overwrite `parent_options` with `child_options` if applicable.
-            let options: BenchOptions;
-            let options: Option<&BenchOptions> = match (parent_options, child_options) {
-                (None, None) => None,
-                (Some(options), None) | (None, Some(options)) => Some(options),
-                (Some(parent_options), Some(child_options)) => {
-                    options = child_options.overwrite(parent_options);
-                    Some(&options)
-                }
-            };
+        #[cfg(not(feature = "hdr"))]
+        if self.hdr_histogram && is_reported {
+            eprintln!("warning: Recording HDR histograms requires the 'hdr' feature");
+        }
 
-            match child {
-                EntryTree::Leaf(child) => self.run_bench_entry(
-                    action,
-                    *child,
-                    shared_context,
-                    options,
-                    tree_painter,
-                    is_last,
-                ),
-                EntryTree::Parent { children, .. } => {
-                    tree_painter.start_parent(name, is_last);
+        let mut user_reporters = self.reporters.borrow_mut();
 
-                    self.run_tree(action, children, shared_context, options, tree_painter);
+        let mut reporters: Vec<&mut dyn Reporter> = Vec::new();
+        if is_reported {
+            reporters.extend(user_reporters.iter_mut().map(|reporter| &mut **reporter as _));
+            reporters.extend(message_writer.as_mut().map(|writer| writer as _));
+            reporters.extend(sample_dump.as_mut().map(|dump| dump as _));
 
-                    tree_painter.finish_parent();
-                }
-            }
+            #[cfg(feature = "parquet")]
+            reporters.extend(sample_parquet.as_mut().map(|parquet| parquet as _));
         }
-    }
-
-    fn run_bench_entry(
-        &self,
-        action: Action,
-        bench_entry: AnyBenchEntry,
-        shared_context: &SharedContext,
-        entry_options: Option<&BenchOptions>,
-        tree_painter: &mut TreePainter,
-        is_last: bool,
-    ) {
-        use crate::bench::BenchContext;
 
-        let display_name = bench_entry.display_name();
+        let is_multi_thread = action.is_bench()
+            && callgrind.is_none()
+            && match self.bench_options.threads {
+                Some(threads) => threads.iter().any(|&n| n != 1),
+                None => EntryTree::has_multi_thread(&tree),
+            };
 
-        // User runtime options override all other options.
-        let options: BenchOptions;
-        let options: &BenchOptions = match entry_options {
-            None => &self.bench_options,
-            Some(entry_options) => {
-                options = self.bench_options.overwrite(entry_options);
-                &options
-            }
+        let mut max_name_span = match self.tree_style {
+            TreeStyle::Flat => EntryTree::max_path_span(&tree, 0),
+            _ => EntryTree::max_name_span(&tree, 0),
         };
 
-        if self.should_ignore(options.ignore.unwrap_or_default()) {
-            tree_painter.ignore_leaf(display_name, is_last);
-            return;
+        // Make room for summary row labels.
+        if self.summary {
+            let label_len = "(summary)".len();
+            max_name_span = match self.tree_style {
+                // Labels follow their parent's path, so this is an upper bound.
+                TreeStyle::Flat => max_name_span + 1 + label_len,
+                _ => max_name_span.max(EntryTree::max_summary_span(&tree, 0, label_len)),
+            };
         }
 
-        // Paint empty leaf when simply listing.
-        if action.is_list() {
-            tree_painter.start_leaf(display_name, is_last);
-            tree_painter.finish_empty_leaf();
-            return;
-        }
+        let mut tree_painter = TreePainter::new(
+            max_name_span,
+            column_widths,
+            TreeColumn::ALL.map(|column| {
+                if let Some(index) = column.perf_event_index() {
+                    return index < shared_context.perf_events.len();
+                }
 
-        // TODO: Add threads options to `Divan`.
-        let mut thread_counts: Vec<NonZeroUsize> = entry_options
-            .and_then(|options| options.threads)
-            .unwrap_or_default()
-            .iter()
-            .map(|&n| match NonZeroUsize::new(n) {
-                Some(n) => n,
-                None => crate::util::known_parallelism(),
-            })
-            .collect();
+                match column {
+                    TreeColumn::Wall => is_multi_thread,
+                    TreeColumn::RunStdDev => run_count > 1,
+                    TreeColumn::Delta => shared_context.baseline.is_some(),
+                    TreeColumn::Significance => shared_context.compare,
+                    _ if column.is_hw_stat() => {
+                        callgrind.is_some()
+                            || (shared_context.hw_counters && column.is_hw_stat_supported())
+                    }
+                    _ if column.is_alloc_stat() => shared_context.alloc_profiler,
+                    _ if column.is_io_count() => shared_context.io_counters,
+                    _ if column.is_time_stat() && callgrind.is_some() => false,
+                    _ if column.is_time_stat() => {
+                        if self.robust_stats && TreeColumn::ROBUST_STATS.contains(&column) {
+                            true
+                        } else if self.stats.is_empty() {
+                            TreeColumn::DEFAULT_TIME_STATS.contains(&column)
+                        } else {
+                            self.stats.contains(&column)
+                        }
+                    }
+                    _ => true,
+                }
+            }),
+            is_quiet,
+            self.use_color(),
+            self.time_unit,
+            self.tree_style,
+            shared_context.perf_events.clone(),
+        );
+
+        // Repeated, shuffled, and sorted by time benchmarks are all measured
+        // before any are reported.
+        let mut premeasured = if run_count > 1 {
+            let runs = (0..run_count)
+                .map(|run| {
+                    eprintln!("Run {} of {run_count}", run + 1);
+
+                    // Each run is shuffled differently.
+                    let shuffle_seed = shuffle_seed.map(|seed| seed.wrapping_add(run.into()));
+                    self.premeasure(&tree, &shared_context, shuffle_seed)
+                })
+                .collect();
+
+            merge_runs(runs)
+        } else if shuffle_seed.is_some() || sort_by_time {
+            self.premeasure(&tree, &shared_context, shuffle_seed)
+        } else {
+            HashMap::new()
+        };
 
-        thread_counts.sort_unstable();
-        thread_counts.dedup();
+        // Benchmarks completed by a previous run are reported as if premeasured.
+        premeasured.extend(completed);
 
-        let thread_counts: &[NonZeroUsize] =
-            if thread_counts.is_empty() { &[NonZeroUsize::MIN] } else { &thread_counts };
+        if sort_by_time {
+            let get_time = |path: &str| {
+                premeasured.get(path)?.iter().find_map(|measurement| match measurement {
+                    Some(Ok(measurement)) => Some(measurement.stats.time.mean),
+                    _ => None,
+                })
+            };
+            EntryTree::sort_by_time(&mut tree, "", &get_time, self.reverse_sort);
+        }
 
-        // Whether we should emit child branches for thread counts.
-        let has_thread_branches = thread_counts.len() > 1;
+        let mut results = BenchResults::default();
 
-        if has_thread_branches {
-            tree_painter.start_parent(display_name, is_last);
-        } else {
-            tree_painter.start_leaf(display_name, is_last);
+        self.run_tree(
+            action,
+            &tree,
+            &shared_context,
+            None,
+            "",
+            &mut tree_painter,
+            &mut reporters,
+            &mut results,
+            &mut premeasured,
+        );
+
+        if let Some(resume) = &shared_context.resume {
+            resume.finish();
         }
 
-        for (i, &thread_count) in thread_counts.iter().enumerate() {
-            let is_last = if has_thread_branches { i == thread_counts.len() - 1 } else { is_last };
+        if self.summary && action.is_bench() && callgrind.is_none() {
+            tree_painter.summary_row("(total)", &ResultTree::entries(&results.tree));
+        }
 
-            if has_thread_branches {
-                tree_painter.start_leaf(&format!("t={thread_count}"), is_last);
+        for reporter in &mut reporters {
+            reporter.on_finish(&results);
+        }
+
+        if is_recorded {
+            if let Some(baseline) = &shared_context.baseline {
+                warn_baseline_sample_mismatch(baseline, &results);
+            }
+
+            if let Some(name) = &self.save_baseline {
+                let baseline = Baseline::from_results(name, &results, timer.kind(), run_info());
+
+                if let Err(error) = baseline.save() {
+                    eprintln!("warning: Failed to save baseline '{name}': {error}");
+                }
+            }
+
+            self.record_results(&results, run_info);
+
+            for (format, path) in &self.outputs {
+                let result = match format {
+                    OutputFormat::Csv => crate::csv::write(path, &results, run_info()),
+                    OutputFormat::Markdown => crate::markdown::write(
+                        path,
+                        &results,
+                        self.bench_options.bytes_format.unwrap_or_default(),
+                        self.counter_format,
+                    ),
+                    OutputFormat::Junit => crate::junit::write(path, &results),
+                    OutputFormat::OpenMetrics => crate::openmetrics::write(path, &results),
+                };
+
+                if let Err(error) = result {
+                    eprintln!(
+                        "warning: Failed to write {} to '{}': {error}",
+                        format.title(),
+                        path.display()
+                    );
+                }
             }
 
-            let mut bench_context = BenchContext::new(shared_context, options, thread_count);
-            bench_entry.bench(Bencher::new(&mut bench_context));
+            if let Some(url) = &self.push_metrics {
+                if let Err(error) = crate::openmetrics::push(url, &results) {
+                    eprintln!("warning: Failed to push metrics to '{url}': {error}");
+                }
+            }
 
-            let should_compute_stats = bench_context.did_run && shared_context.action.is_bench();
+            if let Some(url) = &self.notify_url {
+                #[cfg(feature = "notify")]
+                if let Err(error) = crate::notify::send(url, &results, run_info()) {
+                    eprintln!("warning: Failed to notify '{url}': {error}");
+                }
 
-            if !bench_context.did_run {
-                eprintln!("warning: No benchmark function registered for '{display_name}'");
+                #[cfg(not(feature = "notify"))]
+                eprintln!("warning: Notifying '{url}' requires the 'notify' feature");
             }
 
-            if !should_compute_stats {
-                tree_painter.finish_empty_leaf();
-                continue;
+            if !results.failures.is_empty() {
+                print_threshold_failures(&results.failures, is_quiet);
             }
 
-            let stats = bench_context.compute_stats();
+            if self.github {
+                if let Err(error) =
+                    crate::github::write_summary(&results, shared_context.baseline.as_ref())
+                {
+                    eprintln!("warning: Failed to write GitHub job summary: {error}");
+                }
+            }
+        }
 
-            tree_painter.finish_leaf(is_last, &stats, self.bytes_format);
+        if !results.errors.is_empty() {
+            print_errors(&results.errors, is_quiet);
         }
 
-        if has_thread_branches {
-            tree_painter.finish_parent();
+        if !results.throttled.is_empty() {
+            warn_throttled(&results.throttled);
+        }
+
+        if !results.bimodal.is_empty() {
+            warn_bimodal(&results.bimodal);
+        }
+
+        if !results.noisy.is_empty() {
+            warn_noisy(&results.noisy, self.noise_threshold.unwrap_or(DEFAULT_NOISE_THRESHOLD));
+        }
+
+        if self.github && action.is_bench() && isolate != Some(IsolateRole::Child) {
+            for annotation in crate::github::annotations(&results) {
+                if is_quiet {
+                    eprintln!("{annotation}");
+                } else {
+                    println!("{annotation}");
+                }
+            }
         }
+
+        results
     }
-}
 
-/// Makes `Divan::skip_regex` input polymorphic.
-pub trait SkipRegex {
-    fn skip_regex(self, divan: &mut Divan);
-}
+    /// Prints details about how benchmarks are measured, which help explain
+    /// unexpected results.
+    fn print_verbose_header(&self, shared_context: &SharedContext) {
+        let timer = shared_context.timer;
+        match timer {
+            Timer::Os => eprintln!("Timer: {}", timer.kind().name()),
+            Timer::Tsc { frequency } => {
+                eprintln!(
+                    "Timer: {} ({:.2} GHz)",
+                    timer.kind().name(),
+                    frequency.get() as f64 / 1e9
+                )
+            }
+        }
 
-/// Configuration options.
-impl Divan {
-    /// Creates an instance with options set by parsing CLI arguments.
-    pub fn from_args() -> Self {
-        Self::default().config_with_args()
+        eprintln!("Sample loop overhead: {}", shared_context.bench_overhead);
+
+        let thread_counts = thread_counts(self.bench_options.threads)
+            .iter()
+            .map(|count| count.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        eprintln!(
+            "Threads: {thread_counts} (available parallelism: {})",
+            crate::util::known_parallelism()
+        );
+
+        let environment = crate::environment::Environment::current().summary();
+        if !environment.is_empty() {
+            eprintln!("Environment: {environment}");
+        }
     }
 
-    /// Sets options by parsing CLI arguments.
-    ///
-    /// This may override any previously-set options.
-    #[must_use]
-    pub fn config_with_args(mut self) -> Self {
-        let mut command = crate::cli::command();
+    /// Returns the `--perf-events` that can be counted, warning if there are
+    /// too many or if they cannot be opened.
+    fn open_perf_events(&self) -> Vec<PerfEvent> {
+        let mut events = self.perf_events.clone();
+        if events.is_empty() {
+            return events;
+        }
 
-        let matches = command.get_matches_mut();
-        let is_exact = matches.get_flag("exact");
+        if events.len() > MAX_PERF_EVENTS {
+            eprintln!("warning: Only the first {MAX_PERF_EVENTS} of '--perf-events' are counted");
+            events.truncate(MAX_PERF_EVENTS);
+        }
 
-        let mut parse_filter = |filter: &String| {
-            if is_exact {
-                Filter::Exact(filter.to_owned())
-            } else {
-                match Regex::new(filter) {
-                    Ok(r) => Filter::Regex(r),
-                    Err(error) => {
-                        let kind = clap::error::ErrorKind::ValueValidation;
-                        command.error(kind, error).exit();
-                    }
-                }
+        match crate::hw::PerfCounters::open(&events) {
+            Ok(_) => events,
+            Err(error) => {
+                eprintln!("warning: Perf events are unavailable ({error})");
+                Vec::new()
+            }
+        }
+    }
+
+    /// Loads the named baseline, warning about failures and mismatches.
+    fn load_baseline(&self, name: &str, timer_kind: TimerKind) -> Option<Baseline> {
+        let baseline = match Baseline::load(name) {
+            Ok(baseline) => baseline,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                eprintln!("warning: Baseline '{name}' not found");
+                return None;
+            }
+            Err(error) => {
+                eprintln!("warning: Failed to load baseline '{name}': {error}");
+                return None;
             }
         };
 
-        if let Some(filters) = matches.get_many::<String>("filter") {
-            self.filters.extend(filters.map(&mut parse_filter));
+        if let Some(baseline_timer) = &baseline.timer {
+            if baseline_timer != timer_kind.name() {
+                eprintln!(
+                    "warning: Baseline '{name}' was measured with timer '{baseline_timer}', but this run uses '{}'",
+                    timer_kind.name()
+                );
+            }
         }
 
-        if let Some(skip_filters) = matches.get_many::<String>("skip") {
-            self.skip_filters.extend(skip_filters.map(&mut parse_filter));
+        if self.compare && baseline.samples.is_empty() {
+            eprintln!(
+                "warning: Baseline '{name}' has no samples for testing significance, so it should be saved again"
+            );
         }
 
-        self.action = if matches.get_flag("list") {
-            Action::List
-        } else if matches.get_flag("test") || !matches.get_flag("bench") {
-            // Either of:
-            // `cargo bench -- --test`
-            // `cargo test --benches`
-            Action::Test
+        Some(baseline)
+    }
+
+    /// Appends results to history, regenerates the dashboard, and warns about
+    /// trends, if enabled.
+    fn record_results<'a>(&self, results: &BenchResults, run_info: impl FnOnce() -> &'a RunInfo) {
+        if self.trend.is_some() && self.history.is_none() {
+            eprintln!("warning: Detecting trends requires '--history'");
+        }
+
+        if self.history.is_none() && self.dashboard.is_none() {
+            return;
+        }
+
+        let records = HistoryRecord::from_results(results, run_info());
+
+        let Some(history_path) = &self.history else {
+            // Without history, the dashboard only shows this run.
+            if let Some(dashboard_dir) = &self.dashboard {
+                self.write_dashboard(dashboard_dir, &records);
+            }
+            return;
+        };
+
+        if let Err(error) = crate::history::append(history_path, &records) {
+            eprintln!("warning: Failed to write history to '{}': {error}", history_path.display());
+            return;
+        }
+
+        if self.dashboard.is_none() && self.trend.is_none() {
+            return;
+        }
+
+        let history = match crate::history::read(history_path) {
+            Ok(history) => history,
+            Err(error) => {
+                eprintln!(
+                    "warning: Failed to read history from '{}': {error}",
+                    history_path.display()
+                );
+                return;
+            }
+        };
+
+        if let Some(dashboard_dir) = &self.dashboard {
+            self.write_dashboard(dashboard_dir, &history);
+        }
+
+        if let Some(runs) = self.trend {
+            let trends = crate::history::trends(&history, &records, runs);
+            if !trends.is_empty() {
+                warn_trends(&trends);
+            }
+        }
+    }
+
+    fn write_dashboard(&self, dir: &Path, records: &[HistoryRecord]) {
+        if let Err(error) = crate::dashboard::write(dir, records) {
+            eprintln!("warning: Failed to write dashboard to '{}': {error}", dir.display());
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn run_tree(
+        &self,
+        action: Action,
+        tree: &[EntryTree],
+        shared_context: &SharedContext,
+        parent_options: Option<&BenchOptions>,
+        parent_path: &str,
+        tree_painter: &mut TreePainter,
+        reporters: &mut [&mut dyn Reporter],
+        results: &mut BenchResults,
+        premeasured: &mut HashMap<String, Vec<Option<MeasureResult>>>,
+    ) {
+        // Interleaved benchmarks are all measured before any are reported.
+        let mut interleaved = if self.interleave
+            && action.is_bench()
+            && shared_context.callgrind.is_none()
+            && shared_context.isolate.is_none()
+        {
+            self.measure_interleaved(tree, shared_context, parent_options, parent_path)
         } else {
-            Action::Bench
+            Vec::new()
         };
 
-        if let Some(&color) = matches.get_one("color") {
-            self.color = color;
+        for (i, child) in tree.iter().enumerate() {
+            let is_last = i == tree.len() - 1;
+
+            let name = child.display_name();
+
+            let path = if parent_path.is_empty() {
+                name.to_owned()
+            } else {
+                format!("{parent_path}::{name}")
+            };
+
+            let child_options = child.bench_options();
+            let options = overwrite_options(child_options, parent_options);
+            let options = options.as_deref();
+
+            match child {
+                EntryTree::Leaf(child) => {
+                    self.run_bench_entry(
+                        action,
+                        *child,
+                        shared_context,
+                        options,
+                        &path,
+                        tree_painter,
+                        reporters,
+                        results,
+                        is_last,
+                        interleaved
+                            .get_mut(i)
+                            .and_then(Option::take)
+                            .or_else(|| premeasured.remove(&path)),
+                    );
+
+                    // Runs and interleaved benchmarks are measured before
+                    // being reported, so this is after the last sample.
+                    if panic::catch_unwind(panic::AssertUnwindSafe(|| child.teardown())).is_err() {
+                        eprintln!("warning: Teardown of '{path}' panicked");
+                    }
+                }
+                EntryTree::Parent { children, .. } => {
+                    if action.is_list() {
+                        tree_painter.start_parent(&with_tags(name, child.tags()), is_last);
+                    } else {
+                        tree_painter.start_parent(name, is_last);
+                    }
+
+                    let budget_options = self.budget_options(children, child_options, options);
+
+                    // Collect children's results separately to nest them.
+                    let parent_results = std::mem::take(&mut results.tree);
+
+                    self.run_tree(
+                        action,
+                        children,
+                        shared_context,
+                        budget_options.as_ref().or(options),
+                        &path,
+                        tree_painter,
+                        reporters,
+                        results,
+                        premeasured,
+                    );
+
+                    let children = std::mem::replace(&mut results.tree, parent_results);
+
+                    if self.summary && action.is_bench() && shared_context.callgrind.is_none() {
+                        tree_painter.summary_row("(summary)", &ResultTree::entries(&children));
+                    }
+
+                    if !children.is_empty() {
+                        results.tree.push(ResultTree::Parent { name: name.to_owned(), children });
+                    }
+
+                    tree_painter.finish_parent();
+                }
+            }
+        }
+    }
+
+    /// Writes a `"listed"` event for each benchmark in `tree` with the options
+    /// it would run with.
+    fn list_json(
+        &self,
+        tree: &[EntryTree],
+        parent_options: Option<&BenchOptions>,
+        parent_path: &str,
+        parent_tags: &[&str],
+        writer: &mut MessageWriter,
+    ) {
+        for child in tree {
+            let name = child.display_name();
+
+            let path = if parent_path.is_empty() {
+                name.to_owned()
+            } else {
+                format!("{parent_path}::{name}")
+            };
+
+            let options = overwrite_options(child.bench_options(), parent_options);
+            let options = options.as_deref();
+
+            let mut tags = parent_tags.to_vec();
+            for tag in child.tags() {
+                if !tags.contains(tag) {
+                    tags.push(tag);
+                }
+            }
+
+            match child {
+                EntryTree::Leaf(entry) => {
+                    let options = user_entry_options(&self.bench_options, options, *entry);
+
+                    writer.write_listed(*entry, &path, &tags, &options);
+                }
+                EntryTree::Parent { children, .. } => {
+                    self.list_json(children, options, &path, &tags, writer);
+                }
+            }
+        }
+    }
+
+    /// Divides a group's own time limits among its benchmarks, if it has a
+    /// time budget.
+    fn budget_options(
+        &self,
+        children: &[EntryTree],
+        child_options: Option<&BenchOptions>,
+        options: Option<&BenchOptions>,
+    ) -> Option<BenchOptions> {
+        let child_options = child_options?;
+        if child_options.time_budget != Some(true) {
+            return None;
+        }
+
+        let run_count = self.budget_run_count(children, options);
+        if run_count == 0 {
+            return None;
+        }
+
+        let mut budget_options = options?.clone();
+        budget_options.time_budget = None;
+        if let Some(min_time) = child_options.min_time {
+            budget_options.min_time = Some(min_time / run_count);
+        }
+        if let Some(max_time) = child_options.max_time {
+            budget_options.max_time = Some(max_time / run_count);
+        }
+        Some(budget_options)
+    }
+
+    /// Measures every benchmark and thread count in `tree`, in a random order
+    /// if `shuffle_seed` is set, returning measurements by benchmark path.
+    fn premeasure(
+        &self,
+        tree: &[EntryTree],
+        shared_context: &SharedContext,
+        shuffle_seed: Option<u64>,
+    ) -> HashMap<String, Vec<Option<MeasureResult>>> {
+        let mut leaves = Vec::new();
+        self.collect_leaves(tree, shared_context, None, "", &mut leaves);
+
+        let mut measurements = HashMap::<String, Vec<Option<MeasureResult>>>::new();
+
+        // Each run is a benchmark's index in `leaves` and thread count index.
+        let mut runs = Vec::<(usize, usize, NonZeroUsize)>::new();
+
+        for (leaf_index, (path, _, options)) in leaves.iter().enumerate() {
+            if shared_context.resume.as_ref().is_some_and(|resume| resume.is_checkpointed(path)) {
+                continue;
+            }
+
+            let thread_counts = entry_thread_counts(options, shared_context);
+
+            measurements.insert(path.clone(), thread_counts.iter().map(|_| None).collect());
+
+            runs.extend(
+                thread_counts
+                    .into_iter()
+                    .enumerate()
+                    .map(|(thread_index, thread_count)| (leaf_index, thread_index, thread_count)),
+            );
+        }
+
+        if let Some(seed) = shuffle_seed {
+            Rng::new(seed).shuffle(&mut runs);
+        }
+
+        for (leaf_index, thread_index, thread_count) in runs {
+            let (path, bench_entry, options) = &leaves[leaf_index];
+
+            let measurement =
+                measure(*bench_entry, path, shared_context, options, thread_count, None);
+
+            if let Some(slot) = measurements.get_mut(path).and_then(|m| m.get_mut(thread_index)) {
+                *slot = measurement;
+            }
+        }
+
+        measurements
+    }
+
+    /// Divides `total_time` among the benchmarks in `tree` that will run,
+    /// returning the time allotted to each by path.
+    fn time_allotments(
+        &self,
+        tree: &[EntryTree],
+        shared_context: &SharedContext,
+        total_time: Duration,
+    ) -> HashMap<String, Duration> {
+        let mut leaves = Vec::new();
+        self.collect_leaves(tree, shared_context, None, "", &mut leaves);
+
+        // Benchmarks completed by a previous run take no time.
+        if let Some(resume) = &shared_context.resume {
+            leaves.retain(|(path, ..)| !resume.is_checkpointed(path));
+        }
+
+        let limits: Vec<Limits> = leaves
+            .iter()
+            .map(|(_, _, options)| Limits {
+                runs: entry_thread_counts(options, shared_context).len() as u32,
+                min_time: options.min_time.unwrap_or_default(),
+                max_time: options.max_time,
+            })
+            .collect();
+
+        let (allotments, ignored_min) = crate::budget::allot(total_time, &limits);
+
+        if let Some(min_time) = ignored_min {
+            eprintln!(
+                "warning: Ignoring minimum times of benchmarks, which total {} and exceed '--total-time'",
+                FineDuration::from(min_time)
+            );
+        }
+
+        leaves.into_iter().map(|(path, ..)| path).zip(allotments).collect()
+    }
+
+    /// Collects the benchmarks in `tree` that will run, along with their paths
+    /// and options.
+    fn collect_leaves<'s, 't>(
+        &'s self,
+        tree: &[EntryTree<'t>],
+        shared_context: &SharedContext,
+        parent_options: Option<&BenchOptions>,
+        parent_path: &str,
+        leaves: &mut Vec<(String, AnyBenchEntry<'t>, Cow<'s, BenchOptions>)>,
+    ) {
+        for child in tree {
+            let name = child.display_name();
+
+            let path = if parent_path.is_empty() {
+                name.to_owned()
+            } else {
+                format!("{parent_path}::{name}")
+            };
+
+            let child_options = child.bench_options();
+            let options = overwrite_options(child_options, parent_options);
+            let options = options.as_deref();
+
+            match child {
+                EntryTree::Leaf(bench_entry) => {
+                    let options = self.entry_options(options, &path, *bench_entry, shared_context);
+
+                    if !self.should_ignore(options.ignore.unwrap_or_default()) {
+                        leaves.push((path, *bench_entry, options));
+                    }
+                }
+                EntryTree::Parent { children, .. } => {
+                    let budget_options = self.budget_options(children, child_options, options);
+
+                    self.collect_leaves(
+                        children,
+                        shared_context,
+                        budget_options.as_ref().or(options),
+                        &path,
+                        leaves,
+                    );
+                }
+            }
         }
+    }
+
+    /// Measures the benchmarks in `tree` on separate threads that take turns
+    /// recording samples, returning each child's measurements by thread count.
+    ///
+    /// Only one benchmark runs at a time. Benchmark groups are interleaved
+    /// separately when their parent is run.
+    fn measure_interleaved(
+        &self,
+        tree: &[EntryTree],
+        shared_context: &SharedContext,
+        parent_options: Option<&BenchOptions>,
+        parent_path: &str,
+    ) -> Vec<Option<Vec<Option<MeasureResult>>>> {
+        let mut measurements: Vec<Option<Vec<Option<MeasureResult>>>> =
+            tree.iter().map(|_| None).collect();
+
+        let leaves: Vec<(usize, String, AnyBenchEntry, Cow<BenchOptions>)> = tree
+            .iter()
+            .enumerate()
+            .filter_map(|(i, child)| {
+                let EntryTree::Leaf(bench_entry) = child else {
+                    return None;
+                };
+
+                let name = child.display_name();
+                let path = if parent_path.is_empty() {
+                    name.to_owned()
+                } else {
+                    format!("{parent_path}::{name}")
+                };
+
+                let options = overwrite_options(child.bench_options(), parent_options);
+                let options =
+                    self.entry_options(options.as_deref(), &path, *bench_entry, shared_context);
+
+                if self.should_ignore(options.ignore.unwrap_or_default()) {
+                    return None;
+                }
+
+                Some((i, path, *bench_entry, options))
+            })
+            .collect();
+
+        // Interleaving requires at least two benchmarks.
+        if leaves.len() < 2 {
+            return measurements;
+        }
+
+        let turns = Turns::new(leaves.len());
+
+        thread::scope(|scope| {
+            let handles: Vec<_> = leaves
+                .iter()
+                .enumerate()
+                .map(|(turn_index, (_, path, bench_entry, options))| {
+                    let turns = &turns;
+
+                    scope.spawn(move || {
+                        let turn = turns.enter(turn_index);
+
+                        entry_thread_counts(options, shared_context)
+                            .into_iter()
+                            .map(|thread_count| {
+                                let turn = Some(turn.turn());
+                                measure(
+                                    *bench_entry,
+                                    path,
+                                    shared_context,
+                                    options,
+                                    thread_count,
+                                    turn,
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            for (&(i, ..), handle) in leaves.iter().zip(handles) {
+                let result = handle.join().unwrap_or_else(|payload| panic::resume_unwind(payload));
+                measurements[i] = Some(result);
+            }
+        });
+
+        measurements
+    }
+
+    /// Returns the options for running a benchmark.
+    fn entry_options(
+        &self,
+        entry_options: Option<&BenchOptions>,
+        entry_path: &str,
+        bench_entry: AnyBenchEntry,
+        shared_context: &SharedContext,
+    ) -> Cow<'_, BenchOptions> {
+        // User runtime options override all other options.
+        let mut options = user_entry_options(&self.bench_options, entry_options, bench_entry);
+
+        // Each benchmark spends exactly its share of `--total-time`.
+        if let Some(&allotment) = shared_context.time_allotments.get(entry_path) {
+            let options = options.to_mut();
+            options.min_time = Some(allotment);
+            options.max_time = Some(allotment);
+        }
+
+        // Callgrind counts and cold starts time a single iteration of a
+        // single sample.
+        let is_single_iter = shared_context.callgrind == Some(CallgrindRole::Child)
+            || (shared_context.cold_start && shared_context.isolate == Some(IsolateRole::Child));
+
+        if is_single_iter {
+            Cow::Owned(BenchOptions {
+                sample_count: Some(1),
+                sample_size: Some(1),
+                min_time: Some(Duration::ZERO),
+                max_time: None,
+                warmup_time: None,
+                warmup_samples: None,
+                ..options.into_owned()
+            })
+        } else {
+            options
+        }
+    }
+
+    /// Returns the number of times benchmarks in `tree` will be measured, for
+    /// dividing a group's time budget.
+    fn budget_run_count(&self, tree: &[EntryTree], parent_options: Option<&BenchOptions>) -> u32 {
+        tree.iter()
+            .map(|child| {
+                let options = overwrite_options(child.bench_options(), parent_options);
+                let options = options.as_deref();
+
+                match child {
+                    EntryTree::Leaf(_) => {
+                        let ignore = self
+                            .bench_options
+                            .ignore
+                            .or(options.and_then(|options| options.ignore))
+                            .unwrap_or_default();
+
+                        if self.should_ignore(ignore) {
+                            0
+                        } else {
+                            let threads = self
+                                .bench_options
+                                .threads
+                                .or(options.and_then(|options| options.threads));
+
+                            thread_counts(threads).len() as u32
+                        }
+                    }
+                    EntryTree::Parent { children, .. } => self.budget_run_count(children, options),
+                }
+            })
+            .sum()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn run_bench_entry(
+        &self,
+        action: Action,
+        bench_entry: AnyBenchEntry,
+        shared_context: &SharedContext,
+        entry_options: Option<&BenchOptions>,
+        entry_path: &str,
+        tree_painter: &mut TreePainter,
+        reporters: &mut [&mut dyn Reporter],
+        results: &mut BenchResults,
+        is_last: bool,
+        mut measurements: Option<Vec<Option<MeasureResult>>>,
+    ) {
+        let display_name = bench_entry.display_name();
+
+        if bench_entry.generic_const_value().is_none()
+            && entry_options.is_some_and(|options| options.counters.has_per_const())
+        {
+            eprintln!(
+                "warning: Ignoring `of_const` counters of '{entry_path}' without a numeric `const`"
+            );
+        }
+
+        let options = self.entry_options(entry_options, entry_path, bench_entry, shared_context);
+        let options: &BenchOptions = &options;
+
+        if self.should_ignore(options.ignore.unwrap_or_default()) {
+            tree_painter.ignore_leaf(display_name, options.ignore_reason, is_last);
+            return;
+        }
+
+        // Paint empty leaf when simply listing.
+        if action.is_list() {
+            // Other entries share tags with their parent, which shows them.
+            let tags = match bench_entry {
+                AnyBenchEntry::Bench(entry) => entry.meta.tags,
+                _ => &[],
+            };
+
+            tree_painter.start_leaf(&with_tags(display_name, tags), is_last);
+            tree_painter.finish_empty_leaf();
+            return;
+        }
+
+        let thread_counts = entry_thread_counts(options, shared_context);
+        let thread_counts: &[NonZeroUsize] = &thread_counts;
+
+        match shared_context.isolate {
+            // Measure for the parent process instead of reporting.
+            Some(IsolateRole::Child) => {
+                let measurements: Vec<Option<MeasureResult>> = thread_counts
+                    .iter()
+                    .map(|&thread_count| {
+                        measure(
+                            bench_entry,
+                            entry_path,
+                            shared_context,
+                            options,
+                            thread_count,
+                            None,
+                        )
+                    })
+                    .collect();
+
+                if let Err(error) = crate::isolate::write_output(&measurements) {
+                    eprintln!("warning: Failed to write results of '{entry_path}': {error}");
+                }
+                return;
+            }
+
+            Some(IsolateRole::Parent) if measurements.is_none() => {
+                let result = if shared_context.cold_start {
+                    let run_count = options.sample_count.unwrap_or(DEFAULT_SAMPLE_COUNT);
+                    crate::isolate::run_cold_start(entry_path, run_count, shared_context.seed)
+                } else {
+                    crate::isolate::run(entry_path, shared_context.seed)
+                };
+
+                measurements = Some(match result {
+                    Ok(measurements) => measurements,
+                    Err(error) => {
+                        eprintln!(
+                            "warning: Failed to run '{entry_path}' in a separate process: {error}"
+                        );
+                        Vec::new()
+                    }
+                });
+            }
+
+            _ => {}
+        }
+
+        // Whether we should emit child branches for thread counts.
+        let has_thread_branches = thread_counts.len() > 1;
+
+        if has_thread_branches {
+            tree_painter.start_parent(display_name, is_last);
+        } else {
+            tree_painter.start_leaf(display_name, is_last);
+        }
+
+        // Collect thread counts' results separately to nest them.
+        let parent_results =
+            if has_thread_branches { std::mem::take(&mut results.tree) } else { Vec::new() };
+
+        let mut checkpoint =
+            shared_context.resume.as_ref().map(|resume| resume.checkpoint(entry_path));
+
+        for (i, &thread_count) in thread_counts.iter().enumerate() {
+            let is_last = if has_thread_branches { i == thread_counts.len() - 1 } else { is_last };
+
+            let thread_name = format!("t={thread_count}");
+
+            if has_thread_branches {
+                tree_painter.start_leaf(&thread_name, is_last);
+            }
+
+            let (name, path) = if has_thread_branches {
+                (thread_name, format!("{entry_path}::t={thread_count}"))
+            } else {
+                (display_name.to_owned(), entry_path.to_owned())
+            };
+
+            for reporter in reporters.iter_mut() {
+                reporter.on_bench_start(&path);
+            }
+
+            let (stats, sample_times) = if shared_context.callgrind == Some(CallgrindRole::Parent) {
+                match crate::callgrind::run(&path, shared_context.seed) {
+                    Ok(counts) => (counts.to_stats(), Vec::new()),
+                    Err(error) => {
+                        eprintln!("warning: Failed to run '{path}' under Callgrind: {error}");
+                        tree_painter.finish_empty_leaf();
+                        continue;
+                    }
+                }
+            } else {
+                let measurement = match &mut measurements {
+                    Some(measurements) => measurements.get_mut(i).and_then(Option::take),
+                    None => {
+                        let _progress_row = shared_context
+                            .progress
+                            .as_ref()
+                            .map(|progress| progress.start(tree_painter.leaf_prefix()));
+
+                        measure(
+                            bench_entry,
+                            entry_path,
+                            shared_context,
+                            options,
+                            thread_count,
+                            None,
+                        )
+                    }
+                };
+
+                if let Some(checkpoint) = &mut checkpoint {
+                    checkpoint.push(measurement.as_ref());
+                }
+
+                let Measurement { stats, samples, throttling } = match measurement {
+                    Some(Ok(measurement)) => measurement,
+                    Some(Err(kind)) => {
+                        tree_painter.finish_errored_leaf(&kind);
+                        results.errors.push(BenchError { path, kind });
+                        continue;
+                    }
+                    None => {
+                        tree_painter.finish_empty_leaf();
+                        continue;
+                    }
+                };
+
+                for reporter in reporters.iter_mut() {
+                    for sample in samples.reported_samples() {
+                        reporter.on_sample(
+                            &path,
+                            &Sample {
+                                iter_count: samples.sample_size,
+                                duration: sample.duration,
+                                counter_totals: sample.counter_totals,
+                                hw_counts: sample.hw_counts,
+                                perf_counts: sample.perf_counts,
+                                alloc_counts: sample.alloc_counts,
+                                io_counts: sample.io_counts,
+                            },
+                        );
+                    }
+                }
+
+                if let Some(throttling) = throttling {
+                    results.throttled.push(ThrottledBench { path: path.clone(), throttling });
+                }
+
+                let sample_times = samples
+                    .reported_samples()
+                    .iter()
+                    .map(|sample| sample.duration / samples.sample_size)
+                    .collect();
+
+                (stats, sample_times)
+            };
+
+            for reporter in reporters.iter_mut() {
+                reporter.on_bench_complete(&path, &stats);
+            }
+
+            let baseline = shared_context
+                .baseline
+                .as_ref()
+                .and_then(|baseline| baseline.get(&path))
+                .map(|record| &record.time);
+
+            let p_value = if shared_context.compare {
+                shared_context
+                    .baseline
+                    .as_ref()
+                    .and_then(|baseline| baseline.samples.get(&path))
+                    .and_then(|baseline_times| {
+                        crate::stats::mann_whitney_u(baseline_times, &sample_times)
+                    })
+            } else {
+                None
+            };
+
+            let failure_count = results.failures.len();
+
+            if let Some(max) = options.max_time_mean.map(FineDuration::from) {
+                let mean = stats.time.mean;
+                if mean > max {
+                    results.failures.push(ThresholdFailure {
+                        path: path.clone(),
+                        kind: ThresholdKind::MaxTimeMean { mean, max },
+                    });
+                }
+            }
+
+            if let (Some(max), Some(baseline)) = (self.fail_on_regression, baseline) {
+                let change =
+                    crate::baseline::percent_change(baseline.median.picos, stats.time.median.picos);
+                if let Some(change) = change.filter(|&change| change > max) {
+                    results.failures.push(ThresholdFailure {
+                        path: path.clone(),
+                        kind: ThresholdKind::Regression { change, max },
+                    });
+                }
+            }
+
+            let noise_threshold = self.noise_threshold.unwrap_or(DEFAULT_NOISE_THRESHOLD);
+            let is_noisy =
+                stats.time_cv().filter(|&cv| noise_threshold > 0.0 && cv > noise_threshold);
+            if let Some(cv) = is_noisy {
+                results.noisy.push(NoisyBench { path: path.clone(), cv });
+            }
+
+            if let Some(bimodality) = stats.bimodality {
+                results.bimodal.push(BimodalBench { path: path.clone(), bimodality });
+            }
+
+            let is_throttled = results.throttled.last().is_some_and(|bench| bench.path == path);
+            let is_noisy = is_noisy.is_some();
+            let is_failed = results.failures.len() > failure_count;
+
+            tree_painter.finish_leaf(
+                is_last,
+                &stats,
+                baseline,
+                p_value,
+                options.bytes_format.unwrap_or_default(),
+                self.counter_format,
+                is_throttled,
+                is_noisy,
+                is_failed,
+            );
+
+            results.tree.push(ResultTree::Leaf(EntryResult {
+                name,
+                path,
+                generic_type: bench_entry.generic_type_name().map(str::to_owned),
+                generic_const: bench_entry.generic_const_name().map(str::to_owned),
+                stats,
+                sample_times,
+            }));
+        }
+
+        if let Some(checkpoint) = checkpoint {
+            checkpoint.finish();
+        }
+
+        if has_thread_branches {
+            let children = std::mem::replace(&mut results.tree, parent_results);
+            if !children.is_empty() {
+                results.tree.push(ResultTree::Parent { name: display_name.to_owned(), children });
+            }
+
+            tree_painter.finish_parent();
+        }
+    }
+}
+
+/// Prints benchmarks that exceeded thresholds after the table.
+///
+/// If `is_quiet`, this prints to stderr because the tree is not shown or
+/// stdout is used for messages.
+fn print_threshold_failures(failures: &[ThresholdFailure], is_quiet: bool) {
+    let count = failures.len();
+    let noun = if count == 1 { "benchmark" } else { "benchmarks" };
+
+    let mut summary = format!("{count} {noun} exceeded thresholds:\n");
+    for failure in failures {
+        summary.push_str(&format!("    {failure}\n"));
+    }
+
+    if is_quiet {
+        eprint!("{summary}");
+    } else {
+        print!("{summary}");
+    }
+}
+
+/// Prints benchmarks that panicked after the table.
+///
+/// If `is_quiet`, this prints to stderr because the tree is not shown or
+/// stdout is used for messages.
+fn print_errors(errors: &[BenchError], is_quiet: bool) {
+    let count = errors.len();
+    let noun = if count == 1 { "benchmark" } else { "benchmarks" };
+
+    let mut summary = format!("{count} {noun} errored:\n");
+    for error in errors {
+        summary.push_str(&format!("    {error}\n"));
+    }
+
+    if is_quiet {
+        eprint!("{summary}");
+    } else {
+        print!("{summary}");
+    }
+}
+
+/// Warns about benchmarks during which CPU frequency dropped significantly,
+/// since their results are likely slower than they should be.
+fn warn_throttled(throttled: &[ThrottledBench]) {
+    let count = throttled.len();
+    let noun = if count == 1 { "benchmark" } else { "benchmarks" };
+
+    let mut warning = format!(
+        "warning: {count} {noun} may have been slowed by thermal throttling or frequency scaling:\n"
+    );
+    for bench in throttled {
+        warning.push_str(&format!("    {bench}\n"));
+    }
+
+    eprint!("{warning}");
+}
+
+/// Warns about benchmarks whose sample times clustered around two distinct
+/// values, since their mean and median describe neither.
+fn warn_bimodal(bimodal: &[BimodalBench]) {
+    let count = bimodal.len();
+    let noun = if count == 1 { "benchmark appears" } else { "benchmarks appear" };
+
+    let mut warning = format!(
+        "warning: {count} {noun} bimodal, so mean and median times may describe neither mode:\n"
+    );
+    for bench in bimodal {
+        warning.push_str(&format!("    {bench}\n"));
+    }
+
+    eprint!("{warning}");
+}
+
+/// Warns about benchmarks whose sample times varied more than `threshold`
+/// percent, since their results should not be trusted.
+fn warn_noisy(noisy: &[NoisyBench], threshold: f64) {
+    let count = noisy.len();
+    let noun = if count == 1 { "benchmark was" } else { "benchmarks were" };
+
+    let mut warning = format!(
+        "warning: {count} {noun} noisy, with a coefficient of variation above {threshold}%:\n"
+    );
+    for bench in noisy {
+        warning.push_str(&format!("    {bench}\n"));
+    }
+
+    eprint!("{warning}");
+}
+
+/// Warns about benchmarks whose times drifted in one direction over recent
+/// runs in history.
+fn warn_trends(trends: &[Trend]) {
+    let count = trends.len();
+    let noun = if count == 1 { "benchmark" } else { "benchmarks" };
+
+    let mut warning = format!("warning: {count} {noun} changed steadily over recent runs:\n");
+    for trend in trends {
+        warning.push_str(&format!("    {trend}\n"));
+    }
+
+    eprint!("{warning}");
+}
+
+/// Warns if benchmarks were sampled differently than in `baseline`, which may
+/// make comparisons misleading.
+fn warn_baseline_sample_mismatch(baseline: &Baseline, results: &BenchResults) {
+    let mismatch_count = results
+        .entries()
+        .filter(|entry| {
+            baseline.get(&entry.path).is_some_and(|record| {
+                record.sample_count != entry.stats.sample_count
+                    || record.iter_count != entry.stats.iter_count
+            })
+        })
+        .count();
+
+    if mismatch_count > 0 {
+        eprintln!(
+            "warning: {mismatch_count} benchmarks used a different sample count or size than baseline '{}'",
+            baseline.name
+        );
+    }
+}
+
+/// Overwrites `parent_options` with `child_options` if applicable.
+/// Appends `tags` to `name` for listing.
+fn with_tags<'a>(name: &'a str, tags: &[&str]) -> Cow<'a, str> {
+    if tags.is_empty() {
+        Cow::Borrowed(name)
+    } else {
+        Cow::Owned(format!("{name} [{}]", tags.join(", ")))
+    }
+}
+
+/// Returns `bench_options` overwriting `entry_options`, with `of_const`
+/// counters resolved for `bench_entry`.
+fn user_entry_options<'a>(
+    bench_options: &'a BenchOptions,
+    entry_options: Option<&BenchOptions>,
+    bench_entry: AnyBenchEntry,
+) -> Cow<'a, BenchOptions> {
+    let mut options = match entry_options {
+        None => Cow::Borrowed(bench_options),
+        Some(entry_options) => Cow::Owned(bench_options.overwrite(entry_options)),
+    };
+
+    if options.counters.has_per_const() {
+        let counters = options.counters.resolve_const(bench_entry.generic_const_value());
+        options.to_mut().counters = counters;
+    }
+
+    options
+}
+
+fn overwrite_options<'a>(
+    child_options: Option<&'a BenchOptions>,
+    parent_options: Option<&'a BenchOptions>,
+) -> Option<Cow<'a, BenchOptions>> {
+    match (parent_options, child_options) {
+        (None, None) => None,
+        (Some(options), None) | (None, Some(options)) => Some(Cow::Borrowed(options)),
+        (Some(parent_options), Some(child_options)) => {
+            Some(Cow::Owned(child_options.overwrite(parent_options)))
+        }
+    }
+}
+
+/// Statistics and samples from measuring a benchmark.
+pub(crate) struct Measurement {
+    pub stats: Stats,
+    pub samples: SampleCollection,
+
+    /// A significant drop in CPU frequency while measuring.
+    pub throttling: Option<Throttling>,
+}
+
+/// A measurement, or the reason the benchmark was stopped.
+pub(crate) type MeasureResult = Result<Measurement, BenchErrorKind>;
+
+/// Combines the measurements of each benchmark from repeated runs of the suite.
+fn merge_runs(
+    runs: Vec<HashMap<String, Vec<Option<MeasureResult>>>>,
+) -> HashMap<String, Vec<Option<MeasureResult>>> {
+    // Results of each run, for each thread count of each benchmark.
+    let mut all_results = HashMap::<String, Vec<Vec<MeasureResult>>>::new();
+
+    for run in runs {
+        for (path, measurements) in run {
+            let slots = all_results.entry(path).or_default();
+            slots.resize_with(slots.len().max(measurements.len()), Vec::new);
+
+            for (slot, measurement) in slots.iter_mut().zip(measurements) {
+                slot.extend(measurement);
+            }
+        }
+    }
+
+    all_results
+        .into_iter()
+        .map(|(path, slots)| (path, slots.into_iter().map(merge_run_results).collect()))
+        .collect()
+}
+
+/// Combines the results of a benchmark's runs, which errored if any run
+/// errored.
+///
+/// Samples are those of the last run, since samples of different runs may have
+/// different sizes.
+fn merge_run_results(results: Vec<MeasureResult>) -> Option<MeasureResult> {
+    let mut stats = Vec::with_capacity(results.len());
+    let mut samples = None;
+    let mut throttling = None;
+
+    for result in results {
+        let measurement = match result {
+            Ok(measurement) => measurement,
+            Err(kind) => return Some(Err(kind)),
+        };
+
+        stats.push(measurement.stats);
+        samples = Some(measurement.samples);
+        throttling = throttling.or(measurement.throttling);
+    }
+
+    Some(Ok(Measurement { stats: crate::stats::merge_runs(stats)?, samples: samples?, throttling }))
+}
+
+/// Runs a benchmark with `thread_count` threads, returning `None` if it was
+/// not measured.
+///
+/// Panics and timeouts are reported as errors so that the remaining benchmarks
+/// can still run.
+fn measure(
+    bench_entry: AnyBenchEntry,
+    entry_path: &str,
+    shared_context: &SharedContext,
+    options: &BenchOptions,
+    thread_count: NonZeroUsize,
+    turn: Option<Turn>,
+) -> Option<MeasureResult> {
+    let mut bench_context = BenchContext::new(shared_context, options, thread_count);
+    bench_context.turn = turn;
+    bench_context.entry_path = entry_path;
+
+    let freq_before = crate::throttle::max_cpu_freq_khz();
+
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        bench_entry.bench(Bencher::new(&mut bench_context));
+    }));
+
+    if let Err(payload) = result {
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            (*message).to_owned()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "Box<dyn Any>".to_owned()
+        };
+        return Some(Err(BenchErrorKind::Panicked(message)));
+    }
+
+    if bench_context.timed_out {
+        let timeout = options.timeout.map(FineDuration::from).unwrap_or_default();
+        return Some(Err(BenchErrorKind::TimedOut(timeout)));
+    }
+
+    if !bench_context.did_run {
+        eprintln!("warning: No benchmark function registered for '{}'", bench_entry.display_name());
+        return None;
+    }
+
+    if !shared_context.action.is_bench() {
+        return None;
+    }
+
+    let throttling = Throttling::detect(freq_before, crate::throttle::max_cpu_freq_khz());
+
+    Some(Ok(Measurement {
+        stats: bench_context.compute_stats(),
+        samples: bench_context.samples,
+        throttling,
+    }))
+}
+
+/// Returns the thread counts to benchmark an entry with.
+fn entry_thread_counts(
+    options: &BenchOptions,
+    shared_context: &SharedContext,
+) -> Vec<NonZeroUsize> {
+    if shared_context.callgrind.is_some() || shared_context.cold_start {
+        // Callgrind serializes threads, and cold starts time a single call, so
+        // only one is used.
+        vec![NonZeroUsize::MIN]
+    } else {
+        thread_counts(options.threads)
+    }
+}
+
+/// Returns the sorted and deduplicated thread counts to benchmark with.
+fn thread_counts(threads: Option<&[usize]>) -> Vec<NonZeroUsize> {
+    let mut thread_counts: Vec<NonZeroUsize> = threads
+        .unwrap_or_default()
+        .iter()
+        .map(|&n| match NonZeroUsize::new(n) {
+            Some(n) => n,
+            None => crate::util::known_parallelism(),
+        })
+        .collect();
+
+    thread_counts.sort_unstable();
+    thread_counts.dedup();
+
+    if thread_counts.is_empty() {
+        thread_counts.push(NonZeroUsize::MIN);
+    }
+
+    thread_counts
+}
+
+/// Makes `Divan::skip_regex` input polymorphic.
+pub trait SkipRegex {
+    fn skip_regex(self, divan: &mut Divan);
+}
+
+impl SkipRegex for Regex {
+    fn skip_regex(self, divan: &mut Divan) {
+        divan.skip_filters.push(Filter::Regex(self));
+    }
+}
+
+impl SkipRegex for &str {
+    #[track_caller]
+    fn skip_regex(self, divan: &mut Divan) {
+        Regex::new(self).unwrap().skip_regex(divan);
+    }
+}
+
+impl SkipRegex for String {
+    #[track_caller]
+    fn skip_regex(self, divan: &mut Divan) {
+        self.as_str().skip_regex(divan)
+    }
+}
+
+/// Configuration options.
+impl Divan {
+    /// Creates an instance with options set by parsing CLI arguments.
+    pub fn from_args() -> Self {
+        Self::default().config_with_args()
+    }
+
+    /// Sets options by parsing CLI arguments.
+    ///
+    /// This may override any previously-set options.
+    #[must_use]
+    pub fn config_with_args(mut self) -> Self {
+        let mut command = crate::cli::command();
+
+        let matches = command.get_matches_mut();
+        let is_exact = matches.get_flag("exact");
+
+        let mut parse_filter = |filter: &String| {
+            if is_exact {
+                Filter::Exact(filter.to_owned())
+            } else {
+                match Regex::new(filter) {
+                    Ok(r) => Filter::Regex(r),
+                    Err(error) => {
+                        let kind = clap::error::ErrorKind::ValueValidation;
+                        command.error(kind, error).exit();
+                    }
+                }
+            }
+        };
+
+        if let Some(filters) = matches.get_many::<String>("filter") {
+            self.filters.extend(filters.map(&mut parse_filter));
+        }
+
+        if let Some(skip_filters) = matches.get_many::<String>("skip") {
+            self.skip_filters.extend(skip_filters.map(&mut parse_filter));
+        }
+
+        if let Some(tags) = matches.get_many::<String>("tag") {
+            self.tags.extend(tags.cloned());
+        }
+
+        if let Some(values) = matches.get_many::<String>("const") {
+            self.generic_consts.extend(values.cloned());
+        }
+
+        self.action = if matches.get_flag("list") {
+            Action::List
+        } else if matches.get_flag("test") || !matches.get_flag("bench") {
+            // Either of:
+            // `cargo bench -- --test`
+            // `cargo test --benches`
+            Action::Test
+        } else {
+            Action::Bench
+        };
+
+        if let Some(&color) = matches.get_one("color") {
+            self.color = color;
+        }
+
+        if let Some(&PrivBytesFormat(bytes_format)) = matches.get_one("bytes-format") {
+            self.bench_options.bytes_format = Some(bytes_format);
+        }
+
+        if let Some(&PrivCounterFormat(counter_format)) = matches.get_one("counter-format") {
+            self.counter_format = counter_format;
+        }
+
+        if let Some(&PrivTimeUnit(time_unit)) = matches.get_one("time-unit") {
+            self.time_unit = time_unit;
+        }
+
+        if let Some(&style) = matches.get_one::<TreeStyle>("style") {
+            self.tree_style = style;
+        }
+
+        if matches.get_flag("ignored") {
+            self.run_ignored = RunIgnored::Only;
+        } else if matches.get_flag("include-ignored") {
+            self.run_ignored = RunIgnored::Yes;
+        }
+
+        if let Some(&timer) = matches.get_one("timer") {
+            self.timer = timer;
+        }
+
+        if let Some(&sorting_attr) = matches.get_one("sortr") {
+            self.reverse_sort = true;
+            self.sorting_attr = sorting_attr;
+        } else if let Some(&sorting_attr) = matches.get_one("sort") {
+            self.reverse_sort = false;
+            self.sorting_attr = sorting_attr;
+        }
+
+        if let Some(&sample_count) = matches.get_one("sample-count") {
+            self.bench_options.sample_count = Some(sample_count);
+        }
+
+        if let Some(&sample_size) = matches.get_one("sample-size") {
+            self.bench_options.sample_size = Some(sample_size);
+        }
+
+        if let Some(&ParsedSeconds(sample_time)) = matches.get_one("sample-time") {
+            self.bench_options.sample_time = Some(sample_time);
+        }
+
+        if let Some(&ParsedSeconds(min_time)) = matches.get_one("min-time") {
+            self.bench_options.min_time = Some(min_time);
+        }
+
+        if let Some(&ParsedSeconds(max_time)) = matches.get_one("max-time") {
+            self.bench_options.max_time = Some(max_time);
+        }
+
+        if let Some(&ParsedSeconds(total_time)) = matches.get_one("total-time") {
+            self.total_time = Some(total_time);
+        }
+
+        if let Some(&ParsedSeconds(max_time_mean)) = matches.get_one("max-time-mean") {
+            self.bench_options.max_time_mean = Some(max_time_mean);
+        }
+
+        if let Some(&until_stable) = matches.get_one("until-stable") {
+            self.bench_options.until_stable = Some(until_stable);
+        }
+
+        if let Some(&noise_threshold) = matches.get_one("noise-threshold") {
+            self.noise_threshold = Some(noise_threshold);
+        }
+
+        if let Some(&ParsedSeconds(timeout)) = matches.get_one("timeout") {
+            self.bench_options.timeout = Some(timeout);
+        }
+
+        if let Some(threads) = matches.get_many::<usize>("threads") {
+            self.bench_options.threads = Some(threads.copied().collect::<Vec<_>>().leak());
+        }
+
+        if let Some(&ParsedSeconds(warmup_time)) = matches.get_one("warmup-time") {
+            self.bench_options.warmup_time = Some(warmup_time);
+        }
+
+        if let Some(&warmup_samples) = matches.get_one("warmup-samples") {
+            self.bench_options.warmup_samples = Some(warmup_samples);
+        }
+
+        if let Some(mut skip_ext_time) = matches.get_many::<bool>("skip-ext-time") {
+            // If the option is present without a value, then it's `true`.
+            self.bench_options.skip_ext_time =
+                Some(matches!(skip_ext_time.next(), Some(true) | None));
+        }
+
+        if let Some(mut streaming_stats) = matches.get_many::<bool>("streaming-stats") {
+            // If the option is present without a value, then it's `true`.
+            self.bench_options.streaming_stats =
+                Some(matches!(streaming_stats.next(), Some(true) | None));
+        }
+
+        if let Some(&reservoir_size) = matches.get_one("reservoir-size") {
+            self.bench_options.reservoir_size = Some(reservoir_size);
+        }
+
+        if let Some(mut flush_cache) = matches.get_many::<bool>("flush-cache") {
+            // If the option is present without a value, then it's `true`.
+            self.bench_options.flush_cache = Some(matches!(flush_cache.next(), Some(true) | None));
+        }
+
+        if let Some(&unroll) = matches.get_one("unroll") {
+            self.bench_options.unroll = Some(unroll);
+        }
+
+        if let Some(history) = matches.get_one::<PathBuf>("history") {
+            self.history = Some(history.clone());
+        }
+
+        if let Some(dashboard) = matches.get_one::<PathBuf>("dashboard") {
+            self.dashboard = Some(dashboard.clone());
+        }
+
+        if let Some(&runs) = matches.get_one::<u32>("trend") {
+            self.trend = Some(runs as usize);
+        }
+
+        for arg in ["save-baseline", "baseline", "compare"] {
+            if let Some(name) = matches.get_one::<String>(arg) {
+                if let Err(error) = crate::baseline::check_name(name) {
+                    let kind = clap::error::ErrorKind::ValueValidation;
+                    command.error(kind, error).exit();
+                }
+            }
+        }
+
+        if let Some(name) = matches.get_one::<String>("save-baseline") {
+            self.save_baseline = Some(name.clone());
+        }
+
+        if let Some(name) = matches.get_one::<String>("baseline") {
+            self.baseline = Some(name.clone());
+        }
+
+        if let Some(name) = matches.get_one::<String>("compare") {
+            self.baseline = Some(name.clone());
+            self.compare = true;
+        }
+
+        if let Some(reports) = matches.get_many::<PathBuf>("merge-reports") {
+            self.merge_reports.extend(reports.cloned());
+        }
+
+        if matches.get_flag("hw-counters") {
+            self.hw_counters = true;
+        }
+
+        if let Some(events) = matches.get_many::<PerfEvent>("perf-events") {
+            self.perf_events.extend(events.copied());
+        }
+
+        if matches.get_flag("io-counters") {
+            self.io_counters = true;
+        }
+
+        if let Some(&max) = matches.get_one::<f64>("fail-on-regression") {
+            self.fail_on_regression = Some(max);
+        }
+
+        if let Some(&format) = matches.get_one::<MessageFormat>("message-format") {
+            self.message_format = format;
+        }
+
+        if let Some(path) = matches.get_one::<PathBuf>("message-output") {
+            self.message_output = Some(path.clone());
+        }
+
+        // Machine-readable outputs given without a path are written to
+        // `--output-file`. At most one may be, since they cannot share a file.
+        let output_file = matches.get_one::<PathBuf>("output-file");
+        let mut output_file_users = Vec::<String>::new();
+        let mut output_path = |arg: String, path: Option<PathBuf>| -> PathBuf {
+            path.unwrap_or_else(|| {
+                output_file_users.push(arg);
+                output_file.cloned().unwrap_or_default()
+            })
+        };
+
+        if let Some(stats) = matches.get_many::<TreeColumn>("stats") {
+            self.stats = stats.copied().collect();
+        }
+
+        if matches.get_flag("robust-stats") {
+            self.robust_stats = true;
+        }
+
+        if matches.get_flag("hdr-histogram") {
+            self.hdr_histogram = true;
+        }
+
+        if let Some(&mode) = matches.get_one::<MeasureMode>("mode") {
+            self.mode = mode;
+        }
+
+        if let Some(outputs) = matches.get_occurrences::<String>("output") {
+            for mut output in outputs {
+                let Some(format) = output.next() else {
+                    unreachable!("'--output' takes at least one value");
+                };
+                let path = output.next().map(PathBuf::from);
+
+                match OutputFormat::from_name(format) {
+                    Some(format) => {
+                        let path = output_path(format!("--output {}", format.name()), path);
+                        self.outputs.push((format, path));
+                    }
+                    None => {
+                        let possible_formats: Vec<&str> =
+                            OutputFormat::ALL.iter().map(|format| format.name()).collect();
+
+                        let kind = clap::error::ErrorKind::InvalidValue;
+                        let message = format!(
+                            "invalid output format '{format}' (possible formats: {})",
+                            possible_formats.join(", ")
+                        );
+                        command.error(kind, message).exit();
+                    }
+                }
+            }
+        }
+
+        // Reject unsupported URLs before running benchmarks rather than after.
+        for (name, option) in
+            [("push-metrics", &mut self.push_metrics), ("notify-url", &mut self.notify_url)]
+        {
+            if let Some(url) = matches.get_one::<String>(name) {
+                if let Err(error) = crate::http::parse_url(url) {
+                    let kind = clap::error::ErrorKind::ValueValidation;
+                    command.error(kind, format!("invalid '--{name}': {error}")).exit();
+                }
+                *option = Some(url.clone());
+            }
+        }
+
+        if matches.contains_id("dump-samples") {
+            let path = matches.get_one::<PathBuf>("dump-samples").cloned();
+            self.dump_samples = Some(output_path("--dump-samples".to_owned(), path));
+        }
+
+        if matches.contains_id("dump-samples-parquet") {
+            let path = matches.get_one::<PathBuf>("dump-samples-parquet").cloned();
+            self.dump_samples_parquet =
+                Some(output_path("--dump-samples-parquet".to_owned(), path));
+        }
+
+        match (output_file, output_file_users.as_slice()) {
+            (None, []) | (Some(_), [_]) => {}
+
+            (None, [user]) => {
+                let kind = clap::error::ErrorKind::MissingRequiredArgument;
+                let message = format!("'{user}' requires a path or '--output-file'");
+                command.error(kind, message).exit();
+            }
+
+            (_, [_, _, ..]) => {
+                let kind = clap::error::ErrorKind::ArgumentConflict;
+                let message = format!(
+                    "only one output can be written to '--output-file', but {} have no path",
+                    output_file_users.join(", "),
+                );
+                command.error(kind, message).exit();
+            }
+
+            // JSON Lines events are written to `--output-file` if no other
+            // output is.
+            (Some(path), []) => {
+                if matches.contains_id("message-output") {
+                    let kind = clap::error::ErrorKind::ArgumentConflict;
+                    let message = "'--output-file' has no output to write since JSON Lines events are written to '--message-output'";
+                    command.error(kind, message).exit();
+                }
+
+                self.message_output = Some(path.clone());
+            }
+        }
+
+        if let Some(path) = matches.get_one::<PathBuf>("profile") {
+            self.profile = Some(path.clone());
+        }
+
+        if let Some(path) = matches.get_one::<PathBuf>("resume") {
+            self.resume = Some(path.clone());
+        }
+
+        if matches.get_flag("interleave") {
+            self.interleave = true;
+        }
+
+        if let Some(&ShuffleSeed(seed)) = matches.get_one("shuffle") {
+            self.shuffle = true;
+            self.shuffle_seed = seed;
+        }
+
+        if let Some(&runs) = matches.get_one::<u32>("runs") {
+            self.runs = Some(runs);
+        }
+
+        if let Some(&seed) = matches.get_one::<u64>("seed") {
+            self.seed = Some(seed);
+        }
+
+        if matches.get_flag("verbose") {
+            self.verbose = true;
+        }
+
+        if matches.get_flag("summary") {
+            self.summary = true;
+        }
+
+        if matches.get_flag("github") {
+            self.github = true;
+        }
+
+        if matches.get_flag("high-priority") {
+            self.high_priority = true;
+        }
+
+        if matches.get_flag("status-signal") {
+            self.status_signal = true;
+        }
+
+        if matches.get_flag("isolate") {
+            self.isolate = true;
+        }
+
+        self
+    }
+
+    /// Sets whether output should be colored.
+    ///
+    /// This option is equivalent to the `--color` CLI argument, where [`None`]
+    /// here means "auto".
+    #[must_use]
+    pub fn color(mut self, yes: impl Into<Option<bool>>) -> Self {
+        self.color = match yes.into() {
+            None => ColorChoice::Auto,
+            Some(true) => ColorChoice::Always,
+            Some(false) => ColorChoice::Never,
+        };
+        self
+    }
+
+    /// Determines how [`BytesCount`](crate::counter::BytesCount) and
+    /// allocated bytes are scaled in benchmark outputs, overriding the
+    /// [`bytes_format`](macro@crate::bench#bytes_format) option.
+    ///
+    /// This option is equivalent to the `--bytes-format` CLI argument.
+    #[inline]
+    pub fn bytes_format(mut self, format: BytesFormat) -> Self {
+        self.bench_options.bytes_format = Some(format);
+        self
+    }
+
+    /// Determines whether [counters](crate::counter) are shown as throughput
+    /// per second or as time per count in benchmark outputs.
+    ///
+    /// Time per count, such as "1.5 ns/B", is often easier to reason about
+    /// as the cost of processing each value. Custom units set with
+    /// [`CustomCount`](crate::counter::CustomCount) are shown the same way,
+    /// such as "req/s" or "ns/req".
+    ///
+    /// This option is equivalent to the `--counter-format` CLI argument.
+    #[inline]
+    pub fn counter_format(mut self, format: CounterFormat) -> Self {
+        self.counter_format = format;
+        self
+    }
+
+    /// Uses a fixed unit for all times in benchmark outputs, rather than
+    /// scaling each value to its most readable unit.
+    ///
+    /// This makes it easier to compare rows at a glance when times span
+    /// several orders of magnitude.
+    ///
+    /// This option is equivalent to the `--time-unit` CLI argument.
+    #[must_use]
+    pub fn time_unit(mut self, unit: TimeUnit) -> Self {
+        self.time_unit = unit;
+        self
+    }
+
+    /// Also run benchmarks marked [`#[ignore]`](https://doc.rust-lang.org/reference/attributes/testing.html#the-ignore-attribute).
+    ///
+    /// This option is equivalent to the `--include-ignored` CLI argument.
+    #[must_use]
+    pub fn run_ignored(mut self) -> Self {
+        self.run_ignored = RunIgnored::Yes;
+        self
+    }
+
+    /// Only run benchmarks marked [`#[ignore]`](https://doc.rust-lang.org/reference/attributes/testing.html#the-ignore-attribute).
+    ///
+    /// This option is equivalent to the `--ignored` CLI argument.
+    #[must_use]
+    pub fn run_only_ignored(mut self) -> Self {
+        self.run_ignored = RunIgnored::Only;
+        self
+    }
+
+    /// Skips benchmarks that match `filter` as a regular expression pattern.
+    ///
+    /// This option is equivalent to the `--skip filter` CLI argument, without
+    /// `--exact`.
+    ///
+    /// # Examples
+    ///
+    /// This method is commonly used with a [`&str`](prim@str) or [`String`]:
+    ///
+    /// ```
+    /// # use divan::Divan;
+    /// let filter = "(add|sub)";
+    /// let divan = Divan::default().skip_regex(filter);
+    /// ```
+    ///
+    /// A pre-built [`Regex`] can also be provided:
+    ///
+    /// ```
+    /// # use divan::Divan;
+    /// let filter = regex::Regex::new("(add|sub)").unwrap();
+    /// let divan = Divan::default().skip_regex(filter);
+    /// ```
+    ///
+    /// Calling this repeatedly will add multiple skip filters:
+    ///
+    /// ```
+    /// # use divan::Divan;
+    /// let divan = Divan::default()
+    ///     .skip_regex("(add|sub)")
+    ///     .skip_regex("collections.*default");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `filter` is a string and [`Regex::new`] fails.
+    #[must_use]
+    pub fn skip_regex(mut self, filter: impl SkipRegex) -> Self {
+        filter.skip_regex(&mut self);
+        self
+    }
+
+    /// Skips benchmarks that exactly match `filter`.
+    ///
+    /// This option is equivalent to the `--skip filter --exact` CLI arguments.
+    ///
+    /// # Examples
+    ///
+    /// This method is commonly used with a [`&str`](prim@str) or [`String`]:
+    ///
+    /// ```
+    /// # use divan::Divan;
+    /// let filter = "arithmetic::add";
+    /// let divan = Divan::default().skip_exact(filter);
+    /// ```
+    ///
+    /// Calling this repeatedly will add multiple skip filters:
+    ///
+    /// ```
+    /// # use divan::Divan;
+    /// let divan = Divan::default()
+    ///     .skip_exact("arithmetic::add")
+    ///     .skip_exact("collections::vec::default");
+    /// ```
+    #[must_use]
+    pub fn skip_exact(mut self, filter: impl Into<String>) -> Self {
+        self.skip_filters.push(Filter::Exact(filter.into()));
+        self
+    }
+
+    /// Only runs benchmarks with `tag`, set by the
+    /// [`tags`](macro@crate::bench#tags) option of the benchmark or a parent
+    /// group.
+    ///
+    /// This option is equivalent to the `--tag tag` CLI argument.
+    ///
+    /// # Examples
+    ///
+    /// Calling this repeatedly will run benchmarks with any of the tags:
+    ///
+    /// ```
+    /// # use divan::Divan;
+    /// let divan = Divan::default().tag("io").tag("simd");
+    /// ```
+    #[must_use]
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Only runs generic benchmarks instantiated with the
+    /// [`consts`](macro@crate::bench#consts) value `value`.
+    ///
+    /// This option is equivalent to the `--const value` CLI argument.
+    ///
+    /// # Examples
+    ///
+    /// Calling this repeatedly will run benchmarks with any of the values:
+    ///
+    /// ```
+    /// # use divan::Divan;
+    /// let divan = Divan::default().generic_const(64).generic_const(1024);
+    /// ```
+    #[must_use]
+    pub fn generic_const(mut self, value: impl ToString) -> Self {
+        self.generic_consts.push(value.to_string());
+        self
+    }
+
+    /// Sets the number of sampling iterations.
+    ///
+    /// This option is equivalent to the `--sample-count` CLI argument.
+    ///
+    /// If a benchmark enables [`threads`](macro@crate::bench#threads), sample
+    /// count becomes a multiple of the number of threads. This is because each
+    /// thread operates over the same sample size to ensure there are always N
+    /// competing threads doing the same amount of work.
+    #[inline]
+    pub fn sample_count(mut self, count: u32) -> Self {
+        self.bench_options.sample_count = Some(count);
+        self
+    }
+
+    /// Sets the number of iterations inside a single sample.
+    ///
+    /// This option is equivalent to the `--sample-size` CLI argument.
+    #[inline]
+    pub fn sample_size(mut self, count: u32) -> Self {
+        self.bench_options.sample_size = Some(count);
+        self
+    }
+
+    /// Sets the wall-clock time that each sample should take, from which
+    /// sample size is chosen.
+    ///
+    /// This option is equivalent to the `--sample-time` CLI argument.
+    #[inline]
+    pub fn sample_time(mut self, time: Duration) -> Self {
+        self.bench_options.sample_time = Some(time);
+        self
+    }
+
+    /// Sets the time floor for benchmarking a function.
+    ///
+    /// This option is equivalent to the `--min-time` CLI argument.
+    #[inline]
+    pub fn min_time(mut self, time: Duration) -> Self {
+        self.bench_options.min_time = Some(time);
+        self
+    }
+
+    /// Sets the time ceiling for benchmarking a function.
+    ///
+    /// This option is equivalent to the `--max-time` CLI argument.
+    #[inline]
+    pub fn max_time(mut self, time: Duration) -> Self {
+        self.bench_options.min_time = Some(time);
+        self
+    }
+
+    /// Divides `time` among all benchmarks that will run, so that the whole
+    /// suite takes about that long.
+    ///
+    /// Each benchmark is allotted an equal share as both its minimum and
+    /// maximum time, within the bounds of its own `min_time` and `max_time`.
+    /// Time not taken by benchmarks capped below their share is divided among
+    /// the rest. Benchmarks measured with multiple thread counts are allotted
+    /// time for each.
+    ///
+    /// Like `min_time` and `max_time`, the budget does not include time spent
+    /// warming up. When the suite is [repeated](Self::runs), the budget is
+    /// divided among runs.
+    ///
+    /// This has no effect when [isolating](Self::isolate) or counting with
+    /// [Callgrind](Self::callgrind).
+    ///
+    /// This option is equivalent to the `--total-time` CLI argument.
+    #[inline]
+    pub fn total_time(mut self, time: Duration) -> Self {
+        self.total_time = Some(time);
+        self
+    }
+
+    /// Fails the run if a benchmark's mean time per iteration exceeds `time`,
+    /// overriding the [`max_time_mean`](macro@crate::bench#max_time_mean)
+    /// option.
+    ///
+    /// This option is equivalent to the `--max-time-mean` CLI argument.
+    #[inline]
+    pub fn max_time_mean(mut self, time: Duration) -> Self {
+        self.bench_options.max_time_mean = Some(time);
+        self
+    }
+
+    /// Collects samples until their coefficient of variation is at most
+    /// `max_cv` percent, overriding the
+    /// [`until_stable`](macro@crate::bench#until_stable) option.
+    ///
+    /// This option is equivalent to the `--until-stable` CLI argument.
+    #[inline]
+    pub fn until_stable(mut self, max_cv: f64) -> Self {
+        self.bench_options.until_stable = Some(max_cv);
+        self
+    }
+
+    /// Flags benchmarks as noisy if the coefficient of variation of their
+    /// sample times exceeds `max_cv` percent, or disables flagging if 0.
+    ///
+    /// Noisy benchmarks are marked in the output, and listed in a warning
+    /// after all benchmarks run, since their results should not be trusted.
+    /// The default threshold is 10%.
+    ///
+    /// This option is equivalent to the `--noise-threshold` CLI argument.
+    #[inline]
+    pub fn noise_threshold(mut self, max_cv: f64) -> Self {
+        self.noise_threshold = Some(max_cv);
+        self
+    }
+
+    /// Sets the wall-clock time after which a benchmark is stopped and reported
+    /// as timed out, overriding the [`timeout`](macro@crate::bench#timeout)
+    /// option.
+    ///
+    /// This option is equivalent to the `--timeout` CLI argument.
+    #[inline]
+    pub fn timeout(mut self, time: Duration) -> Self {
+        self.bench_options.timeout = Some(time);
+        self
+    }
+
+    /// Sets the thread counts to benchmark functions with, overriding the
+    /// [`threads`](macro@crate::bench#threads) option.
+    ///
+    /// A count of 0 uses [`std::thread::available_parallelism`].
+    ///
+    /// This option is equivalent to the `--threads` CLI argument.
+    #[inline]
+    pub fn threads(mut self, counts: impl IntoIterator<Item = usize>) -> Self {
+        self.bench_options.threads = Some(counts.into_iter().collect::<Vec<_>>().leak());
+        self
+    }
+
+    /// Sets the time spent running a function before recording samples.
+    ///
+    /// This option is equivalent to the `--warmup-time` CLI argument.
+    #[inline]
+    pub fn warmup_time(mut self, time: Duration) -> Self {
+        self.bench_options.warmup_time = Some(time);
+        self
+    }
+
+    /// Sets the number of samples run and discarded before recording samples.
+    ///
+    /// This option is equivalent to the `--warmup-samples` CLI argument.
+    #[inline]
+    pub fn warmup_samples(mut self, count: u32) -> Self {
+        self.bench_options.warmup_samples = Some(count);
+        self
+    }
+
+    /// When accounting for `min_time` or `max_time`, skip time external to
+    /// benchmarked functions.
+    ///
+    /// This option is equivalent to the `--skip-ext-time` CLI argument.
+    #[inline]
+    pub fn skip_ext_time(mut self, skip: bool) -> Self {
+        self.bench_options.skip_ext_time = Some(skip);
+        self
+    }
+
+    /// Computes statistics incrementally instead of storing every sample,
+    /// overriding the [`streaming_stats`](macro@crate::bench#streaming_stats)
+    /// option.
+    ///
+    /// This option is equivalent to the `--streaming-stats` CLI argument.
+    #[inline]
+    pub fn streaming_stats(mut self, streaming: bool) -> Self {
+        self.bench_options.streaming_stats = Some(streaming);
+        self
+    }
+
+    /// Sets the number of random samples kept by streaming statistics,
+    /// overriding the [`reservoir_size`](macro@crate::bench#reservoir_size)
+    /// option.
+    ///
+    /// This option is equivalent to the `--reservoir-size` CLI argument.
+    #[inline]
+    pub fn reservoir_size(mut self, size: u32) -> Self {
+        self.bench_options.reservoir_size = Some(size);
+        self
+    }
+
+    /// Flushes CPU caches before each sample, overriding the
+    /// [`flush_cache`](macro@crate::bench#flush_cache) option.
+    ///
+    /// This option is equivalent to the `--flush-cache` CLI argument.
+    #[inline]
+    pub fn flush_cache(mut self, flush: bool) -> Self {
+        self.bench_options.flush_cache = Some(flush);
+        self
+    }
+
+    /// Calls benchmarked functions `count` times per sample loop iteration,
+    /// overriding the [`unroll`](macro@crate::bench#unroll) option.
+    ///
+    /// This option is equivalent to the `--unroll` CLI argument.
+    #[inline]
+    pub fn unroll(mut self, count: u32) -> Self {
+        self.bench_options.unroll = Some(count);
+        self
+    }
+
+    /// Appends results of each benchmark run to a [JSON Lines](https://jsonlines.org)
+    /// history file at `path`.
+    ///
+    /// Each line records the benchmark path, Git commit, timestamp, and timing
+    /// statistics. The commit is taken from the `DIVAN_COMMIT` or `GITHUB_SHA`
+    /// environment variables, falling back to `git rev-parse HEAD`.
+    ///
+    /// This option is equivalent to the `--history` CLI argument.
+    #[must_use]
+    pub fn history(mut self, path: impl Into<PathBuf>) -> Self {
+        self.history = Some(path.into());
+        self
+    }
+
+    /// Generates a static HTML dashboard at `dir/index.html` with per-benchmark
+    /// trend charts over commits.
+    ///
+    /// Charts span all runs recorded in [`history`](Self::history). Without
+    /// history, only the current run is shown. The generated page has no
+    /// external dependencies, so it can be published to GitHub Pages as-is.
+    ///
+    /// This option is equivalent to the `--dashboard` CLI argument.
+    #[must_use]
+    pub fn dashboard(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dashboard = Some(dir.into());
+        self
+    }
+
+    /// Warns about benchmarks whose mean time increased or decreased in every
+    /// one of their last `runs` runs recorded in [`history`](Self::history).
+    ///
+    /// This catches slow creep that never crosses a
+    /// [regression threshold](Self::fail_on_regression) in a single run. Only
+    /// runs on the same machine are considered.
+    ///
+    /// This option is equivalent to the `--trend` CLI argument.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `runs` is less than 3, since any two runs differ.
+    #[must_use]
+    #[track_caller]
+    pub fn trend(mut self, runs: usize) -> Self {
+        assert!(runs >= 3, "trend detection requires at least 3 runs");
+        self.trend = Some(runs);
+        self
+    }
+
+    /// Adds [`history`](Self::history) files from other machines to compare
+    /// instead of running benchmarks.
+    ///
+    /// The latest result of each benchmark on each machine is rendered in a
+    /// benchmark × machine matrix of mean times relative to the first machine.
+    /// A summary row gives each machine's overall speed as the geometric mean
+    /// of those ratios, which can be used to normalize results across
+    /// machines.
+    ///
+    /// Machines are identified by the `DIVAN_MACHINE` environment variable at
+    /// the time of recording, falling back to the host name.
+    ///
+    /// This option is equivalent to the `--merge-reports` CLI argument.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// divan::Divan::default()
+    ///     .merge_reports(["x86.jsonl", "arm.jsonl"])
+    ///     .main();
+    /// ```
+    #[must_use]
+    pub fn merge_reports<P: Into<PathBuf>>(mut self, paths: impl IntoIterator<Item = P>) -> Self {
+        self.merge_reports.extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    /// Saves results under `name` for later comparison with
+    /// [`baseline`](Self::baseline).
+    ///
+    /// Baselines are stored in `target/divan/baselines`, or in
+    /// `DIVAN_BASELINE_DIR` if set. An existing baseline of the same name is
+    /// replaced. Names must be a single directory name, such as `main`.
+    ///
+    /// This option is equivalent to the `--save-baseline` CLI argument.
+    #[must_use]
+    pub fn save_baseline(mut self, name: impl Into<String>) -> Self {
+        self.save_baseline = Some(name.into());
+        self
+    }
+
+    /// Compares results against those saved under `name` by
+    /// [`save_baseline`](Self::save_baseline).
+    ///
+    /// The percentage change in median time is shown in a "delta" column.
+    /// Warnings are emitted if the baseline used a different timer or sample
+    /// count, since such comparisons may be misleading.
+    ///
+    /// This option is equivalent to the `--baseline` CLI argument.
+    #[must_use]
+    pub fn baseline(mut self, name: impl Into<String>) -> Self {
+        self.baseline = Some(name.into());
+        self
+    }
+
+    /// Compares results against those saved under `name`, like
+    /// [`baseline`](Self::baseline), and also tests whether each change is
+    /// statistically significant.
+    ///
+    /// Sample times are compared with the
+    /// [Mann-Whitney U test](https://en.wikipedia.org/wiki/Mann%E2%80%93Whitney_U_test),
+    /// and the p-value is shown in a "significance" column. Changes with a
+    /// p-value below 0.05 are marked as significant. Percentage changes alone
+    /// often raise false alarms for benchmarks with noisy samples.
+    ///
+    /// This option is equivalent to the `--compare` CLI argument.
+    #[must_use]
+    pub fn compare(mut self, name: impl Into<String>) -> Self {
+        self.baseline = Some(name.into());
+        self.compare = true;
+        self
+    }
+
+    /// Records hardware performance counters around each sample and shows the
+    /// mean CPU cycles, instructions, cache misses, and branch misses per
+    /// iteration.
+    ///
+    /// Instructions per cycle (IPC) and the percentage of mispredicted branches
+    /// are derived from these, which explain most microarchitectural changes in
+    /// performance.
+    ///
+    /// Instruction counts are much more stable than time in noisy environments
+    /// like CI. Counters are available on Linux via `perf_event_open`, which
+    /// may require lowering `/proc/sys/kernel/perf_event_paranoid`, and on
+    /// Apple Silicon Macs via the private `kperf` framework, which requires
+    /// running as root. On Windows, only cycles are counted via
+    /// `QueryThreadCycleTime`, and columns of other events are hidden. If
+    /// counters cannot be opened, a warning is emitted and benchmarks run
+    /// without them.
+    ///
+    /// This option is equivalent to the `--hw-counters` CLI argument.
+    #[must_use]
+    pub fn hw_counters(mut self, yes: bool) -> Self {
+        self.hw_counters = yes;
+        self
+    }
+
+    /// Records the given performance monitoring events around each sample and
+    /// shows the mean count of each per iteration in its own column.
+    ///
+    /// Events are either generic names from `perf list`, such as
+    /// `"cache-references"` or `"stalled-cycles-backend"`, or raw
+    /// model-specific event codes in hexadecimal, such as `"r01c2"`. Counts are
+    /// also included in JSON messages and sample dumps. Up to 8 events can be
+    /// counted at once, but CPUs may have fewer counters available.
+    ///
+    /// Like [`hw_counters`](Self::hw_counters), this is available on Linux,
+    /// Apple Silicon Macs, and Windows. With `kperf` on macOS, only `cycles`,
+    /// `instructions`, `branches`, `branch-misses`, and `cache-misses` are
+    /// supported by name, and on Windows only `cycles` is supported. If
+    /// counters cannot be opened, a warning is emitted and benchmarks run
+    /// without them.
+    ///
+    /// This option is equivalent to the `--perf-events` CLI argument.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an event is neither a known name nor a raw event code.
+    #[must_use]
+    pub fn perf_events<I>(mut self, events: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        for event in events {
+            let event = event.as_ref();
+            match event.parse() {
+                Ok(event) => self.perf_events.push(event),
+                Err(error) => panic!("invalid perf event '{event}': {error}"),
+            }
+        }
+        self
+    }
+
+    /// Records I/O counters of the OS around each sample and shows the mean
+    /// bytes and syscalls read and written per iteration, as well as bytes
+    /// transferred to and from storage.
+    ///
+    /// Reads served by the page cache count towards read bytes but not disk
+    /// reads, so this shows whether a benchmark hits storage. Counters are only
+    /// available on Linux via `/proc/thread-self/io`. If they cannot be read,
+    /// a warning is emitted and benchmarks run without them.
+    ///
+    /// This option is equivalent to the `--io-counters` CLI argument.
+    #[must_use]
+    pub fn io_counters(mut self, yes: bool) -> Self {
+        self.io_counters = yes;
+        self
+    }
+
+    /// Shows the 5%-trimmed mean, winsorized mean, and median absolute
+    /// deviation of time per iteration alongside other time statistics.
+    ///
+    /// The trimmed mean excludes the fastest and slowest 5% of samples, and the
+    /// winsorized mean clamps them to the fastest and slowest of the rest. On
+    /// shared machines, heavy-tailed sample times can make the plain mean
+    /// unrepresentative, while these are barely affected by rare outliers.
+    ///
+    /// They are also available via
+    /// [`Stats::trimmed_mean`](crate::results::Stats::trimmed_mean),
+    /// [`Stats::winsorized_mean`](crate::results::Stats::winsorized_mean), and
+    /// [`Stats::time_mad`](crate::results::Stats::time_mad) regardless of this
+    /// option.
+    ///
+    /// This option is equivalent to the `--robust-stats` CLI argument.
+    #[must_use]
+    pub fn robust_stats(mut self, yes: bool) -> Self {
+        self.robust_stats = yes;
+        self
+    }
+
+    /// Records the time per iteration of every sample in a high dynamic range
+    /// (HDR) histogram.
+    ///
+    /// Percentiles are then computed from all samples with 3 significant
+    /// digits, even when statistics are streamed or combined across
+    /// [`runs`](Self::runs), and any quantile is available via
+    /// [`Stats::time_quantile`](crate::results::Stats::time_quantile). The
+    /// histogram is also included in `bench_complete` events of
+    /// `--message-format json-lines` as HdrHistogram's compressed encoding in
+    /// base64.
+    ///
+    /// This option is equivalent to the `--hdr-histogram` CLI argument.
+    #[cfg(feature = "hdr")]
+    #[must_use]
+    pub fn hdr_histogram(mut self, yes: bool) -> Self {
+        self.hdr_histogram = yes;
+        self
+    }
+
+    /// Counts instructions of each benchmark with Valgrind's Callgrind instead
+    /// of measuring time.
+    ///
+    /// Each benchmark is run for a single iteration in a child process under
+    /// Callgrind, and its instructions, estimated cycles, last-level cache
+    /// misses, and branch mispredictions are shown in place of time. Unlike
+    /// time, these counts are deterministic, which makes them reliable on
+    /// noisy shared CI runners.
+    ///
+    /// This requires `valgrind` on `PATH` and x86_64 or AArch64. Otherwise, a
+    /// warning is emitted and benchmarks are timed. Benchmarks run with a
+    /// single thread, and history and baselines are not recorded.
+    ///
+    /// This option is equivalent to the `--mode callgrind` CLI argument.
+    #[must_use]
+    pub fn callgrind(mut self, yes: bool) -> Self {
+        self.mode = if yes { MeasureMode::Callgrind } else { MeasureMode::Time };
+        self
+    }
+
+    /// Times only the first call of each benchmark in a freshly spawned
+    /// process, repeated across many processes to build a distribution.
+    ///
+    /// This measures start-up and first-call costs, such as lazy
+    /// initialization, page faults, and instruction cache misses, which are
+    /// hidden by the hot loops of timing many iterations. The benchmark binary
+    /// re-runs itself once per sample, so [`sample_count`](Self::sample_count)
+    /// sets the number of processes spawned for each benchmark.
+    ///
+    /// Benchmarks run with a single thread, and history and baselines are not
+    /// recorded.
+    ///
+    /// This option is equivalent to the `--mode cold-start` CLI argument.
+    #[must_use]
+    pub fn cold_start(mut self, yes: bool) -> Self {
+        self.mode = if yes { MeasureMode::ColdStart } else { MeasureMode::Time };
+        self
+    }
 
-        if let Some(&PrivBytesFormat(bytes_format)) = matches.get_one("bytes-format") {
-            self.bytes_format = bytes_format;
-        }
+    /// Exits the process with a nonzero status if any benchmark's median time
+    /// increased by more than `percent` relative to [`Divan::baseline`].
+    ///
+    /// Failures are listed in a summary after the results table, along with
+    /// benchmarks whose mean time exceeded their
+    /// [`max_time_mean`](macro@crate::bench#max_time_mean) option. This has no
+    /// effect without a baseline.
+    ///
+    /// This option is equivalent to the `--fail-on-regression` CLI argument.
+    #[must_use]
+    pub fn fail_on_regression(mut self, percent: f64) -> Self {
+        self.fail_on_regression = Some(percent);
+        self
+    }
 
-        if matches.get_flag("ignored") {
-            self.run_ignored = RunIgnored::Only;
-        } else if matches.get_flag("include-ignored") {
-            self.run_ignored = RunIgnored::Yes;
-        }
+    /// Writes [JSON Lines](https://jsonlines.org) events to a file as
+    /// benchmarks run, in addition to the tree output.
+    ///
+    /// Events are emitted when each benchmark starts, for each of its recorded
+    /// samples, and when it completes with its statistics. This lets external
+    /// dashboards and wrappers process results incrementally.
+    ///
+    /// To instead write events to stdout in place of the tree output, use the
+    /// `--message-format json-lines` CLI argument. If both are used, events
+    /// are written to the file and the tree output is still printed, so the
+    /// two never mix on stdout.
+    ///
+    /// This option is equivalent to the `--message-output` CLI argument, or to
+    /// `--output-file` if no other output is given without a path.
+    #[must_use]
+    pub fn message_output(mut self, path: impl Into<PathBuf>) -> Self {
+        self.message_output = Some(path.into());
+        self
+    }
 
-        if let Some(&timer) = matches.get_one("timer") {
-            self.timer = timer;
-        }
+    /// Writes results to a CSV file after benchmarks finish, with one row per
+    /// benchmark.
+    ///
+    /// Rows include each benchmark's path, generic type or `const`, sample and
+    /// iteration counts, time statistics in nanoseconds, and counter
+    /// throughputs per second.
+    ///
+    /// This option is equivalent to the `--output csv <PATH>` CLI arguments.
+    #[must_use]
+    pub fn csv_output(mut self, path: impl Into<PathBuf>) -> Self {
+        self.outputs.push((OutputFormat::Csv, path.into()));
+        self
+    }
 
-        if let Some(&sorting_attr) = matches.get_one("sortr") {
-            self.reverse_sort = true;
-            self.sorting_attr = sorting_attr;
-        } else if let Some(&sorting_attr) = matches.get_one("sort") {
-            self.reverse_sort = false;
-            self.sorting_attr = sorting_attr;
-        }
+    /// Writes results to a Markdown file after benchmarks finish, as a
+    /// GitHub-flavored table suitable for pasting into pull request
+    /// descriptions.
+    ///
+    /// Rows follow the tree output, with names indented by depth, and include
+    /// time statistics, median counter throughputs, and sample and iteration
+    /// counts.
+    ///
+    /// This option is equivalent to the `--output markdown <PATH>` CLI
+    /// arguments.
+    #[must_use]
+    pub fn markdown_output(mut self, path: impl Into<PathBuf>) -> Self {
+        self.outputs.push((OutputFormat::Markdown, path.into()));
+        self
+    }
 
-        if let Some(&sample_count) = matches.get_one("sample-count") {
-            self.bench_options.sample_count = Some(sample_count);
-        }
+    /// Writes results to a JUnit XML file after benchmarks finish, for CI
+    /// systems that can only show test reports.
+    ///
+    /// Each benchmark is a test case whose time is its mean time per
+    /// iteration. Benchmarks that exceeded a threshold, such as
+    /// [`max_time_mean`](macro@crate::bench#max_time_mean) or
+    /// [`Divan::fail_on_regression`], are reported as failures, and
+    /// benchmarks that panicked or timed out are reported as errors.
+    ///
+    /// This option is equivalent to the `--output junit <PATH>` CLI arguments.
+    #[must_use]
+    pub fn junit_output(mut self, path: impl Into<PathBuf>) -> Self {
+        self.outputs.push((OutputFormat::Junit, path.into()));
+        self
+    }
 
-        if let Some(&sample_size) = matches.get_one("sample-size") {
-            self.bench_options.sample_size = Some(sample_size);
-        }
+    /// Writes results to an [OpenMetrics](https://openmetrics.io) file after
+    /// benchmarks finish, for graphing in tools like Grafana.
+    ///
+    /// Mean, median, fastest, and slowest times in seconds, median counter
+    /// throughputs, and sample and iteration counts are written as gauges
+    /// labeled by benchmark path.
+    ///
+    /// This option is equivalent to the `--output openmetrics <PATH>` CLI
+    /// arguments.
+    #[must_use]
+    pub fn openmetrics_output(mut self, path: impl Into<PathBuf>) -> Self {
+        self.outputs.push((OutputFormat::OpenMetrics, path.into()));
+        self
+    }
 
-        if let Some(&ParsedSeconds(min_time)) = matches.get_one("min-time") {
-            self.bench_options.min_time = Some(min_time);
-        }
+    /// Pushes results as [OpenMetrics](Self::openmetrics_output) gauges to a
+    /// Prometheus [Pushgateway](https://github.com/prometheus/pushgateway)
+    /// after benchmarks finish.
+    ///
+    /// `url` should include the grouping key, such as
+    /// `http://localhost:9091/metrics/job/divan`. Metrics in that group are
+    /// replaced by each push. Only `http://` URLs are supported.
+    ///
+    /// This option is equivalent to the `--push-metrics` CLI argument.
+    #[must_use]
+    pub fn push_metrics(mut self, url: impl Into<String>) -> Self {
+        self.push_metrics = Some(url.into());
+        self
+    }
 
-        if let Some(&ParsedSeconds(max_time)) = matches.get_one("max-time") {
-            self.bench_options.max_time = Some(max_time);
-        }
+    /// Posts a JSON document of results to `url` after benchmarks finish, for
+    /// bots that report to chat or dashboards.
+    ///
+    /// The document has the Git commit, machine, and environment of the run,
+    /// whether it passed, time statistics of each benchmark in picoseconds,
+    /// and messages describing failed, errored, and throttled benchmarks.
+    ///
+    /// Only plain `http://` URLs are supported, since Divan does not implement
+    /// TLS. To reach an `https://` webhook, post to a relay on the local
+    /// network that forwards requests to it.
+    ///
+    /// This option is equivalent to the `--notify-url` CLI argument.
+    #[cfg(feature = "notify")]
+    #[must_use]
+    pub fn notify_url(mut self, url: impl Into<String>) -> Self {
+        self.notify_url = Some(url.into());
+        self
+    }
 
-        if let Some(mut skip_ext_time) = matches.get_many::<bool>("skip-ext-time") {
-            // If the option is present without a value, then it's `true`.
-            self.bench_options.skip_ext_time =
-                Some(matches!(skip_ext_time.next(), Some(true) | None));
-        }
+    /// Writes every recorded sample to a [JSON Lines](https://jsonlines.org)
+    /// file at `path`, for analyzing the full distribution offline.
+    ///
+    /// Each line has the benchmark path, the sample's index, iteration count,
+    /// and total time in picoseconds, as well as the total counts of any
+    /// [counters](crate::counter) over those iterations.
+    ///
+    /// This option is equivalent to the `--dump-samples` CLI argument.
+    #[must_use]
+    pub fn dump_samples(mut self, path: impl Into<PathBuf>) -> Self {
+        self.dump_samples = Some(path.into());
+        self
+    }
 
+    /// Writes every recorded sample to a [Parquet](https://parquet.apache.org)
+    /// file at `path` after benchmarks finish, for analyzing the full
+    /// distribution with columnar data tools.
+    ///
+    /// Columns are `path`, `index`, `sample_size`, `duration_picos`, and the
+    /// total counts of any [counters](crate::counter) as `bytes`, `chars`, and
+    /// `items`, which are null for benchmarks without that counter.
+    ///
+    /// This option is equivalent to the `--dump-samples-parquet` CLI argument.
+    #[cfg(feature = "parquet")]
+    #[must_use]
+    pub fn dump_samples_parquet(mut self, path: impl Into<PathBuf>) -> Self {
+        self.dump_samples_parquet = Some(path.into());
         self
     }
 
-    /// Sets whether output should be colored.
+    /// Writes the time range of each benchmark to `path`, for attributing
+    /// samples of `perf record` to benchmarks.
     ///
-    /// This option is equivalent to the `--color` CLI argument, where [`None`]
-    /// here means "auto".
+    /// Each line is `<start>,<end> <path> t=<threads>`, with times in seconds
+    /// of `CLOCK_MONOTONIC`. When profiling with `perf record -k mono`, the
+    /// range can be passed directly to `perf report --time` or
+    /// `perf script --time` to show only that benchmark.
+    ///
+    /// This is only supported on Linux, and benchmarks run in separate
+    /// processes are not marked.
+    ///
+    /// This option is equivalent to the `--profile` CLI argument.
     #[must_use]
-    pub fn color(mut self, yes: impl Into<Option<bool>>) -> Self {
-        self.color = match yes.into() {
-            None => ColorChoice::Auto,
-            Some(true) => ColorChoice::Always,
-            Some(false) => ColorChoice::Never,
-        };
+    pub fn profile(mut self, path: impl Into<PathBuf>) -> Self {
+        self.profile = Some(path.into());
         self
     }
 
-    /// Determines how [`BytesCount`](crate::counter::BytesCount) is scaled in
-    /// benchmark outputs.
+    /// Checkpoints the results of each benchmark to `path` as it finishes, so
+    /// that an interrupted run can be resumed.
     ///
-    /// This option is equivalent to the `--bytes-format` CLI argument.
-    #[inline]
-    pub fn bytes_format(mut self, format: BytesFormat) -> Self {
-        self.bytes_format = format;
+    /// If `path` exists, benchmarks completed by a previous run are not
+    /// measured again, and their results are reported alongside those of the
+    /// remaining benchmarks. The file is removed once all benchmarks have run.
+    ///
+    /// Benchmarks that panicked or timed out are measured again. Individual
+    /// samples of reused results are not passed to
+    /// [reporters](Self::with_reporter) or tested for significance with
+    /// [`compare`](Self::compare).
+    ///
+    /// This has no effect when [interleaving](Self::interleave).
+    ///
+    /// This option is equivalent to the `--resume` CLI argument.
+    #[must_use]
+    pub fn resume(mut self, path: impl Into<PathBuf>) -> Self {
+        self.resume = Some(path.into());
         self
     }
 
-    /// Also run benchmarks marked [`#[ignore]`](https://doc.rust-lang.org/reference/attributes/testing.html#the-ignore-attribute).
+    /// Interleaves benchmarks in the same group or module, having them take
+    /// turns recording samples round-robin instead of each running to
+    /// completion.
     ///
-    /// This option is equivalent to the `--include-ignored` CLI argument.
+    /// This spreads thermal drift and background noise evenly across
+    /// benchmarks, which makes comparisons between alternatives fairer. Each
+    /// benchmark runs on its own thread, but only one runs at a time, and time
+    /// spent waiting for others does not count towards
+    /// [`min_time`](Self::min_time) or [`max_time`](Self::max_time). Results
+    /// are shown once all benchmarks in the group finish.
+    ///
+    /// This option is equivalent to the `--interleave` CLI argument.
     #[must_use]
-    pub fn run_ignored(mut self) -> Self {
-        self.run_ignored = RunIgnored::Yes;
+    pub fn interleave(mut self, yes: bool) -> Self {
+        self.interleave = yes;
         self
     }
 
-    /// Only run benchmarks marked [`#[ignore]`](https://doc.rust-lang.org/reference/attributes/testing.html#the-ignore-attribute).
+    /// Runs benchmarks in a random order, so that order-dependent effects
+    /// like cache warm-up and frequency scaling do not systematically favor
+    /// earlier benchmarks.
     ///
-    /// This option is equivalent to the `--ignored` CLI argument.
+    /// Each benchmark and thread count is measured in a shuffled order, but
+    /// results are still shown in sorted order once all benchmarks finish. The
+    /// seed is printed so that the order can be reproduced with
+    /// [`shuffle_seed`](Self::shuffle_seed).
+    ///
+    /// This has no effect when [interleaving](Self::interleave).
+    ///
+    /// This option is equivalent to the `--shuffle` CLI argument.
     #[must_use]
-    pub fn run_only_ignored(mut self) -> Self {
-        self.run_ignored = RunIgnored::Only;
+    pub fn shuffle(mut self, yes: bool) -> Self {
+        self.shuffle = yes;
         self
     }
 
-    /// Skips benchmarks that match `filter` as a regular expression pattern.
+    /// Runs benchmarks in a random order determined by `seed`.
     ///
-    /// This option is equivalent to the `--skip filter` CLI argument, without
-    /// `--exact`.
+    /// See [`shuffle`](Self::shuffle) for details.
     ///
-    /// # Examples
+    /// This option is equivalent to the `--shuffle=<SEED>` CLI argument.
+    #[must_use]
+    pub fn shuffle_seed(mut self, seed: u64) -> Self {
+        self.shuffle = true;
+        self.shuffle_seed = Some(seed);
+        self
+    }
+
+    /// Runs the entire suite `count` times and reports statistics aggregated
+    /// across runs.
     ///
-    /// This method is commonly used with a [`&str`](prim@str) or [`String`]:
+    /// Samples within a single run share code layout, allocator state, and
+    /// system conditions, so their variance underestimates how much results
+    /// differ between runs. The "run stddev" column shows the standard
+    /// deviation of mean time between runs, and the "stddev" column includes
+    /// it.
     ///
-    /// ```
-    /// # use divan::Divan;
-    /// let filter = "(add|sub)";
-    /// let divan = Divan::default().skip_regex(filter);
-    /// ```
+    /// All runs finish before results are shown. When
+    /// [shuffling](Self::shuffle), each run uses a different order derived from
+    /// the shuffle seed. Individual samples passed to
+    /// [reporters](Self::with_reporter) are those of the last run.
     ///
-    /// A pre-built [`Regex`] can also be provided:
+    /// This has no effect when [interleaving](Self::interleave),
+    /// [isolating](Self::isolate), or counting with [Callgrind](Self::callgrind).
     ///
-    /// ```
-    /// # use divan::Divan;
-    /// let filter = regex::Regex::new("(add|sub)").unwrap();
-    /// let divan = Divan::default().skip_regex(filter);
-    /// ```
+    /// This option is equivalent to the `--runs` CLI argument.
+    #[must_use]
+    pub fn runs(mut self, count: u32) -> Self {
+        self.runs = Some(count);
+        self
+    }
+
+    /// Sets the seed for randomly generated inputs, so that they are
+    /// reproducible across runs and machines.
     ///
-    /// Calling this repeatedly will add multiple skip filters:
+    /// The seed is available through [`Bencher::seed`](crate::Bencher::seed)
+    /// and used by `Bencher::with_rng_inputs` when the `rand` feature is
+    /// enabled. If unset, a random seed is chosen
+    /// for each run. The seed is included in history records and JSON
+    /// messages.
     ///
-    /// ```
-    /// # use divan::Divan;
-    /// let divan = Divan::default()
-    ///     .skip_regex("(add|sub)")
-    ///     .skip_regex("collections.*default");
-    /// ```
+    /// This option is equivalent to the `--seed <SEED>` CLI argument.
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Prints the timer kind, sample loop overhead, thread counts, and
+    /// environment before benchmarking, along with the timer precision that is
+    /// always shown.
     ///
-    /// # Panics
+    /// This is useful for understanding results that look wrong, such as times
+    /// close to the timer's precision or the overhead subtracted from samples.
     ///
-    /// Panics if `filter` is a string and [`Regex::new`] fails.
+    /// This option is equivalent to the `--verbose` CLI argument.
     #[must_use]
-    pub fn skip_regex(mut self, filter: impl SkipRegex) -> Self {
-        impl SkipRegex for Regex {
-            fn skip_regex(self, divan: &mut Divan) {
-                divan.skip_filters.push(Filter::Regex(self));
-            }
-        }
+    pub fn verbose(mut self, yes: bool) -> Self {
+        self.verbose = yes;
+        self
+    }
 
-        impl SkipRegex for &str {
-            #[track_caller]
-            fn skip_regex(self, divan: &mut Divan) {
-                Regex::new(self).unwrap().skip_regex(divan);
-            }
-        }
+    /// Prints a summary row below each module and group, and a total row for
+    /// all benchmarks at the end.
+    ///
+    /// Summary rows show the geometric mean of benchmarks' mean times and
+    /// their total iteration count. Unlike the sum or arithmetic mean, the
+    /// geometric mean weighs each benchmark equally regardless of its scale,
+    /// which gives a single number for whether a change helped overall.
+    ///
+    /// This option is equivalent to the `--summary` CLI argument.
+    #[must_use]
+    pub fn summary(mut self, yes: bool) -> Self {
+        self.summary = yes;
+        self
+    }
 
-        impl SkipRegex for String {
-            #[track_caller]
-            fn skip_regex(self, divan: &mut Divan) {
-                self.as_str().skip_regex(divan)
-            }
-        }
+    /// Reports failures, errors, and throttling as GitHub Actions annotations,
+    /// and appends a Markdown table of results to the job summary at
+    /// `$GITHUB_STEP_SUMMARY`.
+    ///
+    /// Combined with [`fail_on_regression`](Self::fail_on_regression), this
+    /// makes benchmarks a CI gate whose regressions are shown on pull
+    /// requests.
+    ///
+    /// This option is equivalent to the `--github` CLI argument.
+    #[must_use]
+    pub fn github(mut self, yes: bool) -> Self {
+        self.github = yes;
+        self
+    }
 
-        filter.skip_regex(&mut self);
+    /// Raises the scheduling priority of the benchmarking process, so that it
+    /// is preempted less often by other processes on busy machines.
+    ///
+    /// On Unix, this sets the process's nice value to -20. On Windows, this
+    /// sets the process's priority class to high and the main thread's priority
+    /// to highest. Raising priority often requires elevated permissions, so a
+    /// warning is printed if it fails and benchmarks run at normal priority.
+    ///
+    /// This option is equivalent to the `--high-priority` CLI argument.
+    #[must_use]
+    pub fn high_priority(mut self, yes: bool) -> Self {
+        self.high_priority = yes;
         self
     }
 
-    /// Skips benchmarks that exactly match `filter`.
+    /// Prints the running benchmark, its sample count, and its interim mean to
+    /// stderr on `SIGUSR1`, or `SIGINFO` (Ctrl+T) on BSD and macOS, for
+    /// diagnosing runs that appear hung.
     ///
-    /// This option is equivalent to the `--skip filter --exact` CLI arguments.
+    /// This is supported on BSD, macOS, and Linux on x86, ARM, RISC-V, and
+    /// MIPS. A signal that already has a handler or is ignored is left alone.
     ///
-    /// # Examples
+    /// This option is equivalent to the `--status-signal` CLI argument.
+    #[must_use]
+    pub fn status_signal(mut self, yes: bool) -> Self {
+        self.status_signal = yes;
+        self
+    }
+
+    /// Runs each benchmark in a freshly spawned process, so that allocator
+    /// state, caches, and global initialization from one benchmark do not
+    /// leak into the next.
     ///
-    /// This method is commonly used with a [`&str`](prim@str) or [`String`]:
+    /// The benchmark binary re-runs itself with the same arguments for each
+    /// benchmark, and results are reported by the original process. Individual
+    /// samples are not passed to [reporters](Self::with_reporter) or
+    /// [`dump_samples`](Self::dump_samples).
     ///
-    /// ```
-    /// # use divan::Divan;
-    /// let filter = "arithmetic::add";
-    /// let divan = Divan::default().skip_exact(filter);
-    /// ```
+    /// This has no effect when [interleaving](Self::interleave),
+    /// [shuffling](Self::shuffle), or counting with
+    /// [Callgrind](Self::callgrind), which already runs each benchmark in a
+    /// separate process.
     ///
-    /// Calling this repeatedly will add multiple skip filters:
+    /// This option is equivalent to the `--isolate` CLI argument.
+    #[must_use]
+    pub fn isolate(mut self, yes: bool) -> Self {
+        self.isolate = yes;
+        self
+    }
+
+    /// Adds a [`Reporter`] that receives benchmark progress and results.
     ///
-    /// ```
-    /// # use divan::Divan;
-    /// let divan = Divan::default()
-    ///     .skip_exact("arithmetic::add")
-    ///     .skip_exact("collections::vec::default");
-    /// ```
+    /// Multiple reporters may be added, and they are called in the order they
+    /// were added. Combine with [`Divan::tree_output`] to replace the default
+    /// output instead of augmenting it.
     #[must_use]
-    pub fn skip_exact(mut self, filter: impl Into<String>) -> Self {
-        self.skip_filters.push(Filter::Exact(filter.into()));
+    pub fn with_reporter(mut self, reporter: impl Reporter + 'static) -> Self {
+        self.reporters.get_mut().push(Box::new(reporter));
         self
     }
 
-    /// Sets the number of sampling iterations.
+    /// Adds a [`ProfilerHook`] that is started and stopped around the sample
+    /// collection of each benchmark.
     ///
-    /// This option is equivalent to the `--sample-count` CLI argument.
+    /// Multiple hooks may be added. They are started in the order they were
+    /// added and stopped in reverse order.
+    #[must_use]
+    pub fn with_profiler_hook(mut self, hook: impl ProfilerHook + 'static) -> Self {
+        self.profiler_hooks.push(Arc::new(Mutex::new(hook)));
+        self
+    }
+
+    /// Sets a function that is called with the benchmark's path before each
+    /// sample, such as for resetting caches or enabling instrumentation.
     ///
-    /// If a benchmark enables [`threads`](macro@crate::bench#threads), sample
-    /// count becomes a multiple of the number of threads. This is because each
-    /// thread operates over the same sample size to ensure there are always N
-    /// competing threads doing the same amount of work.
-    #[inline]
-    pub fn sample_count(mut self, count: u32) -> Self {
-        self.bench_options.sample_count = Some(count);
+    /// Unlike a [`ProfilerHook`], this is called for every sample, including
+    /// warm-up samples. It runs on the main benchmark thread outside of the
+    /// timed region, and its time does not count towards
+    /// [`min_time`](Self::min_time) or [`max_time`](Self::max_time).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    ///
+    /// static TRACING: AtomicBool = AtomicBool::new(false);
+    ///
+    /// fn main() {
+    ///     divan::Divan::from_args()
+    ///         .on_sample_begin(|_| TRACING.store(true, Ordering::Relaxed))
+    ///         .on_sample_end(|_| TRACING.store(false, Ordering::Relaxed))
+    ///         .main();
+    /// }
+    /// ```
+    #[must_use]
+    pub fn on_sample_begin(mut self, hook: SampleHook) -> Self {
+        self.on_sample_begin = Some(hook);
         self
     }
 
-    /// Sets the number of iterations inside a single sample.
+    /// Sets a function that is called with the benchmark's path after each
+    /// sample.
     ///
-    /// This option is equivalent to the `--sample-size` CLI argument.
-    #[inline]
-    pub fn sample_size(mut self, count: u32) -> Self {
-        self.bench_options.sample_size = Some(count);
+    /// See [`Divan::on_sample_begin`] for details.
+    #[must_use]
+    pub fn on_sample_end(mut self, hook: SampleHook) -> Self {
+        self.on_sample_end = Some(hook);
         self
     }
 
-    /// Sets the time floor for benchmarking a function.
+    /// Registers a benchmark at runtime, for benchmarks that cannot be
+    /// declared with [`#[divan::bench]`](macro@crate::bench), such as ones
+    /// generated from queries or configuration files.
     ///
-    /// This option is equivalent to the `--min-time` CLI argument.
-    #[inline]
-    pub fn min_time(mut self, time: Duration) -> Self {
-        self.bench_options.min_time = Some(time);
+    /// `path` is the benchmark's `::`-separated parent names followed by its
+    /// name, which places it in the tree alongside benchmarks registered by
+    /// attributes. `options` are the same as those set in
+    /// [`#[divan::bench]`](macro@crate::bench), and are overridden by CLI
+    /// arguments.
+    ///
+    /// The benchmark is leaked so that it lives as long as attribute-registered
+    /// benchmarks. Benchmarks should be registered the same way every time the
+    /// binary runs, since [`isolate`](Self::isolate) and
+    /// [Callgrind](Self::callgrind) re-run it to find each benchmark.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use divan::{BenchOptions, Divan};
+    ///
+    /// let queries = [("by_id", 1), ("by_name", 2)];
+    ///
+    /// let mut divan = Divan::from_args();
+    /// for (name, query) in queries {
+    ///     let options = BenchOptions { sample_count: Some(10), ..BenchOptions::default() };
+    ///
+    ///     divan = divan.add_bench(&format!("queries::{name}"), options, move |bencher| {
+    ///         bencher.bench(|| divan::black_box(query) * 2);
+    ///     });
+    /// }
+    ///
+    /// divan.main();
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn add_bench(
+        mut self,
+        path: &str,
+        options: BenchOptions,
+        bench: impl Fn(Bencher) + Send + Sync + 'static,
+    ) -> Self {
+        let location = std::panic::Location::caller();
+        self.dynamic_benches.push(BenchEntry::leak_dynamic(path, options, location, bench));
         self
     }
 
-    /// Sets the time ceiling for benchmarking a function.
+    /// Registers options at runtime for the group of benchmarks at `path`,
+    /// like [`#[divan::bench_group]`](macro@crate::bench_group).
     ///
-    /// This option is equivalent to the `--max-time` CLI argument.
-    #[inline]
-    pub fn max_time(mut self, time: Duration) -> Self {
-        self.bench_options.min_time = Some(time);
+    /// `path` is the group's `::`-separated parent names followed by its name.
+    /// The group's options apply to all benchmarks within it, including ones
+    /// registered by attributes and by [`Divan::add_bench`].
+    #[must_use]
+    #[track_caller]
+    pub fn add_group(mut self, path: &str, options: BenchOptions) -> Self {
+        let location = std::panic::Location::caller();
+        self.dynamic_groups.push(GroupEntry::leak_dynamic(path, options, location));
         self
     }
 
-    /// When accounting for `min_time` or `max_time`, skip time external to
-    /// benchmarked functions.
+    /// Sets whether the tree of benchmark results is printed to stdout.
     ///
-    /// This option is equivalent to the `--skip-ext-time` CLI argument.
-    #[inline]
-    pub fn skip_ext_time(mut self, skip: bool) -> Self {
-        self.bench_options.skip_ext_time = Some(skip);
+    /// This is `true` by default.
+    #[must_use]
+    pub fn tree_output(mut self, yes: bool) -> Self {
+        self.hide_tree = !yes;
         self
     }
 }
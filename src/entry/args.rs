@@ -0,0 +1,66 @@
+use std::sync::OnceLock;
+
+use crate::{miri, Bencher};
+
+/// Runtime arguments for `#[divan::bench(args = ...)]`.
+///
+/// Arguments are evaluated once, the first time the benchmark tree is built.
+pub struct BenchArgs {
+    runner: OnceLock<BenchArgsRunner>,
+}
+
+/// Evaluated arguments along with the benchmark function to run them with.
+pub struct BenchArgsRunner {
+    /// Each argument's [`ToString`] result.
+    names: &'static [&'static str],
+
+    /// Benchmarks the argument at the given index.
+    bench: Box<dyn Fn(Bencher, usize) + Send + Sync>,
+}
+
+impl BenchArgs {
+    /// Creates storage for arguments that have not yet been evaluated.
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self { runner: OnceLock::new() }
+    }
+
+    /// Evaluates the arguments on first call, and returns a runner for them.
+    pub fn runner<I, B>(
+        &'static self,
+        make_args: impl FnOnce() -> I,
+        bench: B,
+    ) -> &'static BenchArgsRunner
+    where
+        I: IntoIterator,
+        I::Item: ToString + Send + Sync + 'static,
+        B: Fn(Bencher, &I::Item) + Send + Sync + 'static,
+    {
+        self.runner.get_or_init(|| {
+            let args: Vec<I::Item> = make_args().into_iter().collect();
+
+            let names: Vec<&'static str> = args
+                .iter()
+                .map(|arg| miri::leak(Box::leak(arg.to_string().into_boxed_str())))
+                .collect();
+
+            BenchArgsRunner {
+                names: miri::leak(Box::leak(names.into_boxed_slice())),
+                bench: Box::new(move |bencher, index| bench(bencher, &args[index])),
+            }
+        })
+    }
+}
+
+impl BenchArgsRunner {
+    /// Returns the names of the arguments in the order they were provided.
+    #[inline]
+    pub(crate) fn arg_names(&self) -> &'static [&'static str] {
+        self.names
+    }
+
+    #[inline]
+    pub(crate) fn bench(&self, bencher: Bencher, index: usize) {
+        (self.bench)(bencher, index)
+    }
+}
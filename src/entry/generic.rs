@@ -5,7 +5,7 @@ use std::{
     sync::OnceLock,
 };
 
-use crate::{entry::GroupEntry, Bencher};
+use crate::entry::{BenchEntryRunner, GroupEntry};
 
 /// Compile-time entry for a generic benchmark function, generated by
 /// `#[divan::bench]`.
@@ -21,7 +21,7 @@ pub struct GenericBenchEntry {
     pub group: &'static GroupEntry,
 
     /// The benchmarking function.
-    pub bench: fn(Bencher),
+    pub bench: BenchEntryRunner,
 
     /// A generic type.
     pub ty: Option<EntryType>,
@@ -150,7 +150,7 @@ impl EntryConst {
     /// Returns [`PartialOrd::partial_cmp`] ordering if `<` or `>, falling back
     /// to comparing [`ToString::to_string`] otherwise.
     pub(crate) fn cmp_name(&self, other: &Self) -> Ordering {
-        if self.partial_cmp == other.partial_cmp {
+        if std::ptr::fn_addr_eq(self.partial_cmp, other.partial_cmp) {
             // SAFETY: Both constants have the same comparison function, so they
             // must be the same type.
             if let Some(ordering) = unsafe { (self.partial_cmp)(self.value, other.value) } {
@@ -1,4 +1,4 @@
-use std::sync::OnceLock;
+use std::{panic::Location, sync::OnceLock};
 
 use crate::bench::BenchOptions;
 
@@ -18,6 +18,11 @@ pub struct EntryMeta {
     /// Where the entry was defined.
     pub location: EntryLocation,
 
+    /// Labels for selecting benchmarks with `--tag`.
+    ///
+    /// A group's tags also apply to the benchmarks within it.
+    pub tags: &'static [&'static str],
+
     /// Configures the benchmarker via attribute options.
     pub get_bench_options: Option<fn() -> BenchOptions>,
 
@@ -35,13 +40,46 @@ pub struct EntryLocation {
 }
 
 impl EntryMeta {
+    /// Leaks metadata for an entry registered at runtime, whose `path` is its
+    /// `::`-separated parent names followed by its name.
+    pub(crate) fn leak_dynamic(
+        path: &str,
+        options: BenchOptions,
+        location: &'static Location<'static>,
+    ) -> Self {
+        let path: &'static str = Box::leak(path.into());
+        let (module_path, name) = path.rsplit_once("::").unwrap_or(("", path));
+
+        Self {
+            display_name: name,
+            raw_name: name,
+            module_path,
+            location: EntryLocation {
+                file: location.file(),
+                line: location.line(),
+                col: location.column(),
+            },
+            tags: &[],
+            get_bench_options: None,
+            cached_bench_options: OnceLock::from(options),
+        }
+    }
+
     #[inline]
     pub(crate) fn bench_options(&self) -> Option<&BenchOptions> {
+        // Entries registered at runtime have their options set up front.
+        if let Some(options) = self.cached_bench_options.get() {
+            return Some(options);
+        }
+
         Some(self.cached_bench_options.get_or_init(self.get_bench_options?))
     }
 
+    /// Returns the names of the entry's parents.
+    ///
+    /// This is empty for top-level entries registered at runtime.
     #[inline]
     pub(crate) fn module_path_components<'a>(&self) -> impl Iterator<Item = &'a str> {
-        self.module_path.split("::")
+        self.module_path.split("::").filter(|component| !component.is_empty())
     }
 }
@@ -1,16 +1,20 @@
-use std::ptr::NonNull;
+use std::{panic::Location, ptr::NonNull};
 
-use crate::Bencher;
+use crate::{bench::BenchOptions, Bencher};
 
+mod args;
 mod generic;
 mod list;
 mod meta;
+mod setup;
 mod tree;
 
 pub use self::{
+    args::{BenchArgs, BenchArgsRunner},
     generic::{EntryConst, EntryType, GenericBenchEntry},
     list::EntryList,
     meta::{EntryLocation, EntryMeta},
+    setup::{BenchSetup, BenchSetupRunner},
 };
 pub(crate) use tree::EntryTree;
 
@@ -50,7 +54,78 @@ pub struct BenchEntry {
     pub meta: EntryMeta,
 
     /// The benchmarking function.
-    pub bench: fn(Bencher),
+    pub bench: BenchEntryRunner,
+}
+
+/// How a `BenchEntry` or `GenericBenchEntry` runs its benchmark.
+pub enum BenchEntryRunner {
+    /// Benchmark without arguments.
+    Plain(fn(Bencher)),
+
+    /// Benchmark over runtime arguments, which are provided by the returned
+    /// runner.
+    Args(fn() -> &'static BenchArgsRunner),
+
+    /// Benchmark with state from `setup`, which is provided along with the
+    /// benchmark by the returned runner.
+    Setup(fn() -> &'static BenchSetupRunner),
+
+    /// Benchmark registered at runtime by [`Divan::add_bench`](crate::Divan::add_bench).
+    Dynamic(&'static (dyn Fn(Bencher) + Send + Sync)),
+}
+
+impl BenchEntry {
+    /// Leaks a benchmark registered at runtime, whose `path` is its
+    /// `::`-separated parent names followed by its name.
+    pub(crate) fn leak_dynamic(
+        path: &str,
+        options: BenchOptions,
+        location: &'static Location<'static>,
+        bench: impl Fn(Bencher) + Send + Sync + 'static,
+    ) -> &'static Self {
+        let bench: &'static (dyn Fn(Bencher) + Send + Sync) = Box::leak(Box::new(bench));
+
+        Box::leak(Box::new(Self {
+            meta: EntryMeta::leak_dynamic(path, options, location),
+            bench: BenchEntryRunner::Dynamic(bench),
+        }))
+    }
+
+    /// Returns an entry for each runtime argument, or the entry itself if it
+    /// does not take arguments.
+    pub(crate) fn any_entries(&self) -> Vec<AnyBenchEntry<'_>> {
+        self.bench.any_entries(AnyBenchEntry::Bench(self), |args, index| AnyBenchEntry::Arg {
+            entry: self,
+            args,
+            index,
+        })
+    }
+}
+
+impl GenericBenchEntry {
+    /// Returns an entry for each runtime argument, or the entry itself if it
+    /// does not take arguments.
+    pub(crate) fn any_entries(&self) -> Vec<AnyBenchEntry<'_>> {
+        self.bench.any_entries(AnyBenchEntry::GenericBench(self), |args, index| {
+            AnyBenchEntry::GenericArg { entry: self, args, index }
+        })
+    }
+}
+
+impl BenchEntryRunner {
+    fn any_entries<'a>(
+        &self,
+        plain: AnyBenchEntry<'a>,
+        arg: impl Fn(&'static BenchArgsRunner, usize) -> AnyBenchEntry<'a>,
+    ) -> Vec<AnyBenchEntry<'a>> {
+        match *self {
+            Self::Plain(_) | Self::Setup(_) | Self::Dynamic(_) => vec![plain],
+            Self::Args(get_args) => {
+                let args = get_args();
+                (0..args.arg_names().len()).map(|index| arg(args, index)).collect()
+            }
+        }
+    }
 }
 
 /// Compile-time entry for a benchmark group, generated by
@@ -68,16 +143,36 @@ pub struct GroupEntry {
 }
 
 impl GroupEntry {
+    /// Leaks a group registered at runtime, whose `path` is its `::`-separated
+    /// parent names followed by its name.
+    pub(crate) fn leak_dynamic(
+        path: &str,
+        options: BenchOptions,
+        location: &'static Location<'static>,
+    ) -> &'static Self {
+        Box::leak(Box::new(Self {
+            meta: EntryMeta::leak_dynamic(path, options, location),
+            generic_benches: None,
+        }))
+    }
+
     pub(crate) fn generic_benches_iter(&self) -> impl Iterator<Item = &'static GenericBenchEntry> {
         self.generic_benches.unwrap_or_default().iter().flat_map(|benches| benches.iter())
     }
+
+    /// Returns entries for each generic benchmark and its runtime arguments.
+    pub(crate) fn generic_any_entries(&self) -> impl Iterator<Item = AnyBenchEntry<'static>> {
+        self.generic_benches_iter().flat_map(GenericBenchEntry::any_entries)
+    }
 }
 
-/// `BenchEntry` or `GenericBenchEntry`.
+/// `BenchEntry`, `GenericBenchEntry`, or one of their runtime arguments.
 #[derive(Clone, Copy)]
 pub(crate) enum AnyBenchEntry<'a> {
     Bench(&'a BenchEntry),
+    Arg { entry: &'a BenchEntry, args: &'a BenchArgsRunner, index: usize },
     GenericBench(&'a GenericBenchEntry),
+    GenericArg { entry: &'a GenericBenchEntry, args: &'a BenchArgsRunner, index: usize },
 }
 
 impl<'a> AnyBenchEntry<'a> {
@@ -86,23 +181,70 @@ impl<'a> AnyBenchEntry<'a> {
     pub fn entry_addr(self) -> NonNull<()> {
         match self {
             Self::Bench(entry) => NonNull::from(entry).cast(),
+
+            // Names are contiguous, so arguments sort by location in the order
+            // they were provided.
+            Self::Arg { args, index, .. } | Self::GenericArg { args, index, .. } => {
+                NonNull::from(&args.arg_names()[index]).cast()
+            }
+
             Self::GenericBench(entry) => NonNull::from(entry).cast(),
         }
     }
 
+    /// Runs the benchmark with `bencher`.
+    ///
+    /// This dispatches once per benchmark run, before the sample loop, which is
+    /// monomorphized over the benchmark's closures.
     #[inline]
     pub fn bench(self, bencher: Bencher) {
         match self {
-            Self::Bench(BenchEntry { bench, .. })
-            | Self::GenericBench(GenericBenchEntry { bench, .. }) => bench(bencher),
+            Self::Bench(BenchEntry { bench: BenchEntryRunner::Plain(bench), .. })
+            | Self::GenericBench(GenericBenchEntry {
+                bench: BenchEntryRunner::Plain(bench), ..
+            }) => bench(bencher),
+
+            Self::Bench(BenchEntry { bench: BenchEntryRunner::Dynamic(bench), .. })
+            | Self::GenericBench(GenericBenchEntry {
+                bench: BenchEntryRunner::Dynamic(bench),
+                ..
+            }) => bench(bencher),
+
+            Self::Bench(BenchEntry { bench: BenchEntryRunner::Setup(runner), .. })
+            | Self::GenericBench(GenericBenchEntry {
+                bench: BenchEntryRunner::Setup(runner),
+                ..
+            }) => runner().bench(bencher),
+
+            // Entries with arguments are only run through `Self::Arg` and
+            // `Self::GenericArg`.
+            Self::Bench(BenchEntry { bench: BenchEntryRunner::Args(_), .. })
+            | Self::GenericBench(GenericBenchEntry { bench: BenchEntryRunner::Args(_), .. }) => {}
+
+            Self::Arg { args, index, .. } | Self::GenericArg { args, index, .. } => {
+                args.bench(bencher, index)
+            }
+        }
+    }
+
+    /// Runs the `teardown` of a benchmark with `setup` state, if it was run.
+    pub fn teardown(self) {
+        match self {
+            Self::Bench(BenchEntry { bench: BenchEntryRunner::Setup(runner), .. })
+            | Self::GenericBench(GenericBenchEntry {
+                bench: BenchEntryRunner::Setup(runner),
+                ..
+            }) => runner().teardown(),
+
+            _ => {}
         }
     }
 
     #[inline]
     pub fn meta(self) -> &'a EntryMeta {
         match self {
-            Self::Bench(entry) => &entry.meta,
-            Self::GenericBench(entry) => &entry.group.meta,
+            Self::Bench(entry) | Self::Arg { entry, .. } => &entry.meta,
+            Self::GenericBench(entry) | Self::GenericArg { entry, .. } => &entry.group.meta,
         }
     }
 
@@ -110,6 +252,9 @@ impl<'a> AnyBenchEntry<'a> {
     pub fn raw_name(self) -> &'a str {
         match self {
             Self::Bench(entry) => entry.meta.raw_name,
+            Self::Arg { args, index, .. } | Self::GenericArg { args, index, .. } => {
+                args.arg_names()[index]
+            }
             Self::GenericBench(entry) => entry.raw_name(),
         }
     }
@@ -118,7 +263,76 @@ impl<'a> AnyBenchEntry<'a> {
     pub fn display_name(self) -> &'a str {
         match self {
             Self::Bench(entry) => entry.meta.display_name,
+            Self::Arg { args, index, .. } | Self::GenericArg { args, index, .. } => {
+                args.arg_names()[index]
+            }
             Self::GenericBench(entry) => entry.display_name(),
         }
     }
+
+    /// Returns the display name of the generic type, if any.
+    #[inline]
+    pub fn generic_type_name(self) -> Option<&'a str> {
+        match self {
+            Self::GenericBench(entry) | Self::GenericArg { entry, .. } => {
+                entry.ty.as_ref().map(|ty| ty.display_name())
+            }
+            Self::Bench(_) | Self::Arg { .. } => None,
+        }
+    }
+
+    /// Returns the name of the `const` value, if any.
+    #[inline]
+    pub fn generic_const_name(self) -> Option<&'a str> {
+        match self {
+            Self::GenericBench(entry) | Self::GenericArg { entry, .. } => {
+                entry.const_value.as_ref().map(|value| value.name())
+            }
+            Self::Bench(_) | Self::Arg { .. } => None,
+        }
+    }
+
+    /// Returns the `const` value as a number, if it is a non-negative number.
+    pub fn generic_const_value(self) -> Option<f64> {
+        let value: f64 = self.generic_const_name()?.parse().ok()?;
+        (value.is_finite() && value >= 0.0).then_some(value)
+    }
+
+    /// Returns `path` with generic parameters written like Rust generics,
+    /// such as `crate::sort<u64, 1024>` for `crate::sort::u64::1024`.
+    ///
+    /// Returns `None` if the entry is not generic.
+    pub fn generic_path(self, path: &str) -> Option<String> {
+        let ty = self.generic_type_name();
+        let const_value = self.generic_const_name();
+
+        if ty.is_none() && const_value.is_none() {
+            return None;
+        }
+
+        // Arguments are children of their type or `const`.
+        let (mut prefix, arg) = match self {
+            Self::GenericArg { .. } => {
+                let (prefix, arg) = path.rsplit_once("::")?;
+                (prefix, Some(arg))
+            }
+            _ => (path, None),
+        };
+
+        // Type names may contain `::`, so strip known names rather than
+        // splitting.
+        for param in [const_value, ty].into_iter().flatten() {
+            prefix = prefix.strip_suffix(param)?.strip_suffix("::")?;
+        }
+
+        let params: Vec<&str> = [ty, const_value].into_iter().flatten().collect();
+        let mut generic_path = format!("{prefix}<{}>", params.join(", "));
+
+        if let Some(arg) = arg {
+            generic_path.push_str("::");
+            generic_path.push_str(arg);
+        }
+
+        Some(generic_path)
+    }
 }
@@ -0,0 +1,92 @@
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use crate::Bencher;
+
+/// State for `#[divan::bench(setup = ..., teardown = ...)]`.
+///
+/// `setup` runs the first time the benchmark is run, and `teardown` runs after
+/// its last run. The state is therefore shared by every thread count and
+/// `--runs` repetition.
+pub struct BenchSetup {
+    runner: OnceLock<BenchSetupRunner>,
+}
+
+/// The benchmark function along with its setup, teardown, and state.
+pub struct BenchSetupRunner {
+    state: Box<dyn SetupState>,
+}
+
+/// Type-erased [`SetupStateImp`].
+trait SetupState: Send + Sync {
+    fn bench(&self, bencher: Bencher);
+    fn teardown(&self);
+}
+
+struct SetupStateImp<S, F, B, T> {
+    /// The value returned by `setup`, or `None` before the first run or after
+    /// teardown.
+    value: Mutex<Option<S>>,
+    setup: F,
+    bench: B,
+    teardown: T,
+}
+
+impl BenchSetup {
+    /// Creates storage for state that has not yet been set up.
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self { runner: OnceLock::new() }
+    }
+
+    /// Returns a runner for the benchmark, which calls `setup` when first run.
+    pub fn runner<S, F, B, T>(
+        &'static self,
+        setup: F,
+        bench: B,
+        teardown: T,
+    ) -> &'static BenchSetupRunner
+    where
+        S: Send + 'static,
+        F: Fn() -> S + Send + Sync + 'static,
+        B: Fn(Bencher, &S) + Send + Sync + 'static,
+        T: Fn(S) + Send + Sync + 'static,
+    {
+        self.runner.get_or_init(|| BenchSetupRunner {
+            state: Box::new(SetupStateImp { value: Mutex::new(None), setup, bench, teardown }),
+        })
+    }
+}
+
+impl BenchSetupRunner {
+    #[inline]
+    pub(crate) fn bench(&self, bencher: Bencher) {
+        self.state.bench(bencher)
+    }
+
+    /// Runs `teardown` if the benchmark was set up.
+    #[inline]
+    pub(crate) fn teardown(&self) {
+        self.state.teardown()
+    }
+}
+
+impl<S, F, B, T> SetupState for SetupStateImp<S, F, B, T>
+where
+    S: Send,
+    F: Fn() -> S + Send + Sync,
+    B: Fn(Bencher, &S) + Send + Sync,
+    T: Fn(S) + Send + Sync,
+{
+    fn bench(&self, bencher: Bencher) {
+        let mut value = self.value.lock().unwrap_or_else(PoisonError::into_inner);
+        let value = value.get_or_insert_with(&self.setup);
+        (self.bench)(bencher, value)
+    }
+
+    fn teardown(&self) {
+        let value = self.value.lock().unwrap_or_else(PoisonError::into_inner).take();
+        if let Some(value) = value {
+            (self.teardown)(value)
+        }
+    }
+}
@@ -4,7 +4,8 @@ use crate::{
     bench::{BenchOptions, DEFAULT_SAMPLE_COUNT},
     config::SortingAttr,
     counter::KnownCounterKind,
-    entry::{AnyBenchEntry, EntryLocation, EntryMeta, GenericBenchEntry, GroupEntry},
+    entry::{AnyBenchEntry, EntryConst, EntryLocation, EntryMeta, GroupEntry},
+    time::FineDuration,
     tree_painter::TreeColumn,
 };
 
@@ -35,9 +36,22 @@ impl<'a> EntryTree<'a> {
                 AnyBenchEntry::Bench(bench) => {
                     insert_entry(&mut bench.meta.module_path_components());
                 }
+                AnyBenchEntry::Arg { entry, .. } => {
+                    // Arguments are children of their benchmark.
+                    insert_entry(
+                        &mut entry
+                            .meta
+                            .module_path_components()
+                            .chain(Some(entry.meta.display_name)),
+                    );
+                }
                 AnyBenchEntry::GenericBench(bench) => {
                     insert_entry(&mut bench.path_components());
                 }
+                AnyBenchEntry::GenericArg { entry, .. } => {
+                    // Arguments are children of their type or `const`.
+                    insert_entry(&mut entry.path_components().chain(Some(entry.display_name())));
+                }
             }
         }
 
@@ -60,13 +74,67 @@ impl<'a> EntryTree<'a> {
             .unwrap_or_default()
     }
 
+    /// Returns the maximum span for a row labeled with `label_len` characters
+    /// below the children of a parent in `tree`, such as a summary row.
+    pub fn max_summary_span(tree: &[Self], depth: usize, label_len: usize) -> usize {
+        tree.iter()
+            .filter(|node| matches!(node, Self::Parent { .. }))
+            .map(|node| {
+                let node_span = depth * 3 + label_len;
+                let children_max = Self::max_summary_span(node.children(), depth + 1, label_len);
+
+                node_span.max(children_max)
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Returns the maximum span for a full `::`-separated path in `tree`, as
+    /// shown by flat output.
+    pub fn max_path_span(tree: &[Self], parent_span: usize) -> usize {
+        tree.iter()
+            .map(|node| {
+                let node_name_len = node.display_name().chars().count();
+                let node_path_span = match parent_span {
+                    0 => node_name_len,
+                    _ => parent_span + "::".len() + node_name_len,
+                };
+
+                let children_max = Self::max_path_span(node.children(), node_path_span);
+
+                node_path_span.max(children_max)
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
     /// Returns the likely span for a given column.
     pub fn common_column_width(tree: &[Self], column: TreeColumn) -> usize {
         // Time and throughput info.
-        if column.is_time_stat() {
+        if column.is_time_stat() || column == TreeColumn::RunStdDev || column == TreeColumn::Wall {
             return KnownCounterKind::MAX_COMMON_COLUMN_WIDTH;
         }
 
+        // Percentage change, e.g. "-12.34%".
+        if column == TreeColumn::Delta {
+            return 7;
+        }
+
+        // Significance with p-value, e.g. "yes (p=0.001)".
+        if column == TreeColumn::Significance {
+            return 13;
+        }
+
+        // Event count with metric prefix, e.g. "1.234 K".
+        if column.is_hw_stat() || column.perf_event_index().is_some() {
+            return 7;
+        }
+
+        // Allocation or I/O count or size, e.g. "1.234 K" or "1.234 KiB".
+        if column.is_alloc_stat() || column.is_io_count() {
+            return 9;
+        }
+
         tree.iter()
             .map(|tree| {
                 let Some(options) = tree.bench_options() else {
@@ -90,6 +158,16 @@ impl<'a> EntryTree<'a> {
             .unwrap_or_default()
     }
 
+    /// Returns `true` if any benchmark in `tree` may run with multiple
+    /// threads.
+    pub fn has_multi_thread(tree: &[Self]) -> bool {
+        tree.iter().any(|tree| {
+            let threads = tree.bench_options().and_then(|options| options.threads);
+            threads.is_some_and(|threads| threads.iter().any(|&n| n != 1))
+                || Self::has_multi_thread(tree.children())
+        })
+    }
+
     /// Inserts the benchmark group into a tree.
     ///
     /// Groups are inserted after tree construction because it prevents having
@@ -127,12 +205,21 @@ impl<'a> EntryTree<'a> {
         }
     }
 
-    /// Removes entries from the tree whose paths do not match the filter.
-    pub fn retain(tree: &mut Vec<Self>, mut filter: impl FnMut(&str) -> bool) {
-        fn retain(
-            tree: &mut Vec<EntryTree>,
+    /// Removes entries from the tree whose paths and tags do not match the
+    /// filter.
+    ///
+    /// Only benchmarks are passed to the filter, with their full paths like
+    /// `crate::group::bench`. Groups are kept if any of their benchmarks are.
+    /// Each benchmark's tags include those of its parent groups.
+    pub fn retain(
+        tree: &mut Vec<Self>,
+        mut filter: impl FnMut(&str, &[&str], AnyBenchEntry<'a>) -> bool,
+    ) {
+        fn retain<'a>(
+            tree: &mut Vec<EntryTree<'a>>,
             parent_path: &str,
-            filter: &mut impl FnMut(&str) -> bool,
+            parent_tags: &[&'static str],
+            filter: &mut impl FnMut(&str, &[&str], AnyBenchEntry<'a>) -> bool,
         ) {
             tree.retain_mut(|subtree| {
                 let full_path: String;
@@ -143,16 +230,18 @@ impl<'a> EntryTree<'a> {
                     &full_path
                 };
 
+                let tags = [parent_tags, subtree.tags()].concat();
+
                 match subtree {
                     EntryTree::Parent { children, .. } => {
-                        retain(children, full_path, filter);
+                        retain(children, full_path, &tags, filter);
                         !children.is_empty()
                     }
-                    EntryTree::Leaf { .. } => filter(full_path),
+                    EntryTree::Leaf(entry) => filter(full_path, &tags, *entry),
                 }
             });
         }
-        retain(tree, "", &mut filter);
+        retain(tree, "", &[], &mut filter);
     }
 
     /// Sorts the tree by the given ordering.
@@ -168,6 +257,46 @@ impl<'a> EntryTree<'a> {
         tree.iter_mut().for_each(|tree| Self::sort_by_attr(tree.children_mut(), attr, reverse));
     }
 
+    /// Sorts sibling leaves by their measured time, with unmeasured leaves
+    /// last. Parents keep their relative order.
+    ///
+    /// `get_time` is called with each leaf's full path.
+    pub fn sort_by_time(
+        tree: &mut [Self],
+        parent_path: &str,
+        get_time: &impl Fn(&str) -> Option<FineDuration>,
+        reverse: bool,
+    ) {
+        let path = |node: &Self| {
+            let name = node.display_name();
+            if parent_path.is_empty() {
+                name.to_owned()
+            } else {
+                format!("{parent_path}::{name}")
+            }
+        };
+
+        // Leaves are kept before or after parents, as in `SortingAttr::Kind`.
+        tree.sort_by_cached_key(|node| {
+            let time = match node {
+                Self::Leaf(_) => get_time(&path(node)),
+                Self::Parent { .. } => None,
+            };
+            let picos = time.map(|time| time.picos).unwrap_or_default();
+
+            if reverse {
+                (-node.kind(), time.is_none(), !picos)
+            } else {
+                (node.kind(), time.is_none(), picos)
+            }
+        });
+
+        for node in tree.iter_mut().filter(|node| matches!(node, Self::Parent { .. })) {
+            let path = path(node);
+            Self::sort_by_time(node.children_mut(), &path, get_time, reverse);
+        }
+    }
+
     fn cmp_by_attr(&self, other: &Self, attr: SortingAttr) -> Ordering {
         // We take advantage of the fact that entries have stable addresses,
         // unlike `EntryTree`.
@@ -198,6 +327,9 @@ impl<'a> EntryTree<'a> {
                         location_ordering
                     }
                 }
+
+                // Times are only known after measuring. See `sort_by_time`.
+                SortingAttr::Time => Ordering::Equal,
             };
 
             if ordering.is_ne() {
@@ -283,6 +415,12 @@ impl<'a> EntryTree<'a> {
         self.meta()?.bench_options()
     }
 
+    /// Returns the tags set on this entry or group, excluding those of its
+    /// parents.
+    pub fn tags(&self) -> &'static [&'static str] {
+        self.meta().map(|meta| meta.tags).unwrap_or_default()
+    }
+
     pub fn raw_name(&self) -> &'a str {
         match self {
             Self::Parent { group: Some(group), .. } => group.meta.raw_name,
@@ -318,22 +456,40 @@ impl<'a> EntryTree<'a> {
     /// When sorting by display name, the `PartialOrd` implementation is used by
     /// `EntryConst` to sort integers by value instead of lexicographically.
     fn cmp_display_name(&self, other: &Self) -> Ordering {
+        if let (Some(this), Some(other)) = (self.const_value(), other.const_value()) {
+            return this.cmp_name(other);
+        }
+
         match (self, other) {
+            // Arguments keep the order they were provided in.
             (
-                Self::Leaf(AnyBenchEntry::GenericBench(GenericBenchEntry {
-                    const_value: Some(this),
-                    ..
-                })),
-                Self::Leaf(AnyBenchEntry::GenericBench(GenericBenchEntry {
-                    const_value: Some(other),
-                    ..
-                })),
-            ) => this.cmp_name(other),
+                Self::Leaf(
+                    AnyBenchEntry::Arg { args: this_args, index: this, .. }
+                    | AnyBenchEntry::GenericArg { args: this_args, index: this, .. },
+                ),
+                Self::Leaf(
+                    AnyBenchEntry::Arg { args: other_args, index: other, .. }
+                    | AnyBenchEntry::GenericArg { args: other_args, index: other, .. },
+                ),
+            ) if std::ptr::eq(*this_args, *other_args) => this.cmp(other),
 
             _ => self.display_name().cmp(other.display_name()),
         }
     }
 
+    /// Returns the generic `const` of this entry, or of the runtime arguments
+    /// in this group.
+    fn const_value(&self) -> Option<&'a EntryConst> {
+        match self {
+            Self::Leaf(AnyBenchEntry::GenericBench(entry)) => entry.const_value.as_ref(),
+            Self::Parent { group: None, children, .. } => match children.first()? {
+                Self::Leaf(AnyBenchEntry::GenericArg { entry, .. }) => entry.const_value.as_ref(),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     fn children(&self) -> &[Self] {
         match self {
             Self::Leaf { .. } => &[],
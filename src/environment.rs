@@ -0,0 +1,204 @@
+//! Machine and toolchain context of a benchmark run.
+//!
+//! This is recorded alongside results so that they can be compared later with
+//! knowledge of what they were measured on.
+
+use std::{fs, process::Command, sync::OnceLock};
+
+use crate::json::Json;
+
+/// Machine and toolchain context of a benchmark run.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Environment {
+    /// CPU model name.
+    pub cpu: Option<String>,
+
+    /// Number of CPU cores available to the process.
+    pub cores: Option<usize>,
+
+    /// CPU frequency scaling governor, such as "performance" or "powersave".
+    pub governor: Option<String>,
+
+    /// Operating system name and version.
+    pub os: Option<String>,
+
+    /// Output of `rustc --version`.
+    pub rustc: Option<String>,
+
+    /// Whether divan was built with "debug" or "release" optimizations.
+    pub profile: Option<String>,
+}
+
+impl Environment {
+    /// Names of CSV columns for [`Self::csv_fields`].
+    pub const CSV_HEADER: [&'static str; 6] =
+        ["cpu", "cores", "governor", "os", "rustc", "profile"];
+
+    /// Returns the environment of the current process.
+    ///
+    /// This runs external commands, so it is only computed once.
+    pub fn current() -> &'static Self {
+        static CURRENT: OnceLock<Environment> = OnceLock::new();
+
+        CURRENT.get_or_init(|| Self {
+            cpu: cpu_model(),
+            cores: std::thread::available_parallelism().ok().map(usize::from),
+            governor: cpu_governor(),
+            os: os_version(),
+            rustc: rustc_version(),
+            profile: Some(if cfg!(debug_assertions) { "debug" } else { "release" }.to_owned()),
+        })
+    }
+
+    /// Returns values as CSV columns in the same order as [`Self::CSV_HEADER`].
+    pub fn csv_fields(&self) -> [String; 6] {
+        [
+            self.cpu.clone().unwrap_or_default(),
+            self.cores.map(|cores| cores.to_string()).unwrap_or_default(),
+            self.governor.clone().unwrap_or_default(),
+            self.os.clone().unwrap_or_default(),
+            self.rustc.clone().unwrap_or_default(),
+            self.profile.clone().unwrap_or_default(),
+        ]
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::object()
+            .with("cpu", self.cpu.as_deref())
+            .with("cores", self.cores)
+            .with("governor", self.governor.as_deref())
+            .with("os", self.os.as_deref())
+            .with("rustc", self.rustc.as_deref())
+            .with("profile", self.profile.as_deref())
+    }
+
+    pub fn from_json(json: &Json) -> Self {
+        let string = |key: &str| json.get(key).and_then(Json::as_str).map(ToOwned::to_owned);
+
+        Self {
+            cpu: string("cpu"),
+            cores: json.get("cores").and_then(Json::as_u64).and_then(|n| n.try_into().ok()),
+            governor: string("governor"),
+            os: string("os"),
+            rustc: string("rustc"),
+            profile: string("profile"),
+        }
+    }
+
+    /// Returns a short description for people, such as
+    /// "Intel Xeon, 8 cores, Ubuntu 22.04, rustc 1.75.0, release".
+    pub fn summary(&self) -> String {
+        let cores = self.cores.map(|cores| format!("{cores} cores"));
+
+        [self.cpu.clone(), cores, self.os.clone(), self.rustc.clone(), self.profile.clone()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Returns the trimmed standard output of a successful command.
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let output = String::from_utf8(output.stdout).ok()?;
+    let output = output.trim();
+    (!output.is_empty()).then(|| output.to_owned())
+}
+
+fn cpu_model() -> Option<String> {
+    if cfg!(target_os = "linux") {
+        let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+
+        // x86 uses "model name", while some ARM kernels use "Model" or
+        // "Hardware".
+        ["model name", "Model", "Hardware"].into_iter().find_map(|key| {
+            cpuinfo.lines().find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                (name.trim() == key).then(|| value.trim().to_owned())
+            })
+        })
+    } else if cfg!(target_os = "macos") {
+        command_output("sysctl", &["-n", "machdep.cpu.brand_string"])
+    } else if cfg!(windows) {
+        std::env::var("PROCESSOR_IDENTIFIER").ok()
+    } else {
+        None
+    }
+}
+
+fn cpu_governor() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+
+    let governor =
+        fs::read_to_string("/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor").ok()?;
+    Some(governor.trim().to_owned())
+}
+
+fn os_version() -> Option<String> {
+    if cfg!(target_os = "linux") {
+        let release = fs::read_to_string("/etc/os-release").ok().and_then(|os_release| {
+            os_release.lines().find_map(|line| {
+                let name = line.strip_prefix("PRETTY_NAME=")?;
+                Some(name.trim_matches('"').to_owned())
+            })
+        });
+
+        let kernel = fs::read_to_string("/proc/sys/kernel/osrelease").ok();
+        let kernel = kernel.as_deref().map(str::trim).map(|kernel| format!("Linux {kernel}"));
+
+        match (release, kernel) {
+            (Some(release), Some(kernel)) => Some(format!("{release} ({kernel})")),
+            (release, kernel) => release.or(kernel),
+        }
+    } else if cfg!(target_os = "macos") {
+        command_output("sw_vers", &["-productVersion"]).map(|version| format!("macOS {version}"))
+    } else {
+        Some(std::env::consts::OS.to_owned())
+    }
+}
+
+fn rustc_version() -> Option<String> {
+    // Cargo sets `RUSTC` for build scripts, and users may set it to select a
+    // different compiler.
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+    command_output(&rustc, &["--version"])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_round_trip() {
+        let env = Environment {
+            cpu: Some("Test CPU".to_owned()),
+            cores: Some(8),
+            governor: None,
+            os: Some("Test OS".to_owned()),
+            rustc: Some("rustc 1.0.0".to_owned()),
+            profile: Some("release".to_owned()),
+        };
+
+        let json = Json::parse(&env.to_json().to_string()).unwrap();
+        assert_eq!(Environment::from_json(&json), env);
+    }
+
+    #[test]
+    fn summary() {
+        let env = Environment {
+            cpu: Some("Test CPU".to_owned()),
+            cores: Some(8),
+            profile: Some("release".to_owned()),
+            ..Environment::default()
+        };
+
+        assert_eq!(env.summary(), "Test CPU, 8 cores, release");
+    }
+}
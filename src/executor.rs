@@ -0,0 +1,184 @@
+//! Executors for [benchmarking `async` code](crate::Bencher::bench_async).
+//!
+//! The benchmarked future is driven to completion within the timed section, so
+//! executors should keep per-call overhead low. Expensive setup, such as
+//! building a runtime, should be done once before benchmarking and then passed
+//! to [`Bencher::with_executor`](crate::Bencher::with_executor).
+
+use std::{
+    future::Future,
+    pin::pin,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+};
+
+/// Runs futures to completion on the current thread.
+///
+/// # Examples
+///
+/// With the `tokio` feature, a [Tokio](https://tokio.rs) runtime or its handle
+/// can be used directly:
+///
+/// ```ignore
+/// #[divan::bench]
+/// fn bench(bencher: divan::Bencher) {
+///     let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+///
+///     bencher
+///         .with_executor(&runtime)
+///         .bench_async(|| async {
+///             tokio::task::yield_now().await;
+///         });
+/// }
+/// ```
+///
+/// Other runtimes can be supported by forwarding to their `block_on`:
+///
+/// ```
+/// use std::future::Future;
+/// use divan::executor::{BlockOn, Executor};
+///
+/// struct MyRuntime(BlockOn);
+///
+/// impl Executor for MyRuntime {
+///     fn block_on<F: Future>(&self, future: F) -> F::Output {
+///         self.0.block_on(future)
+///     }
+/// }
+/// ```
+pub trait Executor {
+    /// Runs `future` to completion and returns its output.
+    fn block_on<F: Future>(&self, future: F) -> F::Output;
+}
+
+impl<E: Executor> Executor for &E {
+    #[inline]
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        E::block_on(self, future)
+    }
+}
+
+/// Runs futures with [`Runtime::block_on`](tokio::runtime::Runtime::block_on).
+#[cfg(feature = "tokio")]
+impl Executor for tokio::runtime::Runtime {
+    #[inline]
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        tokio::runtime::Runtime::block_on(self, future)
+    }
+}
+
+/// Runs futures with [`Handle::block_on`](tokio::runtime::Handle::block_on).
+///
+/// This panics if called within an asynchronous execution context.
+#[cfg(feature = "tokio")]
+impl Executor for tokio::runtime::Handle {
+    #[inline]
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        tokio::runtime::Handle::block_on(self, future)
+    }
+}
+
+/// The default minimal [`Executor`], which polls a future on the current
+/// thread and parks the thread while the future is pending.
+///
+/// This does not provide I/O or timers, so futures that depend on a runtime,
+/// such as [Tokio](https://tokio.rs) sockets, need that runtime's executor
+/// instead.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BlockOn;
+
+impl Executor for BlockOn {
+    #[inline]
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        struct ThreadWaker(Thread);
+
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+
+            fn wake_by_ref(self: &Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        thread_local! {
+            // Created once per thread to not allocate on each call.
+            static WAKER: Waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        }
+
+        let mut future = pin!(future);
+
+        WAKER.with(|waker| {
+            let mut context = Context::from_waker(waker);
+            loop {
+                match future.as_mut().poll(&mut context) {
+                    Poll::Ready(output) => return output,
+                    Poll::Pending => thread::park(),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::poll_fn,
+        sync::atomic::{AtomicBool, Ordering},
+    };
+
+    use super::*;
+
+    #[test]
+    fn ready() {
+        assert_eq!(BlockOn.block_on(async { 1 + 1 }), 2);
+    }
+
+    /// Tests that a future woken from another thread is polled again.
+    #[test]
+    fn wake_from_thread() {
+        let done = Arc::new(AtomicBool::new(false));
+        let mut spawned = false;
+
+        let output = BlockOn.block_on(poll_fn(|cx| {
+            if done.load(Ordering::Acquire) {
+                return Poll::Ready("done");
+            }
+
+            if !spawned {
+                spawned = true;
+                let done = done.clone();
+                let waker = cx.waker().clone();
+                thread::spawn(move || {
+                    done.store(true, Ordering::Release);
+                    waker.wake();
+                });
+            }
+
+            Poll::Pending
+        }));
+
+        assert_eq!(output, "done");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn tokio() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+        // Spawned tasks are driven by the runtime.
+        let spawned = async {
+            tokio::task::yield_now().await;
+            tokio::spawn(async { "spawned" }).await.unwrap()
+        };
+        assert_eq!(Executor::block_on(&runtime, spawned), "spawned");
+
+        // A current-thread runtime's handle can only drive the given future.
+        let handle = runtime.handle().clone();
+        let handle_output =
+            Executor::block_on(&handle, async { tokio::runtime::Handle::current().id() });
+        assert_eq!(handle_output, handle.id());
+    }
+}
@@ -0,0 +1,88 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// A file loaded from a fixture directory, for benchmarking over a corpus.
+///
+/// This is usually provided through the
+/// [`args_from_dir`](macro@crate::bench#args_from_dir) option, which creates a
+/// benchmark for each file in a directory. Each benchmark is named by its
+/// file's name.
+///
+/// The benchmark function can take the file as `&FixtureFile`, its path as
+/// [`&Path`](Path), or its contents as `&[u8]`.
+#[derive(Clone)]
+pub struct FixtureFile {
+    path: PathBuf,
+    contents: Vec<u8>,
+}
+
+impl fmt::Debug for FixtureFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FixtureFile").field("path", &self.path).finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for FixtureFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name())
+    }
+}
+
+impl FixtureFile {
+    /// Reads every file directly within `dir`, sorted by name.
+    ///
+    /// Subdirectories are skipped. If `dir` or one of its files cannot be read,
+    /// a warning is printed and the failed entry is skipped.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Vec<Self> {
+        let dir = dir.as_ref();
+
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(error) => {
+                eprintln!("warning: Failed to read fixture directory {dir:?}: {error}");
+                return Vec::new();
+            }
+        };
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect();
+
+        paths.sort_unstable();
+
+        paths
+            .into_iter()
+            .filter_map(|path| match std::fs::read(&path) {
+                Ok(contents) => Some(Self { path, contents }),
+                Err(error) => {
+                    eprintln!("warning: Failed to read fixture file {path:?}: {error}");
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the file's name, which is used to name its benchmark.
+    pub fn name(&self) -> std::borrow::Cow<'_, str> {
+        match self.path.file_name() {
+            Some(name) => name.to_string_lossy(),
+            None => self.path.to_string_lossy(),
+        }
+    }
+
+    /// Returns the path to the file.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the file's contents, which are read before benchmarking.
+    #[inline]
+    pub fn contents(&self) -> &[u8] {
+        &self.contents
+    }
+}
@@ -0,0 +1,141 @@
+//! GitHub Actions output.
+//!
+//! Failures, errors, throttling, noise, and bimodality are reported as
+//! [workflow command](https://docs.github.com/en/actions/using-workflows/workflow-commands-for-github-actions)
+//! annotations, which GitHub shows on the workflow run and pull request.
+//! Results are appended as a Markdown table to the job summary file at
+//! `$GITHUB_STEP_SUMMARY`.
+
+use std::{env, fmt::Write as _, fs, io, io::Write as _};
+
+use crate::{
+    baseline::{self, Baseline},
+    markdown,
+    results::BenchResults,
+};
+
+/// Returns workflow commands annotating problems in `results`.
+pub(crate) fn annotations(results: &BenchResults) -> Vec<String> {
+    let failures = results
+        .failures
+        .iter()
+        .map(|failure| annotation("error", "Benchmark exceeded threshold", failure));
+
+    let errors = results.errors.iter().map(|error| annotation("error", "Benchmark errored", error));
+
+    let throttled = results
+        .throttled
+        .iter()
+        .map(|bench| annotation("warning", "Benchmark was throttled", bench));
+
+    let noisy =
+        results.noisy.iter().map(|bench| annotation("warning", "Benchmark was noisy", bench));
+
+    let bimodal =
+        results.bimodal.iter().map(|bench| annotation("warning", "Benchmark was bimodal", bench));
+
+    failures.chain(errors).chain(throttled).chain(noisy).chain(bimodal).collect()
+}
+
+/// Formats a workflow command, e.g. `::error title=...::message`.
+fn annotation(command: &str, title: &str, message: impl ToString) -> String {
+    format!("::{command} title={}::{}", escape_property(title), escape_data(&message.to_string()))
+}
+
+/// Escapes a workflow command message.
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Escapes a workflow command property value.
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Appends a Markdown table of `results` to the job summary file.
+///
+/// Fails with [`io::ErrorKind::NotFound`] if `$GITHUB_STEP_SUMMARY` is not
+/// set, such as when not running in GitHub Actions.
+pub(crate) fn write_summary(results: &BenchResults, baseline: Option<&Baseline>) -> io::Result<()> {
+    let Some(path) = env::var_os("GITHUB_STEP_SUMMARY") else {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "GITHUB_STEP_SUMMARY is not set"));
+    };
+
+    let mut file = fs::OpenOptions::new().append(true).create(true).open(path)?;
+    file.write_all(render_summary(results, baseline).as_bytes())
+}
+
+/// Renders results as a Markdown job summary.
+pub(crate) fn render_summary(results: &BenchResults, baseline: Option<&Baseline>) -> String {
+    let mut md = String::from("## Benchmark results\n\n");
+
+    let mut header = vec!["Benchmark", "Fastest", "Slowest", "Median", "Mean", "Samples", "Iters"];
+    if baseline.is_some() {
+        header.push("Delta");
+    }
+    markdown::write_row(&mut md, header.iter().copied());
+
+    let alignments = (0..header.len()).map(|i| if i == 0 { ":--" } else { "--:" });
+    markdown::write_row(&mut md, alignments);
+
+    for entry in results.entries() {
+        let stats = &entry.stats;
+        let time = &stats.time;
+
+        let mut row = vec![
+            format!("`{}`", entry.path),
+            time.fastest.to_string(),
+            time.slowest.to_string(),
+            time.median.to_string(),
+            time.mean.to_string(),
+            stats.sample_count.to_string(),
+            stats.iter_count.to_string(),
+        ];
+
+        if let Some(baseline) = baseline {
+            let change = baseline.get(&entry.path).and_then(|record| {
+                baseline::percent_change(record.time.median.picos, time.median.picos)
+            });
+            row.push(change.map(|change| format!("{change:+.2}%")).unwrap_or_default());
+        }
+
+        markdown::write_row(&mut md, row.iter().map(String::as_str));
+    }
+
+    let problems = results
+        .failures
+        .iter()
+        .map(ToString::to_string)
+        .chain(results.errors.iter().map(ToString::to_string));
+
+    let mut has_problems = false;
+    for problem in problems {
+        if !has_problems {
+            md.push_str("\n### Failures\n\n");
+            has_problems = true;
+        }
+        _ = writeln!(md, "- {problem}");
+    }
+
+    md.push('\n');
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape() {
+        assert_eq!(escape_data("50% of\r\nruns"), "50%25 of%0D%0Aruns");
+        assert_eq!(escape_property("a::b, c"), "a%3A%3Ab%2C c");
+    }
+
+    #[test]
+    fn annotation() {
+        assert_eq!(
+            super::annotation("error", "Benchmark errored", "a::b: panicked: oops\nline"),
+            "::error title=Benchmark errored::a::b: panicked: oops%0Aline",
+        );
+    }
+}
@@ -0,0 +1,416 @@
+//! Append-only results history.
+//!
+//! Each benchmark run appends one JSON object per benchmark to a [JSON
+//! Lines](https://jsonlines.org) file. This is simple to append to from CI and
+//! trivially mergeable with `cat`.
+
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    path::Path,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    baseline::percent_change, environment::Environment, json::Json, results::BenchResults,
+    stats::StatsSet, time::FineDuration,
+};
+
+/// A single benchmark's results from a single run.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct HistoryRecord {
+    /// Full `::`-separated path of the benchmark, including generic arguments
+    /// and thread counts.
+    pub path: String,
+
+    /// Git commit of the benchmarked code, if known.
+    pub commit: Option<String>,
+
+    /// Seconds since the Unix epoch when the run finished.
+    pub timestamp: u64,
+
+    /// Name of the machine that ran the benchmark, if known.
+    pub machine: Option<String>,
+
+    /// CPU architecture of the machine that ran the benchmark, if known.
+    pub arch: Option<String>,
+
+    /// Machine and toolchain context, if recorded.
+    pub env: Option<Environment>,
+
+    /// Seed for randomly generated inputs, if recorded.
+    pub seed: Option<u64>,
+
+    pub sample_count: u32,
+    pub iter_count: u64,
+    pub time: StatsSet<FineDuration>,
+}
+
+/// Information shared by all records of a single run.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RunInfo {
+    pub commit: Option<String>,
+    pub timestamp: u64,
+    pub machine: Option<String>,
+    pub arch: Option<String>,
+    pub env: Option<Environment>,
+    pub seed: Option<u64>,
+}
+
+impl RunInfo {
+    /// Returns information about the current run, which generates inputs
+    /// with `seed` if known.
+    pub fn current(seed: Option<u64>) -> Self {
+        Self {
+            commit: current_commit(),
+            timestamp: unix_timestamp(),
+            machine: current_machine(),
+            arch: Some(std::env::consts::ARCH.to_owned()),
+            env: Some(Environment::current().clone()),
+            seed,
+        }
+    }
+}
+
+impl HistoryRecord {
+    /// Creates records for every benchmark in `results`.
+    pub fn from_results(results: &BenchResults, run: &RunInfo) -> Vec<Self> {
+        results
+            .entries()
+            .map(|entry| Self {
+                path: entry.path.clone(),
+                commit: run.commit.clone(),
+                timestamp: run.timestamp,
+                machine: run.machine.clone(),
+                arch: run.arch.clone(),
+                env: run.env.clone(),
+                seed: run.seed,
+                sample_count: entry.stats.sample_count,
+                iter_count: entry.stats.iter_count,
+                time: entry.stats.time,
+            })
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Json {
+        let time = &self.time;
+        Json::object()
+            .with("path", self.path.as_str())
+            .with("commit", self.commit.as_deref())
+            .with("timestamp", self.timestamp)
+            .with("machine", self.machine.as_deref())
+            .with("arch", self.arch.as_deref())
+            .with("env", self.env.as_ref().map(Environment::to_json))
+            .with("seed", self.seed)
+            .with("samples", self.sample_count)
+            .with("iters", self.iter_count)
+            .with(
+                "time",
+                Json::object()
+                    .with("fastest", time.fastest.picos)
+                    .with("slowest", time.slowest.picos)
+                    .with("median", time.median.picos)
+                    .with("mean", time.mean.picos),
+            )
+    }
+
+    pub fn from_json(json: &Json) -> Option<Self> {
+        let time = json.get("time")?;
+        let picos = |key: &str| Some(FineDuration { picos: time.get(key)?.as_u128()? });
+
+        Some(Self {
+            path: json.get("path")?.as_str()?.to_owned(),
+            commit: json.get("commit").and_then(Json::as_str).map(ToOwned::to_owned),
+            timestamp: json.get("timestamp")?.as_u64()?,
+            machine: json.get("machine").and_then(Json::as_str).map(ToOwned::to_owned),
+            arch: json.get("arch").and_then(Json::as_str).map(ToOwned::to_owned),
+            env: json.get("env").filter(|env| **env != Json::Null).map(Environment::from_json),
+            seed: json.get("seed").and_then(Json::as_u64),
+            sample_count: json.get("samples")?.as_u64()?.try_into().ok()?,
+            iter_count: json.get("iters")?.as_u64()?,
+            time: StatsSet {
+                fastest: picos("fastest")?,
+                slowest: picos("slowest")?,
+                median: picos("median")?,
+                mean: picos("mean")?,
+            },
+        })
+    }
+}
+
+/// Appends records to the history file at `path`, creating it if needed.
+pub(crate) fn append(path: &Path, records: &[HistoryRecord]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
+    // Write all lines at once to reduce the chance of interleaving with
+    // concurrent writers.
+    let mut buf = String::new();
+    for record in records {
+        buf.push_str(&record.to_json().to_string());
+        buf.push('\n');
+    }
+
+    fs::OpenOptions::new().create(true).append(true).open(path)?.write_all(buf.as_bytes())
+}
+
+/// Reads all records from the history file at `path`.
+pub(crate) fn read(path: &Path) -> io::Result<Vec<HistoryRecord>> {
+    parse(&fs::read_to_string(path)?)
+}
+
+/// Parses history from JSON Lines.
+pub(crate) fn parse(history: &str) -> io::Result<Vec<HistoryRecord>> {
+    history
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let invalid = |message: String| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {message}", i + 1))
+            };
+
+            let json = Json::parse(line).map_err(|error| invalid(error.to_string()))?;
+            HistoryRecord::from_json(&json).ok_or_else(|| invalid("invalid record".to_owned()))
+        })
+        .collect()
+}
+
+/// A benchmark whose mean time changed in the same direction over each of its
+/// last recorded runs.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Trend {
+    pub path: String,
+
+    /// Mean times from oldest to newest run.
+    pub means: Vec<FineDuration>,
+}
+
+impl fmt::Display for Trend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (Some(&first), Some(&last)) = (self.means.first(), self.means.last()) else {
+            return Ok(());
+        };
+
+        let direction = if last > first { "increased" } else { "decreased" };
+        write!(
+            f,
+            "{}: mean {direction} in each of the last {} runs, from {first} to {last}",
+            self.path,
+            self.means.len(),
+        )?;
+
+        if let Some(change) = percent_change(first.picos, last.picos) {
+            write!(f, " ({change:+.2}%)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns benchmarks in `current` whose mean time strictly increased or
+/// strictly decreased over their last `runs` records in `history`.
+///
+/// Only records from the same machine are considered, since results across
+/// machines are not comparable. History is assumed to be in the order it was
+/// recorded, which is the case for files written by [`append`].
+pub(crate) fn trends(
+    history: &[HistoryRecord],
+    current: &[HistoryRecord],
+    runs: usize,
+) -> Vec<Trend> {
+    if runs < 2 {
+        return Vec::new();
+    }
+
+    current
+        .iter()
+        .filter_map(|current| {
+            let mut means: Vec<FineDuration> = history
+                .iter()
+                .rev()
+                .filter(|record| record.path == current.path && record.machine == current.machine)
+                .take(runs)
+                .map(|record| record.time.mean)
+                .collect();
+
+            if means.len() < runs {
+                return None;
+            }
+            means.reverse();
+
+            let is_increasing = means.windows(2).all(|pair| pair[0] < pair[1]);
+            let is_decreasing = means.windows(2).all(|pair| pair[0] > pair[1]);
+
+            (is_increasing || is_decreasing).then(|| Trend { path: current.path.clone(), means })
+        })
+        .collect()
+}
+
+/// Returns the Git commit of the benchmarked code.
+///
+/// This prefers `DIVAN_COMMIT` and `GITHUB_SHA` over asking `git`, since CI
+/// checkouts may not have a usable repository.
+pub(crate) fn current_commit() -> Option<String> {
+    for var in ["DIVAN_COMMIT", "GITHUB_SHA"] {
+        if let Ok(commit) = std::env::var(var) {
+            if !commit.is_empty() {
+                return Some(commit);
+            }
+        }
+    }
+
+    let output = Command::new("git").args(["rev-parse", "HEAD"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let commit = String::from_utf8(output.stdout).ok()?;
+    let commit = commit.trim();
+    (!commit.is_empty()).then(|| commit.to_owned())
+}
+
+/// Returns the name of the machine running benchmarks.
+///
+/// This prefers `DIVAN_MACHINE` so that CI runners with random host names can
+/// be given a stable name.
+pub(crate) fn current_machine() -> Option<String> {
+    for var in ["DIVAN_MACHINE", "HOSTNAME", "COMPUTERNAME"] {
+        if let Ok(machine) = std::env::var(var) {
+            if !machine.is_empty() {
+                return Some(machine);
+            }
+        }
+    }
+
+    let output = Command::new("hostname").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let machine = String::from_utf8(output.stdout).ok()?;
+    let machine = machine.trim();
+    (!machine.is_empty()).then(|| machine.to_owned())
+}
+
+/// Returns the number of seconds since the Unix epoch.
+pub(crate) fn unix_timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(path: &str, commit: Option<&str>, timestamp: u64, mean: u128) -> HistoryRecord {
+        let picos = |picos| FineDuration { picos };
+        HistoryRecord {
+            path: path.to_owned(),
+            commit: commit.map(ToOwned::to_owned),
+            timestamp,
+            machine: commit.map(|_| "ci-x86".to_owned()),
+            arch: None,
+            env: None,
+            seed: None,
+            sample_count: 100,
+            iter_count: 10_000,
+            time: StatsSet {
+                fastest: picos(mean / 2),
+                slowest: picos(mean * 2),
+                median: picos(mean),
+                mean: picos(mean),
+            },
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let records = [
+            record("a::b", Some("abc123"), 1, 1_000),
+            record("a::c<u8>", None, 2, u128::MAX / 2),
+            record("a::\"quoted\"", Some(""), 3, 0),
+            HistoryRecord {
+                env: Some(Environment { cores: Some(4), ..Environment::default() }),
+                seed: Some(42),
+                ..record("a::env", Some("abc123"), 4, 1)
+            },
+        ];
+
+        let mut history = String::new();
+        for record in &records {
+            history.push_str(&record.to_json().to_string());
+            history.push('\n');
+        }
+
+        // Blank lines are allowed, such as from manual concatenation.
+        history.push('\n');
+
+        assert_eq!(parse(&history).unwrap(), records);
+    }
+
+    #[test]
+    fn trends() {
+        let history = [
+            record("a", Some("c1"), 1, 100),
+            record("b", Some("c1"), 1, 100),
+            record("c", Some("c1"), 1, 100),
+            record("a", Some("c2"), 2, 110),
+            record("b", Some("c2"), 2, 90),
+            record("c", Some("c2"), 2, 120),
+            // Different machine:
+            HistoryRecord { machine: None, ..record("a", Some("c3"), 3, 50) },
+            record("a", Some("c3"), 3, 120),
+            record("b", Some("c3"), 3, 80),
+            record("c", Some("c3"), 3, 110),
+        ];
+        let current = &history[7..];
+
+        let means = |means: &[u128]| means.iter().map(|&picos| FineDuration { picos }).collect();
+
+        assert_eq!(
+            super::trends(&history, current, 3),
+            [
+                Trend { path: "a".to_owned(), means: means(&[100, 110, 120]) },
+                Trend { path: "b".to_owned(), means: means(&[100, 90, 80]) },
+            ]
+        );
+
+        // Not enough runs:
+        assert_eq!(super::trends(&history, current, 4), []);
+
+        assert_eq!(
+            super::trends(&history, current, 3)[0].to_string(),
+            "a: mean increased in each of the last 3 runs, from 0.1 ns to 0.12 ns (+20.00%)"
+        );
+    }
+
+    #[test]
+    fn invalid_line() {
+        let valid = record("a", None, 1, 1).to_json().to_string();
+        let history = format!("{valid}\n{{\"path\":\"b\"}}\n");
+
+        let error = parse(&history).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().starts_with("line 2:"), "{error}");
+    }
+
+    #[test]
+    fn append() {
+        let path = std::env::temp_dir().join(format!("divan-history-{}.jsonl", std::process::id()));
+        _ = fs::remove_file(&path);
+
+        let first = [record("a", Some("abc123"), 1, 1_000)];
+        let second = [record("a", Some("def456"), 2, 2_000), record("b", None, 2, 10)];
+
+        super::append(&path, &first).unwrap();
+        super::append(&path, &second).unwrap();
+
+        let records = read(&path);
+        _ = fs::remove_file(&path);
+
+        assert_eq!(records.unwrap(), [first.as_slice(), &second].concat());
+    }
+}
@@ -0,0 +1,98 @@
+//! Minimal HTTP client for sending results to other services.
+//!
+//! Only plain `http://` URLs are supported, since TLS would require pulling in
+//! large dependencies. This is fine for services that are typically reached
+//! within a private network, such as a Prometheus Pushgateway.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+/// How long to wait for connecting, sending, and receiving.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Sends a request with `body` and fails if the response status is not 2xx.
+pub(crate) fn send(method: &str, url: &str, content_type: &str, body: &[u8]) -> io::Result<()> {
+    let (host, path) = parse_url(url)?;
+
+    let has_port = host.contains(':') && !host.ends_with(']');
+    let addr = if has_port { host.to_owned() } else { format!("{host}:80") };
+    let addr = addr.to_socket_addrs()?.next().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, format!("could not resolve '{host}'"))
+    })?;
+
+    let mut stream = TcpStream::connect_timeout(&addr, TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let head = format!(
+        "{method} {path} HTTP/1.1\r\nHost: {host}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body)?;
+    stream.flush()?;
+
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line)?;
+    let status_line = status_line.trim_end();
+
+    let status = status_line.split(' ').nth(1).and_then(|status| status.parse::<u16>().ok());
+    match status {
+        Some(200..=299) => Ok(()),
+        Some(_) => Err(io::Error::other(format!("server responded with '{status_line}'"))),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid response status '{status_line}'"),
+        )),
+    }
+}
+
+/// Splits an `http://` URL into its host (with optional port) and path.
+pub(crate) fn parse_url(url: &str) -> io::Result<(&str, &str)> {
+    let Some(rest) = url.strip_prefix("http://") else {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("only http:// URLs are supported, got '{url}'"),
+        ));
+    };
+
+    let (host, path) = match rest.find('/') {
+        Some(i) => rest.split_at(i),
+        None => (rest, "/"),
+    };
+
+    if host.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("missing host in '{url}'"),
+        ));
+    }
+
+    Ok((host, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_url() {
+        assert_eq!(
+            super::parse_url("http://localhost:9091/metrics/job/divan").unwrap(),
+            ("localhost:9091", "/metrics/job/divan")
+        );
+        assert_eq!(super::parse_url("http://example.com").unwrap(), ("example.com", "/"));
+
+        assert_eq!(
+            super::parse_url("https://example.com").unwrap_err().kind(),
+            io::ErrorKind::Unsupported
+        );
+        assert_eq!(
+            super::parse_url("http:///path").unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+}
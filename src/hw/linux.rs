@@ -0,0 +1,197 @@
+//! Counters via [`perf_event_open`](https://man7.org/linux/man-pages/man2/perf_event_open.2.html).
+
+use std::{
+    fs::File,
+    io::{self, Read},
+    os::{
+        fd::{AsRawFd, FromRawFd},
+        raw::{c_int, c_long, c_ulong},
+    },
+};
+
+use super::EventSource;
+
+#[cfg(target_arch = "x86_64")]
+const SYS_PERF_EVENT_OPEN: c_long = 298;
+
+#[cfg(target_arch = "x86")]
+const SYS_PERF_EVENT_OPEN: c_long = 336;
+
+#[cfg(target_arch = "arm")]
+const SYS_PERF_EVENT_OPEN: c_long = 364;
+
+#[cfg(any(target_arch = "aarch64", target_arch = "riscv64"))]
+const SYS_PERF_EVENT_OPEN: c_long = 241;
+
+const PERF_TYPE_HARDWARE: u32 = 0;
+const PERF_TYPE_RAW: u32 = 4;
+
+const PERF_FORMAT_TOTAL_TIME_ENABLED: u64 = 1 << 0;
+const PERF_FORMAT_TOTAL_TIME_RUNNING: u64 = 1 << 1;
+const PERF_FORMAT_GROUP: u64 = 1 << 3;
+
+/// The number of `u64` values before event values in a group read: the event
+/// count, the time enabled, and the time running.
+const READ_HEADER_LEN: usize = 3;
+
+// `perf_event_attr` flag bits.
+const FLAG_DISABLED: u64 = 1 << 0;
+const FLAG_EXCLUDE_KERNEL: u64 = 1 << 5;
+const FLAG_EXCLUDE_HV: u64 = 1 << 6;
+
+const PERF_EVENT_IOC_ENABLE: c_ulong = 0x2400;
+const PERF_EVENT_IOC_DISABLE: c_ulong = 0x2401;
+const PERF_EVENT_IOC_RESET: c_ulong = 0x2403;
+
+const PERF_IOC_FLAG_GROUP: c_ulong = 1;
+
+extern "C" {
+    fn syscall(number: c_long, ...) -> c_long;
+
+    #[cfg(not(target_env = "musl"))]
+    fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+
+    #[cfg(target_env = "musl")]
+    fn ioctl(fd: c_int, request: c_int, ...) -> c_int;
+}
+
+/// `perf_event_attr` up to `config2` (`PERF_ATTR_SIZE_VER1`).
+#[repr(C)]
+#[derive(Default)]
+struct PerfEventAttr {
+    kind: u32,
+    size: u32,
+    config: u64,
+    sample_period: u64,
+    sample_type: u64,
+    read_format: u64,
+    flags: u64,
+    wakeup_events: u32,
+    bp_type: u32,
+    config1: u64,
+    config2: u64,
+}
+
+/// Returns `true` if `source` can be counted, which is left to
+/// `perf_event_open` to decide.
+pub(crate) fn is_supported(_source: EventSource) -> bool {
+    true
+}
+
+/// Counters that are enabled, disabled, and read together.
+pub(crate) struct CounterGroup {
+    /// The first file is the group leader.
+    files: Vec<File>,
+
+    /// Buffer for `PERF_FORMAT_GROUP` reads: the event count, the times the
+    /// group was enabled and running, and then each event's value.
+    read_buf: Vec<u64>,
+}
+
+impl CounterGroup {
+    pub fn open(sources: &[EventSource]) -> io::Result<Self> {
+        let mut files = Vec::<File>::with_capacity(sources.len());
+
+        for &source in sources {
+            let (kind, config) = match source {
+                EventSource::Generic(event) => (PERF_TYPE_HARDWARE, event as u64),
+                EventSource::Raw(code) => (PERF_TYPE_RAW, code),
+            };
+
+            let attr = PerfEventAttr {
+                kind,
+                size: std::mem::size_of::<PerfEventAttr>() as u32,
+                config,
+                read_format: PERF_FORMAT_GROUP
+                    | PERF_FORMAT_TOTAL_TIME_ENABLED
+                    | PERF_FORMAT_TOTAL_TIME_RUNNING,
+                // Only the leader starts disabled, since it controls the group.
+                flags: FLAG_EXCLUDE_KERNEL
+                    | FLAG_EXCLUDE_HV
+                    | if files.is_empty() { FLAG_DISABLED } else { 0 },
+                ..Default::default()
+            };
+
+            let group_fd: c_int = files.first().map(|leader| leader.as_raw_fd()).unwrap_or(-1);
+
+            // Count the calling thread on any CPU.
+            let (pid, cpu, flags): (c_int, c_int, c_ulong) = (0, -1, 0);
+
+            // SAFETY: `attr` is a valid `perf_event_attr` with its own size.
+            let fd = unsafe {
+                syscall(
+                    SYS_PERF_EVENT_OPEN,
+                    &attr as *const PerfEventAttr,
+                    pid,
+                    cpu,
+                    group_fd,
+                    flags,
+                )
+            };
+
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            // SAFETY: The file descriptor was just opened and is owned.
+            files.push(unsafe { File::from_raw_fd(fd as c_int) });
+        }
+
+        Ok(Self { read_buf: vec![0; sources.len() + READ_HEADER_LEN], files })
+    }
+
+    /// Resets and enables all counters.
+    #[inline]
+    pub fn start(&mut self) {
+        self.ioctl(PERF_EVENT_IOC_RESET);
+        self.ioctl(PERF_EVENT_IOC_ENABLE);
+    }
+
+    /// Disables all counters.
+    #[inline]
+    pub fn stop(&mut self) {
+        self.ioctl(PERF_EVENT_IOC_DISABLE);
+    }
+
+    /// Reads the value of each counter into `counts`, in the order they were
+    /// opened.
+    ///
+    /// Fails if the kernel multiplexed the group with other events, since the
+    /// counts would then only cover part of the sample.
+    pub fn read(&mut self, counts: &mut [u64]) -> io::Result<()> {
+        let Some(leader) = self.files.first_mut() else {
+            return Ok(());
+        };
+
+        // SAFETY: `u64` has no invalid bit patterns.
+        let bytes = unsafe {
+            std::slice::from_raw_parts_mut(
+                self.read_buf.as_mut_ptr().cast::<u8>(),
+                self.read_buf.len() * std::mem::size_of::<u64>(),
+            )
+        };
+        leader.read_exact(bytes)?;
+
+        let (time_enabled, time_running) = (self.read_buf[1], self.read_buf[2]);
+        if time_running < time_enabled {
+            return Err(io::Error::other("counters were not running for the whole sample"));
+        }
+
+        for (count, &value) in counts.iter_mut().zip(&self.read_buf[READ_HEADER_LEN..]) {
+            *count = value;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn ioctl(&self, request: c_ulong) {
+        let Some(leader) = self.files.first() else {
+            return;
+        };
+
+        // SAFETY: The leader is a valid perf event file descriptor.
+        unsafe {
+            ioctl(leader.as_raw_fd(), request as _, PERF_IOC_FLAG_GROUP);
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! Counters via the private `kperf` framework on Apple Silicon.
+//!
+//! This is the same interface used by Instruments. It is loaded at runtime
+//! since it is not part of the public SDK, and programming counters requires
+//! running as root.
+//!
+//! Apple cores have two fixed counters for cycles and instructions, and a
+//! handful of configurable counters. Branches, branch misses, and cache misses
+//! are mapped to configurable events, where cache misses are L1 data cache load
+//! misses. Configurable counters are programmed for the whole system, so all
+//! groups of the process share them.
+
+use std::{
+    ffi::{c_char, c_int, c_void},
+    io,
+    ops::Range,
+    sync::{Mutex, OnceLock, PoisonError},
+};
+
+use super::{EventSource, GenericEvent};
+
+const KPERF_PATH: &[u8] = b"/System/Library/PrivateFrameworks/kperf.framework/kperf\0";
+
+const RTLD_LAZY: c_int = 0x1;
+
+const KPC_CLASS_FIXED_MASK: u32 = 1 << 0;
+const KPC_CLASS_CONFIGURABLE_MASK: u32 = 1 << 1;
+const KPC_CLASSES: u32 = KPC_CLASS_FIXED_MASK | KPC_CLASS_CONFIGURABLE_MASK;
+
+// Fixed counter indices.
+const FIXED_CYCLES: usize = 0;
+const FIXED_INSTRUCTIONS: usize = 1;
+
+// Configurable event numbers of Apple cores.
+const INST_BRANCH: u64 = 0x8d;
+const BRANCH_MISPRED_NONSPEC: u64 = 0xcb;
+const L1D_CACHE_MISS_LD_NONSPEC: u64 = 0xbf;
+
+/// Configuration bit to count events in 64-bit user space (EL0).
+const CFGWORD_EL0A64EN: u64 = 0x20000;
+
+/// Configurable counters that can count the events above, which are PMC5-7.
+const RESTRICTED_SLOTS: Range<usize> = 3..6;
+
+extern "C" {
+    fn dlopen(path: *const c_char, mode: c_int) -> *mut c_void;
+    fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+}
+
+/// Functions of `kperf`.
+struct Kpc {
+    force_all_ctrs_set: unsafe extern "C" fn(c_int) -> c_int,
+    set_counting: unsafe extern "C" fn(u32) -> c_int,
+    set_thread_counting: unsafe extern "C" fn(u32) -> c_int,
+    set_config: unsafe extern "C" fn(u32, *mut u64) -> c_int,
+    get_counter_count: unsafe extern "C" fn(u32) -> u32,
+    get_config_count: unsafe extern "C" fn(u32) -> u32,
+    get_thread_counters: unsafe extern "C" fn(u32, u32, *mut u64) -> c_int,
+}
+
+impl Kpc {
+    /// Loads `kperf` once for the whole process.
+    fn get() -> io::Result<&'static Self> {
+        static KPC: OnceLock<Option<Kpc>> = OnceLock::new();
+
+        // SAFETY: The symbols have the signatures of `Kpc` fields.
+        KPC.get_or_init(|| unsafe { Self::load() }).as_ref().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "'kperf.framework' could not be loaded")
+        })
+    }
+
+    unsafe fn load() -> Option<Self> {
+        let handle = dlopen(KPERF_PATH.as_ptr().cast(), RTLD_LAZY);
+        if handle.is_null() {
+            return None;
+        }
+
+        macro_rules! symbol {
+            ($name:literal) => {{
+                let ptr = dlsym(handle, concat!($name, "\0").as_ptr().cast());
+                if ptr.is_null() {
+                    return None;
+                }
+
+                // Function pointers have the same size as data pointers.
+                std::mem::transmute_copy(&ptr)
+            }};
+        }
+
+        Some(Self {
+            force_all_ctrs_set: symbol!("kpc_force_all_ctrs_set"),
+            set_counting: symbol!("kpc_set_counting"),
+            set_thread_counting: symbol!("kpc_set_thread_counting"),
+            set_config: symbol!("kpc_set_config"),
+            get_counter_count: symbol!("kpc_get_counter_count"),
+            get_config_count: symbol!("kpc_get_config_count"),
+            get_thread_counters: symbol!("kpc_get_thread_counters"),
+        })
+    }
+}
+
+/// The event of each configurable counter, or 0 if unused.
+///
+/// Counters are never released, since groups are reopened at most when
+/// `--perf-events` change.
+static CONFIGS: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+
+/// Returns `true` if `source` can be counted by Apple cores.
+pub(crate) fn is_supported(source: EventSource) -> bool {
+    match source {
+        EventSource::Generic(GenericEvent::Cycles | GenericEvent::Instructions) => true,
+        EventSource::Generic(event) => configurable_event(event).is_some(),
+        EventSource::Raw(_) => true,
+    }
+}
+
+/// Returns the configurable event number for a generic event.
+fn configurable_event(event: GenericEvent) -> Option<u64> {
+    match event {
+        GenericEvent::Branches => Some(INST_BRANCH),
+        GenericEvent::BranchMisses => Some(BRANCH_MISPRED_NONSPEC),
+        GenericEvent::CacheMisses => Some(L1D_CACHE_MISS_LD_NONSPEC),
+        _ => None,
+    }
+}
+
+/// Counters that are enabled, disabled, and read together.
+pub(crate) struct CounterGroup {
+    kpc: &'static Kpc,
+
+    /// The index of each event in the thread's counter values.
+    indices: Vec<usize>,
+
+    /// Thread counter values at `start` and `stop`.
+    start_buf: Vec<u64>,
+    stop_buf: Vec<u64>,
+
+    /// Whether reading both buffers succeeded.
+    is_valid: bool,
+}
+
+impl CounterGroup {
+    pub fn open(sources: &[EventSource]) -> io::Result<Self> {
+        let kpc = Kpc::get()?;
+
+        // SAFETY: `kperf` functions are called with valid buffer lengths.
+        unsafe {
+            if (kpc.force_all_ctrs_set)(1) != 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "'kperf' counters require running as root",
+                ));
+            }
+
+            let fixed_count = (kpc.get_counter_count)(KPC_CLASS_FIXED_MASK) as usize;
+            let config_count = (kpc.get_config_count)(KPC_CLASS_CONFIGURABLE_MASK) as usize;
+
+            let mut configs = CONFIGS.lock().unwrap_or_else(PoisonError::into_inner);
+            let mut new_configs = configs.clone();
+            new_configs.resize(config_count, 0);
+
+            let mut indices = Vec::with_capacity(sources.len());
+            for &source in sources {
+                let index = match source {
+                    EventSource::Generic(GenericEvent::Cycles) => FIXED_CYCLES,
+                    EventSource::Generic(GenericEvent::Instructions) => FIXED_INSTRUCTIONS,
+
+                    EventSource::Generic(event) => {
+                        let Some(event) = configurable_event(event) else {
+                            return Err(io::Error::new(
+                                io::ErrorKind::Unsupported,
+                                format!("{event:?} is not supported by 'kperf'"),
+                            ));
+                        };
+                        fixed_count + allocate(&mut new_configs, event, RESTRICTED_SLOTS)?
+                    }
+
+                    // Raw events prefer counters that cannot count the generic
+                    // events.
+                    EventSource::Raw(event) => {
+                        let slots = (0..config_count)
+                            .filter(|slot| !RESTRICTED_SLOTS.contains(slot))
+                            .chain(RESTRICTED_SLOTS);
+                        fixed_count + allocate(&mut new_configs, event, slots)?
+                    }
+                };
+                indices.push(index);
+            }
+
+            if *configs != new_configs {
+                if (kpc.set_config)(KPC_CLASS_CONFIGURABLE_MASK, new_configs.as_mut_ptr()) != 0 {
+                    return Err(io::Error::other("failed to configure 'kperf' counters"));
+                }
+                *configs = new_configs;
+            }
+
+            if (kpc.set_counting)(KPC_CLASSES) != 0 || (kpc.set_thread_counting)(KPC_CLASSES) != 0 {
+                return Err(io::Error::other("failed to enable 'kperf' counters"));
+            }
+
+            let buf_len = fixed_count + config_count;
+            Ok(Self {
+                kpc,
+                indices,
+                start_buf: vec![0; buf_len],
+                stop_buf: vec![0; buf_len],
+                is_valid: false,
+            })
+        }
+    }
+
+    /// Records counter values at the start of the sample.
+    ///
+    /// Thread counters cannot be reset, so counts are the difference between
+    /// values at `start` and `stop`.
+    #[inline]
+    pub fn start(&mut self) {
+        self.is_valid = self.read_thread_counters(false);
+    }
+
+    /// Records counter values at the end of the sample.
+    #[inline]
+    pub fn stop(&mut self) {
+        self.is_valid &= self.read_thread_counters(true);
+    }
+
+    /// Reads the value of each counter into `counts`, in the order they were
+    /// opened.
+    pub fn read(&mut self, counts: &mut [u64]) -> io::Result<()> {
+        if !self.is_valid {
+            return Err(io::Error::other("failed to read 'kperf' thread counters"));
+        }
+
+        for (count, &index) in counts.iter_mut().zip(&self.indices) {
+            *count = self.stop_buf[index].wrapping_sub(self.start_buf[index]);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn read_thread_counters(&mut self, is_stop: bool) -> bool {
+        let buf = if is_stop { &mut self.stop_buf } else { &mut self.start_buf };
+
+        // SAFETY: `buf` has room for all fixed and configurable counters, and
+        // thread ID 0 is the current thread.
+        unsafe { (self.kpc.get_thread_counters)(0, buf.len() as u32, buf.as_mut_ptr()) == 0 }
+    }
+}
+
+/// Returns the first of `slots` already counting `event`, or else claims the
+/// first unused one.
+fn allocate(
+    configs: &mut [u64],
+    event: u64,
+    slots: impl IntoIterator<Item = usize>,
+) -> io::Result<usize> {
+    let config = event | CFGWORD_EL0A64EN;
+    let slots: Vec<usize> = slots.into_iter().filter(|&slot| slot < configs.len()).collect();
+
+    let slot = slots
+        .iter()
+        .find(|&&slot| configs[slot] == config)
+        .or_else(|| slots.iter().find(|&&slot| configs[slot] == 0))
+        .copied()
+        .ok_or_else(|| io::Error::other("not enough 'kperf' counters available"))?;
+
+    configs[slot] = config;
+    Ok(slot)
+}
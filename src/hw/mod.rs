@@ -0,0 +1,382 @@
+//! Hardware performance counters.
+//!
+//! Counters are per-thread and only count user-space events. They are enabled
+//! right before and disabled right after the timed section of each sample, so
+//! they measure the same work as the sample's duration. Each thread opens its
+//! counters once and reuses them for every sample.
+//!
+//! On Linux, a sample's counts are discarded if the kernel multiplexed the
+//! counters with other events, since they would only cover part of the sample.
+//! Counts are then not reported for the benchmark.
+//!
+//! Instruction counts are much more stable than time in noisy environments
+//! like CI, which makes them useful for detecting regressions.
+//!
+//! Counters are read via `perf_event_open` on Linux, via `kperf` on Apple
+//! Silicon Macs, and via `QueryThreadCycleTime` on Windows. Events that a
+//! platform cannot count are omitted.
+//!
+//! Other events can be selected with `--perf-events`, which are counted by a
+//! separate group.
+
+use std::{cell::RefCell, error::Error, io, str::FromStr};
+
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    any(
+        target_arch = "aarch64",
+        target_arch = "arm",
+        target_arch = "riscv64",
+        target_arch = "x86",
+        target_arch = "x86_64",
+    ),
+    not(miri),
+))]
+#[path = "linux.rs"]
+mod sys;
+
+#[cfg(all(target_os = "macos", target_arch = "aarch64", not(miri)))]
+#[path = "macos.rs"]
+mod sys;
+
+#[cfg(all(windows, not(miri)))]
+#[path = "windows.rs"]
+mod sys;
+
+#[cfg(not(any(
+    all(
+        any(target_os = "linux", target_os = "android"),
+        any(
+            target_arch = "aarch64",
+            target_arch = "arm",
+            target_arch = "riscv64",
+            target_arch = "x86",
+            target_arch = "x86_64",
+        ),
+        not(miri),
+    ),
+    all(target_os = "macos", target_arch = "aarch64", not(miri)),
+    all(windows, not(miri)),
+)))]
+#[path = "unsupported.rs"]
+mod sys;
+
+/// Hardware event counted around each sample.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HwCounterKind {
+    Cycles,
+    Instructions,
+    CacheMisses,
+    Branches,
+    BranchMisses,
+}
+
+impl HwCounterKind {
+    pub const COUNT: usize = 5;
+
+    pub const ALL: [Self; Self::COUNT] = {
+        use HwCounterKind::*;
+        [Cycles, Instructions, CacheMisses, Branches, BranchMisses]
+    };
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Cycles => "cycles",
+            Self::Instructions => "instructions",
+            Self::CacheMisses => "cache_misses",
+            Self::Branches => "branches",
+            Self::BranchMisses => "branch_misses",
+        }
+    }
+
+    /// Returns `true` if this event can be counted on this platform.
+    pub fn is_supported(self) -> bool {
+        sys::is_supported(self.source())
+    }
+
+    fn source(self) -> EventSource {
+        EventSource::Generic(match self {
+            Self::Cycles => GenericEvent::Cycles,
+            Self::Instructions => GenericEvent::Instructions,
+            Self::CacheMisses => GenericEvent::CacheMisses,
+            Self::Branches => GenericEvent::Branches,
+            Self::BranchMisses => GenericEvent::BranchMisses,
+        })
+    }
+}
+
+/// Event counts of a single sample.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct HwCounts(pub [u64; HwCounterKind::COUNT]);
+
+/// The maximum number of events that can be set with `--perf-events`.
+///
+/// CPUs only have a few programmable counters, so larger groups could never be
+/// scheduled at once.
+pub(crate) const MAX_PERF_EVENTS: usize = 8;
+
+/// An event selected with `--perf-events`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct PerfEvent {
+    /// The name as given by the user, which labels its column and JSON output.
+    pub name: &'static str,
+
+    source: EventSource,
+}
+
+/// Counts of events selected with `--perf-events` for a single sample.
+///
+/// Only the first [`PerfEvent`] count of values are used.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct PerfCounts(pub [u64; MAX_PERF_EVENTS]);
+
+/// How an event is identified to the OS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EventSource {
+    Generic(GenericEvent),
+
+    /// A model-specific event code, as documented by the CPU vendor.
+    Raw(u64),
+}
+
+/// Events that are available on most CPUs under the same name.
+///
+/// Discriminants are the generic hardware event IDs of `perf_event_open`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum GenericEvent {
+    Cycles = 0,
+    Instructions = 1,
+    CacheReferences = 2,
+    CacheMisses = 3,
+    Branches = 4,
+    BranchMisses = 5,
+    BusCycles = 6,
+    StalledCyclesFrontend = 7,
+    StalledCyclesBackend = 8,
+    RefCycles = 9,
+}
+
+impl GenericEvent {
+    /// Names accepted by `--perf-events`, which match those of `perf list`.
+    const NAMES: [(&'static str, Self); 12] = {
+        use GenericEvent::*;
+        [
+            ("cycles", Cycles),
+            ("cpu-cycles", Cycles),
+            ("instructions", Instructions),
+            ("cache-references", CacheReferences),
+            ("cache-misses", CacheMisses),
+            ("branches", Branches),
+            ("branch-instructions", Branches),
+            ("branch-misses", BranchMisses),
+            ("bus-cycles", BusCycles),
+            ("stalled-cycles-frontend", StalledCyclesFrontend),
+            ("stalled-cycles-backend", StalledCyclesBackend),
+            ("ref-cycles", RefCycles),
+        ]
+    };
+}
+
+impl FromStr for PerfEvent {
+    type Err = Box<dyn Error + Send + Sync>;
+
+    /// Parses a generic event name like `branch-misses`, or a raw event code
+    /// in hexadecimal like `r01c2`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        let source = if let Some(&(_, event)) =
+            GenericEvent::NAMES.iter().find(|(name, _)| name.eq_ignore_ascii_case(s))
+        {
+            EventSource::Generic(event)
+        } else if let Some(code) = s.strip_prefix('r').filter(|code| !code.is_empty()) {
+            match u64::from_str_radix(code, 16) {
+                Ok(code) => EventSource::Raw(code),
+                Err(_) => return Err(format!("invalid raw event code '{s}'").into()),
+            }
+        } else {
+            let names: Vec<&str> = GenericEvent::NAMES.iter().map(|(name, _)| *name).collect();
+            return Err(format!(
+                "unknown event '{s}', expected one of {} or a raw code like 'r01c2'",
+                names.join(", ")
+            )
+            .into());
+        };
+
+        // Names are kept for the rest of the program, so leaking is fine.
+        Ok(Self { name: s.to_owned().leak(), source })
+    }
+}
+
+/// A group of counters for the current thread.
+pub(crate) struct HwCounters {
+    /// The supported events, in the order they were opened.
+    kinds: Vec<HwCounterKind>,
+    group: sys::CounterGroup,
+}
+
+impl HwCounters {
+    /// Opens counters of supported events for the current thread.
+    pub fn open() -> io::Result<Self> {
+        let kinds: Vec<HwCounterKind> =
+            HwCounterKind::ALL.into_iter().filter(|kind| kind.is_supported()).collect();
+        let sources: Vec<EventSource> = kinds.iter().map(|kind| kind.source()).collect();
+
+        Ok(Self { group: sys::CounterGroup::open(&sources)?, kinds })
+    }
+
+    /// Reads counts of supported events, leaving others at zero.
+    fn read(&mut self) -> io::Result<HwCounts> {
+        let mut values = [0; HwCounterKind::COUNT];
+        self.group.read(&mut values[..self.kinds.len()])?;
+
+        let mut counts = HwCounts::default();
+        for (&kind, value) in self.kinds.iter().zip(values) {
+            counts.0[kind as usize] = value;
+        }
+        Ok(counts)
+    }
+}
+
+/// A group of counters for events selected with `--perf-events`.
+pub(crate) struct PerfCounters {
+    sources: Vec<EventSource>,
+    group: sys::CounterGroup,
+}
+
+impl PerfCounters {
+    /// Opens counters of `events` for the current thread.
+    pub fn open(events: &[PerfEvent]) -> io::Result<Self> {
+        let sources: Vec<EventSource> = events.iter().map(|event| event.source).collect();
+        Ok(Self { group: sys::CounterGroup::open(&sources)?, sources })
+    }
+}
+
+thread_local! {
+    /// Counters opened lazily on first use by each thread.
+    static THREAD_COUNTERS: RefCell<Option<HwCounters>> = const { RefCell::new(None) };
+
+    /// Counters of `--perf-events` opened lazily on first use by each thread.
+    static THREAD_PERF_COUNTERS: RefCell<Option<PerfCounters>> = const { RefCell::new(None) };
+}
+
+/// Resets and enables counters for the current thread, opening them if needed.
+///
+/// Failure to open counters is silently ignored because support is checked by
+/// [`HwCounters::open`] before benchmarking.
+#[inline]
+pub(crate) fn start() {
+    THREAD_COUNTERS.with(|counters| {
+        let mut counters = counters.borrow_mut();
+
+        if counters.is_none() {
+            *counters = HwCounters::open().ok();
+        }
+
+        if let Some(counters) = &mut *counters {
+            counters.group.start();
+        }
+    });
+}
+
+/// Disables counters for the current thread.
+#[inline]
+pub(crate) fn stop() {
+    THREAD_COUNTERS.with(|counters| {
+        if let Some(counters) = &mut *counters.borrow_mut() {
+            counters.group.stop();
+        }
+    });
+}
+
+/// Reads the counts since the last [`start`] for the current thread.
+pub(crate) fn read() -> Option<HwCounts> {
+    THREAD_COUNTERS.with(|counters| counters.borrow_mut().as_mut()?.read().ok())
+}
+
+/// Resets and enables counters of `events` for the current thread, opening
+/// them if needed.
+///
+/// Like [`start`], failure to open counters is silently ignored.
+#[inline]
+pub(crate) fn start_perf(events: &[PerfEvent]) {
+    THREAD_PERF_COUNTERS.with(|counters| {
+        let mut counters = counters.borrow_mut();
+
+        let is_current = counters.as_ref().is_some_and(|counters| {
+            counters.sources.iter().eq(events.iter().map(|event| &event.source))
+        });
+        if !is_current {
+            *counters = PerfCounters::open(events).ok();
+        }
+
+        if let Some(counters) = &mut *counters {
+            counters.group.start();
+        }
+    });
+}
+
+/// Disables counters of `--perf-events` for the current thread.
+#[inline]
+pub(crate) fn stop_perf() {
+    THREAD_PERF_COUNTERS.with(|counters| {
+        if let Some(counters) = &mut *counters.borrow_mut() {
+            counters.group.stop();
+        }
+    });
+}
+
+/// Reads the counts of `--perf-events` since the last [`start_perf`] for the
+/// current thread.
+pub(crate) fn read_perf() -> Option<PerfCounts> {
+    THREAD_PERF_COUNTERS.with(|counters| {
+        let mut counters = counters.borrow_mut();
+        let counters = counters.as_mut()?;
+
+        let mut counts = PerfCounts::default();
+        counters.group.read(&mut counts.0[..counters.sources.len()]).ok()?;
+        Some(counts)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that counters either fail to open or count instructions, or
+    /// cycles where instructions are not supported.
+    #[test]
+    fn count_instructions() {
+        let Ok(mut counters) = HwCounters::open() else {
+            return;
+        };
+
+        counters.group.start();
+        crate::black_box((0..1000).map(crate::black_box).sum::<u64>());
+        counters.group.stop();
+
+        let kind = if HwCounterKind::Instructions.is_supported() {
+            HwCounterKind::Instructions
+        } else {
+            HwCounterKind::Cycles
+        };
+
+        let counts = counters.read().unwrap();
+        assert!(counts.0[kind as usize] > 0);
+    }
+
+    #[test]
+    fn parse_perf_event() {
+        let event: PerfEvent = "Branch-Misses".parse().unwrap();
+        assert_eq!(event.name, "Branch-Misses");
+        assert_eq!(event.source, EventSource::Generic(GenericEvent::BranchMisses));
+
+        let event: PerfEvent = " r01c2 ".parse().unwrap();
+        assert_eq!(event.name, "r01c2");
+        assert_eq!(event.source, EventSource::Raw(0x01c2));
+
+        assert!("r".parse::<PerfEvent>().is_err());
+        assert!("rxyz".parse::<PerfEvent>().is_err());
+        assert!("page-faults".parse::<PerfEvent>().is_err());
+    }
+}
@@ -0,0 +1,31 @@
+use std::io;
+
+use super::EventSource;
+
+pub(crate) fn is_supported(_source: EventSource) -> bool {
+    false
+}
+
+/// Placeholder for platforms without hardware counter support.
+pub(crate) enum CounterGroup {}
+
+impl CounterGroup {
+    pub fn open(_sources: &[EventSource]) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "hardware counters are not supported on this platform",
+        ))
+    }
+
+    pub fn start(&mut self) {
+        match *self {}
+    }
+
+    pub fn stop(&mut self) {
+        match *self {}
+    }
+
+    pub fn read(&mut self, _counts: &mut [u64]) -> io::Result<()> {
+        match *self {}
+    }
+}
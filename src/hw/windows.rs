@@ -0,0 +1,90 @@
+//! Cycle counts via
+//! [`QueryThreadCycleTime`](https://learn.microsoft.com/en-us/windows/win32/api/realtimeapiset/nf-realtimeapiset-querythreadcycletime).
+//!
+//! Windows has no user-mode API for counting PMU events of a single thread.
+//! ETW can sample PMU events, but only system-wide from an elevated trace
+//! session, and PDH only exposes time-based thread counters. Neither can be
+//! toggled around each sample, so only cycles are supported.
+//!
+//! The kernel accumulates thread cycles from the time stamp counter on each
+//! context switch. They are therefore counted at the TSC's constant rate rather
+//! than the core's current frequency, and include time spent in the kernel on
+//! behalf of the thread.
+
+use std::{ffi::c_void, io};
+
+use super::{EventSource, GenericEvent};
+
+type Handle = *mut c_void;
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn GetCurrentThread() -> Handle;
+    fn QueryThreadCycleTime(thread: Handle, cycle_time: *mut u64) -> i32;
+}
+
+/// Returns `true` if `source` can be counted by `QueryThreadCycleTime`.
+pub(crate) fn is_supported(source: EventSource) -> bool {
+    source == EventSource::Generic(GenericEvent::Cycles)
+}
+
+/// Counters that are enabled, disabled, and read together.
+///
+/// Every counter is the current thread's cycle time.
+pub(crate) struct CounterGroup {
+    start_cycles: Option<u64>,
+    stop_cycles: Option<u64>,
+}
+
+impl CounterGroup {
+    pub fn open(sources: &[EventSource]) -> io::Result<Self> {
+        if let Some(source) = sources.iter().find(|&&source| !is_supported(source)) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("{source:?} is not supported on Windows"),
+            ));
+        }
+
+        if thread_cycles().is_none() {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self { start_cycles: None, stop_cycles: None })
+    }
+
+    /// Records the cycle time at the start of the sample.
+    ///
+    /// Thread cycle time cannot be reset, so counts are the difference between
+    /// values at `start` and `stop`.
+    #[inline]
+    pub fn start(&mut self) {
+        self.start_cycles = thread_cycles();
+    }
+
+    /// Records the cycle time at the end of the sample.
+    #[inline]
+    pub fn stop(&mut self) {
+        self.stop_cycles = thread_cycles();
+    }
+
+    /// Reads the value of each counter into `counts`, in the order they were
+    /// opened.
+    pub fn read(&mut self, counts: &mut [u64]) -> io::Result<()> {
+        let (Some(start), Some(stop)) = (self.start_cycles, self.stop_cycles) else {
+            return Err(io::Error::other("failed to query thread cycle time"));
+        };
+
+        counts.fill(stop.wrapping_sub(start));
+        Ok(())
+    }
+}
+
+#[inline]
+fn thread_cycles() -> Option<u64> {
+    let mut cycles = 0;
+
+    // SAFETY: The current thread pseudo-handle is always valid, and `cycles`
+    // is a valid pointer.
+    let ok = unsafe { QueryThreadCycleTime(GetCurrentThread(), &mut cycles) };
+    (ok != 0).then_some(cycles)
+}
@@ -0,0 +1,186 @@
+//! I/O counters reported by the OS.
+//!
+//! On Linux, these are read from
+//! [`/proc/thread-self/io`](https://man7.org/linux/man-pages/man5/proc_pid_io.5.html)
+//! before and after the timed section of each sample. Counts are per-thread,
+//! so only I/O done by the benchmarked function is measured.
+//!
+//! Comparing bytes read through syscalls with bytes fetched from storage shows
+//! whether reads were served by the page cache.
+
+use std::io;
+
+/// I/O event counted around each sample.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IoCounterKind {
+    /// Bytes passed to `read`-like syscalls, including page cache hits.
+    ReadBytes,
+
+    /// Bytes passed to `write`-like syscalls.
+    WriteBytes,
+
+    /// Number of `read`-like syscalls.
+    ReadCalls,
+
+    /// Number of `write`-like syscalls.
+    WriteCalls,
+
+    /// Bytes fetched from storage.
+    DiskReadBytes,
+
+    /// Bytes sent to storage.
+    DiskWriteBytes,
+}
+
+impl IoCounterKind {
+    pub const COUNT: usize = 6;
+
+    pub const ALL: [Self; Self::COUNT] = {
+        use IoCounterKind::*;
+        [ReadBytes, WriteBytes, ReadCalls, WriteCalls, DiskReadBytes, DiskWriteBytes]
+    };
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ReadBytes => "read_bytes",
+            Self::WriteBytes => "write_bytes",
+            Self::ReadCalls => "read_calls",
+            Self::WriteCalls => "write_calls",
+            Self::DiskReadBytes => "disk_read_bytes",
+            Self::DiskWriteBytes => "disk_write_bytes",
+        }
+    }
+
+    /// Returns `true` if this counts bytes rather than syscalls.
+    pub fn is_bytes(self) -> bool {
+        !matches!(self, Self::ReadCalls | Self::WriteCalls)
+    }
+
+    /// The field of this counter in `/proc/thread-self/io`.
+    fn proc_field(self) -> &'static str {
+        match self {
+            Self::ReadBytes => "rchar",
+            Self::WriteBytes => "wchar",
+            Self::ReadCalls => "syscr",
+            Self::WriteCalls => "syscw",
+            Self::DiskReadBytes => "read_bytes",
+            Self::DiskWriteBytes => "write_bytes",
+        }
+    }
+}
+
+/// I/O counts of a single sample.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct IoCounts(pub [u64; IoCounterKind::COUNT]);
+
+/// I/O counts of the current thread at the start of a sample.
+#[derive(Clone, Copy)]
+pub(crate) struct IoSnapshot {
+    counts: IoCounts,
+}
+
+impl IoSnapshot {
+    /// Takes a snapshot of the current thread's I/O counts.
+    #[inline]
+    pub fn start() -> io::Result<Self> {
+        let (mut counts, read_len) = read_thread_counts()?;
+
+        // Reading the counters is itself a read syscall, which is accounted
+        // after the read completes. Including it here excludes it from the
+        // counts returned by `end`.
+        counts.0[IoCounterKind::ReadBytes as usize] += read_len as u64;
+        counts.0[IoCounterKind::ReadCalls as usize] += 1;
+
+        Ok(Self { counts })
+    }
+
+    /// Returns the I/O done since the snapshot, or `None` if the counters could
+    /// not be read.
+    #[inline]
+    pub fn end(self) -> Option<IoCounts> {
+        let (end, _) = read_thread_counts().ok()?;
+        Some(IoCounts(std::array::from_fn(|i| end.0[i].saturating_sub(self.counts.0[i]))))
+    }
+}
+
+/// Reads the I/O counts of the current thread, along with the number of bytes
+/// read to get them.
+#[cfg(all(any(target_os = "linux", target_os = "android"), not(miri)))]
+fn read_thread_counts() -> io::Result<(IoCounts, usize)> {
+    use std::{fs::File, io::Read};
+
+    // Contents are read with a single syscall into a stack buffer to keep the
+    // adjustment in `IoSnapshot::start` exact and to not allocate.
+    let mut buf = [0u8; 512];
+    let len = File::open("/proc/thread-self/io")?.read(&mut buf)?;
+
+    let counts =
+        std::str::from_utf8(&buf[..len]).ok().and_then(parse_proc_io).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "malformed '/proc/thread-self/io'")
+        })?;
+
+    Ok((counts, len))
+}
+
+#[cfg(not(all(any(target_os = "linux", target_os = "android"), not(miri))))]
+fn read_thread_counts() -> io::Result<(IoCounts, usize)> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "I/O counters are not supported on this platform",
+    ))
+}
+
+/// Parses the `field: value` lines of `/proc/[pid]/io`.
+#[cfg_attr(not(any(test, target_os = "linux", target_os = "android")), allow(dead_code))]
+fn parse_proc_io(contents: &str) -> Option<IoCounts> {
+    let mut counts = [None::<u64>; IoCounterKind::COUNT];
+
+    for line in contents.lines() {
+        let Some((field, value)) = line.split_once(':') else {
+            continue;
+        };
+
+        if let Some(kind) = IoCounterKind::ALL.into_iter().find(|kind| kind.proc_field() == field) {
+            counts[kind as usize] = Some(value.trim().parse().ok()?);
+        }
+    }
+
+    let mut result = IoCounts::default();
+    for (count, parsed) in result.0.iter_mut().zip(counts) {
+        *count = parsed?;
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_proc_io() {
+        let contents = "rchar: 4292\n\
+                        wchar: 145\n\
+                        syscr: 13\n\
+                        syscw: 3\n\
+                        read_bytes: 8192\n\
+                        write_bytes: 0\n\
+                        cancelled_write_bytes: 0\n";
+
+        assert_eq!(super::parse_proc_io(contents), Some(IoCounts([4292, 145, 13, 3, 8192, 0])));
+
+        // Missing fields.
+        assert_eq!(super::parse_proc_io("rchar: 4292\n"), None);
+    }
+
+    /// Tests that reading the counters does not count as I/O.
+    #[test]
+    fn snapshot_excludes_self() {
+        let Ok(snapshot) = IoSnapshot::start() else {
+            return;
+        };
+
+        let counts = snapshot.end().unwrap();
+        assert_eq!(counts.0[IoCounterKind::ReadCalls as usize], 0);
+        assert_eq!(counts.0[IoCounterKind::ReadBytes as usize], 0);
+    }
+}
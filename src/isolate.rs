@@ -0,0 +1,644 @@
+//! Running each benchmark in a freshly spawned process.
+//!
+//! With `--isolate`, the benchmark process re-runs itself once per benchmark.
+//! The child process measures only that benchmark, for each of its thread
+//! counts, and writes the results to a file in a private temporary directory
+//! that the parent reads back. This keeps allocator state, caches, and global
+//! initialization from one benchmark from leaking into the next.
+//!
+//! With `--mode cold-start`, the parent instead spawns a child process per
+//! sample, and each child times only the first call of the benchmark. This
+//! measures start-up and first-call costs, such as lazy initialization, page
+//! faults, and instruction cache misses, which are hidden by hot loops.
+
+use std::{
+    env, fs, io,
+    process::{Command, Stdio},
+};
+
+use crate::{
+    alloc::AllocStats,
+    counter::{Count, KnownCounterKind},
+    divan::{MeasureResult, Measurement},
+    hw::HwCounterKind,
+    io_counters::IoCounterKind,
+    json::Json,
+    results::BenchErrorKind,
+    stats::{
+        Bimodality, SampleCollection, Stats, StatsSet, ThreadStats, TimeHistogram, TIME_PERCENTILES,
+    },
+    throttle::Throttling,
+    time::FineDuration,
+    util::{self, TempDir},
+};
+
+/// Environment variable set to the path of the benchmark run by a child
+/// process.
+const CHILD_ENV: &str = "DIVAN_ISOLATE_CHILD";
+
+/// Environment variable set to the file that a child process writes results
+/// to.
+const OUTPUT_ENV: &str = "DIVAN_ISOLATE_OUTPUT";
+
+/// The role of this process when isolating benchmarks.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IsolateRole {
+    /// Spawns a child process for each benchmark.
+    Parent,
+
+    /// Runs a single benchmark.
+    Child,
+}
+
+/// Returns the path of the benchmark to run if this is a child process.
+pub(crate) fn child_path() -> Option<String> {
+    env::var(CHILD_ENV).ok()
+}
+
+/// Runs the benchmark at `path` in a child process with the same arguments and
+/// input `seed` as this process, returning its measurement for each thread
+/// count.
+pub(crate) fn run(path: &str, seed: u64) -> io::Result<Vec<Option<MeasureResult>>> {
+    let out_dir = TempDir::new("divan-isolate")?;
+    let out_file = out_dir.path().join("results.json");
+
+    let output = Command::new(env::current_exe()?)
+        .args(env::args_os().skip(1))
+        .env(CHILD_ENV, path)
+        .env(OUTPUT_ENV, &out_file)
+        .env("DIVAN_SEED", seed.to_string())
+        .stdin(Stdio::null())
+        .output()?;
+
+    let contents = fs::read_to_string(&out_file);
+    drop(out_dir);
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!(
+            "process exited with {}:\n{}",
+            output.status,
+            stderr.trim_end()
+        )));
+    }
+
+    let json = Json::parse(&contents?)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+    json.as_array()
+        .map(|results| results.iter().map(result_from_json).collect())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed results"))
+}
+
+/// Runs the benchmark at `path` in `run_count` child processes that each time
+/// its first call, returning the distribution of those times as a single
+/// measurement.
+pub(crate) fn run_cold_start(
+    path: &str,
+    run_count: u32,
+    seed: u64,
+) -> io::Result<Vec<Option<MeasureResult>>> {
+    let mut runs = Vec::new();
+
+    for _ in 0..run_count.max(1) {
+        match run(path, seed)?.into_iter().next().flatten() {
+            Some(Ok(measurement)) => runs.push(measurement.stats),
+
+            // Later runs would likely fail the same way.
+            error @ (Some(Err(_)) | None) => return Ok(vec![error]),
+        }
+    }
+
+    Ok(vec![Some(Ok(Measurement {
+        stats: merge_cold_starts(runs),
+        samples: SampleCollection::default(),
+        throttling: None,
+    }))])
+}
+
+/// Combines the single-iteration statistics of cold start runs, treating each
+/// run as a sample.
+fn merge_cold_starts(runs: Vec<Stats>) -> Stats {
+    let mut durations: Vec<FineDuration> = runs.iter().map(|stats| stats.time.mean).collect();
+    durations.sort_unstable();
+
+    let run_count = durations.len();
+    let mean_of = |durations: &[FineDuration]| {
+        let total: u128 = durations.iter().map(|duration| duration.picos).sum();
+        FineDuration { picos: total / durations.len().max(1) as u128 }
+    };
+
+    let time = StatsSet {
+        fastest: durations.first().copied().unwrap_or_default(),
+        slowest: durations.last().copied().unwrap_or_default(),
+        median: mean_of(util::slice_middle(&durations)),
+        mean: mean_of(&durations),
+    };
+
+    let time_percentiles = TIME_PERCENTILES.map(|percentile| {
+        crate::stats::percentile_index(run_count, percentile)
+            .map(|index| durations[index])
+            .unwrap_or_default()
+    });
+
+    let mean_f64 = |values: &mut dyn Iterator<Item = f64>| values.sum::<f64>() / run_count as f64;
+
+    let hw_counts =
+        runs.iter().map(|stats| stats.hw_counts).collect::<Option<Vec<_>>>().map(|hw_counts| {
+            std::array::from_fn(|i| mean_f64(&mut hw_counts.iter().map(|counts| counts[i])))
+        });
+
+    // Every run records the same events.
+    let perf_counts = match runs.first() {
+        Some(first) => (first.perf_counts.iter().enumerate())
+            .map(|(i, &(event, _))| {
+                let mut counts =
+                    runs.iter().map(|stats| stats.perf_counts.get(i).map_or(0.0, |c| c.1));
+                (event, mean_f64(&mut counts))
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let io_counts =
+        runs.iter().map(|stats| stats.io_counts).collect::<Option<Vec<_>>>().map(|io_counts| {
+            std::array::from_fn(|i| mean_f64(&mut io_counts.iter().map(|counts| counts[i])))
+        });
+
+    let allocs = runs.iter().map(|stats| stats.allocs).collect::<Option<Vec<_>>>().map(|allocs| {
+        AllocStats {
+            alloc_count: mean_f64(&mut allocs.iter().map(|allocs| allocs.alloc_count)),
+            alloc_bytes: mean_f64(&mut allocs.iter().map(|allocs| allocs.alloc_bytes)),
+            dealloc_count: mean_f64(&mut allocs.iter().map(|allocs| allocs.dealloc_count)),
+            peak_bytes: allocs.iter().map(|allocs| allocs.peak_bytes).max().unwrap_or_default(),
+        }
+    });
+
+    let mean_time_of = |time: fn(&Stats) -> Option<FineDuration>| {
+        runs.iter().map(time).collect::<Option<Vec<_>>>().map(|times| mean_of(&times))
+    };
+    let gen_time = mean_time_of(|stats| stats.gen_time);
+    let drop_time = mean_time_of(|stats| stats.drop_time);
+
+    let time_histogram =
+        TimeHistogram::merge(runs.iter().map(|stats| stats.time_histogram.as_ref()));
+
+    // Counters describe inputs, which are the same for every run.
+    let (counts, custom_counts) = match runs.into_iter().next() {
+        Some(first) => (first.counts, first.custom_counts),
+        None => ([None; KnownCounterKind::COUNT], Vec::new()),
+    };
+
+    let time_std_dev = crate::stats::std_dev(durations.iter().map(|d| d.picos as f64))
+        .map(|picos| FineDuration { picos: picos.round() as u128 });
+
+    // Each run processes the same count, so only time varies throughput.
+    let throughput_std_dev = |count: Count| {
+        crate::stats::std_dev(
+            (durations.iter().filter(|d| !d.is_zero()))
+                .map(|duration| count.as_f64() * 1e12 / duration.picos as f64),
+        )
+    };
+
+    Stats {
+        sample_count: run_count as u32,
+        iter_count: run_count as u64,
+        time,
+        time_percentiles,
+        counts,
+        time_std_dev,
+        run_std_dev: None,
+        trimmed_mean: crate::stats::trimmed_mean(&durations),
+        winsorized_mean: crate::stats::winsorized_mean(&durations),
+        time_mad: crate::stats::median_abs_dev(&durations),
+        bimodality: crate::stats::detect_bimodality(&durations),
+        time_histogram,
+        throughput_std_dev: counts.map(|counts| throughput_std_dev(counts?.mean)),
+        custom_throughput_std_dev: (custom_counts.iter())
+            .filter_map(|&(unit, counts)| Some((unit, throughput_std_dev(counts.mean)?)))
+            .collect(),
+        custom_counts,
+        hw_counts,
+        perf_counts,
+        threads: None,
+        allocs,
+        io_counts,
+        gen_time,
+        drop_time,
+    }
+}
+
+/// Writes the measurement for each thread count for the parent process to
+/// read.
+pub(crate) fn write_output(results: &[Option<MeasureResult>]) -> io::Result<()> {
+    let path = env::var_os(OUTPUT_ENV)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "missing output path"))?;
+
+    let json = Json::Array(results.iter().map(|result| result_to_json(result.as_ref())).collect());
+    fs::write(path, json.to_string())
+}
+
+pub(crate) fn result_to_json(result: Option<&MeasureResult>) -> Json {
+    match result {
+        None => Json::Null,
+        Some(Ok(measurement)) => {
+            Json::object().with("stats", stats_to_json(&measurement.stats)).with(
+                "throttling",
+                measurement.throttling.map(|throttling| {
+                    Json::object()
+                        .with("before_khz", throttling.before_khz)
+                        .with("after_khz", throttling.after_khz)
+                }),
+            )
+        }
+        Some(Err(BenchErrorKind::Panicked(message))) => {
+            Json::object().with("panicked", message.as_str())
+        }
+        Some(Err(BenchErrorKind::TimedOut(timeout))) => {
+            Json::object().with("timed_out", timeout.picos)
+        }
+    }
+}
+
+pub(crate) fn result_from_json(json: &Json) -> Option<MeasureResult> {
+    if let Some(message) = json.get("panicked").and_then(Json::as_str) {
+        return Some(Err(BenchErrorKind::Panicked(message.to_owned())));
+    }
+
+    if let Some(picos) = json.get("timed_out").and_then(Json::as_u128) {
+        return Some(Err(BenchErrorKind::TimedOut(FineDuration { picos })));
+    }
+
+    let throttling = json.get("throttling").and_then(|throttling| {
+        Some(Throttling {
+            before_khz: throttling.get("before_khz")?.as_u64()?,
+            after_khz: throttling.get("after_khz")?.as_u64()?,
+        })
+    });
+
+    Some(Ok(Measurement {
+        stats: stats_from_json(json.get("stats")?)?,
+        samples: SampleCollection::default(),
+        throttling,
+    }))
+}
+
+fn stats_to_json(stats: &Stats) -> Json {
+    let picos = |set: StatsSet<FineDuration>| stats_set_to_json(set.map(|d| d.picos));
+    let counts = stats_set_to_json::<Count>;
+
+    let known_counts: Vec<Json> =
+        stats.counts.iter().map(|set| Json::from(set.map(counts))).collect();
+
+    let custom_counts: Vec<Json> = stats
+        .custom_counts
+        .iter()
+        .map(|&(unit, set)| Json::object().with("unit", unit).with("counts", counts(set)))
+        .collect();
+
+    Json::object()
+        .with("samples", stats.sample_count)
+        .with("iters", stats.iter_count)
+        .with("time", picos(stats.time))
+        .with(
+            "time_percentiles",
+            stats.time_percentiles.iter().map(|d| d.picos).collect::<Vec<_>>(),
+        )
+        .with("counts", known_counts)
+        .with("custom_counts", custom_counts)
+        .with("time_std_dev", stats.time_std_dev.map(|time| time.picos))
+        .with("run_std_dev", stats.run_std_dev.map(|time| time.picos))
+        .with("trimmed_mean", stats.trimmed_mean.map(|time| time.picos))
+        .with("winsorized_mean", stats.winsorized_mean.map(|time| time.picos))
+        .with("time_mad", stats.time_mad.map(|time| time.picos))
+        .with(
+            "bimodality",
+            stats.bimodality.map(|bimodality| {
+                Json::object()
+                    .with("modes", bimodality.modes.map(|time| time.picos).to_vec())
+                    .with("fast_fraction", bimodality.fast_fraction)
+            }),
+        )
+        .with("time_histogram", stats.time_histogram.as_ref().map(TimeHistogram::to_base64))
+        .with("throughput_std_dev", Vec::from(stats.throughput_std_dev))
+        .with(
+            "custom_throughput_std_dev",
+            (stats.custom_throughput_std_dev.iter())
+                .map(|&(unit, std_dev)| Json::object().with("unit", unit).with("std_dev", std_dev))
+                .collect::<Vec<_>>(),
+        )
+        .with("hw_counts", stats.hw_counts.map(Vec::from))
+        .with(
+            "perf_counts",
+            (stats.perf_counts.iter())
+                .map(|&(event, count)| Json::object().with("event", event).with("count", count))
+                .collect::<Vec<_>>(),
+        )
+        .with(
+            "threads",
+            stats.threads.as_ref().map(|threads| {
+                Json::object().with("wall_time", picos(threads.wall_time)).with(
+                    "per_thread",
+                    threads.per_thread.iter().copied().map(picos).collect::<Vec<_>>(),
+                )
+            }),
+        )
+        .with(
+            "allocs",
+            stats.allocs.map(|allocs| {
+                Json::object()
+                    .with("alloc_count", allocs.alloc_count)
+                    .with("alloc_bytes", allocs.alloc_bytes)
+                    .with("dealloc_count", allocs.dealloc_count)
+                    .with("peak_bytes", allocs.peak_bytes)
+            }),
+        )
+        .with("io_counts", stats.io_counts.map(Vec::from))
+        .with("gen_time", stats.gen_time.map(|time| time.picos))
+        .with("drop_time", stats.drop_time.map(|time| time.picos))
+}
+
+fn bimodality_from_json(json: &Json) -> Option<Bimodality> {
+    let [fast, slow] = json.get("modes")?.as_array()? else {
+        return None;
+    };
+
+    Some(Bimodality {
+        modes: [FineDuration { picos: fast.as_u128()? }, FineDuration { picos: slow.as_u128()? }],
+        fast_fraction: json.get("fast_fraction")?.as_f64()?,
+    })
+}
+
+fn stats_from_json(json: &Json) -> Option<Stats> {
+    let picos = |json: &Json| {
+        Some(stats_set_from_json(json, Json::as_u128)?.map(|picos| FineDuration { picos }))
+    };
+    let counts = |json: &Json| stats_set_from_json(json, Count::from_json);
+
+    let known_counts = json.get("counts")?.as_array()?;
+    if known_counts.len() != KnownCounterKind::COUNT {
+        return None;
+    }
+
+    let time_percentiles = json.get("time_percentiles")?.as_array()?;
+    if time_percentiles.len() != TIME_PERCENTILES.len() {
+        return None;
+    }
+
+    let custom_counts = json
+        .get("custom_counts")?
+        .as_array()?
+        .iter()
+        .map(|custom| {
+            // Units are `'static` because they usually come from literals. The
+            // few units of a run are leaked to keep that type.
+            let unit: &'static str = custom.get("unit")?.as_str()?.to_owned().leak();
+            Some((unit, counts(custom.get("counts")?)?))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let throughput_std_dev = json.get("throughput_std_dev")?.as_array()?;
+    if throughput_std_dev.len() != KnownCounterKind::COUNT {
+        return None;
+    }
+
+    let custom_throughput_std_dev = json
+        .get("custom_throughput_std_dev")?
+        .as_array()?
+        .iter()
+        .map(|custom| {
+            let unit = custom.get("unit")?.as_str()?;
+            let unit = match custom_counts.iter().find(|(u, _)| *u == unit) {
+                Some(&(unit, _)) => unit,
+                None => unit.to_owned().leak(),
+            };
+            Some((unit, custom.get("std_dev")?.as_f64()?))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let hw_counts = match json.get("hw_counts")? {
+        Json::Null => None,
+        hw_counts => {
+            let hw_counts = hw_counts.as_array()?;
+            if hw_counts.len() != HwCounterKind::COUNT {
+                return None;
+            }
+            Some(std::array::from_fn(|i| hw_counts[i].as_f64().unwrap_or_default()))
+        }
+    };
+
+    let perf_counts = json
+        .get("perf_counts")?
+        .as_array()?
+        .iter()
+        .map(|perf| {
+            let event: &'static str = perf.get("event")?.as_str()?.to_owned().leak();
+            Some((event, perf.get("count")?.as_f64()?))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let threads = match json.get("threads")? {
+        Json::Null => None,
+        threads => Some(ThreadStats {
+            wall_time: picos(threads.get("wall_time")?)?,
+            per_thread: threads
+                .get("per_thread")?
+                .as_array()?
+                .iter()
+                .map(picos)
+                .collect::<Option<Vec<_>>>()?,
+        }),
+    };
+
+    let allocs = match json.get("allocs")? {
+        Json::Null => None,
+        allocs => Some(AllocStats {
+            alloc_count: allocs.get("alloc_count")?.as_f64()?,
+            alloc_bytes: allocs.get("alloc_bytes")?.as_f64()?,
+            dealloc_count: allocs.get("dealloc_count")?.as_f64()?,
+            peak_bytes: allocs.get("peak_bytes")?.as_u64()?,
+        }),
+    };
+
+    let io_counts = match json.get("io_counts")? {
+        Json::Null => None,
+        io_counts => {
+            let io_counts = io_counts.as_array()?;
+            if io_counts.len() != IoCounterKind::COUNT {
+                return None;
+            }
+            Some(std::array::from_fn(|i| io_counts[i].as_f64().unwrap_or_default()))
+        }
+    };
+
+    Some(Stats {
+        sample_count: json.get("samples")?.as_u64()?.try_into().ok()?,
+        iter_count: json.get("iters")?.as_u64()?,
+        time: picos(json.get("time")?)?,
+        time_percentiles: std::array::from_fn(|i| FineDuration {
+            picos: time_percentiles[i].as_u128().unwrap_or_default(),
+        }),
+        counts: std::array::from_fn(|i| match &known_counts[i] {
+            Json::Null => None,
+            set => counts(set),
+        }),
+        custom_counts,
+        time_std_dev: json.get("time_std_dev")?.as_u128().map(|picos| FineDuration { picos }),
+        run_std_dev: json
+            .get("run_std_dev")
+            .and_then(Json::as_u128)
+            .map(|picos| FineDuration { picos }),
+        trimmed_mean: json
+            .get("trimmed_mean")
+            .and_then(Json::as_u128)
+            .map(|picos| FineDuration { picos }),
+        winsorized_mean: json
+            .get("winsorized_mean")
+            .and_then(Json::as_u128)
+            .map(|picos| FineDuration { picos }),
+        time_mad: json.get("time_mad").and_then(Json::as_u128).map(|picos| FineDuration { picos }),
+        bimodality: json.get("bimodality").and_then(bimodality_from_json),
+        time_histogram: json
+            .get("time_histogram")
+            .and_then(Json::as_str)
+            .and_then(TimeHistogram::from_base64),
+        throughput_std_dev: std::array::from_fn(|i| throughput_std_dev[i].as_f64()),
+        custom_throughput_std_dev,
+        hw_counts,
+        perf_counts,
+        threads,
+        allocs,
+        io_counts,
+        gen_time: json.get("gen_time")?.as_u128().map(|picos| FineDuration { picos }),
+        drop_time: json.get("drop_time")?.as_u128().map(|picos| FineDuration { picos }),
+    })
+}
+
+fn stats_set_to_json<T: Into<Json>>(set: StatsSet<T>) -> Json {
+    Json::object()
+        .with("fastest", set.fastest)
+        .with("slowest", set.slowest)
+        .with("median", set.median)
+        .with("mean", set.mean)
+}
+
+fn stats_set_from_json<T>(json: &Json, get: fn(&Json) -> Option<T>) -> Option<StatsSet<T>> {
+    Some(StatsSet {
+        fastest: get(json.get("fastest")?)?,
+        slowest: get(json.get("slowest")?)?,
+        median: get(json.get("median")?)?,
+        mean: get(json.get("mean")?)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn result_json_round_trip() {
+        let time = StatsSet {
+            fastest: FineDuration { picos: 1 },
+            slowest: FineDuration { picos: 4 },
+            median: FineDuration { picos: 2 },
+            mean: FineDuration { picos: 3 },
+        };
+
+        let stats = Stats {
+            time_percentiles: [FineDuration { picos: 3 }; TIME_PERCENTILES.len()],
+            counts: std::array::from_fn(|i| {
+                let count = Count::Int(8);
+                (i == 0).then_some(StatsSet {
+                    fastest: count,
+                    slowest: count,
+                    median: count,
+                    mean: count,
+                })
+            }),
+            custom_counts: vec![(
+                "widgets",
+                StatsSet {
+                    fastest: Count::Int(1),
+                    slowest: Count::Int(3),
+                    median: Count::Int(2),
+                    mean: Count::Frac(2.5),
+                },
+            )],
+            time_std_dev: Some(FineDuration { picos: 1 }),
+            run_std_dev: Some(FineDuration { picos: 2 }),
+            trimmed_mean: Some(FineDuration { picos: 3 }),
+            winsorized_mean: Some(FineDuration { picos: 3 }),
+            time_mad: Some(FineDuration { picos: 1 }),
+            bimodality: Some(Bimodality {
+                modes: [FineDuration { picos: 1 }, FineDuration { picos: 4 }],
+                fast_fraction: 0.75,
+            }),
+            throughput_std_dev: std::array::from_fn(|i| (i == 0).then_some(1e9)),
+            custom_throughput_std_dev: vec![("widgets", 0.25)],
+            hw_counts: Some([2000.0, 1000.0, 3.0, 150.0, 0.5]),
+            perf_counts: vec![("r01c2", 12.5)],
+            threads: Some(ThreadStats { wall_time: time, per_thread: vec![time, time] }),
+            allocs: Some(AllocStats {
+                alloc_count: 1.0,
+                alloc_bytes: 1.5,
+                dealloc_count: 1.0,
+                peak_bytes: 64,
+            }),
+            io_counts: Some([4096.0, 0.0, 1.0, 0.0, 0.0, 0.0]),
+            drop_time: Some(time.mean),
+            ..Stats::test_default(10, 100, time)
+        };
+
+        let measurement = Measurement {
+            stats,
+            samples: SampleCollection::default(),
+            throttling: Some(Throttling { before_khz: 3_000_000, after_khz: 2_000_000 }),
+        };
+
+        let results = [
+            Some(Ok(measurement)),
+            Some(Err(BenchErrorKind::Panicked("oops".to_owned()))),
+            Some(Err(BenchErrorKind::TimedOut(FineDuration { picos: 5 }))),
+            None,
+        ];
+
+        let json =
+            Json::Array(results.iter().map(|result| result_to_json(result.as_ref())).collect());
+        let json = Json::parse(&json.to_string()).unwrap();
+        let parsed: Vec<_> = json.as_array().unwrap().iter().map(result_from_json).collect();
+
+        let Some(Ok(measurement)) = &parsed[0] else { panic!("expected measurement") };
+        assert_eq!(result_to_json(parsed[0].as_ref()), result_to_json(results[0].as_ref()));
+        assert_eq!(measurement.stats.custom_counts[0].0, "widgets");
+
+        assert!(
+            matches!(&parsed[1], Some(Err(BenchErrorKind::Panicked(message))) if message == "oops")
+        );
+        assert!(matches!(
+            parsed[2],
+            Some(Err(BenchErrorKind::TimedOut(FineDuration { picos: 5 })))
+        ));
+        assert!(parsed[3].is_none());
+    }
+
+    #[test]
+    fn merge_cold_starts() {
+        let run = |picos: u128| {
+            let time = FineDuration { picos };
+            Stats::test_default(
+                1,
+                1,
+                StatsSet { fastest: time, slowest: time, median: time, mean: time },
+            )
+        };
+
+        let stats = super::merge_cold_starts([40, 10, 30, 20].map(run).into());
+
+        assert_eq!(stats.sample_count, 4);
+        assert_eq!(stats.iter_count, 4);
+        assert_eq!(
+            stats.time.map(|time| time.picos),
+            StatsSet { fastest: 10, slowest: 40, median: 25, mean: 25 }
+        );
+    }
+}
@@ -0,0 +1,513 @@
+//! Minimal JSON support for reading and writing results files.
+//!
+//! This intentionally supports only what Divan needs, so that we don't depend
+//! on `serde` for every user.
+
+use std::fmt;
+
+/// A JSON value.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+
+    /// Non-negative integer. Kept separate from `Float` so that picosecond
+    /// durations do not lose precision.
+    UInt(u128),
+
+    /// Negative integer.
+    Int(i128),
+
+    Float(f64),
+    String(String),
+    Array(Vec<Json>),
+
+    /// Object with insertion order preserved.
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Creates an empty object.
+    #[inline]
+    pub fn object() -> Self {
+        Self::Object(Vec::new())
+    }
+
+    /// Appends a key-value pair if `self` is an object.
+    #[must_use]
+    pub fn with(mut self, key: &str, value: impl Into<Json>) -> Self {
+        if let Self::Object(entries) = &mut self {
+            entries.push((key.to_owned(), value.into()));
+        }
+        self
+    }
+
+    /// Returns the value for `key` if `self` is an object.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Self::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_u128(&self) -> Option<u128> {
+        match *self {
+            Self::UInt(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_u128()?.try_into().ok()
+    }
+
+    #[inline]
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::UInt(n) => Some(n as f64),
+            Self::Int(n) => Some(n as f64),
+            Self::Float(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Parses a single JSON value, which may be surrounded by whitespace.
+    pub fn parse(s: &str) -> Result<Self, JsonError> {
+        let mut parser = Parser { bytes: s.as_bytes(), pos: 0 };
+        let value = parser.parse_value()?;
+
+        parser.skip_whitespace();
+        if parser.pos != parser.bytes.len() {
+            return Err(parser.error("trailing characters"));
+        }
+
+        Ok(value)
+    }
+}
+
+macro_rules! impl_from_uint {
+    ($($t:ty),+) => {
+        $(impl From<$t> for Json {
+            #[inline]
+            fn from(n: $t) -> Self {
+                Self::UInt(n as u128)
+            }
+        })+
+    };
+}
+
+impl_from_uint!(u8, u16, u32, u64, u128, usize);
+
+impl From<bool> for Json {
+    #[inline]
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
+    }
+}
+
+impl From<f64> for Json {
+    #[inline]
+    fn from(n: f64) -> Self {
+        Self::Float(n)
+    }
+}
+
+impl From<&str> for Json {
+    #[inline]
+    fn from(s: &str) -> Self {
+        Self::String(s.to_owned())
+    }
+}
+
+impl From<String> for Json {
+    #[inline]
+    fn from(s: String) -> Self {
+        Self::String(s)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    #[inline]
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or(Self::Null)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    #[inline]
+    fn from(values: Vec<T>) -> Self {
+        Self::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Writes compact JSON.
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::UInt(n) => write!(f, "{n}"),
+            Self::Int(n) => write!(f, "{n}"),
+
+            // JSON has no representation for infinity or NaN.
+            Self::Float(n) if !n.is_finite() => f.write_str("null"),
+            Self::Float(n) => write!(f, "{n}"),
+
+            Self::String(s) => write_str(f, s),
+            Self::Array(values) => {
+                f.write_str("[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                f.write_str("]")
+            }
+            Self::Object(entries) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str(",")?;
+                    }
+                    write_str(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// Writes a quoted and escaped JSON string.
+fn write_str(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for ch in s.chars() {
+        match ch {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            ch if (ch as u32) < 0x20 => write!(f, "\\u{:04x}", ch as u32)?,
+            ch => write!(f, "{ch}")?,
+        }
+    }
+    f.write_str("\"")
+}
+
+/// Failure to parse JSON.
+#[derive(Debug)]
+pub(crate) struct JsonError {
+    message: &'static str,
+    pos: usize,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.pos)
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &'static str) -> JsonError {
+        JsonError { message, pos: self.pos }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), JsonError> {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error("unexpected character"))
+        }
+    }
+
+    fn expect_literal(&mut self, literal: &str, value: Json) -> Result<Json, JsonError> {
+        if self.bytes[self.pos..].starts_with(literal.as_bytes()) {
+            self.pos += literal.len();
+            Ok(value)
+        } else {
+            Err(self.error("invalid literal"))
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Json, JsonError> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            None => Err(self.error("unexpected end of input")),
+            Some(b'n') => self.expect_literal("null", Json::Null),
+            Some(b't') => self.expect_literal("true", Json::Bool(true)),
+            Some(b'f') => self.expect_literal("false", Json::Bool(false)),
+            Some(b'"') => self.parse_string().map(Json::String),
+            Some(b'[') => self.parse_array(),
+            Some(b'{') => self.parse_object(),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            Some(_) => Err(self.error("unexpected character")),
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Json, JsonError> {
+        self.expect(b'[')?;
+
+        let mut values = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Json::Array(values));
+        }
+
+        loop {
+            values.push(self.parse_value()?);
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Json::Array(values));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Json, JsonError> {
+        self.expect(b'{')?;
+
+        let mut entries = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Json::Object(entries));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+
+            self.skip_whitespace();
+            self.expect(b':')?;
+
+            let value = self.parse_value()?;
+            entries.push((key, value));
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Json::Object(entries));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, JsonError> {
+        self.expect(b'"')?;
+
+        let mut result = String::new();
+
+        loop {
+            // Copy runs of unescaped characters at once.
+            let run_start = self.pos;
+            while let Some(b) = self.peek() {
+                if b == b'"' || b == b'\\' {
+                    break;
+                }
+                self.pos += 1;
+            }
+
+            // The input is `&str` and runs are split on ASCII bytes, so this
+            // is always valid UTF-8.
+            result.push_str(
+                std::str::from_utf8(&self.bytes[run_start..self.pos])
+                    .map_err(|_| self.error("invalid UTF-8"))?,
+            );
+
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(result);
+                }
+                Some(_) => {
+                    // Backslash escape.
+                    self.pos += 1;
+                    let escaped = self.peek().ok_or_else(|| self.error("unterminated string"))?;
+                    self.pos += 1;
+
+                    result.push(match escaped {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.parse_unicode_escape()?,
+                        _ => return Err(self.error("invalid escape")),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Parses the `XXXX` in `\uXXXX`, including surrogate pairs.
+    fn parse_unicode_escape(&mut self) -> Result<char, JsonError> {
+        let parse_hex = |parser: &mut Self| -> Result<u32, JsonError> {
+            let hex = parser
+                .bytes
+                .get(parser.pos..parser.pos + 4)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| parser.error("invalid unicode escape"))?;
+            parser.pos += 4;
+            Ok(hex)
+        };
+
+        let high = parse_hex(self)?;
+
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.bytes[self.pos..].starts_with(b"\\u") {
+                return Err(self.error("unpaired surrogate"));
+            }
+            self.pos += 2;
+
+            let low = parse_hex(self)?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate"));
+            }
+
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+
+        char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    fn parse_number(&mut self) -> Result<Json, JsonError> {
+        let start = self.pos;
+        let mut is_float = false;
+
+        while let Some(b) = self.peek() {
+            match b {
+                b'0'..=b'9' | b'-' | b'+' => {}
+                b'.' | b'e' | b'E' => is_float = true,
+                _ => break,
+            }
+            self.pos += 1;
+        }
+
+        // The range only contains ASCII.
+        let text = std::str::from_utf8(&self.bytes[start..self.pos]).unwrap_or_default();
+
+        let value = if is_float {
+            text.parse().ok().map(Json::Float)
+        } else if text.starts_with('-') {
+            text.parse().ok().map(Json::Int)
+        } else {
+            text.parse().ok().map(Json::UInt)
+        };
+
+        value.ok_or(JsonError { message: "invalid number", pos: start })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn round_trip(json: &str) {
+        assert_eq!(Json::parse(json).unwrap().to_string(), json);
+    }
+
+    #[test]
+    fn parse_scalars() {
+        assert_eq!(Json::parse("null").unwrap(), Json::Null);
+        assert_eq!(Json::parse(" true ").unwrap(), Json::Bool(true));
+        assert_eq!(Json::parse("false").unwrap(), Json::Bool(false));
+        assert_eq!(Json::parse("42").unwrap(), Json::UInt(42));
+        assert_eq!(Json::parse("-42").unwrap(), Json::Int(-42));
+        assert_eq!(Json::parse("1.5").unwrap(), Json::Float(1.5));
+        assert_eq!(Json::parse("1e3").unwrap(), Json::Float(1000.));
+
+        // Picoseconds can exceed `u64`.
+        let big = u128::MAX.to_string();
+        assert_eq!(Json::parse(&big).unwrap(), Json::UInt(u128::MAX));
+    }
+
+    #[test]
+    fn parse_strings() {
+        assert_eq!(Json::parse(r#""a\"b\\c\nd""#).unwrap(), Json::from("a\"b\\c\nd"));
+        assert_eq!(Json::parse(r#""µs""#).unwrap(), Json::from("µs"));
+        assert_eq!(Json::parse(r#""🦀""#).unwrap(), Json::from("🦀"));
+        assert_eq!(Json::parse(r#""\ud83e\udd80""#).unwrap(), Json::from("🦀"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        for json in ["", "nul", "[1,", "{\"a\" 1}", "\"abc", "1 2", "[1,]"] {
+            assert!(Json::parse(json).is_err(), "{json:?} should fail");
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        round_trip("[]");
+        round_trip("{}");
+        round_trip(r#"{"a":[1,-2,3.5,null,true],"b":{"c":"d\"e"}}"#);
+        round_trip(r#""\u0001""#);
+    }
+
+    #[test]
+    fn object_builder() {
+        let json =
+            Json::object().with("path", "a::b").with("mean", 1_000u128).with("x", None::<u32>);
+        assert_eq!(json.to_string(), r#"{"path":"a::b","mean":1000,"x":null}"#);
+        assert_eq!(json.get("mean").and_then(Json::as_u128), Some(1000));
+        assert_eq!(json.get("missing"), None);
+    }
+}
@@ -0,0 +1,178 @@
+//! JUnit XML export of benchmark results.
+//!
+//! Each benchmark is a test case whose time is its mean time per iteration in
+//! seconds. Benchmarks that exceeded a threshold are failed test cases, and
+//! benchmarks that panicked or timed out are errored test cases. This lets CI
+//! systems that only understand JUnit show benchmarks alongside tests.
+
+use std::{fmt::Write as _, fs, io, path::Path};
+
+use crate::{
+    results::{BenchResults, ThresholdKind},
+    time::FineDuration,
+};
+
+/// Writes results to a JUnit XML file at `path`.
+pub(crate) fn write(path: &Path, results: &BenchResults) -> io::Result<()> {
+    fs::write(path, render(results))
+}
+
+/// Renders results as a JUnit XML document with a single test suite.
+pub(crate) fn render(results: &BenchResults) -> String {
+    let entries: Vec<_> = results.entries().collect();
+
+    let tests = entries.len() + results.errors.len();
+    let failures = results.failures.len();
+    let errors = results.errors.len();
+    let time = seconds(FineDuration {
+        picos: entries.iter().map(|entry| entry.stats.time.mean.picos).sum(),
+    });
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+    let counts =
+        format!(r#"tests="{tests}" failures="{failures}" errors="{errors}" time="{time}""#);
+    _ = writeln!(xml, r#"<testsuites name="divan" {counts}>"#);
+    _ = writeln!(xml, r#"  <testsuite name="divan" {counts}>"#);
+
+    for entry in entries {
+        let failures: Vec<_> =
+            results.failures.iter().filter(|failure| failure.path == entry.path).collect();
+
+        let time = seconds(entry.stats.time.mean);
+        write_test_case_start(&mut xml, &entry.path, &time, failures.is_empty());
+
+        for failure in &failures {
+            let kind = match failure.kind {
+                ThresholdKind::MaxTimeMean { .. } => "max_time_mean",
+                ThresholdKind::Regression { .. } => "regression",
+            };
+
+            _ = writeln!(
+                xml,
+                r#"      <failure type="{kind}" message="{}"/>"#,
+                escape(&failure.to_string())
+            );
+        }
+
+        if !failures.is_empty() {
+            xml.push_str("    </testcase>\n");
+        }
+    }
+
+    for error in &results.errors {
+        write_test_case_start(&mut xml, &error.path, "0", false);
+
+        _ = writeln!(xml, r#"      <error message="{}"/>"#, escape(&error.to_string()));
+        xml.push_str("    </testcase>\n");
+    }
+
+    xml.push_str("  </testsuite>\n");
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+/// Writes a `<testcase>` tag for the benchmark at `path`, which is
+/// self-closing if it has no children.
+///
+/// The last path component is the test name, and the rest is the class name
+/// that CI systems group tests by.
+fn write_test_case_start(xml: &mut String, path: &str, time: &str, self_closing: bool) {
+    let (class_name, name) = match path.rsplit_once("::") {
+        Some((class_name, name)) => (class_name, name),
+        None => (path, path),
+    };
+
+    _ = write!(
+        xml,
+        r#"    <testcase name="{}" classname="{}" time="{time}""#,
+        escape(name),
+        escape(class_name),
+    );
+    xml.push_str(if self_closing { "/>\n" } else { ">\n" });
+}
+
+/// Formats a duration as fractional seconds.
+fn seconds(duration: FineDuration) -> String {
+    (duration.picos as f64 / 1e12).to_string()
+}
+
+/// Escapes text for use in an XML attribute value.
+fn escape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            '\n' => result.push_str("&#10;"),
+            _ => result.push(ch),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        results::{
+            BenchError, BenchErrorKind, EntryResult, ResultTree, ThresholdFailure, ThresholdKind,
+        },
+        stats::{Stats, StatsSet},
+    };
+
+    fn entry(path: &str, picos: u128) -> ResultTree {
+        let time = FineDuration { picos };
+
+        ResultTree::Leaf(EntryResult {
+            name: path.rsplit("::").next().unwrap().to_owned(),
+            path: path.to_owned(),
+            generic_type: None,
+            generic_const: None,
+            stats: Stats::test_default(
+                10,
+                100,
+                StatsSet { fastest: time, slowest: time, median: time, mean: time },
+            ),
+            sample_times: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn render_results() {
+        let results = BenchResults {
+            tree: vec![entry("parse::small", 1_500), entry("parse::Vec<u8>", 2_000_000)],
+            failures: vec![ThresholdFailure {
+                path: "parse::Vec<u8>".to_owned(),
+                kind: ThresholdKind::Regression { change: 12.5, max: 10.0 },
+            }],
+            errors: vec![BenchError {
+                path: "parse::large".to_owned(),
+                kind: BenchErrorKind::Panicked("out of \"memory\"".to_owned()),
+            }],
+            throttled: Vec::new(),
+            noisy: Vec::new(),
+            bimodal: Vec::new(),
+        };
+
+        assert_eq!(
+            render(&results),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="divan" tests="3" failures="1" errors="1" time="0.0000020015">
+  <testsuite name="divan" tests="3" failures="1" errors="1" time="0.0000020015">
+    <testcase name="small" classname="parse" time="0.0000000015"/>
+    <testcase name="Vec&lt;u8&gt;" classname="parse" time="0.000002">
+      <failure type="regression" message="parse::Vec&lt;u8&gt;: median changed by +12.50%, exceeding regression limit of 10%"/>
+    </testcase>
+    <testcase name="large" classname="parse" time="0">
+      <error message="parse::large: panicked: out of &quot;memory&quot;"/>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+        );
+    }
+}
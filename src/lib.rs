@@ -10,25 +10,74 @@
 #[path = "private.rs"]
 pub mod __private;
 
+mod alloc;
+mod baseline;
 mod bench;
+mod budget;
+mod callgrind;
 mod cli;
+mod compare;
 mod compile_fail;
 mod config;
+mod csv;
+mod dashboard;
 mod divan;
 mod entry;
+mod environment;
+mod fixture;
+mod github;
+mod history;
+mod http;
+mod hw;
+mod io_counters;
+mod isolate;
+mod json;
+mod junit;
+mod markdown;
+mod message;
 mod miri;
+#[cfg(feature = "notify")]
+mod notify;
+mod openmetrics;
+#[cfg(feature = "parquet")]
+mod parquet;
+mod priority;
+mod profile;
+mod progress;
+mod resume;
+mod sample_dump;
+#[cfg(all(target_os = "macos", not(miri)))]
+mod signpost;
 mod stats;
+mod status;
+mod throttle;
 mod time;
+#[cfg(feature = "tracy")]
+mod tracy;
 mod tree_painter;
 mod util;
 
 pub mod counter;
+pub mod executor;
+pub mod profiler;
+pub mod reporter;
+pub mod results;
 
 #[doc(inline)]
 pub use std::hint::black_box;
 
 #[doc(inline)]
-pub use crate::{bench::Bencher, divan::Divan};
+pub use crate::{
+    alloc::AllocProfiler,
+    bench::{BenchOptions, Bencher, TimerGuard},
+    divan::Divan,
+    fixture::FixtureFile,
+    time::TimeUnit,
+};
+
+#[cfg(feature = "rand")]
+#[doc(inline)]
+pub use crate::bench::BenchRng;
 
 /// Runs all registered benchmarks.
 ///
@@ -158,13 +207,29 @@ pub fn main() {
 /// - [`crate`]
 /// - [`consts`]
 /// - [`types`]
+/// - [`args`]
+/// - [`args_from_dir`]
 /// - [`sample_count`]
 /// - [`sample_size`]
+/// - [`sample_time`]
+/// - [`until_stable`]
 /// - [`threads`]
 /// - [`counters`]
+/// - [`bytes_format`]
 /// - [`min_time`]
 /// - [`max_time`]
+/// - [`max_time_mean`] (or `assert_max`)
+/// - [`timeout`]
+/// - [`warmup_time`]
+/// - [`warmup_samples`]
 /// - [`skip_ext_time`]
+/// - [`streaming_stats`]
+/// - [`reservoir_size`]
+/// - [`flush_cache`]
+/// - [`unroll`]
+/// - [`setup`]
+/// - [`teardown`]
+/// - [`tags`]
 /// - [`ignore`]
 ///
 /// ## `name`
@@ -266,6 +331,19 @@ pub fn main() {
 /// }
 /// ```
 ///
+/// Constants can also be provided as a range with integer literal bounds,
+/// which is expanded into a list of each value in the range. Like lists, ranges
+/// are not limited to 20 values, but they are limited to 256 values to keep
+/// compile times reasonable:
+///
+/// ```
+/// #[divan::bench(consts = 1..=32)]
+/// fn init_array<const N: usize>() -> [i32; N] {
+///     // ...
+///     # [0; N]
+/// }
+/// ```
+///
 /// ## `types`
 /// [`types`]: #types
 ///
@@ -304,9 +382,95 @@ pub fn main() {
 /// }
 /// ```
 ///
+/// To run a single scenario, filters also match generic benchmarks by their
+/// path written like Rust generics, such as `'from_range<Vec<i32>, 16>'`. The
+/// `--const 16` CLI argument runs only the benchmarks instantiated with that
+/// `const` value.
+///
 /// [`BTreeSet`]: std::collections::BTreeSet
 /// [`HashSet`]: std::collections::HashSet
 ///
+/// ## `args`
+/// [`args`]: #args
+///
+/// Unlike [`consts`], the [`args`] option takes values that are evaluated at
+/// runtime, such as sizes computed from the environment or data loaded from
+/// files. The benchmark is run once for each item of any [`IntoIterator`],
+/// and each run is named by the item's [`ToString`] output.
+///
+/// The following example benchmarks summing ranges of different lengths:
+///
+/// ```
+/// #[divan::bench(args = [1, 10, 100, 1000])]
+/// fn sum(n: u64) -> u64 {
+///     (0..n).sum()
+/// }
+/// ```
+///
+/// Arguments that implement [`Copy`] can be taken by-value, and any argument
+/// can be taken by-reference. [`String`] arguments can also be taken as
+/// [`&str`](prim@str). A [`Bencher`] can be taken as the first of two
+/// parameters, followed by the argument:
+///
+/// ```
+/// use divan::Bencher;
+///
+/// fn inputs() -> Vec<String> {
+///     // ...
+///     # vec![]
+/// }
+///
+/// #[divan::bench(args = inputs())]
+/// fn parse(bencher: Bencher, input: &str) {
+///     bencher.bench(|| input.parse::<i32>());
+/// }
+/// ```
+///
+/// Arguments are evaluated once before running benchmarks, and are kept in
+/// the order they were provided.
+///
+/// When combined with [`types`] and [`consts`], the benchmark is run for every
+/// combination of type, constant, and argument. Arguments are nested under each
+/// constant, which is nested under each type:
+///
+/// ```
+/// #[divan::bench(
+///     types = [Vec<u8>, String],
+///     consts = [16, 64],
+///     args = [1, 2],
+/// )]
+/// fn fill<T: Default, const N: usize>(repeat: usize) -> T {
+///     // ...
+///     # T::default()
+/// }
+/// ```
+///
+/// The [`args`] option cannot yet be combined with [`setup`] or [`teardown`].
+///
+/// ## `args_from_dir`
+/// [`args_from_dir`]: #args_from_dir
+///
+/// Benchmarks can be run over each file in a directory, such as a corpus of
+/// inputs for a parser, via the [`args_from_dir`] option. This behaves like
+/// [`args`] with a [`FixtureFile`] for each file, and each benchmark is named
+/// by its file's name. Relative paths are resolved from the directory
+/// containing the package's `Cargo.toml`.
+///
+/// Files are read before benchmarking, so the benchmark function can take the
+/// file's contents as `&[u8]`, its path as [`&Path`](std::path::Path), or the
+/// [`FixtureFile`] itself:
+///
+/// ```
+/// #[divan::bench(args_from_dir = "benches/corpus")]
+/// fn parse(contents: &[u8]) -> usize {
+///     // ...
+///     # contents.len()
+/// }
+/// ```
+///
+/// Subdirectories are skipped, and files are benchmarked in order of their
+/// names.
+///
 /// ## `sample_count`
 /// [`sample_count`]: #sample_count
 ///
@@ -344,6 +508,48 @@ pub fn main() {
 /// }
 /// ```
 ///
+/// ## `sample_time`
+/// [`sample_time`]: #sample_time
+///
+/// Instead of choosing [`sample_size`] from timer precision, it can be chosen
+/// so that each sample takes roughly a given wall-clock time via the
+/// [`sample_time`] option. This includes time spent generating and dropping
+/// inputs. This may be overridden at runtime using either the
+/// `DIVAN_SAMPLE_TIME` environment variable or `--sample-time` CLI argument.
+///
+/// This makes total run times predictable: each benchmark takes about
+/// [`sample_count`] × [`sample_time`]. Longer samples also average out noise
+/// in busy environments. [`sample_size`] takes priority if also set.
+///
+/// ```
+/// #[divan::bench(sample_time = "1ms")]
+/// fn add() -> i32 {
+///     // ...
+///     # 0
+/// }
+/// ```
+///
+/// ## `until_stable`
+/// [`until_stable`]: #until_stable
+///
+/// Instead of recording a fixed number of samples, samples can be collected
+/// until their [coefficient of variation](https://en.wikipedia.org/wiki/Coefficient_of_variation)
+/// is at most an [`f64`] percentage via the [`until_stable`] option. This may
+/// be overridden at runtime using either the `DIVAN_UNTIL_STABLE` environment
+/// variable or `--until-stable` CLI argument.
+///
+/// Stable benchmarks then finish after few samples, while noisy benchmarks
+/// collect more. [`sample_count`] becomes the minimum number of samples, which
+/// is 10 by default, and [`max_time`] still bounds the time spent.
+///
+/// ```
+/// #[divan::bench(until_stable = 2.0)]
+/// fn add() -> i32 {
+///     // ...
+///     # 0
+/// }
+/// ```
+///
 /// ## `threads`
 /// [`threads`]: #threads
 ///
@@ -393,6 +599,15 @@ pub fn main() {
 /// }
 /// ```
 ///
+/// Threads are synchronized by a barrier to start each sample at the same
+/// time. In addition to time statistics of all threads' samples, the output
+/// shows the mean "wall" time per iteration for all threads to finish a sample,
+/// which reflects aggregate throughput under contention. Time statistics of
+/// each thread are available through [`Stats::threads`](crate::results::Stats::threads).
+///
+/// Thread counts may be overridden at runtime using either the `DIVAN_THREADS`
+/// environment variable or `--threads` CLI argument, such as `--threads 1,4`.
+///
 /// ## `counters`
 /// [`counters`]: #counters
 ///
@@ -434,11 +649,62 @@ pub fn main() {
 /// }
 /// ```
 ///
+/// User-defined units can be counted with
+/// [`CustomCount`](crate::counter::CustomCount):
+///
+/// ```
+/// use divan::counter::CustomCount;
+///
+/// #[divan::bench(counter = CustomCount::new("packet", 16u32))]
+/// fn route_packets() {
+///     // ...
+/// }
+/// ```
+///
+/// When [`consts`] are input sizes, `of_const` counters such as
+/// [`BytesCount::of_const`](counter::BytesCount::of_const) count the `const`
+/// value of each benchmark instance:
+///
+/// ```
+/// use divan::counter::BytesCount;
+///
+/// #[divan::bench(
+///     consts = [64, 1024],
+///     counter = BytesCount::of_const(),
+/// )]
+/// fn zero_fill<const N: usize>() -> [u8; N] {
+///     divan::black_box([0; N])
+/// }
+/// ```
+///
 /// See:
 /// - [`#[divan::bench_group(counters = ...)]`](macro@bench_group#counters)
 /// - [`Bencher::counter`]
 /// - [`Bencher::input_counter`]
 ///
+/// ## `bytes_format`
+/// [`bytes_format`]: #bytes_format
+///
+/// By default, [`BytesCount`](counter::BytesCount) throughput and allocated
+/// bytes are shown in powers of 1000 (KB, MB, GB). Setting [`bytes_format`]
+/// to [`BytesFormat::Binary`](counter::BytesFormat::Binary) shows them in
+/// powers of 1024 (KiB, MiB, GiB) instead, which is often expected for storage:
+///
+/// ```
+/// use divan::counter::{BytesCount, BytesFormat};
+///
+/// #[divan::bench(
+///     counter = BytesCount::new(4096usize),
+///     bytes_format = BytesFormat::Binary,
+/// )]
+/// fn read_page() {
+///     // ...
+/// }
+/// ```
+///
+/// This may be overridden at runtime using either the `DIVAN_BYTES_FORMAT`
+/// environment variable or `--bytes-format` CLI argument.
+///
 /// ## `min_time`
 /// [`min_time`]: #min_time
 ///
@@ -520,6 +786,107 @@ pub fn main() {
 /// }
 /// ```
 ///
+/// ## `max_time_mean`
+/// [`max_time_mean`]: #max_time_mean
+///
+/// Fails the run if the mean time per iteration exceeds a [`Duration`]. The
+/// benchmark's row is marked as failed, a summary after the results table
+/// lists every benchmark that exceeded its threshold, and the process exits
+/// with a nonzero status. This is useful for enforcing performance budgets and
+/// catching performance regressions in CI. This may be overridden at runtime
+/// using either the `DIVAN_MAX_TIME_MEAN` environment variable or
+/// `--max-time-mean` CLI argument.
+///
+/// Like [`max_time`], this can be set with a [`Duration`] or seconds as [`u64`]
+/// or [`f64`]. It can also be set with a string of a number followed by `ns`,
+/// `µs` (or `us`), `ms`, or `s`. Invalid values will cause a panic at runtime.
+///
+/// ```
+/// #[divan::bench(max_time_mean = "100µs")]
+/// fn add() -> i32 {
+///     // ...
+///     # 0
+/// }
+/// ```
+///
+/// This option can also be set as `assert_max`:
+///
+/// ```
+/// #[divan::bench(assert_max = "250ns")]
+/// fn add() -> i32 {
+///     // ...
+///     # 0
+/// }
+/// ```
+///
+/// To instead fail when results regress relative to a saved baseline, use the
+/// `--fail-on-regression <PERCENT>` CLI argument with `--baseline`.
+///
+/// ## `timeout`
+/// [`timeout`]: #timeout
+///
+/// Stops a benchmark once it has run for longer than a [`Duration`] of
+/// wall-clock time, and reports it as timed out. Like a panic, this makes the
+/// run fail after the remaining benchmarks finish. This may be overridden at
+/// runtime using either the `DIVAN_TIMEOUT` environment variable or
+/// `--timeout` CLI argument.
+///
+/// The timeout is checked between samples, including while tuning the sample
+/// size. Once a sample's iteration time is known, the benchmark also stops
+/// before a sample that would end past the timeout. However, a single
+/// iteration that runs for a long time cannot be interrupted. Like [`max_time_mean`], this can be set
+/// with a [`Duration`], seconds as [`u64`] or [`f64`], or a string like
+/// `"10s"`.
+///
+/// ```
+/// #[divan::bench(timeout = "10s")]
+/// fn add() -> i32 {
+///     // ...
+///     # 0
+/// }
+/// ```
+///
+/// ## `warmup_time`
+/// [`warmup_time`]: #warmup_time
+///
+/// By default, the first samples that tune the sample size also serve as a
+/// warm-up, which can bias tuning towards cold caches and branch predictors.
+/// The [`warmup_time`] option instead runs the benchmarked function for a
+/// [`Duration`] before any samples are recorded. This may be overridden at
+/// runtime using either the `DIVAN_WARMUP_TIME` environment variable or
+/// `--warmup-time` CLI argument.
+///
+/// Warm-up time is not counted towards [`min_time`] or [`max_time`]. Like
+/// [`max_time_mean`], this can be set with a [`Duration`], seconds as [`u64`]
+/// or [`f64`], or a string like `"100ms"`.
+///
+/// ```
+/// #[divan::bench(warmup_time = "500ms")]
+/// fn add() -> i32 {
+///     // ...
+///     # 0
+/// }
+/// ```
+///
+/// ## `warmup_samples`
+/// [`warmup_samples`]: #warmup_samples
+///
+/// The number of samples to run and discard before recording samples can be
+/// set via the [`warmup_samples`] option. This may be overridden at runtime
+/// using either the `DIVAN_WARMUP_SAMPLES` environment variable or
+/// `--warmup-samples` CLI argument.
+///
+/// Warm-up samples use the same size as the first recorded sample. If
+/// [`warmup_time`] is also set, warm-up continues until both are satisfied.
+///
+/// ```
+/// #[divan::bench(warmup_samples = 10)]
+/// fn add() -> i32 {
+///     // ...
+///     # 0
+/// }
+/// ```
+///
 /// ## `skip_ext_time`
 /// [`skip_ext_time`]: #skip_ext_time
 ///
@@ -554,6 +921,181 @@ pub fn main() {
 /// }
 /// ```
 ///
+/// ## `streaming_stats`
+/// [`streaming_stats`]: #streaming_stats
+///
+/// By default, every sample is stored until statistics are computed, so memory
+/// grows with [`sample_count`] or long [`min_time`] runs. Enabling the
+/// [`streaming_stats`] option will instead compute statistics incrementally in
+/// constant memory. This may be overridden at runtime using either the
+/// `DIVAN_STREAMING_STATS` environment variable or `--streaming-stats` CLI
+/// argument.
+///
+/// The median and percentiles are then computed from a [`reservoir_size`]
+/// subset of samples, and the median counter values are the means. Only those
+/// samples are available to reporters. Median [`threads`] times are estimated
+/// per thread with the [P² algorithm](https://www.cse.wustl.edu/~jain/papers/ftp/psqr.pdf).
+///
+/// If unset, this is enabled automatically once a benchmark records a million
+/// samples. Setting it to `false` always stores every sample.
+///
+/// ```
+/// #[divan::bench(min_time = 3600, streaming_stats)]
+/// fn soak() -> i32 {
+///     // ...
+///     # 0
+/// }
+/// ```
+///
+/// ## `reservoir_size`
+/// [`reservoir_size`]: #reservoir_size
+///
+/// When using [`streaming_stats`], a uniformly random subset of samples is kept
+/// via [reservoir sampling](https://en.wikipedia.org/wiki/Reservoir_sampling).
+/// This preserves the shape of the sample distribution for percentiles and
+/// per-sample output like `--dump-samples` in bounded memory. The
+/// [`reservoir_size`] option sets how many samples are kept, which is 10,000
+/// by default. This may be overridden at runtime using either the
+/// `DIVAN_RESERVOIR_SIZE` environment variable or `--reservoir-size` CLI
+/// argument.
+///
+/// If set to 0, no samples are kept and percentiles are instead estimated with
+/// the [P² algorithm](https://www.cse.wustl.edu/~jain/papers/ftp/psqr.pdf).
+///
+/// ```
+/// #[divan::bench(min_time = 3600, streaming_stats, reservoir_size = 1000)]
+/// fn soak() -> i32 {
+///     // ...
+///     # 0
+/// }
+/// ```
+///
+/// ## `flush_cache`
+/// [`flush_cache`]: #flush_cache
+///
+/// By default, data used by a benchmark stays in CPU caches between samples,
+/// so traversals of data structures measure warm-cache behavior. Enabling the
+/// [`flush_cache`] option evicts CPU caches before each sample by reading a
+/// buffer larger than them, outside of the timed section. This may be
+/// overridden at runtime using either the `DIVAN_FLUSH_CACHE` environment
+/// variable or `--flush-cache` CLI argument.
+///
+/// Only the first iteration of each sample runs with cold caches, so this is
+/// usually combined with a [`sample_size`] of 1 and inputs from
+/// [`Bencher::with_inputs`].
+///
+/// ```
+/// #[divan::bench(flush_cache, sample_size = 1)]
+/// fn traverse(bencher: divan::Bencher) {
+///     let list: std::collections::LinkedList<i32> = (0..1000).collect();
+///
+///     bencher.bench_local(|| {
+///         divan::black_box(&list).iter().sum::<i32>()
+///     });
+/// }
+/// ```
+///
+/// ## `unroll`
+/// [`unroll`]: #unroll
+///
+/// Each iteration of a sample loop increments a counter and branches, which
+/// takes about as long as functions that run in under a nanosecond. The
+/// [`unroll`] option calls the benchmarked function this many times per loop
+/// iteration, so that this overhead is shared by every call. This may be
+/// overridden at runtime using either the `DIVAN_UNROLL` environment variable
+/// or `--unroll` CLI argument.
+///
+/// The number is rounded down to a power of two no greater than 16. Per-call
+/// timings remain accurate for any [`sample_size`], since leftover calls run
+/// without unrolling.
+///
+/// ```
+/// #[divan::bench(unroll = 8)]
+/// fn add() -> i32 {
+///     divan::black_box(1) + divan::black_box(2)
+/// }
+/// ```
+///
+/// ## `setup`
+/// [`setup`]: #setup
+///
+/// Expensive one-time preparation can be done by the [`setup`] function, which
+/// runs once before any samples are recorded. Its time is never measured.
+///
+/// The benchmark function can take a reference to the value returned by
+/// [`setup`]. A [`Bencher`] can be taken as the first of two parameters,
+/// followed by the value:
+///
+/// ```
+/// fn load_words() -> Vec<String> {
+///     // ...
+///     # vec![]
+/// }
+///
+/// #[divan::bench(setup = load_words)]
+/// fn sort(bencher: divan::Bencher, words: &[String]) {
+///     bencher
+///         .with_inputs(|| words.to_vec())
+///         .bench_local_values(|mut words| words.sort());
+/// }
+///
+/// #[divan::bench(setup = load_words)]
+/// fn total_len(words: &[String]) -> usize {
+///     words.iter().map(String::len).sum()
+/// }
+/// ```
+///
+/// [`setup`] runs before the benchmark's first sample, and its value is reused
+/// by every [thread count](#threads) and `--runs` repetition. Generic
+/// benchmarks run it once for each of their types and constants. The value must
+/// be [`Send`] and [`Sync`].
+///
+/// ## `teardown`
+/// [`teardown`]: #teardown
+///
+/// The [`teardown`] function runs once after all samples are recorded, if the
+/// benchmark was run. It can either take no arguments or take ownership of the
+/// value returned by [`setup`]:
+///
+/// ```
+/// # use std::path::{Path, PathBuf};
+/// fn create_dir() -> PathBuf {
+///     // ...
+///     # PathBuf::new()
+/// }
+///
+/// fn remove_dir(dir: PathBuf) {
+///     // ...
+///     # _ = dir;
+/// }
+///
+/// #[divan::bench(setup = create_dir, teardown = remove_dir)]
+/// fn list_dir(dir: &Path) {
+///     // ...
+///     # _ = dir;
+/// }
+/// ```
+///
+/// ## `tags`
+/// [`tags`]: #tags
+///
+/// Labels the benchmark for selection with the `--tag` CLI argument, for
+/// choosing benchmarks that cut across paths:
+///
+/// ```
+/// #[divan::bench(tags = ["io", "simd"])]
+/// fn read_checksum() {
+///     // ...
+/// }
+/// ```
+///
+/// Running with `--tag io` only runs benchmarks tagged "io", in addition to
+/// any name filters. Tags are shown by `--list`.
+///
+/// See also:
+/// - [`#[divan::bench_group(tags = ...)]`](macro@bench_group#tags)
+/// - [`Divan::tag`]
+///
 /// ## `ignore`
 /// [`ignore`]: #ignore
 ///
@@ -602,6 +1144,20 @@ pub fn main() {
 /// }
 /// ```
 ///
+/// A reason can be given as a string, either within the attribute or with
+/// `#[ignore = "reason"]`. It is shown in place of results when the benchmark is
+/// skipped:
+///
+/// ```
+/// #[divan::bench(ignore = "too slow for CI")]
+/// fn slow_bench() {
+///     // ...
+/// }
+/// ```
+///
+/// Ignored benchmarks can be run with the `--include-ignored` CLI argument, or
+/// exclusively with `--ignored`.
+///
 /// [`Duration`]: std::time::Duration
 /// [available parallelism]: std::thread::available_parallelism
 pub use divan_macros::bench;
@@ -690,11 +1246,24 @@ pub use divan_macros::bench;
 /// - [`crate`]
 /// - [`sample_count`]
 /// - [`sample_size`]
+/// - [`sample_time`]
+/// - [`until_stable`]
 /// - [`threads`]
 /// - [`counters`]
+/// - [`bytes_format`]
 /// - [`min_time`]
 /// - [`max_time`]
+/// - [`max_time_mean`] (or `assert_max`)
+/// - [`timeout`]
+/// - [`warmup_time`]
+/// - [`warmup_samples`]
+/// - [`time_budget`]
 /// - [`skip_ext_time`]
+/// - [`streaming_stats`]
+/// - [`reservoir_size`]
+/// - [`flush_cache`]
+/// - [`unroll`]
+/// - [`tags`]
 /// - [`ignore`]
 ///
 /// ## `name`
@@ -777,6 +1346,34 @@ pub use divan_macros::bench;
 /// }
 /// ```
 ///
+/// ## `sample_time`
+/// [`sample_time`]: #sample_time
+///
+/// Sizes samples of each benchmark in the group to take roughly a given
+/// wall-clock time. See [`#[divan::bench]`](macro@bench#sample_time) for
+/// details.
+///
+/// ```
+/// #[divan::bench_group(sample_time = "1ms")]
+/// mod math {
+///     // ...
+/// }
+/// ```
+///
+/// ## `until_stable`
+/// [`until_stable`]: #until_stable
+///
+/// Collects samples for each benchmark in the group until their coefficient of
+/// variation is at most an [`f64`] percentage. See
+/// [`#[divan::bench]`](macro@bench#until_stable) for details.
+///
+/// ```
+/// #[divan::bench_group(until_stable = 2.0)]
+/// mod math {
+///     // ...
+/// }
+/// ```
+///
 /// ## `threads`
 /// [`threads`]: #threads
 ///
@@ -854,6 +1451,19 @@ pub use divan_macros::bench;
 /// - [`Bencher::counter`]
 /// - [`Bencher::input_counter`]
 ///
+/// ## `bytes_format`
+/// [`bytes_format`]: #bytes_format
+///
+/// Sets the numerical base for bytes in outputs of each benchmark in the group.
+/// See [`#[divan::bench]`](macro@bench#bytes_format) for details.
+///
+/// ```
+/// #[divan::bench_group(bytes_format = divan::counter::BytesFormat::Binary)]
+/// mod storage {
+///     // ...
+/// }
+/// ```
+///
 /// ## `min_time`
 /// [`min_time`]: #min_time
 ///
@@ -935,6 +1545,92 @@ pub use divan_macros::bench;
 /// }
 /// ```
 ///
+/// ## `max_time_mean`
+/// [`max_time_mean`]: #max_time_mean
+///
+/// Fails the run if the mean time per iteration of any benchmark in the group
+/// exceeds a [`Duration`]. This option can also be set as `assert_max`. See
+/// [`#[divan::bench]`](macro@bench#max_time_mean) for details.
+///
+/// ```
+/// #[divan::bench_group(max_time_mean = "1ms")]
+/// mod group {
+///     // ...
+/// }
+/// ```
+///
+/// ## `timeout`
+/// [`timeout`]: #timeout
+///
+/// Stops any benchmark in the group that runs for longer than a [`Duration`] of
+/// wall-clock time. See [`#[divan::bench]`](macro@bench#timeout) for details.
+///
+/// ```
+/// #[divan::bench_group(timeout = "10s")]
+/// mod group {
+///     // ...
+/// }
+/// ```
+///
+/// ## `warmup_time`
+/// [`warmup_time`]: #warmup_time
+///
+/// Runs each benchmark in the group for a [`Duration`] before recording
+/// samples. See [`#[divan::bench]`](macro@bench#warmup_time) for details.
+///
+/// ```
+/// #[divan::bench_group(warmup_time = 1)]
+/// mod group {
+///     // ...
+/// }
+/// ```
+///
+/// ## `warmup_samples`
+/// [`warmup_samples`]: #warmup_samples
+///
+/// Runs and discards a number of samples of each benchmark in the group before
+/// recording samples. See [`#[divan::bench]`](macro@bench#warmup_samples) for
+/// details.
+///
+/// ```
+/// #[divan::bench_group(warmup_samples = 10)]
+/// mod group {
+///     // ...
+/// }
+/// ```
+///
+/// ## `time_budget`
+/// [`time_budget`]: #time_budget
+///
+/// By default, [`min_time`] and [`max_time`] on a group apply to each of its
+/// benchmarks individually. Enabling the [`time_budget`] option will instead
+/// make the group's own [`min_time`] and [`max_time`] apply to the group as a
+/// whole, divided evenly among its benchmarks. Each thread count of a
+/// [multi-threaded](macro@bench#threads) benchmark counts as a separate
+/// benchmark.
+///
+/// The following example spends at most 30 seconds total on all
+/// serialization benchmarks:
+///
+/// ```
+/// #[divan::bench_group(max_time = 30, time_budget)]
+/// mod serialization {
+///     #[divan::bench]
+///     fn json() {
+///         // ...
+///     }
+///
+///     #[divan::bench]
+///     fn cbor() {
+///         // ...
+///     }
+/// }
+/// ```
+///
+/// Benchmarks and child groups that set their own [`min_time`] or
+/// [`max_time`] are not limited by the budget, and neither are runs where
+/// `--min-time` or `--max-time` is passed on the command line.
+///
 /// ## `skip_ext_time`
 /// [`skip_ext_time`]: #skip_ext_time
 ///
@@ -972,6 +1668,75 @@ pub use divan_macros::bench;
 /// }
 /// ```
 ///
+/// ## `streaming_stats`
+/// [`streaming_stats`]: #streaming_stats
+///
+/// Computes statistics of each benchmark in the group incrementally instead of
+/// storing every sample. See
+/// [`#[divan::bench]`](macro@bench#streaming_stats) for details.
+///
+/// ```
+/// #[divan::bench_group(streaming_stats)]
+/// mod group {
+///     // ...
+/// }
+/// ```
+///
+/// ## `reservoir_size`
+/// [`reservoir_size`]: #reservoir_size
+///
+/// Sets the number of samples kept by [`streaming_stats`] for each benchmark in
+/// the group. See [`#[divan::bench]`](macro@bench#reservoir_size) for details.
+///
+/// ```
+/// #[divan::bench_group(streaming_stats, reservoir_size = 1000)]
+/// mod group {
+///     // ...
+/// }
+/// ```
+///
+/// ## `flush_cache`
+/// [`flush_cache`]: #flush_cache
+///
+/// Flushes CPU caches before each sample of each benchmark in the group. See
+/// [`#[divan::bench]`](macro@bench#flush_cache) for details.
+///
+/// ```
+/// #[divan::bench_group(flush_cache)]
+/// mod group {
+///     // ...
+/// }
+/// ```
+///
+/// ## `unroll`
+/// [`unroll`]: #unroll
+///
+/// Unrolls the sample loop of each benchmark in the group. See
+/// [`#[divan::bench]`](macro@bench#unroll) for details.
+///
+/// ```
+/// #[divan::bench_group(unroll = 8)]
+/// mod group {
+///     // ...
+/// }
+/// ```
+///
+/// ## `tags`
+/// [`tags`]: #tags
+///
+/// Labels all benchmarks in the group for selection with the `--tag` CLI
+/// argument, in addition to their own [`tags`](macro@bench#tags):
+///
+/// ```
+/// #[divan::bench_group(tags = ["io"])]
+/// mod files {
+///     #[divan::bench(tags = ["simd"])]
+///     fn read_checksum() {
+///         // Selected by both `--tag io` and `--tag simd`.
+///     }
+/// }
+/// ```
+///
 /// ## `ignore`
 /// [`ignore`]: #ignore
 ///
@@ -1028,5 +1793,19 @@ pub use divan_macros::bench;
 /// }
 /// ```
 ///
+/// A reason can be given as a string, either within the attribute or with
+/// `#[ignore = "reason"]`. It is shown in place of results when the benchmark group is
+/// skipped:
+///
+/// ```
+/// #[divan::bench_group(ignore = "too slow for CI")]
+/// mod slow_benches {
+///     // ...
+/// }
+/// ```
+///
+/// Ignored benchmark groups can be run with the `--include-ignored` CLI argument, or
+/// exclusively with `--ignored`.
+///
 /// [`Duration`]: std::time::Duration
 pub use divan_macros::bench_group;
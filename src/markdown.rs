@@ -0,0 +1,242 @@
+//! Markdown export of benchmark results.
+//!
+//! Results are rendered as a GitHub-flavored Markdown table in the same
+//! structure as the tree output, with names indented by depth. Throughput
+//! columns are only present if any benchmark uses that counter, and show
+//! median throughput.
+
+use std::{fmt::Write as _, fs, io, path::Path};
+
+use crate::{
+    counter::{AnyCounter, BytesFormat, CounterFormat, KnownCounterKind},
+    results::{BenchResults, EntryResult, ResultTree},
+};
+
+/// Writes results to a Markdown file at `path`.
+pub(crate) fn write(
+    path: &Path,
+    results: &BenchResults,
+    bytes_format: BytesFormat,
+    counter_format: CounterFormat,
+) -> io::Result<()> {
+    fs::write(path, render(results, bytes_format, counter_format))
+}
+
+/// Renders results as a Markdown table.
+pub(crate) fn render(
+    results: &BenchResults,
+    bytes_format: BytesFormat,
+    counter_format: CounterFormat,
+) -> String {
+    let entries = ResultTree::entries(&results.tree);
+
+    let known_counters: Vec<KnownCounterKind> = KnownCounterKind::ALL
+        .into_iter()
+        .filter(|&kind| entries.iter().any(|entry| entry.stats.get_counts(kind).is_some()))
+        .collect();
+
+    let mut custom_units: Vec<&'static str> = Vec::new();
+    for entry in &entries {
+        for &(unit, _) in &entry.stats.custom_counts {
+            if !custom_units.contains(&unit) {
+                custom_units.push(unit);
+            }
+        }
+    }
+
+    let table = Table { known_counters, custom_units, bytes_format, counter_format };
+
+    let mut md = String::new();
+
+    // Header:
+    let mut header: Vec<String> =
+        ["Benchmark", "Fastest", "Slowest", "Median", "Mean"].map(str::to_owned).into();
+
+    let counter_names = table
+        .known_counters
+        .iter()
+        .map(|kind| kind.name())
+        .chain(table.custom_units.iter().map(|unit| &**unit));
+    header.extend(counter_names.map(|name| match counter_format {
+        CounterFormat::PerSec => format!("{name}/s"),
+        CounterFormat::PerIter => format!("time/{name}"),
+    }));
+
+    header.extend(["Samples", "Iters"].map(str::to_owned));
+
+    write_row(&mut md, header.iter().map(String::as_str));
+
+    let alignments = (0..header.len()).map(|i| if i == 0 { ":--" } else { "--:" });
+    write_row(&mut md, alignments);
+
+    // Rows:
+    table.write_rows(&mut md, &results.tree, 0);
+
+    md
+}
+
+/// Columns that depend on the benchmarks being rendered.
+struct Table {
+    known_counters: Vec<KnownCounterKind>,
+    custom_units: Vec<&'static str>,
+    bytes_format: BytesFormat,
+    counter_format: CounterFormat,
+}
+
+impl Table {
+    fn column_count(&self) -> usize {
+        7 + self.known_counters.len() + self.custom_units.len()
+    }
+
+    fn write_rows(&self, md: &mut String, tree: &[ResultTree], depth: usize) {
+        for node in tree {
+            let name = indented_name(node.name(), depth);
+
+            match node {
+                ResultTree::Parent { children, .. } => {
+                    let empty = (1..self.column_count()).map(|_| "");
+                    write_row(md, [name.as_str()].into_iter().chain(empty));
+
+                    self.write_rows(md, children, depth + 1);
+                }
+                ResultTree::Leaf(entry) => {
+                    let row = self.leaf_row(entry);
+                    write_row(
+                        md,
+                        [name.as_str()].into_iter().chain(row.iter().map(String::as_str)),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Returns the cells after the name of a benchmark's row.
+    fn leaf_row(&self, entry: &EntryResult) -> Vec<String> {
+        let stats = &entry.stats;
+        let time = &stats.time;
+
+        let mut row: Vec<String> =
+            [time.fastest, time.slowest, time.median, time.mean].map(|t| t.to_string()).into();
+
+        let known_counters = self.known_counters.iter().map(|&kind| {
+            let counts = stats.get_counts(kind)?;
+            Some(AnyCounter::known(kind, counts.median))
+        });
+
+        let custom_counters = self.custom_units.iter().map(|&unit| {
+            let (_, counts) = stats.custom_counts.iter().find(|(u, _)| *u == unit)?;
+            Some(AnyCounter::custom(unit, counts.median))
+        });
+
+        row.extend(known_counters.chain(custom_counters).map(|counter| {
+            match counter {
+                Some(counter) => counter
+                    .display_throughput(time.median, self.bytes_format, self.counter_format)
+                    .to_string(),
+                None => String::new(),
+            }
+        }));
+
+        row.push(stats.sample_count.to_string());
+        row.push(stats.iter_count.to_string());
+        row
+    }
+}
+
+/// Formats a name as inline code, indented with non-breaking spaces since
+/// Markdown collapses leading whitespace.
+fn indented_name(name: &str, depth: usize) -> String {
+    let mut result = "&nbsp;&nbsp;".repeat(depth);
+
+    // Inline code prevents generic types like `Vec<T>` from being treated as
+    // HTML.
+    _ = write!(result, "`{name}`");
+    result
+}
+
+/// Writes a table row, escaping pipes within cells.
+pub(crate) fn write_row<'a>(md: &mut String, cells: impl IntoIterator<Item = &'a str>) {
+    md.push('|');
+    for cell in cells {
+        md.push(' ');
+        md.push_str(&cell.replace('|', "\\|"));
+        md.push_str(" |");
+    }
+    md.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        counter::{Count, MaxCountUInt},
+        stats::{Stats, StatsSet},
+        time::FineDuration,
+    };
+
+    fn entry(name: &str, picos: u128, bytes: Option<MaxCountUInt>) -> ResultTree {
+        let time = FineDuration { picos };
+        let counts = [
+            bytes.map(Count::Int).map(|bytes| StatsSet {
+                fastest: bytes,
+                slowest: bytes,
+                median: bytes,
+                mean: bytes,
+            }),
+            None,
+            None,
+        ];
+
+        ResultTree::Leaf(EntryResult {
+            name: name.to_owned(),
+            path: name.to_owned(),
+            generic_type: None,
+            generic_const: None,
+            stats: Stats {
+                counts,
+                ..Stats::test_default(
+                    10,
+                    100,
+                    StatsSet { fastest: time, slowest: time, median: time, mean: time },
+                )
+            },
+            sample_times: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn render_tree() {
+        let results = BenchResults {
+            tree: vec![ResultTree::Parent {
+                name: "parse".to_owned(),
+                children: vec![
+                    entry("small", 1_500, Some(1_000)),
+                    entry("Vec<u8>", 2_000_000, None),
+                ],
+            }],
+            failures: Vec::new(),
+            errors: Vec::new(),
+            throttled: Vec::new(),
+            noisy: Vec::new(),
+            bimodal: Vec::new(),
+        };
+
+        assert_eq!(
+            render(&results, BytesFormat::Decimal, CounterFormat::PerSec),
+            "\
+| Benchmark | Fastest | Slowest | Median | Mean | bytes/s | Samples | Iters |
+| :-- | --: | --: | --: | --: | --: | --: | --: |
+| `parse` |  |  |  |  |  |  |  |
+| &nbsp;&nbsp;`small` | 1.5 ns | 1.5 ns | 1.5 ns | 1.5 ns | 666.6 GB/s | 10 | 100 |
+| &nbsp;&nbsp;`Vec<u8>` | 2 µs | 2 µs | 2 µs | 2 µs |  | 10 | 100 |
+"
+        );
+    }
+
+    #[test]
+    fn write_row() {
+        let mut md = String::new();
+        super::write_row(&mut md, ["a|b", ""]);
+        assert_eq!(md, "| a\\|b |  |\n");
+    }
+}
@@ -0,0 +1,264 @@
+//! Machine-readable events emitted as benchmarks run.
+//!
+//! With `--message-format json-lines`, each event is written as a single line
+//! of JSON with an `"event"` field identifying its kind:
+//!
+//! - `"run_start"`: benchmarking is starting, with the Git commit, machine, and
+//!   `"env"` describing the CPU, OS, and toolchain.
+//! - `"bench_start"`: a benchmark is about to be measured.
+//! - `"sample"`: a sample was recorded for the benchmark, with the total time
+//!   of its iterations.
+//! - `"bench_complete"`: a benchmark finished, along with its statistics.
+//!   Multi-threaded benchmarks also include `"wall_time"` for all threads and
+//!   `"thread_time"` for each thread. With `--perf-events`, `"perf_events"` has
+//!   the mean count of each event per iteration. With `--hdr-histogram`,
+//!   `"histogram"` has the time per iteration of every sample as an
+//!   HdrHistogram in compressed V2 encoding and base64.
+//!
+//! With `--list`, no benchmarks are run and instead a `"listed"` event is
+//! written for each benchmark with its module path, source location, generic
+//! type and `const`, tags (including those of parent groups), whether it is
+//! ignored, and the options it would run with.
+//!
+//! Durations are in picoseconds. Sample events are written once a benchmark
+//! finishes sampling so that writing output does not perturb measurements.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    bench::BenchOptions,
+    counter::{BytesFormat, KnownCounterKind},
+    entry::AnyBenchEntry,
+    environment::Environment,
+    history::RunInfo,
+    json::Json,
+    reporter::{Reporter, Sample},
+    stats::{Stats, StatsSet, TIME_PERCENTILES},
+    time::FineDuration,
+};
+
+/// The format of messages written to stdout.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum MessageFormat {
+    /// Tree output for people.
+    #[default]
+    Human,
+
+    /// One JSON event per line.
+    JsonLines,
+}
+
+impl MessageFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Human => "human",
+            Self::JsonLines => "json-lines",
+        }
+    }
+}
+
+/// Writes events as JSON Lines.
+pub(crate) struct MessageWriter {
+    /// The output, which is `None` after a write fails.
+    out: Option<Box<dyn Write>>,
+
+    /// The index of the next sample of the current benchmark.
+    sample_index: usize,
+}
+
+impl MessageWriter {
+    pub fn stdout() -> Self {
+        Self::new(Box::new(io::stdout()))
+    }
+
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self::new(Box::new(BufWriter::new(File::create(path)?))))
+    }
+
+    fn new(out: Box<dyn Write>) -> Self {
+        Self { out: Some(out), sample_index: 0 }
+    }
+
+    /// Writes the context of the run before any benchmarks.
+    pub fn write_run_start(&mut self, run: &RunInfo) {
+        self.write(
+            Json::object()
+                .with("event", "run_start")
+                .with("commit", run.commit.as_deref())
+                .with("machine", run.machine.as_deref())
+                .with("arch", run.arch.as_deref())
+                .with("env", run.env.as_ref().map(Environment::to_json))
+                .with("seed", run.seed),
+        );
+    }
+
+    /// Writes a benchmark found by `--list`, along with the `options` it would
+    /// run with.
+    pub fn write_listed(
+        &mut self,
+        entry: AnyBenchEntry,
+        path: &str,
+        tags: &[&str],
+        options: &BenchOptions,
+    ) {
+        let meta = entry.meta();
+        let location = &meta.location;
+
+        self.write(
+            Json::object()
+                .with("event", "listed")
+                .with("path", path)
+                .with("name", entry.display_name())
+                .with("module_path", meta.module_path)
+                .with(
+                    "location",
+                    Json::object()
+                        .with("file", location.file)
+                        .with("line", location.line)
+                        .with("col", location.col),
+                )
+                .with("generic_type", entry.generic_type_name())
+                .with("generic_const", entry.generic_const_name())
+                .with("tags", tags.to_vec())
+                .with("ignored", options.ignore.unwrap_or_default())
+                .with("ignore_reason", options.ignore_reason)
+                .with("options", options_json(options)),
+        );
+    }
+
+    /// Writes an event and flushes so that readers receive it immediately.
+    ///
+    /// If writing fails, a warning is emitted and no further events are
+    /// written.
+    fn write(&mut self, event: Json) {
+        let Some(out) = &mut self.out else {
+            return;
+        };
+
+        if let Err(error) = writeln!(out, "{event}").and_then(|_| out.flush()) {
+            eprintln!("warning: Failed to write messages: {error}");
+            self.out = None;
+        }
+    }
+}
+
+impl Reporter for MessageWriter {
+    fn on_bench_start(&mut self, path: &str) {
+        self.sample_index = 0;
+        self.write(Json::object().with("event", "bench_start").with("path", path));
+    }
+
+    fn on_sample(&mut self, path: &str, sample: &Sample) {
+        let mut json = Json::object()
+            .with("event", "sample")
+            .with("path", path)
+            .with("index", self.sample_index)
+            .with("iters", sample.iter_count)
+            .with("time", sample.duration.picos);
+
+        if let Some(allocs) = &sample.alloc_counts {
+            json = json.with("allocs", allocs.alloc_count).with("alloc_bytes", allocs.alloc_bytes);
+        }
+
+        self.sample_index += 1;
+        self.write(json);
+    }
+
+    fn on_bench_complete(&mut self, path: &str, stats: &Stats) {
+        let mut time_json = stats_json(&stats.time);
+
+        for (percentile, duration) in TIME_PERCENTILES.iter().zip(&stats.time_percentiles) {
+            time_json = time_json.with(&format!("p{percentile}"), duration.picos);
+        }
+
+        let mut json = Json::object()
+            .with("event", "bench_complete")
+            .with("path", path)
+            .with("samples", stats.sample_count)
+            .with("iters", stats.iter_count)
+            .with("time", time_json);
+
+        if let Some(threads) = &stats.threads {
+            json = json
+                .with("wall_time", stats_json(&threads.wall_time))
+                .with("thread_time", threads.per_thread.iter().map(stats_json).collect::<Vec<_>>());
+        }
+
+        if let Some(gen_time) = stats.gen_time {
+            json = json.with("gen_time", gen_time.picos);
+        }
+
+        if let Some(drop_time) = stats.drop_time {
+            json = json.with("drop_time", drop_time.picos);
+        }
+
+        if !stats.perf_counts.is_empty() {
+            let mut events = Json::object();
+            for &(event, count) in &stats.perf_counts {
+                events = events.with(event, count);
+            }
+            json = json.with("perf_events", events);
+        }
+
+        if let Some(histogram) = &stats.time_histogram {
+            json = json.with("histogram", histogram.to_base64());
+        }
+
+        self.write(json);
+    }
+}
+
+/// Converts options set for a benchmark to an object, with `null` for options
+/// that use their defaults.
+fn options_json(options: &BenchOptions) -> Json {
+    let picos = |duration: Option<std::time::Duration>| {
+        duration.map(|duration| FineDuration::from(duration).picos)
+    };
+
+    let mut counters = Json::object();
+    for kind in KnownCounterKind::ALL {
+        if let Some(count) = options.counters.get(kind) {
+            counters = counters.with(kind.name(), count);
+        }
+    }
+    for &(unit, count) in options.counters.custom_counts() {
+        counters = counters.with(unit, count);
+    }
+
+    Json::object()
+        .with("sample_count", options.sample_count)
+        .with("sample_size", options.sample_size)
+        .with("sample_time", picos(options.sample_time))
+        .with("until_stable", options.until_stable)
+        .with("threads", options.threads.map(<[usize]>::to_vec))
+        .with("counters", counters)
+        .with(
+            "bytes_format",
+            options.bytes_format.map(|format| match format {
+                BytesFormat::Decimal => "decimal",
+                BytesFormat::Binary => "binary",
+            }),
+        )
+        .with("min_time", picos(options.min_time))
+        .with("max_time", picos(options.max_time))
+        .with("max_time_mean", picos(options.max_time_mean))
+        .with("timeout", picos(options.timeout))
+        .with("warmup_time", picos(options.warmup_time))
+        .with("warmup_samples", options.warmup_samples)
+        .with("skip_ext_time", options.skip_ext_time)
+        .with("flush_cache", options.flush_cache)
+        .with("unroll", options.unroll)
+}
+
+/// Converts time statistics to an object of picoseconds.
+pub(crate) fn stats_json(time: &StatsSet<FineDuration>) -> Json {
+    Json::object()
+        .with("fastest", time.fastest.picos)
+        .with("slowest", time.slowest.picos)
+        .with("median", time.median.picos)
+        .with("mean", time.mean.picos)
+}
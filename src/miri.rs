@@ -10,7 +10,7 @@ extern "Rust" {
 
 /// Make Miri quiet about leaking `val`.
 #[inline]
-pub fn leak<T: ?Sized>(val: &'static T) -> &T {
+pub fn leak<T: ?Sized>(val: &'static T) -> &'static T {
     #[cfg(miri)]
     unsafe {
         if std::mem::size_of_val(val) != 0 {
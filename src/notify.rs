@@ -0,0 +1,85 @@
+//! Webhook notification of finished runs.
+//!
+//! With `--notify-url <URL>`, a JSON document of the run's results is posted
+//! once benchmarks finish:
+//!
+//! ```json
+//! {"commit":"abc123","timestamp":1700000000,"machine":"ci-x86","arch":"x86_64","env":{},"seed":42,
+//!  "passed":true,
+//!  "benchmarks":[{"path":"math::add","samples":100,"iters":6400,"time":{"fastest":1000,"slowest":2000,"median":1200,"mean":1300}}],
+//!  "failures":[],"errors":[],"throttled":[]}
+//! ```
+//!
+//! Durations are in picoseconds. `"failures"`, `"errors"`, and `"throttled"`
+//! are messages describing benchmarks that exceeded thresholds, panicked or
+//! timed out, and were slowed by CPU throttling, respectively.
+//!
+//! Like other requests from [`crate::http`], this only supports plain
+//! `http://` URLs. HTTPS webhooks can be reached through a local relay.
+
+use std::io;
+
+use crate::{
+    environment::Environment, history::RunInfo, json::Json, message::stats_json,
+    results::BenchResults,
+};
+
+/// Posts the results document to `url`.
+pub(crate) fn send(url: &str, results: &BenchResults, run: &RunInfo) -> io::Result<()> {
+    let body = results_json(results, run).to_string();
+    crate::http::send("POST", url, "application/json", body.as_bytes())
+}
+
+/// Converts results of the run to a JSON document.
+pub(crate) fn results_json(results: &BenchResults, run: &RunInfo) -> Json {
+    let benchmarks: Vec<Json> = results
+        .entries()
+        .map(|entry| {
+            Json::object()
+                .with("path", entry.path.as_str())
+                .with("samples", entry.stats.sample_count)
+                .with("iters", entry.stats.iter_count)
+                .with("time", stats_json(&entry.stats.time))
+        })
+        .collect();
+
+    fn messages<T: ToString>(items: &[T]) -> Vec<String> {
+        items.iter().map(ToString::to_string).collect()
+    }
+
+    Json::object()
+        .with("commit", run.commit.as_deref())
+        .with("timestamp", run.timestamp)
+        .with("machine", run.machine.as_deref())
+        .with("arch", run.arch.as_deref())
+        .with("env", run.env.as_ref().map(Environment::to_json))
+        .with("seed", run.seed)
+        .with("passed", results.passed())
+        .with("benchmarks", benchmarks)
+        .with("failures", messages(&results.failures))
+        .with("errors", messages(&results.errors))
+        .with("throttled", messages(&results.throttled))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::{BenchError, BenchErrorKind};
+
+    #[test]
+    fn results_document() {
+        let results = BenchResults {
+            errors: vec![BenchError {
+                path: "a::b".to_owned(),
+                kind: BenchErrorKind::Panicked("oops".to_owned()),
+            }],
+            ..BenchResults::default()
+        };
+        let run = RunInfo { commit: Some("abc123".to_owned()), timestamp: 1, ..RunInfo::default() };
+
+        assert_eq!(
+            results_json(&results, &run).to_string(),
+            r#"{"commit":"abc123","timestamp":1,"machine":null,"arch":null,"env":null,"seed":null,"passed":false,"benchmarks":[],"failures":[],"errors":["a::b: panicked: oops"],"throttled":[]}"#
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! [OpenMetrics](https://openmetrics.io) export of benchmark results.
+//!
+//! Each statistic is a gauge family with a `benchmark` label holding the
+//! benchmark's path. Times are in seconds per iteration, and throughputs are
+//! counts per second at the median time, labeled by counter `unit`.
+//!
+//! The output is also valid Prometheus text format, so it can be pushed to a
+//! [Pushgateway](https://github.com/prometheus/pushgateway) for graphing in
+//! Grafana.
+
+use std::{fmt::Write as _, fs, io, path::Path};
+
+use crate::{
+    counter::{Count, KnownCounterKind},
+    results::{BenchResults, EntryResult},
+    time::FineDuration,
+};
+
+/// Gets a statistic of a benchmark.
+type Getter<T> = fn(&EntryResult) -> T;
+
+/// Writes results to an OpenMetrics file at `path`.
+pub(crate) fn write(path: &Path, results: &BenchResults) -> io::Result<()> {
+    fs::write(path, render(results))
+}
+
+/// Replaces the metrics of the Pushgateway job at `url` with results.
+pub(crate) fn push(url: &str, results: &BenchResults) -> io::Result<()> {
+    crate::http::send("PUT", url, "text/plain; version=0.0.4", render(results).as_bytes())
+}
+
+/// Renders results as OpenMetrics text.
+pub(crate) fn render(results: &BenchResults) -> String {
+    let entries: Vec<&EntryResult> = results.entries().collect();
+
+    let mut metrics = String::new();
+
+    let time_families: [(&str, &str, Getter<FineDuration>); 4] = [
+        ("mean", "Mean time per iteration.", |entry| entry.stats.time.mean),
+        ("median", "Median time per iteration.", |entry| entry.stats.time.median),
+        ("fastest", "Fastest time per iteration.", |entry| entry.stats.time.fastest),
+        ("slowest", "Slowest time per iteration.", |entry| entry.stats.time.slowest),
+    ];
+
+    for (stat, help, get_time) in time_families {
+        let family = format!("divan_time_{stat}_seconds");
+        write_family_header(&mut metrics, &family, Some("seconds"), help);
+
+        for &entry in &entries {
+            let seconds = get_time(entry).picos as f64 / 1e12;
+            write_sample(&mut metrics, &family, &[("benchmark", &entry.path)], seconds);
+        }
+    }
+
+    let has_counters = entries.iter().any(|entry| {
+        !entry.stats.custom_counts.is_empty()
+            || KnownCounterKind::ALL.into_iter().any(|kind| entry.stats.get_counts(kind).is_some())
+    });
+
+    if has_counters {
+        let family = "divan_throughput_per_second";
+        write_family_header(
+            &mut metrics,
+            family,
+            None,
+            "Counts processed per second at the median time.",
+        );
+
+        for &entry in &entries {
+            let stats = &entry.stats;
+            let median = stats.time.median;
+
+            let known = KnownCounterKind::ALL
+                .into_iter()
+                .filter_map(|kind| Some((kind.name(), stats.get_counts(kind)?.median)));
+            let custom = stats.custom_counts.iter().map(|&(unit, counts)| (unit, counts.median));
+
+            for (unit, count) in known.chain(custom) {
+                let Some(per_sec) = per_sec(count, median) else {
+                    continue;
+                };
+                let labels = [("benchmark", entry.path.as_str()), ("unit", unit)];
+                write_sample(&mut metrics, family, &labels, per_sec);
+            }
+        }
+    }
+
+    let families: [(&str, &str, Getter<f64>); 2] = [
+        ("divan_samples", "Number of samples recorded.", |entry| entry.stats.sample_count as f64),
+        ("divan_iterations", "Number of iterations measured.", |entry| {
+            entry.stats.iter_count as f64
+        }),
+    ];
+
+    for (family, help, get_value) in families {
+        write_family_header(&mut metrics, family, None, help);
+
+        for &entry in &entries {
+            write_sample(&mut metrics, family, &[("benchmark", &entry.path)], get_value(entry));
+        }
+    }
+
+    metrics.push_str("# EOF\n");
+    metrics
+}
+
+fn write_family_header(metrics: &mut String, family: &str, unit: Option<&str>, help: &str) {
+    _ = writeln!(metrics, "# TYPE {family} gauge");
+    if let Some(unit) = unit {
+        _ = writeln!(metrics, "# UNIT {family} {unit}");
+    }
+    _ = writeln!(metrics, "# HELP {family} {help}");
+}
+
+fn write_sample(metrics: &mut String, family: &str, labels: &[(&str, &str)], value: f64) {
+    metrics.push_str(family);
+
+    metrics.push('{');
+    for (i, (name, value)) in labels.iter().enumerate() {
+        if i > 0 {
+            metrics.push(',');
+        }
+        _ = write!(metrics, "{name}=\"{}\"", escape_label(value));
+    }
+    metrics.push('}');
+
+    _ = writeln!(metrics, " {value}");
+}
+
+/// Escapes a label value.
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Returns the number of counts processed per second.
+fn per_sec(count: Count, time: FineDuration) -> Option<f64> {
+    if time.picos == 0 {
+        return None;
+    }
+    Some(count.as_f64() * 1e12 / time.picos as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        counter::MaxCountUInt,
+        results::ResultTree,
+        stats::{Stats, StatsSet},
+    };
+
+    fn entry(path: &str, picos: u128, bytes: Option<MaxCountUInt>) -> ResultTree {
+        let time = FineDuration { picos };
+
+        let mut counts = [None; KnownCounterKind::COUNT];
+        counts[KnownCounterKind::Bytes as usize] = bytes.map(Count::Int).map(|bytes| StatsSet {
+            fastest: bytes,
+            slowest: bytes,
+            median: bytes,
+            mean: bytes,
+        });
+
+        ResultTree::Leaf(EntryResult {
+            name: path.rsplit("::").next().unwrap().to_owned(),
+            path: path.to_owned(),
+            generic_type: None,
+            generic_const: None,
+            stats: Stats {
+                counts,
+                ..Stats::test_default(
+                    10,
+                    100,
+                    StatsSet { fastest: time, slowest: time, median: time, mean: time },
+                )
+            },
+            sample_times: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn render_results() {
+        let results = BenchResults {
+            tree: vec![entry("parse::small", 2_000, Some(1_000)), entry("parse::\"q\"", 500, None)],
+            ..BenchResults::default()
+        };
+
+        assert_eq!(
+            render(&results),
+            r#"# TYPE divan_time_mean_seconds gauge
+# UNIT divan_time_mean_seconds seconds
+# HELP divan_time_mean_seconds Mean time per iteration.
+divan_time_mean_seconds{benchmark="parse::small"} 0.000000002
+divan_time_mean_seconds{benchmark="parse::\"q\""} 0.0000000005
+# TYPE divan_time_median_seconds gauge
+# UNIT divan_time_median_seconds seconds
+# HELP divan_time_median_seconds Median time per iteration.
+divan_time_median_seconds{benchmark="parse::small"} 0.000000002
+divan_time_median_seconds{benchmark="parse::\"q\""} 0.0000000005
+# TYPE divan_time_fastest_seconds gauge
+# UNIT divan_time_fastest_seconds seconds
+# HELP divan_time_fastest_seconds Fastest time per iteration.
+divan_time_fastest_seconds{benchmark="parse::small"} 0.000000002
+divan_time_fastest_seconds{benchmark="parse::\"q\""} 0.0000000005
+# TYPE divan_time_slowest_seconds gauge
+# UNIT divan_time_slowest_seconds seconds
+# HELP divan_time_slowest_seconds Slowest time per iteration.
+divan_time_slowest_seconds{benchmark="parse::small"} 0.000000002
+divan_time_slowest_seconds{benchmark="parse::\"q\""} 0.0000000005
+# TYPE divan_throughput_per_second gauge
+# HELP divan_throughput_per_second Counts processed per second at the median time.
+divan_throughput_per_second{benchmark="parse::small",unit="bytes"} 500000000000
+# TYPE divan_samples gauge
+# HELP divan_samples Number of samples recorded.
+divan_samples{benchmark="parse::small"} 10
+divan_samples{benchmark="parse::\"q\""} 10
+# TYPE divan_iterations gauge
+# HELP divan_iterations Number of iterations measured.
+divan_iterations{benchmark="parse::small"} 100
+divan_iterations{benchmark="parse::\"q\""} 100
+# EOF
+"#
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! [Parquet](https://parquet.apache.org) export of raw samples, via the
+//! `parquet` crate.
+//!
+//! With `--dump-samples-parquet <PATH>`, every recorded sample is kept and
+//! written as a row once benchmarks finish. The file has these columns:
+//!
+//! - `path`: the benchmark's path as a UTF-8 string.
+//! - `index`: the sample's index within its benchmark.
+//! - `sample_size`: the number of iterations in the sample.
+//! - `duration_picos`: the total duration of those iterations.
+//! - `bytes`, `chars`, `items`: total counts over those iterations, present
+//!   if any benchmark uses that counter, and null for benchmarks that do not.
+//!   These are 64-bit integers, or doubles if any total is fractional.
+//!
+//! Rows are split into row groups of at most [`ROW_GROUP_SIZE`] rows, and
+//! values are stored without compression.
+
+use std::{fs::File, path::Path, sync::Arc};
+
+use ::parquet::{
+    column::writer::ColumnWriter,
+    data_type::ByteArray,
+    errors::ParquetError,
+    file::{properties::WriterProperties, writer::SerializedFileWriter},
+    schema::types::Type,
+};
+
+use crate::{
+    counter::{CountTotal, KnownCounterKind},
+    reporter::{BenchResults, Reporter, Sample},
+};
+
+/// The maximum number of rows in each row group.
+const ROW_GROUP_SIZE: usize = 1 << 20;
+
+/// Collects samples to write as Parquet after benchmarks finish.
+pub(crate) struct SampleParquet {
+    path: Box<Path>,
+
+    /// The index of the next sample of the current benchmark.
+    sample_index: usize,
+
+    paths: Vec<ByteArray>,
+    indices: Vec<i64>,
+    sample_sizes: Vec<i64>,
+    durations: Vec<i64>,
+    counter_totals: [Vec<Option<CountTotal>>; KnownCounterKind::COUNT],
+}
+
+impl SampleParquet {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.into(),
+            sample_index: 0,
+            paths: Vec::new(),
+            indices: Vec::new(),
+            sample_sizes: Vec::new(),
+            durations: Vec::new(),
+            counter_totals: Default::default(),
+        }
+    }
+
+    /// Counters used by any benchmark, which get a column.
+    fn used_counters(&self) -> Vec<KnownCounterKind> {
+        KnownCounterKind::ALL
+            .into_iter()
+            .filter(|&kind| self.counter_totals[kind as usize].iter().any(Option::is_some))
+            .collect()
+    }
+
+    /// Returns `true` if `kind` has fractional totals, which get a `DOUBLE`
+    /// column instead of `INT64`.
+    fn is_fractional(&self, kind: KnownCounterKind) -> bool {
+        self.counter_totals[kind as usize]
+            .iter()
+            .any(|total| matches!(total, Some(CountTotal::Frac(_))))
+    }
+
+    fn schema(&self) -> Result<Type, ParquetError> {
+        use ::parquet::basic::{LogicalType, Repetition, Type as PhysicalType};
+
+        let column = |name: &'static str, ty, repetition| {
+            Type::primitive_type_builder(name, ty).with_repetition(repetition)
+        };
+
+        let mut fields = vec![
+            column("path", PhysicalType::BYTE_ARRAY, Repetition::REQUIRED)
+                .with_logical_type(Some(LogicalType::String))
+                .build()?,
+            column("index", PhysicalType::INT64, Repetition::REQUIRED).build()?,
+            column("sample_size", PhysicalType::INT64, Repetition::REQUIRED).build()?,
+            column("duration_picos", PhysicalType::INT64, Repetition::REQUIRED).build()?,
+        ];
+
+        for kind in self.used_counters() {
+            let ty =
+                if self.is_fractional(kind) { PhysicalType::DOUBLE } else { PhysicalType::INT64 };
+            fields.push(column(kind.name(), ty, Repetition::OPTIONAL).build()?);
+        }
+
+        Type::group_type_builder("samples")
+            .with_fields(fields.into_iter().map(Arc::new).collect())
+            .build()
+    }
+
+    /// Writes collected samples as a Parquet file.
+    fn write(&self) -> Result<(), ParquetError> {
+        let file = File::create(&self.path)?;
+        let properties = Arc::new(WriterProperties::builder().build());
+        let mut writer = SerializedFileWriter::new(file, Arc::new(self.schema()?), properties)?;
+
+        let used_counters = self.used_counters();
+
+        let mut start = 0;
+        while start < self.paths.len() {
+            let rows = start..self.paths.len().min(start + ROW_GROUP_SIZE);
+            start = rows.end;
+
+            let mut row_group = writer.next_row_group()?;
+            let mut index = 0;
+
+            while let Some(mut column) = row_group.next_column()? {
+                let rows = rows.clone();
+
+                match (index, column.untyped()) {
+                    (0, ColumnWriter::ByteArrayColumnWriter(writer)) => {
+                        writer.write_batch(&self.paths[rows], None, None)?;
+                    }
+
+                    (1..=3, ColumnWriter::Int64ColumnWriter(writer)) => {
+                        let values = [&self.indices, &self.sample_sizes, &self.durations];
+                        writer.write_batch(&values[index - 1][rows], None, None)?;
+                    }
+
+                    (_, ColumnWriter::Int64ColumnWriter(writer)) => {
+                        let totals = &self.counter_totals[used_counters[index - 4] as usize][rows];
+
+                        let values: Vec<i64> = (totals.iter().flatten())
+                            .map(|total| match *total {
+                                CountTotal::Int(total) => saturating_i64(total),
+                                CountTotal::Frac(_) => {
+                                    unreachable!("fractional totals are doubles")
+                                }
+                            })
+                            .collect();
+
+                        writer.write_batch(&values, Some(&definition_levels(totals)), None)?;
+                    }
+
+                    (_, ColumnWriter::DoubleColumnWriter(writer)) => {
+                        let totals = &self.counter_totals[used_counters[index - 4] as usize][rows];
+
+                        let values: Vec<f64> =
+                            totals.iter().flatten().map(|total| total.as_f64()).collect();
+
+                        writer.write_batch(&values, Some(&definition_levels(totals)), None)?;
+                    }
+
+                    _ => unreachable!("column {index} has an unexpected type"),
+                }
+
+                column.close()?;
+                index += 1;
+            }
+
+            row_group.close()?;
+        }
+
+        writer.close()?;
+        Ok(())
+    }
+}
+
+impl Reporter for SampleParquet {
+    fn on_bench_start(&mut self, _path: &str) {
+        self.sample_index = 0;
+    }
+
+    fn on_sample(&mut self, path: &str, sample: &Sample) {
+        // Consecutive samples share the same allocation for their path.
+        let path = match self.paths.last() {
+            Some(last) if last.data() == path.as_bytes() => last.clone(),
+            _ => ByteArray::from(path),
+        };
+
+        self.paths.push(path);
+        self.indices.push(self.sample_index as i64);
+        self.sample_sizes.push(sample.iter_count.into());
+        self.durations.push(saturating_i64(sample.duration.picos));
+
+        for kind in KnownCounterKind::ALL {
+            self.counter_totals[kind as usize].push(sample.counter_totals[kind as usize]);
+        }
+
+        self.sample_index += 1;
+    }
+
+    fn on_finish(&mut self, _results: &BenchResults) {
+        if let Err(error) = self.write() {
+            eprintln!("warning: Failed to write samples to '{}': {error}", self.path.display());
+        }
+    }
+}
+
+fn saturating_i64(n: u128) -> i64 {
+    n.try_into().unwrap_or(i64::MAX)
+}
+
+/// Returns 1 for present values and 0 for nulls of an optional column.
+fn definition_levels<T>(values: &[Option<T>]) -> Vec<i16> {
+    values.iter().map(|value| value.is_some().into()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ::parquet::{
+        file::reader::{FileReader, SerializedFileReader},
+        record::Field,
+    };
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("divan-samples-{}.parquet", std::process::id()));
+
+        let mut parquet = SampleParquet::new(&path);
+        parquet.paths = vec!["a".into(), "b::c".into()];
+        parquet.indices = vec![0, 0];
+        parquet.sample_sizes = vec![1, 2];
+        parquet.durations = vec![100, 200];
+        parquet.counter_totals[KnownCounterKind::Bytes as usize] =
+            vec![None, Some(CountTotal::Int(8))];
+        parquet.counter_totals[KnownCounterKind::Chars as usize] = vec![None, None];
+        parquet.counter_totals[KnownCounterKind::Items as usize] =
+            vec![Some(CountTotal::Int(1)), Some(CountTotal::Frac(2.5))];
+        parquet.write().unwrap();
+
+        let reader = SerializedFileReader::try_from(path.as_path()).unwrap();
+        let rows: Vec<Vec<(String, Field)>> =
+            reader.get_row_iter(None).unwrap().map(|row| row.unwrap().into_columns()).collect();
+        std::fs::remove_file(&path).unwrap();
+
+        let expected = |path: &str, size: i64, duration: i64, bytes: Field, items: f64| {
+            vec![
+                ("path".to_owned(), Field::Str(path.to_owned())),
+                ("index".to_owned(), Field::Long(0)),
+                ("sample_size".to_owned(), Field::Long(size)),
+                ("duration_picos".to_owned(), Field::Long(duration)),
+                ("bytes".to_owned(), bytes),
+                ("items".to_owned(), Field::Double(items)),
+            ]
+        };
+
+        assert_eq!(
+            rows,
+            [
+                expected("a", 1, 100, Field::Null, 1.0),
+                expected("b::c", 2, 200, Field::Long(8), 2.5),
+            ]
+        );
+    }
+}
@@ -0,0 +1,87 @@
+//! Raising the scheduling priority of the benchmarking process, so that it is
+//! preempted less often by other processes.
+
+use std::io;
+
+/// Raises the scheduling priority of the current process and thread.
+///
+/// Threads spawned afterwards inherit the raised priority on Unix.
+pub(crate) fn raise() -> io::Result<()> {
+    imp::raise()
+}
+
+/// Returns a hint for how to get permission to raise priority.
+pub(crate) fn permission_hint() -> &'static str {
+    if cfg!(target_os = "linux") {
+        "run as root, grant CAP_SYS_NICE, or raise the nice limit in /etc/security/limits.conf"
+    } else if cfg!(windows) {
+        "run as administrator"
+    } else {
+        "run as root"
+    }
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::{io, os::raw::c_int};
+
+    /// Sets priority for a process ID, where 0 means the calling process.
+    const PRIO_PROCESS: c_int = 0;
+
+    /// The highest priority "nice" value.
+    const NICE_MIN: c_int = -20;
+
+    extern "C" {
+        fn setpriority(which: c_int, who: u32, prio: c_int) -> c_int;
+    }
+
+    pub fn raise() -> io::Result<()> {
+        // SAFETY: `setpriority` has no memory safety requirements.
+        if unsafe { setpriority(PRIO_PROCESS, 0, NICE_MIN) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use std::{ffi::c_void, io, os::raw::c_int};
+
+    type Handle = *mut c_void;
+    type Bool = c_int;
+
+    const HIGH_PRIORITY_CLASS: u32 = 0x80;
+    const THREAD_PRIORITY_HIGHEST: c_int = 2;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCurrentProcess() -> Handle;
+        fn GetCurrentThread() -> Handle;
+        fn SetPriorityClass(process: Handle, priority_class: u32) -> Bool;
+        fn SetThreadPriority(thread: Handle, priority: c_int) -> Bool;
+    }
+
+    pub fn raise() -> io::Result<()> {
+        // SAFETY: Current process and thread pseudo-handles are always valid.
+        unsafe {
+            if SetPriorityClass(GetCurrentProcess(), HIGH_PRIORITY_CLASS) == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if SetThreadPriority(GetCurrentThread(), THREAD_PRIORITY_HIGHEST) == 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod imp {
+    use std::io;
+
+    pub fn raise() -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "unsupported platform"))
+    }
+}
@@ -8,9 +8,11 @@ use crate::miri;
 pub use crate::{
     bench::BenchOptions,
     entry::{
-        BenchEntry, EntryConst, EntryList, EntryLocation, EntryMeta, EntryType, GenericBenchEntry,
-        GroupEntry, BENCH_ENTRIES, GROUP_ENTRIES,
+        BenchArgs, BenchArgsRunner, BenchEntry, BenchEntryRunner, BenchSetup, BenchSetupRunner,
+        EntryConst, EntryList, EntryLocation, EntryMeta, EntryType, GenericBenchEntry, GroupEntry,
+        BENCH_ENTRIES, GROUP_ENTRIES,
     },
+    fixture::FixtureFile,
     time::IntoDuration,
 };
 
@@ -85,6 +87,73 @@ where
     }
 }
 
+/// Used by `#[divan::bench(teardown = ...)]` to call the teardown function
+/// with or without the value returned by `setup`.
+///
+/// Like [`IntoThreads`], this is generic over `IMP` to have separate
+/// implementations for functions with and without a parameter.
+pub trait Teardown<S, const IMP: u32> {
+    fn teardown(self, state: S);
+}
+
+impl<S, F: FnOnce()> Teardown<S, 0> for F {
+    #[inline]
+    fn teardown(self, _state: S) {
+        self()
+    }
+}
+
+impl<S, F: FnOnce(S)> Teardown<S, 1> for F {
+    #[inline]
+    fn teardown(self, state: S) {
+        self(state)
+    }
+}
+
+/// Used by `#[divan::bench(args = ...)]` to pass each argument by-reference or,
+/// if it is `Copy`, by-value.
+///
+/// Like [`IntoThreads`], the generic parameter lets the compiler infer which
+/// implementation to use from the benchmark function's parameter type.
+pub trait Arg<T> {
+    fn get(self) -> T;
+}
+
+impl<'a, T> Arg<&'a T> for &'a T {
+    #[inline]
+    fn get(self) -> &'a T {
+        self
+    }
+}
+
+impl<T: Copy> Arg<T> for &T {
+    #[inline]
+    fn get(self) -> T {
+        *self
+    }
+}
+
+impl<'a> Arg<&'a str> for &'a String {
+    #[inline]
+    fn get(self) -> &'a str {
+        self
+    }
+}
+
+impl<'a> Arg<&'a std::path::Path> for &'a FixtureFile {
+    #[inline]
+    fn get(self) -> &'a std::path::Path {
+        self.path()
+    }
+}
+
+impl<'a> Arg<&'a [u8]> for &'a FixtureFile {
+    #[inline]
+    fn get(self) -> &'a [u8] {
+        self.contents()
+    }
+}
+
 /// Used by `#[divan::bench(counters = [...])]`.
 #[inline]
 pub fn new_counter_set() -> crate::counter::CounterSet {
@@ -0,0 +1,168 @@
+//! Markers for attributing `perf record` samples to benchmarks.
+//!
+//! With `--profile <PATH>`, the time range of each benchmark's sample loop is
+//! written to `PATH` as a line of text:
+//!
+//! ```text
+//! 8273.012345,8273.512345 sort::u64 t=1
+//! ```
+//!
+//! Times are seconds of `CLOCK_MONOTONIC`, which `perf record -k mono` also
+//! uses for its samples. The range can therefore be passed as-is to
+//! `perf report --time` or `perf script --time` to show only that benchmark:
+//!
+//! ```sh
+//! perf record -k mono -g -- cargo bench -- --profile markers.txt
+//! perf report --time "$(grep 'sort::u64 ' markers.txt | cut -d' ' -f1)"
+//! ```
+//!
+//! Ranges of interleaved benchmarks overlap, and benchmarks run in separate
+//! processes are not marked.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{Mutex, PoisonError},
+};
+
+/// Writes the time range of each benchmark.
+pub(crate) struct ProfileMarkers {
+    /// The output, which is `None` after a write fails.
+    out: Mutex<Option<BufWriter<File>>>,
+}
+
+impl ProfileMarkers {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        if monotonic_nanos().is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "profile markers are only supported on Linux",
+            ));
+        }
+
+        Ok(Self { out: Mutex::new(Some(BufWriter::new(File::create(path)?))) })
+    }
+
+    /// Starts the range of the benchmark at `path` run by `thread_count`
+    /// threads, which is written when the returned guard is dropped.
+    pub fn start<'a>(&'a self, path: &'a str, thread_count: usize) -> ProfileRange<'a> {
+        ProfileRange { markers: self, path, thread_count, start: monotonic_nanos() }
+    }
+
+    /// Writes a line, emitting a warning and discarding further lines if it
+    /// fails.
+    fn write(&self, path: &str, thread_count: usize, start: u64, end: u64) {
+        let mut out = self.out.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(writer) = &mut *out else {
+            return;
+        };
+
+        let result =
+            writeln!(writer, "{},{} {path} t={thread_count}", format_secs(start), format_secs(end))
+                .and_then(|_| writer.flush());
+
+        if let Err(error) = result {
+            eprintln!("warning: Failed to write profile markers: {error}");
+            *out = None;
+        }
+    }
+}
+
+/// The time range of a benchmark, which is written when dropped.
+pub(crate) struct ProfileRange<'a> {
+    markers: &'a ProfileMarkers,
+    path: &'a str,
+    thread_count: usize,
+    start: Option<u64>,
+}
+
+impl Drop for ProfileRange<'_> {
+    fn drop(&mut self) {
+        if let (Some(start), Some(end)) = (self.start, monotonic_nanos()) {
+            self.markers.write(self.path, self.thread_count, start, end);
+        }
+    }
+}
+
+/// Formats nanoseconds as seconds with microsecond precision, which is the
+/// precision of `perf --time`.
+fn format_secs(nanos: u64) -> String {
+    format!("{}.{:06}", nanos / 1_000_000_000, nanos % 1_000_000_000 / 1_000)
+}
+
+/// Returns the current time of `CLOCK_MONOTONIC` in nanoseconds.
+#[cfg(all(any(target_os = "linux", target_os = "android"), not(miri)))]
+fn monotonic_nanos() -> Option<u64> {
+    use std::os::raw::{c_int, c_long};
+
+    const CLOCK_MONOTONIC: c_int = 1;
+
+    #[repr(C)]
+    struct Timespec {
+        tv_sec: c_long,
+        tv_nsec: c_long,
+    }
+
+    extern "C" {
+        fn clock_gettime(clock: c_int, time: *mut Timespec) -> c_int;
+    }
+
+    let mut time = Timespec { tv_sec: 0, tv_nsec: 0 };
+
+    // SAFETY: `time` is a valid `timespec`.
+    if unsafe { clock_gettime(CLOCK_MONOTONIC, &mut time) } != 0 {
+        return None;
+    }
+
+    Some(time.tv_sec as u64 * 1_000_000_000 + time.tv_nsec as u64)
+}
+
+#[cfg(not(all(any(target_os = "linux", target_os = "android"), not(miri))))]
+fn monotonic_nanos() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_secs() {
+        assert_eq!(super::format_secs(0), "0.000000");
+        assert_eq!(super::format_secs(8_273_012_345_678), "8273.012345");
+        assert_eq!(super::format_secs(1_000_000), "0.001000");
+    }
+
+    #[test]
+    fn write_ranges() {
+        let path =
+            std::env::temp_dir().join(format!("divan-profile-markers-{}.txt", std::process::id()));
+
+        let Ok(markers) = ProfileMarkers::create(&path) else {
+            return;
+        };
+
+        drop(markers.start("a::b", 1));
+        drop(markers.start("c", 4));
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        _ = std::fs::remove_file(&path);
+
+        let lines: Vec<(f64, f64, &str)> = contents
+            .lines()
+            .map(|line| {
+                let (range, name) = line.split_once(' ').unwrap();
+                let (start, end) = range.split_once(',').unwrap();
+                (start.parse().unwrap(), end.parse().unwrap(), name)
+            })
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].2, "a::b t=1");
+        assert_eq!(lines[1].2, "c t=4");
+
+        assert!(lines[0].0 <= lines[0].1);
+        assert!(lines[0].1 <= lines[1].0);
+    }
+}
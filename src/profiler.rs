@@ -0,0 +1,87 @@
+//! Toggling external profilers around benchmarks.
+//!
+//! See [`ProfilerHook`] for details, or [`SampleHook`] for hooks called around
+//! every sample.
+
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Receives callbacks around the sample collection of each benchmark, for
+/// starting and stopping an external profiler precisely.
+///
+/// Hooks are added with
+/// [`Divan::with_profiler_hook`](crate::Divan::with_profiler_hook). They are
+/// only called when benchmarking, not when testing or listing benchmarks.
+///
+/// Benchmarks are identified by their `path`, which is the display names of
+/// the benchmark and its parents separated by `::`. Multi-threaded benchmarks
+/// call hooks once per thread count, with the same path.
+///
+/// Hooks are called outside of timed sections, but profilers that are slow to
+/// start or stop may still perturb the first and last samples. Interleaved
+/// benchmarks are started on separate threads before any of them is stopped,
+/// which is why hooks must be [`Send`].
+///
+/// # Examples
+///
+/// ```
+/// use divan::{profiler::ProfilerHook, Divan};
+///
+/// struct PrintHook;
+///
+/// impl ProfilerHook for PrintHook {
+///     fn start(&mut self, path: &str) {
+///         eprintln!("profiling {path}");
+///     }
+///
+///     fn stop(&mut self, path: &str) {
+///         eprintln!("finished profiling {path}");
+///     }
+/// }
+///
+/// fn main() {
+///     Divan::from_args()
+///         .with_profiler_hook(PrintHook)
+///         .main();
+/// }
+/// ```
+pub trait ProfilerHook: Send {
+    /// Called right before a benchmark starts collecting samples.
+    fn start(&mut self, path: &str);
+
+    /// Called right after a benchmark stops collecting samples, including when
+    /// it panics or times out.
+    fn stop(&mut self, path: &str);
+}
+
+/// A function called around each sample of a benchmark with the benchmark's
+/// path.
+///
+/// This is set with [`Divan::on_sample_begin`](crate::Divan::on_sample_begin)
+/// and [`Divan::on_sample_end`](crate::Divan::on_sample_end).
+pub type SampleHook = fn(path: &str);
+
+/// A hook shared by all benchmark threads.
+pub(crate) type SharedHook = Arc<Mutex<dyn ProfilerHook>>;
+
+/// Starts `hooks` for the benchmark at `path`, which are stopped in reverse
+/// order when the returned guard is dropped.
+pub(crate) fn start<'a>(hooks: &'a [SharedHook], path: &'a str) -> HookGuard<'a> {
+    for hook in hooks {
+        hook.lock().unwrap_or_else(PoisonError::into_inner).start(path);
+    }
+    HookGuard { hooks, path }
+}
+
+/// Stops profiler hooks when dropped.
+pub(crate) struct HookGuard<'a> {
+    hooks: &'a [SharedHook],
+    path: &'a str,
+}
+
+impl Drop for HookGuard<'_> {
+    fn drop(&mut self) {
+        for hook in self.hooks.iter().rev() {
+            hook.lock().unwrap_or_else(PoisonError::into_inner).stop(self.path);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Live progress of the running benchmark.
+//!
+//! When the tree is printed to a terminal, the progress of each benchmark is
+//! drawn after its name on the row where its results will be printed:
+//!
+//! ```text
+//! ├─ sort_u64   [42/100 samples, 1.25 s elapsed, ETA 1.72 s]
+//! ```
+//!
+//! The progress is redrawn in place and cleared before the results are
+//! printed. Drawing starts after [`REDRAW_INTERVAL`] so that fast benchmarks do
+//! not flicker.
+
+use std::{
+    io::Write,
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use crate::time::FineDuration;
+
+/// How often progress is redrawn.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Draws the progress of benchmarks to stdout.
+pub(crate) struct Progress {
+    /// Whether to dim progress with ANSI escape codes.
+    use_color: bool,
+
+    /// The row of the running benchmark.
+    row: Mutex<Option<Row>>,
+}
+
+struct Row {
+    /// The text of the row before results, which is redrawn before progress.
+    prefix: String,
+
+    start: Instant,
+    last_draw: Instant,
+
+    /// Whether progress is currently shown and needs to be cleared.
+    is_drawn: bool,
+}
+
+/// The phase of a benchmark's sample loop.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Status {
+    WarmingUp,
+    Tuning,
+    Collecting {
+        samples: usize,
+        expected: usize,
+
+        /// The estimated time left, or `None` if no samples were collected.
+        eta: Option<FineDuration>,
+    },
+}
+
+impl Progress {
+    pub fn new(use_color: bool) -> Self {
+        Self { use_color, row: Mutex::new(None) }
+    }
+
+    /// Starts showing progress after `prefix`, which has already been printed
+    /// as the start of the benchmark's row. Progress is cleared when the
+    /// returned guard is dropped.
+    pub fn start(&self, prefix: &str) -> ProgressRow<'_> {
+        let now = Instant::now();
+        *self.lock() =
+            Some(Row { prefix: prefix.to_owned(), start: now, last_draw: now, is_drawn: false });
+
+        ProgressRow { progress: self }
+    }
+
+    /// Redraws progress with the result of `status` if enough time passed.
+    #[inline]
+    pub fn update(&self, status: impl FnOnce() -> Status) {
+        let mut row = self.lock();
+        let Some(row) = &mut *row else {
+            return;
+        };
+
+        let now = Instant::now();
+        if now.duration_since(row.last_draw) < REDRAW_INTERVAL {
+            return;
+        }
+
+        let line = format_status(status(), now.duration_since(row.start));
+        let (dim, reset) = if self.use_color { ("\x1b[2m", "\x1b[0m") } else { ("", "") };
+
+        let mut stdout = std::io::stdout().lock();
+        _ = write!(stdout, "\r{}{dim}[{line}]{reset}\x1b[K", row.prefix);
+        _ = stdout.flush();
+
+        row.last_draw = now;
+        row.is_drawn = true;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Row>> {
+        self.row.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Clears progress of a benchmark's row when dropped.
+pub(crate) struct ProgressRow<'a> {
+    progress: &'a Progress,
+}
+
+impl Drop for ProgressRow<'_> {
+    fn drop(&mut self) {
+        let Some(row) = self.progress.lock().take() else {
+            return;
+        };
+
+        if row.is_drawn {
+            let mut stdout = std::io::stdout().lock();
+            _ = write!(stdout, "\r{}\x1b[K", row.prefix);
+            _ = stdout.flush();
+        }
+    }
+}
+
+fn format_status(status: Status, elapsed: Duration) -> String {
+    let elapsed = FineDuration::from(elapsed);
+
+    match status {
+        Status::WarmingUp => format!("warming up, {elapsed:.3} elapsed"),
+        Status::Tuning => format!("tuning, {elapsed:.3} elapsed"),
+        Status::Collecting { samples, expected, eta } => {
+            let mut line = format!("{samples}/{expected} samples, {elapsed:.3} elapsed");
+            if let Some(eta) = eta {
+                line.push_str(&format!(", ETA {eta:.3}"));
+            }
+            line
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_status() {
+        let elapsed = Duration::from_millis(1250);
+
+        assert_eq!(super::format_status(Status::WarmingUp, elapsed), "warming up, 1.25 s elapsed");
+        assert_eq!(super::format_status(Status::Tuning, elapsed), "tuning, 1.25 s elapsed");
+
+        let collecting = Status::Collecting {
+            samples: 42,
+            expected: 100,
+            eta: Some(FineDuration::from(Duration::from_millis(1720))),
+        };
+        assert_eq!(
+            super::format_status(collecting, elapsed),
+            "42/100 samples, 1.25 s elapsed, ETA 1.72 s"
+        );
+
+        let collecting = Status::Collecting { samples: 0, expected: 100, eta: None };
+        assert_eq!(super::format_status(collecting, elapsed), "0/100 samples, 1.25 s elapsed");
+    }
+}
@@ -0,0 +1,118 @@
+//! Custom output of benchmark progress and results.
+//!
+//! See [`Reporter`] for details.
+
+use std::time::Duration;
+
+use crate::{
+    alloc::AllocCounts,
+    counter::{CountTotal, KnownCounterKind},
+    hw::{HwCounts, PerfCounts},
+    io_counters::IoCounts,
+    time::FineDuration,
+};
+
+pub use crate::results::{BenchResults, Stats, StatsSet, ThreadStats};
+
+/// Receives benchmark progress and results as benchmarks run.
+///
+/// Reporters are added with [`Divan::with_reporter`](crate::Divan::with_reporter)
+/// and are called alongside the tree output, which can be disabled with
+/// [`Divan::tree_output`](crate::Divan::tree_output) to replace it entirely.
+///
+/// Reporters are only called when benchmarking, not when testing or listing
+/// benchmarks. All methods do nothing by default.
+///
+/// Benchmarks are identified by their `path`, which is the display names of
+/// the benchmark and its parents separated by `::`. Generic benchmarks include
+/// their type or `const` as path components, and multi-threaded benchmarks
+/// include their thread count as "t=N".
+///
+/// # Examples
+///
+/// ```
+/// use divan::{reporter::{Reporter, Stats}, Divan};
+///
+/// struct MedianPrinter;
+///
+/// impl Reporter for MedianPrinter {
+///     fn on_bench_complete(&mut self, path: &str, stats: &Stats) {
+///         println!("{path}: {:?}", stats.time().median);
+///     }
+/// }
+///
+/// fn main() {
+///     Divan::from_args()
+///         .tree_output(false)
+///         .with_reporter(MedianPrinter)
+///         .main();
+/// }
+/// ```
+pub trait Reporter {
+    /// Called before a benchmark is measured.
+    #[allow(unused_variables)]
+    fn on_bench_start(&mut self, path: &str) {}
+
+    /// Called for each sample recorded for a benchmark.
+    ///
+    /// Samples are reported once the benchmark finishes sampling so that
+    /// reporting does not perturb measurements.
+    #[allow(unused_variables)]
+    fn on_sample(&mut self, path: &str, sample: &Sample) {}
+
+    /// Called after a benchmark is measured, with its statistics.
+    #[allow(unused_variables)]
+    fn on_bench_complete(&mut self, path: &str, stats: &Stats) {}
+
+    /// Called after all benchmarks are measured, with results of the run.
+    #[allow(unused_variables)]
+    fn on_finish(&mut self, results: &BenchResults) {}
+}
+
+impl<R: Reporter + ?Sized> Reporter for Box<R> {
+    #[inline]
+    fn on_bench_start(&mut self, path: &str) {
+        (**self).on_bench_start(path);
+    }
+
+    #[inline]
+    fn on_sample(&mut self, path: &str, sample: &Sample) {
+        (**self).on_sample(path, sample);
+    }
+
+    #[inline]
+    fn on_bench_complete(&mut self, path: &str, stats: &Stats) {
+        (**self).on_bench_complete(path, stats);
+    }
+
+    #[inline]
+    fn on_finish(&mut self, results: &BenchResults) {
+        (**self).on_finish(results);
+    }
+}
+
+/// Measurement over multiple iterations of a benchmark.
+#[derive(Clone, Copy, Debug)]
+pub struct Sample {
+    pub(crate) iter_count: u32,
+    pub(crate) duration: FineDuration,
+    pub(crate) counter_totals: [Option<CountTotal>; KnownCounterKind::COUNT],
+    pub(crate) hw_counts: Option<HwCounts>,
+    pub(crate) perf_counts: Option<PerfCounts>,
+    pub(crate) alloc_counts: Option<AllocCounts>,
+    pub(crate) io_counts: Option<IoCounts>,
+}
+
+impl Sample {
+    /// The number of iterations measured.
+    #[inline]
+    pub fn iter_count(&self) -> u32 {
+        self.iter_count
+    }
+
+    /// The total time taken by all iterations.
+    #[inline]
+    pub fn duration(&self) -> Duration {
+        self.duration.into()
+    }
+}
@@ -0,0 +1,261 @@
+//! Results collected over a benchmark run.
+//!
+//! These are returned by [`Divan::collect`](crate::Divan::collect) and passed
+//! to [`Reporter::on_finish`](crate::reporter::Reporter::on_finish).
+
+use std::fmt;
+
+use crate::{stats::Bimodality, throttle::Throttling, time::FineDuration};
+
+pub use crate::stats::{Stats, StatsSet, ThreadStats};
+
+/// Results of all benchmarks in a run.
+#[derive(Default)]
+pub struct BenchResults {
+    /// Results in the same structure as the tree output.
+    pub(crate) tree: Vec<ResultTree>,
+
+    /// Benchmarks that exceeded a threshold, which makes the run fail.
+    pub(crate) failures: Vec<ThresholdFailure>,
+
+    /// Benchmarks that panicked or timed out, which makes the run fail.
+    pub(crate) errors: Vec<BenchError>,
+
+    /// Benchmarks during which CPU frequency dropped significantly.
+    pub(crate) throttled: Vec<ThrottledBench>,
+
+    /// Benchmarks whose times varied more than the noise threshold.
+    pub(crate) noisy: Vec<NoisyBench>,
+
+    /// Benchmarks whose times clustered around two distinct values.
+    pub(crate) bimodal: Vec<BimodalBench>,
+}
+
+impl fmt::Debug for BenchResults {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.entries().map(|entry| (entry.path(), entry.stats()))).finish()
+    }
+}
+
+impl BenchResults {
+    /// Returns results in the same structure as the tree output, with
+    /// benchmarks nested within their modules and groups.
+    #[inline]
+    pub fn tree(&self) -> &[ResultTree] {
+        &self.tree
+    }
+
+    /// Returns the result of each benchmark, in the order they were run.
+    pub fn entries(&self) -> impl Iterator<Item = &EntryResult> {
+        ResultTree::entries(&self.tree).into_iter()
+    }
+
+    /// Returns `true` if no benchmark panicked, timed out, or exceeded its
+    /// [`max_time_mean`](macro@crate::bench#max_time_mean) or the
+    /// `--fail-on-regression` threshold.
+    #[inline]
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty() && self.errors.is_empty()
+    }
+}
+
+/// A node in [`BenchResults::tree`].
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum ResultTree {
+    /// A module, group, or benchmark with multiple thread counts.
+    Parent {
+        /// The display name of the node.
+        name: String,
+
+        /// Results within this node, in the order they were run.
+        children: Vec<ResultTree>,
+    },
+
+    /// A measured benchmark.
+    Leaf(EntryResult),
+}
+
+impl ResultTree {
+    /// Returns the display name of this node.
+    #[inline]
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Parent { name, .. } => name,
+            Self::Leaf(entry) => &entry.name,
+        }
+    }
+
+    /// Returns the result of each benchmark in `tree`, in the order they were
+    /// run.
+    pub(crate) fn entries(tree: &[Self]) -> Vec<&EntryResult> {
+        fn collect<'a>(tree: &'a [ResultTree], entries: &mut Vec<&'a EntryResult>) {
+            for node in tree {
+                match node {
+                    ResultTree::Leaf(entry) => entries.push(entry),
+                    ResultTree::Parent { children, .. } => collect(children, entries),
+                }
+            }
+        }
+
+        let mut entries = Vec::new();
+        collect(tree, &mut entries);
+        entries
+    }
+}
+
+/// Statistics of a single benchmark, identified by its full path.
+#[derive(Debug)]
+pub struct EntryResult {
+    pub(crate) name: String,
+    pub(crate) path: String,
+    pub(crate) generic_type: Option<String>,
+    pub(crate) generic_const: Option<String>,
+    pub(crate) stats: Stats,
+
+    /// Per-iteration time of each reported sample, for testing the
+    /// significance of changes. This is empty if samples were measured in
+    /// another process.
+    pub(crate) sample_times: Vec<FineDuration>,
+}
+
+impl EntryResult {
+    /// The display name of the benchmark, or "t=N" for one of multiple thread
+    /// counts.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Display names of the benchmark and its parents, separated by `::`.
+    ///
+    /// Generic benchmarks include their type or `const` as path components,
+    /// and multi-threaded benchmarks include their thread count as "t=N".
+    #[inline]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The display name of the type of a generic benchmark.
+    #[inline]
+    pub fn generic_type(&self) -> Option<&str> {
+        self.generic_type.as_deref()
+    }
+
+    /// The `const` value of a generic benchmark.
+    #[inline]
+    pub fn generic_const(&self) -> Option<&str> {
+        self.generic_const.as_deref()
+    }
+
+    /// Statistics computed from the benchmark's samples.
+    #[inline]
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+}
+
+/// A benchmark that exceeded `max_time_mean` or `--fail-on-regression`.
+pub(crate) struct ThresholdFailure {
+    pub path: String,
+    pub kind: ThresholdKind,
+}
+
+pub(crate) enum ThresholdKind {
+    /// Mean time per iteration exceeded the `max_time_mean` option.
+    MaxTimeMean { mean: FineDuration, max: FineDuration },
+
+    /// Median time increased relative to the baseline by more than the
+    /// allowed percentage.
+    Regression { change: f64, max: f64 },
+}
+
+impl fmt::Display for ThresholdFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = &self.path;
+        match self.kind {
+            ThresholdKind::MaxTimeMean { mean, max } => {
+                write!(f, "{path}: mean {mean} exceeds max_time_mean of {max}")
+            }
+            ThresholdKind::Regression { change, max } => {
+                write!(
+                    f,
+                    "{path}: median changed by {change:+.2}%, exceeding regression limit of {max}%"
+                )
+            }
+        }
+    }
+}
+
+/// A benchmark that was stopped before it could be measured.
+pub(crate) struct BenchError {
+    pub path: String,
+    pub kind: BenchErrorKind,
+}
+
+pub(crate) enum BenchErrorKind {
+    /// The benchmark panicked with this message.
+    Panicked(String),
+
+    /// The benchmark ran longer than its `timeout` option.
+    TimedOut(FineDuration),
+}
+
+impl fmt::Display for BenchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = &self.path;
+        match &self.kind {
+            BenchErrorKind::Panicked(message) => write!(f, "{path}: panicked: {message}"),
+            BenchErrorKind::TimedOut(timeout) => write!(f, "{path}: timed out after {timeout}"),
+        }
+    }
+}
+
+/// A benchmark during which CPU frequency dropped significantly, which makes
+/// its results unreliable.
+pub(crate) struct ThrottledBench {
+    pub path: String,
+    pub throttling: Throttling,
+}
+
+impl fmt::Display for ThrottledBench {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.throttling)
+    }
+}
+
+/// A benchmark whose sample times varied too much for its results to be
+/// trusted.
+pub(crate) struct NoisyBench {
+    pub path: String,
+
+    /// Coefficient of variation of time per iteration as a percentage.
+    pub cv: f64,
+}
+
+impl fmt::Display for NoisyBench {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {:.1}% coefficient of variation", self.path, self.cv)
+    }
+}
+
+/// A benchmark whose sample times clustered around two distinct values, which
+/// a single mean or median misrepresents.
+pub(crate) struct BimodalBench {
+    pub path: String,
+    pub bimodality: Bimodality,
+}
+
+impl fmt::Display for BimodalBench {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Bimodality { modes: [fast, slow], fast_fraction } = self.bimodality;
+        let fast_percent = (fast_fraction * 100.0).round();
+
+        write!(
+            f,
+            "{}: {fast_percent}% of samples near {fast}, {}% near {slow}",
+            self.path,
+            100.0 - fast_percent
+        )
+    }
+}
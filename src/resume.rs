@@ -0,0 +1,187 @@
+//! Resuming interrupted runs from checkpointed results.
+//!
+//! With `--resume <PATH>`, the results of each benchmark are appended to `PATH`
+//! as a line of JSON once all of its thread counts finish:
+//!
+//! ```text
+//! {"path":"sort::u64","results":[{"stats":{...},"throttling":null}]}
+//! ```
+//!
+//! If the run is interrupted, re-running with the same `--resume` path reuses
+//! these results instead of measuring the benchmarks again, and reports them
+//! alongside the newly measured ones. The file is removed once a run finishes.
+//!
+//! Benchmarks that panicked or timed out are not checkpointed, so they run
+//! again when resuming.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+};
+
+use crate::{divan::MeasureResult, isolate, json::Json};
+
+/// The results of each thread count of completed benchmarks, by path.
+pub(crate) type Completed = HashMap<String, Vec<Option<MeasureResult>>>;
+
+/// The state file of a resumable run.
+pub(crate) struct ResumeState {
+    path: PathBuf,
+
+    /// The output, which is `None` after a write fails.
+    out: Mutex<Option<File>>,
+
+    /// Benchmarks whose results are already in the file.
+    checkpointed: HashSet<String>,
+}
+
+impl ResumeState {
+    /// Opens the state file at `path`, returning the benchmarks completed by
+    /// previous runs.
+    ///
+    /// Lines that cannot be parsed, such as one cut short by an interruption,
+    /// are ignored.
+    pub fn open(path: &Path) -> io::Result<(Self, Completed)> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(error),
+        };
+
+        let completed: Completed = contents.lines().filter_map(parse_line).collect();
+
+        let mut out = OpenOptions::new().create(true).append(true).open(path)?;
+
+        // Terminate a line cut short by an interruption, so that appended
+        // lines can be parsed.
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            out.write_all(b"\n")?;
+        }
+
+        let state = Self {
+            path: path.to_owned(),
+            out: Mutex::new(Some(out)),
+            checkpointed: completed.keys().cloned().collect(),
+        };
+
+        Ok((state, completed))
+    }
+
+    /// Starts recording the results of the benchmark at `path`, which are
+    /// written by [`Checkpoint::finish`].
+    pub fn checkpoint<'a>(&'a self, path: &'a str) -> Checkpoint<'a> {
+        Checkpoint { state: self, path, results: Vec::new(), is_errored: false }
+    }
+
+    /// Returns `true` if the benchmark at `path` was completed by a previous
+    /// run.
+    pub fn is_checkpointed(&self, path: &str) -> bool {
+        self.checkpointed.contains(path)
+    }
+
+    /// Closes and removes the state file after all benchmarks have run.
+    pub fn finish(&self) {
+        drop(self.out.lock().unwrap_or_else(PoisonError::into_inner).take());
+
+        if let Err(error) = fs::remove_file(&self.path) {
+            eprintln!("warning: Failed to remove '{}': {error}", self.path.display());
+        }
+    }
+
+    /// Appends a line, emitting a warning and discarding further lines if it
+    /// fails.
+    fn write(&self, line: &str) {
+        let mut out = self.out.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(file) = &mut *out else {
+            return;
+        };
+
+        if let Err(error) = writeln!(file, "{line}") {
+            eprintln!("warning: Failed to write '{}': {error}", self.path.display());
+            *out = None;
+        }
+    }
+}
+
+/// The results of a benchmark's thread counts, which are checkpointed together.
+pub(crate) struct Checkpoint<'a> {
+    state: &'a ResumeState,
+    path: &'a str,
+    results: Vec<Json>,
+    is_errored: bool,
+}
+
+impl Checkpoint<'_> {
+    /// Adds the result of the next thread count.
+    pub fn push(&mut self, result: Option<&MeasureResult>) {
+        self.is_errored |= matches!(result, Some(Err(_)));
+        self.results.push(isolate::result_to_json(result));
+    }
+
+    /// Writes the results unless a thread count errored or they were reused
+    /// from a previous run.
+    pub fn finish(self) {
+        if self.is_errored || self.state.is_checkpointed(self.path) {
+            return;
+        }
+
+        let line = Json::object().with("path", self.path).with("results", self.results);
+        self.state.write(&line.to_string());
+    }
+}
+
+fn parse_line(line: &str) -> Option<(String, Vec<Option<MeasureResult>>)> {
+    let json = Json::parse(line).ok()?;
+
+    let path = json.get("path")?.as_str()?.to_owned();
+    let results = json.get("results")?.as_array()?.iter().map(isolate::result_from_json).collect();
+
+    Some((path, results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::BenchErrorKind;
+
+    #[test]
+    fn resume() {
+        let path = std::env::temp_dir().join(format!("divan-resume-{}.jsonl", std::process::id()));
+
+        // A previous run that was interrupted while writing.
+        fs::write(&path, "{\"path\":\"a\",\"results\":[null]}\n{\"path\":\"b\",\"res").unwrap();
+
+        let (state, completed) = ResumeState::open(&path).unwrap();
+        assert_eq!(completed.keys().collect::<Vec<_>>(), ["a"]);
+        assert!(completed["a"][0].is_none());
+
+        // Reused results are not written again.
+        let mut checkpoint = state.checkpoint("a");
+        checkpoint.push(None);
+        checkpoint.finish();
+
+        // Errored results are not written.
+        let mut checkpoint = state.checkpoint("c");
+        checkpoint.push(None);
+        checkpoint.push(Some(&Err(BenchErrorKind::Panicked("oops".to_owned()))));
+        checkpoint.finish();
+
+        let mut checkpoint = state.checkpoint("d");
+        checkpoint.push(None);
+        checkpoint.push(None);
+        checkpoint.finish();
+
+        let (state, completed) = ResumeState::open(&path).unwrap();
+
+        let mut paths: Vec<_> = completed.keys().collect();
+        paths.sort();
+        assert_eq!(paths, ["a", "d"]);
+        assert_eq!(completed["d"].len(), 2);
+
+        state.finish();
+        assert!(!path.exists());
+    }
+}
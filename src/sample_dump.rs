@@ -0,0 +1,119 @@
+//! Raw sample export for offline analysis.
+//!
+//! With `--dump-samples <PATH>`, every recorded sample is written as a line of
+//! JSON before it is discarded:
+//!
+//! ```json
+//! {"path":"math::add","index":0,"iters":64,"time":1234000,"counters":{"bytes":512}}
+//! ```
+//!
+//! `"time"` is the total duration of the sample's iterations in picoseconds,
+//! and `"counters"` has the total counts over those iterations. Hardware event
+//! counts, allocation counts, and I/O counts are included when enabled, as are
+//! counts of `--perf-events` in `"perf_events"`.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    counter::KnownCounterKind,
+    hw::{HwCounterKind, PerfEvent},
+    io_counters::IoCounterKind,
+    json::Json,
+    reporter::{BenchResults, Reporter, Sample},
+};
+
+/// Writes samples as JSON Lines.
+pub(crate) struct SampleDump {
+    /// The output, which is `None` after a write fails.
+    out: Option<BufWriter<File>>,
+
+    /// The index of the next sample of the current benchmark.
+    sample_index: usize,
+
+    /// Events whose counts are recorded in each sample.
+    perf_events: Vec<PerfEvent>,
+}
+
+impl SampleDump {
+    pub fn create(path: &Path, perf_events: Vec<PerfEvent>) -> io::Result<Self> {
+        Ok(Self { out: Some(BufWriter::new(File::create(path)?)), sample_index: 0, perf_events })
+    }
+
+    /// Writes a line, emitting a warning and discarding further lines if it
+    /// fails.
+    fn write(&mut self, line: Json) {
+        let Some(out) = &mut self.out else {
+            return;
+        };
+
+        if let Err(error) = writeln!(out, "{line}") {
+            eprintln!("warning: Failed to write samples: {error}");
+            self.out = None;
+        }
+    }
+}
+
+impl Reporter for SampleDump {
+    fn on_bench_start(&mut self, _path: &str) {
+        self.sample_index = 0;
+    }
+
+    fn on_sample(&mut self, path: &str, sample: &Sample) {
+        let mut json = Json::object()
+            .with("path", path)
+            .with("index", self.sample_index)
+            .with("iters", sample.iter_count)
+            .with("time", sample.duration.picos);
+
+        let mut counters = Json::object();
+        for kind in KnownCounterKind::ALL {
+            if let Some(total) = sample.counter_totals[kind as usize] {
+                counters = counters.with(kind.name(), total);
+            }
+        }
+        json = json.with("counters", counters);
+
+        if let Some(hw_counts) = &sample.hw_counts {
+            for kind in HwCounterKind::ALL.into_iter().filter(|kind| kind.is_supported()) {
+                json = json.with(kind.name(), hw_counts.0[kind as usize]);
+            }
+        }
+
+        if let Some(perf_counts) = &sample.perf_counts {
+            let mut events = Json::object();
+            for (event, &count) in self.perf_events.iter().zip(&perf_counts.0) {
+                events = events.with(event.name, count);
+            }
+            json = json.with("perf_events", events);
+        }
+
+        if let Some(allocs) = &sample.alloc_counts {
+            json = json
+                .with("allocs", allocs.alloc_count)
+                .with("alloc_bytes", allocs.alloc_bytes)
+                .with("deallocs", allocs.dealloc_count)
+                .with("peak_bytes", allocs.peak_bytes);
+        }
+
+        if let Some(io_counts) = &sample.io_counts {
+            for kind in IoCounterKind::ALL {
+                json = json.with(kind.name(), io_counts.0[kind as usize]);
+            }
+        }
+
+        self.sample_index += 1;
+        self.write(json);
+    }
+
+    fn on_finish(&mut self, _results: &BenchResults) {
+        if let Some(out) = &mut self.out {
+            if let Err(error) = out.flush() {
+                eprintln!("warning: Failed to write samples: {error}");
+            }
+        }
+    }
+}
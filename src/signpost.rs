@@ -0,0 +1,168 @@
+//! [`os_signpost`](https://developer.apple.com/documentation/os/logging/recording_performance_data)
+//! intervals for Instruments.
+//!
+//! Each benchmark and each of its samples is an interval in the "Points of
+//! Interest" track, labeled with the benchmark's path, so that time profiler
+//! samples can be narrowed down to a single benchmark by selecting its
+//! interval. Signposts are only emitted while Instruments is recording.
+
+use std::{
+    ffi::{c_char, c_void, CStr, CString},
+    sync::OnceLock,
+};
+
+type OsLog = *mut c_void;
+
+const OS_SIGNPOST_INTERVAL_BEGIN: u8 = 1;
+const OS_SIGNPOST_INTERVAL_END: u8 = 2;
+
+const SUBSYSTEM: &CStr = c_str(b"divan\0");
+
+/// Category that Instruments shows in the "Points of Interest" track.
+const CATEGORY: &CStr = c_str(b"PointsOfInterest\0");
+
+const BENCH_NAME: &CStr = c_str(b"bench\0");
+const SAMPLE_NAME: &CStr = c_str(b"sample\0");
+
+/// Format of the message, which is the benchmark's path.
+const FORMAT: &CStr = c_str(b"%{public}s\0");
+
+extern "C" {
+    /// The Mach-O header of this image, which signpost names and formats are
+    /// relative to.
+    static __dso_handle: u8;
+
+    fn os_log_create(subsystem: *const c_char, category: *const c_char) -> OsLog;
+    fn os_signpost_enabled(log: OsLog) -> bool;
+    fn os_signpost_id_generate(log: OsLog) -> u64;
+
+    /// The function that `os_signpost_interval_begin` and
+    /// `os_signpost_interval_end` expand to.
+    fn _os_signpost_emit_with_name_impl(
+        dso: *const c_void,
+        log: OsLog,
+        kind: u8,
+        id: u64,
+        name: *const c_char,
+        format: *const c_char,
+        buf: *mut u8,
+        size: u32,
+    );
+}
+
+const fn c_str(bytes: &[u8]) -> &CStr {
+    match CStr::from_bytes_with_nul(bytes) {
+        Ok(s) => s,
+        Err(_) => panic!("invalid C string"),
+    }
+}
+
+/// Returns the log of Divan's signposts.
+fn log() -> OsLog {
+    struct Log(OsLog);
+
+    // SAFETY: `os_log_t` is thread-safe.
+    unsafe impl Send for Log {}
+    unsafe impl Sync for Log {}
+
+    static LOG: OnceLock<Log> = OnceLock::new();
+
+    // SAFETY: Both arguments are valid C strings.
+    LOG.get_or_init(|| Log(unsafe { os_log_create(SUBSYSTEM.as_ptr(), CATEGORY.as_ptr()) })).0
+}
+
+/// Signpost intervals of a benchmark's sample loop.
+///
+/// The benchmark's interval ends when dropped.
+pub(crate) struct BenchSignposts {
+    log: OsLog,
+
+    /// The message of every interval, or `None` if signposts are disabled.
+    path: Option<CString>,
+
+    id: u64,
+}
+
+impl BenchSignposts {
+    /// Begins the interval of the benchmark at `path` if Instruments is
+    /// recording.
+    pub fn begin(path: &str) -> Self {
+        let log = log();
+
+        // SAFETY: `log` is a valid `os_log_t`.
+        let path = if unsafe { os_signpost_enabled(log) } { CString::new(path).ok() } else { None };
+
+        let mut signposts = Self { log, path, id: 0 };
+        signposts.id = signposts.emit(OS_SIGNPOST_INTERVAL_BEGIN, None, BENCH_NAME).unwrap_or(0);
+        signposts
+    }
+
+    /// Begins the interval of a sample, returning its ID.
+    #[inline]
+    pub fn begin_sample(&self) -> Option<u64> {
+        self.emit(OS_SIGNPOST_INTERVAL_BEGIN, None, SAMPLE_NAME)
+    }
+
+    /// Ends the interval of the sample with `id`.
+    #[inline]
+    pub fn end_sample(&self, id: Option<u64>) {
+        if let Some(id) = id {
+            self.emit(OS_SIGNPOST_INTERVAL_END, Some(id), SAMPLE_NAME);
+        }
+    }
+
+    /// Emits a signpost with the benchmark's path as its message, generating
+    /// an ID if `id` is `None`.
+    fn emit(&self, kind: u8, id: Option<u64>, name: &'static CStr) -> Option<u64> {
+        let path = self.path.as_ref()?;
+
+        // SAFETY: `log` is a valid `os_log_t`.
+        let id = id.unwrap_or_else(|| unsafe { os_signpost_id_generate(self.log) });
+
+        // Arguments encoded as by `__builtin_os_log_format`: a header with
+        // "has non-scalar arguments" set and an argument count of 1, followed
+        // by a public string argument of pointer size.
+        let mut buf = [0u8; 4 + std::mem::size_of::<usize>()];
+        buf[..4].copy_from_slice(&[0x02, 0x01, 0x22, std::mem::size_of::<usize>() as u8]);
+        buf[4..].copy_from_slice(&(path.as_ptr() as usize).to_ne_bytes());
+
+        // SAFETY: `name` and `FORMAT` are static strings in this image, and
+        // `buf` encodes the single `%s` argument of `FORMAT`, which outlives
+        // the call.
+        unsafe {
+            _os_signpost_emit_with_name_impl(
+                &__dso_handle as *const u8 as *const c_void,
+                self.log,
+                kind,
+                id,
+                name.as_ptr(),
+                FORMAT.as_ptr(),
+                buf.as_mut_ptr(),
+                buf.len() as u32,
+            );
+        }
+
+        Some(id)
+    }
+}
+
+impl Drop for BenchSignposts {
+    fn drop(&mut self) {
+        self.emit(OS_SIGNPOST_INTERVAL_END, Some(self.id), BENCH_NAME);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that intervals can be emitted whether or not Instruments is
+    /// recording.
+    #[test]
+    fn emit_intervals() {
+        let signposts = BenchSignposts::begin("signpost::tests::emit_intervals");
+
+        let id = signposts.begin_sample();
+        signposts.end_sample(id);
+    }
+}
@@ -0,0 +1,167 @@
+//! Recording time per iteration of every sample in a high dynamic range (HDR)
+//! histogram with `--hdr-histogram`, via `hdrhistogram`.
+//!
+//! The histogram keeps 3 significant digits of each sample in bounded memory,
+//! so high percentiles stay accurate when statistics are streamed or combined
+//! across runs. Its distribution is exported as HdrHistogram's compressed V2
+//! encoding in base64, which other HdrHistogram implementations can read.
+//!
+//! Without the `hdr` feature, [`TimeHistogram`] cannot be constructed.
+
+use crate::time::FineDuration;
+
+/// Significant decimal digits of recorded values.
+#[cfg(feature = "hdr")]
+const SIGNIFICANT_DIGITS: u8 = 3;
+
+/// Histogram of time per iteration in picoseconds.
+#[cfg(feature = "hdr")]
+#[derive(Clone)]
+pub(crate) struct TimeHistogram(hdrhistogram::Histogram<u64>);
+
+/// Histogram of time per iteration, which is uninhabited without the `hdr`
+/// feature.
+#[cfg(not(feature = "hdr"))]
+#[derive(Clone)]
+pub(crate) enum TimeHistogram {}
+
+#[cfg(feature = "hdr")]
+impl TimeHistogram {
+    /// Creates an empty histogram.
+    pub fn new() -> Option<Self> {
+        hdrhistogram::Histogram::new(SIGNIFICANT_DIGITS).ok().map(Self)
+    }
+
+    /// Records a time per iteration, clamped to the trackable range of about
+    /// 53 days.
+    pub fn record(&mut self, time: FineDuration) {
+        let picos = u64::try_from(time.picos).unwrap_or(u64::MAX);
+        if self.0.record(picos).is_err() {
+            self.0.saturating_record(picos);
+        }
+    }
+
+    /// Adds the recorded values of `other`.
+    pub fn add(&mut self, other: &Self) {
+        _ = self.0.add(&other.0);
+    }
+
+    /// Combines `histograms`, returning `None` unless all are present.
+    pub fn merge<'a>(histograms: impl IntoIterator<Item = Option<&'a Self>>) -> Option<Self> {
+        let mut histograms = histograms.into_iter();
+        let mut merged = histograms.next()??.clone();
+        for histogram in histograms {
+            merged.add(histogram?);
+        }
+        Some(merged)
+    }
+
+    /// Returns the time below which `quantile` of recorded values fall.
+    pub fn quantile(&self, quantile: f64) -> FineDuration {
+        FineDuration { picos: self.0.value_at_quantile(quantile) as u128 }
+    }
+
+    /// Returns the time below which `percentile`% of recorded values fall.
+    pub fn percentile(&self, percentile: u8) -> FineDuration {
+        self.quantile(percentile as f64 / 100.0)
+    }
+
+    /// Encodes the histogram as base64 of the compressed V2 format.
+    pub fn to_base64(&self) -> String {
+        use base64::Engine;
+        use hdrhistogram::serialization::{Serializer, V2DeflateSerializer};
+
+        let mut bytes = Vec::new();
+        if V2DeflateSerializer::new().serialize(&self.0, &mut bytes).is_err() {
+            return String::new();
+        }
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    }
+
+    /// Decodes a histogram from [`TimeHistogram::to_base64`].
+    pub fn from_base64(encoded: &str) -> Option<Self> {
+        use base64::Engine;
+        use hdrhistogram::serialization::Deserializer;
+
+        let bytes = base64::engine::general_purpose::STANDARD.decode(encoded).ok()?;
+        let mut histogram: hdrhistogram::Histogram<u64> =
+            Deserializer::new().deserialize(&mut bytes.as_slice()).ok()?;
+
+        // Deserialized histograms are not resizable.
+        histogram.auto(true);
+        Some(Self(histogram))
+    }
+}
+
+#[cfg(not(feature = "hdr"))]
+impl TimeHistogram {
+    pub fn new() -> Option<Self> {
+        None
+    }
+
+    pub fn record(&mut self, _time: FineDuration) {
+        match *self {}
+    }
+
+    pub fn merge<'a>(_histograms: impl IntoIterator<Item = Option<&'a Self>>) -> Option<Self> {
+        None
+    }
+
+    pub fn percentile(&self, _percentile: u8) -> FineDuration {
+        match *self {}
+    }
+
+    pub fn to_base64(&self) -> String {
+        match *self {}
+    }
+
+    pub fn from_base64(_encoded: &str) -> Option<Self> {
+        None
+    }
+}
+
+#[cfg(all(test, feature = "hdr"))]
+mod tests {
+    use super::*;
+
+    fn histogram(picos: impl IntoIterator<Item = u128>) -> TimeHistogram {
+        let mut histogram = TimeHistogram::new().unwrap();
+        for picos in picos {
+            histogram.record(FineDuration { picos });
+        }
+        histogram
+    }
+
+    #[test]
+    fn quantiles() {
+        let histogram = histogram(1..=10_000);
+
+        // Within 3 significant digits.
+        for (quantile, expected) in [(0.5, 5_000.0), (0.99, 9_900.0), (0.999, 9_990.0)] {
+            let picos = histogram.quantile(quantile).picos as f64;
+            assert!((picos - expected).abs() <= expected * 1e-3, "{quantile}: {picos}");
+        }
+    }
+
+    #[test]
+    fn add() {
+        let mut fast = histogram([100; 10]);
+        fast.add(&histogram([1_000_000; 10]));
+
+        assert_eq!(fast.quantile(0.5).picos, 100);
+        assert!(fast.quantile(1.0).picos >= 1_000_000);
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        let histogram = histogram((1..=1_000).map(|i| i * 1_000));
+        let encoded = histogram.to_base64();
+
+        let decoded = TimeHistogram::from_base64(&encoded).unwrap();
+        for quantile in [0.0, 0.5, 0.99, 1.0] {
+            assert_eq!(decoded.quantile(quantile), histogram.quantile(quantile));
+        }
+
+        assert!(TimeHistogram::from_base64("not base64!").is_none());
+    }
+}
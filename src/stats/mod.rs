@@ -1,35 +1,414 @@
 //! Measurement statistics.
 
+use std::{fmt, time::Duration};
+
 use crate::{
-    counter::{KnownCounterKind, MaxCountUInt},
+    alloc::AllocStats,
+    counter::{Count, KnownCounterKind},
+    hw::HwCounterKind,
+    io_counters::IoCounterKind,
     time::FineDuration,
+    util,
 };
 
+mod histogram;
+mod modes;
+mod runs;
 mod sample;
+mod significance;
+mod streaming;
 
+pub(crate) use histogram::*;
+pub(crate) use modes::*;
+pub(crate) use runs::*;
 pub(crate) use sample::*;
+pub(crate) use significance::*;
+pub(crate) use streaming::*;
+
+/// Percentiles of time per iteration computed for each benchmark.
+pub(crate) const TIME_PERCENTILES: [u8; 4] = [75, 90, 95, 99];
+
+/// The percentage of samples at each end that are excluded from the trimmed
+/// mean and clamped in the winsorized mean.
+pub(crate) const ROBUST_TRIM_PERCENT: usize = 5;
 
-/// Statistics from samples.
-pub(crate) struct Stats {
+/// Statistics of a benchmark, computed from its samples.
+pub struct Stats {
     /// Total number of samples taken.
-    pub sample_count: u32,
+    pub(crate) sample_count: u32,
 
     /// Total number of iterations (currently `sample_count * `sample_size`).
-    pub iter_count: u64,
+    pub(crate) iter_count: u64,
+
+    pub(crate) time: StatsSet<FineDuration>,
+
+    /// Time per iteration at each of [`TIME_PERCENTILES`].
+    pub(crate) time_percentiles: [FineDuration; TIME_PERCENTILES.len()],
+
+    pub(crate) counts: [Option<StatsSet<Count>>; KnownCounterKind::COUNT],
+
+    /// Counts of `CustomCount` keyed by unit.
+    pub(crate) custom_counts: Vec<(&'static str, StatsSet<Count>)>,
+
+    /// Standard deviation of time per iteration across samples.
+    pub(crate) time_std_dev: Option<FineDuration>,
+
+    /// Standard deviation of mean time per iteration between runs of the
+    /// suite, if run more than once with `--runs`.
+    pub(crate) run_std_dev: Option<FineDuration>,
+
+    /// Mean time per iteration excluding the fastest and slowest
+    /// [`ROBUST_TRIM_PERCENT`]% of samples.
+    pub(crate) trimmed_mean: Option<FineDuration>,
+
+    /// Mean time per iteration with the fastest and slowest
+    /// [`ROBUST_TRIM_PERCENT`]% of samples clamped to the nearest remaining
+    /// sample.
+    pub(crate) winsorized_mean: Option<FineDuration>,
+
+    /// Median absolute deviation of time per iteration from the median across
+    /// samples.
+    pub(crate) time_mad: Option<FineDuration>,
+
+    /// Modes of time per iteration, if samples appear bimodal.
+    pub(crate) bimodality: Option<Bimodality>,
+
+    /// Histogram of time per iteration of every sample, if recorded with
+    /// `--hdr-histogram`.
+    pub(crate) time_histogram: Option<TimeHistogram>,
+
+    /// Standard deviation of per-sample throughput in counts per second.
+    pub(crate) throughput_std_dev: [Option<f64>; KnownCounterKind::COUNT],
+
+    /// Standard deviation of per-sample throughput of `CustomCount` keyed by
+    /// unit.
+    pub(crate) custom_throughput_std_dev: Vec<(&'static str, f64)>,
+
+    /// Mean hardware event counts per iteration, if enabled.
+    pub(crate) hw_counts: Option<[f64; HwCounterKind::COUNT]>,
+
+    /// Mean counts of `--perf-events` per iteration, keyed by event name.
+    pub(crate) perf_counts: Vec<(&'static str, f64)>,
 
-    pub time: StatsSet<FineDuration>,
+    /// Aggregate and per-thread time, if run with multiple threads.
+    pub(crate) threads: Option<ThreadStats>,
 
-    pub counts: [Option<StatsSet<MaxCountUInt>>; KnownCounterKind::COUNT],
+    /// Allocation statistics, if `AllocProfiler` is used.
+    pub(crate) allocs: Option<AllocStats>,
+
+    /// Mean I/O counts per iteration, if enabled.
+    pub(crate) io_counts: Option<[f64; IoCounterKind::COUNT]>,
+
+    /// Mean time per iteration spent generating inputs, if measured.
+    pub(crate) gen_time: Option<FineDuration>,
+
+    /// Mean time per iteration spent dropping outputs and inputs, if measured.
+    pub(crate) drop_time: Option<FineDuration>,
 }
 
+impl fmt::Debug for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stats")
+            .field("sample_count", &self.sample_count)
+            .field("iter_count", &self.iter_count)
+            .field("time", &self.time)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
 impl Stats {
-    pub fn get_counts(&self, counter_kind: KnownCounterKind) -> Option<&StatsSet<MaxCountUInt>> {
+    /// Returns statistics with `time`, at which all percentiles are its
+    /// median, and no other measurements, for tests to override as needed.
+    pub(crate) fn test_default(
+        sample_count: u32,
+        iter_count: u64,
+        time: StatsSet<FineDuration>,
+    ) -> Self {
+        Self {
+            sample_count,
+            iter_count,
+            time,
+            time_percentiles: [time.median; TIME_PERCENTILES.len()],
+            counts: [None; KnownCounterKind::COUNT],
+            custom_counts: Vec::new(),
+            time_std_dev: None,
+            run_std_dev: None,
+            trimmed_mean: None,
+            winsorized_mean: None,
+            time_mad: None,
+            bimodality: None,
+            time_histogram: None,
+            throughput_std_dev: [None; KnownCounterKind::COUNT],
+            custom_throughput_std_dev: Vec::new(),
+            hw_counts: None,
+            perf_counts: Vec::new(),
+            threads: None,
+            allocs: None,
+            io_counts: None,
+            gen_time: None,
+            drop_time: None,
+        }
+    }
+}
+
+impl Stats {
+    /// The number of samples taken.
+    #[inline]
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// The total number of iterations across all samples.
+    #[inline]
+    pub fn iter_count(&self) -> u64 {
+        self.iter_count
+    }
+
+    /// Time taken per iteration.
+    ///
+    /// [`Duration`] has nanosecond precision, so [`Stats::time_picos`] should
+    /// be used for very fast benchmarks.
+    pub fn time(&self) -> StatsSet<Duration> {
+        self.time.map(Duration::from)
+    }
+
+    /// Time taken per iteration in picoseconds.
+    pub fn time_picos(&self) -> StatsSet<u128> {
+        self.time.map(|duration| duration.picos)
+    }
+
+    /// Time per iteration at or below which `percentile`% of samples fall.
+    ///
+    /// Only the 75th, 90th, 95th, and 99th percentiles are available.
+    pub fn time_percentile(&self, percentile: u8) -> Option<Duration> {
+        self.get_time_percentile(percentile).map(Duration::from)
+    }
+
+    /// Standard deviation of time per iteration across samples, if there were
+    /// at least two samples.
+    #[inline]
+    pub fn time_std_dev(&self) -> Option<Duration> {
+        self.time_std_dev.map(Duration::from)
+    }
+
+    /// Coefficient of variation of time per iteration across samples as a
+    /// percentage, which is the standard deviation relative to the mean.
+    ///
+    /// This is `None` if there were fewer than two samples or the mean is
+    /// zero.
+    pub fn time_cv(&self) -> Option<f64> {
+        let mean = self.time.mean.picos as f64;
+        let std_dev = self.time_std_dev?.picos as f64;
+        (mean > 0.0).then(|| std_dev / mean * 100.0)
+    }
+
+    /// Standard deviation of mean time per iteration between runs of the
+    /// suite, if it was run more than once with
+    /// [`Divan::runs`](crate::Divan::runs).
+    ///
+    /// This reflects run-to-run variance, such as from code layout and system
+    /// state, which samples within a single run underestimate.
+    #[inline]
+    pub fn run_std_dev(&self) -> Option<Duration> {
+        self.run_std_dev.map(Duration::from)
+    }
+
+    /// Mean time per iteration excluding the fastest and slowest 5% of
+    /// samples, if any samples were taken.
+    ///
+    /// Unlike [`Stats::time`], this is not skewed by rare outliers, such as
+    /// from preemption on shared machines.
+    #[inline]
+    pub fn trimmed_mean(&self) -> Option<Duration> {
+        self.trimmed_mean.map(Duration::from)
+    }
+
+    /// Mean time per iteration with the fastest and slowest 5% of samples
+    /// replaced by the fastest and slowest of the rest, if any samples were
+    /// taken.
+    ///
+    /// Like [`Stats::trimmed_mean`], this limits the effect of outliers while
+    /// still accounting for how many there were.
+    #[inline]
+    pub fn winsorized_mean(&self) -> Option<Duration> {
+        self.winsorized_mean.map(Duration::from)
+    }
+
+    /// Median absolute deviation of time per iteration across samples, if any
+    /// samples were taken.
+    ///
+    /// This is the median distance of samples from the median time. Unlike
+    /// [`Stats::time_std_dev`], it is not inflated by outliers, so together
+    /// with the median it estimates noise on shared machines.
+    #[inline]
+    pub fn time_mad(&self) -> Option<Duration> {
+        self.time_mad.map(Duration::from)
+    }
+
+    /// Mean time per iteration of the faster and slower modes, if sample times
+    /// cluster around two distinct values.
+    ///
+    /// This happens when a benchmark alternates between code paths or CPU
+    /// frequency states, in which case the mean and median describe neither
+    /// mode.
+    #[inline]
+    pub fn time_modes(&self) -> Option<[Duration; 2]> {
+        self.bimodality.map(|bimodality| bimodality.modes.map(Duration::from))
+    }
+
+    /// Time per iteration below which `quantile` of samples fall, if recorded
+    /// in a histogram with
+    /// [`Divan::hdr_histogram`](crate::Divan::hdr_histogram).
+    ///
+    /// This can be any quantile between 0 and 1, such as 0.999 for p99.9, and
+    /// is accurate to 3 significant digits even when statistics are streamed.
+    #[cfg(feature = "hdr")]
+    #[inline]
+    pub fn time_quantile(&self, quantile: f64) -> Option<Duration> {
+        self.time_histogram.as_ref().map(|histogram| histogram.quantile(quantile).into())
+    }
+
+    /// Standard deviation of per-sample throughput in counts per second for
+    /// each counter, keyed by `"bytes"`, `"chars"`, `"items"`, or the unit of
+    /// [`CustomCount`](crate::counter::CustomCount).
+    ///
+    /// Unlike count statistics, this reflects how stable throughput is across
+    /// samples.
+    pub fn throughput_std_dev(&self) -> impl Iterator<Item = (&'static str, f64)> + '_ {
+        let known = KnownCounterKind::ALL
+            .into_iter()
+            .filter_map(|kind| Some((kind.name(), self.throughput_std_dev[kind as usize]?)));
+        known.chain(self.custom_throughput_std_dev.iter().copied())
+    }
+
+    /// Bytes processed per iteration, if counted with
+    /// [`BytesCount`](crate::counter::BytesCount).
+    ///
+    /// Each statistic is the count of the sample that the corresponding time
+    /// statistic was taken from.
+    pub fn bytes(&self) -> Option<StatsSet<f64>> {
+        self.public_counts(KnownCounterKind::Bytes)
+    }
+
+    /// Characters processed per iteration, if counted with
+    /// [`CharsCount`](crate::counter::CharsCount).
+    pub fn chars(&self) -> Option<StatsSet<f64>> {
+        self.public_counts(KnownCounterKind::Chars)
+    }
+
+    /// Items processed per iteration, if counted with
+    /// [`ItemsCount`](crate::counter::ItemsCount).
+    pub fn items(&self) -> Option<StatsSet<f64>> {
+        self.public_counts(KnownCounterKind::Items)
+    }
+
+    /// Units processed per iteration by each
+    /// [`CustomCount`](crate::counter::CustomCount), keyed by unit name.
+    pub fn custom_counts(&self) -> impl Iterator<Item = (&'static str, StatsSet<f64>)> + '_ {
+        self.custom_counts.iter().map(|(unit, counts)| (*unit, counts.map(Count::as_f64)))
+    }
+
+    fn public_counts(&self, counter_kind: KnownCounterKind) -> Option<StatsSet<f64>> {
+        self.get_counts(counter_kind).map(|counts| counts.map(Count::as_f64))
+    }
+
+    /// Mean time per iteration spent generating inputs before the timed
+    /// section, if measured with `--stats gen` or JSON messages.
+    ///
+    /// When this approaches or exceeds the benchmarked time, generating inputs
+    /// dominates the run time of the benchmark.
+    #[inline]
+    pub fn gen_time(&self) -> Option<Duration> {
+        self.gen_time.map(Duration::from)
+    }
+
+    /// Mean time per iteration spent dropping outputs and inputs after the
+    /// timed section, if measured with `--stats drop`.
+    #[inline]
+    pub fn drop_time(&self) -> Option<Duration> {
+        self.drop_time.map(Duration::from)
+    }
+
+    /// Aggregate and per-thread time, if the benchmark was run with multiple
+    /// [`threads`](macro@crate::bench#threads).
+    #[inline]
+    pub fn threads(&self) -> Option<&ThreadStats> {
+        self.threads.as_ref()
+    }
+
+    pub(crate) fn get_counts(&self, counter_kind: KnownCounterKind) -> Option<&StatsSet<Count>> {
         self.counts[counter_kind as usize].as_ref()
     }
+
+    pub(crate) fn get_time_percentile(&self, percentile: u8) -> Option<FineDuration> {
+        let index = TIME_PERCENTILES.iter().position(|&p| p == percentile)?;
+        Some(self.time_percentiles[index])
+    }
+
+    pub(crate) fn get_hw_count(&self, kind: HwCounterKind) -> Option<f64> {
+        Some(self.hw_counts?[kind as usize])
+    }
+
+    /// Instructions retired per CPU cycle, if hardware counters were recorded.
+    pub(crate) fn instructions_per_cycle(&self) -> Option<f64> {
+        let cycles = self.get_hw_count(HwCounterKind::Cycles)?;
+        let instructions = self.get_hw_count(HwCounterKind::Instructions)?;
+        (cycles > 0.0).then(|| instructions / cycles)
+    }
+
+    /// Percentage of branches that were mispredicted, if hardware counters
+    /// were recorded.
+    pub(crate) fn branch_miss_rate(&self) -> Option<f64> {
+        let branches = self.get_hw_count(HwCounterKind::Branches)?;
+        let misses = self.get_hw_count(HwCounterKind::BranchMisses)?;
+        (branches > 0.0).then(|| misses / branches * 100.0)
+    }
+
+    pub(crate) fn get_io_count(&self, kind: IoCounterKind) -> Option<f64> {
+        Some(self.io_counts?[kind as usize])
+    }
 }
 
-#[derive(Debug)]
-pub(crate) struct StatsSet<T> {
+/// Time statistics of a benchmark run concurrently by multiple threads.
+///
+/// Each thread runs the same number of iterations per sample, and threads are
+/// synchronized to start each sample at the same time.
+#[derive(Clone, Debug)]
+pub struct ThreadStats {
+    /// Wall clock time per iteration for all threads to finish a sample.
+    pub(crate) wall_time: StatsSet<FineDuration>,
+
+    /// Time per iteration of each thread.
+    pub(crate) per_thread: Vec<StatsSet<FineDuration>>,
+}
+
+impl ThreadStats {
+    /// The number of threads that ran the benchmark.
+    #[inline]
+    pub fn thread_count(&self) -> usize {
+        self.per_thread.len()
+    }
+
+    /// Wall clock time per iteration, from when all threads started a sample
+    /// until the last thread finished it.
+    ///
+    /// Dividing the thread count by this gives the aggregate throughput of all
+    /// threads under contention.
+    pub fn wall_time(&self) -> StatsSet<Duration> {
+        self.wall_time.map(Duration::from)
+    }
+
+    /// Time per iteration of each thread, in a consistent order across
+    /// samples.
+    pub fn per_thread(&self) -> impl Iterator<Item = StatsSet<Duration>> + '_ {
+        self.per_thread.iter().map(|stats| stats.map(Duration::from))
+    }
+}
+
+/// Statistics of a measurement across samples.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StatsSet<T> {
     /// Associated with minimum amount of time taken by an iteration.
     pub fastest: T,
 
@@ -42,3 +421,193 @@ pub(crate) struct StatsSet<T> {
     /// Associated with average time taken by all iterations.
     pub mean: T,
 }
+
+impl<T> StatsSet<T> {
+    /// Applies `f` to each statistic.
+    #[inline]
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> StatsSet<U> {
+        StatsSet {
+            fastest: f(self.fastest),
+            slowest: f(self.slowest),
+            median: f(self.median),
+            mean: f(self.mean),
+        }
+    }
+}
+
+/// Returns the index of the value at `percentile` in a sorted slice of `len`
+/// values, using the nearest-rank method.
+pub(crate) fn percentile_index(len: usize, percentile: u8) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+
+    // Smallest rank such that `percentile`% of values are at or below it.
+    let rank = (len * percentile as usize).div_ceil(100);
+    Some(rank.clamp(1, len) - 1)
+}
+
+/// Returns the mean of `sorted` excluding the lowest and highest
+/// [`ROBUST_TRIM_PERCENT`]% of values, or `None` if it is empty.
+pub(crate) fn trimmed_mean(sorted: &[FineDuration]) -> Option<FineDuration> {
+    let trim = sorted.len() * ROBUST_TRIM_PERCENT / 100;
+    let kept = &sorted[trim..sorted.len() - trim];
+
+    let total: u128 = kept.iter().map(|time| time.picos).sum();
+    Some(FineDuration { picos: total.checked_div(kept.len() as u128)? })
+}
+
+/// Returns the mean of `sorted` with the lowest and highest
+/// [`ROBUST_TRIM_PERCENT`]% of values clamped to the nearest remaining value,
+/// or `None` if it is empty.
+pub(crate) fn winsorized_mean(sorted: &[FineDuration]) -> Option<FineDuration> {
+    let trim = sorted.len() * ROBUST_TRIM_PERCENT / 100;
+    let low = sorted.get(trim)?.picos;
+    let high = sorted[sorted.len() - 1 - trim].picos;
+
+    let total: u128 = sorted.iter().map(|time| time.picos.clamp(low, high)).sum();
+    Some(FineDuration { picos: total / sorted.len() as u128 })
+}
+
+/// Returns the median absolute deviation of `sorted` from its median, or `None`
+/// if it is empty.
+pub(crate) fn median_abs_dev(sorted: &[FineDuration]) -> Option<FineDuration> {
+    let median = |sorted: &[u128]| -> Option<u128> {
+        let middle = util::slice_middle(sorted);
+        middle.iter().sum::<u128>().checked_div(middle.len() as u128)
+    };
+
+    let picos: Vec<u128> = sorted.iter().map(|time| time.picos).collect();
+    let center = median(&picos)?;
+
+    let mut deviations: Vec<u128> = picos.iter().map(|&picos| picos.abs_diff(center)).collect();
+    deviations.sort_unstable();
+
+    Some(FineDuration { picos: median(&deviations)? })
+}
+
+/// Returns the sample standard deviation of `values`, or `None` if there are
+/// fewer than two.
+pub(crate) fn std_dev(values: impl IntoIterator<Item = f64>) -> Option<f64> {
+    let mut count = 0.0;
+    let mut mean = 0.0;
+    let mut sq_diff_sum = 0.0;
+
+    // Welford's algorithm avoids cancellation with large values.
+    for value in values {
+        count += 1.0;
+        let diff = value - mean;
+        mean += diff / count;
+        sq_diff_sum += diff * (value - mean);
+    }
+
+    (count >= 2.0).then(|| (sq_diff_sum / (count - 1.0)).sqrt())
+}
+
+/// Returns the geometric mean of non-zero `times`, or `None` if there are
+/// none.
+///
+/// Unlike the arithmetic mean, this is not dominated by the slowest times, so
+/// each benchmark contributes equally to changes in the result.
+pub(crate) fn geometric_mean(
+    times: impl IntoIterator<Item = FineDuration>,
+) -> Option<FineDuration> {
+    let mut log_sum = 0.0;
+    let mut count = 0;
+
+    for time in times {
+        if !time.is_zero() {
+            log_sum += (time.picos as f64).ln();
+            count += 1;
+        }
+    }
+
+    if count == 0 {
+        return None;
+    }
+
+    Some(FineDuration { picos: (log_sum / count as f64).exp().round() as u128 })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::time::FineDuration;
+
+    #[test]
+    fn geometric_mean() {
+        #[track_caller]
+        fn test(picos: &[u128], expected: Option<u128>) {
+            let times = picos.iter().map(|&picos| FineDuration { picos });
+            let expected = expected.map(|picos| FineDuration { picos });
+            assert_eq!(super::geometric_mean(times), expected);
+        }
+
+        test(&[], None);
+        test(&[0], None);
+        test(&[5], Some(5));
+        test(&[1, 100], Some(10));
+        test(&[2, 0, 8], Some(4));
+    }
+
+    #[test]
+    fn std_dev() {
+        assert_eq!(super::std_dev([]), None);
+        assert_eq!(super::std_dev([5.0]), None);
+        assert_eq!(super::std_dev([3.0, 3.0, 3.0]), Some(0.0));
+        assert_eq!(
+            super::std_dev([2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]),
+            Some(2.138089935299395)
+        );
+    }
+
+    #[test]
+    fn percentile_index() {
+        assert_eq!(super::percentile_index(0, 50), None);
+        assert_eq!(super::percentile_index(1, 99), Some(0));
+        assert_eq!(super::percentile_index(100, 0), Some(0));
+        assert_eq!(super::percentile_index(100, 75), Some(74));
+        assert_eq!(super::percentile_index(100, 99), Some(98));
+        assert_eq!(super::percentile_index(100, 100), Some(99));
+        assert_eq!(super::percentile_index(10, 95), Some(9));
+    }
+
+    #[test]
+    fn median_abs_dev() {
+        #[track_caller]
+        fn test(picos: &[u128], expected: Option<u128>) {
+            let times: Vec<_> = picos.iter().map(|&picos| FineDuration { picos }).collect();
+            let mad = super::median_abs_dev(&times).map(|time| time.picos);
+            assert_eq!(mad, expected);
+        }
+
+        test(&[], None);
+        test(&[5], Some(0));
+        test(&[1, 2, 3], Some(1));
+
+        // Outliers do not inflate the deviation.
+        test(&[1, 1, 2, 2, 4, 6, 9_000], Some(1));
+    }
+
+    #[test]
+    fn robust_means() {
+        #[track_caller]
+        fn test(picos: &[u128], trimmed: Option<u128>, winsorized: Option<u128>) {
+            let times: Vec<_> = picos.iter().map(|&picos| FineDuration { picos }).collect();
+            let picos = |time: Option<FineDuration>| time.map(|time| time.picos);
+            assert_eq!(picos(super::trimmed_mean(&times)), trimmed);
+            assert_eq!(picos(super::winsorized_mean(&times)), winsorized);
+        }
+
+        test(&[], None, None);
+        test(&[7], Some(7), Some(7));
+
+        // Fewer than 20 samples have none trimmed.
+        test(&[1, 2, 3, 1000], Some(251), Some(251));
+
+        // One of 20 samples is trimmed from each end.
+        let mut picos = vec![10; 18];
+        picos.insert(0, 0);
+        picos.push(1000);
+        test(&picos, Some(10), Some(10));
+    }
+}
@@ -0,0 +1,141 @@
+//! Detecting sample times that cluster around two distinct values, such as
+//! from alternating code paths or CPU frequency states.
+//!
+//! Sorted samples are split in two where the variance between the halves is
+//! greatest, like 1D k-means with two clusters. The split is considered bimodal
+//! if both halves are substantial and there is a valley between their means:
+//! the middle half of the range between the means must be much sparser than
+//! equally wide ranges around each mean. Unimodal distributions fail this
+//! because their densest region is between the halves' means.
+//!
+//! Requiring a minimum fraction of samples in each mode keeps long tails of
+//! outliers from being reported as a second mode.
+
+use crate::time::FineDuration;
+
+/// The minimum number of samples needed to detect modes.
+const MIN_SAMPLES: usize = 20;
+
+/// The minimum fraction of samples in each mode.
+const MIN_MODE_FRACTION: f64 = 0.1;
+
+/// The maximum number of samples in the valley between modes, relative to the
+/// number around the sparser mode.
+const MAX_VALLEY_RATIO: f64 = 0.6;
+
+/// The minimum relative difference between modes, which keeps samples that
+/// differ by a timer tick from being considered bimodal.
+const MIN_RELATIVE_GAP: f64 = 0.05;
+
+/// Two modes of sample times.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Bimodality {
+    /// Mean time of the faster and slower modes.
+    pub modes: [FineDuration; 2],
+
+    /// The fraction of samples in the faster mode.
+    pub fast_fraction: f64,
+}
+
+/// Returns the modes of `sorted` if it appears bimodal.
+pub(crate) fn detect_bimodality(sorted: &[FineDuration]) -> Option<Bimodality> {
+    let len = sorted.len();
+    if len < MIN_SAMPLES {
+        return None;
+    }
+
+    let values: Vec<f64> = sorted.iter().map(|time| time.picos as f64).collect();
+
+    // Prefix sums of values, for the mean of each split in constant time.
+    let mut sums = vec![0.0; len + 1];
+    for (i, &value) in values.iter().enumerate() {
+        sums[i + 1] = sums[i] + value;
+    }
+
+    let mean = |start: usize, end: usize| (sums[end] - sums[start]) / (end - start) as f64;
+
+    let min_count = ((len as f64 * MIN_MODE_FRACTION).ceil() as usize).max(1);
+
+    // Split before the index that maximizes variance between the halves.
+    let split = (min_count..=len - min_count)
+        .filter(|&split| values[split - 1] < values[split])
+        .max_by(|&a, &b| {
+            let between = |split: usize| {
+                let weight = split as f64 * (len - split) as f64;
+                weight * (mean(split, len) - mean(0, split)).powi(2)
+            };
+            between(a).total_cmp(&between(b))
+        })?;
+
+    let fast_mean = mean(0, split);
+    let slow_mean = mean(split, len);
+    let gap = slow_mean - fast_mean;
+
+    if gap < fast_mean * MIN_RELATIVE_GAP {
+        return None;
+    }
+
+    // Number of samples in `start..end`.
+    let count_in = |start: f64, end: f64| -> usize {
+        values.partition_point(|&value| value < end)
+            - values.partition_point(|&value| value < start)
+    };
+
+    let quarter = gap / 4.0;
+    let fast_peak = count_in(fast_mean - quarter, fast_mean + quarter);
+    let slow_peak = count_in(slow_mean - quarter, slow_mean + quarter);
+    let valley = count_in(fast_mean + quarter, slow_mean - quarter);
+
+    // A mean between distant outliers and the rest is not a mode.
+    let peak = fast_peak.min(slow_peak);
+    if peak < min_count / 2 || valley as f64 > peak as f64 * MAX_VALLEY_RATIO {
+        return None;
+    }
+
+    Some(Bimodality {
+        modes: [fast_mean, slow_mean].map(|picos| FineDuration { picos: picos.round() as u128 }),
+        fast_fraction: split as f64 / len as f64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(picos: impl IntoIterator<Item = u128>) -> Option<Bimodality> {
+        let mut times: Vec<_> = picos.into_iter().map(|picos| FineDuration { picos }).collect();
+        times.sort_unstable();
+        detect_bimodality(&times)
+    }
+
+    #[test]
+    fn bimodal() {
+        // Alternating between about 100 and 200.
+        let bimodality =
+            detect((0..60).map(|i| if i % 3 == 0 { 200 + i % 5 } else { 100 + i % 7 }))
+                .expect("expected bimodal");
+
+        let [fast, slow] = bimodality.modes.map(|time| time.picos);
+        assert!((100..110).contains(&fast), "{fast}");
+        assert!((200..205).contains(&slow), "{slow}");
+        assert!((bimodality.fast_fraction - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unimodal() {
+        // Too few samples.
+        assert_eq!(detect([100, 100, 200, 200]), None);
+
+        // Evenly spread.
+        assert_eq!(detect((0..100).map(|i| 100 + i)), None);
+
+        // Roughly normal, from summing uniform values.
+        assert_eq!(detect((0..200).map(|i: u128| 1_000 + (i * 7) % 100 + (i * 13) % 100)), None);
+
+        // A few outliers are a tail rather than a mode.
+        assert_eq!(detect((0..100).map(|i| if i < 3 { 1_000 } else { 100 + i % 10 })), None);
+
+        // Adjacent timer ticks.
+        assert_eq!(detect((0..100).map(|i| 1_000 + i % 2)), None);
+    }
+}
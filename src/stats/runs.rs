@@ -0,0 +1,226 @@
+//! Combining statistics of a benchmark from repeated runs of the suite.
+
+use crate::{
+    alloc::AllocStats,
+    stats::{Stats, StatsSet, ThreadStats, TimeHistogram, TIME_PERCENTILES},
+    time::FineDuration,
+    util,
+};
+
+/// Combines the statistics of a benchmark from each of `runs`, returning
+/// `None` if there are none.
+///
+/// Times are combined from each run's statistics, since samples of different
+/// runs may have different sizes. The standard deviation of time includes the
+/// variance between runs, which is also kept separately as
+/// [`Stats::run_std_dev`].
+pub(crate) fn merge_runs(runs: Vec<Stats>) -> Option<Stats> {
+    let first = runs.first()?;
+    let run_count = runs.len() as f64;
+
+    let mean_f64 = |values: &mut dyn Iterator<Item = f64>| values.sum::<f64>() / run_count;
+
+    let mean_time = |times: &mut dyn Iterator<Item = FineDuration>| {
+        let total: u128 = times.map(|time| time.picos).sum();
+        FineDuration { picos: total / runs.len() as u128 }
+    };
+
+    let time = merge_time_sets(runs.iter().map(|stats| stats.time));
+
+    // Histograms of all runs give percentiles of all samples.
+    let time_histogram =
+        TimeHistogram::merge(runs.iter().map(|stats| stats.time_histogram.as_ref()));
+
+    let time_percentiles = match &time_histogram {
+        Some(histogram) => TIME_PERCENTILES.map(|percentile| histogram.percentile(percentile)),
+        None => std::array::from_fn(|i| {
+            mean_time(&mut runs.iter().map(|stats| stats.time_percentiles[i]))
+        }),
+    };
+
+    // Pooled variance is the mean of each run's variance plus the variance of
+    // run means, weighted by sample count.
+    let sample_count: u32 = runs.iter().map(|stats| stats.sample_count).sum();
+    let time_std_dev = (sample_count > 1).then(|| {
+        let grand_mean = time.mean.picos as f64;
+        let sum_sq: f64 = runs
+            .iter()
+            .map(|stats| {
+                let std_dev = stats.time_std_dev.map_or(0.0, |time| time.picos as f64);
+                let diff = stats.time.mean.picos as f64 - grand_mean;
+                stats.sample_count as f64 * (std_dev * std_dev + diff * diff)
+            })
+            .sum();
+        FineDuration { picos: (sum_sq / sample_count as f64).sqrt().round() as u128 }
+    });
+
+    let run_std_dev = crate::stats::std_dev(runs.iter().map(|stats| stats.time.mean.picos as f64))
+        .map(|picos| FineDuration { picos: picos.round() as u128 });
+
+    let throughput_std_dev = std::array::from_fn(|i| {
+        let std_devs: Vec<f64> =
+            runs.iter().filter_map(|stats| stats.throughput_std_dev[i]).collect();
+        (!std_devs.is_empty()).then(|| std_devs.iter().sum::<f64>() / std_devs.len() as f64)
+    });
+
+    let custom_throughput_std_dev = (first.custom_throughput_std_dev.iter())
+        .map(|&(unit, _)| {
+            let mut std_devs = runs.iter().map(|stats| {
+                (stats.custom_throughput_std_dev.iter())
+                    .find(|&&(u, _)| u == unit)
+                    .map_or(0.0, |&(_, std_dev)| std_dev)
+            });
+            (unit, mean_f64(&mut std_devs))
+        })
+        .collect();
+
+    let hw_counts =
+        runs.iter().map(|stats| stats.hw_counts).collect::<Option<Vec<_>>>().map(|hw_counts| {
+            std::array::from_fn(|i| mean_f64(&mut hw_counts.iter().map(|counts| counts[i])))
+        });
+
+    // Every run records the same events.
+    let perf_counts = (first.perf_counts.iter().enumerate())
+        .map(|(i, &(event, _))| {
+            let mut counts = runs.iter().map(|stats| stats.perf_counts.get(i).map_or(0.0, |c| c.1));
+            (event, mean_f64(&mut counts))
+        })
+        .collect();
+
+    let io_counts =
+        runs.iter().map(|stats| stats.io_counts).collect::<Option<Vec<_>>>().map(|io_counts| {
+            std::array::from_fn(|i| mean_f64(&mut io_counts.iter().map(|counts| counts[i])))
+        });
+
+    let allocs = runs.iter().map(|stats| stats.allocs).collect::<Option<Vec<_>>>().map(|allocs| {
+        AllocStats {
+            alloc_count: mean_f64(&mut allocs.iter().map(|allocs| allocs.alloc_count)),
+            alloc_bytes: mean_f64(&mut allocs.iter().map(|allocs| allocs.alloc_bytes)),
+            dealloc_count: mean_f64(&mut allocs.iter().map(|allocs| allocs.dealloc_count)),
+            peak_bytes: allocs.iter().map(|allocs| allocs.peak_bytes).max().unwrap_or_default(),
+        }
+    });
+
+    let mean_time_of = |time: fn(&Stats) -> Option<FineDuration>| {
+        runs.iter()
+            .map(time)
+            .collect::<Option<Vec<_>>>()
+            .map(|times| mean_time(&mut times.into_iter()))
+    };
+    let trimmed_mean = mean_time_of(|stats| stats.trimmed_mean);
+    let winsorized_mean = mean_time_of(|stats| stats.winsorized_mean);
+    let time_mad = mean_time_of(|stats| stats.time_mad);
+
+    // Modes of separate runs cannot be combined, so the last run's are kept
+    // like its samples.
+    let bimodality = runs.last().and_then(|stats| stats.bimodality);
+    let gen_time = mean_time_of(|stats| stats.gen_time);
+    let drop_time = mean_time_of(|stats| stats.drop_time);
+
+    let threads = runs.iter().map(|stats| stats.threads.as_ref()).collect::<Option<Vec<_>>>().map(
+        |threads| ThreadStats {
+            wall_time: merge_time_sets(threads.iter().map(|threads| threads.wall_time)),
+            per_thread: (0..threads[0].per_thread.len())
+                .map(|i| {
+                    merge_time_sets(
+                        threads.iter().filter_map(|threads| threads.per_thread.get(i).copied()),
+                    )
+                })
+                .collect(),
+        },
+    );
+
+    let iter_count = runs.iter().map(|stats| stats.iter_count).sum();
+
+    // Counters describe inputs, which are the same for every run.
+    let first = runs.into_iter().next()?;
+
+    Some(Stats {
+        sample_count,
+        iter_count,
+        time,
+        time_percentiles,
+        counts: first.counts,
+        custom_counts: first.custom_counts,
+        time_std_dev,
+        run_std_dev,
+        trimmed_mean,
+        winsorized_mean,
+        time_mad,
+        bimodality,
+        time_histogram,
+        throughput_std_dev,
+        custom_throughput_std_dev,
+        hw_counts,
+        perf_counts,
+        threads,
+        allocs,
+        io_counts,
+        gen_time,
+        drop_time,
+    })
+}
+
+/// Combines time statistics of separate runs, using the median of medians and
+/// the mean of means.
+fn merge_time_sets(sets: impl Iterator<Item = StatsSet<FineDuration>>) -> StatsSet<FineDuration> {
+    let sets: Vec<_> = sets.collect();
+
+    let mean_of = |times: &[FineDuration]| {
+        let total: u128 = times.iter().map(|time| time.picos).sum();
+        FineDuration { picos: total / times.len().max(1) as u128 }
+    };
+
+    let mut medians: Vec<FineDuration> = sets.iter().map(|set| set.median).collect();
+    medians.sort_unstable();
+
+    let means: Vec<FineDuration> = sets.iter().map(|set| set.mean).collect();
+
+    StatsSet {
+        fastest: sets.iter().map(|set| set.fastest).min().unwrap_or_default(),
+        slowest: sets.iter().map(|set| set.slowest).max().unwrap_or_default(),
+        median: mean_of(util::slice_middle(&medians)),
+        mean: mean_of(&means),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_runs() {
+        let run = |fastest: u128, mean: u128, slowest: u128| {
+            let time = |picos| FineDuration { picos };
+            Stats {
+                time_std_dev: Some(time(0)),
+                ..Stats::test_default(
+                    10,
+                    100,
+                    StatsSet {
+                        fastest: time(fastest),
+                        slowest: time(slowest),
+                        median: time(mean),
+                        mean: time(mean),
+                    },
+                )
+            }
+        };
+
+        assert!(super::merge_runs(Vec::new()).is_none());
+
+        let stats = super::merge_runs(vec![run(5, 10, 15), run(15, 20, 25)]).unwrap();
+
+        assert_eq!(stats.sample_count, 20);
+        assert_eq!(stats.iter_count, 200);
+        assert_eq!(
+            stats.time.map(|time| time.picos),
+            StatsSet { fastest: 5, slowest: 25, median: 15, mean: 15 }
+        );
+
+        // Runs differ by 5 from the grand mean, with no variance within runs.
+        assert_eq!(stats.time_std_dev, Some(FineDuration { picos: 5 }));
+        // Sample standard deviation of 10 and 20.
+        assert_eq!(stats.run_std_dev, Some(FineDuration { picos: 7 }));
+    }
+}
@@ -1,6 +1,11 @@
 use crate::{
-    counter::KnownCounterKind,
+    alloc::{AllocCounts, AllocStats},
+    counter::{CountTotal, KnownCounterKind},
+    hw::{HwCounterKind, HwCounts, PerfCounts, MAX_PERF_EVENTS},
+    io_counters::{IoCounterKind, IoCounts},
+    stats::{StatsSet, StreamingStats, ThreadStats, TimeHistogram},
     time::{FineDuration, Timer, Timestamp},
+    util,
 };
 
 /// Processed measurement.
@@ -10,6 +15,21 @@ pub(crate) struct Sample {
     /// This is gotten from [`RawSample`] with:
     /// `end.duration_since(start, timer).clamp_to(timer.precision())`.
     pub duration: FineDuration,
+
+    /// Counter totals over all iterations, for counters in use.
+    pub counter_totals: [Option<CountTotal>; KnownCounterKind::COUNT],
+
+    /// Hardware event counts over all iterations, if enabled.
+    pub hw_counts: Option<HwCounts>,
+
+    /// Counts of `--perf-events` over all iterations, if enabled.
+    pub perf_counts: Option<PerfCounts>,
+
+    /// Allocations over all iterations, if `AllocProfiler` is used.
+    pub alloc_counts: Option<AllocCounts>,
+
+    /// I/O counts over all iterations, if enabled.
+    pub io_counts: Option<IoCounts>,
 }
 
 /// Unprocessed measurement.
@@ -20,7 +40,21 @@ pub(crate) struct RawSample {
     pub start: Timestamp,
     pub end: Timestamp,
     pub timer: Timer,
-    pub counter_totals: [u128; KnownCounterKind::COUNT],
+    pub counter_totals: [CountTotal; KnownCounterKind::COUNT],
+    pub hw_counts: Option<HwCounts>,
+    pub perf_counts: Option<PerfCounts>,
+    pub alloc_counts: Option<AllocCounts>,
+    pub io_counts: Option<IoCounts>,
+
+    /// The duration reported by the benchmark itself, which takes the place
+    /// of `end - start`.
+    pub custom_duration: Option<FineDuration>,
+
+    /// Time spent generating inputs before `start`, if measured.
+    pub gen_duration: Option<FineDuration>,
+
+    /// Time spent dropping outputs and inputs after `end`, if measured.
+    pub drop_duration: Option<FineDuration>,
 }
 
 /// Multi-thread measurement.
@@ -32,18 +66,29 @@ pub(crate) struct ThreadSample {
     /// In other words, it is the time spent between the timing section
     /// barriers.
     // TODO: Report counter throughput.
-    #[allow(dead_code)]
     pub total_wall_time: FineDuration,
 }
 
 impl RawSample {
-    /// Simply computes `end - start` without clamping to precision.
+    /// Simply computes `end - start` without clamping to precision, unless the
+    /// benchmark reported its own duration.
     #[inline]
     pub fn duration(&self) -> FineDuration {
-        self.end.duration_since(self.start, self.timer)
+        match self.custom_duration {
+            Some(duration) => duration,
+            None => self.end.duration_since(self.start, self.timer),
+        }
     }
 }
 
+/// The number of samples after which statistics are computed incrementally,
+/// unless `streaming_stats` is set.
+pub(crate) const STREAMING_SAMPLE_THRESHOLD: usize = 1_000_000;
+
+/// The number of samples kept when streaming statistics, unless
+/// `reservoir_size` is set.
+pub(crate) const DEFAULT_RESERVOIR_SIZE: usize = 10_000;
+
 /// [`Sample`] collection.
 #[derive(Default)]
 pub(crate) struct SampleCollection {
@@ -51,13 +96,39 @@ pub(crate) struct SampleCollection {
     pub sample_size: u32,
 
     /// Collected samples.
+    ///
+    /// This is empty once `streaming` is used.
     pub all: Vec<Sample>,
 
+    /// The number of samples after which to use `streaming` instead of `all`.
+    pub streaming_threshold: Option<usize>,
+
+    /// The number of samples kept by `streaming` for computing percentiles and
+    /// reporting.
+    pub reservoir_size: usize,
+
+    /// The number of threads recording each sample set, whose samples are
+    /// pushed in the same thread order.
+    pub thread_count: usize,
+
+    /// Aggregates of samples once there are too many to store.
+    pub streaming: Option<Box<StreamingStats>>,
+
+    /// Histogram of every sample's time per iteration, if enabled.
+    pub histogram: Option<TimeHistogram>,
+
     /// Collected multi-thread data.
     ///
     /// To associate this with samples in `all`, stride over `all` with the
     /// thread count.
     pub threads: Vec<ThreadSample>,
+
+    /// Total time spent generating inputs of collected samples, if measured.
+    pub gen_duration: Option<FineDuration>,
+
+    /// Total time spent dropping outputs and inputs of collected samples, if
+    /// measured.
+    pub drop_duration: Option<FineDuration>,
 }
 
 impl SampleCollection {
@@ -66,6 +137,82 @@ impl SampleCollection {
     pub fn clear(&mut self) {
         self.all.clear();
         self.threads.clear();
+        self.streaming = None;
+        if self.histogram.is_some() {
+            self.histogram = TimeHistogram::new();
+        }
+        self.gen_duration = None;
+        self.drop_duration = None;
+    }
+
+    /// Records `sample`, switching to streaming aggregates once there are
+    /// `streaming_threshold` samples.
+    pub fn push(&mut self, sample: Sample) {
+        if let Some(histogram) = &mut self.histogram {
+            histogram.record(sample.duration / self.sample_size);
+        }
+
+        if let Some(streaming) = &mut self.streaming {
+            streaming.push(sample);
+            return;
+        }
+
+        if self.streaming_threshold.is_some_and(|threshold| self.all.len() >= threshold) {
+            let mut streaming =
+                Box::new(StreamingStats::new(self.reservoir_size, self.thread_count));
+
+            // Release stored samples.
+            for sample in std::mem::take(&mut self.all).into_iter().chain(Some(sample)) {
+                streaming.push(sample);
+            }
+            if let Some(threads) = &mut streaming.threads {
+                for thread_sample in std::mem::take(&mut self.threads) {
+                    threads.wall_time.push(thread_sample.total_wall_time);
+                }
+            }
+            self.threads = Vec::new();
+            self.streaming = Some(streaming);
+        } else {
+            self.all.push(sample);
+        }
+    }
+
+    /// Records the wall clock time of a multi-thread sample set, before its
+    /// samples are pushed.
+    pub fn push_thread_sample(&mut self, sample: ThreadSample) {
+        match &mut self.streaming {
+            Some(streaming) => {
+                if let Some(threads) = &mut streaming.threads {
+                    threads.wall_time.push(sample.total_wall_time);
+                }
+            }
+            None => self.threads.push(sample),
+        }
+    }
+
+    /// Returns the samples to report individually, which are the reservoir of
+    /// kept samples when streaming.
+    #[inline]
+    pub fn reported_samples(&self) -> &[Sample] {
+        match &self.streaming {
+            Some(streaming) => &streaming.reservoir,
+            None => &self.all,
+        }
+    }
+
+    /// Returns whether samples are aggregated instead of stored.
+    #[inline]
+    pub fn is_streaming(&self) -> bool {
+        self.streaming.is_some()
+    }
+
+    /// Returns the number of recorded samples.
+    #[inline]
+    pub fn len(&self) -> usize {
+        match &self.streaming {
+            Some(streaming) => streaming.count,
+            None => self.all.len(),
+        }
     }
 
     /// Computes the total number of iterations across all samples.
@@ -73,13 +220,163 @@ impl SampleCollection {
     /// We use `u64` in case sample count and sizes are huge.
     #[inline]
     pub fn iter_count(&self) -> u64 {
-        self.sample_size as u64 * self.all.len() as u64
+        self.sample_size as u64 * self.len() as u64
+    }
+
+    /// Computes the mean time spent generating inputs per iteration, if
+    /// measured.
+    #[inline]
+    pub fn gen_time_per_iter(&self) -> Option<FineDuration> {
+        self.per_iter(self.gen_duration?)
+    }
+
+    /// Computes the mean time spent dropping outputs and inputs per iteration,
+    /// if measured.
+    #[inline]
+    pub fn drop_time_per_iter(&self) -> Option<FineDuration> {
+        self.per_iter(self.drop_duration?)
+    }
+
+    #[inline]
+    fn per_iter(&self, total: FineDuration) -> Option<FineDuration> {
+        Some(FineDuration { picos: total.picos.checked_div(self.iter_count() as u128)? })
     }
 
     /// Computes the total time across all samples.
     #[inline]
     pub fn total_duration(&self) -> FineDuration {
-        FineDuration { picos: self.all.iter().map(|s| s.duration.picos).sum() }
+        match &self.streaming {
+            Some(streaming) => streaming.total_duration,
+            None => FineDuration { picos: self.all.iter().map(|s| s.duration.picos).sum() },
+        }
+    }
+
+    /// Computes the mean hardware event counts per iteration, if recorded for
+    /// all samples.
+    pub fn hw_counts_per_iter(&self) -> Option<[f64; HwCounterKind::COUNT]> {
+        let iter_count = self.iter_count();
+        if iter_count == 0 {
+            return None;
+        }
+
+        let totals = match &self.streaming {
+            Some(streaming) => streaming.hw_totals?,
+            None => {
+                let mut totals = [0u128; HwCounterKind::COUNT];
+                for sample in &self.all {
+                    for (total, count) in totals.iter_mut().zip(sample.hw_counts?.0) {
+                        *total += count as u128;
+                    }
+                }
+                totals
+            }
+        };
+
+        Some(totals.map(|total| total as f64 / iter_count as f64))
+    }
+
+    /// Computes the mean counts of `--perf-events` per iteration, if recorded
+    /// for all samples.
+    pub fn perf_counts_per_iter(&self) -> Option<[f64; MAX_PERF_EVENTS]> {
+        let iter_count = self.iter_count();
+        if iter_count == 0 {
+            return None;
+        }
+
+        let totals = match &self.streaming {
+            Some(streaming) => streaming.perf_totals?,
+            None => {
+                let mut totals = [0u128; MAX_PERF_EVENTS];
+                for sample in &self.all {
+                    for (total, count) in totals.iter_mut().zip(sample.perf_counts?.0) {
+                        *total += count as u128;
+                    }
+                }
+                totals
+            }
+        };
+
+        Some(totals.map(|total| total as f64 / iter_count as f64))
+    }
+
+    /// Computes the mean I/O counts per iteration, if recorded for all
+    /// samples.
+    pub fn io_counts_per_iter(&self) -> Option<[f64; IoCounterKind::COUNT]> {
+        let iter_count = self.iter_count();
+        if iter_count == 0 {
+            return None;
+        }
+
+        let totals = match &self.streaming {
+            Some(streaming) => streaming.io_totals?,
+            None => {
+                let mut totals = [0u128; IoCounterKind::COUNT];
+                for sample in &self.all {
+                    for (total, count) in totals.iter_mut().zip(sample.io_counts?.0) {
+                        *total += count as u128;
+                    }
+                }
+                totals
+            }
+        };
+
+        Some(totals.map(|total| total as f64 / iter_count as f64))
+    }
+
+    /// Computes allocation statistics, if recorded for all samples.
+    pub fn alloc_stats(&self) -> Option<AllocStats> {
+        let iter_count = self.iter_count();
+        if iter_count == 0 {
+            return None;
+        }
+
+        let totals = match &self.streaming {
+            Some(streaming) => streaming.alloc_totals?,
+            None => {
+                let mut totals = AllocCounts::default();
+                for sample in &self.all {
+                    let counts = sample.alloc_counts?;
+                    totals.alloc_count += counts.alloc_count;
+                    totals.alloc_bytes += counts.alloc_bytes;
+                    totals.dealloc_count += counts.dealloc_count;
+                    totals.peak_bytes = totals.peak_bytes.max(counts.peak_bytes);
+                }
+                totals
+            }
+        };
+
+        let per_iter = |total: u64| total as f64 / iter_count as f64;
+
+        Some(AllocStats {
+            alloc_count: per_iter(totals.alloc_count),
+            alloc_bytes: per_iter(totals.alloc_bytes),
+            dealloc_count: per_iter(totals.dealloc_count),
+            peak_bytes: totals.peak_bytes,
+        })
+    }
+
+    /// Computes aggregate and per-thread time statistics, if samples were
+    /// recorded across multiple threads.
+    pub fn thread_stats(&self) -> Option<ThreadStats> {
+        let thread_count = self.all.len().checked_div(self.threads.len())?;
+        if thread_count <= 1 {
+            return None;
+        }
+
+        let wall_time = per_iter_stats(
+            self.threads.iter().map(|sample| sample.total_wall_time).collect(),
+            self.sample_size,
+        );
+
+        // Samples of each sample set are in the same thread order.
+        let per_thread = (0..thread_count)
+            .map(|thread| {
+                let durations = self.all.iter().skip(thread).step_by(thread_count);
+                per_iter_stats(durations.map(|s| s.duration).collect(), self.sample_size)
+            })
+            .collect();
+
+        Some(ThreadStats { wall_time, per_thread })
     }
 
     /// Returns all samples sorted by duration.
@@ -90,3 +387,28 @@ impl SampleCollection {
         result
     }
 }
+
+/// Computes time per iteration statistics from sample durations.
+fn per_iter_stats(mut durations: Vec<FineDuration>, sample_size: u32) -> StatsSet<FineDuration> {
+    durations.sort_unstable();
+
+    let total: u128 = durations.iter().map(|d| d.picos).sum();
+    let mean = FineDuration {
+        picos: total.checked_div(durations.len() as u128 * sample_size as u128).unwrap_or_default(),
+    };
+
+    let median_durations = util::slice_middle(&durations);
+    let median = if median_durations.is_empty() {
+        FineDuration::default()
+    } else {
+        let sum: u128 = median_durations.iter().map(|d| d.picos).sum();
+        FineDuration { picos: sum / median_durations.len() as u128 } / sample_size
+    };
+
+    StatsSet {
+        fastest: durations.first().map(|&d| d / sample_size).unwrap_or_default(),
+        slowest: durations.last().map(|&d| d / sample_size).unwrap_or_default(),
+        median,
+        mean,
+    }
+}
@@ -0,0 +1,122 @@
+use crate::time::FineDuration;
+
+/// The p-value below which a change is considered significant.
+pub(crate) const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// Returns the two-sided p-value of the Mann-Whitney U test for whether `a`
+/// and `b` come from the same distribution, or `None` if either is empty.
+///
+/// This uses the normal approximation with tie and continuity corrections,
+/// which is accurate for the sample counts that benchmarks typically have.
+/// Unlike Welch's t-test, it does not assume that times are normally
+/// distributed, which they rarely are due to outliers.
+pub(crate) fn mann_whitney_u(a: &[FineDuration], b: &[FineDuration]) -> Option<f64> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+
+    let mut combined: Vec<(FineDuration, bool)> =
+        a.iter().map(|&time| (time, true)).chain(b.iter().map(|&time| (time, false))).collect();
+    combined.sort_unstable_by_key(|&(time, _)| time);
+
+    // Sum of `a`'s ranks, where tied values share their average rank.
+    let mut rank_sum_a = 0.0;
+
+    // Sum of `t³ - t` over groups of `t` tied values.
+    let mut tie_sum = 0.0;
+
+    let mut start = 0;
+    while start < combined.len() {
+        let time = combined[start].0;
+        let end = start + combined[start..].iter().take_while(|&&(t, _)| t == time).count();
+
+        let tie_count = (end - start) as f64;
+        let average_rank = (start + end + 1) as f64 / 2.0;
+
+        let count_a = combined[start..end].iter().filter(|&&(_, is_a)| is_a).count();
+        rank_sum_a += average_rank * count_a as f64;
+        tie_sum += tie_count.powi(3) - tie_count;
+
+        start = end;
+    }
+
+    let n_a = a.len() as f64;
+    let n_b = b.len() as f64;
+    let n = n_a + n_b;
+
+    let u = rank_sum_a - n_a * (n_a + 1.0) / 2.0;
+    let u_mean = n_a * n_b / 2.0;
+    let u_variance = n_a * n_b / 12.0 * ((n + 1.0) - tie_sum / (n * (n - 1.0)));
+
+    // All values are equal.
+    if u_variance <= 0.0 {
+        return Some(1.0);
+    }
+
+    let z = ((u - u_mean).abs() - 0.5).max(0.0) / u_variance.sqrt();
+
+    Some(erfc(z / std::f64::consts::SQRT_2).min(1.0))
+}
+
+/// Complementary error function, with a fractional error below 1.2e-7.
+///
+/// See "Numerical Recipes in C", 2nd edition, section 6.2.
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+
+    let poly = -1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807
+                            + t * (-1.13520398
+                                + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+
+    let result = t * (-z * z + poly).exp();
+
+    if x >= 0.0 {
+        result
+    } else {
+        2.0 - result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn times(picos: impl IntoIterator<Item = u128>) -> Vec<FineDuration> {
+        picos.into_iter().map(|picos| FineDuration { picos }).collect()
+    }
+
+    #[test]
+    fn erfc() {
+        assert!((super::erfc(0.0) - 1.0).abs() < 1e-7);
+        assert!((super::erfc(1.0) - 0.157_299_207).abs() < 1e-7);
+        assert!((super::erfc(-1.0) - 1.842_700_793).abs() < 1e-7);
+    }
+
+    #[test]
+    fn mann_whitney_u() {
+        assert_eq!(super::mann_whitney_u(&[], &times([1])), None);
+
+        // Identical distributions.
+        let a = times(1..=20);
+        assert_eq!(super::mann_whitney_u(&a, &a), Some(1.0));
+        assert_eq!(super::mann_whitney_u(&times([5; 10]), &times([5; 10])), Some(1.0));
+
+        // Disjoint distributions.
+        let b = times(21..=40);
+        let p = super::mann_whitney_u(&a, &b).unwrap();
+        assert!(p < 0.001, "{p}");
+        assert_eq!(super::mann_whitney_u(&b, &a), Some(p));
+
+        // Interleaved distributions.
+        let evens = times((2..=40).step_by(2));
+        let odds = times((1..=39).step_by(2));
+        let p = super::mann_whitney_u(&evens, &odds).unwrap();
+        assert!(p > SIGNIFICANCE_LEVEL, "{p}");
+    }
+}
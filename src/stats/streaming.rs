@@ -0,0 +1,508 @@
+use crate::{
+    alloc::AllocCounts,
+    counter::{CountTotal, KnownCounterKind},
+    hw::{HwCounterKind, MAX_PERF_EVENTS},
+    io_counters::IoCounterKind,
+    stats::{Sample, StatsSet, ThreadStats, TIME_PERCENTILES},
+    time::FineDuration,
+    util::{self, Rng},
+};
+
+/// Aggregates of samples that are computed incrementally instead of storing
+/// every [`Sample`], so that memory stays bounded for long runs.
+///
+/// The median and percentiles are computed from a bounded reservoir of
+/// randomly kept samples, or estimated with the P² algorithm if the reservoir
+/// is disabled.
+pub(crate) struct StreamingStats {
+    /// The number of samples aggregated.
+    pub count: usize,
+
+    /// The total duration of all samples.
+    pub total_duration: FineDuration,
+
+    /// The fastest and slowest samples' durations and counter totals.
+    pub fastest: Option<SampleExtreme>,
+    pub slowest: Option<SampleExtreme>,
+
+    /// The median followed by each of [`TIME_PERCENTILES`].
+    pub quantiles: [P2Quantile; 1 + TIME_PERCENTILES.len()],
+
+    /// Counter totals over all samples, if recorded for all samples.
+    pub counter_totals: [Option<CountTotal>; KnownCounterKind::COUNT],
+
+    /// Hardware event counts over all samples, if recorded for all samples.
+    pub hw_totals: Option<[u128; HwCounterKind::COUNT]>,
+
+    /// Counts of `--perf-events` over all samples, if recorded for all
+    /// samples.
+    pub perf_totals: Option<[u128; MAX_PERF_EVENTS]>,
+
+    /// Allocations over all samples, if recorded for all samples.
+    pub alloc_totals: Option<AllocCounts>,
+
+    /// I/O counts over all samples, if recorded for all samples.
+    pub io_totals: Option<[u128; IoCounterKind::COUNT]>,
+
+    /// Aggregates of each thread's samples, if benchmarked with multiple
+    /// threads.
+    pub threads: Option<StreamingThreadStats>,
+
+    /// A uniformly random subset of samples, which preserves the shape of
+    /// their distribution.
+    pub reservoir: Vec<Sample>,
+
+    /// The maximum number of samples in `reservoir`.
+    reservoir_size: usize,
+
+    rng: Rng,
+}
+
+/// Streaming counterpart of [`ThreadStats`].
+pub(crate) struct StreamingThreadStats {
+    /// Wall clock time for all threads to finish each sample set.
+    pub wall_time: DurationStats,
+
+    /// Durations of each thread's samples.
+    pub per_thread: Vec<DurationStats>,
+}
+
+/// Aggregates of durations, with the median estimated by the P² algorithm.
+pub(crate) struct DurationStats {
+    count: usize,
+    total: FineDuration,
+    fastest: Option<FineDuration>,
+    slowest: Option<FineDuration>,
+    median: P2Quantile,
+}
+
+/// The duration and counter totals of the fastest or slowest sample.
+#[derive(Clone, Copy)]
+pub(crate) struct SampleExtreme {
+    pub duration: FineDuration,
+    pub counter_totals: [Option<CountTotal>; KnownCounterKind::COUNT],
+}
+
+impl StreamingStats {
+    /// Creates empty aggregates for samples recorded by `thread_count`
+    /// threads.
+    pub fn new(reservoir_size: usize, thread_count: usize) -> Self {
+        let threads = (thread_count > 1).then(|| StreamingThreadStats {
+            wall_time: DurationStats::new(),
+            per_thread: (0..thread_count).map(|_| DurationStats::new()).collect(),
+        });
+
+        Self {
+            count: 0,
+            total_duration: FineDuration::default(),
+            fastest: None,
+            slowest: None,
+            quantiles: std::array::from_fn(|i| {
+                let percentile = if i == 0 { 50 } else { TIME_PERCENTILES[i - 1] };
+                P2Quantile::new(percentile as f64 / 100.0)
+            }),
+            counter_totals: [Some(CountTotal::ZERO); KnownCounterKind::COUNT],
+            hw_totals: Some([0; HwCounterKind::COUNT]),
+            perf_totals: Some([0; MAX_PERF_EVENTS]),
+            alloc_totals: Some(AllocCounts::default()),
+            io_totals: Some([0; IoCounterKind::COUNT]),
+            threads,
+            reservoir: Vec::new(),
+            reservoir_size,
+            rng: Rng::new(Rng::random_seed()),
+        }
+    }
+
+    /// Folds `sample` into the aggregates.
+    pub fn push(&mut self, sample: Sample) {
+        self.count += 1;
+        self.total_duration.picos += sample.duration.picos;
+
+        let extreme =
+            SampleExtreme { duration: sample.duration, counter_totals: sample.counter_totals };
+
+        if self.fastest.is_none_or(|fastest| sample.duration < fastest.duration) {
+            self.fastest = Some(extreme);
+        }
+        if self.slowest.is_none_or(|slowest| sample.duration > slowest.duration) {
+            self.slowest = Some(extreme);
+        }
+
+        if let Some(threads) = &mut self.threads {
+            // Samples of each sample set are in the same thread order.
+            let thread = (self.count - 1) % threads.per_thread.len();
+            threads.per_thread[thread].push(sample.duration);
+        }
+
+        if self.reservoir_size == 0 {
+            for quantile in &mut self.quantiles {
+                quantile.push(sample.duration.picos as f64);
+            }
+        }
+
+        for (total, count) in self.counter_totals.iter_mut().zip(sample.counter_totals) {
+            *total = total.zip(count).map(|(total, count)| total + count);
+        }
+
+        self.hw_totals = self.hw_totals.zip(sample.hw_counts).map(|(mut totals, counts)| {
+            for (total, count) in totals.iter_mut().zip(counts.0) {
+                *total += count as u128;
+            }
+            totals
+        });
+
+        self.perf_totals = self.perf_totals.zip(sample.perf_counts).map(|(mut totals, counts)| {
+            for (total, count) in totals.iter_mut().zip(counts.0) {
+                *total += count as u128;
+            }
+            totals
+        });
+
+        self.alloc_totals =
+            self.alloc_totals.zip(sample.alloc_counts).map(|(mut totals, counts)| {
+                totals.alloc_count += counts.alloc_count;
+                totals.alloc_bytes += counts.alloc_bytes;
+                totals.dealloc_count += counts.dealloc_count;
+                totals.peak_bytes = totals.peak_bytes.max(counts.peak_bytes);
+                totals
+            });
+
+        self.io_totals = self.io_totals.zip(sample.io_counts).map(|(mut totals, counts)| {
+            for (total, count) in totals.iter_mut().zip(counts.0) {
+                *total += count as u128;
+            }
+            totals
+        });
+
+        // Reservoir sampling (Algorithm R): the `n`th sample replaces a random
+        // kept sample with probability `reservoir_size / n`.
+        if self.reservoir.len() < self.reservoir_size {
+            self.reservoir.push(sample);
+        } else if self.reservoir_size > 0 {
+            let index = self.rng.below(self.count);
+            if let Some(kept) = self.reservoir.get_mut(index) {
+                *kept = sample;
+            }
+        }
+    }
+
+    /// Returns the estimated median sample duration followed by the sample
+    /// duration at each of [`TIME_PERCENTILES`].
+    pub fn quantile_durations(&self) -> (FineDuration, [FineDuration; TIME_PERCENTILES.len()]) {
+        if self.reservoir_size == 0 {
+            return (
+                self.duration_at(50),
+                TIME_PERCENTILES.map(|percentile| self.duration_at(percentile)),
+            );
+        }
+
+        let mut durations: Vec<FineDuration> = self.reservoir.iter().map(|s| s.duration).collect();
+        durations.sort_unstable();
+
+        let median_durations = util::slice_middle(&durations);
+        let median = if median_durations.is_empty() {
+            FineDuration::default()
+        } else {
+            let sum: u128 = median_durations.iter().map(|d| d.picos).sum();
+            FineDuration { picos: sum / median_durations.len() as u128 }
+        };
+
+        let percentiles = TIME_PERCENTILES.map(|percentile| {
+            crate::stats::percentile_index(durations.len(), percentile)
+                .map(|index| durations[index])
+                .unwrap_or_default()
+        });
+
+        (median, percentiles)
+    }
+
+    /// Computes per-thread statistics of time per iteration, if benchmarked
+    /// with multiple threads.
+    pub fn thread_stats(&self, sample_size: u32) -> Option<ThreadStats> {
+        let threads = self.threads.as_ref()?;
+
+        Some(ThreadStats {
+            wall_time: threads.wall_time.per_iter(sample_size),
+            per_thread: threads
+                .per_thread
+                .iter()
+                .map(|stats| stats.per_iter(sample_size))
+                .collect(),
+        })
+    }
+
+    /// Returns the P² estimate of the sample duration at `percentile`, which
+    /// must be 50 or one of [`TIME_PERCENTILES`].
+    fn duration_at(&self, percentile: u8) -> FineDuration {
+        let index = if percentile == 50 {
+            0
+        } else {
+            match TIME_PERCENTILES.iter().position(|&p| p == percentile) {
+                Some(index) => index + 1,
+                None => return FineDuration::default(),
+            }
+        };
+
+        FineDuration { picos: self.quantiles[index].estimate().round() as u128 }
+    }
+}
+
+impl DurationStats {
+    pub fn new() -> Self {
+        Self {
+            count: 0,
+            total: FineDuration::default(),
+            fastest: None,
+            slowest: None,
+            median: P2Quantile::new(0.5),
+        }
+    }
+
+    pub fn push(&mut self, duration: FineDuration) {
+        self.count += 1;
+        self.total.picos += duration.picos;
+        self.fastest = Some(self.fastest.map_or(duration, |fastest| fastest.min(duration)));
+        self.slowest = Some(self.slowest.map_or(duration, |slowest| slowest.max(duration)));
+        self.median.push(duration.picos as f64);
+    }
+
+    /// Returns statistics of time per iteration, where each duration is of
+    /// `sample_size` iterations.
+    ///
+    /// The median is estimated, unlike that of stored samples.
+    pub fn per_iter(&self, sample_size: u32) -> StatsSet<FineDuration> {
+        let sample_size = sample_size.max(1);
+
+        StatsSet {
+            fastest: self.fastest.map(|d| d / sample_size).unwrap_or_default(),
+            slowest: self.slowest.map(|d| d / sample_size).unwrap_or_default(),
+            median: FineDuration { picos: self.median.estimate().round() as u128 } / sample_size,
+            mean: FineDuration {
+                picos: self
+                    .total
+                    .picos
+                    .checked_div(self.count as u128 * sample_size as u128)
+                    .unwrap_or_default(),
+            },
+        }
+    }
+}
+
+/// Estimates a quantile in constant memory with the P² algorithm by Jain and
+/// Chlamtac, which tracks five markers whose heights approximate the minimum,
+/// maximum, quantile, and midpoints between them.
+pub(crate) struct P2Quantile {
+    /// The quantile being estimated, in `0.0..=1.0`.
+    quantile: f64,
+
+    /// The number of values observed.
+    count: usize,
+
+    /// Marker heights.
+    heights: [f64; 5],
+
+    /// Actual marker positions.
+    positions: [f64; 5],
+
+    /// Desired marker positions.
+    desired: [f64; 5],
+
+    /// Increments to desired marker positions for each observation.
+    increments: [f64; 5],
+}
+
+impl P2Quantile {
+    pub fn new(quantile: f64) -> Self {
+        let p = quantile;
+        Self {
+            quantile,
+            count: 0,
+            heights: [0.0; 5],
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired: [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0],
+            increments: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0],
+        }
+    }
+
+    pub fn push(&mut self, value: f64) {
+        // The first five observations initialize the markers.
+        if self.count < 5 {
+            self.heights[self.count] = value;
+            self.count += 1;
+
+            if self.count == 5 {
+                self.heights.sort_unstable_by(f64::total_cmp);
+            }
+            return;
+        }
+
+        self.count += 1;
+
+        // Find the cell containing `value`, extending the extremes if needed.
+        let cell = if value < self.heights[0] {
+            self.heights[0] = value;
+            0
+        } else if value >= self.heights[4] {
+            self.heights[4] = value;
+            3
+        } else {
+            (1..5).find(|&i| value < self.heights[i]).unwrap_or(4) - 1
+        };
+
+        for position in &mut self.positions[cell + 1..] {
+            *position += 1.0;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(self.increments) {
+            *desired += increment;
+        }
+
+        // Adjust the middle markers if they are off from their desired
+        // positions.
+        for i in 1..4 {
+            let offset = self.desired[i] - self.positions[i];
+
+            let can_move_up = self.positions[i + 1] - self.positions[i] > 1.0;
+            let can_move_down = self.positions[i - 1] - self.positions[i] < -1.0;
+
+            if (offset >= 1.0 && can_move_up) || (offset <= -1.0 && can_move_down) {
+                let step = offset.signum();
+
+                let parabolic = self.parabolic(i, step);
+                self.heights[i] =
+                    if self.heights[i - 1] < parabolic && parabolic < self.heights[i + 1] {
+                        parabolic
+                    } else {
+                        self.linear(i, step)
+                    };
+
+                self.positions[i] += step;
+            }
+        }
+    }
+
+    /// Returns the estimated quantile, or 0 if no values were observed.
+    pub fn estimate(&self) -> f64 {
+        if self.count >= 5 {
+            return self.heights[2];
+        }
+
+        // Use the nearest rank of the few observed values.
+        let mut values = self.heights;
+        let values = &mut values[..self.count];
+        values.sort_unstable_by(f64::total_cmp);
+
+        let percentile = (self.quantile * 100.0).round() as u8;
+        crate::stats::percentile_index(values.len(), percentile)
+            .map(|index| values[index])
+            .unwrap_or_default()
+    }
+
+    fn parabolic(&self, i: usize, step: f64) -> f64 {
+        let [n_prev, n, n_next] = [self.positions[i - 1], self.positions[i], self.positions[i + 1]];
+        let [q_prev, q, q_next] = [self.heights[i - 1], self.heights[i], self.heights[i + 1]];
+
+        q + step / (n_next - n_prev)
+            * ((n - n_prev + step) * (q_next - q) / (n_next - n)
+                + (n_next - n - step) * (q - q_prev) / (n - n_prev))
+    }
+
+    fn linear(&self, i: usize, step: f64) -> f64 {
+        let j = if step > 0.0 { i + 1 } else { i - 1 };
+        self.heights[i]
+            + step * (self.heights[j] - self.heights[i]) / (self.positions[j] - self.positions[i])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(picos: u128) -> Sample {
+        Sample {
+            duration: FineDuration { picos },
+            counter_totals: [None; KnownCounterKind::COUNT],
+            hw_counts: None,
+            perf_counts: None,
+            alloc_counts: None,
+            io_counts: None,
+        }
+    }
+
+    #[test]
+    fn reservoir() {
+        let mut stats = StreamingStats::new(10, 1);
+        for picos in 0..1_000 {
+            stats.push(sample(picos));
+        }
+
+        assert_eq!(stats.count, 1_000);
+        assert_eq!(stats.reservoir.len(), 10);
+        assert_eq!(stats.total_duration.picos, (0..1_000).sum());
+        assert_eq!(stats.fastest.unwrap().duration.picos, 0);
+        assert_eq!(stats.slowest.unwrap().duration.picos, 999);
+    }
+
+    #[test]
+    fn reservoir_keeps_few_samples() {
+        let mut stats = StreamingStats::new(10, 1);
+        for picos in [3, 1, 2] {
+            stats.push(sample(picos));
+        }
+
+        let (median, percentiles) = stats.quantile_durations();
+        assert_eq!(median.picos, 2);
+        assert_eq!(percentiles.map(|d| d.picos), [3; TIME_PERCENTILES.len()]);
+    }
+
+    #[test]
+    fn thread_stats() {
+        assert!(StreamingStats::new(10, 1).thread_stats(1).is_none());
+
+        let mut stats = StreamingStats::new(10, 2);
+
+        // Thread 0 takes 10-30 picos per sample and thread 1 takes 100-300.
+        for picos in [10, 100, 20, 200, 30, 300] {
+            stats.push(sample(picos * 2));
+        }
+        for picos in [100, 200, 300] {
+            stats.threads.as_mut().unwrap().wall_time.push(FineDuration { picos: picos * 2 });
+        }
+
+        let thread_stats = stats.thread_stats(2).unwrap();
+        assert_eq!(thread_stats.thread_count(), 2);
+
+        let picos = |stats: StatsSet<FineDuration>| {
+            [stats.fastest, stats.slowest, stats.median, stats.mean].map(|d| d.picos)
+        };
+        assert_eq!(picos(thread_stats.per_thread[0]), [10, 30, 20, 20]);
+        assert_eq!(picos(thread_stats.per_thread[1]), [100, 300, 200, 200]);
+        assert_eq!(picos(thread_stats.wall_time), [100, 300, 200, 200]);
+    }
+
+    #[test]
+    fn p2_few_values() {
+        let mut quantile = P2Quantile::new(0.5);
+        assert_eq!(quantile.estimate(), 0.0);
+
+        for value in [3.0, 1.0, 2.0] {
+            quantile.push(value);
+        }
+        assert_eq!(quantile.estimate(), 2.0);
+    }
+
+    #[test]
+    fn p2_estimates_uniform() {
+        let mut median = P2Quantile::new(0.5);
+        let mut p90 = P2Quantile::new(0.9);
+
+        // Shuffle 0..10_000 with a multiplicative permutation.
+        for i in 0..10_000u64 {
+            let value = ((i * 7_919) % 10_000) as f64;
+            median.push(value);
+            p90.push(value);
+        }
+
+        assert!((median.estimate() - 5_000.0).abs() < 100.0, "{}", median.estimate());
+        assert!((p90.estimate() - 9_000.0).abs() < 100.0, "{}", p90.estimate());
+    }
+}
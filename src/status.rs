@@ -0,0 +1,331 @@
+//! Printing the status of the running benchmark on request, for diagnosing runs
+//! that appear hung.
+//!
+//! When enabled with `--status-signal`, the status is requested by sending
+//! `SIGUSR1` to the process, or with `SIGINFO` (Ctrl+T in the terminal) on BSD
+//! and macOS. It is printed to stderr by a background thread, so it is shown
+//! even if a sample never finishes.
+//!
+//! The signal handler wakes the thread by writing to a pipe, so the thread
+//! stays blocked and does not disturb measurements until a status is requested.
+//! Signal numbers are only known for an allowlist of targets; elsewhere, no
+//! handler is installed. Signals that already have a handler or are ignored
+//! are left alone.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Mutex, OnceLock, PoisonError,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::time::FineDuration;
+
+/// Set by the signal handler, which only wakes the background thread if this
+/// was unset. This keeps repeated signals from filling the pipe.
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// The path and start time of the running benchmark.
+static RUNNING: Mutex<Option<(String, Instant)>> = Mutex::new(None);
+
+/// The number of samples collected by the running benchmark.
+static SAMPLES: AtomicUsize = AtomicUsize::new(0);
+
+/// The interim mean time per iteration in picoseconds, or `u64::MAX` if
+/// unknown.
+static MEAN_PICOS: AtomicU64 = AtomicU64::new(u64::MAX);
+
+/// Installs signal handlers for requesting the status, returning `false` if
+/// this is unsupported on the current platform or every signal is already
+/// handled.
+pub(crate) fn install() -> bool {
+    static INSTALLED: OnceLock<bool> = OnceLock::new();
+
+    *INSTALLED.get_or_init(|| {
+        if !imp::install() {
+            return false;
+        }
+
+        thread::Builder::new()
+            .name("divan-status".to_owned())
+            .spawn(|| {
+                while imp::wait() {
+                    if REQUESTED.swap(false, Ordering::Relaxed) {
+                        eprintln!("{}", current_status());
+                    }
+                }
+            })
+            .is_ok()
+    })
+}
+
+/// Marks the benchmark at `path` as running until the returned guard is
+/// dropped.
+pub(crate) fn start(path: &str) -> RunningGuard {
+    SAMPLES.store(0, Ordering::Relaxed);
+    MEAN_PICOS.store(u64::MAX, Ordering::Relaxed);
+    *lock_running() = Some((path.to_owned(), Instant::now()));
+
+    RunningGuard(())
+}
+
+/// Updates the progress of the running benchmark.
+#[inline]
+pub(crate) fn update(samples: usize, mean: Option<FineDuration>) {
+    SAMPLES.store(samples, Ordering::Relaxed);
+
+    let mean_picos = mean.map_or(u64::MAX, |mean| mean.picos.min(u64::MAX as u128 - 1) as u64);
+    MEAN_PICOS.store(mean_picos, Ordering::Relaxed);
+}
+
+/// Clears the running benchmark when dropped.
+pub(crate) struct RunningGuard(());
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        *lock_running() = None;
+    }
+}
+
+fn lock_running() -> std::sync::MutexGuard<'static, Option<(String, Instant)>> {
+    RUNNING.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn current_status() -> String {
+    let running = lock_running();
+    let Some((path, start)) = &*running else {
+        return "No benchmark is running".to_owned();
+    };
+
+    let mean = match MEAN_PICOS.load(Ordering::Relaxed) {
+        u64::MAX => None,
+        picos => Some(FineDuration { picos: picos.into() }),
+    };
+
+    format_status(path, SAMPLES.load(Ordering::Relaxed), mean, start.elapsed())
+}
+
+fn format_status(
+    path: &str,
+    samples: usize,
+    mean: Option<FineDuration>,
+    elapsed: Duration,
+) -> String {
+    let noun = if samples == 1 { "sample" } else { "samples" };
+    let elapsed = FineDuration::from(elapsed);
+
+    match mean {
+        Some(mean) => format!("{path}: {samples} {noun}, mean {mean}, {elapsed:.3} elapsed"),
+        None => format!("{path}: {samples} {noun}, {elapsed:.3} elapsed"),
+    }
+}
+
+#[cfg(all(unix, not(miri)))]
+mod imp {
+    use std::{
+        io,
+        os::raw::{c_int, c_void},
+        sync::atomic::{AtomicI32, Ordering},
+    };
+
+    #[cfg(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
+    const SIGNALS: &[c_int] = &[
+        29, // SIGINFO
+        30, // SIGUSR1
+    ];
+
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        any(target_arch = "mips", target_arch = "mips64"),
+    ))]
+    const SIGNALS: &[c_int] = &[16]; // SIGUSR1
+
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        any(
+            target_arch = "x86",
+            target_arch = "x86_64",
+            target_arch = "arm",
+            target_arch = "aarch64",
+            target_arch = "riscv64",
+        ),
+    ))]
+    const SIGNALS: &[c_int] = &[10]; // SIGUSR1
+
+    // Signal numbers differ on other targets, such as 10 being `SIGBUS` on
+    // illumos and SPARC, so no handler is installed.
+    #[cfg(not(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        all(
+            any(target_os = "linux", target_os = "android"),
+            any(
+                target_arch = "x86",
+                target_arch = "x86_64",
+                target_arch = "arm",
+                target_arch = "aarch64",
+                target_arch = "riscv64",
+                target_arch = "mips",
+                target_arch = "mips64",
+            ),
+        ),
+    )))]
+    const SIGNALS: &[c_int] = &[];
+
+    /// `SIG_DFL`, the default disposition of a signal.
+    const SIG_DFL: usize = 0;
+
+    /// `SIG_ERR`, returned by `signal` on failure.
+    const SIG_ERR: usize = !0;
+
+    /// `F_SETFD` and `FD_CLOEXEC`, for closing the pipe in spawned processes.
+    const F_SETFD: c_int = 2;
+    const FD_CLOEXEC: c_int = 1;
+
+    /// The index of `sa_handler` in `struct sigaction` as `usize` words. On
+    /// Linux MIPS, it follows the 32-bit `sa_flags`.
+    #[cfg(not(all(
+        any(target_os = "linux", target_os = "android"),
+        any(target_arch = "mips", target_arch = "mips64"),
+    )))]
+    const SA_HANDLER_INDEX: usize = 0;
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        any(target_arch = "mips", target_arch = "mips64"),
+    ))]
+    const SA_HANDLER_INDEX: usize = 1;
+
+    /// The read and write ends of the pipe that wakes the background thread.
+    static READ_FD: AtomicI32 = AtomicI32::new(-1);
+    static WRITE_FD: AtomicI32 = AtomicI32::new(-1);
+
+    extern "C" {
+        fn signal(signum: c_int, handler: usize) -> usize;
+        #[cfg_attr(target_os = "netbsd", link_name = "__sigaction14")]
+        fn sigaction(signum: c_int, act: *const c_void, old_act: *mut c_void) -> c_int;
+        fn pipe(fds: *mut c_int) -> c_int;
+        fn fcntl(fd: c_int, cmd: c_int, ...) -> c_int;
+        fn read(fd: c_int, buf: *mut c_void, count: usize) -> isize;
+        fn write(fd: c_int, buf: *const c_void, count: usize) -> isize;
+    }
+
+    extern "C" fn handle(_: c_int) {
+        // Only async-signal-safe operations are allowed here.
+        if !super::REQUESTED.swap(true, Ordering::Relaxed) {
+            let byte = 0u8;
+
+            // SAFETY: `write` is async-signal-safe and `byte` is valid for
+            // reads of 1 byte.
+            unsafe { write(WRITE_FD.load(Ordering::Relaxed), (&byte as *const u8).cast(), 1) };
+        }
+    }
+
+    /// Returns `true` if `signum` has its default disposition, and so is not
+    /// handled or ignored by other code in the process.
+    fn is_default(signum: c_int) -> bool {
+        // Larger than `struct sigaction` on all supported targets, and only
+        // `sa_handler` is read.
+        let mut old_act = [0usize; 64];
+
+        // SAFETY: A null `act` only queries the disposition, and `old_act` is
+        // valid for writes of `struct sigaction`.
+        if unsafe { sigaction(signum, std::ptr::null(), old_act.as_mut_ptr().cast()) } != 0 {
+            return false;
+        }
+
+        old_act[SA_HANDLER_INDEX] == SIG_DFL
+    }
+
+    pub fn install() -> bool {
+        let signals: Vec<c_int> =
+            SIGNALS.iter().copied().filter(|&signum| is_default(signum)).collect();
+        if signals.is_empty() {
+            return false;
+        }
+
+        let mut fds: [c_int; 2] = [-1; 2];
+
+        // SAFETY: `fds` is valid for writes of 2 file descriptors.
+        if unsafe { pipe(fds.as_mut_ptr()) } != 0 {
+            return false;
+        }
+
+        // Keep the pipe from leaking into `--isolate` workers and other
+        // spawned processes.
+        // SAFETY: `fcntl` with `F_SETFD` has no memory safety requirements.
+        if fds.iter().any(|&fd| unsafe { fcntl(fd, F_SETFD, FD_CLOEXEC) } != 0) {
+            return false;
+        }
+
+        READ_FD.store(fds[0], Ordering::Relaxed);
+        WRITE_FD.store(fds[1], Ordering::Relaxed);
+
+        let handler = handle as extern "C" fn(c_int) as usize;
+
+        // SAFETY: `handle` only performs an atomic swap and a pipe write, which
+        // are async-signal-safe.
+        signals.iter().all(|&signum| unsafe { signal(signum, handler) } != SIG_ERR)
+    }
+
+    /// Blocks until the signal handler wakes the thread, returning `false` if
+    /// the pipe was closed or cannot be read.
+    pub fn wait() -> bool {
+        let mut byte = 0u8;
+        loop {
+            // SAFETY: `byte` is valid for writes of 1 byte.
+            match unsafe { read(READ_FD.load(Ordering::Relaxed), (&mut byte as *mut u8).cast(), 1) }
+            {
+                1 => return true,
+                0 => return false,
+
+                // Interrupted, such as by our own signal.
+                _ if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => continue,
+
+                _ => return false,
+            }
+        }
+    }
+}
+
+#[cfg(not(all(unix, not(miri))))]
+mod imp {
+    pub fn install() -> bool {
+        false
+    }
+
+    pub fn wait() -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_status() {
+        let elapsed = Duration::from_millis(3500);
+
+        assert_eq!(
+            super::format_status("a::b", 0, None, elapsed),
+            "a::b: 0 samples, 3.5 s elapsed"
+        );
+        assert_eq!(
+            super::format_status("a::b", 1, Some(FineDuration { picos: 1_500_000 }), elapsed),
+            "a::b: 1 sample, mean 1.5 µs, 3.5 s elapsed"
+        );
+    }
+}
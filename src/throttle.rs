@@ -0,0 +1,127 @@
+//! Detecting CPU frequency drops during benchmarks, which are usually caused by
+//! thermal throttling and make results slower than they should be.
+
+use std::{fmt, fs};
+
+/// The percentage by which CPU frequency must drop during a benchmark for it to
+/// be considered throttled.
+const THRESHOLD_PERCENT: u64 = 10;
+
+/// Returns the highest current CPU core frequency in kHz, or `None` if it is
+/// unavailable on this platform.
+///
+/// The highest frequency tracks the cores running benchmarks, since idle cores
+/// may be clocked down.
+pub(crate) fn max_cpu_freq_khz() -> Option<u64> {
+    if cfg!(miri) || !cfg!(target_os = "linux") {
+        return None;
+    }
+
+    sysfs_max_freq_khz().or_else(|| {
+        let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+        cpuinfo_max_freq_khz(&cpuinfo)
+    })
+}
+
+/// Reads `scaling_cur_freq` of each core from cpufreq.
+fn sysfs_max_freq_khz() -> Option<u64> {
+    fs::read_dir("/sys/devices/system/cpu")
+        .ok()?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+
+            let name = entry.file_name();
+            let index = name.to_str()?.strip_prefix("cpu")?;
+            if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+
+            let freq = fs::read_to_string(entry.path().join("cpufreq/scaling_cur_freq")).ok()?;
+            freq.trim().parse::<u64>().ok()
+        })
+        .max()
+}
+
+/// Parses the highest "cpu MHz" line of `/proc/cpuinfo`.
+fn cpuinfo_max_freq_khz(cpuinfo: &str) -> Option<u64> {
+    cpuinfo
+        .lines()
+        .filter_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            if name.trim() != "cpu MHz" {
+                return None;
+            }
+
+            let mhz: f64 = value.trim().parse().ok()?;
+            Some((mhz * 1000.0).round() as u64)
+        })
+        .max()
+}
+
+/// A significant drop in CPU frequency during a benchmark.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Throttling {
+    /// Frequency in kHz before the benchmark.
+    pub before_khz: u64,
+
+    /// Frequency in kHz after the benchmark.
+    pub after_khz: u64,
+}
+
+impl Throttling {
+    /// Returns the drop in frequency between readings taken before and after
+    /// a benchmark, if it is significant.
+    pub fn detect(before_khz: Option<u64>, after_khz: Option<u64>) -> Option<Self> {
+        let (before_khz, after_khz) = (before_khz?, after_khz?);
+
+        let drop_khz = before_khz.checked_sub(after_khz)?;
+        if drop_khz.saturating_mul(100) <= before_khz.saturating_mul(THRESHOLD_PERCENT) {
+            return None;
+        }
+
+        Some(Self { before_khz, after_khz })
+    }
+
+    /// The percentage decrease in frequency.
+    pub fn drop_percent(&self) -> f64 {
+        (self.before_khz - self.after_khz) as f64 / self.before_khz as f64 * 100.0
+    }
+}
+
+impl fmt::Display for Throttling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ghz = |khz: u64| khz as f64 / 1_000_000.0;
+
+        write!(
+            f,
+            "CPU frequency dropped {:.0}% from {:.2} GHz to {:.2} GHz",
+            self.drop_percent(),
+            ghz(self.before_khz),
+            ghz(self.after_khz),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect() {
+        // Small drops and increases are not throttling.
+        assert_eq!(Throttling::detect(Some(3_000_000), Some(2_800_000)), None);
+        assert_eq!(Throttling::detect(Some(2_000_000), Some(3_000_000)), None);
+        assert_eq!(Throttling::detect(None, Some(2_000_000)), None);
+
+        let throttling = Throttling::detect(Some(3_200_000), Some(2_400_000)).unwrap();
+        assert_eq!(throttling.to_string(), "CPU frequency dropped 25% from 3.20 GHz to 2.40 GHz");
+    }
+
+    #[test]
+    fn cpuinfo() {
+        let cpuinfo =
+            "processor\t: 0\ncpu MHz\t\t: 2400.125\n\nprocessor\t: 1\ncpu MHz\t\t: 3100.000\n";
+        assert_eq!(cpuinfo_max_freq_khz(cpuinfo), Some(3_100_000));
+        assert_eq!(cpuinfo_max_freq_khz("processor\t: 0\n"), None);
+    }
+}
@@ -1,6 +1,6 @@
 use std::{fmt, ops, time::Duration};
 
-use crate::util;
+use crate::{time::TimeUnit, util};
 
 /// [Picosecond](https://en.wikipedia.org/wiki/Picosecond)-precise [`Duration`].
 #[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
@@ -21,6 +21,19 @@ impl From<Duration> for FineDuration {
     }
 }
 
+impl From<FineDuration> for Duration {
+    /// Converts with nanosecond precision, saturating at [`Duration::MAX`].
+    #[inline]
+    fn from(duration: FineDuration) -> Self {
+        let nanos = duration.picos / 1_000;
+        let secs = nanos / 1_000_000_000;
+        match u64::try_from(secs) {
+            Ok(secs) => Duration::new(secs, (nanos % 1_000_000_000) as u32),
+            Err(_) => Duration::MAX,
+        }
+    }
+}
+
 impl fmt::Display for FineDuration {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let sig_figs = f.precision().unwrap_or(4);
@@ -63,7 +76,7 @@ impl fmt::Display for FineDuration {
         if let Some(fill_len) = f.width().and_then(|width| width.checked_sub(str.len())) {
             match f.align() {
                 None | Some(fmt::Alignment::Left) => {
-                    str.extend(std::iter::repeat(f.fill()).take(fill_len));
+                    str.extend(std::iter::repeat_n(f.fill(), fill_len));
                 }
                 _ => return Err(fmt::Error),
             }
@@ -133,6 +146,38 @@ impl FineDuration {
             self.min(other)
         }
     }
+
+    /// Formats with `unit`, or the most readable unit for [`TimeUnit::Auto`].
+    pub fn to_string_in(self, unit: TimeUnit) -> String {
+        let scale = match unit {
+            TimeUnit::Auto => return self.to_string(),
+            TimeUnit::Picos => TimeScale::PicoSec,
+            TimeUnit::Nanos => TimeScale::NanoSec,
+            TimeUnit::Micros => TimeScale::MicroSec,
+            TimeUnit::Millis => TimeScale::MilliSec,
+            TimeUnit::Secs => TimeScale::Sec,
+        };
+
+        let sig_figs = 4;
+        let val = self.picos as f64 / scale.picos() as f64;
+
+        let mut str = if val >= 1.0 || val == 0.0 {
+            util::format_f64(val, sig_figs)
+        } else {
+            // Keep `sig_figs` digits after the leading fractional zeros so
+            // that small values in a large unit remain distinguishable.
+            // Like `util::format_f64`, extra digits are truncated rather than
+            // rounded.
+            let fract_digits = (-val.log10()).floor() as usize + sig_figs;
+            let mut str = format!("{val:.0$}", fract_digits + 2);
+            str.truncate("0.".len() + fract_digits);
+            str.trim_end_matches('0').trim_end_matches('.').to_owned()
+        };
+
+        str.push(' ');
+        str.push_str(scale.suffix());
+        str
+    }
 }
 
 mod picos {
@@ -228,6 +273,20 @@ impl TimeScale {
 mod tests {
     use super::*;
 
+    #[test]
+    fn into_duration() {
+        #[track_caller]
+        fn test(picos: u128, expected: Duration) {
+            assert_eq!(Duration::from(FineDuration { picos }), expected);
+        }
+
+        test(0, Duration::ZERO);
+        test(999, Duration::ZERO);
+        test(1_500, Duration::from_nanos(1));
+        test(2_000_000_000_000_000, Duration::from_secs(2_000));
+        test(u128::MAX, Duration::MAX);
+    }
+
     #[test]
     fn clamp_to() {
         #[track_caller]
@@ -461,5 +520,23 @@ mod tests {
             test(u128::MAX / 1000, "3938453320844195178 d");
             test(u128::MAX, "3938453320844195178974 d");
         }
+
+        #[test]
+        fn fixed_unit() {
+            #[track_caller]
+            fn test(picos: u128, unit: TimeUnit, expected: &str) {
+                assert_eq!(FineDuration { picos }.to_string_in(unit), expected);
+            }
+
+            test(1_500, TimeUnit::Auto, "1.5 ns");
+            test(1_500, TimeUnit::Picos, "1500 ps");
+            test(1_500, TimeUnit::Nanos, "1.5 ns");
+            test(1_500, TimeUnit::Micros, "0.0015 µs");
+            test(1_500, TimeUnit::Millis, "0.0000015 ms");
+            test(123_456_789, TimeUnit::Micros, "123.4 µs");
+            test(123_456_789, TimeUnit::Millis, "0.1234 ms");
+            test(picos::MIN, TimeUnit::Secs, "60 s");
+            test(0, TimeUnit::Millis, "0 ms");
+        }
     }
 }
@@ -36,3 +36,116 @@ impl IntoDuration for f64 {
         Duration::from_secs_f64(self)
     }
 }
+
+/// The unit of time used for benchmark outputs.
+///
+/// See [`Divan::time_unit`](crate::Divan::time_unit) for more info.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum TimeUnit {
+    /// The most readable unit for each value. This is the default.
+    #[default]
+    Auto,
+
+    /// Picoseconds.
+    Picos,
+
+    /// Nanoseconds.
+    Nanos,
+
+    /// Microseconds.
+    Micros,
+
+    /// Milliseconds.
+    Millis,
+
+    /// Seconds.
+    Secs,
+}
+
+/// Private `TimeUnit` that prevents leaking trait implementations we don't
+/// want to publicly commit to.
+#[derive(Clone, Copy)]
+pub(crate) struct PrivTimeUnit(pub TimeUnit);
+
+impl clap::ValueEnum for PrivTimeUnit {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            Self(TimeUnit::Picos),
+            Self(TimeUnit::Nanos),
+            Self(TimeUnit::Micros),
+            Self(TimeUnit::Millis),
+            Self(TimeUnit::Secs),
+            Self(TimeUnit::Auto),
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        use clap::builder::PossibleValue;
+
+        Some(match self.0 {
+            TimeUnit::Auto => PossibleValue::new("auto"),
+            TimeUnit::Picos => PossibleValue::new("ps"),
+            TimeUnit::Nanos => PossibleValue::new("ns"),
+            TimeUnit::Micros => PossibleValue::new("us").alias("µs").alias("μs"),
+            TimeUnit::Millis => PossibleValue::new("ms"),
+            TimeUnit::Secs => PossibleValue::new("s"),
+        })
+    }
+}
+
+/// Parses durations such as "100µs" or "1.5ms", for use in attribute options.
+///
+/// # Panics
+///
+/// Panics if the string is not a non-negative number followed by one of the
+/// units `ns`, `us`/`µs`, `ms`, or `s`.
+impl IntoDuration for &str {
+    #[track_caller]
+    fn into_duration(self) -> Duration {
+        match parse_duration(self) {
+            Some(duration) => duration,
+            None => panic!(
+                "invalid duration '{self}', expected a number with unit 'ns', 'µs', 'ms', or 's'"
+            ),
+        }
+    }
+}
+
+/// Parses a number followed by a time unit.
+pub(crate) fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+    let (value, unit) = s.split_at(split);
+
+    let value: f64 = value.parse().ok()?;
+    let secs = match unit.trim_start() {
+        "ns" => value / 1e9,
+        // Accept both the micro sign (U+00B5) and Greek mu (U+03BC).
+        "us" | "µs" | "μs" => value / 1e6,
+        "ms" => value / 1e3,
+        "s" => value,
+        _ => return None,
+    };
+
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration() {
+        assert_eq!(super::parse_duration("100ns"), Some(Duration::from_nanos(100)));
+        assert_eq!(super::parse_duration("100µs"), Some(Duration::from_micros(100)));
+        assert_eq!(super::parse_duration("100μs"), Some(Duration::from_micros(100)));
+        assert_eq!(super::parse_duration("100us"), Some(Duration::from_micros(100)));
+        assert_eq!(super::parse_duration("1.5 ms"), Some(Duration::from_micros(1500)));
+        assert_eq!(super::parse_duration("2s"), Some(Duration::from_secs(2)));
+
+        assert_eq!(super::parse_duration("2"), None);
+        assert_eq!(super::parse_duration("ms"), None);
+        assert_eq!(super::parse_duration("2 min"), None);
+    }
+}
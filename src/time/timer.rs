@@ -173,6 +173,17 @@ pub(crate) enum TimerKind {
     Tsc,
 }
 
+impl TimerKind {
+    /// Returns the name used for this timer in CLI arguments.
+    #[inline]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Os => "os",
+            Self::Tsc => "tsc",
+        }
+    }
+}
+
 #[cfg(feature = "internal_benches")]
 #[crate::bench(crate = crate)]
 fn get_tsc() -> Result<Timer, TscUnavailable> {
@@ -28,6 +28,17 @@ impl Timestamp {
         value
     }
 
+    #[inline(always)]
+    pub fn end(timer_kind: TimerKind) -> Self {
+        fence::compiler_fence();
+        let value = match timer_kind {
+            TimerKind::Os => Self::Os(Instant::now()),
+            TimerKind::Tsc => Self::Tsc(TscTimestamp::end()),
+        };
+        fence::full_fence();
+        value
+    }
+
     pub fn duration_since(self, earlier: Self, timer: Timer) -> FineDuration {
         match (self, earlier, timer) {
             (Self::Os(this), Self::Os(earlier), Timer::Os) => this.duration_since(earlier).into(),
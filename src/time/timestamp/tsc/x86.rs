@@ -257,7 +257,7 @@ mod util {
         ];
 
         // SAFETY: Converting from `u32` to bytes.
-        Some(unsafe { std::mem::transmute(result) })
+        Some(unsafe { std::mem::transmute::<[[u32; 4]; 3], [u8; 48]>(result) })
     }
 }
 
@@ -0,0 +1,78 @@
+//! [Tracy](https://github.com/wolfpld/tracy) profiler instrumentation.
+//!
+//! Each benchmark's sample loop is a zone named after the benchmark's path,
+//! containing a zone for each sample. Every sample ends with a frame mark of the
+//! same name, so Tracy's frame view shows one frame per sample, and sample
+//! durations are plotted to find outliers on the timeline.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock, PoisonError},
+};
+
+use tracy_client::{Client, FrameName, PlotName, Span};
+
+use crate::time::FineDuration;
+
+/// Tracy instrumentation of a benchmark's sample loop.
+pub(crate) struct TracyBench<'a> {
+    client: Client,
+    path: &'a str,
+    names: Names,
+
+    /// Zone of the whole sample loop, which ends when dropped.
+    _zone: Span,
+}
+
+impl<'a> TracyBench<'a> {
+    /// Starts the Tracy client if needed and begins a zone for the benchmark
+    /// at `path`.
+    pub fn start(path: &'a str) -> Self {
+        let client = Client::start();
+        let zone = client.clone().span_alloc(Some(path), "sample_loop", file!(), line!(), 0);
+
+        Self { names: Names::get(path), path, client, _zone: zone }
+    }
+
+    /// Begins a zone for a sample of `sample_size` iterations, which ends when
+    /// dropped.
+    #[inline]
+    pub fn start_sample(&self, sample_size: u32) -> Span {
+        let zone = self.client.clone().span_alloc(Some(self.path), "sample", file!(), line!(), 0);
+        zone.emit_value(sample_size.into());
+        zone
+    }
+
+    /// Marks the end of a sample and plots its duration in nanoseconds.
+    #[inline]
+    pub fn end_sample(&self, duration: Option<FineDuration>) {
+        self.client.secondary_frame_mark(self.names.frame);
+
+        if let Some(duration) = duration {
+            self.client.plot(self.names.plot, duration.picos as f64 / 1_000.0);
+        }
+    }
+}
+
+/// Frame and plot names of a benchmark.
+#[derive(Clone, Copy)]
+struct Names {
+    frame: FrameName,
+    plot: PlotName,
+}
+
+impl Names {
+    /// Returns the names for `path`, which are leaked once per benchmark since
+    /// Tracy requires them to live until the program exits.
+    fn get(path: &str) -> Self {
+        static NAMES: OnceLock<Mutex<HashMap<String, Names>>> = OnceLock::new();
+
+        let mut names =
+            NAMES.get_or_init(Default::default).lock().unwrap_or_else(PoisonError::into_inner);
+
+        *names.entry(path.to_owned()).or_insert_with(|| Self {
+            frame: FrameName::new_leak(path.to_owned()),
+            plot: PlotName::new_leak(format!("{path} (ns)")),
+        })
+    }
+}
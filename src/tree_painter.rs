@@ -1,10 +1,15 @@
 //! Happy little trees.
 
-use std::{io::Write, iter::repeat};
+use std::{io::Write, iter::repeat_n};
 
 use crate::{
-    counter::{AnyCounter, BytesFormat, KnownCounterKind},
-    stats::{Stats, StatsSet},
+    counter::{AnyCounter, BytesFormat, Count, CounterFormat, KnownCounterKind},
+    hw::{HwCounterKind, PerfEvent},
+    io_counters::IoCounterKind,
+    results::{BenchErrorKind, EntryResult},
+    stats::{self, Stats, StatsSet},
+    time::{FineDuration, TimeUnit},
+    util,
 };
 
 const TREE_COL_BUF: usize = 2;
@@ -17,25 +22,68 @@ pub(crate) struct TreePainter {
 
     column_widths: [usize; TreeColumn::COUNT],
 
+    /// Which columns to show. Some columns are only applicable to certain
+    /// modes, such as [`TreeColumn::Delta`] when comparing against a baseline.
+    shown_columns: [bool; TreeColumn::COUNT],
+
     depth: usize,
 
     /// The current prefix to the name and content, e.g.
     /// <code>│     │  </code> for three levels of nesting with the second level
     /// being on the last node.
+    ///
+    /// With [`TreeStyle::Flat`], this is instead the path of parent names,
+    /// e.g. `module::group::`.
     current_prefix: String,
 
+    /// Lengths of `current_prefix` before entering each parent.
+    prefix_lens: Vec<usize>,
+
     /// Buffer for writing to before printing to stdout.
     write_buf: String,
+
+    /// Whether to not print anything, such as when stdout is used for
+    /// machine-readable messages.
+    is_quiet: bool,
+
+    /// Whether to style output with ANSI escape codes.
+    use_color: bool,
+
+    /// The unit for time columns.
+    time_unit: TimeUnit,
+
+    /// How the tree structure is drawn.
+    style: TreeStyle,
+
+    /// Events whose counts are shown in [`TreeColumn::Perf0`] and onward.
+    perf_events: Vec<PerfEvent>,
 }
 
 impl TreePainter {
-    pub fn new(max_name_span: usize, column_widths: [usize; TreeColumn::COUNT]) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        max_name_span: usize,
+        column_widths: [usize; TreeColumn::COUNT],
+        shown_columns: [bool; TreeColumn::COUNT],
+        is_quiet: bool,
+        use_color: bool,
+        time_unit: TimeUnit,
+        style: TreeStyle,
+        perf_events: Vec<PerfEvent>,
+    ) -> Self {
         Self {
             max_name_span,
             column_widths,
+            shown_columns,
             depth: 0,
             current_prefix: String::new(),
+            prefix_lens: Vec::new(),
             write_buf: String::new(),
+            is_quiet,
+            use_color,
+            time_unit,
+            style,
+            perf_events,
         }
     }
 }
@@ -44,18 +92,30 @@ impl TreePainter {
     /// Enter a parent node.
     pub fn start_parent(&mut self, name: &str, is_last: bool) {
         let is_top_level = self.depth == 0;
+
+        // Flat output only shows top-level parents, as headings.
+        if is_top_level || self.style != TreeStyle::Flat {
+            self.write_parent(name, is_last);
+        }
+
+        self.depth += 1;
+        self.prefix_lens.push(self.current_prefix.len());
+
+        if self.style == TreeStyle::Flat {
+            self.current_prefix.extend([name, "::"]);
+        } else if !is_top_level {
+            self.current_prefix.push_str(self.style.indent(is_last));
+        }
+    }
+
+    fn write_parent(&mut self, name: &str, is_last: bool) {
+        let is_top_level = self.depth == 0;
         let has_columns = self.has_columns();
 
         let buf = &mut self.write_buf;
         buf.clear();
 
-        let branch = if is_top_level {
-            ""
-        } else if !is_last {
-            "├─ "
-        } else {
-            "╰─ "
-        };
+        let branch = if is_top_level { "" } else { self.style.branch(is_last) };
         buf.extend([self.current_prefix.as_str(), branch, name]);
 
         // Right-pad name if `has_columns`
@@ -63,7 +123,7 @@ impl TreePainter {
             let max_span = self.max_name_span;
             let buf_len = buf.chars().count();
             let pad_len = TREE_COL_BUF + max_span.saturating_sub(buf_len);
-            buf.extend(repeat(' ').take(pad_len));
+            buf.extend(repeat_n(' ', pad_len));
 
             if buf_len > max_span {
                 self.max_name_span = buf_len;
@@ -72,21 +132,25 @@ impl TreePainter {
 
         // Write column headings.
         if has_columns && is_top_level {
-            let names = TreeColumnData::from_fn(TreeColumn::name);
-            names.write(buf, &mut self.column_widths);
+            let names = TreeColumnData::from_fn(|column| match column.perf_event_index() {
+                Some(index) => self.perf_events.get(index).map_or("", |event| event.name),
+                None => column.name(),
+            });
+            names.write(buf, &mut self.column_widths, &self.shown_columns, self.style);
         }
 
         // Write column spacers.
         if has_columns && !is_top_level {
-            TreeColumnData([""; TreeColumn::COUNT]).write(buf, &mut self.column_widths);
+            TreeColumnData([""; TreeColumn::COUNT]).write(
+                buf,
+                &mut self.column_widths,
+                &self.shown_columns,
+                self.style,
+            );
         }
 
-        println!("{buf}");
-
-        self.depth += 1;
-
-        if !is_top_level {
-            self.current_prefix.push_str(if !is_last { "│  " } else { "   " });
+        if !self.is_quiet {
+            println!("{buf}");
         }
     }
 
@@ -95,29 +159,25 @@ impl TreePainter {
         self.depth -= 1;
 
         // Improve legibility for multiple top-level parents.
-        if self.depth == 0 {
+        if self.depth == 0 && !self.is_quiet {
             println!();
         }
 
-        // The prefix is extended by 3 `char`s at a time.
-        let new_prefix_len = {
-            let mut iter = self.current_prefix.chars();
-            _ = iter.by_ref().rev().nth(2);
-            iter.as_str().len()
-        };
-        self.current_prefix.truncate(new_prefix_len);
+        if let Some(prefix_len) = self.prefix_lens.pop() {
+            self.current_prefix.truncate(prefix_len);
+        }
     }
 
-    /// Indicate that the next child node was ignored.
+    /// Indicate that the next child node was ignored, optionally with a reason.
     ///
     /// This semantically combines start/finish operations.
-    pub fn ignore_leaf(&mut self, name: &str, is_last: bool) {
+    pub fn ignore_leaf(&mut self, name: &str, reason: Option<&str>, is_last: bool) {
         let has_columns = self.has_columns();
 
         let buf = &mut self.write_buf;
         buf.clear();
 
-        let branch = if !is_last { "├─ " } else { "╰─ " };
+        let branch = self.style.branch(is_last);
         buf.extend([self.current_prefix.as_str(), branch, name]);
 
         // Right-pad buffer.
@@ -125,22 +185,39 @@ impl TreePainter {
             let max_span = self.max_name_span;
             let buf_len = buf.chars().count();
             let pad_len = TREE_COL_BUF + max_span.saturating_sub(buf_len);
-            buf.extend(repeat(' ').take(pad_len));
+            buf.extend(repeat_n(' ', pad_len));
 
             if buf_len > max_span {
                 self.max_name_span = buf_len;
             }
         }
 
-        if has_columns {
+        if let Some(reason) = reason {
+            // Reasons are written outside of columns so that long reasons do
+            // not widen them.
+            buf.extend(["(ignored: ", reason, ")"]);
+        } else if has_columns {
             let mut columns = [""; TreeColumn::COUNT];
-            columns[0] = "(ignored)";
-            TreeColumnData(columns).write(buf, &mut self.column_widths);
+            let first_column = self.shown_columns.iter().position(|&shown| shown);
+            columns[first_column.unwrap_or_default()] = "(ignored)";
+            TreeColumnData(columns).write(
+                buf,
+                &mut self.column_widths,
+                &self.shown_columns,
+                self.style,
+            );
         } else {
             buf.push_str("(ignored)");
         }
 
-        println!("{buf}");
+        if !self.is_quiet {
+            if self.use_color {
+                // Grey out ignored entries.
+                println!("\x1b[2m{buf}\x1b[0m");
+            } else {
+                println!("{buf}");
+            }
+        }
     }
 
     /// Enter a leaf node.
@@ -150,7 +227,7 @@ impl TreePainter {
         let buf = &mut self.write_buf;
         buf.clear();
 
-        let branch = if !is_last { "├─ " } else { "╰─ " };
+        let branch = self.style.branch(is_last);
         buf.extend([self.current_prefix.as_str(), branch, name]);
 
         // Right-pad buffer if this leaf will have info displayed.
@@ -158,45 +235,124 @@ impl TreePainter {
             let max_span = self.max_name_span;
             let buf_len = buf.chars().count();
             let pad_len = TREE_COL_BUF + max_span.saturating_sub(buf_len);
-            buf.extend(repeat(' ').take(pad_len));
+            buf.extend(repeat_n(' ', pad_len));
 
             if buf_len > max_span {
                 self.max_name_span = buf_len;
             }
         }
 
-        print!("{buf}");
-        _ = std::io::stdout().flush();
+        if !self.is_quiet {
+            print!("{buf}");
+            _ = std::io::stdout().flush();
+        }
+    }
+
+    /// Returns the text printed by [`TreePainter::start_leaf`], which is
+    /// redrawn after clearing progress.
+    pub fn leaf_prefix(&self) -> &str {
+        &self.write_buf
     }
 
     /// Exit the current leaf node.
     pub fn finish_empty_leaf(&mut self) {
-        println!();
+        if !self.is_quiet {
+            println!();
+        }
+    }
+
+    /// Exit the current leaf node, indicating that the benchmark was stopped.
+    pub fn finish_errored_leaf(&mut self, kind: &BenchErrorKind) {
+        let status = match kind {
+            BenchErrorKind::Panicked(_) => "(errored)",
+            BenchErrorKind::TimedOut(_) => "(timed out)",
+        };
+
+        if !self.is_quiet {
+            if self.use_color {
+                println!("\x1b[31m{status}\x1b[0m");
+            } else {
+                println!("{status}");
+            }
+        }
     }
 
     /// Exit the current leaf node, emitting statistics.
-    pub fn finish_leaf(&mut self, is_last: bool, stats: &Stats, bytes_format: BytesFormat) {
+    ///
+    /// If `baseline` is provided, the change in median time is emitted in
+    /// [`TreeColumn::Delta`], and `p_value` of the change is emitted in
+    /// [`TreeColumn::Significance`]. If `is_throttled`, the row is annotated to
+    /// indicate that CPU frequency dropped while measuring, and if `is_noisy`,
+    /// that sample times varied too much to be trusted. Rows of bimodal
+    /// `stats` are also annotated. If `is_failed`, the row is highlighted to
+    /// indicate that it exceeded a threshold.
+    #[allow(clippy::too_many_arguments)]
+    pub fn finish_leaf(
+        &mut self,
+        is_last: bool,
+        stats: &Stats,
+        baseline: Option<&StatsSet<FineDuration>>,
+        p_value: Option<f64>,
+        bytes_format: BytesFormat,
+        counter_format: CounterFormat,
+        is_throttled: bool,
+        is_noisy: bool,
+        is_failed: bool,
+    ) {
         let buf = &mut self.write_buf;
         buf.clear();
 
         // Serialize counter stats early so we can resize columns early.
-        let serialized_counters = KnownCounterKind::ALL.map(|counter_kind| {
-            let counter_stats = stats.get_counts(counter_kind);
-
-            TreeColumn::ALL
-                .map(|column| -> Option<String> {
-                    let count = *column.get_stat(counter_stats?)?;
-                    let time = *column.get_stat(&stats.time)?;
-
-                    Some(
-                        AnyCounter::known(counter_kind, count)
-                            .display_throughput(time, bytes_format)
-                            .to_string(),
-                    )
-                })
-                .map(Option::unwrap_or_default)
+        let serialize_counter =
+            |counter_stats: Option<&StatsSet<Count>>,
+             std_dev: Option<f64>,
+             make_counter: &dyn Fn(Count) -> AnyCounter| {
+                TreeColumn::ALL
+                    .map(|column| -> Option<String> {
+                        if column == TreeColumn::StdDev {
+                            let display = display_throughput_std_dev(
+                                stats.time.mean,
+                                counter_stats?.mean,
+                                std_dev?,
+                                make_counter,
+                                bytes_format,
+                                counter_format,
+                            )?;
+                            return Some(display);
+                        }
+
+                        let count = *column.get_stat(counter_stats?)?;
+                        let time = *column.get_stat(&stats.time)?;
+
+                        let counter = make_counter(count);
+                        Some(
+                            counter
+                                .display_throughput(time, bytes_format, counter_format)
+                                .to_string(),
+                        )
+                    })
+                    .map(Option::unwrap_or_default)
+            };
+
+        let known_counters = KnownCounterKind::ALL.map(|counter_kind| {
+            let std_dev = stats.throughput_std_dev[counter_kind as usize];
+            serialize_counter(stats.get_counts(counter_kind), std_dev, &|count| {
+                AnyCounter::known(counter_kind, count)
+            })
         });
 
+        let custom_counters = stats.custom_counts.iter().map(|(unit, counter_stats)| {
+            let std_dev = (stats.custom_throughput_std_dev.iter())
+                .find(|(u, _)| u == unit)
+                .map(|&(_, std_dev)| std_dev);
+            serialize_counter(Some(counter_stats), std_dev, &|count| {
+                AnyCounter::custom(unit, count)
+            })
+        });
+
+        let serialized_counters: Vec<[String; TreeColumn::COUNT]> =
+            known_counters.into_iter().chain(custom_counters).collect();
+
         let max_counter_width = serialized_counters
             .iter()
             .flatten()
@@ -211,25 +367,205 @@ impl TreePainter {
 
         // Write time stats with iter and sample counts.
         TreeColumnData::from_fn(|column| -> String {
+            let time_unit = self.time_unit;
+
             let stat: &dyn ToString = match column {
-                TreeColumn::Fastest => &stats.time.fastest,
-                TreeColumn::Slowest => &stats.time.slowest,
-                TreeColumn::Median => &stats.time.median,
-                TreeColumn::Mean => &stats.time.mean,
+                TreeColumn::Fastest => return stats.time.fastest.to_string_in(time_unit),
+                TreeColumn::Slowest => return stats.time.slowest.to_string_in(time_unit),
+                TreeColumn::Median => return stats.time.median.to_string_in(time_unit),
+                TreeColumn::Mean => return stats.time.mean.to_string_in(time_unit),
+                TreeColumn::P75 | TreeColumn::P90 | TreeColumn::P95 | TreeColumn::P99 => {
+                    return column
+                        .percentile()
+                        .and_then(|percentile| stats.get_time_percentile(percentile))
+                        .map(|time| time.to_string_in(time_unit))
+                        .unwrap_or_default();
+                }
+                TreeColumn::TrimmedMean => {
+                    return stats
+                        .trimmed_mean
+                        .map(|time| time.to_string_in(time_unit))
+                        .unwrap_or_default();
+                }
+                TreeColumn::WinsorizedMean => {
+                    return stats
+                        .winsorized_mean
+                        .map(|time| time.to_string_in(time_unit))
+                        .unwrap_or_default();
+                }
+                TreeColumn::Gen => {
+                    return stats
+                        .gen_time
+                        .map(|time| time.to_string_in(time_unit))
+                        .unwrap_or_default();
+                }
+                TreeColumn::Drop => {
+                    return stats
+                        .drop_time
+                        .map(|time| time.to_string_in(time_unit))
+                        .unwrap_or_default();
+                }
+                TreeColumn::StdDev => {
+                    return stats
+                        .time_std_dev
+                        .map(|time| time.to_string_in(time_unit))
+                        .unwrap_or_default();
+                }
+                TreeColumn::Mad => {
+                    return stats
+                        .time_mad
+                        .map(|time| time.to_string_in(time_unit))
+                        .unwrap_or_default();
+                }
+                TreeColumn::RunStdDev => {
+                    return stats
+                        .run_std_dev
+                        .map(|time| time.to_string_in(time_unit))
+                        .unwrap_or_default();
+                }
+                TreeColumn::Wall => {
+                    return stats
+                        .threads
+                        .as_ref()
+                        .map(|threads| threads.wall_time.mean.to_string_in(time_unit))
+                        .unwrap_or_default();
+                }
+                TreeColumn::Delta => {
+                    let change = baseline.and_then(|baseline| {
+                        crate::baseline::percent_change(
+                            baseline.median.picos,
+                            stats.time.median.picos,
+                        )
+                    });
+                    return match change {
+                        Some(change) => format!("{change:+.2}%"),
+                        None => String::new(),
+                    };
+                }
+                TreeColumn::Significance => {
+                    let Some(p) = p_value else {
+                        return String::new();
+                    };
+                    let is_significant = if p < stats::SIGNIFICANCE_LEVEL { "yes" } else { "no" };
+                    return if p < 0.001 {
+                        format!("{is_significant} (p<0.001)")
+                    } else {
+                        format!("{is_significant} (p={p:.3})")
+                    };
+                }
+                TreeColumn::Cycles
+                | TreeColumn::Instructions
+                | TreeColumn::CacheMisses
+                | TreeColumn::BranchMisses => {
+                    return column
+                        .hw_counter_kind()
+                        .and_then(|kind| stats.get_hw_count(kind))
+                        .map(format_count)
+                        .unwrap_or_default();
+                }
+                TreeColumn::Ipc => {
+                    return stats
+                        .instructions_per_cycle()
+                        .map(|ipc| format!("{ipc:.2}"))
+                        .unwrap_or_default();
+                }
+                TreeColumn::BranchMissRate => {
+                    return stats
+                        .branch_miss_rate()
+                        .map(|rate| format!("{rate:.2}%"))
+                        .unwrap_or_default();
+                }
+                TreeColumn::Perf0
+                | TreeColumn::Perf1
+                | TreeColumn::Perf2
+                | TreeColumn::Perf3
+                | TreeColumn::Perf4
+                | TreeColumn::Perf5
+                | TreeColumn::Perf6
+                | TreeColumn::Perf7 => {
+                    return column
+                        .perf_event_index()
+                        .and_then(|index| stats.perf_counts.get(index))
+                        .map(|&(_, count)| format_count(count))
+                        .unwrap_or_default();
+                }
+                TreeColumn::Allocs
+                | TreeColumn::Deallocs
+                | TreeColumn::AllocBytes
+                | TreeColumn::PeakBytes => {
+                    let Some(allocs) = &stats.allocs else {
+                        return String::new();
+                    };
+                    return match column {
+                        TreeColumn::Allocs => format_count(allocs.alloc_count),
+                        TreeColumn::Deallocs => format_count(allocs.dealloc_count),
+                        TreeColumn::AllocBytes => format_bytes(allocs.alloc_bytes, bytes_format),
+                        _ => format_bytes(allocs.peak_bytes as f64, bytes_format),
+                    };
+                }
+                TreeColumn::IoRead
+                | TreeColumn::IoWrite
+                | TreeColumn::ReadCalls
+                | TreeColumn::WriteCalls
+                | TreeColumn::DiskRead
+                | TreeColumn::DiskWrite => {
+                    let Some(kind) = column.io_counter_kind() else {
+                        return String::new();
+                    };
+                    return match stats.get_io_count(kind) {
+                        Some(count) if kind.is_bytes() => format_bytes(count, bytes_format),
+                        Some(count) => format_count(count),
+                        None => String::new(),
+                    };
+                }
                 TreeColumn::Samples => &stats.sample_count,
                 TreeColumn::Iters => &stats.iter_count,
             };
             stat.to_string()
         })
         .as_ref::<str>()
-        .write(buf, &mut self.column_widths);
+        .write(buf, &mut self.column_widths, &self.shown_columns, self.style);
+
+        if is_failed {
+            if self.use_color {
+                buf.insert_str(0, "\x1b[31m");
+                buf.push_str(" (failed)\x1b[0m");
+            } else {
+                buf.push_str(" (failed)");
+            }
+        }
 
-        println!("{buf}");
+        if is_throttled {
+            if self.use_color {
+                buf.push_str(" \x1b[33m(throttled)\x1b[0m");
+            } else {
+                buf.push_str(" (throttled)");
+            }
+        }
+
+        if is_noisy {
+            if self.use_color {
+                buf.push_str(" \x1b[33m(noisy)\x1b[0m");
+            } else {
+                buf.push_str(" (noisy)");
+            }
+        }
+
+        if stats.bimodality.is_some() {
+            if self.use_color {
+                buf.push_str(" \x1b[33m(bimodal)\x1b[0m");
+            } else {
+                buf.push_str(" (bimodal)");
+            }
+        }
+
+        if !self.is_quiet {
+            println!("{buf}");
+        }
 
         // Write counter stats.
-        let counter_stats = serialized_counters.map(TreeColumnData);
-        for counter_kind in KnownCounterKind::ALL {
-            let counter_stats = counter_stats[counter_kind as usize].as_ref::<str>();
+        for counter_stats in serialized_counters.into_iter().map(TreeColumnData) {
+            let counter_stats = counter_stats.as_ref::<str>();
 
             // Skip empty rows.
             if counter_stats.0.iter().all(|s| s.is_empty()) {
@@ -237,10 +573,14 @@ impl TreePainter {
             }
 
             buf.clear();
-            buf.push_str(&self.current_prefix);
 
-            if !is_last {
-                buf.push('│');
+            // Flat output has no tree lines to continue.
+            if self.style != TreeStyle::Flat {
+                buf.push_str(&self.current_prefix);
+
+                if !is_last {
+                    buf.push_str(self.style.vertical());
+                }
             }
 
             // Right-pad buffer.
@@ -248,15 +588,71 @@ impl TreePainter {
                 let buf_len = buf.chars().count();
                 let max_span = self.max_name_span;
                 let pad_len = TREE_COL_BUF + self.max_name_span.saturating_sub(buf_len);
-                buf.extend(repeat(' ').take(pad_len));
+                buf.extend(repeat_n(' ', pad_len));
 
                 if buf_len > max_span {
                     self.max_name_span = buf_len;
                 }
             };
 
-            counter_stats.write(buf, &mut self.column_widths);
-            println!("{buf}");
+            counter_stats.write(buf, &mut self.column_widths, &self.shown_columns, self.style);
+            if !self.is_quiet {
+                println!("{buf}");
+            }
+        }
+    }
+
+    /// Writes a row summarizing `entries` with the geometric mean of their
+    /// mean times and their total iteration count.
+    ///
+    /// The row is written below the current parent's children.
+    pub fn summary_row(&mut self, label: &str, entries: &[&EntryResult]) {
+        let mean_times = entries.iter().map(|entry| entry.stats.time.mean);
+        let Some(geomean) = stats::geometric_mean(mean_times) else {
+            return;
+        };
+        let iter_count: u64 = entries.iter().map(|entry| entry.stats.iter_count).sum();
+
+        let buf = &mut self.write_buf;
+        buf.clear();
+
+        if self.style == TreeStyle::Flat {
+            // Name the parent rather than listing it as a path component.
+            let parent_path = self.current_prefix.trim_end_matches("::");
+            if !parent_path.is_empty() {
+                buf.extend([parent_path, " "]);
+            }
+        } else {
+            buf.push_str(&self.current_prefix);
+        }
+        buf.push_str(label);
+
+        // Right-pad buffer.
+        {
+            let max_span = self.max_name_span;
+            let buf_len = buf.chars().count();
+            let pad_len = TREE_COL_BUF + max_span.saturating_sub(buf_len);
+            buf.extend(repeat_n(' ', pad_len));
+
+            if buf_len > max_span {
+                self.max_name_span = buf_len;
+            }
+        }
+
+        TreeColumnData::from_fn(|column| match column {
+            TreeColumn::Mean => geomean.to_string_in(self.time_unit),
+            TreeColumn::Iters => iter_count.to_string(),
+            _ => String::new(),
+        })
+        .as_ref::<str>()
+        .write(buf, &mut self.column_widths, &self.shown_columns, self.style);
+
+        if !self.is_quiet {
+            if self.use_color {
+                println!("\x1b[1m{buf}\x1b[0m");
+            } else {
+                println!("{buf}");
+            }
         }
     }
 
@@ -265,6 +661,67 @@ impl TreePainter {
     }
 }
 
+/// How the tree structure is drawn.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum TreeStyle {
+    /// Unicode box-drawing characters.
+    #[default]
+    Unicode,
+
+    /// ASCII characters, for terminals and log viewers that mangle Unicode.
+    Ascii,
+
+    /// Full `module::path::bench` names without indentation. Uses ASCII
+    /// column separators.
+    Flat,
+}
+
+impl TreeStyle {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Unicode => "unicode",
+            Self::Ascii => "ascii",
+            Self::Flat => "flat",
+        }
+    }
+
+    /// Returns the branch drawn before a child's name.
+    fn branch(self, is_last: bool) -> &'static str {
+        match (self, is_last) {
+            (Self::Unicode, false) => "├─ ",
+            (Self::Unicode, true) => "╰─ ",
+            (Self::Ascii, false) => "|- ",
+            (Self::Ascii, true) => "`- ",
+            (Self::Flat, _) => "",
+        }
+    }
+
+    /// Returns the prefix extension for a parent's children.
+    fn indent(self, is_last: bool) -> &'static str {
+        match (self, is_last) {
+            (Self::Flat, _) => "",
+            (_, true) => "   ",
+            (Self::Unicode, false) => "│  ",
+            (Self::Ascii, false) => "|  ",
+        }
+    }
+
+    /// Returns the line continuing to a later sibling.
+    fn vertical(self) -> &'static str {
+        match self {
+            Self::Unicode => "│",
+            Self::Ascii | Self::Flat => "|",
+        }
+    }
+
+    fn column_separator(self) -> &'static str {
+        match self {
+            Self::Unicode => " │ ",
+            Self::Ascii | Self::Flat => " | ",
+        }
+    }
+}
+
 /// Columns of the table next to the tree.
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub(crate) enum TreeColumn {
@@ -272,22 +729,174 @@ pub(crate) enum TreeColumn {
     Slowest,
     Median,
     Mean,
+
+    /// Mean time excluding or clamping outlier samples, which are hidden unless
+    /// selected with `--stats` or `--robust-stats`.
+    TrimmedMean,
+    WinsorizedMean,
+
+    /// Time percentiles, which are hidden unless selected with `--stats`.
+    P75,
+    P90,
+    P95,
+    P99,
+
+    /// Mean time per iteration spent generating inputs, and dropping outputs
+    /// and inputs, which are hidden unless selected with `--stats`.
+    Gen,
+    Drop,
+
+    /// Standard deviation of time per iteration and of throughput across
+    /// samples, which is hidden unless selected with `--stats`.
+    StdDev,
+
+    /// Median absolute deviation of time per iteration across samples, which
+    /// is hidden unless selected with `--stats` or `--robust-stats`.
+    Mad,
+
+    /// Standard deviation of mean time per iteration between runs of the
+    /// suite, which is only shown with `--runs`.
+    RunStdDev,
+
+    /// Mean wall clock time per iteration of multi-threaded benchmarks.
+    Wall,
+
+    /// Change in median time relative to a baseline.
+    Delta,
+
+    /// Whether the change relative to a baseline is statistically
+    /// significant, with its p-value.
+    Significance,
+
+    /// Mean hardware event counts per iteration.
+    Cycles,
+    Instructions,
+
+    /// Instructions per cycle.
+    Ipc,
+
+    CacheMisses,
+    BranchMisses,
+
+    /// Percentage of branches that were mispredicted.
+    BranchMissRate,
+
+    /// Mean counts of `--perf-events` per iteration, labeled by event name.
+    Perf0,
+    Perf1,
+    Perf2,
+    Perf3,
+    Perf4,
+    Perf5,
+    Perf6,
+    Perf7,
+
+    /// Mean allocations per iteration, measured by `AllocProfiler`.
+    Allocs,
+    Deallocs,
+    AllocBytes,
+
+    /// Peak bytes allocated during a sample.
+    PeakBytes,
+
+    /// Mean I/O counts per iteration, reported by the OS.
+    IoRead,
+    IoWrite,
+    ReadCalls,
+    WriteCalls,
+    DiskRead,
+    DiskWrite,
+
     Samples,
     Iters,
 }
 
 impl TreeColumn {
-    pub const COUNT: usize = 6;
+    pub const COUNT: usize = 44;
 
     pub const ALL: [Self; Self::COUNT] = {
         use TreeColumn::*;
-        [Fastest, Slowest, Median, Mean, Samples, Iters]
+        [
+            Fastest,
+            Slowest,
+            Median,
+            Mean,
+            TrimmedMean,
+            WinsorizedMean,
+            P75,
+            P90,
+            P95,
+            P99,
+            Gen,
+            Drop,
+            StdDev,
+            Mad,
+            RunStdDev,
+            Wall,
+            Delta,
+            Significance,
+            Cycles,
+            Instructions,
+            Ipc,
+            CacheMisses,
+            BranchMisses,
+            BranchMissRate,
+            Perf0,
+            Perf1,
+            Perf2,
+            Perf3,
+            Perf4,
+            Perf5,
+            Perf6,
+            Perf7,
+            Allocs,
+            Deallocs,
+            AllocBytes,
+            PeakBytes,
+            IoRead,
+            IoWrite,
+            ReadCalls,
+            WriteCalls,
+            DiskRead,
+            DiskWrite,
+            Samples,
+            Iters,
+        ]
+    };
+
+    /// Columns that can be selected with `--stats`.
+    pub const TIME_STATS: [Self; 14] = {
+        use TreeColumn::*;
+        [
+            Fastest,
+            Slowest,
+            Median,
+            Mean,
+            TrimmedMean,
+            WinsorizedMean,
+            P75,
+            P90,
+            P95,
+            P99,
+            Gen,
+            Drop,
+            StdDev,
+            Mad,
+        ]
+    };
+
+    /// Columns added by `--robust-stats`.
+    pub const ROBUST_STATS: [Self; 3] = [Self::TrimmedMean, Self::WinsorizedMean, Self::Mad];
+
+    /// Columns shown when `--stats` is not set.
+    pub const DEFAULT_TIME_STATS: [Self; 4] = {
+        use TreeColumn::*;
+        [Fastest, Slowest, Median, Mean]
     };
 
     #[inline]
     pub fn time_stats() -> impl Iterator<Item = Self> {
-        use TreeColumn::*;
-        [Fastest, Slowest, Median, Mean].into_iter()
+        Self::TIME_STATS.into_iter()
     }
 
     #[inline]
@@ -296,12 +905,50 @@ impl TreeColumn {
         self == last
     }
 
-    fn name(self) -> &'static str {
+    pub fn name(self) -> &'static str {
         match self {
             Self::Fastest => "fastest",
             Self::Slowest => "slowest",
             Self::Median => "median",
             Self::Mean => "mean",
+            Self::TrimmedMean => "trimmed",
+            Self::WinsorizedMean => "winsorized",
+            Self::P75 => "p75",
+            Self::P90 => "p90",
+            Self::P95 => "p95",
+            Self::P99 => "p99",
+            Self::Gen => "gen",
+            Self::Drop => "drop",
+            Self::StdDev => "stddev",
+            Self::Mad => "mad",
+            Self::RunStdDev => "run stddev",
+            Self::Wall => "wall",
+            Self::Delta => "delta",
+            Self::Significance => "significance",
+            Self::Cycles => "cycles",
+            Self::Instructions => "instructions",
+            Self::CacheMisses => "cache misses",
+            Self::Ipc => "ipc",
+            Self::BranchMisses => "branch misses",
+            Self::BranchMissRate => "branch miss rate",
+            Self::Perf0 => "perf 0",
+            Self::Perf1 => "perf 1",
+            Self::Perf2 => "perf 2",
+            Self::Perf3 => "perf 3",
+            Self::Perf4 => "perf 4",
+            Self::Perf5 => "perf 5",
+            Self::Perf6 => "perf 6",
+            Self::Perf7 => "perf 7",
+            Self::Allocs => "allocs",
+            Self::Deallocs => "deallocs",
+            Self::AllocBytes => "alloc bytes",
+            Self::PeakBytes => "peak bytes",
+            Self::IoRead => "io read",
+            Self::IoWrite => "io write",
+            Self::ReadCalls => "read calls",
+            Self::WriteCalls => "write calls",
+            Self::DiskRead => "disk read",
+            Self::DiskWrite => "disk write",
             Self::Samples => "samples",
             Self::Iters => "iters",
         }
@@ -309,8 +956,81 @@ impl TreeColumn {
 
     #[inline]
     pub fn is_time_stat(self) -> bool {
+        Self::TIME_STATS.contains(&self)
+    }
+
+    /// Returns the percentile of time that this column shows.
+    #[inline]
+    pub fn percentile(self) -> Option<u8> {
+        match self {
+            Self::P75 => Some(75),
+            Self::P90 => Some(90),
+            Self::P95 => Some(95),
+            Self::P99 => Some(99),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if this column shows hardware counts or values derived
+    /// from them.
+    #[inline]
+    pub fn is_hw_stat(self) -> bool {
+        self.hw_counter_kind().is_some() || matches!(self, Self::Ipc | Self::BranchMissRate)
+    }
+
+    /// Returns `true` if the hardware counts this column shows or derives from
+    /// can be counted on this platform.
+    pub fn is_hw_stat_supported(self) -> bool {
+        use HwCounterKind::*;
+
+        let kinds: &[HwCounterKind] = match self {
+            Self::Ipc => &[Cycles, Instructions],
+            Self::BranchMissRate => &[Branches, BranchMisses],
+            _ => return self.hw_counter_kind().is_some_and(HwCounterKind::is_supported),
+        };
+        kinds.iter().all(|kind| kind.is_supported())
+    }
+
+    /// Returns the index of the `--perf-events` event that this column shows.
+    #[inline]
+    pub fn perf_event_index(self) -> Option<usize> {
         use TreeColumn::*;
-        matches!(self, Fastest | Slowest | Median | Mean)
+        [Perf0, Perf1, Perf2, Perf3, Perf4, Perf5, Perf6, Perf7].iter().position(|&c| c == self)
+    }
+
+    #[inline]
+    pub fn is_alloc_stat(self) -> bool {
+        use TreeColumn::*;
+        matches!(self, Allocs | Deallocs | AllocBytes | PeakBytes)
+    }
+
+    #[inline]
+    pub fn is_io_count(self) -> bool {
+        self.io_counter_kind().is_some()
+    }
+
+    #[inline]
+    fn hw_counter_kind(self) -> Option<HwCounterKind> {
+        match self {
+            Self::Cycles => Some(HwCounterKind::Cycles),
+            Self::Instructions => Some(HwCounterKind::Instructions),
+            Self::CacheMisses => Some(HwCounterKind::CacheMisses),
+            Self::BranchMisses => Some(HwCounterKind::BranchMisses),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn io_counter_kind(self) -> Option<IoCounterKind> {
+        match self {
+            Self::IoRead => Some(IoCounterKind::ReadBytes),
+            Self::IoWrite => Some(IoCounterKind::WriteBytes),
+            Self::ReadCalls => Some(IoCounterKind::ReadCalls),
+            Self::WriteCalls => Some(IoCounterKind::WriteCalls),
+            Self::DiskRead => Some(IoCounterKind::DiskReadBytes),
+            Self::DiskWrite => Some(IoCounterKind::DiskWriteBytes),
+            _ => None,
+        }
     }
 
     #[inline]
@@ -320,7 +1040,7 @@ impl TreeColumn {
             Self::Slowest => Some(&stats.slowest),
             Self::Median => Some(&stats.median),
             Self::Mean => Some(&stats.mean),
-            Self::Samples | Self::Iters => None,
+            _ => None,
         }
     }
 }
@@ -339,16 +1059,29 @@ impl<T> TreeColumnData<T> {
 
 impl TreeColumnData<&str> {
     /// Writes the column data into the buffer.
-    fn write(&self, buf: &mut String, column_widths: &mut [usize; TreeColumn::COUNT]) {
+    ///
+    /// Columns not in `shown_columns` are skipped.
+    fn write(
+        &self,
+        buf: &mut String,
+        column_widths: &mut [usize; TreeColumn::COUNT],
+        shown_columns: &[bool; TreeColumn::COUNT],
+        style: TreeStyle,
+    ) {
+        let mut is_first = true;
+
         for (column, value) in self.0.iter().enumerate() {
-            let is_first = column == 0;
+            if !shown_columns[column] {
+                continue;
+            }
+
             let is_last = column == TreeColumn::COUNT - 1;
 
             let value_width = value.chars().count();
 
             // Write separator.
             if !is_first {
-                let mut sep = " │ ";
+                let mut sep = style.column_separator();
 
                 // Prevent trailing spaces.
                 if is_last && value_width == 0 {
@@ -359,11 +1092,12 @@ impl TreeColumnData<&str> {
             }
 
             buf.push_str(value);
+            is_first = false;
 
             // Right-pad remaining width or update column width to new maximum.
             if !is_last {
                 if let Some(rem_width) = column_widths[column].checked_sub(value_width) {
-                    buf.extend(repeat(' ').take(rem_width));
+                    buf.extend(repeat_n(' ', rem_width));
                 } else {
                     column_widths[column] = value_width;
                 }
@@ -381,3 +1115,84 @@ impl<T> TreeColumnData<T> {
         TreeColumnData::from_fn(|column| self.0[column as usize].as_ref())
     }
 }
+
+/// Formats the standard deviation of throughput in counts per second, or of
+/// time per count with [`CounterFormat::PerIter`].
+///
+/// Time per count is approximated by scaling the mean time per count by the
+/// relative standard deviation of throughput.
+fn display_throughput_std_dev(
+    mean_time: FineDuration,
+    mean_count: Count,
+    std_dev: f64,
+    make_counter: &dyn Fn(Count) -> AnyCounter,
+    bytes_format: BytesFormat,
+    counter_format: CounterFormat,
+) -> Option<String> {
+    let (counter, time) = match counter_format {
+        CounterFormat::PerSec => {
+            (make_counter(Count::from_f64(std_dev)), FineDuration { picos: 1_000_000_000_000 })
+        }
+        CounterFormat::PerIter => {
+            let mean_throughput = mean_count.as_f64() * 1e12 / mean_time.picos as f64;
+            if !(mean_throughput.is_finite() && mean_throughput > 0.0) {
+                return None;
+            }
+
+            let picos = mean_time.picos as f64 * std_dev / mean_throughput;
+            (make_counter(mean_count), FineDuration { picos: picos.round() as u128 })
+        }
+    };
+
+    Some(counter.display_throughput(time, bytes_format, counter_format).to_string())
+}
+
+/// Formats a count with a metric prefix, e.g. "1.234 K".
+fn format_count(count: f64) -> String {
+    format_scaled(count, 1000., &["", " K", " M", " G", " T"])
+}
+
+/// Formats a number of bytes, e.g. "1.5 KiB".
+fn format_bytes(bytes: f64, bytes_format: BytesFormat) -> String {
+    match bytes_format {
+        BytesFormat::Binary => format_scaled(bytes, 1024., &[" B", " KiB", " MiB", " GiB", " TiB"]),
+        BytesFormat::Decimal => format_scaled(bytes, 1000., &[" B", " KB", " MB", " GB", " TB"]),
+    }
+}
+
+/// Formats `value` scaled down by powers of `base` with the matching suffix.
+fn format_scaled(mut value: f64, base: f64, suffixes: &[&str]) -> String {
+    let mut suffix = suffixes[0];
+    for next_suffix in &suffixes[1..] {
+        if value < base {
+            break;
+        }
+        value /= base;
+        suffix = next_suffix;
+    }
+
+    let mut result = util::format_f64(value, 4);
+    result.push_str(suffix);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_count() {
+        assert_eq!(super::format_count(0.), "0");
+        assert_eq!(super::format_count(12.5), "12.5");
+        assert_eq!(super::format_count(999.), "999");
+        assert_eq!(super::format_count(1234.), "1.234 K");
+        assert_eq!(super::format_count(5_600_000.), "5.6 M");
+    }
+
+    #[test]
+    fn format_bytes() {
+        assert_eq!(super::format_bytes(24., BytesFormat::Binary), "24 B");
+        assert_eq!(super::format_bytes(1536., BytesFormat::Binary), "1.5 KiB");
+        assert_eq!(super::format_bytes(1500., BytesFormat::Decimal), "1.5 KB");
+    }
+}
@@ -1,7 +1,12 @@
 use std::{
     any::{Any, TypeId},
+    collections::hash_map::RandomState,
+    fs,
+    hash::{BuildHasher, Hasher},
+    io,
     num::NonZeroUsize,
     ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
     sync::atomic::{AtomicUsize, Ordering::Relaxed},
 };
 
@@ -61,7 +66,7 @@ pub(crate) fn slice_middle<T>(slice: &[T]) -> &[T] {
 
     if len == 0 {
         slice
-    } else if len % 2 == 0 {
+    } else if len.is_multiple_of(2) {
         &slice[(len / 2) - 1..][..2]
     } else {
         &slice[len / 2..][..1]
@@ -127,6 +132,108 @@ pub(crate) fn known_parallelism() -> NonZeroUsize {
     }
 }
 
+/// Small pseudo-random number generator ([SplitMix64](https://prng.di.unimi.it/splitmix64.c)).
+///
+/// This is not suitable for cryptography, only for reproducibly randomizing
+/// benchmark runs from a seed.
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    #[inline]
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns a seed that differs between runs.
+    pub fn random_seed() -> u64 {
+        // `RandomState` is seeded randomly by the OS once per process and then
+        // incremented, so hashing the current time adds variation.
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos(),
+        );
+        hasher.finish()
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..n`.
+    #[inline]
+    pub fn below(&mut self, n: usize) -> usize {
+        // Lemire's multiply-shift reduction. The bias is negligible for
+        // shuffling benchmarks.
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+
+    /// Shuffles `slice` in place with the Fisher–Yates algorithm.
+    pub fn shuffle<T>(&mut self, slice: &mut [T]) {
+        for i in (1..slice.len()).rev() {
+            slice.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// Randomly named directory that only the current user can access, which is
+/// removed along with its contents when dropped.
+///
+/// Files exchanged with child processes are placed here rather than directly
+/// in the shared temporary directory, where other users could predict their
+/// names and create them first.
+pub(crate) struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new(prefix: &str) -> io::Result<Self> {
+        let mut builder = fs::DirBuilder::new();
+
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+
+        let mut attempts = 0;
+        loop {
+            let path = std::env::temp_dir().join(format!(
+                "{prefix}-{}-{:016x}",
+                std::process::id(),
+                Rng::random_seed()
+            ));
+
+            // Creating fails rather than reusing a directory that already
+            // exists, so the directory is always our own.
+            match builder.create(&path) {
+                Ok(()) => return Ok(Self { path }),
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists && attempts < 16 => {
+                    attempts += 1;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        _ = fs::remove_dir_all(&self.path);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::black_box;
@@ -151,4 +258,44 @@ mod tests {
         assert_eq!(slice_middle(&[1, 2, 3, 4]), &[2, 3]);
         assert_eq!(slice_middle(&[1, 2, 3, 4, 5]), &[3]);
     }
+
+    #[test]
+    fn rng_shuffle() {
+        let shuffled = |seed| {
+            let mut values: Vec<u32> = (0..100).collect();
+            Rng::new(seed).shuffle(&mut values);
+            values
+        };
+
+        // Same seed produces the same order.
+        assert_eq!(shuffled(42), shuffled(42));
+        assert_ne!(shuffled(42), shuffled(43));
+
+        // Shuffling keeps all values.
+        let mut values = shuffled(42);
+        values.sort_unstable();
+        assert_eq!(values, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn temp_dir() {
+        let dir = TempDir::new("divan-test").unwrap();
+        let path = dir.path().to_owned();
+        assert!(path.is_dir());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+
+        // Directories are unique.
+        assert_ne!(TempDir::new("divan-test").unwrap().path(), path);
+
+        fs::write(path.join("file"), "contents").unwrap();
+        drop(dir);
+        assert!(!path.exists());
+    }
 }
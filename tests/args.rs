@@ -0,0 +1,82 @@
+// Tests that `args` runs benchmarks once per argument.
+
+// Miri does not work with `linkme`.
+#![cfg(not(miri))]
+
+use std::sync::Mutex;
+
+use std::path::Path;
+
+use divan::{Bencher, Divan, FixtureFile};
+
+static SIZES: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+static NAMES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+static GENERIC: Mutex<Vec<(usize, usize, usize)>> = Mutex::new(Vec::new());
+static FIXTURES: Mutex<Vec<(String, Vec<u8>)>> = Mutex::new(Vec::new());
+static FIXTURE_PATHS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn sizes() -> impl Iterator<Item = usize> {
+    (0..3).map(|i| 10usize.pow(i))
+}
+
+#[divan::bench(args = sizes())]
+fn by_value(size: usize) {
+    SIZES.lock().unwrap().push(size);
+}
+
+#[divan::bench(args = vec![String::from("a"), String::from("b")])]
+fn by_ref(bencher: Bencher, name: &str) {
+    NAMES.lock().unwrap().push(name.to_owned());
+    bencher.bench(|| name.len());
+}
+
+#[divan::bench(args = ["x"])]
+fn str_arg(s: &str) -> usize {
+    s.len()
+}
+
+#[divan::bench(types = [u8, u32], consts = [1, 2], args = [10, 20])]
+fn generic<T, const N: usize>(arg: usize) {
+    GENERIC.lock().unwrap().push((std::mem::size_of::<T>(), N, arg));
+}
+
+#[divan::bench(args_from_dir = "tests/fixtures")]
+fn fixture(file: &FixtureFile) {
+    FIXTURES.lock().unwrap().push((file.to_string(), file.contents().to_vec()));
+}
+
+#[divan::bench(args_from_dir = "tests/fixtures")]
+fn fixture_path(bencher: Bencher, path: &Path) {
+    FIXTURE_PATHS.lock().unwrap().push(path.file_name().unwrap().to_string_lossy().into_owned());
+    bencher.bench(|| path.exists());
+}
+
+#[divan::bench(args_from_dir = "tests/fixtures")]
+fn fixture_contents(contents: &[u8]) -> usize {
+    contents.len()
+}
+
+#[test]
+fn run_args() {
+    Divan::default().sample_count(1).sample_size(1).test_benches();
+
+    assert_eq!(*SIZES.lock().unwrap(), [1, 10, 100]);
+    assert_eq!(*NAMES.lock().unwrap(), ["a", "b"]);
+
+    let mut generic = GENERIC.lock().unwrap().clone();
+    generic.sort_unstable();
+    generic.dedup();
+
+    let expected: Vec<_> = [1, 4]
+        .into_iter()
+        .flat_map(|size| [1, 2].into_iter().map(move |n| (size, n)))
+        .flat_map(|(size, n)| [10, 20].into_iter().map(move |arg| (size, n, arg)))
+        .collect();
+    assert_eq!(generic, expected);
+
+    assert_eq!(
+        *FIXTURES.lock().unwrap(),
+        [("a.txt".to_owned(), b"hello\n".to_vec()), ("b.txt".to_owned(), b"world!\n".to_vec())]
+    );
+    assert_eq!(*FIXTURE_PATHS.lock().unwrap(), ["a.txt", "b.txt"]);
+}
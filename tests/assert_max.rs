@@ -0,0 +1,30 @@
+// Tests that the `assert_max` option fails benchmarks over their budget.
+
+// Miri does not work with `linkme`.
+#![cfg(not(miri))]
+
+use std::time::Duration;
+
+use divan::Divan;
+
+#[divan::bench(assert_max = 60)]
+fn within_budget() -> usize {
+    divan::black_box(1) + 1
+}
+
+#[test]
+fn within_budget_passes() {
+    let results = Divan::default().sample_count(1).sample_size(1).skip_regex("over").collect();
+    assert!(results.passed());
+}
+
+#[divan::bench(assert_max = "1ns")]
+fn over_budget() {
+    std::thread::sleep(Duration::from_micros(10));
+}
+
+#[test]
+fn over_budget_fails() {
+    let results = Divan::default().sample_count(1).sample_size(1).skip_regex("within").collect();
+    assert!(!results.passed());
+}
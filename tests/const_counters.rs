@@ -0,0 +1,46 @@
+// Tests that `of_const` counters are multiplied by each benchmark's generic
+// `const` value.
+
+// Miri does not work with `linkme`.
+#![cfg(not(miri))]
+
+use divan::{
+    counter::{BytesCount, CustomCount, ItemsCount},
+    Divan,
+};
+
+#[divan::bench(consts = [4, 16], counters = [BytesCount::of_const(), ItemsCount::new(2u32)])]
+fn sized<const N: usize>() {}
+
+#[divan::bench_group(counters = [CustomCount::of_const("row")])]
+mod rows {
+    use divan::counter::CustomCount;
+
+    #[divan::bench(consts = [3])]
+    fn scan<const N: usize>() {}
+
+    #[divan::bench(counter = CustomCount::of_const("row"))]
+    fn plain() {}
+}
+
+#[test]
+fn const_counters() {
+    let results = Divan::default().sample_count(1).sample_size(1).collect();
+
+    let counts = |path: &str| {
+        let entry = results.entries().find(|entry| entry.path() == path).unwrap();
+        let stats = entry.stats();
+        (
+            stats.bytes().map(|bytes| bytes.mean),
+            stats.items().map(|items| items.mean),
+            stats.custom_counts().map(|(unit, counts)| (unit, counts.mean)).collect::<Vec<_>>(),
+        )
+    };
+
+    assert_eq!(counts("const_counters::sized::4"), (Some(4.0), Some(2.0), vec![]));
+    assert_eq!(counts("const_counters::sized::16"), (Some(16.0), Some(2.0), vec![]));
+    assert_eq!(counts("const_counters::rows::scan::3"), (None, None, vec![("row", 3.0)]));
+
+    // Without a `const` value, there is nothing to count.
+    assert_eq!(counts("const_counters::rows::plain"), (None, None, vec![]));
+}
@@ -0,0 +1,46 @@
+// Tests that benchmarks and groups registered at runtime are run alongside
+// attribute-registered benchmarks.
+
+// Miri does not work with `linkme`.
+#![cfg(not(miri))]
+
+use divan::{BenchOptions, Divan};
+
+#[divan::bench]
+fn static_bench() -> usize {
+    divan::black_box(1) + 1
+}
+
+#[test]
+fn dynamic() {
+    let group_options = BenchOptions { sample_count: Some(3), ..BenchOptions::default() };
+    let ignore_options = BenchOptions { ignore: Some(true), ..BenchOptions::default() };
+
+    let mut divan = Divan::default().sample_size(1).add_group("dynamic::queries", group_options);
+    for (name, value) in [("by_name", 2), ("by_id", 1)] {
+        divan = divan.add_bench(
+            &format!("dynamic::queries::{name}"),
+            BenchOptions::default(),
+            move |bencher| bencher.bench(|| divan::black_box(value) * 2),
+        );
+    }
+    divan = divan.add_bench("dynamic::ignored", ignore_options, |bencher| bencher.bench(|| ()));
+
+    let results = divan.collect();
+    assert!(results.passed());
+
+    let entries: Vec<(&str, u32)> = results
+        .entries()
+        .filter(|entry| entry.path().starts_with("dynamic::"))
+        .map(|entry| (entry.path(), entry.stats().sample_count()))
+        .collect();
+
+    assert_eq!(
+        entries,
+        [
+            ("dynamic::static_bench", 100),
+            ("dynamic::queries::by_id", 3),
+            ("dynamic::queries::by_name", 3),
+        ]
+    );
+}
@@ -24,6 +24,13 @@ fn ignored_1() {}
 #[divan::bench(ignore)]
 fn ignored_2() {}
 
+#[divan::bench(ignore = "reason 3")]
+fn ignored_3() {}
+
+#[divan::bench]
+#[ignore = "reason 4"]
+fn ignored_4() {}
+
 #[divan::bench_group]
 #[allow(unused_attributes)]
 #[ignore]
@@ -118,3 +125,21 @@ fn ignore() {
     assert!(!get_ignore(find_outer()));
     assert!(!get_ignore(find_outer_group()));
 }
+
+#[test]
+fn ignore_reason() {
+    fn get_ignore_reason(meta: &EntryMeta) -> Option<&'static str> {
+        meta.get_bench_options.and_then(|get| get().ignore_reason)
+    }
+
+    assert_eq!(get_ignore_reason(find_meta!(BENCH_ENTRIES, "ignored_1")), None);
+    assert_eq!(get_ignore_reason(find_meta!(BENCH_ENTRIES, "ignored_2")), None);
+
+    let ignored_3 = find_meta!(BENCH_ENTRIES, "ignored_3");
+    assert!(ignored_3.get_bench_options.and_then(|get| get().ignore).unwrap_or_default());
+    assert_eq!(get_ignore_reason(ignored_3), Some("reason 3"));
+
+    let ignored_4 = find_meta!(BENCH_ENTRIES, "ignored_4");
+    assert!(ignored_4.get_bench_options.and_then(|get| get().ignore).unwrap_or_default());
+    assert_eq!(get_ignore_reason(ignored_4), Some("reason 4"));
+}
@@ -0,0 +1,39 @@
+// Tests that generic benchmarks can be filtered by their generic parameters.
+
+// Miri does not work with `linkme`.
+#![cfg(not(miri))]
+
+use divan::Divan;
+
+#[divan::bench(types = [u8, u16], consts = [1, 2])]
+fn generic<T, const N: usize>() {}
+
+#[divan::bench]
+fn plain() {}
+
+fn run(divan: Divan) -> Vec<String> {
+    let results = divan.sample_count(1).sample_size(1).collect();
+
+    results
+        .entries()
+        .map(|entry| entry.path())
+        .filter(|path| path.starts_with("generic_filter::"))
+        .map(str::to_owned)
+        .collect()
+}
+
+#[test]
+fn generic_const() {
+    assert_eq!(
+        run(Divan::default().generic_const(2)),
+        ["generic_filter::generic::u16::2", "generic_filter::generic::u8::2"]
+    );
+}
+
+#[test]
+fn generic_path() {
+    assert_eq!(
+        run(Divan::default().skip_regex("generic<u8, 2>").skip_regex("generic<u16,")),
+        ["generic_filter::plain", "generic_filter::generic::u8::1"]
+    );
+}
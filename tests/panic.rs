@@ -0,0 +1,35 @@
+// Tests that a panicking benchmark does not stop other benchmarks from running.
+
+// Miri does not work with `linkme`.
+#![cfg(not(miri))]
+
+use divan::Divan;
+
+#[divan::bench]
+fn a_panics() {
+    panic!("expected panic");
+}
+
+#[divan::bench(threads = 2)]
+fn b_panics_threaded() {
+    panic!("expected panic");
+}
+
+#[divan::bench]
+fn c_runs() -> usize {
+    divan::black_box(1) + 1
+}
+
+#[test]
+fn panic_isolation() {
+    let results = Divan::default().sample_count(1).sample_size(1).collect();
+
+    assert!(!results.passed());
+
+    let paths: Vec<&str> = results
+        .entries()
+        .map(|entry| entry.path())
+        .filter(|path| path.starts_with("panic::"))
+        .collect();
+    assert_eq!(paths, ["panic::c_runs"]);
+}
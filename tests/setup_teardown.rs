@@ -0,0 +1,69 @@
+// Tests that `setup` and `teardown` run once around each benchmark, across
+// thread counts and repeated runs.
+
+// Miri does not work with `linkme`.
+#![cfg(not(miri))]
+
+use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+use divan::{Bencher, Divan};
+
+static SETUP_COUNT: AtomicUsize = AtomicUsize::new(0);
+static TEARDOWN_COUNT: AtomicUsize = AtomicUsize::new(0);
+static STATE_SUM: AtomicUsize = AtomicUsize::new(0);
+
+fn setup() -> Vec<usize> {
+    SETUP_COUNT.fetch_add(1, SeqCst);
+    vec![1, 2, 3]
+}
+
+fn teardown(state: Vec<usize>) {
+    TEARDOWN_COUNT.fetch_add(1, SeqCst);
+    STATE_SUM.fetch_add(state.iter().sum(), SeqCst);
+}
+
+#[divan::bench(setup = setup, teardown = teardown)]
+fn state_only(state: &[usize]) -> usize {
+    state.len()
+}
+
+#[divan::bench(setup = setup, teardown = teardown)]
+fn bencher_and_state(bencher: Bencher, state: &[usize]) {
+    bencher.bench(|| state.len());
+}
+
+#[divan::bench(consts = [1, 2], setup = setup, teardown = teardown)]
+fn generic<const N: usize>(state: &[usize]) -> usize {
+    state.len() + N
+}
+
+#[divan::bench(setup = setup)]
+fn unused_state() {}
+
+type AliasBencher<'a, 'b> = Bencher<'a, 'b>;
+
+#[divan::bench(threads = [1, 2], setup = setup, teardown = teardown)]
+fn threads_and_alias(bencher: AliasBencher, state: &[usize]) {
+    bencher.bench(|| state.len());
+}
+
+#[divan::bench(teardown = || { TEARDOWN_COUNT.fetch_add(1, SeqCst); })]
+fn teardown_only(bencher: Bencher) {
+    bencher.bench(|| {});
+}
+
+#[test]
+fn run_once() {
+    Divan::default().sample_count(10).sample_size(5).runs(2).run_benches();
+
+    // `state_only`, `bencher_and_state`, `generic` × 2, `unused_state`, and
+    // `threads_and_alias`.
+    assert_eq!(SETUP_COUNT.load(SeqCst), 6);
+
+    // `state_only`, `bencher_and_state`, `generic` × 2, `teardown_only`, and
+    // `threads_and_alias`.
+    assert_eq!(TEARDOWN_COUNT.load(SeqCst), 6);
+
+    // Every teardown that takes the state receives what `setup` returned.
+    assert_eq!(STATE_SUM.load(SeqCst), 5 * 6);
+}
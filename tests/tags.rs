@@ -0,0 +1,54 @@
+// Tests that `--tag` selects benchmarks by their own tags and those of their
+// parent groups.
+
+// Miri does not work with `linkme`.
+#![cfg(not(miri))]
+
+use divan::Divan;
+
+#[divan::bench(tags = ["io"])]
+fn io_bench() {}
+
+#[divan::bench(tags = ["simd"])]
+fn simd_bench() {}
+
+#[divan::bench]
+fn untagged_bench() {}
+
+#[divan::bench_group(tags = ["io"])]
+mod io_group {
+    #[divan::bench]
+    fn inherited() {}
+
+    #[divan::bench(types = [u8, u16])]
+    fn generic<T>() {}
+}
+
+fn run(divan: Divan) -> Vec<String> {
+    let results = divan.sample_count(1).sample_size(1).collect();
+
+    results
+        .entries()
+        .map(|entry| entry.path())
+        .filter(|path| path.starts_with("tags::"))
+        .map(str::to_owned)
+        .collect()
+}
+
+#[test]
+fn tag() {
+    assert_eq!(
+        run(Divan::default().tag("io")),
+        [
+            "tags::io_bench",
+            "tags::io_group::inherited",
+            "tags::io_group::generic::u16",
+            "tags::io_group::generic::u8",
+        ]
+    );
+
+    assert_eq!(
+        run(Divan::default().tag("io").tag("simd").skip_regex("io_group")),
+        ["tags::io_bench", "tags::simd_bench"]
+    );
+}
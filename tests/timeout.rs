@@ -0,0 +1,57 @@
+// Tests that the `timeout` option stops long-running benchmarks.
+
+// Miri does not work with `linkme`.
+#![cfg(not(miri))]
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use divan::Divan;
+
+#[divan::bench(timeout = "1ms", sample_count = 1000, min_time = 60)]
+fn a_times_out() {
+    std::thread::sleep(Duration::from_millis(2));
+}
+
+#[divan::bench]
+fn b_runs() -> usize {
+    divan::black_box(1) + 1
+}
+
+static STOPS_EARLY_ITERS: AtomicUsize = AtomicUsize::new(0);
+
+// The first sample takes 30ms, so a second sample would end 10ms past the
+// timeout and is not run.
+#[divan::bench(timeout = "50ms", sample_count = 2, sample_size = 30)]
+fn c_stops_early() {
+    STOPS_EARLY_ITERS.fetch_add(1, Ordering::Relaxed);
+    std::thread::sleep(Duration::from_millis(1));
+}
+
+#[test]
+fn timeout() {
+    let results = Divan::default()
+        .sample_count(1)
+        .sample_size(1)
+        .skip_exact("timeout::c_stops_early")
+        .collect();
+
+    assert!(!results.passed());
+
+    let paths: Vec<&str> = results
+        .entries()
+        .map(|entry| entry.path())
+        .filter(|path| path.starts_with("timeout::"))
+        .collect();
+    assert_eq!(paths, ["timeout::b_runs"]);
+}
+
+#[test]
+fn timeout_stops_before_overrun() {
+    let results = Divan::default().skip_regex("a_times_out|b_runs").collect();
+
+    assert!(!results.passed());
+    assert_eq!(STOPS_EARLY_ITERS.load(Ordering::Relaxed), 30);
+}
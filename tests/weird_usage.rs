@@ -5,7 +5,7 @@
 
 use std::time::Duration;
 
-use divan::{Divan, __private::BENCH_ENTRIES};
+use divan::{__private::BENCH_ENTRIES, Divan};
 
 #[divan::bench(min_time = Duration::ZERO)]
 fn min_min() {}
@@ -63,6 +63,12 @@ macro_rules! consts {
 #[divan::bench(consts = consts!())]
 fn bench_consts<const N: isize>() {}
 
+#[divan::bench(consts = -2..2)]
+fn range_consts<const N: isize>() {}
+
+#[divan::bench(types = [i32, u8], consts = 1..=3)]
+fn range_inclusive_consts<T, const N: usize>() {}
+
 #[divan::bench(types = [])]
 #[allow(dead_code)]
 fn empty_types<T>() {}
@@ -71,6 +77,10 @@ fn empty_types<T>() {}
 #[allow(dead_code)]
 fn empty_consts<const C: usize>() {}
 
+#[divan::bench(consts = 3..3)]
+#[allow(dead_code)]
+fn empty_range_consts<const C: usize>() {}
+
 #[divan::bench(types = [], consts = [])]
 #[allow(dead_code)]
 fn empty_types_consts_1<T, const C: usize>() {}